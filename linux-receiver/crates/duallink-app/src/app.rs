@@ -1,15 +1,23 @@
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Duration;
 
 use anyhow::Result;
-use duallink_core::{EncodedFrame, StreamConfig, detect_usb_ethernet};
-use duallink_decoder::DecoderFactory;
+use duallink_core::{
+    DecoderBenchmarks, FileTransferEvent, FileTransfers, InputRecording, Resolution, StreamConfig, detect_usb_ethernet,
+};
+use duallink_decoder::{
+    benchmark_decoders, receiver_capabilities, CompositeDisplay, CompositeLayout, DecoderFactory, DisplayOutput,
+};
 use duallink_discovery::{DualLinkAdvertiser, detect_local_ip};
-use duallink_transport::{DualLinkReceiver, DisplayChannels, InputSender, SignalingEvent, SIGNALING_PORT};
-use tokio::sync::mpsc;
+use duallink_receiver_lib::{Opener, ReceiverSession, SessionHooks};
+use duallink_transport::{
+    configured_base_port, hooks::Hooks, DualLinkReceiver, DisplayChannels, DisplayConfig, InputSender,
+    ReassemblyBudget, SignalingEvent,
+};
 use tracing::{info, warn};
 
+#[cfg(unix)]
+use duallink_transport::handover::HandoverServer;
+
 /// Main receiver loop — Phase 5B (multi-display + cross-platform receiver)
 ///
 /// # Display count
@@ -19,8 +27,89 @@ use tracing::{info, warn};
 ///   - Display 1: UDP 7880 / TCP 7881
 ///   - Display n: UDP 7878+2n / TCP 7879+2n
 ///
+/// `DUALLINK_BASE_PORT=9000` (or the saved settings' `basePort`) moves the
+/// whole layout (display n: UDP 9000+2n / TCP 9001+2n). The ports actually
+/// bound are advertised in the mDNS `ports` TXT key and in `hello_ack`, so
+/// senders find them without configuration.
+///
+/// # Per-display overrides
+/// Each display `n` can be tuned independently (e.g. 4K + 1080p panels):
+///   - `DUALLINK_DISPLAY_<n>_RESOLUTION=WxH` — resolution hint for the sender
+///   - `DUALLINK_DISPLAY_<n>_DECODER=avdec_h264` — preferred decoder element
+///   - `DUALLINK_DISPLAY_<n>_PORTS=video,signaling` — non-default port pair
+///   - `DUALLINK_DISPLAY_<n>_ENABLED=0` — skip this display
+///   - `DUALLINK_DISPLAY_<n>_REASSEMBLY=frames,MiB` — reassembly memory budget
+///   - `DUALLINK_DISPLAY_<n>_RECV_BATCH=32` — datagrams per `recvmmsg` (1 = off)
+///   - `DUALLINK_DISPLAY_<n>_MAX_KBPS=20000` — bitrate ceiling sent in `hello_ack`
+///   - `DUALLINK_DISPLAY_<n>_MAX_RESOLUTION=WxH` — resolution ceiling
+///   - `DUALLINK_DISPLAY_<n>_MAX_FPS=30` — frame-rate ceiling, for e-ink and
+///     30 Hz panels
+///   - `DUALLINK_DISPLAY_<n>_LIMITS=reject` — refuse over-limit streams instead
+///     of clamping them
+///
+/// Frames from a sender that keeps exceeding the bitrate ceiling are dropped
+/// (with a warning) until it backs off.
+///
+/// # Decoder preference
+/// `DUALLINK_DECODER=nvh264dec,avdec_h264` (or the GUI's saved choice) lists
+/// decoder elements to try before the probe order, for every display; a
+/// per-display `DUALLINK_DISPLAY_<n>_DECODER` is tried before it.
+///
+/// # Composition
+/// Set `DUALLINK_COMPOSE=side-by-side` (or `pip`) to render all displays in a
+/// single window instead of one window each. `DUALLINK_COMPOSE_SIZE=WxH`
+/// overrides the canvas size (default: 1920×1080 per column / one 1080p
+/// canvas for PiP). Input from the shared window is mapped to the display
+/// under the pointer.
+///
+/// # Session hooks
+/// Commands / webhooks from the saved settings' `hooks`, plus
+/// `DUALLINK_HOOK_EXEC=<command>` and `DUALLINK_HOOK_URL=<http url>`, run when
+/// a session starts, stops or its sender disconnects (see
+/// [`duallink_transport::hooks`]).
+///
+/// # View-only
+/// `DUALLINK_VIEW_ONLY=1` (or the saved settings' `viewOnly`) runs every
+/// session display-only: the sender is told in `hello_ack` and input from
+/// the video windows is dropped.
+///
+/// # Input replay
+/// `DUALLINK_INPUT_REPLAY=<file>` replays a recorded input macro (the GUI's
+/// "Input macro" card saves one) to whichever sender is connected, over and
+/// over — for demo kiosks and unattended UI tests (see
+/// [`duallink_core::input_macro`]).
+///
+/// # Frame dump
+/// `DUALLINK_DUMP=SECS` writes the first seconds of every session's stream
+/// (Annex-B, as decoded) into a `duallink-dump-*` directory, and
+/// `DUALLINK_DUMP=SECS,png` the decoded frames too, for reproducing decoder
+/// artifacts offline (see [`duallink_core::dump`]).
+///
+/// # Hotkeys
+/// Chords typed into a video window are checked before input is forwarded:
+/// Ctrl+Alt+F fullscreen, Ctrl+Alt+S stats overlay, Ctrl+Alt+P freeze /
+/// unfreeze the picture, Ctrl+Alt+B blank the picture and pause the sender's
+/// capture (privacy mode), Ctrl+Alt+D release / re-grab input, Ctrl+Alt+Q
+/// end the session, Ctrl+Alt+T record a frame timing trace, Ctrl+Alt+O hide /
+/// show the overlay widgets (see [`duallink_core::overlay`]). Rebind them in
+/// the saved settings' `hotkeys` map (see [`duallink_core::hotkeys`]); composited
+/// windows have none.
+///
+/// # Decoder benchmark
+/// On first launch (no cached results yet), or every launch with
+/// `--benchmark`, the installed H.264 decoders are timed in the background
+/// and later sessions pick the fastest (see
+/// [`duallink_decoder::benchmark_decoders`]).
+///
+/// # Handover
+/// On Unix the receiver listens on a control socket
+/// (`$XDG_RUNTIME_DIR/duallink-receiver.sock`). When the GUI starts while
+/// this receiver holds the ports, it asks for them there: the displays are
+/// handed over without the ports closing and this process exits cleanly
+/// (see [`duallink_transport::handover`]).
+///
 /// # Flow (per display)
-/// 1. Bind UDP + TCP ports via `DualLinkReceiver::start_all`
+/// 1. Bind UDP + TCP ports via `DualLinkReceiver::start_with_configs`
 /// 2. Wait for `hello` handshake → obtain `StreamConfig`
 /// 3. Initialise the best available GStreamer display decoder
 /// 4. Receive → decode → display loop
@@ -34,6 +123,22 @@ pub async fn run() -> Result<()> {
         .max(1)
         .min(8);
 
+    let compose = std::env::var("DUALLINK_COMPOSE")
+        .ok()
+        .and_then(|s| CompositeLayout::from_name(&s));
+
+    // ── Time the decoders on first launch / --benchmark ────────────────────
+    let force_benchmark = std::env::args().any(|a| a == "--benchmark");
+    if force_benchmark || DecoderBenchmarks::load().is_none() {
+        info!("Benchmarking decoders — sessions started once it finishes use the fastest");
+        tokio::task::spawn_blocking(move || {
+            benchmark_decoders(force_benchmark, |element, latency| match latency {
+                Some(d) => info!("Decoder benchmark: {} {:.1} ms/frame", element, d.as_secs_f64() * 1e3),
+                None => warn!("Decoder benchmark: {} failed", element),
+            })
+        });
+    }
+
     // ── Detect USB Ethernet for low-latency transport ──────────────────────
     if let Some(usb) = detect_usb_ethernet() {
         info!(
@@ -50,15 +155,34 @@ pub async fn run() -> Result<()> {
         display_count
     );
 
-    let (_recv, channels, input_sender, startup) =
-        DualLinkReceiver::start_all(display_count).await?;
+    let base_port = configured_base_port();
+    let configs = (0..display_count).map(|n| display_config_from_env(n, base_port)).collect();
+    let (recv, channels, input_sender, startup) =
+        DualLinkReceiver::start_with_configs(configs, receiver_capabilities()).await?;
+    let recv = Arc::new(recv);
+
+    // ── Hand the ports over when the GUI asks for them ─────────────────────
+    #[cfg(unix)]
+    match HandoverServer::bind() {
+        Ok(server) => {
+            let recv = Arc::clone(&recv);
+            // Releasing the sockets closes every display channel, so the
+            // display tasks below wind down and `run` returns.
+            tokio::spawn(async move {
+                match server.serve(recv).await {
+                    Ok(()) => info!("Receiver handed over to another DualLink instance — exiting."),
+                    Err(e) => warn!("Handover failed: {:#}", e),
+                }
+            });
+        }
+        Err(e) => warn!("Handover socket unavailable: {:#}", e),
+    }
 
     // ── Advertise via mDNS so senders can auto-discover this receiver ──────
     let local_ip = detect_local_ip();
     let _advertiser = DualLinkAdvertiser::register(
         "DualLink Receiver",
-        display_count,
-        SIGNALING_PORT,
+        &recv.port_map(),
         local_ip,
         &startup.tls_fingerprint,
     )
@@ -72,13 +196,54 @@ pub async fn run() -> Result<()> {
     info!("Pairing PIN: {}  |  TLS fingerprint: {}…", startup.pairing_pin, &startup.tls_fingerprint[..16.min(startup.tls_fingerprint.len())]);
     info!("Enter {}  in the DualLink sender app.", local_ip);
 
+    // ── Shared composition window (optional) ───────────────────────────────
+    let composite = match compose {
+        Some(layout) => {
+            let canvas = std::env::var("DUALLINK_COMPOSE_SIZE")
+                .ok()
+                .and_then(|s| {
+                    let (w, h) = s.split_once(['x', 'X'])?;
+                    Some((w.parse().ok()?, h.parse().ok()?))
+                })
+                .unwrap_or_else(|| layout.default_canvas(display_count));
+            let display = tokio::task::spawn_blocking(move || {
+                CompositeDisplay::new(layout, display_count, canvas)
+            })
+            .await??;
+            Some(display)
+        }
+        None => None,
+    };
+
+    // ── Kiosk input replay (optional) ──────────────────────────────────────
+    let _replay = match std::env::var_os("DUALLINK_INPUT_REPLAY") {
+        Some(path) => match InputRecording::load(path.as_ref()) {
+            Ok(recording) => {
+                info!(
+                    "Replaying {} input events from {} in a loop",
+                    recording.events.len(),
+                    std::path::Path::new(&path).display()
+                );
+                Some(input_sender.replay(recording, true))
+            }
+            Err(e) => {
+                warn!("Input replay unavailable: {e}");
+                None
+            }
+        },
+        None => None,
+    };
+
     // ── Spawn one task per display ─────────────────────────────────────────
+    let hooks = Hooks::load();
     let mut handles = Vec::with_capacity(channels.len());
-    for ch in channels {
+    for mut ch in channels {
+        ch.event_rx = hooks.tap(ch.display_index, ch.event_rx);
         let is = input_sender.clone();
+        let comp = composite.clone();
         let handle = tokio::spawn(async move {
             let idx = ch.display_index;
-            if let Err(e) = run_display(ch, is).await {
+            if let Err(e) = run_display(ch, is, comp).await {
                 warn!("Display[{idx}] exited with error: {:#}", e);
             }
         });
@@ -93,255 +258,103 @@ pub async fn run() -> Result<()> {
     Ok(())
 }
 
+/// [`DisplayConfig`] for display `n` with `DUALLINK_DISPLAY_<n>_*` overrides.
+fn display_config_from_env(n: u8, base_port: u16) -> DisplayConfig {
+    let var = |key: &str| std::env::var(format!("DUALLINK_DISPLAY_{n}_{key}")).ok();
+    let resolution = |key: &str| {
+        let s = var(key)?;
+        let (w, h) = s.split_once(['x', 'X'])?;
+        Some(Resolution::new(w.trim().parse().ok()?, h.trim().parse().ok()?))
+    };
+    let mut cfg = DisplayConfig::with_base_port(n, base_port);
+    cfg.resolution_hint = resolution("RESOLUTION");
+    cfg.decoder = var("DECODER");
+    if let Some((v, s)) = var("PORTS").as_deref().and_then(|s| s.split_once(',')) {
+        if let (Ok(v), Ok(s)) = (v.trim().parse(), s.trim().parse()) {
+            cfg.video_port = v;
+            cfg.signaling_port = s;
+        }
+    }
+    cfg.enabled = var("ENABLED").as_deref() != Some("0");
+    if let Some((f, m)) = var("REASSEMBLY").as_deref().and_then(|s| s.split_once(',')) {
+        if let (Ok(f), Ok(m)) = (f.trim().parse(), m.trim().parse::<usize>()) {
+            cfg.reassembly = ReassemblyBudget { max_partial_frames: f, max_buffered_bytes: m << 20 };
+        }
+    }
+    if let Some(batch) = var("RECV_BATCH").and_then(|s| s.trim().parse().ok()) {
+        cfg.recv_batch = batch;
+    }
+    cfg.limits.max_bitrate_kbps = var("MAX_KBPS").and_then(|s| s.trim().parse().ok());
+    cfg.limits.max_resolution = resolution("MAX_RESOLUTION");
+    cfg.limits.max_fps = var("MAX_FPS").and_then(|s| s.trim().parse().ok()).filter(|&fps| fps > 0);
+    cfg.reject_over_limits = var("LIMITS").as_deref() == Some("reject");
+    cfg
+}
+
 // ── Per-display loop ───────────────────────────────────────────────────────────
 
 /// Runs a single display's receive → decode → display loop.
 ///
-/// After each session ends (sender disconnects or stops) the function loops
-/// back to wait for the **next** connection on the same bound ports, so the
+/// After each session ends (sender disconnects or stops) the loop goes back
+/// to wait for the **next** connection on the same bound ports, so the
 /// receiver never needs a restart between sessions.
+///
+/// With `composite` set, each session attaches a branch to the shared window
+/// instead of opening its own.
 async fn run_display(
     ch: DisplayChannels,
     input_sender: InputSender,
+    composite: Option<Arc<CompositeDisplay>>,
 ) -> Result<()> {
-    let DisplayChannels { display_index, mut frame_rx, mut event_rx } = ch;
-
-    let mut session_count: u32 = 0;
-
-    // Pending config forwarded from a mid-session ConfigUpdated event (hot-reload).
-    // When set, the next 'reconnect iteration uses it instead of waiting for a new hello.
-    let mut pending_config: Option<StreamConfig> = None;
-
-    // ── Reconnect loop: one iteration per sender session ──────────────────
-    'reconnect: loop {
-        if session_count == 0 {
-            info!(
-                "Display[{}] Waiting for sender to connect...",
-                display_index
-            );
-        } else if pending_config.is_some() {
-            // Hot-reload path: session is still alive, just reinitialise the decoder.
-            info!(
-                "Display[{}] Session {} — hot-reloading decoder (resolution change)",
-                display_index, session_count
-            );
-        } else {
-            info!(
-                "Display[{}] Session {} ended — ready for next connection",
-                display_index, session_count
-            );
-            // Brief pause so the OS has time to clean up the prior TCP conn
-            tokio::time::sleep(Duration::from_millis(300)).await;
-        }
-
-        // ── Obtain StreamConfig: pending hot-reload config or wait for hello ──
-        let config = if let Some(cfg) = pending_config.take() {
-            // Hot-reload: re-initialise decoder with the new resolution from ConfigUpdated,
-            // without waiting for a new ClientHello (the TCP session is still alive).
-            info!(
-                "Display[{}] Hot-reloading decoder with updated config: {:?}",
-                display_index, cfg
-            );
-            cfg
-        } else {
-            // Normal path: wait for the sender's hello handshake.
-            let cfg = loop {
-                match event_rx.recv().await {
-                    Some(SignalingEvent::SessionStarted {
-                        session_id,
-                        device_name,
-                        config,
-                        client_addr,
-                    }) => {
-                        session_count += 1;
-                        info!(
-                            "Display[{}] Session #{} started: id={} from='{}' addr={} config={:?}",
-                            display_index, session_count, session_id,
-                            device_name, client_addr, config
-                        );
-                        break config;
-                    }
-                    Some(SignalingEvent::ClientDisconnected) => {
-                        warn!(
-                            "Display[{}] Client disconnected before hello — waiting again",
-                            display_index
-                        );
-                    }
-                    Some(other) => {
-                        tracing::debug!("Display[{}] Pre-session event: {:?}", display_index, other);
-                    }
-                    None => {
-                        // Channel closed permanently — no more connections possible
-                        info!(
-                            "Display[{}] Signaling channel closed (total sessions: {}). Exiting.",
-                            display_index, session_count
-                        );
-                        break 'reconnect;
-                    }
-                }
-            };
-            cfg
-        };
-
-        // ── Initialise display decoder (new instance per session) ─────────
-        let width  = config.resolution.width;
-        let height = config.resolution.height;
-
-        let display_decoder = match tokio::task::spawn_blocking(move || {
-            DecoderFactory::best_available_with_display(width, height)
-        })
-        .await
-        {
-            Ok(Ok(dec)) => dec,
-            Ok(Err(e)) => {
-                warn!(
-                    "Display[{}] Decoder init failed: {} — skipping session",
-                    display_index, e
-                );
-                continue 'reconnect;
-            }
-            Err(e) => {
-                warn!("Display[{}] Spawn-blocking panicked: {}", display_index, e);
-                continue 'reconnect;
-            }
-        };
-
-        let hw   = display_decoder.is_hardware_accelerated();
-        let elem = display_decoder.element_name().to_string();
-        info!(
-            "Display[{}] Decoder ready: {} hw={} — video window should appear",
-            display_index, elem, hw
-        );
-
-        // ── Dedicated blocking thread for decode + display + input ─────────
-        let (decode_tx, mut decode_rx) = mpsc::channel::<EncodedFrame>(64);
-        let push_errors = Arc::new(AtomicU64::new(0));
-        let pe   = Arc::clone(&push_errors);
-        let idx  = display_index;
-        let is2  = input_sender.clone();
-
-        let decode_handle = tokio::task::spawn_blocking(move || {
-            while let Some(frame) = decode_rx.blocking_recv() {
-                let sz = frame.data.len();
-                let kf = frame.is_keyframe;
-                match display_decoder.push_frame(frame) {
-                    Ok(()) => {
-                        let n = display_decoder.frames_pushed();
-                        if n == 1 {
-                            info!("Display[{idx}] First frame decoded and displayed!");
-                        }
-                        if n % 300 == 0 {
-                            info!("Display[{idx}] Displayed {} frames", n);
-                        }
-                    }
-                    Err(e) => {
-                        let errs = pe.fetch_add(1, Ordering::Relaxed) + 1;
-                        if errs <= 10 || errs % 100 == 0 {
-                            warn!(
-                                "Display[{idx}] push error #{} ({} bytes keyframe={}): {}",
-                                errs, sz, kf, e
-                            );
-                        }
-                    }
-                }
-                // Forward input events captured from the GStreamer window
-                for event in display_decoder.poll_input_events() {
-                    let _ = is2.try_send(event);
-                }
-            }
-            info!("Display[{idx}] decode+display thread exiting");
-        });
-
-        // ── Main async receive → decode loop ───────────────────────────────
-        info!(
-            "Display[{}] Streaming — receiving and displaying frames...",
-            display_index
-        );
-        let mut frames_received: u64 = 0;
-
-        let session_exit_reason = loop {
-            tokio::select! {
-                // Incoming encoded frame
-                Some(frame) = frame_rx.recv() => {
-                    frames_received += 1;
-                    if frames_received <= 5 {
-                        tracing::debug!(
-                            "Display[{}] Frame #{}: {} bytes keyframe={}",
-                            display_index, frames_received, frame.data.len(), frame.is_keyframe
-                        );
-                    }
-                    if frames_received % 300 == 0 {
-                        let errs = push_errors.load(Ordering::Relaxed);
-                        info!(
-                            "Display[{}] Stats: received={} errors={}",
-                            display_index, frames_received, errs
-                        );
-                    }
-                    if decode_tx.send(frame).await.is_err() {
-                        warn!("Display[{}] Decode thread gone — stopping session", display_index);
-                        break "decode_thread_gone";
-                    }
-                }
+    // Per-display decoder first, then the global preference.
+    let preference: Vec<String> = ch
+        .config
+        .decoder
+        .iter()
+        .cloned()
+        .chain(DecoderFactory::from_settings().preference().iter().cloned())
+        .collect();
+    let display_index = ch.display_index;
+    let files = ch.files.clone();
+    ReceiverSession::new(ch, input_sender, AppSession { display_index, preference, composite, files })
+        .run()
+        .await;
+    Ok(())
+}
 
-                // Signaling events mid-session
-                Some(event) = event_rx.recv() => {
-                    match event {
-                        SignalingEvent::SessionStopped { session_id } => {
-                            info!(
-                                "Display[{}] Session {} stopped by sender",
-                                display_index, session_id
-                            );
-                            break "session_stopped";
-                        }
-                        SignalingEvent::ClientDisconnected => {
-                            warn!("Display[{}] Sender disconnected unexpectedly", display_index);
-                            break "client_disconnected";
-                        }
-                        SignalingEvent::ConfigUpdated { config: new_cfg } => {
-                            info!("Display[{}] Config update received: {:?}", display_index, new_cfg);
-                            let cur_w = config.resolution.width;
-                            let cur_h = config.resolution.height;
-                            if new_cfg.resolution.width != cur_w || new_cfg.resolution.height != cur_h {
-                                info!(
-                                    "Display[{}] Resolution change {}×{} → {}×{}: hot-reloading decoder",
-                                    display_index,
-                                    cur_w, cur_h,
-                                    new_cfg.resolution.width, new_cfg.resolution.height
-                                );
-                                pending_config = Some(new_cfg);
-                                break "config_updated";
-                            }
-                            // Same resolution — no decoder restart needed
-                        }
-                        _ => {}
-                    }
-                }
+/// [`SessionHooks`] of the headless receiver: picks the output, declines
+/// offered files — there is no one to ask — and leaves the rest to the
+/// session's logging.
+struct AppSession {
+    display_index: u8,
+    preference:    Vec<String>,
+    composite:     Option<Arc<CompositeDisplay>>,
+    files:         FileTransfers,
+}
 
-                else => break "channels_closed",
+impl SessionHooks for AppSession {
+    fn opener(&mut self, config: &StreamConfig, excluded: &[String]) -> Opener {
+        let display_index = self.display_index;
+        let config = config.clone();
+        let comp = self.composite.clone();
+        let preferred = self.preference.clone();
+        let excluded = excluded.to_vec();
+        Box::new(move || {
+            let preferred: Vec<&str> = preferred.iter().map(String::as_str).collect();
+            match comp {
+                Some(c) => c
+                    .attach(display_index, &config, &preferred)
+                    .map(|slot| Box::new(slot) as Box<dyn DisplayOutput>),
+                None => DecoderFactory::with_preference(&preferred)
+                    .excluding(&excluded)
+                    .decoder_for(&config),
             }
-        };
-
-        // Signal decode thread to stop and wait for it
-        drop(decode_tx);
-        let _ = decode_handle.await;
-
-        let total_errs = push_errors.load(Ordering::Relaxed);
-        info!(
-            "Display[{}] Session #{} complete ({}). received={} errors={}",
-            display_index, session_count, session_exit_reason,
-            frames_received, total_errs
-        );
+        })
+    }
 
-        // "channels_closed" means the transport layer shut down permanently
-        if session_exit_reason == "channels_closed" {
-            break 'reconnect;
+    fn event(&mut self, event: &SignalingEvent) {
+        if let SignalingEvent::File(FileTransferEvent::Offered(offer)) = event {
+            self.files.answer(&offer.transfer_id, false);
         }
-
-        // "config_updated": pending_config already set above — loop back to re-init decoder.
-        // All other reasons: loop back and wait for the next sender connection.
     }
-
-    info!("Display[{}] Receiver loop exited.", display_index);
-    Ok(())
 }
-
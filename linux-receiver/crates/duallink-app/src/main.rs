@@ -1,5 +1,15 @@
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
 use anyhow::Result;
+use duallink_core::firewall::reachability;
+use duallink_core::logbuf::{self, RotatingFile};
+use duallink_core::{receiver_ports, DiagnosticsReport, FirewallCheck, PortMap};
+use duallink_transport::configured_base_port;
 use tracing::{error, info};
+use tracing_subscriber::prelude::*;
 use tracing_subscriber::EnvFilter;
 
 mod app;
@@ -9,15 +19,52 @@ async fn main() -> Result<()> {
     // Inicializar logging
     // Usar RUST_LOG=debug para mais detalhes
     // Usar GST_DEBUG=3 para GStreamer debug
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
-        )
-        .with_target(true)
-        .with_thread_ids(false)
+    // Usar DUALLINK_LOG_FILE=<path> para gravar também num arquivo rotativo
+    let log_file = logbuf::log_file().and_then(|path| {
+        RotatingFile::open(&path, logbuf::configured_log_max_bytes())
+            .map_err(|e| eprintln!("Log file {} not opened: {e}", path.display()))
+            .ok()
+    });
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(tracing_subscriber::fmt::layer().with_target(true).with_thread_ids(false))
+        .with(log_file.map(|file| {
+            tracing_subscriber::fmt::layer().with_target(true).with_ansi(false).with_writer(Mutex::new(file))
+        }))
         .init();
 
     info!("DualLink Receiver v{}", env!("CARGO_PKG_VERSION"));
+
+    // --diagnose: write a diagnostics bundle for bug reports and exit
+    if std::env::args().any(|a| a == "--diagnose") {
+        let mut report = DiagnosticsReport::collect(env!("CARGO_PKG_VERSION"));
+        duallink_decoder::fill_diagnostics(&mut report);
+        let path = report.write_to(Path::new("."))?;
+        println!("Diagnostics written to {}", path.display());
+        return Ok(());
+    }
+
+    // --trace[=SECS]: record per-frame timing spans once frames arrive
+    if let Some(duration) = duallink_core::trace::duration_arg(std::env::args()) {
+        duallink_core::trace::start(duration, ".".into());
+    }
+
+    // firewall: check the receiver's ports and offer to open them
+    if std::env::args().nth(1).as_deref() == Some("firewall") {
+        return firewall_command();
+    }
+
+    // probe [--json]: report the decoders found and the ones sessions would use
+    if std::env::args().nth(1).as_deref() == Some("probe") {
+        let report = duallink_decoder::DecoderFactory::from_settings().probe_report().await;
+        if std::env::args().any(|a| a == "--json") {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            println!("{report}");
+        }
+        return Ok(());
+    }
+
     info!("Starting...");
 
     // Iniciar o app principal
@@ -32,3 +79,55 @@ async fn main() -> Result<()> {
         }
     }
 }
+
+/// `duallink-receiver firewall`: report what may keep senders out and,
+/// after a y/N prompt, open the ports the firewall blocks.
+fn firewall_command() -> Result<()> {
+    let display_count: u8 = std::env::var("DUALLINK_DISPLAY_COUNT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(1)
+        .clamp(1, 8);
+    let map = PortMap::contiguous(configured_base_port(), display_count);
+    let ports = receiver_ports(&map);
+    let check = FirewallCheck::run(&ports);
+
+    for port in &check.privileged {
+        println!(
+            "Port {port} is privileged: run as root, grant the binary cap_net_bind_service \
+             (sudo setcap cap_net_bind_service=+ep <binary>) or set DUALLINK_BASE_PORT above 1024."
+        );
+    }
+
+    let signaling = map.signaling_port(0);
+    let reach = reachability(signaling, Duration::from_millis(500));
+    if reach.iter().any(|(_, ok)| *ok) {
+        for (ip, _) in reach.iter().filter(|(_, ok)| !ok) {
+            println!("The receiver does not answer on {ip}:{signaling} — is it bound to another interface?");
+        }
+    } else {
+        println!("No receiver is listening on TCP {signaling}; start one to test reachability.");
+    }
+
+    let Some(firewall) = check.firewall else {
+        println!("No active firewall (firewalld or ufw) found.");
+        return Ok(());
+    };
+    if check.blocked.is_empty() {
+        println!("{firewall} lets every receiver port in.");
+        return Ok(());
+    }
+    println!("{firewall} may block senders on {}.", check.blocked_list());
+    println!("Opening them runs, as root:\n  {}", firewall.open_script(&check.blocked));
+    print!("Open these ports now? [y/N] ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    if matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes") {
+        firewall.open(&check.blocked)?;
+        println!("Opened.");
+    } else {
+        println!("Left unchanged.");
+    }
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Look of the DualLink UIs: theme, UI scale and window geometry.
+//!
+//! Stored as JSON in `duallink/appearance.json`, shared by the receiver GUI
+//! and the sender UIs so a theme picked in one applies to all. Window
+//! geometry is kept per UI under its [`AppearanceSettings::windows`] key
+//! and written when the window closes.
+//!
+//! ```json
+//! {
+//!   "theme": "light",
+//!   "uiScale": 1.5,
+//!   "windows": { "receiver": { "position": [40.0, 60.0], "size": [560.0, 720.0] } }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::config_file;
+
+const FILE_NAME: &str = "appearance.json";
+
+/// UI scale factors offered by the pickers.
+pub const UI_SCALES: [f32; 8] = [0.75, 1.0, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0];
+
+/// Smallest and largest UI scale applied; saved values are clamped to it.
+pub const UI_SCALE_RANGE: (f32, f32) = (0.5, 4.0);
+
+// MARK: - Theme
+
+/// Colour scheme of the UIs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    /// Light text on dark panels.
+    #[default]
+    Dark,
+    /// Dark text on light panels, for bright rooms and projectors.
+    Light,
+    /// Follow the desktop's dark / light preference.
+    System,
+}
+
+impl Theme {
+    /// Every theme, in picker order.
+    pub const ALL: [Theme; 3] = [Self::Dark, Self::Light, Self::System];
+}
+
+// MARK: - Window geometry
+
+/// Where a UI window was when it last closed, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    /// Outer top-left corner; `None` where the platform does not report it
+    /// (Wayland).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<[f32; 2]>,
+    /// Inner size.
+    pub size:     [f32; 2],
+}
+
+// MARK: - AppearanceSettings
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AppearanceSettings {
+    pub theme:    Theme,
+    /// Factor applied to all UI sizes, for HiDPI screens (1.0 = native).
+    pub ui_scale: f32,
+    /// Last geometry of each UI's main window, by UI name (`receiver`,
+    /// `linux-sender`, `windows-sender`).
+    pub windows:  BTreeMap<String, WindowGeometry>,
+}
+
+impl Default for AppearanceSettings {
+    fn default() -> Self {
+        Self { theme: Theme::default(), ui_scale: 1.0, windows: BTreeMap::new() }
+    }
+}
+
+impl AppearanceSettings {
+    /// Load the saved settings; defaults if none were saved or the file is unreadable.
+    pub fn load() -> Self {
+        let Some(path) = config_file(FILE_NAME) else { return Self::default() };
+        std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the settings back to the config directory.
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = config_file(FILE_NAME).ok_or_else(|| std::io::Error::other("no config directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    /// [`ui_scale`](Self::ui_scale) within [`UI_SCALE_RANGE`]; 1.0 if not a
+    /// number.
+    pub fn scale(&self) -> f32 {
+        if self.ui_scale.is_finite() {
+            self.ui_scale.clamp(UI_SCALE_RANGE.0, UI_SCALE_RANGE.1)
+        } else {
+            1.0
+        }
+    }
+
+    /// Saved geometry of UI `app`, if it is big enough to be usable.
+    pub fn window(&self, app: &str) -> Option<WindowGeometry> {
+        self.windows.get(app).copied().filter(|g| g.size[0] >= 200.0 && g.size[1] >= 150.0)
+    }
+
+    /// Remember `geometry` for UI `app`, keeping the other settings as they
+    /// are on disk (another UI may have changed them meanwhile).
+    pub fn save_window(app: &str, geometry: WindowGeometry) -> std::io::Result<()> {
+        let mut settings = Self::load();
+        settings.windows.insert(app.to_owned(), geometry);
+        settings.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_partial_files_and_clamps_the_scale() {
+        let s: AppearanceSettings = serde_json::from_str(r#"{"theme":"system"}"#).unwrap();
+        assert_eq!(s.theme, Theme::System);
+        assert_eq!(s.scale(), 1.0);
+
+        let s: AppearanceSettings = serde_json::from_str(
+            r#"{"uiScale":9,"windows":{"receiver":{"size":[560,720]},"tiny":{"size":[10,10]}}}"#,
+        )
+        .unwrap();
+        assert_eq!(s.scale(), UI_SCALE_RANGE.1);
+        assert_eq!(s.window("receiver"), Some(WindowGeometry { position: None, size: [560.0, 720.0] }));
+        assert_eq!(s.window("tiny"), None);
+    }
+}
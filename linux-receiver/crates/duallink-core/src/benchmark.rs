@@ -0,0 +1,107 @@
+//! Cached decoder latency benchmark.
+//!
+//! The static decoder probe order was measured on one machine (GT-2001,
+//! a Legion 5 Pro); on other hardware VA-API can be slower than NVDEC or
+//! software decoding. The receiver times each installed decoder once — on
+//! first launch or with `--benchmark` — and keeps the results in
+//! `decoder-benchmark.json` in the DualLink config directory. Decoder
+//! probing then tries measured decoders fastest first.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::config_file;
+
+/// Measured per-frame decode times, by GStreamer element.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecoderBenchmarks {
+    /// Unix time of the last measurement, in seconds.
+    #[serde(default)]
+    pub measured_at: u64,
+    /// Milliseconds per frame; `None` = the element failed the benchmark.
+    #[serde(default)]
+    pub results:     BTreeMap<String, Option<f64>>,
+}
+
+impl DecoderBenchmarks {
+    /// `decoder-benchmark.json` in the DualLink config directory.
+    pub fn path() -> Option<PathBuf> {
+        config_file("decoder-benchmark.json")
+    }
+
+    /// The cached results; `None` if there are none yet (first launch) or
+    /// the file is unreadable.
+    pub fn load() -> Option<Self> {
+        let text = std::fs::read_to_string(Self::path()?).ok()?;
+        serde_json::from_str(&text)
+            .map_err(|e| tracing::warn!("Ignoring decoder benchmark cache: {e}"))
+            .ok()
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = Self::path().ok_or_else(|| std::io::Error::other("no config directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Store the result for `element` (`None` = failed).
+    pub fn record(&mut self, element: &str, latency: Option<Duration>) {
+        self.results.insert(element.to_string(), latency.map(|d| d.as_secs_f64() * 1e3));
+        self.measured_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    }
+
+    /// `true` if every one of `elements` has been benchmarked.
+    pub fn covers<'a>(&self, elements: impl IntoIterator<Item = &'a str>) -> bool {
+        elements.into_iter().all(|e| self.results.contains_key(e))
+    }
+
+    /// Measured time for `element`; `None` if not measured or failed.
+    pub fn latency(&self, element: &str) -> Option<Duration> {
+        self.results.get(element).copied().flatten().map(|ms| Duration::from_secs_f64(ms / 1e3))
+    }
+
+    /// Reorder `elements` (given in static priority order): measured
+    /// elements fastest first, then unmeasured ones in their given order,
+    /// then those that failed the benchmark.
+    pub fn rank<T>(&self, elements: &mut [T], name: impl Fn(&T) -> &str) {
+        let key = |e: &T| match self.results.get(name(e)) {
+            Some(Some(ms)) => (0, *ms),
+            None => (1, 0.0),
+            Some(None) => (2, 0.0),
+        };
+        // Stable: ties keep the static order.
+        elements.sort_by(|a, b| {
+            let (a, b) = (key(a), key(b));
+            a.0.cmp(&b.0).then(a.1.total_cmp(&b.1))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measured_decoders_rank_fastest_first() {
+        let mut bench = DecoderBenchmarks::default();
+        bench.record("vaapih264dec", Some(Duration::from_micros(9_000)));
+        bench.record("nvh264dec", Some(Duration::from_micros(4_500)));
+        bench.record("vaapidecodebin", None);
+        let mut order = ["vaapih264dec", "vaapidecodebin", "nvh264dec", "avdec_h264"];
+        bench.rank(&mut order, |e| e);
+        assert_eq!(order, ["nvh264dec", "vaapih264dec", "avdec_h264", "vaapidecodebin"]);
+        assert!(bench.covers(["nvh264dec", "vaapidecodebin"]));
+        assert!(!bench.covers(["avdec_h264"]));
+        assert_eq!(bench.latency("nvh264dec"), Some(Duration::from_micros(4_500)));
+        assert_eq!(bench.latency("vaapidecodebin"), None);
+
+        let json = serde_json::to_string(&bench).unwrap();
+        assert_eq!(serde_json::from_str::<DecoderBenchmarks>(&json).unwrap(), bench);
+    }
+}
@@ -0,0 +1,226 @@
+//! Presentation timestamps from the sender, mapped onto the receiver clock.
+//!
+//! DLNK v1 headers carry a 32-bit millisecond PTS that wraps after ~49.7
+//! days; [`PtsUnwrapper`] extends it to 64 bits. DLNK v2 headers (sent to
+//! receivers advertising [`CAP_DLNK_V2`](crate::CAP_DLNK_V2)) carry a 64-bit
+//! microsecond PTS plus the sender's clock epoch, an id that changes
+//! whenever the sender's clock origin does.
+//!
+//! [`ClockMapper`] places those timestamps on the receiver's timeline: the
+//! first frame of an epoch is anchored at its arrival time and later frames
+//! keep the sender's spacing. Mappers made with `default()` share one
+//! timeline ([`timeline_start`]), so frames of different display streams
+//! captured together get nearby timestamps (see [`crate::display_sync`]). Comparing each arrival with its mapped time
+//! gives the queueing delay on top of the best transit seen so far.
+//!
+//! [`PlayoutBuffer`] is the jitter buffer of
+//! [`LatencyMode::Quality`](crate::LatencyMode::Quality) sessions: it holds
+//! each mapped frame until a fixed delay after the time the sender's spacing
+//! puts it at.
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+// MARK: - PtsUnwrapper
+
+/// Extends wrapping 32-bit millisecond timestamps (DLNK v1) to 64 bits.
+///
+/// Steps of up to ±24.8 days are taken as the shortest way around, so
+/// slightly reordered frames step back instead of jumping a full cycle.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PtsUnwrapper {
+    last_ms: Option<u64>,
+}
+
+impl PtsUnwrapper {
+    /// The 64-bit timestamp, in microseconds, for `pts_ms`.
+    pub fn unwrap(&mut self, pts_ms: u32) -> u64 {
+        let ms = match self.last_ms {
+            None => pts_ms as u64,
+            Some(last) => {
+                let step = pts_ms.wrapping_sub(last as u32) as i32;
+                last.saturating_add_signed(step as i64)
+            }
+        };
+        self.last_ms = Some(ms);
+        ms * 1_000
+    }
+}
+
+// MARK: - ClockMapper
+
+/// Start of the receiver timeline shared by the process's default mappers.
+pub fn timeline_start() -> Instant {
+    static START: OnceLock<Instant> = OnceLock::new();
+    *START.get_or_init(Instant::now)
+}
+
+/// Backwards PTS step taken as a sender clock restart rather than reordering.
+const MAX_PTS_REWIND_US: u64 = 1_000_000;
+
+/// Maps sender PTS onto the receiver timeline (µs since the mapper was
+/// created) and measures queueing delay.
+#[derive(Debug, Clone)]
+pub struct ClockMapper {
+    start:       Instant,
+    /// Epoch the anchor belongs to; `None` for v1 senders.
+    epoch:       Option<u32>,
+    /// Sender PTS and receiver time of the anchoring frame.
+    anchor:      Option<(u64, u64)>,
+    last_pts:    u64,
+    last_out:    u64,
+    /// Smallest `arrival - mapped` seen since the anchor, and the latest.
+    min_transit: i64,
+    transit:     i64,
+}
+
+impl Default for ClockMapper {
+    fn default() -> Self {
+        Self::new(timeline_start())
+    }
+}
+
+impl ClockMapper {
+    /// Mapper whose timeline starts at `start`.
+    pub fn new(start: Instant) -> Self {
+        Self {
+            start,
+            epoch: None,
+            anchor: None,
+            last_pts: 0,
+            last_out: 0,
+            min_transit: 0,
+            transit: 0,
+        }
+    }
+
+    /// Receiver time, in µs since `start`, of the frame with `pts_us` from
+    /// clock `epoch` (`None` for v1) that arrived at `arrival`.
+    ///
+    /// Re-anchors on a new epoch or when the PTS jumps back by more than a
+    /// second; mapped times never go backwards across a re-anchor.
+    pub fn map(&mut self, epoch: Option<u32>, pts_us: u64, arrival: Instant) -> u64 {
+        let now = arrival.saturating_duration_since(self.start).as_micros() as u64;
+        let rewound = pts_us.saturating_add(MAX_PTS_REWIND_US) < self.last_pts;
+        let (anchor_pts, anchor_out) = match self.anchor {
+            Some(anchor) if epoch == self.epoch && !rewound => anchor,
+            _ => {
+                let anchor = (pts_us, now.max(self.last_out));
+                self.epoch = epoch;
+                self.anchor = Some(anchor);
+                self.min_transit = i64::MAX;
+                anchor
+            }
+        };
+
+        let out = if pts_us >= anchor_pts {
+            anchor_out.saturating_add(pts_us - anchor_pts)
+        } else {
+            anchor_out.saturating_sub(anchor_pts - pts_us)
+        };
+        self.last_pts = pts_us;
+        self.last_out = self.last_out.max(out);
+        self.transit = now as i64 - out as i64;
+        self.min_transit = self.min_transit.min(self.transit);
+        out
+    }
+
+    /// How much longer the latest frame took to arrive than the fastest one
+    /// since the anchor — network and sender queueing above the baseline.
+    pub fn queueing_delay(&self) -> Duration {
+        Duration::from_micros(self.transit.saturating_sub(self.min_transit).max(0) as u64)
+    }
+}
+
+// MARK: - PlayoutBuffer
+
+/// Schedules frames with mapped timestamps ([`ClockMapper::map`]) `delay`
+/// after the sender's spacing puts them, absorbing that much jitter.
+///
+/// The first frame anchors the schedule. Frames arriving later than their
+/// slot are released at once; a frame more than `delay` late (a stall, a
+/// re-anchored mapper) re-anchors the schedule on itself.
+#[derive(Debug, Clone)]
+pub struct PlayoutBuffer {
+    delay:  Duration,
+    /// Mapped timestamp and release time of the anchoring frame.
+    anchor: Option<(u64, Instant)>,
+}
+
+impl PlayoutBuffer {
+    pub fn new(delay: Duration) -> Self {
+        Self { delay, anchor: None }
+    }
+
+    /// How long to hold the frame mapped to `timestamp_us` that is ready
+    /// `now`.
+    pub fn hold(&mut self, timestamp_us: u64, now: Instant) -> Duration {
+        let release = self.anchor.and_then(|(ts, at)| match timestamp_us.checked_sub(ts) {
+            Some(ahead) => at.checked_add(Duration::from_micros(ahead)),
+            None => at.checked_sub(Duration::from_micros(ts - timestamp_us)),
+        });
+        match release {
+            Some(release) if now.saturating_duration_since(release) <= self.delay => {
+                release.saturating_duration_since(now)
+            }
+            _ => {
+                self.anchor = Some((timestamp_us, now + self.delay));
+                self.delay
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClockMapper, PlayoutBuffer, PtsUnwrapper};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn v1_timestamps_unwrap_across_u32() {
+        let mut u = PtsUnwrapper::default();
+        assert_eq!(u.unwrap(u32::MAX - 1), (u32::MAX as u64 - 1) * 1_000);
+        assert_eq!(u.unwrap(3), (u32::MAX as u64 + 4) * 1_000);
+        // A reordered frame from before the wrap steps back, not forward.
+        assert_eq!(u.unwrap(u32::MAX), u32::MAX as u64 * 1_000);
+    }
+
+    #[test]
+    fn mapping_keeps_sender_spacing_and_reanchors_monotonically() {
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+        let mut m = ClockMapper::new(t0);
+
+        assert_eq!(m.map(Some(7), 5_000_000, at(10)), 10_000);
+        // 16 ms of sender time later, but it arrived 4 ms late.
+        assert_eq!(m.map(Some(7), 5_016_000, at(30)), 26_000);
+        assert_eq!(m.queueing_delay(), Duration::from_millis(4));
+
+        // Sender restarted its clock: new epoch, PTS back near zero.
+        let out = m.map(Some(8), 0, at(20));
+        assert_eq!(out, 26_000);
+        assert_eq!(m.map(Some(8), 16_000, at(40)), 42_000);
+
+        // v1 sender restart: same (absent) epoch, PTS rewinds.
+        m.map(None, 90_000_000, at(50));
+        assert_eq!(m.map(None, 0, at(60)), 60_000);
+    }
+
+    #[test]
+    fn playout_holds_frames_a_fixed_delay_past_their_slot() {
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+        let ms = Duration::from_millis;
+        let mut p = PlayoutBuffer::new(ms(50));
+
+        assert_eq!(p.hold(1_000_000, at(0)), ms(50));
+        // On time, early and late (within the buffer) frames.
+        assert_eq!(p.hold(1_016_000, at(16)), ms(50));
+        assert_eq!(p.hold(1_033_000, at(20)), ms(63));
+        assert_eq!(p.hold(1_050_000, at(90)), ms(10));
+        assert_eq!(p.hold(1_066_000, at(116)), Duration::ZERO);
+        // A stall longer than the buffer starts a new schedule.
+        assert_eq!(p.hold(1_083_000, at(300)), ms(50));
+        assert_eq!(p.hold(1_100_000, at(317)), ms(50));
+    }
+}
@@ -1,4 +1,7 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
+use crate::slices::CAP_NAL_SLICES;
 use crate::types::{Resolution, VideoCodec};
 
 /// Configuração de stream de vídeo.
@@ -13,12 +16,49 @@ pub struct StreamConfig {
     pub codec: VideoCodec,
     #[serde(alias = "lowLatencyMode")]
     pub low_latency_mode: bool,
+    /// Latency / quality trade-off both ends tune for. Requested by the
+    /// sender in `hello`; the receiver falls back to
+    /// [`LatencyMode::UltraLow`] unless it advertises [`CAP_LATENCY_MODE`].
+    #[serde(alias = "latencyMode", default)]
+    pub latency_mode: LatencyMode,
     /// Zero-based index identifying which display channel this config belongs to.
     /// Drives port selection: video=7878+2*n, signaling=7879+2*n.
     #[serde(alias = "displayIndex", default)]
     pub display_index: u8,
+    /// Named quality preset the sender is applying, if any. Sent in `hello` and
+    /// in mid-session `config_update` messages when the user switches presets.
+    #[serde(alias = "qualityPreset", default, skip_serializing_if = "Option::is_none")]
+    pub quality_preset: Option<QualityPreset>,
+    /// Near-lossless text mode: H.264 High 4:4:4 at constant QP ≤ 18.
+    ///
+    /// Requested by the sender in `hello`; the receiver clears it in the
+    /// negotiated config (echoed in `hello_ack`) unless it advertises
+    /// [`CAP_H264_444`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lossless: bool,
+    /// Colour range and matrix of the encoded stream. Both ends set caps from
+    /// this instead of relying on `videoconvert`'s resolution-based defaults.
+    #[serde(default)]
+    pub color: ColorSpace,
+    /// HDR static metadata. `Some` means a 10-bit HEVC Main10 PQ stream
+    /// (`codec` = H.265); [`color`](Self::color) is then ignored in favour of
+    /// [`HDR_COLORIMETRY`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hdr: Option<HdrMetadata>,
+    /// Slice-level decoding (see [`crate::slices`]): frames are sent NAL
+    /// unit by NAL unit and decoded with `alignment=nal`. Requested by the
+    /// sender in `hello`; cleared unless the receiver advertises
+    /// [`CAP_NAL_SLICES`] and the stream is H.264.
+    #[serde(alias = "nalSlices", default, skip_serializing_if = "std::ops::Not::not")]
+    pub nal_slices: bool,
 }
 
+/// Receiver capability: can decode H.264 High 4:4:4 Predictive.
+pub const CAP_H264_444: &str = "h264_444";
+
+/// Receiver capability: can decode 10-bit HEVC Main10 (required for HDR).
+pub const CAP_HEVC_MAIN10: &str = "hevc_main10";
+
 impl Default for StreamConfig {
     fn default() -> Self {
         Self {
@@ -27,7 +67,13 @@ impl Default for StreamConfig {
             max_bitrate_bps: 8_000_000,
             codec: VideoCodec::H264,
             low_latency_mode: true,
+            latency_mode: LatencyMode::UltraLow,
             display_index: 0,
+            quality_preset: None,
+            lossless: false,
+            color: ColorSpace::default(),
+            hdr: None,
+            nal_slices: false,
         }
     }
 }
@@ -41,7 +87,13 @@ impl StreamConfig {
             max_bitrate_bps: 20_000_000,
             codec: VideoCodec::H264,
             low_latency_mode: true,
+            latency_mode: LatencyMode::UltraLow,
             display_index: 0,
+            quality_preset: None,
+            lossless: false,
+            color: ColorSpace::default(),
+            hdr: None,
+            nal_slices: false,
         }
     }
 
@@ -49,11 +101,458 @@ impl StreamConfig {
     pub fn frame_interval_us(&self) -> u64 {
         1_000_000 / self.target_fps as u64
     }
+
+    /// Reconciles the sender's requested config with the receiver's
+    /// advertised capabilities, disabling features the receiver cannot decode.
+    pub fn negotiate(mut self, capabilities: &[String]) -> Self {
+        let has = |cap: &str| capabilities.iter().any(|c| c == cap);
+        if self.lossless && !has(CAP_H264_444) {
+            self.lossless = false;
+        }
+        if self.hdr.is_some() && !has(CAP_HEVC_MAIN10) {
+            // Fall back to an 8-bit SDR H.264 stream.
+            self.hdr = None;
+            self.codec = VideoCodec::H264;
+        }
+        if self.latency_mode != LatencyMode::UltraLow && !has(CAP_LATENCY_MODE) {
+            self.latency_mode = LatencyMode::UltraLow;
+            self.low_latency_mode = true;
+        }
+        if self.nal_slices && (!has(CAP_NAL_SLICES) || self.codec != VideoCodec::H264) {
+            self.nal_slices = false;
+        }
+        self
+    }
+
+    /// GStreamer `colorimetry` caps value for this stream (PQ / BT.2020 when HDR).
+    pub fn gst_colorimetry(&self) -> String {
+        match self.hdr {
+            Some(_) => HDR_COLORIMETRY.to_owned(),
+            None => self.color.gst_colorimetry(),
+        }
+    }
+
+    /// Applies a quality preset's frame rate and bitrate (scaled for the
+    /// latency mode), keeping resolution, codec and display index.
+    pub fn with_preset(mut self, preset: QualityPreset) -> Self {
+        let params = preset.params();
+        self.target_fps = params.target_fps;
+        self.max_bitrate_bps = self.latency_mode.scale_bitrate(params.max_bitrate_bps);
+        self.quality_preset = Some(preset);
+        self
+    }
+
+    /// Lowers bitrate, frame rate and resolution to `limits`. An oversized
+    /// resolution is scaled down to fit, keeping the aspect ratio and even
+    /// dimensions.
+    pub fn clamp_to(mut self, limits: &StreamLimits) -> Self {
+        if let Some(max) = limits.max_bitrate_bps() {
+            self.max_bitrate_bps = self.max_bitrate_bps.min(max);
+        }
+        if let Some(max) = limits.max_fps {
+            self.target_fps = self.target_fps.min(max.max(1));
+        }
+        if let Some(max) = limits.max_resolution {
+            let Resolution { width, height } = self.resolution;
+            if width > max.width || height > max.height {
+                let scale = (max.width as f64 / width as f64).min(max.height as f64 / height as f64);
+                let even = |v: u32| ((v as f64 * scale) as u32 & !1).max(2);
+                self.resolution = Resolution::new(even(width), even(height));
+            }
+        }
+        self
+    }
+}
+
+// MARK: - StreamLimits
+
+/// Receiver-side ceilings on what a sender may stream, sent in `hello_ack`.
+///
+/// Requested configs above them are clamped (or rejected) before the session
+/// starts, and the receiver drops frames of senders that ignore the
+/// negotiated bitrate, and skips what it can of those above the frame rate
+/// (see [`FrameRateCap`](crate::FrameRateCap)).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct StreamLimits {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_bitrate_kbps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_resolution:   Option<Resolution>,
+    /// For e-ink and 30 Hz panels, where more frames are wasted decoding.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fps:          Option<u32>,
+}
+
+impl StreamLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_bitrate_kbps.is_none() && self.max_resolution.is_none() && self.max_fps.is_none()
+    }
+
+    pub fn max_bitrate_bps(&self) -> Option<u64> {
+        self.max_bitrate_kbps.map(|kbps| kbps as u64 * 1000)
+    }
+
+    /// Why `config` exceeds these limits, or `None` if it is within them.
+    pub fn violation(&self, config: &StreamConfig) -> Option<String> {
+        if let Some(max) = self.max_bitrate_kbps {
+            let kbps = config.max_bitrate_bps / 1000;
+            if kbps > max as u64 {
+                return Some(format!("bitrate {kbps} kbps exceeds {max} kbps"));
+            }
+        }
+        if let Some(max) = self.max_resolution {
+            let res = config.resolution;
+            if res.width > max.width || res.height > max.height {
+                return Some(format!("resolution {res} exceeds {max}"));
+            }
+        }
+        if let Some(max) = self.max_fps {
+            if config.target_fps > max {
+                return Some(format!("frame rate {} fps exceeds {max} fps", config.target_fps));
+            }
+        }
+        None
+    }
+}
+
+// MARK: - ColorSpace
+
+/// Quantisation range of the encoded luma/chroma samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorRange {
+    /// 16–235 (TV / studio range) — what hardware decoders assume by default.
+    #[default]
+    Limited,
+    /// 0–255 (PC range).
+    Full,
+}
+
+/// YCbCr matrix coefficients (and matching primaries) of the encoded stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMatrix {
+    Bt601,
+    #[default]
+    Bt709,
+}
+
+/// Colour description carried in [`StreamConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorSpace {
+    pub range: ColorRange,
+    pub matrix: ColorMatrix,
+}
+
+impl ColorSpace {
+    /// GStreamer `colorimetry` caps value, `range:matrix:transfer:primaries`
+    /// in `GstVideoColorimetry` enum order. The default is equivalent to `bt709`.
+    pub fn gst_colorimetry(self) -> String {
+        let range = match self.range {
+            ColorRange::Full => 1,
+            ColorRange::Limited => 2,
+        };
+        // Transfer is BT.709 (5) for both; primaries BT.709 (1) / SMPTE 170M (4).
+        let (matrix, primaries) = match self.matrix {
+            ColorMatrix::Bt709 => (3, 1),
+            ColorMatrix::Bt601 => (4, 4),
+        };
+        format!("{range}:{matrix}:5:{primaries}")
+    }
+
+    /// Parses `"<matrix>"` or `"<matrix>-<range>"`, e.g. `bt709`, `bt601-full`.
+    pub fn from_name(s: &str) -> Option<Self> {
+        let (matrix, range) = s.split_once('-').unwrap_or((s, "limited"));
+        let matrix = match matrix {
+            "bt601" => ColorMatrix::Bt601,
+            "bt709" => ColorMatrix::Bt709,
+            _ => return None,
+        };
+        let range = match range {
+            "limited" => ColorRange::Limited,
+            "full" => ColorRange::Full,
+            _ => return None,
+        };
+        Some(Self { range, matrix })
+    }
+}
+
+impl std::fmt::Display for ColorSpace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let matrix = match self.matrix {
+            ColorMatrix::Bt601 => "BT.601",
+            ColorMatrix::Bt709 => "BT.709",
+        };
+        let range = match self.range {
+            ColorRange::Limited => "limited",
+            ColorRange::Full => "full",
+        };
+        write!(f, "{matrix} {range}")
+    }
+}
+
+// MARK: - HDR
+
+/// GStreamer colorimetry for HDR10: limited range, BT.2020 matrix,
+/// SMPTE ST 2084 (PQ) transfer, BT.2020 primaries.
+pub const HDR_COLORIMETRY: &str = "2:6:14:7";
+
+/// SMPTE ST 2086 mastering display colour volume, in HEVC SEI units:
+/// chromaticities in 0.00002 steps, luminance in 0.0001 cd/m² steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MasteringDisplay {
+    /// Red, green, blue `[x, y]` chromaticities.
+    pub primaries: [[u16; 2]; 3],
+    pub white_point: [u16; 2],
+    pub max_luminance: u32,
+    pub min_luminance: u32,
+}
+
+impl MasteringDisplay {
+    /// From CIE 1931 chromaticities and luminance in cd/m² (as reported by
+    /// e.g. DXGI output descriptions).
+    pub fn from_cie(primaries: [[f32; 2]; 3], white_point: [f32; 2], max_nits: f32, min_nits: f32) -> Self {
+        let xy = |c: [f32; 2]| c.map(|v| (v * 50_000.0).round() as u16);
+        Self {
+            primaries: primaries.map(xy),
+            white_point: xy(white_point),
+            max_luminance: (max_nits * 10_000.0).round() as u32,
+            min_luminance: (min_nits * 10_000.0).round() as u32,
+        }
+    }
+}
+
+/// HDR10 static metadata carried in signaling with an HDR stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HdrMetadata {
+    #[serde(alias = "masteringDisplay")]
+    pub mastering_display: MasteringDisplay,
+    /// Maximum content light level, cd/m².
+    #[serde(alias = "maxCLL")]
+    pub max_cll: u16,
+    /// Maximum frame-average light level, cd/m².
+    #[serde(alias = "maxFALL")]
+    pub max_fall: u16,
+}
+
+impl HdrMetadata {
+    /// GStreamer `mastering-display-info` caps value
+    /// (`Rx:Ry:Gx:Gy:Bx:By:Wx:Wy:max:min`).
+    pub fn gst_mastering_display_info(&self) -> String {
+        let m = &self.mastering_display;
+        let [[rx, ry], [gx, gy], [bx, by]] = m.primaries;
+        let [wx, wy] = m.white_point;
+        format!(
+            "{rx}:{ry}:{gx}:{gy}:{bx}:{by}:{wx}:{wy}:{}:{}",
+            m.max_luminance, m.min_luminance
+        )
+    }
+
+    /// GStreamer `content-light-level` caps value (`maxCLL:maxFALL`).
+    pub fn gst_content_light_level(&self) -> String {
+        format!("{}:{}", self.max_cll, self.max_fall)
+    }
+}
+// MARK: - LatencyMode
+
+/// Receiver capability: honours [`StreamConfig::latency_mode`].
+pub const CAP_LATENCY_MODE: &str = "latency_mode";
+
+/// How long a [`LatencyMode::Quality`] receiver holds frames to smooth out
+/// network jitter.
+pub const QUALITY_JITTER_BUFFER: Duration = Duration::from_millis(50);
+
+/// Latency / quality trade-off of a session, picked by the sender
+/// (`DUALLINK_LATENCY_MODE`) and negotiated in `hello`.
+///
+/// | | `UltraLow` | `Quality` |
+/// |---|---|---|
+/// | encoder | zero-latency tune, no B-frames | 2 B-frames |
+/// | bitrate | as configured | +25 % |
+/// | receiver | one-frame sink queue, shed layered frames when behind | [`QUALITY_JITTER_BUFFER`], no shedding |
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyMode {
+    /// Interactive use: every frame shown as soon as it is decoded.
+    #[default]
+    UltraLow,
+    /// Presentations / video: a steadier, sharper picture for a few frames
+    /// of extra latency.
+    Quality,
+}
+
+impl LatencyMode {
+    pub const ALL: [Self; 2] = [Self::UltraLow, Self::Quality];
+
+    /// Parses the wire name (`ultra_low`, `quality`, case-insensitive).
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ultra_low" | "ultra-low" | "ultralow" | "low" => Some(Self::UltraLow),
+            "quality" => Some(Self::Quality),
+            _ => None,
+        }
+    }
+
+    /// The mode set in `DUALLINK_LATENCY_MODE`; ultra-low if unset or invalid.
+    pub fn from_env() -> Self {
+        let Ok(s) = std::env::var("DUALLINK_LATENCY_MODE") else { return Self::default() };
+        Self::from_name(&s).unwrap_or_else(|| {
+            tracing::warn!("Ignoring DUALLINK_LATENCY_MODE='{s}' — expected ultra_low or quality");
+            Self::default()
+        })
+    }
+
+    /// Human-readable name shown in the sender UIs.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::UltraLow => "Ultra-low latency",
+            Self::Quality => "Quality",
+        }
+    }
+
+    /// B-frames between reference frames.
+    pub fn b_frames(self) -> u32 {
+        match self {
+            Self::UltraLow => 0,
+            Self::Quality => 2,
+        }
+    }
+
+    /// How long the receiver holds each frame, on top of the sender's frame
+    /// spacing, before decoding it.
+    pub fn jitter_buffer(self) -> Duration {
+        match self {
+            Self::UltraLow => Duration::ZERO,
+            Self::Quality => QUALITY_JITTER_BUFFER,
+        }
+    }
+
+    /// Decoded frames queued in front of the receiver's video sink.
+    pub fn sink_queue(self) -> u32 {
+        match self {
+            Self::UltraLow => 1,
+            Self::Quality => 3,
+        }
+    }
+
+    /// Bitrate to ask for in this mode given the configured `bps`.
+    pub fn scale_bitrate(self, bps: u64) -> u64 {
+        match self {
+            Self::UltraLow => bps,
+            Self::Quality => bps + bps / 4,
+        }
+    }
+}
+
+// MARK: - QualityPreset
+
+/// Named quality presets selectable from the sender UIs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityPreset {
+    /// Static text / code: lower fps, high bitrate, long GOP — crisp glyphs.
+    TextSharp,
+    /// Video / scrolling / games: full fps, short GOP.
+    VideoSmooth,
+    /// Laptop on battery: low fps and bitrate, cheapest encoder settings.
+    BatterySaver,
+}
+
+/// How the sender should bias encoder-specific tuning knobs
+/// (x264 `speed-preset`, VA-API `quality-level`, NVENC `preset`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncoderTune {
+    Quality,
+    LowLatency,
+    LowPower,
+}
+
+/// Encoder settings a [`QualityPreset`] maps to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresetParams {
+    pub target_fps: u32,
+    pub max_bitrate_bps: u64,
+    /// Keyframe interval (GOP length) in frames.
+    pub keyframe_interval: u32,
+    pub tune: EncoderTune,
+}
+
+impl QualityPreset {
+    pub const ALL: [Self; 3] = [Self::TextSharp, Self::VideoSmooth, Self::BatterySaver];
+
+    pub fn params(self) -> PresetParams {
+        match self {
+            Self::TextSharp => PresetParams {
+                target_fps: 30,
+                max_bitrate_bps: 12_000_000,
+                keyframe_interval: 120,
+                tune: EncoderTune::Quality,
+            },
+            Self::VideoSmooth => PresetParams {
+                target_fps: 60,
+                max_bitrate_bps: 15_000_000,
+                keyframe_interval: 60,
+                tune: EncoderTune::LowLatency,
+            },
+            Self::BatterySaver => PresetParams {
+                target_fps: 30,
+                max_bitrate_bps: 4_000_000,
+                keyframe_interval: 90,
+                tune: EncoderTune::LowPower,
+            },
+        }
+    }
+
+    /// Human-readable name shown in the sender UIs.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::TextSharp => "Text sharp",
+            Self::VideoSmooth => "Video smooth",
+            Self::BatterySaver => "Battery saver",
+        }
+    }
+
+    /// Parses the wire name (`text_sharp`, `video_smooth`, `battery_saver`).
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "text_sharp" => Some(Self::TextSharp),
+            "video_smooth" => Some(Self::VideoSmooth),
+            "battery_saver" => Some(Self::BatterySaver),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::StreamConfig;
+    use super::{
+        ColorMatrix, ColorRange, ColorSpace, HdrMetadata, LatencyMode, MasteringDisplay, QualityPreset,
+        StreamConfig, StreamLimits, CAP_H264_444, CAP_HEVC_MAIN10, CAP_LATENCY_MODE, HDR_COLORIMETRY,
+    };
+    use crate::types::{Resolution, VideoCodec};
+
+    #[test]
+    fn clamps_to_receiver_limits() {
+        let limits =
+            StreamLimits { max_bitrate_kbps: Some(20_000), max_resolution: Some(Resolution::FHD), max_fps: Some(30) };
+        let cfg = StreamConfig { resolution: Resolution::UHD, max_bitrate_bps: 50_000_000, ..StreamConfig::default() };
+        assert!(limits.violation(&cfg).is_some());
+        let fast = StreamConfig { target_fps: 60, ..cfg.clone().clamp_to(&limits) };
+        assert_eq!(limits.violation(&fast).as_deref(), Some("frame rate 60 fps exceeds 30 fps"));
+
+        let clamped = cfg.clamp_to(&limits);
+        assert_eq!(clamped.resolution, Resolution::FHD);
+        assert_eq!(clamped.max_bitrate_bps, 20_000_000);
+        assert_eq!(clamped.target_fps, 30);
+        assert_eq!(limits.violation(&clamped), None);
+
+        // Aspect ratio is kept when only one side is too large.
+        let tall = StreamConfig { resolution: Resolution::new(1080, 1920), ..StreamConfig::default() };
+        assert_eq!(tall.clamp_to(&limits).resolution, Resolution::new(606, 1080));
+        assert!(StreamLimits::default().violation(&clamped).is_none());
+    }
 
     #[test]
     fn deserializes_camel_case_fields() {
@@ -86,4 +585,103 @@ mod tests {
         assert_eq!(cfg.max_bitrate_bps, 8_000_000);
         assert!(!cfg.low_latency_mode);
     }
+
+    #[test]
+    fn quality_preset_round_trips_and_applies() {
+        let cfg = StreamConfig::default().with_preset(QualityPreset::BatterySaver);
+        assert_eq!(cfg.target_fps, 30);
+        assert_eq!(cfg.max_bitrate_bps, 4_000_000);
+
+        let json = serde_json::to_string(&cfg).unwrap();
+        assert!(json.contains(r#""quality_preset":"battery_saver""#));
+
+        let camel = r#"{"targetFPS": 60, "qualityPreset": "video_smooth"}"#;
+        let parsed: StreamConfig = serde_json::from_str(camel).unwrap();
+        assert_eq!(parsed.quality_preset, Some(QualityPreset::VideoSmooth));
+
+        // Absent field stays None and is not serialized.
+        let plain = serde_json::to_string(&StreamConfig::default()).unwrap();
+        assert!(!plain.contains("quality_preset"));
+    }
+
+    #[test]
+    fn lossless_requires_receiver_capability() {
+        let requested = StreamConfig { lossless: true, ..Default::default() };
+
+        let refused = requested.clone().negotiate(&[]);
+        assert!(!refused.lossless);
+
+        let accepted = requested.negotiate(&[CAP_H264_444.to_owned()]);
+        assert!(accepted.lossless);
+    }
+
+    #[test]
+    fn color_space_maps_to_gst_colorimetry() {
+        // Default must match GStreamer's "bt709" so legacy peers are unaffected.
+        let default = StreamConfig::default();
+        assert_eq!(default.color.gst_colorimetry(), "2:3:5:1");
+
+        let full_601 = ColorSpace { range: ColorRange::Full, matrix: ColorMatrix::Bt601 };
+        assert_eq!(full_601.gst_colorimetry(), "1:4:5:4");
+        assert_eq!(ColorSpace::from_name("bt601-full"), Some(full_601));
+        assert_eq!(ColorSpace::from_name("bt709"), Some(ColorSpace::default()));
+        assert_eq!(ColorSpace::from_name("srgb"), None);
+
+        // Configs from senders that predate the field fall back to the default.
+        let legacy: StreamConfig = serde_json::from_str(r#"{"targetFPS": 30}"#).unwrap();
+        assert_eq!(legacy.color, ColorSpace::default());
+    }
+
+    #[test]
+    fn hdr_falls_back_to_sdr_without_main10() {
+        let hdr = HdrMetadata {
+            // BT.2020 primaries, D65, 1000 / 0.005 nits.
+            mastering_display: MasteringDisplay::from_cie(
+                [[0.708, 0.292], [0.170, 0.797], [0.131, 0.046]],
+                [0.3127, 0.3290],
+                1000.0,
+                0.005,
+            ),
+            max_cll: 1000,
+            max_fall: 400,
+        };
+        assert_eq!(
+            hdr.gst_mastering_display_info(),
+            "35400:14600:8500:39850:6550:2300:15635:16450:10000000:50"
+        );
+        assert_eq!(hdr.gst_content_light_level(), "1000:400");
+
+        let requested = StreamConfig { codec: VideoCodec::H265, hdr: Some(hdr), ..Default::default() };
+        assert_eq!(requested.gst_colorimetry(), HDR_COLORIMETRY);
+
+        let sdr = requested.clone().negotiate(&[]);
+        assert_eq!(sdr.hdr, None);
+        assert_eq!(sdr.codec, VideoCodec::H264);
+
+        let kept = requested.negotiate(&[CAP_HEVC_MAIN10.to_owned()]);
+        assert_eq!(kept.hdr, Some(hdr));
+    }
+
+    #[test]
+    fn quality_mode_needs_receiver_support() {
+        let requested = StreamConfig {
+            latency_mode: LatencyMode::Quality,
+            low_latency_mode: false,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&requested).unwrap();
+        assert_eq!(json["latency_mode"], "quality");
+
+        let legacy = requested.clone().negotiate(&[]);
+        assert_eq!(legacy.latency_mode, LatencyMode::UltraLow);
+        assert!(legacy.low_latency_mode);
+        let kept = requested.negotiate(&[CAP_LATENCY_MODE.to_owned()]);
+        assert_eq!(kept.latency_mode, LatencyMode::Quality);
+
+        // Quality asks for a quarter more bitrate than the preset's.
+        let smooth = kept.with_preset(QualityPreset::VideoSmooth);
+        assert_eq!(smooth.max_bitrate_bps, 18_750_000);
+        assert_eq!(LatencyMode::from_name("Ultra-Low"), Some(LatencyMode::UltraLow));
+        assert_eq!(LatencyMode::from_name("balanced"), None);
+    }
 }
@@ -0,0 +1,321 @@
+//! Diagnostics bundle for bug reports.
+//!
+//! One JSON file with what a bug report usually needs five commands for:
+//! GStreamer decoder availability, the selected decoder, the VA-API driver,
+//! network interfaces and — when exported from a running receiver — its
+//! recent log and session stats.
+//!
+//! [`DiagnosticsReport::collect`] gathers the system side; the decoder crate
+//! adds the GStreamer side (`duallink_decoder::fill_diagnostics`) and the
+//! GUI its log and stats before [`DiagnosticsReport::write_to`] saves it as
+//! `duallink-diagnostics-<unix time>.json`. The receivers write one with
+//! `--diagnose` (into the current directory) or the GUI's "Export
+//! diagnostics" button (into the home directory).
+//!
+//! No MAC addresses, PINs or certificate material are included.
+//!
+//! [`ProbeReport`] is the decoder part on its own, as returned by
+//! `duallink_decoder::DecoderFactory::probe_report` and printed by
+//! `duallink-receiver probe`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::network::NetworkKind;
+
+// MARK: - Report
+
+/// Everything in a diagnostics bundle.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsReport {
+    /// Unix time the report was made, in seconds.
+    pub generated_at:      u64,
+    /// DualLink version of the binary that made it.
+    pub version:           String,
+    /// OS name and kernel release.
+    pub os:                String,
+    /// GStreamer version, if it initialised.
+    pub gstreamer:         Option<String>,
+    /// Every decoder the receiver knows of, installed or not.
+    pub decoders:          Vec<DecoderEntry>,
+    /// H.264 decoder the probe picks (or the running session's).
+    pub selected_decoder:  Option<String>,
+    pub vaapi:             VaapiInfo,
+    pub interfaces:        Vec<InterfaceInfo>,
+    /// Recent receiver log lines, oldest first (empty from `--diagnose`).
+    pub logs:              Vec<String>,
+    /// Session stats by name (empty from `--diagnose`).
+    pub session:           BTreeMap<String, String>,
+}
+
+/// One decoder element of the probe lists.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecoderEntry {
+    /// `"h264"` or `"h265"`.
+    pub codec:        String,
+    pub element:      String,
+    pub label:        String,
+    pub installed:    bool,
+    /// Cached benchmark result in ms per frame, if measured.
+    pub benchmark_ms: Option<f64>,
+}
+
+/// What VA-API will load.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaapiInfo {
+    /// `LIBVA_DRIVER_NAME`, if set.
+    pub driver_override: Option<String>,
+    /// DRM render nodes (`/dev/dri/renderD*`).
+    pub render_nodes:    Vec<String>,
+    /// `vainfo` output; `None` if it is not installed.
+    pub vainfo:          Option<String>,
+    /// Driver VA-API loads, from `vainfo`'s `Driver version` line.
+    pub driver:          Option<String>,
+}
+
+impl VaapiInfo {
+    /// What VA-API will load on this machine; runs `vainfo`.
+    pub fn collect() -> Self {
+        vaapi_info()
+    }
+}
+
+/// A network interface and its addresses.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InterfaceInfo {
+    pub name:      String,
+    pub kind:      NetworkKind,
+    /// Kernel operstate (`up`, `down`, `unknown`, …).
+    pub state:     String,
+    pub mtu:       Option<u32>,
+    /// Addresses with prefix length, e.g. `192.168.1.7/24`.
+    pub addresses: Vec<String>,
+}
+
+// MARK: - Probe report
+
+/// What the decoder probe finds: GStreamer, every known decoder element
+/// and the ones a new session would use.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeReport {
+    /// GStreamer version; `None` if it did not initialise.
+    pub gstreamer:        Option<String>,
+    /// Why GStreamer did not initialise.
+    pub gstreamer_error:  Option<String>,
+    /// Every known decoder, H.264 then H.265, each in probe order.
+    pub candidates:       Vec<DecoderEntry>,
+    /// VA-API driver, if GStreamer has a VA-API plugin and a driver loads.
+    pub va_driver:        Option<String>,
+    /// H.264 decoder a new session would use.
+    pub recommended:      Option<String>,
+    /// H.265 decoder a new session would use.
+    pub recommended_hevc: Option<String>,
+}
+
+impl ProbeReport {
+    /// The candidates whose plugin is installed.
+    pub fn installed(&self) -> impl Iterator<Item = &DecoderEntry> {
+        self.candidates.iter().filter(|c| c.installed)
+    }
+}
+
+impl fmt::Display for ProbeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.gstreamer, &self.gstreamer_error) {
+            (Some(version), _) => writeln!(f, "GStreamer:  {version}")?,
+            (None, Some(error)) => writeln!(f, "GStreamer:  unavailable ({error})")?,
+            (None, None) => writeln!(f, "GStreamer:  unavailable")?,
+        }
+        writeln!(f, "VA-API:     {}", self.va_driver.as_deref().unwrap_or("no driver"))?;
+        for c in &self.candidates {
+            let mark = if c.installed { "✓" } else { "✗" };
+            write!(f, "  {mark} {:<5} {:<16} {}", c.codec, c.element, c.label)?;
+            match c.benchmark_ms {
+                Some(ms) => writeln!(f, " ({ms:.1} ms/frame)")?,
+                None => writeln!(f)?,
+            }
+        }
+        writeln!(f, "H.264:      {}", self.recommended.as_deref().unwrap_or("none installed"))?;
+        write!(f, "H.265:      {}", self.recommended_hevc.as_deref().unwrap_or("none installed"))
+    }
+}
+
+impl DiagnosticsReport {
+    /// The system side of a report: OS, VA-API and network interfaces.
+    pub fn collect(version: &str) -> Self {
+        Self {
+            generated_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+            version: version.to_owned(),
+            os: os_description(),
+            vaapi: vaapi_info(),
+            interfaces: interfaces(),
+            ..Self::default()
+        }
+    }
+
+    /// `duallink-diagnostics-<generated_at>.json`.
+    pub fn file_name(&self) -> String {
+        format!("duallink-diagnostics-{}.json", self.generated_at)
+    }
+
+    /// Write the report into `dir`; returns the file's path.
+    pub fn write_to(&self, dir: &Path) -> std::io::Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(self.file_name());
+        std::fs::write(&path, serde_json::to_vec_pretty(self)?)?;
+        Ok(path)
+    }
+}
+
+/// The user's home directory, where the GUI saves its bundles.
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")).map(PathBuf::from)
+}
+
+// MARK: - Collection
+
+/// Stdout of `cmd`, if it ran and succeeded.
+fn run(cmd: &str, args: &[&str]) -> Option<String> {
+    std::process::Command::new(cmd)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim_end().to_owned())
+}
+
+fn os_description() -> String {
+    let name = std::fs::read_to_string("/etc/os-release")
+        .ok()
+        .and_then(|s| os_release_name(&s))
+        .unwrap_or_else(|| std::env::consts::OS.to_owned());
+    match run("uname", &["-r"]) {
+        Some(kernel) => format!("{name} ({} {kernel})", std::env::consts::ARCH),
+        None => format!("{name} ({})", std::env::consts::ARCH),
+    }
+}
+
+/// `PRETTY_NAME` (or `NAME`) from `/etc/os-release`.
+fn os_release_name(text: &str) -> Option<String> {
+    let value = |key: &str| {
+        text.lines()
+            .find_map(|l| l.strip_prefix(key)?.strip_prefix('='))
+            .map(|v| v.trim().trim_matches('"').to_owned())
+    };
+    value("PRETTY_NAME").or_else(|| value("NAME"))
+}
+
+fn vaapi_info() -> VaapiInfo {
+    let mut render_nodes: Vec<String> = std::fs::read_dir("/dev/dri")
+        .map(|dir| {
+            dir.flatten()
+                .map(|e| e.path())
+                .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("renderD")))
+                .map(|p| p.display().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    render_nodes.sort();
+    let vainfo = run("vainfo", &[]);
+    VaapiInfo {
+        driver_override: std::env::var("LIBVA_DRIVER_NAME").ok(),
+        render_nodes,
+        driver: vainfo.as_deref().and_then(va_driver),
+        vainfo,
+    }
+}
+
+/// The driver from `vainfo` output, e.g. `vainfo: Driver version: Mesa
+/// Gallium driver 24.0.5 for AMD Radeon 680M`.
+fn va_driver(vainfo: &str) -> Option<String> {
+    vainfo
+        .lines()
+        .find_map(|l| l.split_once("Driver version:"))
+        .map(|(_, driver)| driver.trim().to_owned())
+        .filter(|driver| !driver.is_empty())
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn interfaces() -> Vec<InterfaceInfo> {
+    let net = Path::new("/sys/class/net");
+    let Ok(dir) = std::fs::read_dir(net) else { return Vec::new() };
+    let addresses = run("ip", &["-o", "addr", "show"]).map(|s| parse_ip_addr(&s)).unwrap_or_default();
+    let mut list: Vec<InterfaceInfo> = dir
+        .flatten()
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|name| name != "lo")
+        .map(|name| {
+            let read = |file: &str| std::fs::read_to_string(net.join(&name).join(file)).ok();
+            InterfaceInfo {
+                kind:      crate::network::interface_kind(&name),
+                state:     read("operstate").map_or_else(|| "unknown".to_owned(), |s| s.trim().to_owned()),
+                mtu:       read("mtu").and_then(|s| s.trim().parse().ok()),
+                addresses: addresses.get(&name).cloned().unwrap_or_default(),
+                name,
+            }
+        })
+        .collect();
+    list.sort_by(|a, b| a.name.cmp(&b.name));
+    list
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn interfaces() -> Vec<InterfaceInfo> {
+    Vec::new()
+}
+
+/// Addresses by interface from `ip -o addr show`, e.g.
+/// `2: wlp3s0    inet 192.168.1.7/24 brd 192.168.1.255 scope global wlp3s0`.
+#[cfg(any(target_os = "linux", test))]
+fn parse_ip_addr(output: &str) -> BTreeMap<String, Vec<String>> {
+    let mut map: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for line in output.lines() {
+        let mut words = line.split_whitespace();
+        let (Some(_), Some(name), Some("inet" | "inet6"), Some(addr)) =
+            (words.next(), words.next(), words.next(), words.next())
+        else {
+            continue;
+        };
+        map.entry(name.to_owned()).or_default().push(addr.to_owned());
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_os_release_and_ip_addr() {
+        let os = "NAME=\"Fedora Linux\"\nPRETTY_NAME=\"Fedora Linux 40 (Workstation Edition)\"\n";
+        assert_eq!(os_release_name(os).as_deref(), Some("Fedora Linux 40 (Workstation Edition)"));
+        assert_eq!(os_release_name("NAME=Arch\n").as_deref(), Some("Arch"));
+
+        let ip = "1: lo    inet 127.0.0.1/8 scope host lo\\       valid_lft forever\n\
+                  2: wlp3s0    inet 192.168.1.7/24 brd 192.168.1.255 scope global wlp3s0\n\
+                  2: wlp3s0    inet6 fe80::1/64 scope link\n\
+                  garbage";
+        let map = parse_ip_addr(ip);
+        assert_eq!(map["wlp3s0"], ["192.168.1.7/24", "fe80::1/64"]);
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn parses_the_va_driver() {
+        let vainfo = "Trying display: wayland\n\
+                      vainfo: VA-API version: 1.20 (libva 2.20.1)\n\
+                      vainfo: Driver version: Mesa Gallium driver 24.0.5 for AMD Radeon 680M\n\
+                      vainfo: Supported profile and entrypoints";
+        assert_eq!(va_driver(vainfo).as_deref(), Some("Mesa Gallium driver 24.0.5 for AMD Radeon 680M"));
+        assert_eq!(va_driver("vainfo: Driver version: \n"), None);
+    }
+}
@@ -0,0 +1,169 @@
+//! Presenting frames of several display streams together.
+//!
+//! Every display stream is decoded and shown on its own, so with two
+//! receiver displays side by side a window dragged across the boundary
+//! tears: one half is a frame or two ahead of the other. Frame timestamps
+//! are mapped onto one receiver timeline shared by all displays (see
+//! [`crate::clock`]), so frames the sender captured together carry nearby
+//! timestamps. [`DisplaySync`] holds a display's decoded frame until every
+//! other display streaming at the time has a frame within
+//! [`SYNC_TOLERANCE`] of it ready too, for at most the configured wait.
+//!
+//! The wait is set by `DUALLINK_DISPLAY_SYNC_MS` or the saved settings'
+//! `displaySyncMs` ([`DEFAULT_SYNC_WAIT`] if neither is set, `0` = off).
+//! Displays that showed nothing recently — a static screen with adaptive
+//! frame rate, a paused stream — are not waited for.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::ReceiverSettings;
+
+/// Longest a frame waits for the other displays unless configured otherwise.
+pub const DEFAULT_SYNC_WAIT: Duration = Duration::from_millis(16);
+
+/// Frames this close together count as the same moment (half a 60 fps frame).
+pub const SYNC_TOLERANCE: Duration = Duration::from_millis(8);
+
+/// A display that had no frame ready for this long is not waited for.
+const IDLE_AFTER: Duration = Duration::from_millis(100);
+
+// MARK: - DisplaySync
+
+/// The latest frame each display has ready.
+#[derive(Debug, Clone, Copy)]
+struct Ready {
+    timestamp_us: u64,
+    at:           Instant,
+}
+
+/// Aligns presentation across the receiver's display streams.
+#[derive(Debug)]
+pub struct DisplaySync {
+    max_wait: Duration,
+    ready:    Mutex<BTreeMap<u8, Ready>>,
+    changed:  Condvar,
+}
+
+impl DisplaySync {
+    /// Sync where frames wait at most `max_wait` for the other displays.
+    pub fn new(max_wait: Duration) -> Arc<Self> {
+        Arc::new(Self { max_wait, ready: Mutex::new(BTreeMap::new()), changed: Condvar::new() })
+    }
+
+    /// The configured wait: `DUALLINK_DISPLAY_SYNC_MS`, else the settings'
+    /// `displaySyncMs`, else [`DEFAULT_SYNC_WAIT`]. `ZERO` = sync off.
+    pub fn configured_wait(settings: &ReceiverSettings) -> Duration {
+        let env = std::env::var("DUALLINK_DISPLAY_SYNC_MS").ok().and_then(|v| match v.trim().parse() {
+            Ok(ms) => Some(ms),
+            Err(_) => {
+                tracing::warn!("Ignoring DUALLINK_DISPLAY_SYNC_MS='{v}' — expected milliseconds");
+                None
+            }
+        });
+        env.or(settings.display_sync_ms).map_or(DEFAULT_SYNC_WAIT, Duration::from_millis)
+    }
+
+    pub fn max_wait(&self) -> Duration {
+        self.max_wait
+    }
+
+    /// Take part as `display` until the returned member is dropped.
+    pub fn join(self: &Arc<Self>, display: u8) -> SyncMember {
+        SyncMember { sync: Arc::clone(self), display }
+    }
+
+    /// Mark `display`'s frame at `timestamp_us` ready at `start` and wait
+    /// until it may be shown. Returns how long it waited.
+    fn present(&self, display: u8, timestamp_us: u64, start: Instant) -> Duration {
+        let mut ready = self.ready.lock().unwrap();
+        ready.insert(display, Ready { timestamp_us, at: start });
+        self.changed.notify_all();
+        let deadline = start + self.max_wait;
+        let tolerance = SYNC_TOLERANCE.as_micros() as u64;
+        loop {
+            let now = Instant::now();
+            let behind = ready.iter().any(|(&other, r)| {
+                other != display
+                    && now.saturating_duration_since(r.at) < IDLE_AFTER
+                    && r.timestamp_us.saturating_add(tolerance) < timestamp_us
+            });
+            if !behind || now >= deadline {
+                return now.saturating_duration_since(start);
+            }
+            ready = self.changed.wait_timeout(ready, deadline - now).unwrap().0;
+        }
+    }
+
+    fn leave(&self, display: u8) {
+        self.ready.lock().unwrap().remove(&display);
+        self.changed.notify_all();
+    }
+}
+
+// MARK: - SyncMember
+
+/// One display's part in a [`DisplaySync`]; leaves it when dropped.
+#[derive(Debug)]
+pub struct SyncMember {
+    sync:    Arc<DisplaySync>,
+    display: u8,
+}
+
+impl SyncMember {
+    /// Block until the decoded frame at `timestamp_us` (receiver timeline)
+    /// may be shown. Returns how long it waited.
+    pub fn present(&self, timestamp_us: u64) -> Duration {
+        if self.sync.max_wait.is_zero() {
+            return Duration::ZERO;
+        }
+        self.sync.present(self.display, timestamp_us, Instant::now())
+    }
+}
+
+impl Drop for SyncMember {
+    fn drop(&mut self) {
+        self.sync.leave(self.display);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn no_wait(waited: Duration) -> bool {
+        waited < Duration::from_millis(10)
+    }
+
+    #[test]
+    fn a_display_ahead_waits_for_the_other() {
+        let sync = DisplaySync::new(Duration::from_millis(500));
+        let (a, b) = (sync.join(0), sync.join(1));
+        // Nothing from display 1 yet: display 0 doesn't wait.
+        assert!(no_wait(a.present(1_000_000)));
+        b.present(1_000_000);
+
+        let ahead = thread::spawn(move || (a.present(1_016_000), a));
+        thread::sleep(Duration::from_millis(50));
+        // Within the tolerance of display 0's frame: both go.
+        assert!(no_wait(b.present(1_020_000)));
+        let (waited, a) = ahead.join().unwrap();
+        assert!(waited >= Duration::from_millis(40), "{waited:?}");
+
+        // A display that left is not waited for.
+        drop(b);
+        assert!(no_wait(a.present(1_100_000)));
+    }
+
+    #[test]
+    fn waits_are_bounded() {
+        let sync = DisplaySync::new(Duration::from_millis(20));
+        let (a, b) = (sync.join(0), sync.join(1));
+        b.present(1_000_000);
+        let waited = a.present(2_000_000);
+        assert!(waited >= Duration::from_millis(20) && waited < Duration::from_millis(400), "{waited:?}");
+        assert_eq!(DisplaySync::new(Duration::ZERO).join(0).present(5), Duration::ZERO);
+    }
+}
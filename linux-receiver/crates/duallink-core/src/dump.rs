@@ -0,0 +1,153 @@
+//! Frame dump for decoder debugging.
+//!
+//! With `DUALLINK_DUMP=SECS` set, every display session writes the
+//! assembled access units of its first `SECS` seconds — Annex-B, exactly
+//! as fed to the decoder — to `stream.h264` (or `.h265`), playable with
+//! `ffplay` or `gst-launch-1.0 filesrc ! h264parse ! avdec_h264 ! …`.
+//! `DUALLINK_DUMP=SECS,png` also saves each decoded frame as a PNG next to
+//! it, so artifacts can be told apart from a decoder that renders the same
+//! stream fine offline.
+//!
+//! Each session gets its own directory,
+//! `duallink-dump-<unix time>-display<N>`, under `DUALLINK_DUMP_DIR` (the
+//! current directory by default).
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{EncodedFrame, VideoCodec};
+
+// MARK: - DumpSettings
+
+/// What `DUALLINK_DUMP` asks for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DumpSettings {
+    /// How much of each session to dump, from its first frame.
+    pub duration: Duration,
+    /// Also save decoded frames as PNGs.
+    pub decoded:  bool,
+    /// Where the session directories go.
+    pub dir:      PathBuf,
+}
+
+impl DumpSettings {
+    /// Parse `"SECS"` / `"SECS,png"`.
+    pub fn parse(spec: &str, dir: PathBuf) -> Option<Self> {
+        let mut parts = spec.split(',').map(str::trim);
+        let secs = parts.next()?.parse::<f64>().ok().filter(|s| *s > 0.0)?;
+        let mut decoded = false;
+        for part in parts {
+            match part.to_ascii_lowercase().as_str() {
+                "png" => decoded = true,
+                _ => return None,
+            }
+        }
+        Some(Self { duration: Duration::from_secs_f64(secs), decoded, dir })
+    }
+
+    /// The configured dump; `None` if `DUALLINK_DUMP` is unset or invalid.
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var("DUALLINK_DUMP").ok()?;
+        let dir = std::env::var_os("DUALLINK_DUMP_DIR").filter(|d| !d.is_empty()).unwrap_or_else(|| ".".into());
+        let dir = PathBuf::from(dir);
+        let settings = Self::parse(&spec, dir);
+        if settings.is_none() {
+            tracing::warn!("Ignoring DUALLINK_DUMP='{spec}' — expected seconds, optionally followed by ',png'");
+        }
+        settings
+    }
+}
+
+// MARK: - StreamDump
+
+/// The access units of one session, written as they are decoded.
+pub struct StreamDump {
+    dir:      PathBuf,
+    file:     BufWriter<File>,
+    duration: Duration,
+    /// First frame's arrival; `None` before it.
+    started:  Option<Instant>,
+    frames:   u64,
+    bytes:    u64,
+}
+
+impl StreamDump {
+    /// Create the session directory for `display` and its stream file.
+    pub fn create(settings: &DumpSettings, display: u8, codec: VideoCodec) -> std::io::Result<Self> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let dir = settings.dir.join(format!("duallink-dump-{now}-display{display}"));
+        std::fs::create_dir_all(&dir)?;
+        let extension = match codec {
+            VideoCodec::H264 => "h264",
+            VideoCodec::H265 => "h265",
+        };
+        let file = BufWriter::new(File::create(dir.join(format!("stream.{extension}")))?);
+        Ok(Self { dir, file, duration: settings.duration, started: None, frames: 0, bytes: 0 })
+    }
+
+    /// The session directory, where decoded PNGs go too.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Frames and bytes written so far.
+    pub fn written(&self) -> (u64, u64) {
+        (self.frames, self.bytes)
+    }
+
+    /// Append `frame` if the dump still runs at `now`. Returns `false` once
+    /// the duration is over; the file is flushed then.
+    pub fn write(&mut self, frame: &EncodedFrame, now: Instant) -> std::io::Result<bool> {
+        let started = *self.started.get_or_insert(now);
+        if now.saturating_duration_since(started) >= self.duration {
+            self.file.flush()?;
+            return Ok(false);
+        }
+        self.file.write_all(&frame.data)?;
+        self.frames += 1;
+        self.bytes += frame.data.len() as u64;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dump_spec() {
+        let dir = PathBuf::from("/tmp");
+        let settings = DumpSettings::parse("5", dir.clone()).unwrap();
+        assert_eq!((settings.duration, settings.decoded), (Duration::from_secs(5), false));
+        assert_eq!(DumpSettings::parse("2.5, PNG", dir.clone()).map(|s| s.decoded), Some(true));
+        assert_eq!(DumpSettings::parse("0", dir.clone()), None);
+        assert_eq!(DumpSettings::parse("5,jpeg", dir), None);
+    }
+
+    #[test]
+    fn stops_after_the_duration() {
+        let root = std::env::temp_dir().join(format!("duallink-dump-test-{}", std::process::id()));
+        let settings = DumpSettings { duration: Duration::from_secs(1), decoded: false, dir: root.clone() };
+        let mut dump = StreamDump::create(&settings, 1, VideoCodec::H264).unwrap();
+        let frame = |data: &'static [u8]| EncodedFrame {
+            data:           bytes::Bytes::from_static(data),
+            timestamp_us:   0,
+            is_keyframe:    false,
+            codec:          VideoCodec::H264,
+            temporal_layer: 0,
+            partial:        false,
+        };
+        let t0 = Instant::now();
+        assert!(dump.write(&frame(b"\0\0\0\x01\x65"), t0).unwrap());
+        assert!(dump.write(&frame(b"\0\0\0\x01\x41"), t0 + Duration::from_millis(900)).unwrap());
+        assert!(!dump.write(&frame(b"\0\0\0\x01\x41"), t0 + Duration::from_secs(1)).unwrap());
+        assert_eq!(dump.written(), (2, 10));
+
+        let stream = std::fs::read(dump.dir().join("stream.h264")).unwrap();
+        assert_eq!(stream, b"\0\0\0\x01\x65\0\0\0\x01\x41");
+        assert!(dump.dir().file_name().unwrap().to_string_lossy().ends_with("-display1"));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,131 @@
+//! Duplicate decoded frames.
+//!
+//! VA-API decoders under load sometimes hand the same picture downstream
+//! twice. Each duplicate costs a render, and frame-rate counters that count
+//! pushes or sink buffers report 60 fps for a stream that shows 30 distinct
+//! pictures. The display outputs (see `duallink-decoder`) pass every decoded
+//! buffer through a [`DuplicateFilter`] in front of the sink: a buffer with
+//! the previous one's PTS, or — for buffers in system memory — the previous
+//! one's [`frame_hash`], is counted and dropped. The stats card shows the
+//! rate of the unique frames that remain.
+
+use std::time::{Duration, Instant};
+
+/// Bytes hashed per sample in [`frame_hash`].
+const SAMPLE_LEN: usize = 64;
+
+/// Number of evenly spaced samples hashed by [`frame_hash`].
+const SAMPLES: usize = 64;
+
+/// A cheap fingerprint of a decoded frame: FNV-1a over the length and
+/// [`SAMPLES`] evenly spaced runs of bytes. Good enough to tell a repeated
+/// buffer from a new picture; not a content checksum.
+pub fn frame_hash(data: &[u8]) -> u64 {
+    const PRIME: u64 = 0x100_0000_01b3;
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in (data.len() as u64).to_le_bytes() {
+        hash = (hash ^ byte as u64).wrapping_mul(PRIME);
+    }
+    let stride = (data.len() / SAMPLES).max(SAMPLE_LEN);
+    for start in (0..data.len()).step_by(stride) {
+        for &byte in &data[start..(start + SAMPLE_LEN).min(data.len())] {
+            hash = (hash ^ byte as u64).wrapping_mul(PRIME);
+        }
+    }
+    hash
+}
+
+// MARK: - DuplicateFilter
+
+/// Remembers the last decoded buffer and counts the ones that repeat it.
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateFilter {
+    last_pts:   Option<u64>,
+    last_hash:  Option<u64>,
+    unique:     u64,
+    duplicates: u64,
+}
+
+impl DuplicateFilter {
+    /// Feed a decoded buffer's PTS (ns) and content hash, if known. Returns
+    /// `true` if it repeats the previous buffer and should be dropped.
+    pub fn is_duplicate(&mut self, pts: Option<u64>, hash: Option<u64>) -> bool {
+        let same_pts = pts.is_some() && pts == self.last_pts;
+        let same_hash = hash.is_some() && hash == self.last_hash;
+        if same_pts || same_hash {
+            self.duplicates += 1;
+            return true;
+        }
+        self.last_pts = pts;
+        self.last_hash = hash;
+        self.unique += 1;
+        false
+    }
+
+    /// Buffers that went through.
+    pub fn unique(&self) -> u64 {
+        self.unique
+    }
+
+    /// Buffers dropped as duplicates.
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+}
+
+// MARK: - RateMeter
+
+/// Turns a cumulative frame counter into a rate over roughly the last
+/// second.
+#[derive(Debug, Clone, Default)]
+pub struct RateMeter {
+    since: Option<(Instant, u64)>,
+    rate:  f64,
+}
+
+impl RateMeter {
+    /// Feed the counter's current value; returns the latest rate.
+    pub fn update(&mut self, count: u64) -> f64 {
+        let now = Instant::now();
+        match self.since {
+            Some((_, base)) if count < base => self.since = Some((now, count)),
+            Some((at, base)) if now.duration_since(at) >= Duration::from_secs(1) => {
+                self.rate = (count - base) as f64 / now.duration_since(at).as_secs_f64();
+                self.since = Some((now, count));
+            }
+            Some(_) => {}
+            None => self.since = Some((now, count)),
+        }
+        self.rate
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_pts_or_content_is_dropped() {
+        let a = vec![1u8; 1920 * 1080];
+        let mut b = a.clone();
+        b[1920 * 540] = 2;
+        assert_eq!(frame_hash(&a), frame_hash(&a.clone()));
+        assert_ne!(frame_hash(&a), frame_hash(&a[..a.len() - 1]));
+
+        let mut filter = DuplicateFilter::default();
+        assert!(!filter.is_duplicate(Some(0), Some(frame_hash(&a))));
+        assert!(filter.is_duplicate(Some(0), Some(frame_hash(&b))));
+        assert!(filter.is_duplicate(Some(16_666_667), Some(frame_hash(&a))));
+        assert!(!filter.is_duplicate(Some(33_333_333), Some(frame_hash(&b))));
+        // GPU memory: PTS only.
+        assert!(!filter.is_duplicate(Some(50_000_000), None));
+        assert!(filter.is_duplicate(Some(50_000_000), None));
+        assert!(!filter.is_duplicate(None, None));
+        assert!(!filter.is_duplicate(None, None));
+        assert_eq!((filter.unique(), filter.duplicates()), (5, 3));
+    }
+}
@@ -1,3 +1,5 @@
+use std::any::Any;
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -32,11 +34,27 @@ pub enum DecoderError {
     #[error("GStreamer pipeline error: {0}")]
     GStreamerPipeline(String),
 
+    /// ERROR message posted on a running pipeline's bus.
+    #[error("Pipeline error from {source_element}: {message}")]
+    Pipeline {
+        source_element: String,
+        message: String,
+        debug: Option<String>,
+    },
+
     #[error("Failed to decode frame: {reason}")]
     DecodeFailed { reason: String },
 
     #[error("Decoder not initialized")]
     NotInitialized,
+
+    /// The GStreamer-free decoder (OpenH264 + wgpu) failed to open or show a frame.
+    #[error("Software decoder error: {0}")]
+    Software(String),
+
+    /// The decode thread panicked, with the panic's message.
+    #[error("Decode thread panicked: {0}")]
+    Panicked(String),
 }
 
 #[derive(Error, Debug)]
@@ -53,3 +71,51 @@ pub enum TransportError {
     #[error("Timeout after {ms}ms")]
     Timeout { ms: u64 },
 }
+
+/// The message of a panic caught with `catch_unwind`, for threads that turn
+/// their panics into errors instead of disappearing.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_owned()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_owned()
+    }
+}
+
+/// An `inputEvent` that does not follow the wire schema (see
+/// [`input_schema`](crate::input_schema)).
+#[derive(Error, Debug)]
+pub enum InputSchemaError {
+    #[error("Input event is not a JSON object")]
+    NotAnObject,
+
+    #[error("Input event has no kind")]
+    MissingKind,
+
+    #[error("Unknown input event kind '{0}'")]
+    UnknownKind(String),
+
+    #[error("Unknown field '{field}' in {kind} input event")]
+    UnknownField { kind: String, field: String },
+
+    #[error("Invalid {kind} input event: {source}")]
+    Invalid {
+        kind: &'static str,
+        source: serde_json::Error,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panic_messages_are_recovered() {
+        let caught = |f: fn()| panic_message(std::panic::catch_unwind(f).unwrap_err().as_ref());
+        assert_eq!(caught(|| panic!("index out of bounds")), "index out of bounds");
+        assert_eq!(caught(|| panic!("frame {} too short", 7)), "frame 7 too short");
+        assert_eq!(caught(|| std::panic::panic_any(7u8)), "unknown panic");
+    }
+}
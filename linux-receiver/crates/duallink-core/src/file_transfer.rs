@@ -0,0 +1,747 @@
+//! File transfer over the signaling channel.
+//!
+//! Between peers that both advertise [`CAP_FILE_TRANSFER`], either end can
+//! offer the other a file — the receiver a file dropped on its window, the
+//! sender one dropped on its UI. The other end's user accepts or declines,
+//! and an accepted file follows in chunks over the same TLS connection:
+//!
+//! ```text
+//! A ── file_offer  { transferId, fileName, fileSize } ──▶ B   (B's user is asked)
+//! A ◀─ file_accept { transferId, accepted, reason? }  ─── B
+//! A ── file_chunk  { transferId, offset, data }       ──▶ B   (base64, in order)
+//! ```
+//!
+//! The transfer is complete when `offset + len(data)` reaches `fileSize`.
+//! `file_accept` with `accepted: false` from either end also cancels a
+//! transfer under way. Chunks are [`FILE_CHUNK_SIZE`] bytes, one every
+//! [`FILE_CHUNK_INTERVAL`], which keeps them under the receiver's message
+//! size and rate limits and leaves room for input events and keepalives.
+//!
+//! Files arrive in [`download_dir`] as `<name>.part` and are renamed when
+//! complete, to `<name> (1)` and so on if `<name>` exists. Offers above
+//! [`configured_max_file_size`] are declined without asking, as are offers
+//! beyond [`MAX_PENDING_OFFERS`] waiting for an answer; offers left
+//! unanswered — either way — are cancelled after [`OFFER_TIMEOUT`].
+//!
+//! [`FileTransfers`] does the bookkeeping without any I/O of its own on the
+//! connection: the transport hands it the [`FileMessage`]s that arrive and
+//! sends the ones [`FileTransfers::next_messages`] returns, while the UI
+//! offers and answers files through a clone and follows the
+//! [`FileTransferEvent`]s. Those calls read and write files — from async
+//! code, make them in `spawn_blocking`. The disk I/O happens outside the
+//! lock they share, so the UI never waits on another end's write.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Capability (in `hello` and `hello_ack`): handles `file_offer`,
+/// `file_accept` and `file_chunk`.
+pub const CAP_FILE_TRANSFER: &str = "file_transfer";
+
+/// File bytes per `file_chunk`; base64 makes 256 KiB of them.
+pub const FILE_CHUNK_SIZE: usize = 192 * 1024;
+
+/// Pause between chunks: 40 messages/s, below the receiver's 50/s limit.
+pub const FILE_CHUNK_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Largest file accepted unless configured otherwise.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 2 << 30;
+
+/// Offers nobody answered for this long are cancelled.
+pub const OFFER_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Incoming offers waiting for the user's answer at once; more are declined.
+pub const MAX_PENDING_OFFERS: usize = 8;
+
+/// Events kept for a UI that doesn't collect them; the oldest go first.
+const MAX_EVENTS: usize = 256;
+
+/// Largest accepted incoming file: `DUALLINK_MAX_FILE_MB` (`0` = refuse
+/// every file), else [`DEFAULT_MAX_FILE_SIZE`].
+pub fn configured_max_file_size() -> u64 {
+    std::env::var("DUALLINK_MAX_FILE_MB")
+        .ok()
+        .and_then(|v| match v.trim().parse::<u64>() {
+            Ok(mb) => Some(mb << 20),
+            Err(_) => {
+                tracing::warn!("Ignoring DUALLINK_MAX_FILE_MB='{v}' — expected megabytes");
+                None
+            }
+        })
+        .unwrap_or(DEFAULT_MAX_FILE_SIZE)
+}
+
+/// Where received files go: `DUALLINK_DOWNLOAD_DIR`, else `~/Downloads`.
+pub fn download_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("DUALLINK_DOWNLOAD_DIR").filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    crate::diagnostics::home_dir().map_or_else(std::env::temp_dir, |home| home.join("Downloads"))
+}
+
+/// The last component of a peer-supplied file name, if it is usable as a
+/// file name here.
+pub fn safe_file_name(name: &str) -> Option<String> {
+    let name = name.rsplit(['/', '\\']).next()?.trim();
+    let usable = !name.is_empty()
+        && name != "."
+        && name != ".."
+        && name.len() <= 255
+        && !name.chars().any(|c| c.is_control() || c == ':');
+    usable.then(|| name.to_owned())
+}
+
+// MARK: - Messages and events
+
+/// A file one end offers the other (`file_offer`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileOffer {
+    pub transfer_id: String,
+    pub name:        String,
+    /// Size in bytes.
+    pub size:        u64,
+}
+
+/// A file-transfer message, whatever its wire encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileMessage {
+    /// `file_offer`.
+    Offer(FileOffer),
+    /// `file_accept`: the answer to an offer, or with `accepted: false`
+    /// later on, a cancellation.
+    Answer { transfer_id: String, accepted: bool, reason: Option<String> },
+    /// `file_chunk`: `data` belongs at `offset`.
+    Chunk { transfer_id: String, offset: u64, data: Vec<u8> },
+}
+
+/// What happened to a transfer, for the UI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileTransferEvent {
+    /// The peer offers a file; answer with [`FileTransfers::answer`].
+    Offered(FileOffer),
+    /// `done` of `total` bytes moved; `incoming` tells receiving from
+    /// sending.
+    Progress { transfer_id: String, name: String, done: u64, total: u64, incoming: bool },
+    /// A file arrived complete at `path`.
+    Received { transfer_id: String, name: String, path: PathBuf },
+    /// The peer has all of a file.
+    Sent { transfer_id: String, name: String },
+    /// Declined, cancelled or broken off, by either end.
+    Failed { transfer_id: String, name: String, reason: String },
+}
+
+impl FileTransferEvent {
+    pub fn transfer_id(&self) -> &str {
+        match self {
+            Self::Offered(offer) => &offer.transfer_id,
+            Self::Progress { transfer_id, .. }
+            | Self::Received { transfer_id, .. }
+            | Self::Sent { transfer_id, .. }
+            | Self::Failed { transfer_id, .. } => transfer_id,
+        }
+    }
+}
+
+// MARK: - IncomingFile
+
+/// An accepted file being written to `<path>.part`; the part file goes
+/// away unless [`finish`](Self::finish) renames it.
+#[derive(Debug)]
+struct IncomingFile {
+    offer:    FileOffer,
+    path:     PathBuf,
+    part:     PathBuf,
+    file:     Option<File>,
+    received: u64,
+}
+
+impl IncomingFile {
+    fn create(dir: &Path, offer: FileOffer) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = unused_path(dir, &offer.name);
+        let part = part_path(&path);
+        let file = File::create(&part)?;
+        Ok(Self { offer, path, part, file: Some(file), received: 0 })
+    }
+
+    /// Append a chunk; chunks must come in order and stay within the
+    /// offered size.
+    fn write(&mut self, offset: u64, data: &[u8]) -> io::Result<()> {
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+        if offset != self.received {
+            return Err(invalid(format!("chunk at {offset}, expected {}", self.received)));
+        }
+        if self.received + data.len() as u64 > self.offer.size {
+            return Err(invalid(format!("more than the {} bytes offered", self.offer.size)));
+        }
+        let Some(file) = &mut self.file else { return Err(invalid("file closed".into())) };
+        file.write_all(data)?;
+        self.received += data.len() as u64;
+        Ok(())
+    }
+
+    fn is_complete(&self) -> bool {
+        self.received == self.offer.size
+    }
+
+    /// Close the part file and give it its name; returns the final path.
+    fn finish(&mut self) -> io::Result<PathBuf> {
+        if let Some(file) = self.file.take() {
+            file.sync_all()?;
+        }
+        // Taken meanwhile: the next free name.
+        let mut path = self.path.clone();
+        if path.exists() {
+            path = unused_path(path.parent().unwrap_or(Path::new(".")), &self.offer.name);
+        }
+        std::fs::rename(&self.part, &path)?;
+        self.part = PathBuf::new();
+        Ok(path)
+    }
+}
+
+impl Drop for IncomingFile {
+    fn drop(&mut self) {
+        if !self.part.as_os_str().is_empty() {
+            self.file = None;
+            let _ = std::fs::remove_file(&self.part);
+        }
+    }
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    PathBuf::from(part)
+}
+
+/// `dir/name`, or `dir/name (n).ext` for the first `n` neither it nor its
+/// part file is taken.
+fn unused_path(dir: &Path, name: &str) -> PathBuf {
+    let free = |p: &Path| !p.exists() && !part_path(p).exists();
+    let first = dir.join(name);
+    if free(&first) {
+        return first;
+    }
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (name, String::new()),
+    };
+    (1..)
+        .map(|n| dir.join(format!("{stem} ({n}){ext}")))
+        .find(|p| free(p))
+        .unwrap_or(first)
+}
+
+// MARK: - FileTransfers
+
+enum Incoming {
+    /// Waiting for the user's answer since the `Instant`.
+    Offered(FileOffer, Instant),
+    /// Written to outside the [`Exchange`] lock.
+    Receiving(FileOffer, Arc<Mutex<IncomingFile>>),
+}
+
+impl Incoming {
+    fn offer(&self) -> &FileOffer {
+        match self {
+            Self::Offered(offer, _) | Self::Receiving(offer, _) => offer,
+        }
+    }
+}
+
+struct Outgoing {
+    offer:    FileOffer,
+    /// Read from outside the [`Exchange`] lock.
+    file:     Arc<File>,
+    sent:     u64,
+    /// When the offer went out; `None` once accepted.
+    offered:  Option<Instant>,
+}
+
+struct Exchange {
+    dir:      PathBuf,
+    max_size: u64,
+    incoming: HashMap<String, Incoming>,
+    /// In the order they were offered; sent one after the other.
+    outgoing: Vec<Outgoing>,
+    outbox:   VecDeque<FileMessage>,
+    events:   VecDeque<FileTransferEvent>,
+    next_id:  u64,
+}
+
+impl Exchange {
+    fn push_event(&mut self, event: FileTransferEvent) {
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    fn decline(&mut self, transfer_id: &str, reason: String) {
+        self.outbox.push_back(FileMessage::Answer {
+            transfer_id: transfer_id.to_owned(),
+            accepted:    false,
+            reason:      Some(reason),
+        });
+    }
+
+    fn fail(&mut self, transfer_id: &str, name: &str, reason: String) {
+        tracing::warn!("File transfer of '{}' failed: {}", name, reason);
+        self.push_event(FileTransferEvent::Failed {
+            transfer_id: transfer_id.to_owned(),
+            name:        name.to_owned(),
+            reason,
+        });
+    }
+
+    /// Cancel a transfer for both ends.
+    fn cancel(&mut self, transfer_id: &str, name: &str, reason: String) {
+        self.decline(transfer_id, reason.clone());
+        self.fail(transfer_id, name, reason);
+    }
+
+    fn offered(&mut self, offer: FileOffer) {
+        let refusal = if self.max_size == 0 {
+            Some("Receiving files is turned off".to_owned())
+        } else if offer.size > self.max_size {
+            Some(format!("{} MB is above the {} MB limit", offer.size >> 20, self.max_size >> 20))
+        } else if safe_file_name(&offer.name).is_none() {
+            Some("Unusable file name".to_owned())
+        } else {
+            None
+        };
+        if self.incoming.contains_key(&offer.transfer_id) {
+            return;
+        }
+        let pending = self.incoming.values().filter(|i| matches!(i, Incoming::Offered(..))).count();
+        let refusal = refusal.or_else(|| {
+            (pending >= MAX_PENDING_OFFERS).then(|| format!("{pending} files are already waiting for an answer"))
+        });
+        if let Some(reason) = refusal {
+            tracing::info!("Declining file '{}': {}", offer.name, reason);
+            self.decline(&offer.transfer_id, reason);
+            return;
+        }
+        let offer = FileOffer { name: safe_file_name(&offer.name).unwrap_or_default(), ..offer };
+        tracing::info!("Peer offers '{}' ({} bytes)", offer.name, offer.size);
+        self.incoming.insert(offer.transfer_id.clone(), Incoming::Offered(offer.clone(), Instant::now()));
+        self.push_event(FileTransferEvent::Offered(offer));
+    }
+
+    fn answered(&mut self, transfer_id: &str, accepted: bool, reason: Option<String>) {
+        if let Some(at) = self.outgoing.iter().position(|o| o.offer.transfer_id == transfer_id) {
+            if accepted && self.outgoing[at].offered.is_some() {
+                tracing::info!("Peer accepted '{}'", self.outgoing[at].offer.name);
+                self.outgoing[at].offered = None;
+            } else if !accepted {
+                let outgoing = self.outgoing.remove(at);
+                self.fail(transfer_id, &outgoing.offer.name, reason.unwrap_or_else(|| "Declined".into()));
+            }
+        } else if !accepted {
+            if let Some(incoming) = self.incoming.remove(transfer_id) {
+                let name = incoming.offer().name.clone();
+                self.fail(transfer_id, &name, reason.unwrap_or_else(|| "Cancelled by the peer".into()));
+            }
+        }
+    }
+}
+
+/// The file transfers of one connection — or of one display, across its
+/// connections — shared by the transport and the UI.
+#[derive(Clone)]
+pub struct FileTransfers(Arc<Mutex<Exchange>>);
+
+impl Default for FileTransfers {
+    fn default() -> Self {
+        Self::new(download_dir(), DEFAULT_MAX_FILE_SIZE)
+    }
+}
+
+impl FileTransfers {
+    /// Transfers saving to `dir` and accepting files up to `max_size` bytes.
+    pub fn new(dir: PathBuf, max_size: u64) -> Self {
+        Self(Arc::new(Mutex::new(Exchange {
+            dir,
+            max_size,
+            incoming: HashMap::new(),
+            outgoing: Vec::new(),
+            outbox: VecDeque::new(),
+            events: VecDeque::new(),
+            next_id: 0,
+        })))
+    }
+
+    /// Transfers saving to [`download_dir`], up to
+    /// [`configured_max_file_size`].
+    pub fn configured() -> Self {
+        Self::new(download_dir(), configured_max_file_size())
+    }
+
+    /// Offer the peer the file at `path`; it is sent once accepted.
+    pub fn send(&self, path: &Path) -> io::Result<FileOffer> {
+        let file = File::open(path)?;
+        let meta = file.metadata()?;
+        if !meta.is_file() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a file"));
+        }
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let mut x = self.0.lock().unwrap();
+        x.next_id += 1;
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis());
+        let offer = FileOffer { transfer_id: format!("{millis:x}-{}", x.next_id), name, size: meta.len() };
+        tracing::info!("Offering '{}' ({} bytes)", offer.name, offer.size);
+        x.outbox.push_back(FileMessage::Offer(offer.clone()));
+        x.outgoing.push(Outgoing { offer: offer.clone(), file: Arc::new(file), sent: 0, offered: Some(Instant::now()) });
+        Ok(offer)
+    }
+
+    /// Accept or decline a file the peer [offered](FileTransferEvent::Offered).
+    pub fn answer(&self, transfer_id: &str, accept: bool) {
+        let (offer, dir) = {
+            let mut x = self.0.lock().unwrap();
+            let Some(Incoming::Offered(offer, _)) = x.incoming.get(transfer_id) else { return };
+            let offer = offer.clone();
+            if !accept {
+                x.incoming.remove(transfer_id);
+                tracing::info!("Declined '{}'", offer.name);
+                x.decline(transfer_id, "Declined".into());
+                return;
+            }
+            (offer, x.dir.clone())
+        };
+        let created = IncomingFile::create(&dir, offer.clone());
+        let mut x = self.0.lock().unwrap();
+        // Cancelled, timed out or answered meanwhile: dropping `created`
+        // removes its part file.
+        if !matches!(x.incoming.get(transfer_id), Some(Incoming::Offered(o, _)) if *o == offer) {
+            return;
+        }
+        match created {
+            Ok(file) => {
+                x.incoming.insert(transfer_id.to_owned(), Incoming::Receiving(offer, Arc::new(Mutex::new(file))));
+                x.outbox.push_back(FileMessage::Answer {
+                    transfer_id: transfer_id.to_owned(),
+                    accepted:    true,
+                    reason:      None,
+                });
+            }
+            Err(e) => {
+                x.incoming.remove(transfer_id);
+                x.cancel(transfer_id, &offer.name, format!("Saving in {}: {e}", dir.display()));
+            }
+        }
+    }
+
+    /// Handle a message from the peer.
+    pub fn receive(&self, message: FileMessage) {
+        match message {
+            FileMessage::Offer(offer) => self.0.lock().unwrap().offered(offer),
+            FileMessage::Answer { transfer_id, accepted, reason } => {
+                self.0.lock().unwrap().answered(&transfer_id, accepted, reason);
+            }
+            FileMessage::Chunk { transfer_id, offset, data } => self.chunk(&transfer_id, offset, &data),
+        }
+    }
+
+    /// Messages for the peer: answers and offers, then at most one chunk.
+    /// Call every [`FILE_CHUNK_INTERVAL`] while connected; offers either
+    /// way older than [`OFFER_TIMEOUT`] at `now` are cancelled.
+    pub fn next_messages(&self, now: Instant) -> Vec<FileMessage> {
+        let timed_out = |at: Instant| now.saturating_duration_since(at) >= OFFER_TIMEOUT;
+        let mut x = self.0.lock().unwrap();
+        let expired: Vec<FileOffer> = x
+            .outgoing
+            .iter()
+            .filter(|o| o.offered.is_some_and(timed_out))
+            .map(|o| o.offer.clone())
+            .collect();
+        for offer in expired {
+            x.outgoing.retain(|o| o.offer.transfer_id != offer.transfer_id);
+            x.cancel(&offer.transfer_id, &offer.name, "Not answered".into());
+        }
+        let unanswered: Vec<FileOffer> = x
+            .incoming
+            .values()
+            .filter_map(|i| match i {
+                Incoming::Offered(offer, at) if timed_out(*at) => Some(offer.clone()),
+                _ => None,
+            })
+            .collect();
+        for offer in unanswered {
+            x.incoming.remove(&offer.transfer_id);
+            x.cancel(&offer.transfer_id, &offer.name, "Not answered".into());
+        }
+        let mut messages: Vec<FileMessage> = x.outbox.drain(..).collect();
+        drop(x);
+        messages.extend(self.next_chunk());
+        messages
+    }
+
+    /// Write a chunk of an accepted incoming file.
+    fn chunk(&self, transfer_id: &str, offset: u64, data: &[u8]) {
+        let file = match self.0.lock().unwrap().incoming.get(transfer_id) {
+            Some(Incoming::Receiving(_, file)) => Arc::clone(file),
+            _ => {
+                tracing::debug!("Chunk for unknown transfer {}", transfer_id);
+                return;
+            }
+        };
+        let (written, finished) = {
+            let mut file = file.lock().unwrap();
+            let written = file.write(offset, data).map(|()| file.received);
+            let finished = (written.is_ok() && file.is_complete()).then(|| file.finish());
+            (written, finished)
+        };
+        let mut x = self.0.lock().unwrap();
+        let (name, total) = match x.incoming.get(transfer_id) {
+            Some(Incoming::Receiving(offer, f)) if Arc::ptr_eq(f, &file) => (offer.name.clone(), offer.size),
+            // Cancelled meanwhile, and reported as failed then: none of it
+            // stays. Dropping `file` removes its part file.
+            _ => {
+                if let Some(Ok(path)) = finished {
+                    let _ = std::fs::remove_file(path);
+                }
+                return;
+            }
+        };
+        let transfer_id = transfer_id.to_owned();
+        match (written, finished) {
+            (Err(e), _) => {
+                x.incoming.remove(&transfer_id);
+                x.cancel(&transfer_id, &name, e.to_string());
+            }
+            (Ok(done), None) => {
+                x.push_event(FileTransferEvent::Progress { transfer_id, name, done, total, incoming: true });
+            }
+            (Ok(_), Some(Ok(path))) => {
+                x.incoming.remove(&transfer_id);
+                tracing::info!("Received '{}' → {}", name, path.display());
+                x.push_event(FileTransferEvent::Received { transfer_id, name, path });
+            }
+            (Ok(_), Some(Err(e))) => {
+                x.incoming.remove(&transfer_id);
+                x.fail(&transfer_id, &name, e.to_string());
+            }
+        }
+    }
+
+    /// Read the next chunk of the first accepted outgoing file.
+    fn next_chunk(&self) -> Option<FileMessage> {
+        let (offer, file, offset) = {
+            let x = self.0.lock().unwrap();
+            let outgoing = x.outgoing.iter().find(|o| o.offered.is_none())?;
+            (outgoing.offer.clone(), Arc::clone(&outgoing.file), outgoing.sent)
+        };
+        let len = (offer.size - offset).min(FILE_CHUNK_SIZE as u64) as usize;
+        let mut data = vec![0; len];
+        let read = (&*file).read_exact(&mut data);
+        let mut x = self.0.lock().unwrap();
+        // Cancelled meanwhile: nothing to send.
+        let at = x.outgoing.iter().position(|o| Arc::ptr_eq(&o.file, &file) && o.sent == offset)?;
+        let FileOffer { transfer_id, name, size: total } = offer;
+        if let Err(e) = read {
+            x.outgoing.remove(at);
+            x.cancel(&transfer_id, &name, format!("Reading the file: {e}"));
+            return None;
+        }
+        x.outgoing[at].sent += len as u64;
+        let done = x.outgoing[at].sent;
+        if done == total {
+            x.outgoing.remove(at);
+            tracing::info!("Sent '{}' ({} bytes)", name, total);
+            x.push_event(FileTransferEvent::Sent { transfer_id: transfer_id.clone(), name });
+        } else {
+            let (transfer_id, incoming) = (transfer_id.clone(), false);
+            x.push_event(FileTransferEvent::Progress { transfer_id, name, done, total, incoming });
+        }
+        Some(FileMessage::Chunk { transfer_id, offset, data })
+    }
+
+    /// Fail every transfer — the connection is gone.
+    pub fn abort_all(&self, reason: &str) {
+        let mut x = self.0.lock().unwrap();
+        let mut names: Vec<(String, String)> =
+            x.outgoing.drain(..).map(|o| (o.offer.transfer_id, o.offer.name)).collect();
+        names.extend(x.incoming.drain().map(|(id, incoming)| (id, incoming.offer().name.clone())));
+        x.outbox.clear();
+        for (transfer_id, name) in names {
+            x.fail(&transfer_id, &name, reason.to_owned());
+        }
+    }
+
+    /// `true` while any transfer is offered or under way.
+    pub fn is_busy(&self) -> bool {
+        let x = self.0.lock().unwrap();
+        !x.incoming.is_empty() || !x.outgoing.is_empty()
+    }
+
+    /// The events since the last call.
+    pub fn take_events(&self) -> Vec<FileTransferEvent> {
+        self.0.lock().unwrap().events.drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("duallink-files-{}-{tag}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Pass messages both ways until neither end has anything to send.
+    fn pump(a: &FileTransfers, b: &FileTransfers) {
+        loop {
+            let (to_b, to_a) = (a.next_messages(Instant::now()), b.next_messages(Instant::now()));
+            if to_b.is_empty() && to_a.is_empty() {
+                return;
+            }
+            to_b.into_iter().for_each(|m| b.receive(m));
+            to_a.into_iter().for_each(|m| a.receive(m));
+        }
+    }
+
+    #[test]
+    fn names_lose_their_directories() {
+        assert_eq!(safe_file_name("../../etc/passwd").as_deref(), Some("passwd"));
+        assert_eq!(safe_file_name(r"C:\Users\me\notes.txt").as_deref(), Some("notes.txt"));
+        assert_eq!(safe_file_name("report final.pdf").as_deref(), Some("report final.pdf"));
+        for bad in ["", "..", "dir/", "a\nb", "x:y"] {
+            assert_eq!(safe_file_name(bad), None, "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn accepted_file_arrives_under_a_free_name() {
+        let (src, dst) = (temp_dir("src"), temp_dir("dst"));
+        let content: Vec<u8> = (0..FILE_CHUNK_SIZE * 2 + 123).map(|i| (i % 251) as u8).collect();
+        let path = src.join("photo.jpg");
+        std::fs::write(&path, &content).unwrap();
+        std::fs::write(dst.join("photo.jpg"), b"already here").unwrap();
+
+        let (a, b) = (FileTransfers::new(src.clone(), 0), FileTransfers::new(dst.clone(), 1 << 20));
+        let offer = a.send(&path).unwrap();
+        pump(&a, &b);
+        assert_eq!(b.take_events(), [FileTransferEvent::Offered(offer.clone())]);
+        assert!(a.is_busy());
+
+        b.answer(&offer.transfer_id, true);
+        pump(&a, &b);
+        let received = b.take_events();
+        let Some(FileTransferEvent::Received { path: saved, .. }) = received.last() else { panic!("{received:?}") };
+        assert_eq!(saved, &dst.join("photo (1).jpg"));
+        assert_eq!(std::fs::read(saved).unwrap(), content);
+        assert_eq!(received.len(), 3, "two progress events, then received");
+        assert!(matches!(a.take_events().last(), Some(FileTransferEvent::Sent { .. })));
+        assert!(!a.is_busy() && !b.is_busy());
+        let _ = std::fs::remove_dir_all(src);
+        let _ = std::fs::remove_dir_all(dst);
+    }
+
+    #[test]
+    fn refused_and_broken_transfers_fail_on_both_ends() {
+        let (src, dst) = (temp_dir("src2"), temp_dir("dst2"));
+        let path = src.join("big.bin");
+        std::fs::write(&path, vec![7u8; 2 << 20]).unwrap();
+
+        // Over the limit: declined without asking.
+        let (a, b) = (FileTransfers::new(src.clone(), 0), FileTransfers::new(dst.clone(), 1 << 20));
+        a.send(&path).unwrap();
+        pump(&a, &b);
+        assert!(b.take_events().is_empty());
+        assert!(matches!(&a.take_events()[..], [FileTransferEvent::Failed { reason, .. }] if reason.contains("limit")));
+
+        // Out-of-order chunk: the part file goes, the sender is told.
+        let b = FileTransfers::new(dst.clone(), 4 << 20);
+        let offer = a.send(&path).unwrap();
+        pump(&a, &b);
+        b.answer(&offer.transfer_id, true);
+        let id = offer.transfer_id.clone();
+        b.receive(FileMessage::Chunk { transfer_id: id.clone(), offset: 5, data: vec![1] });
+        assert!(matches!(&b.take_events()[..], [_, FileTransferEvent::Failed { .. }]));
+        assert_eq!(std::fs::read_dir(&dst).unwrap().count(), 0);
+        pump(&a, &b);
+        assert!(matches!(a.take_events().last(), Some(FileTransferEvent::Failed { .. })));
+
+        // Unanswered offers time out.
+        let offer = a.send(&path).unwrap();
+        let later = Instant::now() + OFFER_TIMEOUT;
+        let cancel = a.next_messages(later);
+        assert!(cancel.contains(&FileMessage::Offer(offer.clone())));
+        assert!(cancel.iter().any(|m| matches!(m, FileMessage::Answer { accepted: false, .. })));
+        let _ = std::fs::remove_dir_all(src);
+        let _ = std::fs::remove_dir_all(dst);
+    }
+
+    #[test]
+    fn waiting_offers_are_capped_and_expire() {
+        let dst = temp_dir("dst3");
+        let b = FileTransfers::new(dst.clone(), 1 << 20);
+        let offer = |n: usize| FileOffer { transfer_id: format!("t{n}"), name: format!("f{n}.txt"), size: 10 };
+        for n in 0..=MAX_PENDING_OFFERS {
+            b.receive(FileMessage::Offer(offer(n)));
+        }
+        assert_eq!(b.take_events().len(), MAX_PENDING_OFFERS);
+        let declined = b.next_messages(Instant::now());
+        let last = format!("t{MAX_PENDING_OFFERS}");
+        assert!(matches!(
+            &declined[..],
+            [FileMessage::Answer { transfer_id, accepted: false, .. }] if *transfer_id == last
+        ));
+
+        // Answering one makes room; the rest time out on both ends.
+        b.answer("t0", false);
+        b.receive(FileMessage::Offer(offer(MAX_PENDING_OFFERS)));
+        assert!(matches!(&b.take_events()[..], [FileTransferEvent::Offered(_)]));
+        let cancelled = b.next_messages(Instant::now() + OFFER_TIMEOUT);
+        assert_eq!(cancelled.len(), MAX_PENDING_OFFERS + 1, "t0's answer, then one cancellation per offer");
+        assert_eq!(b.take_events().len(), MAX_PENDING_OFFERS);
+        assert!(!b.is_busy());
+        // Accepting too late does nothing.
+        b.answer("t1", true);
+        assert!(b.next_messages(Instant::now()).is_empty());
+        assert_eq!(std::fs::read_dir(&dst).unwrap().count(), 0);
+        let _ = std::fs::remove_dir_all(dst);
+    }
+
+    #[test]
+    fn writes_happen_outside_the_shared_lock() {
+        let dst = temp_dir("dst4");
+        let b = FileTransfers::new(dst.clone(), 1 << 20);
+        let offer = FileOffer { transfer_id: "t".into(), name: "notes.txt".into(), size: 4 };
+        b.receive(FileMessage::Offer(offer));
+        b.answer("t", true);
+        let file = match b.0.lock().unwrap().incoming.get("t") {
+            Some(Incoming::Receiving(_, file)) => Arc::clone(file),
+            _ => panic!("not receiving"),
+        };
+
+        // A write stuck on the disk leaves the UI free to cancel; the part
+        // file goes once the write is done.
+        let held = file.lock().unwrap();
+        let chunk = FileMessage::Chunk { transfer_id: "t".into(), offset: 0, data: vec![1; 4] };
+        let writer = {
+            let b = b.clone();
+            std::thread::spawn(move || b.receive(chunk))
+        };
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(b.is_busy());
+        b.abort_all("gone");
+        drop(held);
+        drop(file);
+        writer.join().unwrap();
+        let events = b.take_events();
+        assert!(matches!(events.last(), Some(FileTransferEvent::Failed { .. })), "{events:?}");
+        assert!(!events.iter().any(|e| matches!(e, FileTransferEvent::Received { .. })));
+        assert_eq!(std::fs::read_dir(&dst).unwrap().count(), 0);
+        let _ = std::fs::remove_dir_all(dst);
+    }
+}
@@ -0,0 +1,267 @@
+//! Firewall and privileged-port check for the receiver's ports.
+//!
+//! A receiver behind firewalld or ufw binds its ports fine but never sees a
+//! sender. [`FirewallCheck::run`] finds the active firewall, asks it which
+//! of the receiver's ports it lets in, and lists ports the receiver may not
+//! bind without root (below `net.ipv4.ip_unprivileged_port_start`).
+//!
+//! [`Firewall::open`] opens the blocked ports — permanently, via
+//! `firewall-cmd` or `ufw` under `pkexec` — and is only ever called after
+//! the user confirmed: the GUI's "Open ports" button or the `firewall`
+//! subcommand's prompt (`duallink-receiver firewall`).
+//!
+//! [`reachability`] connects to a listening port on each local
+//! interface's address. Local connections skip most firewall rules, so it
+//! finds a receiver bound to the wrong interface rather than a blocking
+//! firewall; the rule query covers that.
+//!
+//! Linux only; elsewhere no firewall is detected.
+
+use std::fmt;
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::time::Duration;
+
+use crate::ports::PortMap;
+
+/// UDP port of mDNS, used for receiver discovery.
+pub const MDNS_PORT: u16 = 5353;
+
+// MARK: - Ports
+
+/// Transport protocol of a [`FirewallPort`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+/// A port the receiver needs reachable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FirewallPort {
+    pub port:     u16,
+    pub protocol: Protocol,
+}
+
+impl fmt::Display for FirewallPort {
+    /// `7878/udp`, the form both `firewall-cmd` and `ufw` take.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let proto = match self.protocol {
+            Protocol::Tcp => "tcp",
+            Protocol::Udp => "udp",
+        };
+        write!(f, "{}/{proto}", self.port)
+    }
+}
+
+/// Every port of `ports` — UDP video and TCP signaling per display — plus
+/// mDNS.
+pub fn receiver_ports(ports: &PortMap) -> Vec<FirewallPort> {
+    let mut list: Vec<FirewallPort> = ports
+        .iter()
+        .flat_map(|p| {
+            [
+                FirewallPort { port: p.video, protocol: Protocol::Udp },
+                FirewallPort { port: p.signaling, protocol: Protocol::Tcp },
+            ]
+        })
+        .collect();
+    list.push(FirewallPort { port: MDNS_PORT, protocol: Protocol::Udp });
+    list
+}
+
+// MARK: - Firewall
+
+/// A host firewall DualLink knows how to query and open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Firewall {
+    Firewalld,
+    Ufw,
+}
+
+impl fmt::Display for Firewall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Firewalld => "firewalld",
+            Self::Ufw => "ufw",
+        })
+    }
+}
+
+impl Firewall {
+    /// The running firewall, if any.
+    #[cfg(target_os = "linux")]
+    pub fn detect() -> Option<Self> {
+        if run("firewall-cmd", &["--state"]).is_some_and(|s| s.trim() == "running") {
+            return Some(Self::Firewalld);
+        }
+        // `ufw status` needs root; its config file does not.
+        let ufw = std::fs::read_to_string("/etc/ufw/ufw.conf").ok();
+        ufw.is_some_and(|conf| ufw_enabled(&conf)).then_some(Self::Ufw)
+    }
+
+    /// Non-Linux stub — no firewall is detected.
+    #[cfg(not(target_os = "linux"))]
+    pub fn detect() -> Option<Self> {
+        None
+    }
+
+    /// Whether the firewall lets `port` in; `None` if it cannot tell
+    /// (ufw, which only answers root).
+    pub fn allows(&self, port: FirewallPort) -> Option<bool> {
+        match self {
+            Self::Firewalld => {
+                if run("firewall-cmd", &["--get-default-zone"]).is_some_and(|z| z.trim() == "trusted") {
+                    return Some(true);
+                }
+                let query = format!("--query-port={port}");
+                let output = std::process::Command::new("firewall-cmd").arg(query).output().ok()?;
+                // Exit status 0 = "yes", 1 = "no"; anything else is an error.
+                match output.status.code() {
+                    Some(0) => Some(true),
+                    Some(1) => Some(false),
+                    _ => None,
+                }
+            }
+            Self::Ufw => None,
+        }
+    }
+
+    /// Shell commands that open `ports` permanently.
+    pub fn open_script(&self, ports: &[FirewallPort]) -> String {
+        match self {
+            Self::Firewalld => {
+                let adds: Vec<String> = ports.iter().map(|p| format!("--add-port={p}")).collect();
+                format!("firewall-cmd --permanent {} && firewall-cmd --reload", adds.join(" "))
+            }
+            Self::Ufw => ports.iter().map(|p| format!("ufw allow {p}")).collect::<Vec<_>>().join(" && "),
+        }
+    }
+
+    /// Run [`open_script`](Self::open_script), through `pkexec` unless
+    /// already root. Blocks until the user answered the password prompt.
+    pub fn open(&self, ports: &[FirewallPort]) -> std::io::Result<()> {
+        let script = self.open_script(ports);
+        let mut cmd = if is_root() {
+            std::process::Command::new("sh")
+        } else {
+            let mut cmd = std::process::Command::new("pkexec");
+            cmd.arg("sh");
+            cmd
+        };
+        let output = cmd.args(["-c", &script]).output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(std::io::Error::other(format!("`{script}` failed: {}", stderr.trim())))
+        }
+    }
+}
+
+// MARK: - FirewallCheck
+
+/// What stands between senders and the receiver's ports.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FirewallCheck {
+    /// The active firewall, if any.
+    pub firewall:   Option<Firewall>,
+    /// Ports the firewall does not let in, or — when it cannot tell — all of
+    /// them.
+    pub blocked:    Vec<FirewallPort>,
+    /// Ports below the unprivileged port range while not running as root.
+    pub privileged: Vec<u16>,
+}
+
+impl FirewallCheck {
+    /// Check `ports`. Runs `firewall-cmd` once per port — call it from
+    /// `spawn_blocking` in async code.
+    pub fn run(ports: &[FirewallPort]) -> Self {
+        let firewall = Firewall::detect();
+        let blocked = match firewall {
+            Some(fw) => ports.iter().copied().filter(|p| fw.allows(*p) != Some(true)).collect(),
+            None => Vec::new(),
+        };
+        let start = unprivileged_port_start();
+        let mut privileged: Vec<u16> =
+            if is_root() { Vec::new() } else { ports.iter().map(|p| p.port).filter(|p| *p < start).collect() };
+        privileged.sort_unstable();
+        privileged.dedup();
+        Self { firewall, blocked, privileged }
+    }
+
+    /// `true` if the user should be told (and offered to open ports).
+    pub fn needs_action(&self) -> bool {
+        !self.blocked.is_empty() || !self.privileged.is_empty()
+    }
+
+    /// [`blocked`](Self::blocked) as `7878/udp, 7879/tcp`.
+    pub fn blocked_list(&self) -> String {
+        self.blocked.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+    }
+}
+
+// MARK: - Reachability
+
+/// Each local IPv4 address, and whether a TCP connection to `port` on it
+/// was accepted within `timeout`.
+pub fn reachability(port: u16, timeout: Duration) -> Vec<(IpAddr, bool)> {
+    crate::diagnostics::interfaces()
+        .iter()
+        .flat_map(|i| i.addresses.iter())
+        .filter_map(|a| a.split('/').next()?.parse::<IpAddr>().ok())
+        .filter(IpAddr::is_ipv4)
+        .map(|ip| (ip, TcpStream::connect_timeout(&SocketAddr::new(ip, port), timeout).is_ok()))
+        .collect()
+}
+
+// MARK: - Helpers
+
+/// Stdout of `cmd`, if it ran and succeeded.
+fn run(cmd: &str, args: &[&str]) -> Option<String> {
+    std::process::Command::new(cmd)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+}
+
+/// `ENABLED=yes` in `/etc/ufw/ufw.conf`.
+#[cfg(any(target_os = "linux", test))]
+fn ufw_enabled(conf: &str) -> bool {
+    conf.lines().any(|l| l.trim().strip_prefix("ENABLED=").is_some_and(|v| v.trim_matches('"') == "yes"))
+}
+
+/// First port an unprivileged process may bind (1024 unless lowered).
+fn unprivileged_port_start() -> u16 {
+    std::fs::read_to_string("/proc/sys/net/ipv4/ip_unprivileged_port_start")
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(1024)
+}
+
+/// Effective user id 0, from `/proc/self/status`.
+fn is_root() -> bool {
+    std::fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|s| s.lines().find_map(|l| l.strip_prefix("Uid:")).map(|ids| ids.split_whitespace().nth(1) == Some("0")))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_open_every_port() {
+        let ports = receiver_ports(&PortMap::contiguous(7878, 1));
+        assert_eq!(ports.iter().map(ToString::to_string).collect::<Vec<_>>(), ["7878/udp", "7879/tcp", "5353/udp"]);
+        assert_eq!(
+            Firewall::Firewalld.open_script(&ports[..2]),
+            "firewall-cmd --permanent --add-port=7878/udp --add-port=7879/tcp && firewall-cmd --reload"
+        );
+        assert_eq!(Firewall::Ufw.open_script(&ports[..2]), "ufw allow 7878/udp && ufw allow 7879/tcp");
+        assert!(ufw_enabled("# comment\nENABLED=yes\nLOGLEVEL=low\n"));
+        assert!(!ufw_enabled("ENABLED=no\n"));
+    }
+}
@@ -0,0 +1,230 @@
+//! Gesture lifecycles for trackpad / touch input.
+//!
+//! Window toolkits report raw touch points, or bare per-frame zoom factors,
+//! without gesture phases. The sender needs `Begin → Changed… → End` to drive
+//! momentum and to know when a gesture is over, so [`GestureTracker`] turns
+//! them into phased events:
+//!
+//! - two fingers → [`InputEvent::GesturePinch`] + [`InputEvent::GestureRotation`]
+//! - three or more fingers → [`InputEvent::GestureSwipe`]
+//! - consecutive zoom factors → one [`InputEvent::GesturePinch`] lifecycle
+//!
+//! A gesture ends when its finger count changes; adding a finger begins the
+//! gesture for the new count, lifting one does not.
+
+use std::collections::BTreeMap;
+
+use crate::{GesturePhase, InputEvent};
+
+// MARK: - GestureTracker
+
+/// Turns touch-point updates (normalised `0.0..=1.0` coordinates) into
+/// phased gesture events.
+#[derive(Debug, Clone)]
+pub struct GestureTracker {
+    /// Width / height of the surface, so angles and spreads are measured in
+    /// square units.
+    aspect:     f64,
+    touches:    BTreeMap<u64, (f64, f64)>,
+    /// Spread and angle (degrees) of the active two-finger gesture.
+    two_finger: Option<(f64, f64)>,
+    /// Centroid of the active swipe.
+    swipe:      Option<(f64, f64)>,
+    /// Position of the active zoom-factor pinch.
+    zoom:       Option<(f64, f64)>,
+}
+
+impl Default for GestureTracker {
+    fn default() -> Self {
+        Self::new(16.0 / 9.0)
+    }
+}
+
+impl GestureTracker {
+    /// Tracker for a surface `aspect` (width / height) wide.
+    pub fn new(aspect: f64) -> Self {
+        Self { aspect, touches: BTreeMap::new(), two_finger: None, swipe: None, zoom: None }
+    }
+
+    /// Update the surface aspect (window resized).
+    pub fn set_aspect(&mut self, aspect: f64) {
+        if aspect.is_finite() && aspect > 0.0 {
+            self.aspect = aspect;
+        }
+    }
+
+    /// A finger touched down.
+    pub fn touch_down(&mut self, id: u64, x: f64, y: f64) -> Vec<InputEvent> {
+        let mut out = self.end(GesturePhase::End);
+        self.touches.insert(id, (x, y));
+        let (cx, cy) = self.centroid();
+        match self.touches.len() {
+            2 => {
+                self.two_finger = self.spread_and_angle();
+                out.push(InputEvent::GesturePinch { x: cx, y: cy, magnification: 0.0, phase: GesturePhase::Begin });
+                out.push(InputEvent::GestureRotation { x: cx, y: cy, rotation: 0.0, phase: GesturePhase::Begin });
+            }
+            n if n >= 3 => {
+                self.swipe = Some((cx, cy));
+                out.push(InputEvent::GestureSwipe { delta_x: 0.0, delta_y: 0.0, phase: GesturePhase::Begin });
+            }
+            _ => {}
+        }
+        out
+    }
+
+    /// A touching finger moved.
+    pub fn touch_move(&mut self, id: u64, x: f64, y: f64) -> Vec<InputEvent> {
+        let Some(point) = self.touches.get_mut(&id) else { return Vec::new() };
+        *point = (x, y);
+        let (cx, cy) = self.centroid();
+        let mut out = Vec::new();
+
+        if let (Some((spread, angle)), Some((new_spread, new_angle))) = (self.two_finger, self.spread_and_angle()) {
+            let magnification = if spread > f64::EPSILON { new_spread / spread - 1.0 } else { 0.0 };
+            // Shortest way round, so crossing ±180° is a small step.
+            let rotation = (new_angle - angle + 540.0) % 360.0 - 180.0;
+            if magnification != 0.0 {
+                out.push(InputEvent::GesturePinch { x: cx, y: cy, magnification, phase: GesturePhase::Changed });
+            }
+            if rotation != 0.0 {
+                out.push(InputEvent::GestureRotation { x: cx, y: cy, rotation, phase: GesturePhase::Changed });
+            }
+            self.two_finger = Some((new_spread, new_angle));
+        }
+        if let Some((px, py)) = self.swipe {
+            if (cx, cy) != (px, py) {
+                out.push(InputEvent::GestureSwipe { delta_x: cx - px, delta_y: cy - py, phase: GesturePhase::Changed });
+            }
+            self.swipe = Some((cx, cy));
+        }
+        out
+    }
+
+    /// A finger lifted.
+    pub fn touch_up(&mut self, id: u64) -> Vec<InputEvent> {
+        if !self.touches.contains_key(&id) {
+            return Vec::new();
+        }
+        let out = self.end(GesturePhase::End);
+        self.touches.remove(&id);
+        out
+    }
+
+    /// The toolkit cancelled all touches; active gestures end as cancelled.
+    pub fn cancel(&mut self) -> Vec<InputEvent> {
+        let mut out = self.end(GesturePhase::Cancelled);
+        out.extend(self.zoom.take().map(|(x, y)| InputEvent::GesturePinch {
+            x,
+            y,
+            magnification: 0.0,
+            phase: GesturePhase::Cancelled,
+        }));
+        self.touches.clear();
+        out
+    }
+
+    /// One zoom factor step (`magnification` = factor − 1) at `(x, y)`:
+    /// begins a pinch, or continues the active one.
+    pub fn zoom(&mut self, x: f64, y: f64, magnification: f64) -> InputEvent {
+        let phase = if self.zoom.replace((x, y)).is_some() { GesturePhase::Changed } else { GesturePhase::Begin };
+        InputEvent::GesturePinch { x, y, magnification, phase }
+    }
+
+    /// End the active zoom-factor pinch, if any — call once zoom steps stop.
+    pub fn end_zoom(&mut self) -> Option<InputEvent> {
+        self.zoom.take().map(|(x, y)| InputEvent::GesturePinch { x, y, magnification: 0.0, phase: GesturePhase::End })
+    }
+
+    /// End events for the active touch gestures.
+    fn end(&mut self, phase: GesturePhase) -> Vec<InputEvent> {
+        let (x, y) = self.centroid();
+        let mut out = Vec::new();
+        if self.two_finger.take().is_some() {
+            out.push(InputEvent::GesturePinch { x, y, magnification: 0.0, phase });
+            out.push(InputEvent::GestureRotation { x, y, rotation: 0.0, phase });
+        }
+        if self.swipe.take().is_some() {
+            out.push(InputEvent::GestureSwipe { delta_x: 0.0, delta_y: 0.0, phase });
+        }
+        out
+    }
+
+    fn centroid(&self) -> (f64, f64) {
+        let n = self.touches.len().max(1) as f64;
+        let (sx, sy) = self.touches.values().fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+        (sx / n, sy / n)
+    }
+
+    /// Spread and angle (degrees, clockwise on screen) of exactly two touches.
+    fn spread_and_angle(&self) -> Option<(f64, f64)> {
+        let mut points = self.touches.values();
+        let (&(x0, y0), &(x1, y1)) = (points.next()?, points.next()?);
+        if points.next().is_some() {
+            return None;
+        }
+        let (dx, dy) = ((x1 - x0) * self.aspect, y1 - y0);
+        Some((dx.hypot(dy), dy.atan2(dx).to_degrees()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phases(events: &[InputEvent]) -> Vec<(&'static str, GesturePhase)> {
+        events
+            .iter()
+            .map(|e| match e {
+                InputEvent::GesturePinch { phase, .. } => ("pinch", *phase),
+                InputEvent::GestureRotation { phase, .. } => ("rotation", *phase),
+                InputEvent::GestureSwipe { phase, .. } => ("swipe", *phase),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn two_finger_spread_and_twist() {
+        let mut t = GestureTracker::new(1.0);
+        assert!(t.touch_down(1, 0.4, 0.5).is_empty());
+        let begin = t.touch_down(2, 0.6, 0.5);
+        assert_eq!(phases(&begin), [("pinch", GesturePhase::Begin), ("rotation", GesturePhase::Begin)]);
+
+        // Move the second finger straight down: spread grows, angle turns clockwise.
+        let changed = t.touch_move(2, 0.6, 0.7);
+        let InputEvent::GesturePinch { magnification, .. } = changed[0] else { panic!() };
+        let InputEvent::GestureRotation { rotation, .. } = changed[1] else { panic!() };
+        assert!((magnification - (2f64.sqrt() - 1.0)).abs() < 1e-9);
+        assert!((rotation - 45.0).abs() < 1e-9);
+
+        let end = t.touch_up(1);
+        assert_eq!(phases(&end), [("pinch", GesturePhase::End), ("rotation", GesturePhase::End)]);
+        assert!(t.touch_move(2, 0.5, 0.5).is_empty());
+    }
+
+    #[test]
+    fn third_finger_turns_pinch_into_swipe() {
+        let mut t = GestureTracker::default();
+        t.touch_down(1, 0.2, 0.5);
+        t.touch_down(2, 0.4, 0.5);
+        let events = t.touch_down(3, 0.6, 0.5);
+        assert_eq!(
+            phases(&events),
+            [("pinch", GesturePhase::End), ("rotation", GesturePhase::End), ("swipe", GesturePhase::Begin)]
+        );
+        let events = t.touch_move(3, 0.9, 0.5);
+        let InputEvent::GestureSwipe { delta_x, delta_y, phase } = events[0] else { panic!() };
+        assert!((delta_x - 0.1).abs() < 1e-9 && delta_y == 0.0 && phase == GesturePhase::Changed);
+        assert_eq!(phases(&t.cancel()), [("swipe", GesturePhase::Cancelled)]);
+    }
+
+    #[test]
+    fn zoom_steps_form_one_pinch() {
+        let mut t = GestureTracker::default();
+        assert_eq!(phases(&[t.zoom(0.5, 0.5, 0.1)]), [("pinch", GesturePhase::Begin)]);
+        assert_eq!(phases(&[t.zoom(0.5, 0.5, 0.1)]), [("pinch", GesturePhase::Changed)]);
+        assert_eq!(phases(&[t.end_zoom().unwrap()]), [("pinch", GesturePhase::End)]);
+        assert!(t.end_zoom().is_none());
+    }
+}
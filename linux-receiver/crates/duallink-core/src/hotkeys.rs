@@ -0,0 +1,363 @@
+//! Receiver window hotkeys.
+//!
+//! Key chords typed into a display window are checked against a [`Keymap`]
+//! before they are forwarded to the sender. A matching chord triggers a
+//! [`HotkeyAction`] on the receiver and its key events never reach the
+//! sender. Default bindings:
+//!
+//! | Chord        | Action                                  |
+//! |--------------|-----------------------------------------|
+//! | `Ctrl+Alt+F` | [`HotkeyAction::ToggleFullscreen`]      |
+//! | `Ctrl+Alt+S` | [`HotkeyAction::ToggleStats`]           |
+//! | `Ctrl+Alt+P` | [`HotkeyAction::ToggleFreeze`]          |
+//! | `Ctrl+Alt+B` | [`HotkeyAction::ToggleBlank`]           |
+//! | `Ctrl+Alt+D` | [`HotkeyAction::ReleaseInput`]          |
+//! | `Ctrl+Alt+Q` | [`HotkeyAction::EndSession`]            |
+//! | `Ctrl+Alt+T` | [`HotkeyAction::RecordTrace`]           |
+//! | `Ctrl+Alt+O` | [`HotkeyAction::ToggleOverlays`]        |
+//! | `Ctrl+Alt+K` | [`HotkeyAction::CaptureSystemKeys`]     |
+//!
+//! The saved settings' `hotkeys` map rebinds actions, e.g.
+//! `{"endSession": "Ctrl+Shift+F12"}`; an empty string unbinds one.
+//!
+//! Hotkeys are picked out of the window's key events, so they keep working
+//! while system keys are captured; whether windows start that way is
+//! [`configured_capture_system_keys`].
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{keyval_from_name, InputEvent, ReceiverSettings};
+
+// MARK: - HotkeyAction
+
+/// What a hotkey does on the receiver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HotkeyAction {
+    /// Toggle the display window between windowed and fullscreen.
+    ToggleFullscreen,
+    /// Show or hide the statistics overlay.
+    ToggleStats,
+    /// Hold the current frame while the sender keeps streaming; pressed
+    /// again, jump back to live.
+    ToggleFreeze,
+    /// Blank the window and have the sender pause capture (privacy mode);
+    /// pressed again, resume.
+    ToggleBlank,
+    /// Stop forwarding input to the sender until pressed again.
+    ReleaseInput,
+    /// End the session and close the window.
+    EndSession,
+    /// Record a frame timing trace (see [`crate::trace`]).
+    RecordTrace,
+    /// Hide or show the configured overlay widgets (see [`crate::overlay`]).
+    ToggleOverlays,
+    /// Grab the keyboard so Alt+Tab, Super and other desktop shortcuts go
+    /// to the sender; pressed again, leave them to the receiver's desktop.
+    CaptureSystemKeys,
+}
+
+impl HotkeyAction {
+    pub const ALL: [Self; 9] = [
+        Self::ToggleFullscreen,
+        Self::ToggleStats,
+        Self::ToggleFreeze,
+        Self::ToggleBlank,
+        Self::ReleaseInput,
+        Self::EndSession,
+        Self::RecordTrace,
+        Self::ToggleOverlays,
+        Self::CaptureSystemKeys,
+    ];
+
+    /// The chord bound when the settings don't rebind the action.
+    pub fn default_chord(self) -> &'static str {
+        match self {
+            Self::ToggleFullscreen => "Ctrl+Alt+F",
+            Self::ToggleStats      => "Ctrl+Alt+S",
+            Self::ToggleFreeze     => "Ctrl+Alt+P",
+            Self::ToggleBlank      => "Ctrl+Alt+B",
+            Self::ReleaseInput     => "Ctrl+Alt+D",
+            Self::EndSession       => "Ctrl+Alt+Q",
+            Self::RecordTrace      => "Ctrl+Alt+T",
+            Self::ToggleOverlays   => "Ctrl+Alt+O",
+            Self::CaptureSystemKeys => "Ctrl+Alt+K",
+        }
+    }
+}
+
+/// Whether display windows start with system keys captured:
+/// `DUALLINK_CAPTURE_SYSTEM_KEYS=1`, else the settings' `captureSystemKeys`.
+pub fn configured_capture_system_keys() -> bool {
+    match std::env::var("DUALLINK_CAPTURE_SYSTEM_KEYS") {
+        Ok(v) => v == "1",
+        Err(_) => ReceiverSettings::load().capture_system_keys,
+    }
+}
+
+// MARK: - Hotkey
+
+const CTRL: u8 = 1;
+const ALT: u8 = 2;
+const SHIFT: u8 = 4;
+const SUPER: u8 = 8;
+
+/// Modifier bit of an X11 keyval; 0 for other keys.
+fn modifier_bit(keycode: u32) -> u8 {
+    match keycode {
+        0xffe1 | 0xffe2 => SHIFT,
+        0xffe3 | 0xffe4 => CTRL,
+        0xffe9 | 0xffea => ALT,
+        0xffeb | 0xffec => SUPER,
+        _ => 0,
+    }
+}
+
+/// Letters match regardless of Shift / Caps Lock.
+fn fold_case(keycode: u32) -> u32 {
+    match keycode {
+        0x41..=0x5a => keycode + 0x20,
+        _ => keycode,
+    }
+}
+
+/// A key chord: modifiers plus one X11 keyval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkey {
+    modifiers: u8,
+    keycode:   u32,
+}
+
+impl Hotkey {
+    /// Parse `"Ctrl+Alt+F"`-style chords. Modifiers are `Ctrl`/`Control`,
+    /// `Alt`, `Shift` and `Super`/`Meta`/`Win`, in any order and case; the key
+    /// is a character or an X11 key name (`F11`, `Escape`, `Delete`, …).
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut modifiers = 0;
+        let mut key = None;
+        for part in s.split('+').map(str::trim) {
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= CTRL,
+                "alt" => modifiers |= ALT,
+                "shift" => modifiers |= SHIFT,
+                "super" | "meta" | "win" => modifiers |= SUPER,
+                _ if key.is_none() && !part.is_empty() => key = Some(part),
+                _ => return None,
+            }
+        }
+        let keycode = fold_case(keyval_from_name(key?));
+        (keycode != 0).then_some(Self { modifiers, keycode })
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (bit, name) in [(CTRL, "Ctrl+"), (ALT, "Alt+"), (SHIFT, "Shift+"), (SUPER, "Super+")] {
+            if self.modifiers & bit != 0 {
+                f.write_str(name)?;
+            }
+        }
+        match char::from_u32(self.keycode).filter(|c| c.is_ascii_graphic()) {
+            Some(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            None => write!(f, "0x{:x}", self.keycode),
+        }
+    }
+}
+
+// MARK: - Keymap
+
+/// Hotkey → action bindings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<(Hotkey, HotkeyAction)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_overrides(&BTreeMap::new())
+    }
+}
+
+impl Keymap {
+    /// Default bindings with `overrides` applied. An empty chord unbinds the
+    /// action; an unparsable one keeps the default.
+    pub fn from_overrides(overrides: &BTreeMap<HotkeyAction, String>) -> Self {
+        let bindings = HotkeyAction::ALL
+            .into_iter()
+            .filter_map(|action| {
+                let chord = overrides.get(&action).map(String::as_str).unwrap_or(action.default_chord());
+                if chord.trim().is_empty() {
+                    return None;
+                }
+                let hotkey = Hotkey::parse(chord).or_else(|| {
+                    warn!("Invalid hotkey '{}' for {:?} — using {}", chord, action, action.default_chord());
+                    Hotkey::parse(action.default_chord())
+                })?;
+                Some((hotkey, action))
+            })
+            .collect();
+        Self { bindings }
+    }
+
+    /// Bindings from the saved [`ReceiverSettings`].
+    pub fn configured() -> Self {
+        Self::from_overrides(&ReceiverSettings::load().hotkeys)
+    }
+
+    /// The action bound to `keycode` pressed with exactly `modifiers` held.
+    fn action_for(&self, modifiers: u8, keycode: u32) -> Option<HotkeyAction> {
+        let keycode = fold_case(keycode);
+        self.bindings
+            .iter()
+            .find(|(h, _)| h.keycode == keycode && h.modifiers == modifiers)
+            .map(|(_, action)| *action)
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = &(Hotkey, HotkeyAction)> {
+        self.bindings.iter()
+    }
+}
+
+// MARK: - HotkeyFilter
+
+/// Result of passing one event through a [`HotkeyFilter`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filtered {
+    /// Not a hotkey — forward to the sender.
+    Forward(InputEvent),
+    /// A hotkey was pressed; its key event is consumed.
+    Hotkey(HotkeyAction),
+    /// Part of a hotkey already handled (key release, auto-repeat).
+    Consumed,
+}
+
+/// Tracks held modifiers across window key events and pulls hotkeys out of
+/// the stream before it is forwarded.
+///
+/// Modifier presses are always forwarded: `Ctrl` alone is still `Ctrl` on
+/// the sender. Only the chord's final key (press, repeats and release) is
+/// consumed.
+#[derive(Debug, Clone)]
+pub struct HotkeyFilter {
+    keymap:    Keymap,
+    /// Modifier keyvals currently held, in press order.
+    held:      Vec<u32>,
+    /// Hotkey keyvals (case-folded) whose release is still to be consumed.
+    swallowed: Vec<u32>,
+}
+
+impl HotkeyFilter {
+    pub fn new(keymap: Keymap) -> Self {
+        Self { keymap, held: Vec::new(), swallowed: Vec::new() }
+    }
+
+    fn modifiers(&self) -> u8 {
+        self.held.iter().fold(0, |m, &k| m | modifier_bit(k))
+    }
+
+    pub fn filter(&mut self, event: InputEvent) -> Filtered {
+        match event {
+            InputEvent::KeyDown { keycode, .. } if modifier_bit(keycode) != 0 => {
+                if !self.held.contains(&keycode) {
+                    self.held.push(keycode);
+                }
+                Filtered::Forward(event)
+            }
+            InputEvent::KeyDown { keycode, .. } => {
+                let folded = fold_case(keycode);
+                if self.swallowed.contains(&folded) {
+                    return Filtered::Consumed;
+                }
+                match self.keymap.action_for(self.modifiers(), keycode) {
+                    Some(action) => {
+                        self.swallowed.push(folded);
+                        Filtered::Hotkey(action)
+                    }
+                    None => Filtered::Forward(event),
+                }
+            }
+            InputEvent::KeyUp { keycode } => {
+                self.held.retain(|&k| k != keycode);
+                let folded = fold_case(keycode);
+                match self.swallowed.iter().position(|&k| k == folded) {
+                    Some(i) => {
+                        self.swallowed.remove(i);
+                        Filtered::Consumed
+                    }
+                    None => Filtered::Forward(event),
+                }
+            }
+            other => Filtered::Forward(other),
+        }
+    }
+
+    /// Releases for every modifier held right now. Sent to the sender before
+    /// input stops being forwarded so no modifier stays stuck down there.
+    pub fn release_modifiers(&self) -> Vec<InputEvent> {
+        self.held.iter().map(|&keycode| InputEvent::KeyUp { keycode }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CTRL_L: u32 = 0xffe3;
+    const ALT_L: u32 = 0xffe9;
+
+    fn down(keycode: u32) -> InputEvent {
+        InputEvent::KeyDown { keycode, text: None }
+    }
+
+    #[test]
+    fn parses_chords() {
+        assert_eq!(Hotkey::parse("ctrl + alt + f"), Hotkey::parse("Alt+Control+F"));
+        assert_eq!(Hotkey::parse("Ctrl+Alt+F").unwrap().to_string(), "Ctrl+Alt+F");
+        assert_eq!(Hotkey::parse("Shift+F12").unwrap().keycode, 0xffc9);
+        assert_eq!(Hotkey::parse("Ctrl+A+B"), None);
+        assert_eq!(Hotkey::parse("Ctrl+"), None);
+    }
+
+    #[test]
+    fn overrides_rebind_and_unbind() {
+        let settings: ReceiverSettings =
+            serde_json::from_str(r#"{"hotkeys":{"endSession":"Ctrl+Shift+F12","toggleStats":""}}"#).unwrap();
+        let keymap = Keymap::from_overrides(&settings.hotkeys);
+        let actions: Vec<_> = keymap.bindings().map(|(_, a)| *a).collect();
+        assert_eq!(
+            actions,
+            [
+                HotkeyAction::ToggleFullscreen,
+                HotkeyAction::ToggleFreeze,
+                HotkeyAction::ToggleBlank,
+                HotkeyAction::ReleaseInput,
+                HotkeyAction::EndSession,
+                HotkeyAction::RecordTrace,
+                HotkeyAction::ToggleOverlays,
+                HotkeyAction::CaptureSystemKeys,
+            ]
+        );
+        assert_eq!(keymap.action_for(CTRL | SHIFT, 0xffc9), Some(HotkeyAction::EndSession));
+        assert_eq!(keymap.action_for(CTRL | ALT, 'q' as u32), None);
+    }
+
+    #[test]
+    fn filter_consumes_only_the_chord_key() {
+        let mut filter = HotkeyFilter::new(Keymap::default());
+        assert!(matches!(filter.filter(down(CTRL_L)), Filtered::Forward(_)));
+        assert!(matches!(filter.filter(down(ALT_L)), Filtered::Forward(_)));
+        assert_eq!(filter.filter(down('F' as u32)), Filtered::Hotkey(HotkeyAction::ToggleFullscreen));
+        // Auto-repeat does not retrigger.
+        assert_eq!(filter.filter(down('f' as u32)), Filtered::Consumed);
+        assert_eq!(filter.filter(InputEvent::KeyUp { keycode: 'f' as u32 }), Filtered::Consumed);
+        assert_eq!(filter.release_modifiers().len(), 2);
+
+        // Without Alt, Ctrl+F goes to the sender.
+        assert!(matches!(filter.filter(InputEvent::KeyUp { keycode: ALT_L }), Filtered::Forward(_)));
+        assert!(matches!(filter.filter(down('f' as u32)), Filtered::Forward(_)));
+    }
+}
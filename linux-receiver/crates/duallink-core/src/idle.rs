@@ -0,0 +1,123 @@
+//! Idle streams: a lower rate while nobody uses the shared screen.
+//!
+//! A sender left running all day mostly streams a static desktop. Its
+//! session feeds an [`IdleDetector`] every input event the receiver
+//! forwards and every encoded frame; once neither input nor a
+//! [changed frame](CHANGED_FRAME_BYTES) came for the configured period, the
+//! stream drops to [`IDLE_FPS`] and at most [`IDLE_BITRATE_KBPS`], and goes
+//! back to its own rate at the next input event or changed frame.
+//!
+//! The period is set with `DUALLINK_IDLE_SECS` ([`DEFAULT_IDLE_AFTER`] if
+//! unset, `0` = never idle).
+
+use std::time::{Duration, Instant};
+
+/// Frame rate of an idle stream.
+pub const IDLE_FPS: u32 = 5;
+
+/// Bitrate ceiling of an idle stream.
+pub const IDLE_BITRATE_KBPS: u32 = 500;
+
+/// Quiet period before a stream goes idle unless configured otherwise.
+pub const DEFAULT_IDLE_AFTER: Duration = Duration::from_secs(120);
+
+/// Inter frames at least this large count as a changed screen; a blinking
+/// cursor or a ticking clock stays below it.
+pub const CHANGED_FRAME_BYTES: usize = 4096;
+
+/// The configured quiet period: `DUALLINK_IDLE_SECS`, else
+/// [`DEFAULT_IDLE_AFTER`]. `ZERO` = never idle.
+pub fn configured_idle_after() -> Duration {
+    match std::env::var("DUALLINK_IDLE_SECS") {
+        Err(_) => DEFAULT_IDLE_AFTER,
+        Ok(v) => v.trim().parse().map(Duration::from_secs).unwrap_or_else(|_| {
+            tracing::warn!("Ignoring DUALLINK_IDLE_SECS='{v}' — expected seconds");
+            DEFAULT_IDLE_AFTER
+        }),
+    }
+}
+
+// MARK: - IdleDetector
+
+/// Whether a stream had input or a changed frame within its quiet period.
+#[derive(Debug, Clone)]
+pub struct IdleDetector {
+    idle_after:    Duration,
+    last_activity: Instant,
+    idle:          bool,
+}
+
+impl IdleDetector {
+    /// A detector, active at `now`, that goes idle after `idle_after`
+    /// without activity (`ZERO` = never).
+    pub fn new(idle_after: Duration, now: Instant) -> Self {
+        Self { idle_after, last_activity: now, idle: false }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// Input arrived at `now`. Returns `true` if this ended idling.
+    pub fn input(&mut self, now: Instant) -> bool {
+        self.last_activity = now;
+        std::mem::replace(&mut self.idle, false)
+    }
+
+    /// A frame of `bytes` was encoded at `now`. Keyframes say nothing about
+    /// change; other frames of at least [`CHANGED_FRAME_BYTES`] count as
+    /// activity. Returns `true` if this ended idling.
+    pub fn frame(&mut self, bytes: usize, keyframe: bool, now: Instant) -> bool {
+        if keyframe || bytes < CHANGED_FRAME_BYTES {
+            return false;
+        }
+        self.input(now)
+    }
+
+    /// Returns `true` if the stream went idle by `now`.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        if self.idle || self.idle_after.is_zero() || now.saturating_duration_since(self.last_activity) < self.idle_after {
+            return false;
+        }
+        self.idle = true;
+        true
+    }
+
+    /// `(kbps, fps)` lowered to the idle rate while idle.
+    pub fn cap(&self, kbps: u32, fps: u32) -> (u32, u32) {
+        if self.idle {
+            (kbps.min(IDLE_BITRATE_KBPS), fps.min(IDLE_FPS))
+        } else {
+            (kbps, fps)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiet_streams_idle_until_input_or_change() {
+        let t0 = Instant::now();
+        let secs = |s| t0 + Duration::from_secs(s);
+        let mut idle = IdleDetector::new(Duration::from_secs(60), t0);
+        // Small inter frames and keyframes are no activity.
+        assert!(!idle.frame(800, false, secs(30)));
+        assert!(!idle.frame(90_000, true, secs(40)));
+        assert!(!idle.poll(secs(59)));
+        assert!(idle.poll(secs(60)));
+        assert!(!idle.poll(secs(61)));
+        assert_eq!(idle.cap(8_000, 60), (IDLE_BITRATE_KBPS, IDLE_FPS));
+
+        assert!(idle.frame(20_000, false, secs(70)));
+        assert_eq!(idle.cap(8_000, 60), (8_000, 60));
+        assert!(!idle.poll(secs(129)));
+        assert!(idle.poll(secs(130)));
+        assert!(idle.input(secs(131)));
+        assert!(!idle.input(secs(132)));
+
+        let mut never = IdleDetector::new(Duration::ZERO, t0);
+        assert!(!never.poll(secs(100_000)));
+    }
+}
@@ -11,7 +11,7 @@ use serde::{Deserialize, Serialize};
 // MARK: - InputEvent
 
 /// A user input event captured from the Linux display window.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum InputEvent {
     /// Mouse moved to (x, y) in normalised coordinates [0.0, 1.0].
@@ -122,6 +122,64 @@ pub enum MouseButton {
     Middle,
 }
 
+// MARK: - Keyvals
+
+/// Map a GStreamer/X11 key name to a keyval; 0 if unknown.
+///
+/// GStreamer sends X11 key names (e.g. "a", "Return", "Shift_L", "space").
+/// We pass the raw X11 keyval so the Mac side can map it.
+pub fn keyval_from_name(name: &str) -> u32 {
+    // Common special keys — full mapping via xkbcommon if needed later
+    match name {
+        "Return" | "KP_Enter" => 0xff0d,
+        "Escape" => 0xff1b,
+        "Tab" => 0xff09,
+        "BackSpace" => 0xff08,
+        "Delete" => 0xffff,
+        "space" => 0x0020,
+        "Shift_L" => 0xffe1,
+        "Shift_R" => 0xffe2,
+        "Control_L" => 0xffe3,
+        "Control_R" => 0xffe4,
+        "Alt_L" => 0xffe9,
+        "Alt_R" => 0xffea,
+        "Super_L" => 0xffeb,
+        "Super_R" => 0xffec,
+        "Left" => 0xff51,
+        "Up" => 0xff52,
+        "Right" => 0xff53,
+        "Down" => 0xff54,
+        "Home" => 0xff50,
+        "End" => 0xff57,
+        "Page_Up" => 0xff55,
+        "Page_Down" => 0xff56,
+        "F1" => 0xffbe,
+        "F2" => 0xffbf,
+        "F3" => 0xffc0,
+        "F4" => 0xffc1,
+        "F5" => 0xffc2,
+        "F6" => 0xffc3,
+        "F7" => 0xffc4,
+        "F8" => 0xffc5,
+        "F9" => 0xffc6,
+        "F10" => 0xffc7,
+        "F11" => 0xffc8,
+        "F12" => 0xffc9,
+        "Caps_Lock" => 0xffe5,
+        _ => {
+            // For single-char keys, use the Unicode codepoint
+            let mut chars = name.chars();
+            if let Some(c) = chars.next() {
+                if chars.next().is_none() {
+                    return c as u32;
+                }
+            }
+            // Unknown — pass name hash as fallback
+            0
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,174 @@
+//! Input macros — timestamped [`InputEvent`]s recorded on the receiver,
+//! saved to a file and replayed later (by `duallink_transport::InputSender`).
+//!
+//! Files are JSON lines, one [`TimedInputEvent`] per line with its offset
+//! from the start of the recording:
+//!
+//! ```text
+//! {"atMs":0,"event":{"kind":"mouse_move","x":0.5,"y":0.5}}
+//! {"atMs":140,"event":{"kind":"mouse_down","x":0.5,"y":0.5,"button":"left"}}
+//! ```
+
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::config_file;
+use crate::InputEvent;
+
+// MARK: - TimedInputEvent
+
+/// One recorded event, `at_ms` after the recording started.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimedInputEvent {
+    pub at_ms: u64,
+    pub event: InputEvent,
+}
+
+// MARK: - InputRecording
+
+/// A finished recording, in the order the events were sent.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputRecording {
+    pub events: Vec<TimedInputEvent>,
+}
+
+impl InputRecording {
+    /// `input-macro.jsonl` in the DualLink config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        config_file("input-macro.jsonl")
+    }
+
+    /// Offset of the last event.
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.events.last().map_or(0, |e| e.at_ms))
+    }
+
+    /// Write as JSON lines.
+    pub fn write_to(&self, mut out: impl Write) -> std::io::Result<()> {
+        for event in &self.events {
+            serde_json::to_writer(&mut out, event)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()
+    }
+
+    /// Read JSON lines; blank lines are skipped, events are put back in
+    /// time order.
+    pub fn read_from(input: impl BufRead) -> std::io::Result<Self> {
+        let mut events = Vec::new();
+        for (n, line) in input.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event: TimedInputEvent = serde_json::from_str(&line).map_err(|e| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, format!("line {}: {e}", n + 1))
+            })?;
+            events.push(event);
+        }
+        events.sort_by_key(|e| e.at_ms);
+        Ok(Self { events })
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        self.write_to(std::io::BufWriter::new(std::fs::File::create(path)?))
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        Self::read_from(std::io::BufReader::new(std::fs::File::open(path)?))
+    }
+}
+
+// MARK: - InputRecorder
+
+/// Collects events as they are sent, stamped relative to when it was
+/// created.
+#[derive(Debug)]
+pub struct InputRecorder {
+    started: Instant,
+    events:  Vec<TimedInputEvent>,
+}
+
+impl Default for InputRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputRecorder {
+    pub fn new() -> Self {
+        Self { started: Instant::now(), events: Vec::new() }
+    }
+
+    pub fn record(&mut self, event: &InputEvent) {
+        let at_ms = self.started.elapsed().as_millis() as u64;
+        self.events.push(TimedInputEvent { at_ms, event: event.clone() });
+    }
+
+    /// Events recorded so far.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn finish(self) -> InputRecording {
+        InputRecording { events: self.events }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moved(x: f64) -> InputEvent {
+        InputEvent::MouseMove { x, y: 0.5 }
+    }
+
+    #[test]
+    fn json_lines_round_trip() {
+        let recording = InputRecording {
+            events: vec![
+                TimedInputEvent { at_ms: 0, event: moved(0.1) },
+                TimedInputEvent { at_ms: 40, event: moved(0.2) },
+            ],
+        };
+        let mut file = Vec::new();
+        recording.write_to(&mut file).unwrap();
+        assert_eq!(String::from_utf8_lossy(&file).lines().count(), 2);
+        assert_eq!(InputRecording::read_from(&file[..]).unwrap(), recording);
+        assert_eq!(recording.duration(), Duration::from_millis(40));
+    }
+
+    #[test]
+    fn bad_lines_are_reported_and_order_is_restored() {
+        let file = "{\"atMs\":20,\"event\":{\"kind\":\"mouse_move\",\"x\":0.2,\"y\":0.5}}\n\n\
+                    {\"atMs\":10,\"event\":{\"kind\":\"mouse_move\",\"x\":0.1,\"y\":0.5}}\n";
+        let recording = InputRecording::read_from(file.as_bytes()).unwrap();
+        assert_eq!(recording.events.iter().map(|e| e.at_ms).collect::<Vec<_>>(), [10, 20]);
+
+        let err = InputRecording::read_from("{\"atMs\":1}\n".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("line 1:"));
+    }
+
+    #[test]
+    fn recorder_stamps_in_order() {
+        let mut recorder = InputRecorder::new();
+        recorder.record(&moved(0.1));
+        std::thread::sleep(Duration::from_millis(5));
+        recorder.record(&moved(0.2));
+        let recording = recorder.finish();
+        assert_eq!(recording.events.len(), 2);
+        assert!(recording.events[1].at_ms >= recording.events[0].at_ms + 5);
+    }
+}
@@ -0,0 +1,186 @@
+//! Versioned wire schema for [`InputEvent`].
+//!
+//! Input events cross a language boundary: the receivers serialise them
+//! with serde, the Mac client decodes them with a hand-written `Codable`
+//! (`mac-client/Sources/DualLinkCore/Models.swift`). [`INPUT_SCHEMA`] lists
+//! the fields of every event kind in schema version
+//! [`INPUT_SCHEMA_VERSION`]; any change that an older peer could not decode
+//! bumps the version. The golden fixtures in
+//! `fixtures/input-events-v1.json` hold one event of every kind in the
+//! exact wire form and are checked by the tests here and by the Swift
+//! tests, so both sides fail when either drifts.
+//!
+//! # Parsing
+//!
+//! [`parse_input_event`] checks an event against the schema first:
+//!
+//! - [`InputParseMode::Strict`] rejects unknown kinds and unknown fields —
+//!   for conformance testing, and the default with the `strict-input`
+//!   feature.
+//! - [`InputParseMode::Compatible`] (default) ignores fields a newer client
+//!   added and skips events of kinds it does not know, so the session goes
+//!   on.
+//!
+//! Senders parse `inputEvent` with [`deserialize_input_event`], which uses
+//! the configured mode.
+
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+use crate::errors::InputSchemaError;
+use crate::input::InputEvent;
+
+/// Version of the input event wire format.
+pub const INPUT_SCHEMA_VERSION: u32 = 1;
+
+// MARK: - Schema
+
+/// Fields of one event kind, besides `kind` itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KindSchema {
+    /// Value of the `kind` tag.
+    pub kind:     &'static str,
+    pub required: &'static [&'static str],
+    /// Fields left out when unset.
+    pub optional: &'static [&'static str],
+}
+
+impl KindSchema {
+    const fn new(kind: &'static str, required: &'static [&'static str]) -> Self {
+        Self { kind, required, optional: &[] }
+    }
+
+    fn allows(&self, field: &str) -> bool {
+        field == "kind" || self.required.contains(&field) || self.optional.contains(&field)
+    }
+}
+
+/// Every event kind of schema version [`INPUT_SCHEMA_VERSION`].
+pub const INPUT_SCHEMA: &[KindSchema] = &[
+    KindSchema::new("mouse_move", &["x", "y"]),
+    KindSchema::new("mouse_down", &["x", "y", "button"]),
+    KindSchema::new("mouse_up", &["x", "y", "button"]),
+    KindSchema::new("mouse_scroll", &["x", "y", "delta_x", "delta_y"]),
+    KindSchema { kind: "key_down", required: &["keycode"], optional: &["text"] },
+    KindSchema::new("key_up", &["keycode"]),
+    KindSchema::new("gesture_pinch", &["x", "y", "magnification", "phase"]),
+    KindSchema::new("gesture_rotation", &["x", "y", "rotation", "phase"]),
+    KindSchema::new("gesture_swipe", &["delta_x", "delta_y", "phase"]),
+    KindSchema::new("scroll_smooth", &["x", "y", "delta_x", "delta_y", "phase"]),
+];
+
+/// The schema of `kind`; `None` for kinds this version does not know.
+pub fn kind_schema(kind: &str) -> Option<&'static KindSchema> {
+    INPUT_SCHEMA.iter().find(|s| s.kind == kind)
+}
+
+// MARK: - Parsing
+
+/// How strictly [`parse_input_event`] follows the schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputParseMode {
+    /// Unknown kinds and fields are errors.
+    Strict,
+    /// Unknown fields are ignored and unknown kinds skipped.
+    Compatible,
+}
+
+impl InputParseMode {
+    /// [`Strict`](Self::Strict) with the `strict-input` feature,
+    /// [`Compatible`](Self::Compatible) otherwise.
+    pub const fn configured() -> Self {
+        if cfg!(feature = "strict-input") {
+            Self::Strict
+        } else {
+            Self::Compatible
+        }
+    }
+}
+
+/// Parse one wire event. `Ok(None)` is a compatible-mode skip of a kind
+/// from a newer schema.
+pub fn parse_input_event(value: Value, mode: InputParseMode) -> Result<Option<InputEvent>, InputSchemaError> {
+    let Value::Object(fields) = &value else {
+        return Err(InputSchemaError::NotAnObject);
+    };
+    let kind = fields.get("kind").and_then(Value::as_str).ok_or(InputSchemaError::MissingKind)?;
+    let Some(schema) = kind_schema(kind) else {
+        return match mode {
+            InputParseMode::Strict => Err(InputSchemaError::UnknownKind(kind.to_owned())),
+            InputParseMode::Compatible => Ok(None),
+        };
+    };
+    if mode == InputParseMode::Strict {
+        if let Some(field) = fields.keys().find(|f| !schema.allows(f)) {
+            return Err(InputSchemaError::UnknownField { kind: kind.to_owned(), field: field.clone() });
+        }
+    }
+    let kind = schema.kind;
+    serde_json::from_value(value).map(Some).map_err(|source| InputSchemaError::Invalid { kind, source })
+}
+
+/// `deserialize_with` for an optional `inputEvent` field, parsed in the
+/// [configured](InputParseMode::configured) mode. Events skipped in
+/// compatible mode come out as `None`.
+pub fn deserialize_input_event<'de, D>(deserializer: D) -> Result<Option<InputEvent>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(value) = Option::<Value>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    let event = parse_input_event(value, InputParseMode::configured()).map_err(serde::de::Error::custom)?;
+    if event.is_none() {
+        tracing::debug!("Skipping input event of a kind from a newer schema");
+    }
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES_V1: &str = include_str!("../fixtures/input-events-v1.json");
+
+    #[test]
+    fn golden_fixtures_cover_every_kind_and_round_trip() {
+        let fixtures: Vec<Value> = serde_json::from_str(FIXTURES_V1).unwrap();
+        for schema in INPUT_SCHEMA {
+            assert!(
+                fixtures.iter().any(|f| f["kind"] == schema.kind),
+                "no fixture for input event kind {}",
+                schema.kind
+            );
+        }
+        for fixture in fixtures {
+            let event = parse_input_event(fixture.clone(), InputParseMode::Strict).unwrap().unwrap();
+            assert_eq!(serde_json::to_value(&event).unwrap(), fixture);
+        }
+    }
+
+    #[test]
+    fn compatible_mode_tolerates_additive_changes() {
+        let newer = serde_json::json!({"kind": "mouse_move", "x": 0.5, "y": 0.5, "pressure": 0.8});
+        assert_eq!(
+            parse_input_event(newer.clone(), InputParseMode::Compatible).unwrap(),
+            Some(InputEvent::MouseMove { x: 0.5, y: 0.5 })
+        );
+        assert!(matches!(
+            parse_input_event(newer, InputParseMode::Strict),
+            Err(InputSchemaError::UnknownField { field, .. }) if field == "pressure"
+        ));
+
+        let unknown = serde_json::json!({"kind": "pen_tilt", "x": 0.1});
+        assert_eq!(parse_input_event(unknown.clone(), InputParseMode::Compatible).unwrap(), None);
+        assert!(matches!(
+            parse_input_event(unknown, InputParseMode::Strict),
+            Err(InputSchemaError::UnknownKind(kind)) if kind == "pen_tilt"
+        ));
+
+        let broken = serde_json::json!({"kind": "key_up"});
+        assert!(matches!(
+            parse_input_event(broken, InputParseMode::Compatible),
+            Err(InputSchemaError::Invalid { kind: "key_up", .. })
+        ));
+    }
+}
@@ -0,0 +1,182 @@
+//! Temporal layer markers: frames the receiver may drop under load.
+//!
+//! A frame no other frame references can be skipped without damaging the
+//! picture; with every other frame such a one, skipping them halves the
+//! frame rate. Senders find these frames in the bitstream
+//! ([`temporal_layer`]: H.264 slices with `nal_ref_idc` 0, H.265 pictures
+//! above temporal sub-layer 0 or not referenced within it) and mark them as
+//! layer [`DROPPABLE_LAYER`] in the DLNK header. Encoders only produce them
+//! with hierarchical-P prediction (`vaapih264enc temporal-levels=2`);
+//! elsewhere every frame is layer 0 and nothing is ever dropped.
+//!
+//! The receiver's decode thread runs a [`LayerShedder`]: once
+//! [`SHED_ABOVE`] frames queue up in front of the decoder it drops layered
+//! frames until the queue has drained to [`SHED_UNTIL`] — 60 fps degrades
+//! to 30 fps instead of latency piling up.
+//!
+//! Displays with a frame-rate cap (`StreamLimits::max_fps`, for e-ink and
+//! 30 Hz panels) run a [`FrameRateCap`] on the UDP path as well: layered
+//! frames arriving before the next frame is due are skipped before they
+//! reach the decoder. Senders are asked to stay under the cap anyway; this
+//! catches those that don't.
+
+use std::time::{Duration, Instant};
+
+use crate::parameter_sets::{is_slice, leading_nal_units};
+use crate::VideoCodec;
+
+/// Lowest temporal layer whose frames no layer-0 frame references.
+pub const DROPPABLE_LAYER: u8 = 1;
+/// Frames queued for the decoder at which layered frames start being
+/// dropped.
+pub const SHED_ABOVE: usize = 4;
+/// Queued frames at which dropping stops again.
+pub const SHED_UNTIL: usize = 1;
+
+/// Temporal layer of the access unit `data`, from its first slice's NAL
+/// header; 0 when it has no slice.
+pub fn temporal_layer(codec: VideoCodec, data: &[u8]) -> u8 {
+    let Some((ty, range)) = leading_nal_units(codec, data).into_iter().find(|(ty, _)| is_slice(codec, *ty)) else {
+        return 0;
+    };
+    let unit = &data[range];
+    let Some(start) = unit.iter().position(|&b| b == 1) else { return 0 };
+    let header = &unit[start + 1..];
+    match codec {
+        // nal_ref_idc 0: not used for reference.
+        VideoCodec::H264 => u8::from(header[0] & 0x60 == 0),
+        VideoCodec::H265 => {
+            let temporal_id = header.get(1).map_or(0, |b| (b & 0x07).saturating_sub(1));
+            // Even types up to RSV_VCL_N14 are sub-layer non-reference.
+            let non_reference = ty <= 14 && ty % 2 == 0;
+            temporal_id.max(u8::from(non_reference))
+        }
+    }
+}
+
+// MARK: - LayerShedder
+
+/// Decides, frame by frame, whether the decode thread skips a layered
+/// frame.
+#[derive(Debug, Default)]
+pub struct LayerShedder {
+    shedding: bool,
+    dropped:  u64,
+}
+
+impl LayerShedder {
+    /// Whether a frame of `layer` should be decoded with `queued` frames
+    /// waiting behind it.
+    pub fn admit(&mut self, layer: u8, queued: usize) -> bool {
+        if queued >= SHED_ABOVE {
+            self.shedding = true;
+        } else if queued <= SHED_UNTIL {
+            self.shedding = false;
+        }
+        if self.shedding && layer >= DROPPABLE_LAYER {
+            self.dropped += 1;
+            return false;
+        }
+        true
+    }
+
+    /// `true` while layered frames are being dropped.
+    pub fn is_shedding(&self) -> bool {
+        self.shedding
+    }
+
+    /// Frames dropped so far.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+// MARK: - FrameRateCap
+
+/// Thins one display's frames to at most a given rate by skipping layered
+/// frames that arrive early. Layer-0 frames always pass, so streams without
+/// layers are never thinned.
+#[derive(Debug, Clone)]
+pub struct FrameRateCap {
+    interval: Duration,
+    /// When the next frame is due.
+    due:      Option<Instant>,
+    skipped:  u64,
+}
+
+impl FrameRateCap {
+    pub fn new(max_fps: u32) -> Self {
+        Self { interval: Duration::from_secs(1) / max_fps.max(1), due: None, skipped: 0 }
+    }
+
+    /// Whether a frame of `layer` arriving at `now` goes on to the decoder.
+    pub fn admit(&mut self, layer: u8, now: Instant) -> bool {
+        // Up to a quarter interval early is arrival jitter, not excess.
+        let early = self.due.is_some_and(|due| now + self.interval / 4 < due);
+        if early {
+            if layer >= DROPPABLE_LAYER {
+                self.skipped += 1;
+                return false;
+            }
+            return true;
+        }
+        self.due = Some(match self.due {
+            Some(due) if now.saturating_duration_since(due) < self.interval => due + self.interval,
+            _ => now + self.interval,
+        });
+        true
+    }
+
+    /// Frames skipped so far.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_non_reference_frames() {
+        let sps_idr: &[u8] = &[0, 0, 0, 1, 0x67, 0x42, 0, 0, 0, 1, 0x65, 0x88];
+        assert_eq!(temporal_layer(VideoCodec::H264, sps_idr), 0);
+        assert_eq!(temporal_layer(VideoCodec::H264, &[0, 0, 0, 1, 0x41, 0x9a]), 0);
+        // nal_ref_idc 0, non-IDR slice.
+        assert_eq!(temporal_layer(VideoCodec::H264, &[0, 0, 0, 1, 0x01, 0x9e]), 1);
+        assert_eq!(temporal_layer(VideoCodec::H264, &[0, 0, 0, 1, 0x09, 0xf0]), 0);
+
+        // TRAIL_R at tid 0, TRAIL_N at tid 0, TRAIL_R at tid 1.
+        assert_eq!(temporal_layer(VideoCodec::H265, &[0, 0, 1, 0x02, 0x01, 0xaf]), 0);
+        assert_eq!(temporal_layer(VideoCodec::H265, &[0, 0, 1, 0x00, 0x01, 0xaf]), 1);
+        assert_eq!(temporal_layer(VideoCodec::H265, &[0, 0, 1, 0x02, 0x02, 0xaf]), 1);
+    }
+
+    #[test]
+    fn sheds_layered_frames_while_backed_up() {
+        let mut shedder = LayerShedder::default();
+        assert!(shedder.admit(1, 3));
+        assert!(!shedder.admit(1, 4));
+        assert!(shedder.admit(0, 5), "layer 0 is always decoded");
+        assert!(!shedder.admit(1, 2), "keeps shedding until drained");
+        assert!(shedder.admit(1, 1));
+        assert!(!shedder.is_shedding());
+        assert_eq!(shedder.dropped(), 2);
+    }
+
+    #[test]
+    fn caps_the_frame_rate_with_layered_frames() {
+        let start = Instant::now();
+        let at = |i: u64| start + Duration::from_micros(i * 1_000_000 / 60);
+
+        // 60 fps, every other frame layered, capped at 30: the layered half goes.
+        let mut cap = FrameRateCap::new(30);
+        let passed = (0..120).filter(|&i| cap.admit((i % 2) as u8, at(i))).count();
+        assert_eq!(passed, 60);
+        assert_eq!(cap.skipped(), 60);
+
+        // Without layers nothing is skipped, whatever the rate.
+        let mut cap = FrameRateCap::new(10);
+        assert!((0..60).all(|i| cap.admit(0, at(i))));
+    }
+}
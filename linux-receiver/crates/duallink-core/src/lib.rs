@@ -1,11 +1,98 @@
+pub mod appearance;
+pub mod benchmark;
+pub mod checksum;
+pub mod clock;
 pub mod config;
+pub mod diagnostics;
+pub mod display_sync;
+pub mod dump;
+pub mod duplicates;
 pub mod errors;
+pub mod file_transfer;
+pub mod firewall;
+pub mod gesture;
+pub mod hotkeys;
+pub mod idle;
+pub mod inhibit;
+pub mod layers;
 pub mod input;
+pub mod input_macro;
+pub mod input_schema;
+pub mod link;
+pub mod locale;
+pub mod logbuf;
+pub mod monitor;
+pub mod network;
+pub mod overlay;
+pub mod parameter_sets;
+pub mod ports;
+pub mod power;
+pub mod queues;
+pub mod resume;
+pub mod settings;
+pub mod slices;
+pub mod stats;
+pub mod test_pattern;
+pub mod trace;
 pub mod types;
+pub mod usage;
 pub mod usb;
+pub mod visibility;
+pub mod wake;
 
-pub use config::StreamConfig;
-pub use errors::DualLinkError;
+pub use appearance::{AppearanceSettings, Theme, WindowGeometry, UI_SCALES};
+pub use benchmark::DecoderBenchmarks;
+pub use checksum::{crc32, frame_checksum};
+pub use clock::{timeline_start, ClockMapper, PlayoutBuffer, PtsUnwrapper};
+pub use config::{
+    ColorMatrix, ColorRange, ColorSpace, EncoderTune, HdrMetadata, LatencyMode, MasteringDisplay, PresetParams,
+    QualityPreset, StreamConfig, StreamLimits, CAP_H264_444, CAP_HEVC_MAIN10, CAP_LATENCY_MODE, HDR_COLORIMETRY,
+    QUALITY_JITTER_BUFFER,
+};
+pub use diagnostics::{DecoderEntry, DiagnosticsReport, InterfaceInfo, ProbeReport, VaapiInfo};
+pub use display_sync::{DisplaySync, SyncMember, DEFAULT_SYNC_WAIT};
+pub use dump::{DumpSettings, StreamDump};
+pub use duplicates::{frame_hash, DuplicateFilter, RateMeter};
+pub use errors::{panic_message, DualLinkError};
+pub use file_transfer::{
+    configured_max_file_size, download_dir, FileMessage, FileOffer, FileTransferEvent, FileTransfers, CAP_FILE_TRANSFER,
+    FILE_CHUNK_INTERVAL, FILE_CHUNK_SIZE,
+};
+pub use firewall::{receiver_ports, Firewall, FirewallCheck, FirewallPort};
+pub use gesture::GestureTracker;
+pub use hotkeys::{configured_capture_system_keys, Filtered, Hotkey, HotkeyAction, HotkeyFilter, Keymap};
+pub use idle::{
+    configured_idle_after, IdleDetector, CHANGED_FRAME_BYTES, DEFAULT_IDLE_AFTER, IDLE_BITRATE_KBPS, IDLE_FPS,
+};
+pub use inhibit::IdleInhibitor;
+pub use layers::{temporal_layer, FrameRateCap, LayerShedder, DROPPABLE_LAYER};
 pub use input::*;
+pub use input_macro::{InputRecorder, InputRecording, TimedInputEvent};
+pub use input_schema::{parse_input_event, InputParseMode, INPUT_SCHEMA_VERSION};
+pub use link::{
+    BitrateGuard, FrameCounters, LinkQuality, SequenceEvent, SequenceStats, SequenceTracker, CAP_BLANK,
+    CAP_DISPLAY_STATE, CAP_DLNK_V2, CAP_KEEPALIVE_ACK, CAP_KEYFRAME_REQUEST, CAP_PREVIEW,
+};
+pub use locale::{set_language, Language};
+pub use logbuf::{LogBuffer, RateLimiter, RotatingFile, Suppressed};
+pub use monitor::{
+    detect_monitors, MonitorAssignments, MonitorInfo, CAP_DISPLAYS_CHANGED, CAP_DISPLAY_INFO,
+};
+pub use network::{NetworkCap, NetworkKind, NetworkPolicy, ROUTE_POLL_INTERVAL};
+pub use overlay::{OverlayCorner, OverlayWidget};
+pub use parameter_sets::{coded_size, ParameterSets, Repair};
+pub use ports::{DisplayPorts, PortMap, DEFAULT_SIGNALING_PORT, DEFAULT_VIDEO_PORT};
+pub use power::{read_power, saver_below, PowerState, CAP_POWER, POWER_POLL_INTERVAL};
+pub use queues::{ReceiverQueues, SenderQueues, MAX_QUEUE_DEPTH};
+pub use resume::{ResumeEntry, ResumeTokens, DEFAULT_RESUME_TTL};
+pub use settings::{HookAction, HookEvent, ReceiverSettings, SessionHook};
+pub use slices::{configured_slice_decode, nal_starts, CAP_NAL_SLICES};
+pub use stats::{
+    FrameSample, IntervalMeter, IntervalSummary, SessionEvent, StatsFile, StatsSink, StatsSinks, STATS_INTERVAL,
+};
+pub use test_pattern::{test_pattern_arg, DEFAULT_TEST_PATTERN, TEST_PATTERNS};
 pub use types::*;
+pub use usage::{SessionSummary, UsageMeter};
 pub use usb::{detect_usb_ethernet, UsbEthernetInfo};
+pub use visibility::{HiddenMode, VisibilityTracker, CAP_FPS_REQUEST, HIDDEN_FPS, HIDDEN_GRACE};
+pub use wake::WakeAddresses;
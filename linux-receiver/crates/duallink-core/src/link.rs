@@ -0,0 +1,377 @@
+//! Link quality measured over the signaling channel.
+//!
+//! Senders that advertise [`CAP_KEEPALIVE_ACK`] get every 1 Hz `keepalive`
+//! answered with a `keepalive_ack` echoing the original timestamp, plus the
+//! receiver's cumulative frame counters for that display. The sender turns
+//! consecutive acks into a [`LinkQuality`] sample: round-trip time from the
+//! echoed timestamp, and a loss estimate from the counter deltas.
+//!
+//! The receiver follows each display's `frame_seq` with a
+//! [`SequenceTracker`], which classifies every completed frame as in order,
+//! after a gap, late or duplicate and keeps the totals ([`SequenceStats`]).
+//!
+//! Senders that advertise [`CAP_KEYFRAME_REQUEST`] are sent a
+//! `keyframe_request` (a PLI) when the receiver drops delta frames while
+//! waiting for a keyframe — after a join, a decoder restart or lost frames.
+//!
+//! Displays with a bitrate ceiling ([`StreamLimits`](crate::StreamLimits))
+//! pass every completed frame through a [`BitrateGuard`], which drops frames
+//! of senders streaming well above the negotiated rate.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+// MARK: - Capability
+
+/// Sender capability (in `hello`): wants each `keepalive` answered with a
+/// `keepalive_ack`.
+pub const CAP_KEEPALIVE_ACK: &str = "keepalive_ack";
+
+/// Sender capability (in `hello`): forces a keyframe on `keyframe_request`.
+pub const CAP_KEYFRAME_REQUEST: &str = "keyframe_request";
+
+/// Capability (in `hello` and `hello_ack`): handles `blank` — the sender
+/// blanking the receiver's display, or the receiver pausing the sender's
+/// capture, without ending the session.
+pub const CAP_BLANK: &str = "blank";
+
+/// Capability (in `hello` and `hello_ack`): handles `display_state` —
+/// either end pausing or resuming one display's stream while the others
+/// keep going.
+pub const CAP_DISPLAY_STATE: &str = "display_state";
+
+/// Receiver capability (in `hello_ack`): accepts DLNK v2 video headers with
+/// a 64-bit µs PTS and clock epoch (see [`crate::clock`]).
+pub const CAP_DLNK_V2: &str = "dlnk_v2";
+
+/// Capability (in `hello` and `hello_ack`): the sender wants, and the
+/// receiver sends, periodic `preview` thumbnails of what it shows.
+pub const CAP_PREVIEW: &str = "preview";
+
+// MARK: - FrameCounters
+
+/// Receiver-side frame counters for one display, carried in `keepalive_ack`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct FrameCounters {
+    /// Frames fully reassembled since the display was bound.
+    pub received: u64,
+    /// Frames that never arrived complete (sequence gaps).
+    pub dropped:  u64,
+}
+
+impl FrameCounters {
+    /// Percentage of frames dropped between `earlier` and `self`, or `None`
+    /// if no frames were seen in between (or the counters were reset).
+    pub fn loss_since(&self, earlier: &FrameCounters) -> Option<f32> {
+        let received = self.received.checked_sub(earlier.received)?;
+        let dropped = self.dropped.checked_sub(earlier.dropped)?;
+        let total = received + dropped;
+        (total > 0).then(|| dropped as f32 * 100.0 / total as f32)
+    }
+}
+
+// MARK: - SequenceTracker
+
+/// Frames behind the newest one that are still told apart as late or
+/// duplicate; a frame further back is taken as a restarted sender.
+pub const REORDER_WINDOW: u32 = 64;
+
+/// Forward jump beyond which the sender is assumed to have restarted
+/// rather than lost that many frames.
+const MAX_GAP: u32 = 1024;
+
+/// Per-display sequence accounting, reported in the receiver metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SequenceStats {
+    /// Completed frames passed on (in order or after a gap).
+    pub received:  u64,
+    /// Frames skipped by a sequence gap and not (yet) seen late.
+    pub lost:      u64,
+    /// Frames that completed after a newer one; discarded.
+    pub late:      u64,
+    /// Frames completed a second time; discarded.
+    pub duplicate: u64,
+}
+
+impl SequenceStats {
+    /// The counters carried to the sender in `keepalive_ack`.
+    pub fn counters(&self) -> FrameCounters {
+        FrameCounters { received: self.received, dropped: self.lost }
+    }
+}
+
+impl fmt::Display for SequenceStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} lost · {} late · {} dup", self.lost, self.late, self.duplicate)
+    }
+}
+
+/// How a completed frame relates to the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceEvent {
+    /// Next expected frame (or the first one).
+    InOrder,
+    /// `missing` frames were skipped before this one.
+    Gap { missing: u32 },
+    /// Older than the newest frame; the decoder has moved past it.
+    Late,
+    /// Already seen.
+    Duplicate,
+    /// Far outside the window — the sender restarted its counter.
+    Restart,
+}
+
+impl SequenceEvent {
+    /// `true` if the frame should reach the decoder.
+    pub fn is_accepted(&self) -> bool {
+        !matches!(self, SequenceEvent::Late | SequenceEvent::Duplicate)
+    }
+}
+
+/// Classifies frame sequence numbers, tolerating wrap-around.
+#[derive(Debug, Clone, Default)]
+pub struct SequenceTracker {
+    newest: Option<u32>,
+    /// Bit `i` set: frame `newest - i` was seen.
+    seen:   u64,
+    stats:  SequenceStats,
+}
+
+impl SequenceTracker {
+    pub fn observe(&mut self, seq: u32) -> SequenceEvent {
+        let Some(newest) = self.newest else {
+            return self.restart(seq, SequenceEvent::InOrder);
+        };
+        let ahead = seq.wrapping_sub(newest);
+        let behind = newest.wrapping_sub(seq);
+        if ahead == 0 {
+            self.stats.duplicate += 1;
+            SequenceEvent::Duplicate
+        } else if ahead <= MAX_GAP {
+            let missing = ahead - 1;
+            self.seen = if ahead >= 64 { 1 } else { (self.seen << ahead) | 1 };
+            self.newest = Some(seq);
+            self.stats.received += 1;
+            self.stats.lost += missing as u64;
+            if missing == 0 { SequenceEvent::InOrder } else { SequenceEvent::Gap { missing } }
+        } else if behind < REORDER_WINDOW {
+            let bit = 1u64 << behind;
+            if self.seen & bit != 0 {
+                self.stats.duplicate += 1;
+                return SequenceEvent::Duplicate;
+            }
+            // Counted lost when the gap opened; it did arrive, just too late.
+            self.seen |= bit;
+            self.stats.lost = self.stats.lost.saturating_sub(1);
+            self.stats.late += 1;
+            SequenceEvent::Late
+        } else {
+            self.restart(seq, SequenceEvent::Restart)
+        }
+    }
+
+    pub fn stats(&self) -> SequenceStats {
+        self.stats
+    }
+
+    /// Forget the sequence position — the sender started a new stream. The
+    /// next frame is taken as in order; totals are kept.
+    pub fn reset(&mut self) {
+        self.newest = None;
+        self.seen = 0;
+    }
+
+    fn restart(&mut self, seq: u32, event: SequenceEvent) -> SequenceEvent {
+        self.newest = Some(seq);
+        self.seen = 1;
+        self.stats.received += 1;
+        event
+    }
+}
+
+// MARK: - BitrateGuard
+
+/// How far above its ceiling a sender may run before frames are dropped;
+/// encoders overshoot their target briefly.
+pub const BITRATE_TOLERANCE: f64 = 1.25;
+
+/// Token bucket holding this much of the ceiling, so keyframes and short
+/// peaks pass.
+const BITRATE_BURST: Duration = Duration::from_secs(1);
+
+/// Enforces a bitrate ceiling on one display's incoming frames.
+#[derive(Debug, Clone)]
+pub struct BitrateGuard {
+    limit_bps:      u64,
+    /// Bits that may still pass; refills at the ceiling × tolerance.
+    tokens:         f64,
+    refilled:       Option<Instant>,
+    window_start:   Option<Instant>,
+    window_bits:    u64,
+    window_dropped: u32,
+}
+
+impl BitrateGuard {
+    pub fn new(limit_bps: u64) -> Self {
+        Self {
+            limit_bps,
+            tokens: limit_bps as f64 * BITRATE_BURST.as_secs_f64(),
+            refilled: None,
+            window_start: None,
+            window_bits: 0,
+            window_dropped: 0,
+        }
+    }
+
+    pub fn limit_bps(&self) -> u64 {
+        self.limit_bps
+    }
+
+    /// Whether a frame of `bytes` arriving at `now` fits under the ceiling.
+    pub fn admit(&mut self, bytes: usize, now: Instant) -> bool {
+        let capacity = self.limit_bps as f64 * BITRATE_BURST.as_secs_f64();
+        if let Some(last) = self.refilled {
+            let refill = now.duration_since(last).as_secs_f64() * self.limit_bps as f64 * BITRATE_TOLERANCE;
+            self.tokens = (self.tokens + refill).min(capacity);
+        }
+        self.refilled = Some(now);
+        self.window_start.get_or_insert(now);
+
+        let bits = bytes as f64 * 8.0;
+        self.window_bits += bits as u64;
+        if bits <= self.tokens {
+            self.tokens -= bits;
+            true
+        } else {
+            self.window_dropped += 1;
+            false
+        }
+    }
+
+    /// Once per second: the bitrate that arrived over the last second, if
+    /// any frame was dropped during it.
+    pub fn overrun(&mut self, now: Instant) -> Option<(u64, u32)> {
+        let elapsed = now.duration_since(*self.window_start.get_or_insert(now));
+        if elapsed < BITRATE_BURST {
+            return None;
+        }
+        let report = (self.window_dropped > 0)
+            .then(|| ((self.window_bits as f64 / elapsed.as_secs_f64()) as u64, self.window_dropped));
+        self.window_start = Some(now);
+        self.window_bits = 0;
+        self.window_dropped = 0;
+        report
+    }
+}
+
+// MARK: - LinkQuality
+
+/// Round-trip time above which [`LinkQuality::is_degraded`] reports the link
+/// as the likely cause of stutter.
+pub const DEGRADED_RTT_MS: u32 = 50;
+
+/// Frame loss above which [`LinkQuality::is_degraded`] trips, in percent.
+pub const DEGRADED_LOSS_PCT: f32 = 2.0;
+
+/// One link-quality sample, taken when a `keepalive_ack` arrives.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LinkQuality {
+    /// Keepalive round-trip time in milliseconds.
+    pub rtt_ms:   u32,
+    /// Frames lost since the previous ack, in percent; `None` until two acks
+    /// with counters have arrived or while the stream is idle.
+    pub loss_pct: Option<f32>,
+}
+
+impl LinkQuality {
+    /// `true` if RTT or loss is high enough to explain visible stutter.
+    pub fn is_degraded(&self) -> bool {
+        self.rtt_ms > DEGRADED_RTT_MS || self.loss_pct.is_some_and(|l| l > DEGRADED_LOSS_PCT)
+    }
+}
+
+impl fmt::Display for LinkQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RTT {} ms", self.rtt_ms)?;
+        if let Some(loss) = self.loss_pct {
+            write!(f, " · loss {loss:.1}%")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{BitrateGuard, FrameCounters, LinkQuality, SequenceEvent, SequenceTracker};
+
+    #[test]
+    fn bitrate_guard_drops_only_sustained_overruns() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        // 8 Mbit/s ceiling, 60 fps at 6 Mbit/s: everything passes.
+        let mut guard = BitrateGuard::new(8_000_000);
+        for i in 0..120 {
+            assert!(guard.admit(12_500, at(i * 1000 / 60)));
+        }
+        assert_eq!(guard.overrun(at(2000)), None);
+
+        // 40 Mbit/s: the burst drains and most frames are dropped.
+        let mut guard = BitrateGuard::new(8_000_000);
+        let passed = (0..120).filter(|i| guard.admit(83_333, at(i * 1000 / 60))).count();
+        // 8 Mbit burst + 2 s × 10 Mbit/s refill ≈ 42 frames of 0.67 Mbit.
+        assert!(passed < 50, "{passed} frames passed");
+        let (bps, dropped) = guard.overrun(at(2000)).unwrap();
+        assert!(bps > 30_000_000 && dropped > 70);
+    }
+
+    #[test]
+    fn loss_is_computed_from_counter_deltas() {
+        let earlier = FrameCounters { received: 100, dropped: 2 };
+        let now = FrameCounters { received: 157, dropped: 5 };
+        assert_eq!(now.loss_since(&earlier), Some(5.0));
+        assert_eq!(earlier.loss_since(&earlier), None);
+        // Receiver restarted the display — counters went backwards.
+        assert_eq!(FrameCounters::default().loss_since(&earlier), None);
+    }
+
+    #[test]
+    fn sequence_gaps_late_and_duplicate_frames() {
+        let mut t = SequenceTracker::default();
+        assert_eq!(t.observe(100), SequenceEvent::InOrder);
+        assert_eq!(t.observe(101), SequenceEvent::InOrder);
+        assert_eq!(t.observe(104), SequenceEvent::Gap { missing: 2 });
+        assert_eq!(t.observe(102), SequenceEvent::Late);
+        assert_eq!(t.observe(102), SequenceEvent::Duplicate);
+        assert_eq!(t.observe(104), SequenceEvent::Duplicate);
+        let s = t.stats();
+        assert_eq!((s.received, s.lost, s.late, s.duplicate), (3, 1, 1, 2));
+
+        // Wrap-around is an ordinary step; a far jump back is a new sender.
+        let mut t = SequenceTracker::default();
+        t.observe(u32::MAX);
+        assert_eq!(t.observe(0), SequenceEvent::InOrder);
+        assert_eq!(t.observe(5_000), SequenceEvent::Restart);
+        assert_eq!(t.observe(1), SequenceEvent::Restart);
+        assert_eq!(t.stats().lost, 0);
+
+        // A reconnecting sender counts from 0 again.
+        let mut t = SequenceTracker::default();
+        t.observe(0);
+        t.observe(1);
+        t.reset();
+        assert_eq!(t.observe(0), SequenceEvent::InOrder);
+        assert_eq!(t.stats().received, 3);
+    }
+
+    #[test]
+    fn degraded_on_high_rtt_or_loss() {
+        assert!(!LinkQuality { rtt_ms: 4, loss_pct: Some(0.5) }.is_degraded());
+        assert!(LinkQuality { rtt_ms: 120, loss_pct: None }.is_degraded());
+        assert!(LinkQuality { rtt_ms: 4, loss_pct: Some(8.0) }.is_degraded());
+    }
+}
@@ -0,0 +1,205 @@
+//! UI languages and string lookup.
+//!
+//! The receiver GUI and the sender UIs keep their text in a [`Catalog`]:
+//! a table from a key to the string in every [`Language`]. They look
+//! strings up with [`tr`] / [`tr_fill`] in the current language, which
+//! can be switched while the UI runs with [`set_language`].
+//!
+//! The language starting out is [`Language::configured`]:
+//!
+//! ```text
+//! DUALLINK_LANG=pt-BR    # en, pt-BR or es; overrides everything else
+//! ```
+//!
+//! then the one last picked in a UI (`duallink/language.json` in the
+//! config directory), then the system locale (`LC_ALL`, `LC_MESSAGES`,
+//! `LANG`), then English. Keys missing from a catalog come out as the key
+//! itself, so a gap shows up in the UI rather than as a blank.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::config_file;
+
+const FILE_NAME: &str = "language.json";
+
+// MARK: - Language
+
+/// A language the UIs are translated into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "pt-BR")]
+    PortugueseBr,
+    #[serde(rename = "es")]
+    Spanish,
+}
+
+impl Language {
+    /// Every language, in catalog column order.
+    pub const ALL: [Language; 3] = [Self::English, Self::PortugueseBr, Self::Spanish];
+
+    /// BCP 47 tag, as in `DUALLINK_LANG`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::PortugueseBr => "pt-BR",
+            Self::Spanish => "es",
+        }
+    }
+
+    /// Name of the language in itself, for language pickers.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::PortugueseBr => "Português (Brasil)",
+            Self::Spanish => "Español",
+        }
+    }
+
+    /// Parse a tag or POSIX locale: `pt-BR`, `pt_BR.UTF-8`, `es_MX`, `en`.
+    /// Any Portuguese or Spanish variant maps to the one translation.
+    pub fn from_code(s: &str) -> Option<Self> {
+        let lang = s.trim().split(['-', '_', '.', '@']).next()?.to_ascii_lowercase();
+        match lang.as_str() {
+            "en" | "c" | "posix" => Some(Self::English),
+            "pt" => Some(Self::PortugueseBr),
+            "es" => Some(Self::Spanish),
+            _ => None,
+        }
+    }
+
+    /// The language to start with; see the [module docs](self).
+    pub fn configured() -> Self {
+        if let Ok(s) = std::env::var("DUALLINK_LANG") {
+            match Self::from_code(&s) {
+                Some(lang) => return lang,
+                None => tracing::warn!("Ignoring DUALLINK_LANG='{s}' — expected en, pt-BR or es"),
+            }
+        }
+        Self::load_saved().or_else(Self::from_system).unwrap_or_default()
+    }
+
+    /// The language of the system locale, if translated.
+    pub fn from_system() -> Option<Self> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|v| !v.is_empty())
+            .and_then(|v| Self::from_code(&v))
+    }
+
+    /// The language last picked in a UI.
+    pub fn load_saved() -> Option<Self> {
+        let path = config_file(FILE_NAME)?;
+        serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+    }
+
+    /// Remember this language for the next start of any DualLink UI.
+    pub fn save(self) -> std::io::Result<()> {
+        let path = config_file(FILE_NAME).ok_or_else(|| std::io::Error::other("no config directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_vec(&self)?)
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.native_name())
+    }
+}
+
+// MARK: - Current language
+
+/// Index into [`Language::ALL`]; `u8::MAX` until first used.
+static CURRENT: AtomicU8 = AtomicU8::new(u8::MAX);
+
+/// The language strings are looked up in; [`Language::configured`] until
+/// [`set_language`] is called.
+pub fn language() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        u8::MAX => {
+            let lang = Language::configured();
+            CURRENT.store(lang.index() as u8, Ordering::Relaxed);
+            lang
+        }
+        i => Language::ALL[i as usize],
+    }
+}
+
+/// Switch the UI language. Strings already on screen change with the next
+/// repaint; log lines already written stay as they are.
+pub fn set_language(lang: Language) {
+    CURRENT.store(lang.index() as u8, Ordering::Relaxed);
+}
+
+// MARK: - Catalogs
+
+/// Strings of one UI: `(key, [English, Portuguese (Brazil), Spanish])`,
+/// columns in [`Language::ALL`] order.
+pub type Catalog = &'static [(&'static str, [&'static str; 3])];
+
+/// `key` in `catalog` in the current language; the key itself if missing.
+pub fn tr(catalog: Catalog, key: &'static str) -> &'static str {
+    tr_in(catalog, key, language())
+}
+
+/// `key` in `catalog` in `lang`; the key itself if missing.
+pub fn tr_in(catalog: Catalog, key: &'static str, lang: Language) -> &'static str {
+    match catalog.iter().find(|(k, _)| *k == key) {
+        Some((_, strings)) => strings[lang.index()],
+        None => {
+            tracing::debug!("No string for '{key}'");
+            key
+        }
+    }
+}
+
+/// [`tr`] with `{name}` placeholders replaced by `args`.
+pub fn tr_fill(catalog: Catalog, key: &'static str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    fill(tr(catalog, key), args)
+}
+
+/// Replace each `{name}` in `template` with its value from `args`.
+/// Unknown placeholders are left as they are.
+pub fn fill(template: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut out = template.to_owned();
+    for (name, value) in args {
+        out = out.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATALOG: Catalog = &[
+        ("status.waiting", ["Waiting", "Aguardando", "Esperando"]),
+        ("log.pin", ["PIN: {pin}", "PIN: {pin}", "PIN: {pin}"]),
+    ];
+
+    #[test]
+    fn looks_up_fills_and_falls_back() {
+        assert_eq!(tr_in(CATALOG, "status.waiting", Language::PortugueseBr), "Aguardando");
+        assert_eq!(tr_in(CATALOG, "status.waiting", Language::Spanish), "Esperando");
+        assert_eq!(tr_in(CATALOG, "status.gone", Language::Spanish), "status.gone");
+        assert_eq!(fill("PIN: {pin} {other}", &[("pin", &123456)]), "PIN: 123456 {other}");
+
+        assert_eq!(Language::from_code("pt_BR.UTF-8"), Some(Language::PortugueseBr));
+        assert_eq!(Language::from_code("es-MX"), Some(Language::Spanish));
+        assert_eq!(Language::from_code("C"), Some(Language::English));
+        assert_eq!(Language::from_code("de_DE"), None);
+        assert_eq!(serde_json::to_string(&Language::PortugueseBr).unwrap(), r#""pt-BR""#);
+    }
+}
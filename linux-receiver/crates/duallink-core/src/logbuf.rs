@@ -0,0 +1,319 @@
+//! Logs that stay readable over a day-long session.
+//!
+//! - [`LogBuffer`] — the last [`LOG_CAPACITY`] lines a frontend shows. A
+//!   line repeating the one before it is counted on that line instead of
+//!   added again, and lines pushed with a category go through a
+//!   [`RateLimiter`]: past [`RATE_BURST`] lines in [`RATE_WINDOW`], the rest
+//!   are counted and summed up in one line once the window ends
+//!   ("Decode error ×240 in last 60 s").
+//! - [`RotatingFile`] — a log file for the headless receiver
+//!   (`DUALLINK_LOG_FILE=<path>`, see [`log_file`]) that moves to `<path>.1`
+//!   once it reaches `DUALLINK_LOG_MAX_MB` ([`DEFAULT_LOG_MAX_BYTES`] if
+//!   unset), keeping [`LOG_FILES_KEPT`] old files.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Lines a [`LogBuffer`] keeps by default.
+pub const LOG_CAPACITY: usize = 300;
+
+/// Window a category's lines are counted over.
+pub const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Lines of one category logged per window before the rest are suppressed.
+pub const RATE_BURST: u32 = 5;
+
+/// Size a log file grows to before it is rotated, unless configured.
+pub const DEFAULT_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Rotated log files kept next to the current one (`<path>.1` … `<path>.5`).
+pub const LOG_FILES_KEPT: usize = 5;
+
+/// Log file from `DUALLINK_LOG_FILE`, if set and not empty.
+pub fn log_file() -> Option<PathBuf> {
+    std::env::var_os("DUALLINK_LOG_FILE").filter(|p| !p.is_empty()).map(PathBuf::from)
+}
+
+/// Rotation size from `DUALLINK_LOG_MAX_MB`, else [`DEFAULT_LOG_MAX_BYTES`].
+pub fn configured_log_max_bytes() -> u64 {
+    std::env::var("DUALLINK_LOG_MAX_MB")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|&mb| mb > 0)
+        .map_or(DEFAULT_LOG_MAX_BYTES, |mb| mb * 1024 * 1024)
+}
+
+// MARK: - RateLimiter
+
+/// Lines of one category a [`RateLimiter`] held back over one window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppressed {
+    pub category: String,
+    pub count:    u64,
+    pub window:   Duration,
+}
+
+impl fmt::Display for Suppressed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ×{} in last {} s", self.category, self.count, self.window.as_secs())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Window {
+    since:      Instant,
+    passed:     u32,
+    suppressed: u64,
+}
+
+/// Lets through the first `burst` lines of each category per `window` and
+/// counts the rest.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    window:  Duration,
+    burst:   u32,
+    windows: HashMap<String, Window>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RATE_WINDOW, RATE_BURST)
+    }
+}
+
+impl RateLimiter {
+    pub fn new(window: Duration, burst: u32) -> Self {
+        Self { window, burst, windows: HashMap::new() }
+    }
+
+    /// Whether a line of `category` at `now` should be logged. Call
+    /// [`take_suppressed`](Self::take_suppressed) first so a window that
+    /// ended is summed up before the next one starts.
+    pub fn admit(&mut self, category: &str, now: Instant) -> bool {
+        let window = self
+            .windows
+            .entry(category.to_owned())
+            .or_insert(Window { since: now, passed: 0, suppressed: 0 });
+        if window.passed < self.burst {
+            window.passed += 1;
+            true
+        } else {
+            window.suppressed += 1;
+            false
+        }
+    }
+
+    /// The categories whose window ended by `now` with lines held back;
+    /// those windows, and ones that held nothing back, are closed.
+    pub fn take_suppressed(&mut self, now: Instant) -> Vec<Suppressed> {
+        let mut ended = Vec::new();
+        self.windows.retain(|category, w| {
+            if now.saturating_duration_since(w.since) < self.window {
+                return true;
+            }
+            if w.suppressed > 0 {
+                ended.push(Suppressed { category: category.clone(), count: w.suppressed, window: self.window });
+            }
+            false
+        });
+        ended.sort_by(|a, b| a.category.cmp(&b.category));
+        ended
+    }
+}
+
+// MARK: - LogBuffer
+
+/// The last lines of a log, with repeats and bursts folded.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    lines:    VecDeque<String>,
+    capacity: usize,
+    /// The last line as pushed, and how often it came in a row.
+    last:     Option<(String, u64)>,
+    limiter:  RateLimiter,
+    /// Turns a summary into a line; frontends translate it here.
+    summary:  fn(&Suppressed) -> String,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(LOG_CAPACITY)
+    }
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            capacity: capacity.max(1),
+            last: None,
+            limiter: RateLimiter::default(),
+            summary: |s| s.to_string(),
+        }
+    }
+
+    /// Write summaries of suppressed lines with `summary` instead of
+    /// [`Suppressed`]'s English one.
+    pub fn with_summary(mut self, summary: fn(&Suppressed) -> String) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    /// Append `line`, or count it on the last line if it repeats it.
+    pub fn push(&mut self, line: impl Into<String>) {
+        let line = line.into();
+        if let (Some((last, count)), Some(shown)) = (&mut self.last, self.lines.back_mut()) {
+            if *last == line {
+                *count += 1;
+                *shown = format!("{line} ×{count}");
+                return;
+            }
+        }
+        if self.lines.len() >= self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line.clone());
+        self.last = Some((line, 1));
+    }
+
+    /// Append `line` unless its `category` already logged [`RATE_BURST`]
+    /// lines this window. Returns `true` if it was appended.
+    pub fn push_limited(&mut self, category: &str, line: impl Into<String>, now: Instant) -> bool {
+        self.flush(now);
+        let admitted = self.limiter.admit(category, now);
+        if admitted {
+            self.push(line);
+        }
+        admitted
+    }
+
+    /// Append summaries of the windows that ended by `now`.
+    pub fn flush(&mut self, now: Instant) {
+        for suppressed in self.limiter.take_suppressed(now) {
+            let line = (self.summary)(&suppressed);
+            self.push(line);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.lines.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+// MARK: - RotatingFile
+
+/// An append-only log file rotated by size.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path:      PathBuf,
+    max_bytes: u64,
+    file:      File,
+    len:       u64,
+}
+
+impl RotatingFile {
+    /// Open `path` for appending, creating its directory if needed.
+    pub fn open(path: &Path, max_bytes: u64) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self { path: path.to_owned(), max_bytes: max_bytes.max(1), file, len })
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{n}"));
+        name.into()
+    }
+
+    /// `<path>.4` → `<path>.5`, …, `<path>` → `<path>.1`, then a new `<path>`.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        for n in (1..LOG_FILES_KEPT).rev() {
+            let from = self.rotated(n);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated(n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated(1))?;
+        self.file = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.len > 0 && self.len + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.len += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_folds_repeats_and_bursts() {
+        let t0 = Instant::now();
+        let mut log = LogBuffer::new(4);
+        log.push("started");
+        log.push("started");
+        for i in 0..240 {
+            log.push_limited("Decode error", format!("Decode error {i}"), t0 + Duration::from_millis(i * 100));
+        }
+        assert_eq!(log.len(), 4);
+        assert_eq!(log.iter().next().unwrap(), "Decode error 1");
+
+        log.flush(t0 + RATE_WINDOW);
+        assert_eq!(log.iter().last().unwrap(), "Decode error ×235 in last 60 s");
+        assert!(log.push_limited("Decode error", "Decode error again", t0 + RATE_WINDOW));
+
+        let mut log = LogBuffer::new(10);
+        log.push("started");
+        log.push("started");
+        log.push("started");
+        assert_eq!(log.iter().collect::<Vec<_>>(), ["started ×3"]);
+    }
+
+    #[test]
+    fn file_rotates_at_max_size() {
+        let dir = std::env::temp_dir().join(format!("duallink-logbuf-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("receiver.log");
+        let mut file = RotatingFile::open(&path, 100).unwrap();
+        for _ in 0..8 {
+            file.write_all(&[b'x'; 60]).unwrap();
+        }
+        drop(file);
+        let len = |p: PathBuf| std::fs::metadata(p).map(|m| m.len()).ok();
+        assert_eq!(len(path.clone()), Some(60));
+        let file = RotatingFile::open(&path, 100).unwrap();
+        assert_eq!(len(file.rotated(1)), Some(60));
+        assert_eq!(len(file.rotated(LOG_FILES_KEPT)), Some(60));
+        assert_eq!(len(file.rotated(LOG_FILES_KEPT + 1)), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,427 @@
+//! Monitor enumeration.
+//!
+//! The receiver reports the geometry of its own panels to the sender in
+//! `hello_ack` so the sender can size its (virtual) display to match the real
+//! panel — resolution, refresh rate and HiDPI scale. The Linux sender uses
+//! the same enumeration to let the user pick which monitor each stream
+//! captures; [`MonitorAssignments`] persists that choice.
+//!
+//! - **Wayland:** parses `wayland-info` (`wl_output` sections).
+//! - **X11:** parses `xrandr --query`.
+//! - **macOS / Windows:** stub returns an empty list for now.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::config_file;
+use crate::types::Resolution;
+
+/// Sender capability (in `hello`): understands mid-session `display_info`
+/// messages pushed when the receiver's monitors change.
+pub const CAP_DISPLAY_INFO: &str = "display_info";
+
+/// Sender capability (in `hello`): understands `displays_changed` messages
+/// pushed when the receiver adds or removes displays at runtime.
+pub const CAP_DISPLAYS_CHANGED: &str = "displays_changed";
+
+// MARK: - MonitorInfo
+
+/// One physical monitor attached to the machine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorInfo {
+    /// Connector name, e.g. `"DP-1"`.
+    pub name: String,
+    /// Preferred (EDID native) mode, falling back to the current mode.
+    pub resolution: Resolution,
+    #[serde(alias = "refreshHz")]
+    pub refresh_hz: f32,
+    /// Physical size in millimetres; 0 when the EDID does not report it.
+    #[serde(alias = "widthMm", default)]
+    pub width_mm: u32,
+    #[serde(alias = "heightMm", default)]
+    pub height_mm: u32,
+    /// Integer HiDPI scale (compositor-reported on Wayland, estimated from
+    /// the physical size on X11).
+    #[serde(default = "default_scale")]
+    pub scale: u32,
+    /// Top-left position in the desktop layout.
+    #[serde(default)]
+    pub x: i32,
+    #[serde(default)]
+    pub y: i32,
+    #[serde(default)]
+    pub primary: bool,
+}
+
+fn default_scale() -> u32 {
+    1
+}
+
+impl MonitorInfo {
+    /// Horizontal pixel density, if the physical size is known.
+    pub fn dpi(&self) -> Option<f32> {
+        (self.width_mm > 0).then(|| self.resolution.width as f32 * 25.4 / self.width_mm as f32)
+    }
+
+    /// Logical (scaled) resolution the sender's display should expose.
+    pub fn logical_resolution(&self) -> Resolution {
+        let s = self.scale.max(1);
+        Resolution::new(self.resolution.width / s, self.resolution.height / s)
+    }
+}
+
+/// HiDPI scale guess for outputs that don't report one: 2× from ~192 DPI.
+fn estimate_scale(width_px: u32, width_mm: u32) -> u32 {
+    if width_mm == 0 {
+        return 1;
+    }
+    let dpi = width_px as f32 * 25.4 / width_mm as f32;
+    ((dpi / 96.0).round() as u32).max(1)
+}
+
+// MARK: - Detection
+
+/// Enumerate the receiver's monitors, primary first.
+///
+/// Tries `wayland-info` when `WAYLAND_DISPLAY` is set, then `xrandr`.
+/// Returns an empty list if neither tool is available.
+#[cfg(target_os = "linux")]
+pub fn detect_monitors() -> Vec<MonitorInfo> {
+    let run = |cmd: &str, args: &[&str]| {
+        std::process::Command::new(cmd)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).into_owned())
+    };
+
+    let mut monitors = Vec::new();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        if let Some(out) = run("wayland-info", &["-i", "wl_output"]) {
+            monitors = parse_wayland_info(&out);
+        }
+    }
+    if monitors.is_empty() {
+        if let Some(out) = run("xrandr", &["--query"]) {
+            monitors = parse_xrandr(&out);
+        }
+    }
+
+    monitors.sort_by_key(|m| (!m.primary, m.x, m.y));
+    for m in &monitors {
+        tracing::info!(
+            "Monitor {}: {} @ {:.2} Hz, {}×{} mm, scale {}{}",
+            m.name, m.resolution, m.refresh_hz, m.width_mm, m.height_mm, m.scale,
+            if m.primary { " (primary)" } else { "" }
+        );
+    }
+    monitors
+}
+
+/// Non-Linux stub — monitor enumeration not yet implemented on this OS.
+#[cfg(not(target_os = "linux"))]
+pub fn detect_monitors() -> Vec<MonitorInfo> {
+    Vec::new()
+}
+
+/// Parse `xrandr --query` output.
+///
+/// ```text
+/// DP-1 connected primary 1920x1080+0+0 (normal left ...) 527mm x 296mm
+///    1920x1080     60.00*+  59.94
+/// ```
+/// The `+`-marked mode is the EDID preferred one; `*` is current.
+pub fn parse_xrandr(output: &str) -> Vec<MonitorInfo> {
+    let mut monitors: Vec<MonitorInfo> = Vec::new();
+    // Stop at the first preferred mode of the output being parsed.
+    let mut have_preferred = false;
+
+    for line in output.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            have_preferred = false;
+            let mut words = line.split_whitespace();
+            let Some(name) = words.next() else { continue };
+            if words.next() != Some("connected") {
+                continue;
+            }
+            let rest: Vec<&str> = words.collect();
+            let primary = rest.first() == Some(&"primary");
+            let (x, y) = rest
+                .iter()
+                .find_map(|w| parse_xrandr_geometry(w))
+                .map(|(_, _, x, y)| (x, y))
+                .unwrap_or((0, 0));
+            let mm: Vec<u32> = rest
+                .iter()
+                .filter_map(|w| w.strip_suffix("mm")?.parse().ok())
+                .collect();
+            let (width_mm, height_mm) = match mm[..] {
+                [w, h, ..] => (w, h),
+                _ => (0, 0),
+            };
+            monitors.push(MonitorInfo {
+                name: name.to_owned(),
+                resolution: Resolution::new(0, 0),
+                refresh_hz: 0.0,
+                width_mm,
+                height_mm,
+                scale: 1,
+                x,
+                y,
+                primary,
+            });
+            continue;
+        }
+
+        // Mode line — belongs to the last connected output.
+        let Some(m) = monitors.last_mut() else { continue };
+        if have_preferred {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let Some((w, h)) = words.next().and_then(|s| {
+            let (w, h) = s.split_once('x')?;
+            Some((w.parse::<u32>().ok()?, h.trim_end_matches('i').parse::<u32>().ok()?))
+        }) else {
+            continue;
+        };
+        // Markers may be detached from the rate: "60.00 +  74.97*".
+        let mut words = words.peekable();
+        while let Some(rate) = words.next() {
+            let mut marks = rate.to_owned();
+            if words.peek().is_some_and(|w| w.trim_matches(['*', '+']).is_empty()) {
+                marks.push_str(words.next().unwrap_or_default());
+            }
+            let preferred = marks.contains('+');
+            let current = marks.contains('*');
+            if !(preferred || current) {
+                continue;
+            }
+            let hz = rate.trim_end_matches(['*', '+']).parse().unwrap_or(0.0);
+            m.resolution = Resolution::new(w, h);
+            m.refresh_hz = hz;
+            m.scale = estimate_scale(w, m.width_mm);
+            have_preferred = preferred;
+            break;
+        }
+    }
+
+    monitors.retain(|m| m.resolution.width > 0);
+    monitors
+}
+
+/// `"1920x1080+1920+0"` → `(1920, 1080, 1920, 0)`.
+fn parse_xrandr_geometry(s: &str) -> Option<(u32, u32, i32, i32)> {
+    let (size, pos) = s.split_once('+')?;
+    let (w, h) = size.split_once('x')?;
+    let (x, y) = pos.split_once('+')?;
+    Some((w.parse().ok()?, h.parse().ok()?, x.parse().ok()?, y.parse().ok()?))
+}
+
+/// Parse `wayland-info` output (`wl_output` interfaces).
+///
+/// ```text
+/// interface: 'wl_output', version: 4, name: 60
+///     name: DP-1
+///     x: 0, y: 0, scale: 2,
+///     physical_width: 600 mm, physical_height: 340 mm,
+///     mode:
+///         width: 3840 px, height: 2160 px, refresh: 59.997 Hz,
+///         flags: current preferred
+/// ```
+pub fn parse_wayland_info(output: &str) -> Vec<MonitorInfo> {
+    let mut monitors: Vec<MonitorInfo> = Vec::new();
+    let mut in_output = false;
+    let mut have_preferred = false;
+    let mut mode: Option<(u32, u32, f32)> = None;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("interface:") {
+            in_output = trimmed.contains("'wl_output'");
+            have_preferred = false;
+            mode = None;
+            if in_output {
+                monitors.push(MonitorInfo {
+                    name: format!("output-{}", monitors.len()),
+                    resolution: Resolution::new(0, 0),
+                    refresh_hz: 0.0,
+                    width_mm: 0,
+                    height_mm: 0,
+                    scale: 1,
+                    x: 0,
+                    y: 0,
+                    primary: monitors.is_empty(),
+                });
+            }
+            continue;
+        }
+        if !in_output {
+            continue;
+        }
+        let Some(m) = monitors.last_mut() else { continue };
+
+        let num = |v: &str| -> Option<f32> { v.split_whitespace().next()?.parse().ok() };
+        for field in trimmed.split(',') {
+            let Some((key, value)) = field.split_once(':') else { continue };
+            let value = value.trim();
+            match key.trim() {
+                "name" if !value.is_empty() => m.name = value.trim_matches('\'').to_owned(),
+                "x" => m.x = num(value).unwrap_or(0.0) as i32,
+                "y" => m.y = num(value).unwrap_or(0.0) as i32,
+                "scale" => m.scale = (num(value).unwrap_or(1.0) as u32).max(1),
+                "physical_width" => m.width_mm = num(value).unwrap_or(0.0) as u32,
+                "physical_height" => m.height_mm = num(value).unwrap_or(0.0) as u32,
+                "width" => mode = Some((num(value).unwrap_or(0.0) as u32, 0, 0.0)),
+                "height" => {
+                    if let Some(md) = mode.as_mut() {
+                        md.1 = num(value).unwrap_or(0.0) as u32;
+                    }
+                }
+                "refresh" => {
+                    if let Some(md) = mode.as_mut() {
+                        md.2 = num(value).unwrap_or(0.0);
+                    }
+                }
+                "flags" => {
+                    let preferred = value.contains("preferred");
+                    if let Some((w, h, hz)) = mode.take() {
+                        if !have_preferred && (preferred || value.contains("current")) {
+                            m.resolution = Resolution::new(w, h);
+                            m.refresh_hz = hz;
+                            have_preferred = preferred;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    monitors.retain(|m| m.resolution.width > 0);
+    monitors
+}
+
+// MARK: - Sender monitor assignments
+
+const FILE_NAME: &str = "sender-monitors.json";
+
+/// Which local monitor each sender stream captures, by monitor name.
+///
+/// Stored as JSON in `duallink/sender-monitors.json` under the user config
+/// directory. Streams without an entry capture the `display_index`-th monitor.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MonitorAssignments {
+    streams: BTreeMap<u8, String>,
+}
+
+impl MonitorAssignments {
+    /// Load the saved assignments; empty if none were saved or the file is unreadable.
+    pub fn load() -> Self {
+        let Some(path) = config_file(FILE_NAME) else { return Self::default() };
+        std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the assignments back to the config directory.
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = config_file(FILE_NAME).ok_or_else(|| std::io::Error::other("no config directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Monitor assigned to stream `display_index`, if any.
+    pub fn get(&self, display_index: u8) -> Option<&str> {
+        self.streams.get(&display_index).map(String::as_str)
+    }
+
+    /// Assign `monitor` to stream `display_index` (`None` = automatic).
+    pub fn set(&mut self, display_index: u8, monitor: Option<String>) {
+        match monitor {
+            Some(name) => self.streams.insert(display_index, name),
+            None => self.streams.remove(&display_index),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_wayland_info, parse_xrandr, MonitorAssignments};
+    use crate::types::Resolution;
+
+    #[test]
+    fn parses_xrandr_preferred_modes() {
+        let out = "\
+Screen 0: minimum 8 x 8, current 5760 x 2160, maximum 32767 x 32767
+DP-1 connected primary 3840x2160+0+0 (normal left inverted right x axis y axis) 600mm x 340mm
+   3840x2160     60.00*+  30.00
+   1920x1080     60.00    59.94
+HDMI-1 connected 1920x1080+3840+0 (normal left inverted right x axis y axis) 527mm x 296mm
+   1920x1080     60.00 +  74.97*
+HDMI-2 disconnected (normal left inverted right x axis y axis)
+";
+        let monitors = parse_xrandr(out);
+        assert_eq!(monitors.len(), 2);
+
+        let dp = &monitors[0];
+        assert_eq!(dp.name, "DP-1");
+        assert!(dp.primary);
+        assert_eq!(dp.resolution, Resolution::UHD);
+        assert_eq!((dp.width_mm, dp.height_mm), (600, 340));
+        assert_eq!(dp.scale, 2);
+        assert_eq!(dp.logical_resolution(), Resolution::FHD);
+
+        let hdmi = &monitors[1];
+        assert_eq!((hdmi.x, hdmi.y), (3840, 0));
+        // Preferred mode wins over the current 75 Hz one.
+        assert_eq!(hdmi.refresh_hz, 60.0);
+        assert_eq!(hdmi.scale, 1);
+    }
+
+    #[test]
+    fn parses_wayland_info_outputs() {
+        let out = "\
+interface: 'wl_compositor',                              version:  6, name:  1
+interface: 'wl_output',                                  version:  4, name: 60
+\tname: eDP-1
+\tdescription: Built-in display
+\tx: 0, y: 0, scale: 2,
+\tphysical_width: 300 mm, physical_height: 190 mm,
+\tmake: 'BOE', model: '0x0bca',
+\tmode:
+\t\twidth: 2880 px, height: 1800 px, refresh: 90.001 Hz,
+\t\tflags: current preferred
+\tmode:
+\t\twidth: 1920 px, height: 1200 px, refresh: 60.000 Hz,
+\t\tflags: none
+";
+        let monitors = parse_wayland_info(out);
+        assert_eq!(monitors.len(), 1);
+        let m = &monitors[0];
+        assert_eq!(m.name, "eDP-1");
+        assert_eq!(m.resolution, Resolution::new(2880, 1800));
+        assert_eq!(m.scale, 2);
+        assert_eq!((m.width_mm, m.height_mm), (300, 190));
+        assert!((m.refresh_hz - 90.001).abs() < 0.01);
+    }
+
+    #[test]
+    fn monitor_assignments_round_trip() {
+        let mut a = MonitorAssignments::default();
+        a.set(0, Some("DP-1".into()));
+        a.set(1, Some("HDMI-1".into()));
+        a.set(1, None);
+        assert_eq!(a.get(0), Some("DP-1"));
+        assert_eq!(a.get(1), None);
+
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(json, r#"{"streams":{"0":"DP-1"}}"#);
+        assert_eq!(serde_json::from_str::<MonitorAssignments>(&json).unwrap(), a);
+    }
+}
@@ -0,0 +1,220 @@
+//! Per-network bitrate / fps caps for senders.
+//!
+//! A sender on a metered or flaky link (Wi-Fi, a phone's USB tether) may
+//! want a lower ceiling than on wired Ethernet. [`NetworkPolicy`] holds one
+//! [`NetworkCap`] per [`NetworkKind`], configured with
+//! `DUALLINK_NETWORK_CAPS`:
+//!
+//! ```text
+//! DUALLINK_NETWORK_CAPS=wifi=6000@30,usb=3000@30,ethernet=20000
+//! ```
+//!
+//! (`kind=kbps[@fps]`; `kbps` may be empty to cap fps only, e.g. `wifi=@30`.)
+//!
+//! Senders look up the kind of interface the route to the receiver
+//! currently leaves through ([`route_kind`] on Linux, the sender's own
+//! adapter query on Windows), re-check it every [`ROUTE_POLL_INTERVAL`] and
+//! re-apply the cap when it changes mid-session.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How often senders re-check the route to the receiver.
+pub const ROUTE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+// MARK: - NetworkKind
+
+/// Kind of interface the route to the receiver leaves through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkKind {
+    Ethernet,
+    Wifi,
+    /// USB networking: a phone's tether, or a USB-C link to the receiver.
+    UsbTether,
+    /// Not detected (unsupported OS, no route yet).
+    Unknown,
+}
+
+impl NetworkKind {
+    /// Parse `"ethernet"` / `"wifi"` / `"usb"` (case-insensitive).
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ethernet" | "wired" => Some(Self::Ethernet),
+            "wifi" | "wi-fi" | "wlan" => Some(Self::Wifi),
+            "usb" | "tether" | "usb-tether" => Some(Self::UsbTether),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for NetworkKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ethernet => "Ethernet",
+            Self::Wifi => "Wi-Fi",
+            Self::UsbTether => "USB tether",
+            Self::Unknown => "unknown network",
+        })
+    }
+}
+
+// MARK: - NetworkPolicy
+
+/// Ceilings applied while streaming over one kind of network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkCap {
+    pub max_bitrate_kbps: Option<u32>,
+    pub max_fps:          Option<u32>,
+}
+
+impl NetworkCap {
+    /// `(kbps, fps)` lowered to this cap.
+    pub fn apply(&self, kbps: u32, fps: u32) -> (u32, u32) {
+        (self.max_bitrate_kbps.map_or(kbps, |m| kbps.min(m)), self.max_fps.map_or(fps, |m| fps.min(m)))
+    }
+}
+
+impl fmt::Display for NetworkCap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.max_bitrate_kbps, self.max_fps) {
+            (None, None) => f.write_str("uncapped"),
+            (Some(kbps), None) => write!(f, "≤ {kbps} kbps"),
+            (None, Some(fps)) => write!(f, "≤ {fps} fps"),
+            (Some(kbps), Some(fps)) => write!(f, "≤ {kbps} kbps @ {fps} fps"),
+        }
+    }
+}
+
+/// [`NetworkCap`]s by [`NetworkKind`]; kinds without one are uncapped.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NetworkPolicy {
+    caps: BTreeMap<NetworkKind, NetworkCap>,
+}
+
+impl NetworkPolicy {
+    /// Policy from `DUALLINK_NETWORK_CAPS`; empty if unset or invalid.
+    pub fn from_env() -> Self {
+        let Ok(spec) = std::env::var("DUALLINK_NETWORK_CAPS") else { return Self::default() };
+        Self::parse(&spec).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring DUALLINK_NETWORK_CAPS: {e}");
+            Self::default()
+        })
+    }
+
+    /// Parse `kind=kbps[@fps],…`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut caps = BTreeMap::new();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (kind, cap) = entry.split_once('=').ok_or_else(|| format!("'{entry}': expected kind=kbps[@fps]"))?;
+            let kind = NetworkKind::from_name(kind).ok_or_else(|| format!("'{kind}': unknown network kind"))?;
+            let (kbps, fps) = cap.split_once('@').map_or((cap, None), |(k, f)| (k, Some(f)));
+            let number = |s: &str| s.trim().parse::<u32>().map_err(|_| format!("'{entry}': bad number '{s}'"));
+            let max_bitrate_kbps = if kbps.trim().is_empty() { None } else { Some(number(kbps)?) };
+            let max_fps = fps.map(number).transpose()?;
+            caps.insert(kind, NetworkCap { max_bitrate_kbps, max_fps });
+        }
+        Ok(Self { caps })
+    }
+
+    pub fn set(&mut self, kind: NetworkKind, cap: NetworkCap) {
+        self.caps.insert(kind, cap);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.caps.is_empty()
+    }
+
+    /// Cap for `kind`; uncapped if none is configured.
+    pub fn cap(&self, kind: NetworkKind) -> NetworkCap {
+        self.caps.get(&kind).copied().unwrap_or_default()
+    }
+}
+
+// MARK: - Route detection
+
+/// Kind of the interface the route to `host` leaves through.
+///
+/// Asks the kernel's routing table (`ip route get`, netlink underneath)
+/// for the outgoing interface, then classifies it from sysfs: a
+/// `wireless` / `phy80211` entry means Wi-Fi, a USB networking driver
+/// (RNDIS, CDC, `ipheth`) a USB tether, anything else with a device
+/// Ethernet.
+#[cfg(target_os = "linux")]
+pub fn route_kind(host: &str) -> NetworkKind {
+    route_interface(host).map_or(NetworkKind::Unknown, |iface| interface_kind(&iface))
+}
+
+/// Non-Linux stub — platform senders detect the route themselves.
+#[cfg(not(target_os = "linux"))]
+pub fn route_kind(_host: &str) -> NetworkKind {
+    NetworkKind::Unknown
+}
+
+/// Outgoing interface for `host`, from `ip -o route get <host>`.
+#[cfg(target_os = "linux")]
+fn route_interface(host: &str) -> Option<String> {
+    use std::net::ToSocketAddrs;
+
+    let ip = (host, 0).to_socket_addrs().ok()?.next()?.ip();
+    let output = std::process::Command::new("ip").args(["-o", "route", "get", &ip.to_string()]).output().ok()?;
+    parse_route_dev(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// `dev` of an `ip route get` line, e.g.
+/// `192.168.1.20 dev wlp3s0 src 192.168.1.7 uid 1000 \    cache`.
+#[cfg(target_os = "linux")]
+fn parse_route_dev(line: &str) -> Option<String> {
+    let mut words = line.split_whitespace();
+    words.find(|w| *w == "dev")?;
+    words.next().map(str::to_owned)
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn interface_kind(iface: &str) -> NetworkKind {
+    let dir = std::path::Path::new("/sys/class/net").join(iface);
+    if dir.join("wireless").exists() || dir.join("phy80211").exists() {
+        return NetworkKind::Wifi;
+    }
+    let driver = std::fs::read_link(dir.join("device/driver")).ok();
+    match driver.as_deref().and_then(|d| d.file_name()).and_then(|d| d.to_str()) {
+        Some(d) if is_usb_net_driver(d) => NetworkKind::UsbTether,
+        Some(_) => NetworkKind::Ethernet,
+        // Virtual (bridges, VPNs): nothing to tell from the interface itself.
+        None => NetworkKind::Unknown,
+    }
+}
+
+/// Kernel drivers of USB networking gadgets and phone tethers.
+#[cfg(target_os = "linux")]
+fn is_usb_net_driver(driver: &str) -> bool {
+    matches!(driver, "rndis_host" | "cdc_ether" | "cdc_ncm" | "cdc_eem" | "cdc_mbim" | "ipheth" | "qmi_wwan")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policy_parses_caps() {
+        let p = NetworkPolicy::parse("wifi=6000@30, usb=@24,ethernet=20000").unwrap();
+        assert_eq!(p.cap(NetworkKind::Wifi), NetworkCap { max_bitrate_kbps: Some(6000), max_fps: Some(30) });
+        assert_eq!(p.cap(NetworkKind::UsbTether).apply(8000, 60), (8000, 24));
+        assert_eq!(p.cap(NetworkKind::Ethernet).apply(8000, 60), (8000, 60));
+        assert_eq!(p.cap(NetworkKind::Unknown), NetworkCap::default());
+        assert!(NetworkPolicy::parse("lte=1000").is_err());
+        assert!(NetworkPolicy::parse("wifi=fast").is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn route_dev_is_parsed() {
+        let line = "192.168.1.20 dev wlp3s0 src 192.168.1.7 uid 1000 \\    cache ";
+        assert_eq!(parse_route_dev(line).as_deref(), Some("wlp3s0"));
+        assert_eq!(parse_route_dev("RTNETLINK answers: Network is unreachable"), None);
+    }
+}
@@ -0,0 +1,156 @@
+//! Receiver-side widgets drawn over the remote picture.
+//!
+//! The saved settings' `overlays` list turns a receiver into a room display:
+//!
+//! ```json
+//! "overlays": [
+//!     {"kind": "clock", "format": "%H:%M"},
+//!     {"kind": "timer", "minutes": 45, "corner": "bottomRight"},
+//!     {"kind": "logo", "path": "/usr/share/pixmaps/acme.png", "alpha": 0.6, "corner": "bottomLeft"},
+//!     {"kind": "badge", "text": "● REC", "corner": "topLeft"}
+//! ]
+//! ```
+//!
+//! Widgets are composited by the display pipeline on system-memory frames
+//! only, like the stats overlay, and [`HotkeyAction::ToggleOverlays`]
+//! (`Ctrl+Alt+O`) hides and shows them all.
+//!
+//! [`HotkeyAction::ToggleOverlays`]: crate::HotkeyAction::ToggleOverlays
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+// MARK: - OverlayCorner
+
+/// Which corner of the window a widget sits in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OverlayCorner {
+    /// Shared with the stats overlay when that is shown.
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl OverlayCorner {
+    /// Horizontal and vertical alignment as GStreamer overlay enum nicks.
+    pub fn alignment(self) -> (&'static str, &'static str) {
+        match self {
+            Self::TopLeft     => ("left", "top"),
+            Self::TopRight    => ("right", "top"),
+            Self::BottomLeft  => ("left", "bottom"),
+            Self::BottomRight => ("right", "bottom"),
+        }
+    }
+}
+
+// MARK: - OverlayWidget
+
+/// One widget from the saved settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum OverlayWidget {
+    /// Local wall-clock time; `format` is strftime (`None` = `%H:%M`).
+    Clock {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+        #[serde(default)]
+        corner: OverlayCorner,
+    },
+    /// Time since the session started, or — with `minutes` set — time left
+    /// of a meeting that long, going negative once it overruns.
+    Timer {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        minutes: Option<u32>,
+        #[serde(default)]
+        corner: OverlayCorner,
+    },
+    /// An image file (PNG, JPEG, SVG) as a watermark.
+    Logo {
+        path: PathBuf,
+        /// Opacity, `0.0..=1.0`.
+        #[serde(default = "default_alpha")]
+        alpha: f64,
+        #[serde(default)]
+        corner: OverlayCorner,
+    },
+    /// Fixed text, e.g. a "recording" badge.
+    Badge {
+        text: String,
+        #[serde(default)]
+        corner: OverlayCorner,
+    },
+}
+
+fn default_alpha() -> f64 {
+    1.0
+}
+
+/// strftime format of a [`OverlayWidget::Clock`] without one.
+pub const DEFAULT_CLOCK_FORMAT: &str = "%H:%M";
+
+impl OverlayWidget {
+    pub fn corner(&self) -> OverlayCorner {
+        match self {
+            Self::Clock { corner, .. }
+            | Self::Timer { corner, .. }
+            | Self::Logo { corner, .. }
+            | Self::Badge { corner, .. } => *corner,
+        }
+    }
+}
+
+/// Text of a [`OverlayWidget::Timer`] `elapsed` into the session:
+/// `M:SS` / `H:MM:SS` counting up, or the time left of a `minutes`-long
+/// meeting with a leading `-` once it has run over.
+pub fn timer_text(elapsed: Duration, minutes: Option<u32>) -> String {
+    let elapsed = elapsed.as_secs() as i64;
+    let secs = match minutes {
+        Some(m) => i64::from(m) * 60 - elapsed,
+        None => elapsed,
+    };
+    let sign = if secs < 0 { "-" } else { "" };
+    let secs = secs.unsigned_abs();
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{sign}{h}:{m:02}:{s:02}")
+    } else {
+        format!("{sign}{m}:{s:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReceiverSettings;
+
+    #[test]
+    fn timer_counts_up_and_down() {
+        assert_eq!(timer_text(Duration::from_secs(75), None), "1:15");
+        assert_eq!(timer_text(Duration::from_secs(3725), None), "1:02:05");
+        assert_eq!(timer_text(Duration::from_secs(60), Some(45)), "44:00");
+        assert_eq!(timer_text(Duration::from_secs(45 * 60 + 5), Some(45)), "-0:05");
+    }
+
+    #[test]
+    fn widgets_parse_from_settings() {
+        let settings: ReceiverSettings = serde_json::from_str(
+            r#"{"overlays":[{"kind":"clock"},{"kind":"logo","path":"/tmp/a.png","corner":"bottomLeft"},
+                {"kind":"badge","text":"REC","corner":"topLeft"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.overlays[0],
+            OverlayWidget::Clock { format: None, corner: OverlayCorner::TopRight }
+        );
+        assert_eq!(
+            settings.overlays[1],
+            OverlayWidget::Logo { path: "/tmp/a.png".into(), alpha: 1.0, corner: OverlayCorner::BottomLeft }
+        );
+        assert_eq!(settings.overlays[2].corner().alignment(), ("left", "top"));
+    }
+}
@@ -0,0 +1,424 @@
+//! Parameter-set cache for H.264 / H.265 stream repair.
+//!
+//! A decoder can't decode anything before it has seen the stream's
+//! parameter sets (SPS and PPS, plus VPS for H.265). Senders put them in
+//! front of keyframes, but some only in front of the first one: a decoder
+//! restarted mid-session, or one whose first keyframe was lost, then
+//! rejects every frame. [`ParameterSets`] keeps the last parameter sets
+//! seen on keyframes and [`repair`](ParameterSets::repair) puts missing
+//! ones back in front of a keyframe.
+//!
+//! Frames are Annex-B access units; parameter sets come before the first
+//! slice, so only the head of a frame is scanned.
+//!
+//! [`coded_size`] reads the picture size out of a keyframe's SPS, for
+//! checking a stream against the resolution its session negotiated.
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{Resolution, VideoCodec};
+
+/// 4-byte Annex-B start code put in front of injected NAL units.
+const START_CODE: [u8; 4] = [0, 0, 0, 1];
+
+// MARK: - NAL units
+
+/// NAL unit type of the unit whose header starts `nal`.
+fn nal_type(codec: VideoCodec, nal: &[u8]) -> Option<u8> {
+    let header = *nal.first()?;
+    Some(match codec {
+        VideoCodec::H264 => header & 0x1f,
+        VideoCodec::H265 => (header >> 1) & 0x3f,
+    })
+}
+
+/// Parameter-set NAL types a decoder needs, in stream order.
+fn required(codec: VideoCodec) -> &'static [u8] {
+    match codec {
+        VideoCodec::H264 => &[7, 8],
+        VideoCodec::H265 => &[32, 33, 34],
+    }
+}
+
+/// Whether `ty` is a slice (VCL) NAL type: everything after it is picture
+/// data.
+pub(crate) fn is_slice(codec: VideoCodec, ty: u8) -> bool {
+    match codec {
+        VideoCodec::H264 => (1..=5).contains(&ty),
+        VideoCodec::H265 => ty < 32,
+    }
+}
+
+/// Whether `ty` is an access unit delimiter, which must stay first.
+fn is_delimiter(codec: VideoCodec, ty: u8) -> bool {
+    match codec {
+        VideoCodec::H264 => ty == 9,
+        VideoCodec::H265 => ty == 35,
+    }
+}
+
+/// Types and byte ranges (start codes included) of the NAL units in
+/// `data`, up to and including the first slice, which runs to the end.
+pub(crate) fn leading_nal_units(codec: VideoCodec, data: &[u8]) -> Vec<(u8, std::ops::Range<usize>)> {
+    let mut units: Vec<(u8, std::ops::Range<usize>)> = Vec::new();
+    let mut zeros = 0;
+    for (i, &b) in data.iter().enumerate() {
+        if b == 1 && zeros >= 2 {
+            let begin = i - zeros.min(3);
+            if let Some((_, previous)) = units.last_mut() {
+                previous.end = begin;
+            }
+            let Some(ty) = nal_type(codec, &data[i + 1..]) else { break };
+            units.push((ty, begin..data.len()));
+            if is_slice(codec, ty) {
+                break;
+            }
+        }
+        zeros = if b == 0 { zeros + 1 } else { 0 };
+    }
+    units
+}
+
+// MARK: - Coded size
+
+/// Exp-Golomb bit reader over an SPS payload with emulation prevention
+/// bytes removed.
+struct Bits {
+    rbsp: Vec<u8>,
+    pos:  usize,
+}
+
+impl Bits {
+    fn new(nal: &[u8]) -> Self {
+        let mut rbsp = Vec::with_capacity(nal.len());
+        let mut zeros = 0;
+        for &b in nal {
+            if zeros >= 2 && b == 3 {
+                zeros = 0;
+                continue;
+            }
+            zeros = if b == 0 { zeros + 1 } else { 0 };
+            rbsp.push(b);
+        }
+        Self { rbsp, pos: 0 }
+    }
+
+    fn bit(&mut self) -> Option<u32> {
+        let byte = *self.rbsp.get(self.pos / 8)?;
+        let bit = (byte >> (7 - self.pos % 8)) & 1;
+        self.pos += 1;
+        Some(bit as u32)
+    }
+
+    fn bits(&mut self, n: u32) -> Option<u32> {
+        (0..n).try_fold(0, |v, _| Some(v << 1 | self.bit()?))
+    }
+
+    fn skip(&mut self, n: usize) -> Option<()> {
+        self.pos += n;
+        (self.pos <= self.rbsp.len() * 8).then_some(())
+    }
+
+    /// `ue(v)`.
+    fn ue(&mut self) -> Option<u32> {
+        let mut zeros = 0;
+        while self.bit()? == 0 {
+            zeros += 1;
+            if zeros > 31 {
+                return None;
+            }
+        }
+        Some((1u64 << zeros) as u32 - 1 + self.bits(zeros)?)
+    }
+
+    /// `se(v)`, whose value no caller needs.
+    fn skip_se(&mut self) -> Option<()> {
+        self.ue().map(drop)
+    }
+}
+
+/// Cropping units (SubWidthC, SubHeightC) of `chroma_format_idc`; 1 × 1
+/// for monochrome and 4:4:4.
+fn chroma_units(chroma_format_idc: u32) -> (u32, u32) {
+    match chroma_format_idc {
+        1 => (2, 2),
+        2 => (2, 1),
+        _ => (1, 1),
+    }
+}
+
+/// Displayed size of an H.264 SPS (header byte included): the macroblock
+/// size less the frame cropping.
+fn h264_size(sps: &[u8]) -> Option<Resolution> {
+    let mut b = Bits::new(sps.get(1..)?);
+    let profile_idc = b.bits(8)?;
+    b.skip(16)?; // constraint flags, level_idc
+    b.ue()?; // seq_parameter_set_id
+    let mut chroma_format_idc = 1;
+    if matches!(profile_idc, 100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135) {
+        chroma_format_idc = b.ue()?;
+        if chroma_format_idc == 3 && b.bit()? == 1 {
+            // Colour planes coded separately: cropped like monochrome.
+            chroma_format_idc = 0;
+        }
+        b.ue()?; // bit_depth_luma_minus8
+        b.ue()?; // bit_depth_chroma_minus8
+        b.skip(1)?; // qpprime_y_zero_transform_bypass_flag
+        if b.bit()? == 1 {
+            let lists = if chroma_format_idc == 3 { 12 } else { 8 };
+            for i in 0..lists {
+                if b.bit()? == 1 {
+                    let size = if i < 6 { 16 } else { 64 };
+                    let (mut last, mut next) = (8i64, 8i64);
+                    for _ in 0..size {
+                        if next != 0 {
+                            let delta = b.ue()? as i64;
+                            let delta = if delta % 2 == 1 { (delta + 1) / 2 } else { -(delta / 2) };
+                            next = (last + delta + 256) % 256;
+                        }
+                        if next != 0 {
+                            last = next;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    b.ue()?; // log2_max_frame_num_minus4
+    match b.ue()? {
+        0 => {
+            b.ue()?; // log2_max_pic_order_cnt_lsb_minus4
+        }
+        1 => {
+            b.skip(1)?; // delta_pic_order_always_zero_flag
+            b.skip_se()?; // offset_for_non_ref_pic
+            b.skip_se()?; // offset_for_top_to_bottom_field
+            for _ in 0..b.ue()? {
+                b.skip_se()?;
+            }
+        }
+        _ => {}
+    }
+    b.ue()?; // max_num_ref_frames
+    b.skip(1)?; // gaps_in_frame_num_value_allowed_flag
+    let width_mbs = b.ue()? + 1;
+    let height_map_units = b.ue()? + 1;
+    let frame_mbs_only = b.bit()?;
+    if frame_mbs_only == 0 {
+        b.skip(1)?; // mb_adaptive_frame_field_flag
+    }
+    b.skip(1)?; // direct_8x8_inference_flag
+    let (mut width, mut height) = (width_mbs * 16, height_map_units * 16 * (2 - frame_mbs_only));
+    if b.bit()? == 1 {
+        let (unit_x, unit_y) = chroma_units(chroma_format_idc);
+        let unit_y = unit_y * (2 - frame_mbs_only);
+        let (left, right, top, bottom) = (b.ue()?, b.ue()?, b.ue()?, b.ue()?);
+        width = width.checked_sub((left + right) * unit_x)?;
+        height = height.checked_sub((top + bottom) * unit_y)?;
+    }
+    Some(Resolution::new(width, height))
+}
+
+/// Displayed size of an H.265 SPS (header bytes included): the coded size
+/// less the conformance window.
+fn h265_size(sps: &[u8]) -> Option<Resolution> {
+    let mut b = Bits::new(sps.get(2..)?);
+    b.skip(4)?; // sps_video_parameter_set_id
+    let sub_layers = b.bits(3)? as usize;
+    b.skip(1)?; // sps_temporal_id_nesting_flag
+    // profile_tier_level: general profile, flags and level.
+    b.skip(96)?;
+    let mut present = Vec::with_capacity(sub_layers);
+    for _ in 0..sub_layers {
+        present.push((b.bit()?, b.bit()?));
+    }
+    if sub_layers > 0 {
+        b.skip(2 * (8 - sub_layers))?; // reserved_zero_2bits
+    }
+    for (profile, level) in present {
+        b.skip(88 * profile as usize + 8 * level as usize)?;
+    }
+    b.ue()?; // sps_seq_parameter_set_id
+    let chroma_format_idc = b.ue()?;
+    if chroma_format_idc == 3 {
+        b.skip(1)?; // separate_colour_plane_flag
+    }
+    let (mut width, mut height) = (b.ue()?, b.ue()?);
+    if b.bit()? == 1 {
+        let (sub_w, sub_h) = chroma_units(chroma_format_idc);
+        let (left, right, top, bottom) = (b.ue()?, b.ue()?, b.ue()?, b.ue()?);
+        width = width.checked_sub((left + right) * sub_w)?;
+        height = height.checked_sub((top + bottom) * sub_h)?;
+    }
+    Some(Resolution::new(width, height))
+}
+
+/// Picture size of the SPS in front of `data` (a keyframe), after
+/// cropping: what the encoder really encodes, whatever the session
+/// negotiated. `None` without an SPS or with one too short to parse.
+pub fn coded_size(codec: VideoCodec, data: &[u8]) -> Option<Resolution> {
+    let sps_type = match codec {
+        VideoCodec::H264 => 7,
+        VideoCodec::H265 => 33,
+    };
+    let (_, range) = leading_nal_units(codec, data).into_iter().find(|(ty, _)| *ty == sps_type)?;
+    let unit = &data[range];
+    // Skip the start code.
+    let sps = &unit[unit.iter().position(|&b| b == 1)? + 1..];
+    let size = match codec {
+        VideoCodec::H264 => h264_size(sps)?,
+        VideoCodec::H265 => h265_size(sps)?,
+    };
+    (size.width > 0 && size.height > 0).then_some(size)
+}
+
+// MARK: - ParameterSets
+
+/// What [`ParameterSets::repair`] did to a keyframe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repair {
+    /// The keyframe carried every parameter set.
+    Complete,
+    /// This many cached parameter sets were put in front of it.
+    Injected(usize),
+    /// Parameter sets are missing and none are cached: the keyframe can't
+    /// be decoded by a fresh decoder.
+    Missing,
+}
+
+/// The last parameter sets of one stream.
+#[derive(Debug, Clone)]
+pub struct ParameterSets {
+    codec: VideoCodec,
+    /// NAL type and unit (with start code), one per type of [`required`].
+    sets:  Vec<(u8, Bytes)>,
+}
+
+impl ParameterSets {
+    pub fn new(codec: VideoCodec) -> Self {
+        Self { codec, sets: Vec::new() }
+    }
+
+    pub fn codec(&self) -> VideoCodec {
+        self.codec
+    }
+
+    /// `true` once every parameter set the codec needs has been seen.
+    pub fn is_complete(&self) -> bool {
+        required(self.codec).iter().all(|ty| self.sets.iter().any(|(t, _)| t == ty))
+    }
+
+    /// Remember the parameter sets in front of `data` (a keyframe).
+    pub fn observe(&mut self, data: &Bytes) {
+        for (ty, range) in leading_nal_units(self.codec, data) {
+            if !required(self.codec).contains(&ty) {
+                continue;
+            }
+            let unit = data.slice(range);
+            match self.sets.iter_mut().find(|(t, _)| *t == ty) {
+                Some(slot) => slot.1 = unit,
+                None => self.sets.push((ty, unit)),
+            }
+        }
+    }
+
+    /// Put the cached parameter sets `data` lacks in front of its first
+    /// slice (after an access unit delimiter, if any).
+    pub fn repair(&self, data: &mut Bytes) -> Repair {
+        let units = leading_nal_units(self.codec, data);
+        let missing: Vec<u8> =
+            required(self.codec).iter().copied().filter(|ty| !units.iter().any(|(t, _)| t == ty)).collect();
+        if missing.is_empty() {
+            return Repair::Complete;
+        }
+        let cached: Option<Vec<&Bytes>> =
+            missing.iter().map(|ty| self.sets.iter().find(|(t, _)| t == ty).map(|(_, unit)| unit)).collect();
+        let Some(cached) = cached else { return Repair::Missing };
+
+        let at = match units.first() {
+            Some((ty, range)) if is_delimiter(self.codec, *ty) => range.end,
+            _ => 0,
+        };
+        let extra: usize = cached.iter().map(|u| u.len() + START_CODE.len()).sum();
+        let mut repaired = BytesMut::with_capacity(data.len() + extra);
+        repaired.extend_from_slice(&data[..at]);
+        for unit in &cached {
+            // Cached units keep their own start code, which may be 3 bytes.
+            if !unit.starts_with(&START_CODE) {
+                repaired.extend_from_slice(&[0]);
+            }
+            repaired.extend_from_slice(unit);
+        }
+        repaired.extend_from_slice(&data[at..]);
+        *data = repaired.freeze();
+        Repair::Injected(cached.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPS: &[u8] = &[0, 0, 0, 1, 0x67, 0x42, 0xc0, 0x1f];
+    const PPS: &[u8] = &[0, 0, 1, 0x68, 0xce, 0x3c, 0x80];
+    const IDR: &[u8] = &[0, 0, 0, 1, 0x65, 0x88, 0x84, 0x00, 0x00, 0x03, 0x01];
+    const AUD: &[u8] = &[0, 0, 0, 1, 0x09, 0xf0];
+
+    fn frame(parts: &[&[u8]]) -> Bytes {
+        Bytes::from(parts.concat())
+    }
+
+    #[test]
+    fn injects_cached_sets_into_bare_keyframes() {
+        let mut sets = ParameterSets::new(VideoCodec::H264);
+        let mut bare = frame(&[IDR]);
+        assert_eq!(sets.repair(&mut bare), Repair::Missing);
+
+        let full = frame(&[SPS, PPS, IDR]);
+        sets.observe(&full);
+        assert!(sets.is_complete());
+        assert_eq!(sets.repair(&mut full.clone()), Repair::Complete);
+
+        assert_eq!(sets.repair(&mut bare), Repair::Injected(2));
+        assert_eq!(bare, frame(&[SPS, &[0], PPS, IDR]));
+
+        // Parameter sets go after the access unit delimiter.
+        let mut delimited = frame(&[AUD, PPS, IDR]);
+        assert_eq!(sets.repair(&mut delimited), Repair::Injected(1));
+        assert_eq!(delimited, frame(&[AUD, SPS, PPS, IDR]));
+    }
+
+    #[test]
+    fn scans_only_up_to_the_first_slice() {
+        // Slice data that happens to look like an SPS start code.
+        let data = frame(&[IDR, &[0, 0, 1, 0x67, 0x00]]);
+        let units = leading_nal_units(VideoCodec::H264, &data);
+        assert_eq!(units.iter().map(|(t, _)| *t).collect::<Vec<_>>(), [5]);
+
+        let hevc = frame(&[&[0, 0, 0, 1, 0x40, 0x01], &[0, 0, 0, 1, 0x42, 0x01], &[0, 0, 0, 1, 0x44, 0x01]]);
+        let mut sets = ParameterSets::new(VideoCodec::H265);
+        sets.observe(&hevc);
+        assert!(sets.is_complete());
+    }
+
+    #[test]
+    fn coded_size_reads_the_cropped_sps_size() {
+        // x264 1080p High: 120 × 68 macroblocks cropped by 8 rows.
+        let h264_sps: &[u8] = &[
+            0, 0, 0, 1, 0x67, 0x64, 0x00, 0x28, 0xac, 0xd9, 0x40, 0x78, 0x02, 0x27, 0xe5, 0xc0, 0x44, 0x00, 0x00,
+            0x03, 0x00, 0x04, 0x00, 0x00, 0x03, 0x00, 0xf0, 0x3c, 0x60, 0xc6, 0x58,
+        ];
+        let frame = frame(&[AUD, h264_sps, PPS, IDR]);
+        assert_eq!(coded_size(VideoCodec::H264, &frame), Some(Resolution::FHD));
+        assert_eq!(coded_size(VideoCodec::H264, IDR), None);
+        assert_eq!(coded_size(VideoCodec::H264, &h264_sps[..8]), None);
+
+        // x265 1080p Main: 1920 × 1088 with an 8-row conformance window.
+        let h265_sps: &[u8] = &[
+            0, 0, 0, 1, 0x42, 0x01, 0x01, 0x01, 0x60, 0x00, 0x00, 0x03, 0x00, 0x90, 0x00, 0x00, 0x03, 0x00, 0x00,
+            0x03, 0x00, 0x5d, 0xa0, 0x03, 0xc0, 0x80, 0x10, 0xe5, 0x96, 0x66, 0x69, 0x24, 0xca, 0xe0, 0x10, 0x00,
+            0x00, 0x03, 0x00, 0x10, 0x00, 0x00, 0x03, 0x01, 0xe0, 0x80,
+        ];
+        assert_eq!(coded_size(VideoCodec::H265, h265_sps), Some(Resolution::FHD));
+    }
+}
@@ -0,0 +1,180 @@
+//! Per-display UDP/TCP port layout.
+//!
+//! By default display `n` uses UDP `7878 + 2n` for video and TCP `7879 + 2n`
+//! for signaling. Receivers may move the base port or bind individual
+//! displays elsewhere, so they advertise the actual layout as a [`PortMap`]
+//! — in the mDNS `ports` TXT key and in every `hello_ack` — and senders look
+//! ports up there instead of computing them.
+
+use serde::{Deserialize, Serialize};
+
+/// UDP video port of display 0 in the default layout.
+pub const DEFAULT_VIDEO_PORT: u16 = 7878;
+/// TCP signaling port of display 0 in the default layout.
+pub const DEFAULT_SIGNALING_PORT: u16 = 7879;
+
+// MARK: - DisplayPorts
+
+/// The port pair one display is served on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayPorts {
+    pub display_index: u8,
+    /// UDP video port.
+    pub video:         u16,
+    /// TCP (TLS) signaling port.
+    pub signaling:     u16,
+}
+
+impl DisplayPorts {
+    /// Ports of display `display_index` when display 0's video port is
+    /// `base`: UDP `base + 2n`, TCP `base + 2n + 1`.
+    pub fn from_base(base: u16, display_index: u8) -> Self {
+        let video = base.saturating_add(display_index as u16 * 2);
+        Self { display_index, video, signaling: video.saturating_add(1) }
+    }
+}
+
+// MARK: - PortMap
+
+/// Port pairs of a receiver's displays, sorted by display index.
+///
+/// Serialized as a JSON array of [`DisplayPorts`]. Lookups for displays
+/// missing from the map extrapolate the `+2n` layout from the lowest listed
+/// display, so an empty map means the default ports.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PortMap {
+    displays: Vec<DisplayPorts>,
+}
+
+impl PortMap {
+    /// Build a map from individual port pairs; later duplicates of a
+    /// display index replace earlier ones.
+    pub fn new(ports: impl IntoIterator<Item = DisplayPorts>) -> Self {
+        let mut map = Self::default();
+        for p in ports {
+            map.insert(p);
+        }
+        map
+    }
+
+    /// `count` displays laid out `+2n` from `base` (display 0's video port).
+    pub fn contiguous(base: u16, count: u8) -> Self {
+        Self::new((0..count).map(|n| DisplayPorts::from_base(base, n)))
+    }
+
+    /// Add or replace the ports of `ports.display_index`.
+    pub fn insert(&mut self, ports: DisplayPorts) {
+        match self.displays.binary_search_by_key(&ports.display_index, |p| p.display_index) {
+            Ok(i) => self.displays[i] = ports,
+            Err(i) => self.displays.insert(i, ports),
+        }
+    }
+
+    /// The advertised ports of `display_index`, if listed.
+    pub fn get(&self, display_index: u8) -> Option<DisplayPorts> {
+        self.displays.iter().find(|p| p.display_index == display_index).copied()
+    }
+
+    /// Ports of `display_index`: the listed pair, else the `+2n` layout
+    /// continued from the lowest listed display (or the defaults).
+    pub fn ports(&self, display_index: u8) -> DisplayPorts {
+        if let Some(p) = self.get(display_index) {
+            return p;
+        }
+        let base = self
+            .displays
+            .first()
+            .map(|p| p.video.saturating_sub(p.display_index as u16 * 2))
+            .unwrap_or(DEFAULT_VIDEO_PORT);
+        DisplayPorts::from_base(base, display_index)
+    }
+
+    /// UDP video port of `display_index` (see [`ports`](Self::ports)).
+    pub fn video_port(&self, display_index: u8) -> u16 {
+        self.ports(display_index).video
+    }
+
+    /// TCP signaling port of `display_index` (see [`ports`](Self::ports)).
+    pub fn signaling_port(&self, display_index: u8) -> u16 {
+        self.ports(display_index).signaling
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DisplayPorts> {
+        self.displays.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.displays.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.displays.is_empty()
+    }
+
+    /// Compact form for the mDNS `ports` TXT key: `0=7878/7879,1=7880/7881`.
+    pub fn to_txt(&self) -> String {
+        self.displays
+            .iter()
+            .map(|p| format!("{}={}/{}", p.display_index, p.video, p.signaling))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Parse [`to_txt`](Self::to_txt) output; `None` if any entry is malformed.
+    pub fn from_txt(s: &str) -> Option<Self> {
+        s.split(',')
+            .filter(|e| !e.trim().is_empty())
+            .map(|entry| {
+                let (index, pair) = entry.trim().split_once('=')?;
+                let (video, signaling) = pair.split_once('/')?;
+                Some(DisplayPorts {
+                    display_index: index.parse().ok()?,
+                    video:         video.parse().ok()?,
+                    signaling:     signaling.parse().ok()?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Self::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DisplayPorts, PortMap, DEFAULT_SIGNALING_PORT, DEFAULT_VIDEO_PORT};
+
+    #[test]
+    fn empty_map_is_default_layout() {
+        let map = PortMap::default();
+        assert_eq!(map.video_port(0), DEFAULT_VIDEO_PORT);
+        assert_eq!(map.signaling_port(0), DEFAULT_SIGNALING_PORT);
+        assert_eq!(map.signaling_port(3), DEFAULT_SIGNALING_PORT + 6);
+    }
+
+    #[test]
+    fn listed_ports_win_and_layout_extrapolates() {
+        let map = PortMap::new([
+            DisplayPorts { display_index: 1, video: 9002, signaling: 9003 },
+            DisplayPorts { display_index: 2, video: 40000, signaling: 40001 },
+        ]);
+        assert_eq!(map.video_port(2), 40000);
+        // Display 0 is missing: continue the layout from display 1.
+        assert_eq!(map.ports(0), DisplayPorts { display_index: 0, video: 9000, signaling: 9001 });
+    }
+
+    #[test]
+    fn txt_round_trip() {
+        let map = PortMap::contiguous(9000, 3);
+        assert_eq!(map.to_txt(), "0=9000/9001,1=9002/9003,2=9004/9005");
+        assert_eq!(PortMap::from_txt(&map.to_txt()), Some(map));
+        assert_eq!(PortMap::from_txt(""), Some(PortMap::default()));
+        assert_eq!(PortMap::from_txt("0=9000"), None);
+    }
+
+    #[test]
+    fn serializes_as_array() {
+        let json = serde_json::to_string(&PortMap::contiguous(7878, 1)).unwrap();
+        assert_eq!(json, r#"[{"displayIndex":0,"video":7878,"signaling":7879}]"#);
+    }
+}
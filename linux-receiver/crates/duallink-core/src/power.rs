@@ -0,0 +1,180 @@
+//! Battery awareness and the battery-saver profile.
+//!
+//! Senders and receivers read their machine's power source every
+//! [`POWER_POLL_INTERVAL`] with [`read_power`]: on Linux from the sysfs
+//! supply class UPower itself reads, on Windows from
+//! `GetSystemPowerStatus`. An end running on battery below the configured
+//! charge wants battery saver:
+//!
+//! ```text
+//! DUALLINK_BATTERY_SAVER_BELOW=30    # percent (default)
+//! DUALLINK_BATTERY_SAVER_BELOW=off   # never switch automatically
+//! ```
+//!
+//! Both ends exchange their [`PowerState`] in `power` messages when the
+//! other side advertises [`CAP_POWER`]. The sender streams with
+//! [`QualityPreset::BatterySaver`](crate::QualityPreset::BatterySaver)
+//! while either end wants saver and goes back to its own preset once
+//! neither does, so both UIs show the same profile.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Capability (in `hello` / `hello_ack`): sends and accepts `power`
+/// messages.
+pub const CAP_POWER: &str = "power";
+
+/// How often each end re-reads its power source.
+pub const POWER_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Charge (percent) below which an end on battery wants saver by default.
+pub const DEFAULT_SAVER_BELOW: u8 = 30;
+
+// MARK: - PowerState
+
+/// One end's power source, as shown in the UIs and sent to the peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerState {
+    /// Running from the battery rather than mains power.
+    pub on_battery: bool,
+    /// Remaining charge; `None` without a system battery.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent:    Option<u8>,
+    /// This end wants the battery-saver profile.
+    #[serde(default)]
+    pub saver:      bool,
+}
+
+impl PowerState {
+    /// A state whose `saver` flag follows `saver_below` (see
+    /// [`saver_below`]).
+    pub fn new(on_battery: bool, percent: Option<u8>, saver_below: Option<u8>) -> Self {
+        let saver = on_battery && matches!((percent, saver_below), (Some(p), Some(below)) if p < below);
+        Self { on_battery, percent, saver }
+    }
+}
+
+impl fmt::Display for PowerState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.on_battery { "on battery" } else { "on AC" })?;
+        if let Some(p) = self.percent {
+            write!(f, " {p}%")?;
+        }
+        if self.saver {
+            f.write_str(" (saver)")?;
+        }
+        Ok(())
+    }
+}
+
+/// The configured saver threshold (`DUALLINK_BATTERY_SAVER_BELOW`);
+/// `None` if automatic switching is off.
+pub fn saver_below() -> Option<u8> {
+    match std::env::var("DUALLINK_BATTERY_SAVER_BELOW") {
+        Err(_) => Some(DEFAULT_SAVER_BELOW),
+        Ok(s) if s.trim().eq_ignore_ascii_case("off") => None,
+        Ok(s) => s.trim().trim_end_matches('%').parse().map(|p: u8| p.min(100)).ok().or_else(|| {
+            tracing::warn!("Ignoring DUALLINK_BATTERY_SAVER_BELOW='{s}' — expected a percentage or off");
+            Some(DEFAULT_SAVER_BELOW)
+        }),
+    }
+}
+
+// MARK: - Platform readers
+
+/// This machine's power state; `None` without a system battery (desktops),
+/// where there is nothing to show or save.
+///
+/// Reads `/sys/class/power_supply`: a `Battery` supply with `scope` other
+/// than `Device` (mice and headsets report theirs too) is the system
+/// battery; the machine is on battery while no `Mains` supply is online,
+/// or — without a mains entry — while the battery is discharging.
+#[cfg(target_os = "linux")]
+pub fn read_power() -> Option<PowerState> {
+    let read = |path: std::path::PathBuf| std::fs::read_to_string(path).map(|s| s.trim().to_owned()).ok();
+
+    let mut battery: Option<(u8, bool)> = None;
+    let mut mains_online: Option<bool> = None;
+    for entry in std::fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+        let dir = entry.path();
+        match read(dir.join("type")).as_deref() {
+            Some("Mains") => {
+                let online = read(dir.join("online")).as_deref() == Some("1");
+                mains_online = Some(mains_online.unwrap_or(false) || online);
+            }
+            Some("Battery") if read(dir.join("scope")).as_deref() != Some("Device") && battery.is_none() => {
+                let Some(capacity) = read(dir.join("capacity")).and_then(|c| c.parse::<u8>().ok()) else {
+                    continue;
+                };
+                let discharging = read(dir.join("status")).as_deref() == Some("Discharging");
+                battery = Some((capacity.min(100), discharging));
+            }
+            _ => {}
+        }
+    }
+    let (percent, discharging) = battery?;
+    let on_battery = mains_online.map_or(discharging, |online| !online);
+    Some(PowerState::new(on_battery, Some(percent), saver_below()))
+}
+
+/// This machine's power state from `GetSystemPowerStatus`; `None` without
+/// a system battery or while Windows does not know.
+#[cfg(windows)]
+pub fn read_power() -> Option<PowerState> {
+    /// `SYSTEM_POWER_STATUS` (winbase.h).
+    #[repr(C)]
+    #[derive(Default)]
+    #[allow(dead_code)] // filled in by Windows
+    struct SystemPowerStatus {
+        ac_line_status:         u8,
+        battery_flag:           u8,
+        battery_life_percent:   u8,
+        system_status_flag:     u8,
+        battery_life_time:      u32,
+        battery_full_life_time: u32,
+    }
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+
+    let mut status = SystemPowerStatus::default();
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+    // 128 = no system battery, 255 = unknown.
+    if status.battery_flag & 128 != 0 || status.battery_life_percent > 100 {
+        return None;
+    }
+    let on_battery = status.ac_line_status == 0;
+    Some(PowerState::new(on_battery, Some(status.battery_life_percent), saver_below()))
+}
+
+/// Stub for other platforms.
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn read_power() -> Option<PowerState> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saver_only_on_battery_below_threshold() {
+        assert!(PowerState::new(true, Some(20), Some(30)).saver);
+        assert!(!PowerState::new(true, Some(30), Some(30)).saver);
+        assert!(!PowerState::new(false, Some(5), Some(30)).saver);
+        assert!(!PowerState::new(true, Some(5), None).saver);
+        assert!(!PowerState::new(true, None, Some(30)).saver);
+
+        let state = PowerState::new(true, Some(12), Some(30));
+        assert_eq!(state.to_string(), "on battery 12% (saver)");
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(json, r#"{"onBattery":true,"percent":12,"saver":true}"#);
+        assert_eq!(serde_json::from_str::<PowerState>(&json).unwrap(), state);
+    }
+}
@@ -0,0 +1,233 @@
+//! Queue depths between pipeline stages.
+//!
+//! Every queue between two stages trades latency for smoothness: a deeper
+//! queue rides out a burst from the network or a slow frame from the
+//! decoder, but each frame waiting in it shows up that much later. The
+//! defaults suit a LAN at 60 fps; on a jittery link a few more frames of
+//! slack may look better, on a clean cable fewer feel snappier.
+//!
+//! [`ReceiverQueues`] is set in the receiver settings (`queues`) and
+//! [`SenderQueues`] in the sender's pipeline config; both take
+//! `DUALLINK_QUEUES` on top:
+//!
+//! ```text
+//! DUALLINK_QUEUES=frames=16,decoded=2,drop-decoded=true   # receiver
+//! DUALLINK_QUEUES=captured=1,encoded=2,input=128          # sender
+//! ```
+//!
+//! Each side ignores the other's keys. Combinations that would stall a
+//! pipeline are refused by `validate`, and then the defaults are used.
+
+use serde::{Deserialize, Serialize};
+
+/// Deepest queue accepted; at 60 fps 1024 frames are 17 s of video.
+pub const MAX_QUEUE_DEPTH: usize = 1024;
+
+const RECEIVER_KEYS: [&str; 4] = ["frames", "events", "decoded", "drop-decoded"];
+const SENDER_KEYS: [&str; 4] = ["captured", "encoded", "encoded-frames", "input"];
+
+/// `key=value` entries of a `DUALLINK_QUEUES` spec.
+fn entries(spec: &str) -> Result<Vec<(&str, &str)>, String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|e| {
+            e.split_once('=').map(|(k, v)| (k.trim(), v.trim())).ok_or_else(|| format!("'{e}': expected key=value"))
+        })
+        .collect()
+}
+
+fn depth(key: &str, value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("{key}: bad number '{value}'"))
+}
+
+fn flag(key: &str, value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" => Ok(true),
+        "0" | "false" | "no" => Ok(false),
+        _ => Err(format!("{key}: expected true or false, got '{value}'")),
+    }
+}
+
+fn check_depth(name: &str, n: usize) -> Result<(), String> {
+    if (1..=MAX_QUEUE_DEPTH).contains(&n) {
+        Ok(())
+    } else {
+        Err(format!("{name} must be 1–{MAX_QUEUE_DEPTH}, got {n}"))
+    }
+}
+
+/// `tuning` with `DUALLINK_QUEUES` applied, or the defaults if either is
+/// invalid.
+fn with_env<T: Default>(
+    tuning: T,
+    apply: fn(T, &str) -> Result<T, String>,
+    validate: fn(&T) -> Result<(), String>,
+) -> T {
+    let tuning = match std::env::var("DUALLINK_QUEUES") {
+        Ok(spec) => apply(tuning, &spec).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring DUALLINK_QUEUES: {e}");
+            T::default()
+        }),
+        Err(_) => tuning,
+    };
+    match validate(&tuning) {
+        Ok(()) => tuning,
+        Err(e) => {
+            tracing::warn!("Queue depths refused: {e} — using the defaults");
+            T::default()
+        }
+    }
+}
+
+// MARK: - ReceiverQueues
+
+/// Queue depths on the receiver, per display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ReceiverQueues {
+    /// Reassembled frames waiting for the decoder. When it fills, the UDP
+    /// task stops reading and the socket buffer absorbs the rest; every
+    /// frame queued adds one frame time of latency while the decoder
+    /// catches up.
+    pub frames:       usize,
+    /// Signaling events (session start, link stats, …) waiting for the
+    /// session task. Stats events are dropped when it is full; latency is
+    /// not affected.
+    pub events:       usize,
+    /// Decoded pictures the decoder's `appsink` holds (`max-buffers`).
+    /// More smooths uneven decode times at one frame time each.
+    pub decoded:      usize,
+    /// Drop the oldest decoded picture when `decoded` is full instead of
+    /// making the decoder wait (`drop`). Keeps latency flat at the cost of
+    /// skipped frames.
+    pub drop_decoded: bool,
+}
+
+impl Default for ReceiverQueues {
+    fn default() -> Self {
+        Self { frames: 64, events: 16, decoded: 4, drop_decoded: true }
+    }
+}
+
+impl ReceiverQueues {
+    /// `self` with the receiver keys of `spec` (`key=value,…`) applied.
+    pub fn apply(mut self, spec: &str) -> Result<Self, String> {
+        for (key, value) in entries(spec)? {
+            match key {
+                "frames" => self.frames = depth(key, value)?,
+                "events" => self.events = depth(key, value)?,
+                "decoded" => self.decoded = depth(key, value)?,
+                "drop-decoded" => self.drop_decoded = flag(key, value)?,
+                k if SENDER_KEYS.contains(&k) => {}
+                k => return Err(format!("'{k}': unknown queue")),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Refuse depths out of range, and a blocking decoded queue of one
+    /// picture: the decoder would wait on the sink after every frame.
+    pub fn validate(&self) -> Result<(), String> {
+        check_depth("frames", self.frames)?;
+        check_depth("events", self.events)?;
+        check_depth("decoded", self.decoded)?;
+        if !self.drop_decoded && self.decoded < 2 {
+            return Err("decoded must be at least 2 unless drop-decoded is set".into());
+        }
+        Ok(())
+    }
+
+    /// The saved [`ReceiverSettings::queues`](crate::ReceiverSettings::queues)
+    /// with `DUALLINK_QUEUES` applied; the defaults if invalid.
+    pub fn configured() -> Self {
+        with_env(crate::ReceiverSettings::load().queues, Self::apply, Self::validate)
+    }
+}
+
+// MARK: - SenderQueues
+
+/// Queue depths on the sender, per display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SenderQueues {
+    /// Captured frames the capture `appsink` holds (`max-buffers`); the
+    /// oldest is dropped when full, so a deeper queue only sends staler
+    /// frames.
+    pub captured:       usize,
+    /// Encoded frames the encoder's `appsink` holds (`max-buffers`). Never
+    /// dropped — a lost frame would corrupt the picture until the next
+    /// keyframe — so the encoder waits when it is full.
+    pub encoded:        usize,
+    /// Encoded frames waiting to be packetized and sent. A burst the link
+    /// can't take queues here; each frame adds one frame time of latency.
+    pub encoded_frames: usize,
+    /// Input events from the receiver waiting to be injected. Pointer
+    /// motion comes in bursts; events beyond this wait in the socket.
+    pub input:          usize,
+}
+
+impl Default for SenderQueues {
+    fn default() -> Self {
+        Self { captured: 2, encoded: 4, encoded_frames: 16, input: 256 }
+    }
+}
+
+impl SenderQueues {
+    /// `self` with the sender keys of `spec` (`key=value,…`) applied.
+    pub fn apply(mut self, spec: &str) -> Result<Self, String> {
+        for (key, value) in entries(spec)? {
+            match key {
+                "captured" => self.captured = depth(key, value)?,
+                "encoded" => self.encoded = depth(key, value)?,
+                "encoded-frames" => self.encoded_frames = depth(key, value)?,
+                "input" => self.input = depth(key, value)?,
+                k if RECEIVER_KEYS.contains(&k) => {}
+                k => return Err(format!("'{k}': unknown queue")),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Refuse depths out of range, and an encoded-frame queue shallower
+    /// than the encoder's `appsink`: a keyframe burst would block the
+    /// encoder while the sink still had room.
+    pub fn validate(&self) -> Result<(), String> {
+        check_depth("captured", self.captured)?;
+        check_depth("encoded", self.encoded)?;
+        check_depth("encoded-frames", self.encoded_frames)?;
+        check_depth("input", self.input)?;
+        if self.encoded_frames < self.encoded {
+            return Err(format!("encoded-frames ({}) must be at least encoded ({})", self.encoded_frames, self.encoded));
+        }
+        Ok(())
+    }
+
+    /// The defaults with `DUALLINK_QUEUES` applied; the defaults if invalid.
+    pub fn from_env() -> Self {
+        with_env(Self::default(), Self::apply, Self::validate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs_apply_per_side_and_validate() {
+        let spec = "frames=16, decoded=2, drop-decoded=false, input=64";
+        let receiver = ReceiverQueues::default().apply(spec).unwrap();
+        assert_eq!(receiver, ReceiverQueues { frames: 16, events: 16, decoded: 2, drop_decoded: false });
+        assert!(receiver.validate().is_ok());
+        let sender = SenderQueues::default().apply(spec).unwrap();
+        assert_eq!(sender, SenderQueues { input: 64, ..SenderQueues::default() });
+
+        assert!(ReceiverQueues::default().apply("frame=3").is_err());
+        assert!(ReceiverQueues::default().apply("drop-decoded=maybe").is_err());
+        let blocking = ReceiverQueues { decoded: 1, drop_decoded: false, ..ReceiverQueues::default() };
+        assert!(blocking.validate().is_err());
+        assert!(ReceiverQueues { frames: 0, ..ReceiverQueues::default() }.validate().is_err());
+        assert!(SenderQueues::default().apply("encoded=8").unwrap().validate().is_ok());
+        assert!(SenderQueues::default().apply("encoded=32").unwrap().validate().is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//! Resumable sessions — senders reconnecting after a receiver restart skip
+//! the pairing PIN.
+//!
+//! Every accepted `hello` is answered with a fresh `resumeToken` in
+//! `hello_ack`, which the receiver records in [`ResumeTokens`] (stored as
+//! JSON in `duallink/resume-tokens.json` under the user config directory, so
+//! it outlives the process). A `hello` carrying a recorded token for the same
+//! display is accepted without the PIN — even by a receiver started since —
+//! and the session resumes at a keyframe. Tokens are good once: the resumed
+//! session's `hello_ack` hands out the next. They expire after the resume
+//! TTL (see `duallink_transport::configured_resume_ttl`).
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::config_file;
+
+const FILE_NAME: &str = "resume-tokens.json";
+
+/// How long a resume token stays valid unless configured otherwise.
+pub const DEFAULT_RESUME_TTL: Duration = Duration::from_secs(12 * 60 * 60);
+
+// MARK: - ResumeEntry
+
+/// The session a resume token was handed out for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeEntry {
+    pub session_id:    String,
+    pub device_name:   String,
+    pub display_index: u8,
+    /// Unix time the token was issued, in seconds.
+    pub issued_at:     u64,
+}
+
+impl ResumeEntry {
+    fn expired(&self, ttl: Duration, now: u64) -> bool {
+        now.saturating_sub(self.issued_at) >= ttl.as_secs()
+    }
+}
+
+// MARK: - ResumeTokens
+
+/// Resume tokens handed out and not yet used or expired, by token.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResumeTokens {
+    tokens: BTreeMap<String, ResumeEntry>,
+}
+
+impl ResumeTokens {
+    /// Load the saved tokens; empty if none were saved or the file is unreadable.
+    pub fn load() -> Self {
+        let Some(path) = config_file(FILE_NAME) else { return Self::default() };
+        std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the tokens back to the config directory.
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = config_file(FILE_NAME).ok_or_else(|| std::io::Error::other("no config directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        self.save_to(&path)
+    }
+
+    /// Write the tokens to `path`. Tokens stand in for the PIN, so the file
+    /// is owner-only on unix; it is written beside `path` and renamed over
+    /// it, so a crash never leaves a truncated store.
+    fn save_to(&self, path: &Path) -> std::io::Result<()> {
+        let tmp = path.with_extension("json.tmp");
+        // A leftover from a crashed save may have other permissions; `mode`
+        // only applies to files it creates.
+        let _ = std::fs::remove_file(&tmp);
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&tmp)?;
+        file.write_all(&serde_json::to_vec_pretty(self)?)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)
+    }
+
+    /// Record `token` for `entry`'s session, dropping expired tokens and any
+    /// earlier one for the same display.
+    pub fn insert(&mut self, token: String, entry: ResumeEntry, ttl: Duration) {
+        let now = entry.issued_at;
+        self.tokens.retain(|_, e| !e.expired(ttl, now) && e.display_index != entry.display_index);
+        self.tokens.insert(token, entry);
+    }
+
+    /// Use up `token`: the session it was issued for, if it was issued for
+    /// `display_index` less than `ttl` before `now` (Unix seconds).
+    pub fn redeem(&mut self, token: &str, display_index: u8, ttl: Duration, now: u64) -> Option<ResumeEntry> {
+        let entry = self.tokens.get(token).filter(|e| e.display_index == display_index)?;
+        let valid = !entry.expired(ttl, now);
+        let entry = self.tokens.remove(token)?;
+        valid.then_some(entry)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_secs(3600);
+
+    fn entry(display_index: u8, issued_at: u64) -> ResumeEntry {
+        ResumeEntry { session_id: "s".into(), device_name: "mac".into(), display_index, issued_at }
+    }
+
+    #[test]
+    fn tokens_are_good_once_for_their_display() {
+        let mut tokens = ResumeTokens::default();
+        tokens.insert("a".into(), entry(0, 1000), TTL);
+        tokens.insert("b".into(), entry(1, 1000), TTL);
+        assert_eq!(tokens.redeem("b", 0, TTL, 1001), None);
+        assert_eq!(tokens.redeem("a", 0, TTL, 1001), Some(entry(0, 1000)));
+        assert_eq!(tokens.redeem("a", 0, TTL, 1002), None);
+        assert_eq!(tokens.len(), 1);
+    }
+
+    #[test]
+    fn tokens_expire_and_are_replaced() {
+        let mut tokens = ResumeTokens::default();
+        tokens.insert("a".into(), entry(0, 1000), TTL);
+        assert_eq!(tokens.redeem("a", 0, TTL, 1000 + 3600), None);
+        assert!(tokens.is_empty());
+
+        tokens.insert("a".into(), entry(0, 1000), TTL);
+        tokens.insert("b".into(), entry(1, 1000), TTL);
+        tokens.insert("c".into(), entry(0, 1100), TTL);
+        assert_eq!(tokens.redeem("a", 0, TTL, 1101), None);
+        // An expired token for another display is dropped on insert too.
+        tokens.insert("d".into(), entry(2, 1000 + 3600), TTL);
+        assert_eq!(tokens.len(), 2);
+    }
+
+    #[test]
+    fn save_replaces_the_store_owner_only() {
+        let dir = std::env::temp_dir().join(format!("duallink-resume-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(FILE_NAME);
+        std::fs::write(&path, b"{\"tokens\":").unwrap();
+        std::fs::write(path.with_extension("json.tmp"), b"stale").unwrap();
+
+        let mut tokens = ResumeTokens::default();
+        tokens.insert("a".into(), entry(0, 1000), TTL);
+        tokens.save_to(&path).unwrap();
+
+        let saved: ResumeTokens = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(saved, tokens);
+        assert!(!path.with_extension("json.tmp").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+//! Receiver settings persisted in the user config directory.
+//!
+//! Stored as JSON in `duallink/receiver.json`. Environment variables
+//! (e.g. `DUALLINK_DECODER`) take precedence over the saved values; the GUI
+//! receiver writes the file when the user changes a setting.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{HotkeyAction, OverlayWidget, ReceiverQueues};
+
+// MARK: - ReceiverSettings
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ReceiverSettings {
+    /// Decoder elements to try first, in order (empty = probe order).
+    pub decoder_preference: Vec<String>,
+    /// Commands and webhooks run on session events.
+    pub hooks:              Vec<SessionHook>,
+    /// UDP video port of display 0 (`None` = 7878); display `n` uses
+    /// `base + 2n` / `base + 2n + 1`.
+    pub base_port:          Option<u16>,
+    /// Make sessions display-only: input from the receiver's windows is
+    /// not sent back to the sender.
+    pub view_only:          bool,
+    /// Rebound display-window hotkeys, e.g. `{"endSession": "Ctrl+Shift+F12"}`
+    /// (see [`crate::hotkeys`]); unlisted actions keep their defaults.
+    pub hotkeys:            BTreeMap<HotkeyAction, String>,
+    /// File each session's [`SessionSummary`](crate::SessionSummary) is
+    /// appended to (CSV if it ends in `.csv`, else JSON lines); `None` = off.
+    pub usage_history:      Option<PathBuf>,
+    /// Widgets drawn over the remote picture (see [`crate::overlay`]).
+    pub overlays:           Vec<OverlayWidget>,
+    /// Seconds a session's resume token stays valid (`None` = 12 hours,
+    /// `0` = sessions can't be resumed; see [`crate::resume`]).
+    pub resume_ttl_secs:    Option<u64>,
+    /// Longest a display's frame waits for the other displays' frames of the
+    /// same moment, in ms (`None` = 16, `0` = off; see
+    /// [`crate::display_sync`]).
+    pub display_sync_ms:    Option<u64>,
+    /// Seconds a display may go without frames before it reports a stalled
+    /// stream (`None` = 5, `0` = never).
+    pub stall_secs:         Option<u64>,
+    /// Give every display its own pairing PIN, so senders paired with one
+    /// display can't take over another.
+    pub separate_pins:      bool,
+    /// PEM certificate chain (leaf first) the receiver presents instead of
+    /// a self-signed one; needs [`tls_key`](Self::tls_key).
+    pub tls_cert:           Option<PathBuf>,
+    /// PEM private key of [`tls_cert`](Self::tls_cert).
+    pub tls_key:            Option<PathBuf>,
+    /// Display windows start with system keys captured (see
+    /// [`HotkeyAction::CaptureSystemKeys`]).
+    pub capture_system_keys: bool,
+    /// Queue depths between the receive, decode and display stages (see
+    /// [`crate::queues`]).
+    pub queues:             ReceiverQueues,
+}
+
+impl ReceiverSettings {
+    /// Load the saved settings; defaults if none were saved or the file is unreadable.
+    pub fn load() -> Self {
+        let Some(path) = config_file("receiver.json") else { return Self::default() };
+        std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the settings back to the config directory.
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = config_file("receiver.json").ok_or_else(|| std::io::Error::other("no config directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+}
+
+// MARK: - Hooks
+
+/// Session lifecycle events that can trigger a [`SessionHook`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HookEvent {
+    SessionStarted,
+    SessionStopped,
+    ClientDisconnected,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::SessionStarted => "sessionStarted",
+            HookEvent::SessionStopped => "sessionStopped",
+            HookEvent::ClientDisconnected => "clientDisconnected",
+        }
+    }
+}
+
+/// What a [`SessionHook`] does.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HookAction {
+    /// Shell command (`sh -c`, `cmd /C` on Windows); the event is passed
+    /// in `DUALLINK_*` environment variables.
+    Exec(String),
+    /// `http://` URL; the event is POSTed as a JSON body.
+    Webhook(String),
+}
+
+/// One configured hook, e.g.
+/// `{"events": ["sessionStarted"], "action": {"exec": "cec-ctl --to 0 --image-view-on"}}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHook {
+    /// Events that fire the hook; empty = all of them.
+    #[serde(default)]
+    pub events: Vec<HookEvent>,
+    pub action: HookAction,
+}
+
+impl SessionHook {
+    pub fn fires_on(&self, event: HookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+// MARK: - Config directory
+
+/// `name` inside the DualLink config directory:
+/// `$XDG_CONFIG_HOME` / `~/.config` on Unix, `%APPDATA%` on Windows.
+pub(crate) fn config_file(name: &str) -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(base.join("duallink").join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HookAction, HookEvent, ReceiverSettings};
+
+    #[test]
+    fn missing_fields_default() {
+        let s: ReceiverSettings = serde_json::from_str("{}").unwrap();
+        assert!(s.decoder_preference.is_empty());
+        let s: ReceiverSettings = serde_json::from_str(r#"{"decoderPreference":["avdec_h264"]}"#).unwrap();
+        assert_eq!(s.decoder_preference, ["avdec_h264"]);
+    }
+
+    #[test]
+    fn hooks_parse() {
+        let s: ReceiverSettings = serde_json::from_str(
+            r#"{"hooks":[{"events":["sessionStopped"],"action":{"webhook":"http://tv.local/off"}},
+                         {"action":{"exec":"true"}}]}"#,
+        )
+        .unwrap();
+        assert_eq!(s.hooks[0].action, HookAction::Webhook("http://tv.local/off".into()));
+        assert!(!s.hooks[0].fires_on(HookEvent::SessionStarted));
+        assert!(s.hooks[1].fires_on(HookEvent::ClientDisconnected));
+    }
+}
@@ -0,0 +1,65 @@
+//! Slice-level decoding: a frame's first slices reach the decoder before
+//! its last fragment arrives.
+//!
+//! By default the receiver hands its decoder whole access units
+//! (`alignment=au`), so decoding starts only once every fragment of a frame
+//! is in. With [`StreamConfig::nal_slices`](crate::StreamConfig::nal_slices)
+//! negotiated instead:
+//!
+//! 1. the sender starts a new fragment at every NAL unit ([`nal_starts`])
+//!    and flags the last fragment of each one (`FLAG_NAL_END`, DLNK v2
+//!    only);
+//! 2. the receiver hands over each run of complete NAL units as soon as it
+//!    and everything before it arrived, as an
+//!    [`EncodedFrame`](crate::EncodedFrame) with `partial` set;
+//! 3. the decoder takes `alignment=nal` input and can start on a slice
+//!    while the rest of the frame is still on the wire.
+//!
+//! It only pays off with encoders that cut a frame into several slices,
+//! and costs the frame checksum (which covers the whole frame): both ends
+//! opt in with `DUALLINK_SLICE_DECODE=1` ([`configured_slice_decode`]).
+//! H.264 only.
+
+/// Receiver capability: takes H.264 a NAL unit at a time
+/// ([`StreamConfig::nal_slices`](crate::StreamConfig::nal_slices)).
+pub const CAP_NAL_SLICES: &str = "nal_slices";
+
+/// Whether slice-level decoding is wanted (`DUALLINK_SLICE_DECODE=1`).
+/// Off by default.
+pub fn configured_slice_decode() -> bool {
+    std::env::var("DUALLINK_SLICE_DECODE").is_ok_and(|v| matches!(v.trim(), "1" | "true" | "yes"))
+}
+
+/// Byte offsets at which the Annex-B NAL units of `data` start, start
+/// codes included. The first is always 0 so that bytes before the first
+/// start code stay with the first unit.
+pub fn nal_starts(data: &[u8]) -> Vec<usize> {
+    let mut starts = vec![0];
+    let mut i = 0;
+    while i + 3 <= data.len() {
+        if data[i..i + 3] != [0, 0, 1] {
+            i += 1;
+            continue;
+        }
+        // A 4-byte start code begins one zero earlier.
+        let start = if i > 0 && data[i - 1] == 0 { i - 1 } else { i };
+        if start > 0 {
+            starts.push(start);
+        }
+        i += 3;
+    }
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nal_starts_split_at_start_codes() {
+        let au: &[u8] = &[0, 0, 0, 1, 0x67, 0x42, 0, 0, 1, 0x68, 0xce, 0, 0, 0, 1, 0x65, 0x88, 0, 0, 0, 1, 0x65, 0x11];
+        assert_eq!(nal_starts(au), [0, 6, 11, 17]);
+        assert_eq!(nal_starts(&[0x65, 0x88]), [0]);
+        assert_eq!(nal_starts(&[]), [0]);
+    }
+}
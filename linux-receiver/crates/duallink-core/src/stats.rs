@@ -0,0 +1,331 @@
+//! Stats sinks — where a session's frame counters go.
+//!
+//! Receiver and sender sessions report what they stream to a [`StatsSink`]:
+//! every frame ([`FrameSample`]), sessions starting and ending
+//! ([`SessionEvent`]) and, every [`STATS_INTERVAL`], an [`IntervalSummary`]
+//! of the frames since the last one (counted by an [`IntervalMeter`]).
+//! [`StatsSinks`] hands them to several sinks: the frontend's own — the
+//! receiver GUI's counters — and, with `DUALLINK_STATS_FILE=<path>` set, a
+//! [`StatsFile`] appending to disk:
+//!
+//! - `*.csv` — one row per interval summary, with a header row in a new file
+//! - anything else — JSON lines, one per session event or interval summary,
+//!   told apart by their `event` field (`started`, `ended`, `interval`)
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// How often sessions report an [`IntervalSummary`].
+pub const STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+const CSV_HEADER: &str = "at_ms,display,interval_secs,frames,keyframes,bytes,fps,bitrate_kbps,dropped";
+
+/// Stats file from `DUALLINK_STATS_FILE`, if set and not empty.
+pub fn stats_file() -> Option<PathBuf> {
+    std::env::var_os("DUALLINK_STATS_FILE").filter(|p| !p.is_empty()).map(PathBuf::from)
+}
+
+fn unix_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64)
+}
+
+// MARK: - Records
+
+/// One frame a session decoded (receiver) or sent (sender).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameSample {
+    pub display:  u8,
+    pub bytes:    usize,
+    pub keyframe: bool,
+    /// Frames the decoder rejected so far this session; `0` on the sender.
+    pub errors:   u64,
+}
+
+/// A session starting or ending on one display.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum SessionEvent {
+    #[serde(rename_all = "camelCase")]
+    Started {
+        display:    u8,
+        session_id: String,
+        /// Sender device name on the receiver, receiver host on the sender.
+        peer:       String,
+    },
+    Ended {
+        display: u8,
+        reason:  String,
+    },
+}
+
+/// The frames of one display over one interval.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntervalSummary {
+    pub display:       u8,
+    /// Unix time the interval ended, in milliseconds.
+    pub at_ms:         u64,
+    pub interval_secs: f64,
+    pub frames:        u64,
+    pub keyframes:     u64,
+    pub bytes:         u64,
+    pub fps:           f64,
+    pub bitrate_kbps:  u64,
+    /// Frames the pipeline dropped or rejected during the interval.
+    pub dropped:       u64,
+}
+
+impl IntervalSummary {
+    fn csv_row(&self) -> String {
+        format!(
+            "{},{},{:.3},{},{},{},{:.1},{},{}",
+            self.at_ms,
+            self.display,
+            self.interval_secs,
+            self.frames,
+            self.keyframes,
+            self.bytes,
+            self.fps,
+            self.bitrate_kbps,
+            self.dropped,
+        )
+    }
+}
+
+// MARK: - StatsSink
+
+/// Receives a session's stats. Every method defaults to nothing.
+///
+/// `on_frame` runs once per frame on the session task, so sinks keep it
+/// cheap and do their I/O per summary.
+#[allow(unused_variables)]
+pub trait StatsSink: Send {
+    fn on_frame(&mut self, frame: &FrameSample) {}
+
+    fn on_session_event(&mut self, event: &SessionEvent) {}
+
+    fn on_interval_summary(&mut self, summary: &IntervalSummary) {}
+}
+
+/// Several sinks fed the same stats, in the order they were added.
+#[derive(Default)]
+pub struct StatsSinks(Vec<Box<dyn StatsSink>>);
+
+impl StatsSinks {
+    /// The sinks every session gets: a [`StatsFile`] if `DUALLINK_STATS_FILE`
+    /// is set and can be opened, else none.
+    pub fn configured() -> Self {
+        let mut sinks = Self::default();
+        if let Some(path) = stats_file() {
+            match StatsFile::open(&path) {
+                Ok(file) => sinks.push(file),
+                Err(e) => tracing::warn!("Stats file {} not opened: {}", path.display(), e),
+            }
+        }
+        sinks
+    }
+
+    pub fn push(&mut self, sink: impl StatsSink + 'static) {
+        self.0.push(Box::new(sink));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl StatsSink for StatsSinks {
+    fn on_frame(&mut self, frame: &FrameSample) {
+        self.0.iter_mut().for_each(|s| s.on_frame(frame));
+    }
+
+    fn on_session_event(&mut self, event: &SessionEvent) {
+        self.0.iter_mut().for_each(|s| s.on_session_event(event));
+    }
+
+    fn on_interval_summary(&mut self, summary: &IntervalSummary) {
+        self.0.iter_mut().for_each(|s| s.on_interval_summary(summary));
+    }
+}
+
+// MARK: - IntervalMeter
+
+/// Counts frames between [`IntervalSummary`]s.
+#[derive(Debug, Clone)]
+pub struct IntervalMeter {
+    start:        Instant,
+    frames:       u64,
+    keyframes:    u64,
+    bytes:        u64,
+    /// The pipeline's dropped-frame total at the last summary.
+    dropped_seen: u64,
+}
+
+impl Default for IntervalMeter {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+impl IntervalMeter {
+    /// A meter whose first interval starts at `now`.
+    pub fn new(now: Instant) -> Self {
+        Self { start: now, frames: 0, keyframes: 0, bytes: 0, dropped_seen: 0 }
+    }
+
+    pub fn record(&mut self, frame: &FrameSample) {
+        self.frames += 1;
+        self.keyframes += u64::from(frame.keyframe);
+        self.bytes += frame.bytes as u64;
+    }
+
+    /// The frames since the last summary, up to `now`, and start the next
+    /// interval. `dropped` is the pipeline's running total of dropped frames.
+    pub fn take(&mut self, display: u8, dropped: u64, now: Instant) -> IntervalSummary {
+        let secs = now.saturating_duration_since(self.start).as_secs_f64();
+        let per_sec = |n: u64| if secs > 0.0 { n as f64 / secs } else { 0.0 };
+        let summary = IntervalSummary {
+            display,
+            at_ms:         unix_ms(),
+            interval_secs: secs,
+            frames:        self.frames,
+            keyframes:     self.keyframes,
+            bytes:         self.bytes,
+            fps:           per_sec(self.frames),
+            bitrate_kbps:  (per_sec(self.bytes) * 8.0 / 1000.0) as u64,
+            dropped:       dropped.saturating_sub(self.dropped_seen),
+        };
+        *self = Self { dropped_seen: dropped.max(self.dropped_seen), ..Self::new(now) };
+        summary
+    }
+}
+
+// MARK: - StatsFile
+
+/// Appends session events and interval summaries to a file.
+#[derive(Debug)]
+pub struct StatsFile {
+    /// `None` once a write failed; the sink stays quiet after one warning.
+    file: Option<File>,
+    csv:  bool,
+}
+
+impl StatsFile {
+    /// Open `path` for appending: CSV if it ends in `.csv`, else JSON lines.
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let csv = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        if csv && file.metadata()?.len() == 0 {
+            file.write_all(format!("{CSV_HEADER}\n").as_bytes())?;
+        }
+        Ok(Self { file: Some(file), csv })
+    }
+
+    fn write_line(&mut self, mut line: String) {
+        let Some(file) = &mut self.file else { return };
+        line.push('\n');
+        if let Err(e) = file.write_all(line.as_bytes()) {
+            tracing::warn!("Writing stats file: {} — no more stats logged", e);
+            self.file = None;
+        }
+    }
+
+    /// `value` as a JSON line with `event` set to `event` unless it has one.
+    fn json_line(mut value: serde_json::Value, event: &str) -> String {
+        if let Some(fields) = value.as_object_mut() {
+            fields.entry("event").or_insert_with(|| event.into());
+            fields.entry("atMs").or_insert_with(|| unix_ms().into());
+        }
+        value.to_string()
+    }
+}
+
+impl StatsSink for StatsFile {
+    fn on_session_event(&mut self, event: &SessionEvent) {
+        if !self.csv {
+            if let Ok(value) = serde_json::to_value(event) {
+                self.write_line(Self::json_line(value, "session"));
+            }
+        }
+    }
+
+    fn on_interval_summary(&mut self, summary: &IntervalSummary) {
+        let line = if self.csv {
+            summary.csv_row()
+        } else {
+            match serde_json::to_value(summary) {
+                Ok(value) => Self::json_line(value, "interval"),
+                Err(_) => return,
+            }
+        };
+        self.write_line(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(bytes: usize, keyframe: bool) -> FrameSample {
+        FrameSample { display: 1, bytes, keyframe, errors: 0 }
+    }
+
+    #[test]
+    fn meter_summarises_each_interval() {
+        let t0 = Instant::now();
+        let mut meter = IntervalMeter::new(t0);
+        meter.record(&frame(100_000, true));
+        for _ in 0..29 {
+            meter.record(&frame(10_000, false));
+        }
+        let s = meter.take(1, 4, t0 + Duration::from_millis(500));
+        assert_eq!((s.frames, s.keyframes, s.bytes, s.dropped), (30, 1, 390_000, 4));
+        assert_eq!((s.fps, s.bitrate_kbps), (60.0, 6_240));
+
+        let s = meter.take(1, 5, t0 + Duration::from_millis(1500));
+        assert_eq!((s.frames, s.dropped, s.fps), (0, 1, 0.0));
+    }
+
+    #[test]
+    fn file_logs_csv_or_json_lines() {
+        let dir = std::env::temp_dir();
+        let pid = std::process::id();
+        let summary = IntervalMeter::new(Instant::now()).take(2, 0, Instant::now());
+        let started = SessionEvent::Started { display: 2, session_id: "s1".into(), peer: "mac".into() };
+
+        let csv = dir.join(format!("duallink-stats-{pid}.csv"));
+        let _ = std::fs::remove_file(&csv);
+        for _ in 0..2 {
+            let mut file = StatsFile::open(&csv).unwrap();
+            file.on_session_event(&started);
+            file.on_interval_summary(&summary);
+        }
+        let text = std::fs::read_to_string(&csv).unwrap();
+        std::fs::remove_file(&csv).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(lines[1].split(',').nth(1), Some("2"));
+
+        let ndjson = dir.join(format!("duallink-stats-{pid}.ndjson"));
+        let _ = std::fs::remove_file(&ndjson);
+        let mut file = StatsFile::open(&ndjson).unwrap();
+        file.on_session_event(&started);
+        file.on_interval_summary(&summary);
+        file.on_session_event(&SessionEvent::Ended { display: 2, reason: "stopped".into() });
+        let text = std::fs::read_to_string(&ndjson).unwrap();
+        std::fs::remove_file(&ndjson).unwrap();
+        let events: Vec<serde_json::Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        let kinds: Vec<_> = events.iter().map(|e| e["event"].as_str().unwrap()).collect();
+        assert_eq!(kinds, ["started", "interval", "ended"]);
+        assert_eq!(events[0]["sessionId"], "s1");
+        assert_eq!(events[1]["display"], 2);
+    }
+}
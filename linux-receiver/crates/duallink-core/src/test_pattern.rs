@@ -0,0 +1,60 @@
+//! Test-pattern streams: generated video instead of the screen.
+//!
+//! `--test-pattern` makes a sender stream a `videotestsrc` pattern through
+//! its real encoder and transport, so a network or decoder setup can be
+//! checked without granting capture permission or showing what is on
+//! screen. A ball moves over the pattern and the stream's running time is
+//! burned in, so a frozen, stuttering or lagging stream is obvious on the
+//! receiver.
+//!
+//! `--test-pattern=<name>` picks one of [`TEST_PATTERNS`]; without a name
+//! it is [`DEFAULT_TEST_PATTERN`], whose bars have well-known values for
+//! checking colour range and matrix handling.
+
+/// Pattern of a bare `--test-pattern`: SMPTE 75 % colour bars.
+pub const DEFAULT_TEST_PATTERN: &str = "smpte75";
+
+/// `videotestsrc` patterns `--test-pattern=<name>` accepts.
+pub const TEST_PATTERNS: [&str; 10] = [
+    "smpte75",
+    "smpte",
+    "smpte100",
+    "bar",
+    "colors",
+    "gradient",
+    "checkers-8",
+    "zone-plate",
+    "snow",
+    "black",
+];
+
+/// Pattern of a `--test-pattern` / `--test-pattern=<name>` argument, if
+/// present. An unknown name falls back to [`DEFAULT_TEST_PATTERN`].
+pub fn test_pattern_arg(args: impl IntoIterator<Item = String>) -> Option<&'static str> {
+    args.into_iter().find_map(|arg| match arg.strip_prefix("--test-pattern")? {
+        "" => Some(DEFAULT_TEST_PATTERN),
+        name => {
+            let name = name.strip_prefix('=')?;
+            let known = TEST_PATTERNS.iter().find(|p| p.eq_ignore_ascii_case(name.trim()));
+            Some(known.copied().unwrap_or_else(|| {
+                tracing::warn!("Unknown test pattern '{name}' — using {DEFAULT_TEST_PATTERN}");
+                DEFAULT_TEST_PATTERN
+            }))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern_args_pick_known_patterns() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(test_pattern_arg(args(&["sender"])), None);
+        assert_eq!(test_pattern_arg(args(&["sender", "--test-pattern"])), Some(DEFAULT_TEST_PATTERN));
+        assert_eq!(test_pattern_arg(args(&["sender", "--test-pattern=Zone-Plate"])), Some("zone-plate"));
+        assert_eq!(test_pattern_arg(args(&["sender", "--test-pattern=plaid"])), Some(DEFAULT_TEST_PATTERN));
+        assert_eq!(test_pattern_arg(args(&["sender", "--test-patterns"])), None);
+    }
+}
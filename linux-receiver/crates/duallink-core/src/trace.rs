@@ -0,0 +1,289 @@
+//! Per-frame timing trace for performance debugging.
+//!
+//! While armed, the receiver records how long each frame spends in each
+//! [`Stage`] and writes the spans as a Chrome `trace_event` JSON file —
+//! open it in `chrome://tracing` or <https://ui.perfetto.dev>. Each display
+//! is a process and each stage a thread lane; a span's `frame` argument is
+//! the frame's PTS, so one frame can be followed across the lanes.
+//!
+//! Recording is armed with `--trace[=SECS]` on the command line (file in
+//! the current directory) or the record-trace hotkey in a display window
+//! (file in the home directory). It starts with the next frame and stops
+//! after the requested duration, [`DEFAULT_DURATION`] unless given; the
+//! file is `duallink-trace-<unix time>.json`.
+//!
+//! Stages that start and end on different threads are matched by PTS:
+//! [`begin`] on one thread, [`advance`] or [`end`] on the other.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+/// How long a recording lasts when no duration is given.
+pub const DEFAULT_DURATION: Duration = Duration::from_secs(10);
+
+/// Unmatched [`begin`]s kept before the oldest are forgotten (frames the
+/// leaky sink queue dropped never end their display stage).
+const MAX_PENDING: usize = 4096;
+
+// MARK: - Stage
+
+/// Where a frame spends its time on the receiver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// First to last fragment of the frame arriving.
+    Receive,
+    /// Last fragment to the frame being handed to the decoder's queue.
+    Reassemble,
+    /// Waiting in the queue until the decode thread takes it.
+    Queue,
+    /// Pushed into the decoder until the decoded picture comes out.
+    Decode,
+    /// Decoded until it leaves for the video sink.
+    Display,
+}
+
+impl Stage {
+    pub const ALL: [Self; 5] = [Self::Receive, Self::Reassemble, Self::Queue, Self::Decode, Self::Display];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Receive => "receive",
+            Self::Reassemble => "reassemble",
+            Self::Queue => "queue",
+            Self::Decode => "decode",
+            Self::Display => "display",
+        }
+    }
+
+    fn lane(self) -> usize {
+        self as usize
+    }
+}
+
+// MARK: - FrameTrace
+
+/// One finished span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+    stage:   Stage,
+    display: u8,
+    pts:     u64,
+    start:   Instant,
+    end:     Instant,
+}
+
+/// Spans of one recording.
+#[derive(Debug)]
+pub struct FrameTrace {
+    duration: Duration,
+    /// First span's start; `None` until a frame arrives.
+    started:  Option<Instant>,
+    spans:    Vec<Span>,
+    pending:  HashMap<(Stage, u64), (u8, Instant)>,
+}
+
+impl FrameTrace {
+    pub fn new(duration: Duration) -> Self {
+        Self { duration, started: None, spans: Vec::new(), pending: HashMap::new() }
+    }
+
+    /// `true` once `now` is past the recording's duration.
+    pub fn is_done(&self, now: Instant) -> bool {
+        self.started.is_some_and(|s| now.saturating_duration_since(s) >= self.duration)
+    }
+
+    /// Record a span whose start and end are both known.
+    pub fn span(&mut self, stage: Stage, display: u8, pts: u64, start: Instant, end: Instant) {
+        self.started.get_or_insert(start);
+        self.spans.push(Span { stage, display, pts, start, end });
+    }
+
+    /// Start `stage` of frame `pts`; [`end`](Self::end) finishes it.
+    pub fn begin(&mut self, stage: Stage, display: u8, pts: u64, at: Instant) {
+        if self.pending.len() >= MAX_PENDING {
+            self.pending.clear();
+        }
+        self.pending.insert((stage, pts), (display, at));
+    }
+
+    /// Finish `stage` of frame `pts`; ignored without a matching begin.
+    pub fn end(&mut self, stage: Stage, pts: u64, at: Instant) {
+        if let Some((display, start)) = self.pending.remove(&(stage, pts)) {
+            self.span(stage, display, pts, start, at);
+        }
+    }
+
+    /// Finish `from` and begin `to` of frame `pts` on the same display —
+    /// for threads that see the PTS but not the display.
+    pub fn advance(&mut self, from: Stage, to: Stage, pts: u64, at: Instant) {
+        if let Some((display, start)) = self.pending.remove(&(from, pts)) {
+            self.span(from, display, pts, start, at);
+            self.begin(to, display, pts, at);
+        }
+    }
+
+    /// The recording as a Chrome `trace_event` document, times in µs since
+    /// the first span.
+    pub fn to_json(&self) -> Value {
+        let Some(origin) = self.started else { return json!({ "traceEvents": [] }) };
+        let mut displays: Vec<u8> = self.spans.iter().map(|s| s.display).collect();
+        displays.sort_unstable();
+        displays.dedup();
+
+        let mut events = Vec::new();
+        for display in displays {
+            events.push(json!({
+                "name": "process_name", "ph": "M", "pid": display,
+                "args": { "name": format!("Display {display}") },
+            }));
+            for stage in Stage::ALL {
+                events.push(json!({
+                    "name": "thread_name", "ph": "M", "pid": display, "tid": stage.lane(),
+                    "args": { "name": stage.name() },
+                }));
+                events.push(json!({
+                    "name": "thread_sort_index", "ph": "M", "pid": display, "tid": stage.lane(),
+                    "args": { "sort_index": stage.lane() },
+                }));
+            }
+        }
+        for span in &self.spans {
+            events.push(json!({
+                "name": span.stage.name(),
+                "cat": "frame",
+                "ph": "X",
+                "ts": span.start.saturating_duration_since(origin).as_micros() as u64,
+                "dur": span.end.saturating_duration_since(span.start).as_micros() as u64,
+                "pid": span.display,
+                "tid": span.stage.lane(),
+                "args": { "frame": span.pts },
+            }));
+        }
+        json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+    }
+
+    /// Write the recording into `dir`; returns the file's path.
+    pub fn write_to(&self, dir: &Path) -> std::io::Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let path = dir.join(format!("duallink-trace-{now}.json"));
+        std::fs::write(&path, serde_json::to_vec(&self.to_json())?)?;
+        Ok(path)
+    }
+}
+
+// MARK: - Global recorder
+
+static RECORDING: AtomicBool = AtomicBool::new(false);
+static RECORDER: Mutex<Option<(FrameTrace, PathBuf)>> = Mutex::new(None);
+
+/// Arm a recording of `duration` into `dir`. Returns `false` if one is
+/// already running.
+pub fn start(duration: Duration, dir: PathBuf) -> bool {
+    let mut recorder = RECORDER.lock().unwrap();
+    if recorder.is_some() {
+        return false;
+    }
+    info!("Frame trace armed for {:.0?} — written to {}", duration, dir.display());
+    *recorder = Some((FrameTrace::new(duration), dir));
+    RECORDING.store(true, Ordering::Release);
+    true
+}
+
+/// `true` while a recording is armed. Cheap; check it before taking
+/// timestamps.
+pub fn is_recording() -> bool {
+    RECORDING.load(Ordering::Relaxed)
+}
+
+/// Record a span of `stage`; see [`FrameTrace::span`].
+pub fn span(stage: Stage, display: u8, pts: u64, start: Instant, end: Instant) {
+    with_trace(end, |trace| trace.span(stage, display, pts, start, end));
+}
+
+/// Start `stage` of frame `pts`; see [`FrameTrace::begin`].
+pub fn begin(stage: Stage, display: u8, pts: u64, at: Instant) {
+    with_trace(at, |trace| trace.begin(stage, display, pts, at));
+}
+
+/// Finish `stage` of frame `pts`; see [`FrameTrace::end`].
+pub fn end(stage: Stage, pts: u64, at: Instant) {
+    with_trace(at, |trace| trace.end(stage, pts, at));
+}
+
+/// Move frame `pts` from one stage to the next; see [`FrameTrace::advance`].
+pub fn advance(from: Stage, to: Stage, pts: u64, at: Instant) {
+    with_trace(at, |trace| trace.advance(from, to, pts, at));
+}
+
+/// Apply `f` to the armed recording, then write it out on a separate
+/// thread once it has run its duration.
+fn with_trace(now: Instant, f: impl FnOnce(&mut FrameTrace)) {
+    if !is_recording() {
+        return;
+    }
+    let mut recorder = RECORDER.lock().unwrap();
+    let Some((trace, _)) = recorder.as_mut() else { return };
+    f(trace);
+    if !trace.is_done(now) {
+        return;
+    }
+    let (trace, dir) = recorder.take().unwrap();
+    RECORDING.store(false, Ordering::Release);
+    std::thread::spawn(move || match trace.write_to(&dir) {
+        Ok(path) => info!("Frame trace written to {} ({} spans)", path.display(), trace.spans.len()),
+        Err(e) => warn!("Writing the frame trace failed: {}", e),
+    });
+}
+
+/// Duration of a `--trace` / `--trace=SECS` argument, if present.
+pub fn duration_arg(args: impl IntoIterator<Item = String>) -> Option<Duration> {
+    args.into_iter().find_map(|arg| match arg.strip_prefix("--trace")? {
+        "" => Some(DEFAULT_DURATION),
+        secs => secs.strip_prefix('=')?.parse::<f64>().ok().filter(|s| *s > 0.0).map(Duration::from_secs_f64),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_spans_as_chrome_trace() {
+        let t0 = Instant::now();
+        let ms = |n| t0 + Duration::from_millis(n);
+        let mut trace = FrameTrace::new(Duration::from_millis(100));
+        trace.span(Stage::Receive, 1, 5000, t0, ms(2));
+        trace.begin(Stage::Queue, 1, 5000, ms(3));
+        trace.end(Stage::Decode, 5000, ms(4));
+        trace.advance(Stage::Queue, Stage::Decode, 5000, ms(5));
+        trace.end(Stage::Decode, 5000, ms(9));
+        assert!(!trace.is_done(ms(99)));
+        assert!(trace.is_done(ms(100)));
+
+        let json = trace.to_json();
+        let spans: Vec<&Value> = json["traceEvents"].as_array().unwrap().iter().filter(|e| e["ph"] == "X").collect();
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[1]["name"], "queue");
+        assert_eq!((spans[1]["ts"].as_u64(), spans[1]["dur"].as_u64()), (Some(3000), Some(2000)));
+        assert_eq!((spans[1]["pid"].as_u64(), spans[1]["tid"].as_u64()), (Some(1), Some(2)));
+        assert_eq!(spans[1]["args"]["frame"], 5000);
+        assert_eq!((spans[2]["name"].as_str(), spans[2]["pid"].as_u64()), (Some("decode"), Some(1)));
+    }
+
+    #[test]
+    fn parses_trace_argument() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(duration_arg(args(&["receiver", "--trace"])), Some(DEFAULT_DURATION));
+        assert_eq!(duration_arg(args(&["--trace=2.5"])), Some(Duration::from_millis(2500)));
+        assert_eq!(duration_arg(args(&["--trace=0"])), None);
+        assert_eq!(duration_arg(args(&["--tracer"])), None);
+        assert_eq!(duration_arg(args(&["--diagnose"])), None);
+    }
+}
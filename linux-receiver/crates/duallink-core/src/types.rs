@@ -147,4 +147,11 @@ pub struct EncodedFrame {
     pub timestamp_us: u64,
     pub is_keyframe: bool,
     pub codec: VideoCodec,
+    /// Temporal layer: 0 for frames others reference, [`DROPPABLE_LAYER`](crate::layers::DROPPABLE_LAYER)
+    /// for frames the decoder may skip (see [`crate::layers`]).
+    pub temporal_layer: u8,
+    /// Leading NAL units of an access unit whose rest follows in the next
+    /// frame(s), same timestamp; only with slice-level decoding (see
+    /// [`crate::slices`]).
+    pub partial: bool,
 }
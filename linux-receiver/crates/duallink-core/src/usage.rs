@@ -0,0 +1,241 @@
+//! Bandwidth accounting per session.
+//!
+//! Both ends count the bytes they move for a session — video datagrams and
+//! signaling — in a [`UsageMeter`]. When the session ends it is turned into
+//! a [`SessionSummary`] (duration, bytes each way, average and peak
+//! bitrate), logged, and appended to the usage history file if one is
+//! configured (`DUALLINK_USAGE_HISTORY=<path>`, see [`history_file`]):
+//!
+//! - `*.csv` — one row per session, with a header row in a new file
+//! - anything else — JSON lines, one [`SessionSummary`] per line
+//!
+//! so users on metered connections can add up what a day of use costs.
+
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Length of the windows the peak bitrate is measured over.
+const PEAK_WINDOW: Duration = Duration::from_secs(1);
+
+const CSV_HEADER: &str = "session_id,peer,started_at,duration_secs,bytes_sent,bytes_received,average_kbps,peak_kbps";
+
+/// Usage history file from `DUALLINK_USAGE_HISTORY`, if set and not empty.
+pub fn history_file() -> Option<PathBuf> {
+    std::env::var_os("DUALLINK_USAGE_HISTORY").filter(|p| !p.is_empty()).map(PathBuf::from)
+}
+
+// MARK: - SessionSummary
+
+/// Data used by one session, as seen from one end.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub session_id:     String,
+    /// Sender device name on the receiver, receiver host on the sender.
+    pub peer:           String,
+    /// Unix time the session started, in seconds.
+    pub started_at:     u64,
+    pub duration_secs:  f64,
+    pub bytes_sent:     u64,
+    pub bytes_received: u64,
+    /// Both directions over the whole session.
+    pub average_kbps:   u64,
+    /// Both directions over the busiest second.
+    pub peak_kbps:      u64,
+}
+
+impl SessionSummary {
+    pub fn total_bytes(&self) -> u64 {
+        self.bytes_sent + self.bytes_received
+    }
+
+    /// Append to the history file at `path`: a CSV row if it ends in
+    /// `.csv`, else a JSON line.
+    pub fn append_to(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let csv = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        let mut line = if csv {
+            let header = if file.metadata()?.len() == 0 { format!("{CSV_HEADER}\n") } else { String::new() };
+            header + &self.csv_row()
+        } else {
+            serde_json::to_string(self)?
+        };
+        line.push('\n');
+        file.write_all(line.as_bytes())
+    }
+
+    fn csv_row(&self) -> String {
+        format!(
+            "{},{},{},{:.1},{},{},{},{}",
+            csv_field(&self.session_id),
+            csv_field(&self.peer),
+            self.started_at,
+            self.duration_secs,
+            self.bytes_sent,
+            self.bytes_received,
+            self.average_kbps,
+            self.peak_kbps,
+        )
+    }
+}
+
+impl fmt::Display for SessionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.duration_secs as u64;
+        write!(
+            f,
+            "{} with {}: {}h{:02}m{:02}s, {:.1} MB sent, {:.1} MB received, avg {} kbps, peak {} kbps",
+            self.session_id,
+            self.peer,
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60,
+            self.bytes_sent as f64 / 1e6,
+            self.bytes_received as f64 / 1e6,
+            self.average_kbps,
+            self.peak_kbps,
+        )
+    }
+}
+
+/// Quote a CSV field if it needs it.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+// MARK: - UsageMeter
+
+/// Byte counters for one session, shared (cloning shares them) between the
+/// tasks that move its data.
+#[derive(Debug, Clone)]
+pub struct UsageMeter(Arc<Mutex<Usage>>);
+
+#[derive(Debug)]
+struct Usage {
+    started:      Instant,
+    started_at:   SystemTime,
+    sent:         u64,
+    received:     u64,
+    window_start: Instant,
+    window_bytes: u64,
+    peak_bytes:   u64,
+}
+
+impl Default for UsageMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsageMeter {
+    /// A meter for a session starting now.
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Usage::new(Instant::now()))))
+    }
+
+    /// Zero the counters for a new session.
+    pub fn restart(&self) {
+        *self.0.lock().unwrap() = Usage::new(Instant::now());
+    }
+
+    pub fn add_sent(&self, bytes: usize) {
+        self.0.lock().unwrap().add(bytes as u64, 0, Instant::now());
+    }
+
+    pub fn add_received(&self, bytes: usize) {
+        self.0.lock().unwrap().add(0, bytes as u64, Instant::now());
+    }
+
+    /// The session so far.
+    pub fn summary(&self, session_id: &str, peer: &str) -> SessionSummary {
+        self.0.lock().unwrap().summary(session_id, peer, Instant::now())
+    }
+}
+
+impl Usage {
+    fn new(now: Instant) -> Self {
+        Self {
+            started:      now,
+            started_at:   SystemTime::now(),
+            sent:         0,
+            received:     0,
+            window_start: now,
+            window_bytes: 0,
+            peak_bytes:   0,
+        }
+    }
+
+    fn add(&mut self, sent: u64, received: u64, now: Instant) {
+        if now.duration_since(self.window_start) >= PEAK_WINDOW {
+            self.peak_bytes = self.peak_bytes.max(self.window_bytes);
+            self.window_start = now;
+            self.window_bytes = 0;
+        }
+        self.sent += sent;
+        self.received += received;
+        self.window_bytes += sent + received;
+    }
+
+    fn summary(&self, session_id: &str, peer: &str, now: Instant) -> SessionSummary {
+        let duration = now.duration_since(self.started).as_secs_f64();
+        let total = self.sent + self.received;
+        let average_kbps = if duration > 0.0 { (total as f64 * 8.0 / 1000.0 / duration) as u64 } else { 0 };
+        SessionSummary {
+            session_id:     session_id.to_string(),
+            peer:           peer.to_string(),
+            started_at:     self.started_at.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+            duration_secs:  duration,
+            bytes_sent:     self.sent,
+            bytes_received: self.received,
+            average_kbps,
+            peak_kbps:      self.peak_bytes.max(self.window_bytes) * 8 / 1000,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peak_is_the_busiest_second() {
+        let t0 = Instant::now();
+        let mut usage = Usage::new(t0);
+        usage.add(0, 250_000, t0);
+        usage.add(0, 250_000, t0 + Duration::from_millis(500));
+        usage.add(50_000, 0, t0 + Duration::from_millis(1200));
+        let s = usage.summary("s1", "mac", t0 + Duration::from_secs(4));
+        assert_eq!((s.bytes_sent, s.bytes_received, s.total_bytes()), (50_000, 500_000, 550_000));
+        assert_eq!(s.peak_kbps, 4_000);
+        assert_eq!(s.average_kbps, 1_100);
+    }
+
+    #[test]
+    fn csv_history_gets_one_header() {
+        let path = std::env::temp_dir().join(format!("duallink-usage-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut s = Usage::new(Instant::now()).summary("s1", "Desk, left", Instant::now());
+        s.append_to(&path).unwrap();
+        s.session_id = "s2".into();
+        s.append_to(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[1].starts_with("s1,\"Desk, left\","));
+        assert!(lines[2].starts_with("s2,"));
+    }
+}
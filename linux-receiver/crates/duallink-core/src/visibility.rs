@@ -0,0 +1,126 @@
+//! Power saving while a receiver's video window cannot be seen.
+//!
+//! A minimized or fully covered window still costs a full-rate decode. The
+//! display outputs notice when their sink stops taking frames (see
+//! `duallink-decoder`) and feed that into a [`VisibilityTracker`]; while the
+//! window is hidden the receiver
+//!
+//! - handles frames according to its [`HiddenMode`], and
+//! - asks senders that advertise [`CAP_FPS_REQUEST`] to drop to
+//!   [`HIDDEN_FPS`] with a `config_update`, and to go back to the
+//!   negotiated rate with another once the window shows again.
+//!
+//! The mode is configured with `DUALLINK_HIDDEN_MODE`:
+//!
+//! ```text
+//! DUALLINK_HIDDEN_MODE=drop        # decode everything, drop it before the sink (default)
+//! DUALLINK_HIDDEN_MODE=keyframes   # decode keyframes only
+//! DUALLINK_HIDDEN_MODE=off         # keep decoding and asking for the full rate
+//! ```
+
+use std::time::Duration;
+
+/// Sender capability (in `hello`): applies the `targetFps` of a receiver's
+/// `config_update` as a frame-rate ceiling.
+pub const CAP_FPS_REQUEST: &str = "fps_request";
+
+/// Frame rate asked of senders while the window is hidden.
+pub const HIDDEN_FPS: u32 = 5;
+
+/// How long a sink must refuse frames before its window counts as hidden.
+pub const HIDDEN_GRACE: Duration = Duration::from_secs(1);
+
+// MARK: - HiddenMode
+
+/// What the receiver does with frames while the window is hidden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HiddenMode {
+    /// Decode every frame and drop it in front of the sink: the decoder
+    /// keeps its reference frames, so showing the window again is instant.
+    DecodeAndDrop,
+    /// Decode keyframes only and wait for the next one when the window
+    /// shows again.
+    KeyframesOnly,
+}
+
+impl HiddenMode {
+    /// Parse `"drop"` / `"keyframes"` (case-insensitive).
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "drop" | "decode-and-drop" => Some(Self::DecodeAndDrop),
+            "keyframes" | "keyframe" | "keyframes-only" => Some(Self::KeyframesOnly),
+            _ => None,
+        }
+    }
+
+    /// The configured mode; `None` if hiding changes nothing
+    /// (`DUALLINK_HIDDEN_MODE=off`).
+    pub fn from_env() -> Option<Self> {
+        match std::env::var("DUALLINK_HIDDEN_MODE") {
+            Err(_) => Some(Self::DecodeAndDrop),
+            Ok(s) if s.trim().eq_ignore_ascii_case("off") => None,
+            Ok(s) => Self::from_name(&s).or_else(|| {
+                tracing::warn!("Ignoring DUALLINK_HIDDEN_MODE='{s}' — expected drop, keyframes or off");
+                Some(Self::DecodeAndDrop)
+            }),
+        }
+    }
+}
+
+// MARK: - VisibilityTracker
+
+/// Debounces a window's visibility: it turns hidden once the sink has been
+/// idle for longer than the grace period, and visible again as soon as the
+/// sink takes a frame.
+#[derive(Debug, Clone)]
+pub struct VisibilityTracker {
+    grace:   Duration,
+    visible: bool,
+}
+
+impl Default for VisibilityTracker {
+    fn default() -> Self {
+        Self::new(HIDDEN_GRACE)
+    }
+}
+
+impl VisibilityTracker {
+    pub fn new(grace: Duration) -> Self {
+        Self { grace, visible: true }
+    }
+
+    /// Feed how long the sink has gone without taking a frame while frames
+    /// were being pushed. Returns the new state when it changed.
+    pub fn observe(&mut self, sink_idle: Duration) -> Option<bool> {
+        let visible = sink_idle <= self.grace;
+        (visible != self.visible).then(|| {
+            self.visible = visible;
+            visible
+        })
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hides_after_grace_and_shows_on_next_frame() {
+        let mut tracker = VisibilityTracker::new(Duration::from_secs(1));
+        assert_eq!(tracker.observe(Duration::from_millis(900)), None);
+        assert_eq!(tracker.observe(Duration::from_secs(1)), None);
+        assert_eq!(tracker.observe(Duration::from_millis(1500)), Some(false));
+        assert_eq!(tracker.observe(Duration::from_secs(5)), None);
+        assert!(!tracker.is_visible());
+        assert_eq!(tracker.observe(Duration::from_millis(10)), Some(true));
+        assert!(tracker.is_visible());
+
+        assert_eq!(HiddenMode::from_name("Keyframes"), Some(HiddenMode::KeyframesOnly));
+        assert_eq!(HiddenMode::from_name("drop"), Some(HiddenMode::DecodeAndDrop));
+        assert_eq!(HiddenMode::from_name("sometimes"), None);
+    }
+}
@@ -0,0 +1,82 @@
+//! Receiver MAC addresses remembered by senders, for Wake-on-LAN.
+//!
+//! Receivers advertise their MAC in the `mac` mDNS TXT key, but only while
+//! they are awake — the sender has to have seen one before it can wake it.
+//! [`WakeAddresses`] keeps every MAC a sender learned (stored as JSON in
+//! `duallink/sender-wake.json` under the user config directory), so the
+//! "Wake" action works for a receiver that was already asleep when the
+//! sender started.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::config_file;
+
+const FILE_NAME: &str = "sender-wake.json";
+
+/// Receiver MAC addresses, by host.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WakeAddresses {
+    receivers: BTreeMap<String, String>,
+}
+
+impl WakeAddresses {
+    /// Load the saved addresses; empty if none were saved or the file is unreadable.
+    pub fn load() -> Self {
+        let Some(path) = config_file(FILE_NAME) else { return Self::default() };
+        std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the addresses back to the config directory.
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = config_file(FILE_NAME).ok_or_else(|| std::io::Error::other("no config directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)
+    }
+
+    /// MAC address last seen for `host`, if any.
+    pub fn get(&self, host: &str) -> Option<&str> {
+        self.receivers.get(host).map(String::as_str)
+    }
+
+    /// Remember `mac` for `host`; `true` if that changed anything (and is
+    /// worth a [`save`](Self::save)).
+    pub fn insert(&mut self, host: &str, mac: &str) -> bool {
+        let (host, mac) = (host.trim(), mac.trim());
+        if host.is_empty() || mac.is_empty() || self.get(host) == Some(mac) {
+            return false;
+        }
+        self.receivers.insert(host.to_owned(), mac.to_owned());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WakeAddresses;
+
+    #[test]
+    fn wake_addresses_round_trip() {
+        let mut a = WakeAddresses::default();
+        assert!(a.insert("192.168.1.20", "aa:bb:cc:dd:ee:ff"));
+        assert!(!a.insert("192.168.1.20", "aa:bb:cc:dd:ee:ff"));
+        assert!(a.insert("laptop.local", "11:22:33:44:55:66"));
+        assert!(a.insert("laptop.local", "11:22:33:44:55:77"));
+        assert!(!a.insert("", "11:22:33:44:55:66"));
+        assert_eq!(a.get("laptop.local"), Some("11:22:33:44:55:77"));
+        assert_eq!(a.get("desktop.local"), None);
+
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(
+            json,
+            r#"{"receivers":{"192.168.1.20":"aa:bb:cc:dd:ee:ff","laptop.local":"11:22:33:44:55:77"}}"#
+        );
+        assert_eq!(serde_json::from_str::<WakeAddresses>(&json).unwrap(), a);
+    }
+}
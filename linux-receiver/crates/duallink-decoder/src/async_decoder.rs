@@ -0,0 +1,606 @@
+//! Async facade over a display output's dedicated decode thread.
+//!
+//! A GStreamer output owns a window and its message loop, so it must be
+//! created and driven from one OS thread. [`AsyncDecoder`] spawns that
+//! thread, opens the output on it and feeds it from a bounded queue; session
+//! loops only `push` frames and forward [`InputEvents`].
+//!
+//! ```text
+//! session loop ──push()──▶ [queue 64] ──▶ decode thread ──▶ DisplayOutput
+//!      ▲                                        │
+//!      └───────────── InputEvents ◀─────────────┘
+//! ```
+//!
+//! The decode thread also follows whether the output's window is shown (see
+//! [Hidden windows](crate#hidden-windows)); in
+//! [`HiddenMode::KeyframesOnly`] it drops delta frames while it is hidden.
+//! The session loop learns of changes through
+//! [`AsyncDecoder::visibility_changed`] and asks the sender for fewer frames.
+//!
+//! When the decoder can't keep up and frames back up in the queue, the
+//! decode thread drops frames the sender marked droppable (temporal layer
+//! above 0, see [`duallink_core::layers`]) until the queue has drained:
+//! playback falls to half rate instead of lagging further and further.
+//!
+//! In a [`LatencyMode::Quality`] session ([`AsyncDecoder::set_latency_mode`])
+//! the decode thread instead holds each frame in a
+//! [`PlayoutBuffer`](duallink_core::PlayoutBuffer) and sheds nothing: frames
+//! back up by design.
+//!
+//! With slice-level decoding a frame arrives in pieces (see
+//! [`EncodedFrame::partial`]): the decode thread decides on the first piece
+//! and applies the verdict to the rest, and counts the frame once it is
+//! complete.
+//!
+//! A panic in the output — opening it or on any frame — is caught on the
+//! decode thread and reported as [`DecoderError::Panicked`], which the
+//! session handles like a pipeline error: it fails over to the next decoder.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use duallink_core::trace::{self, Stage as TraceStage};
+use duallink_core::{
+    errors::DecoderError, panic_message, DumpSettings, EncodedFrame, HiddenMode, HotkeyAction, InputEvent,
+    LatencyMode, LayerShedder, MonitorInfo, PlayoutBuffer, StreamConfig, StreamDump, VisibilityTracker, HIDDEN_GRACE,
+};
+use futures_core::Stream;
+use tokio::sync::{mpsc, oneshot, Notify};
+use tracing::{error, info, warn};
+
+use crate::DisplayOutput;
+
+/// Encoded frames buffered ahead of the decode thread.
+const FRAME_QUEUE: usize = 64;
+
+/// Input events buffered before the session loop forwards them.
+const EVENT_QUEUE: usize = 256;
+
+enum Command {
+    Frame(EncodedFrame),
+    MoveToMonitor(MonitorInfo),
+    SetInputEnabled(bool),
+    SetFrozen(bool),
+    SetBlanked(bool),
+    SetSystemKeys(bool),
+    SetLatencyMode(LatencyMode),
+    SetPlaying(bool),
+    SetStream(Box<StreamConfig>, oneshot::Sender<bool>),
+    Snapshot(u32, oneshot::Sender<Option<Vec<u8>>>),
+}
+
+/// Counters kept by the decode thread.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecoderStats {
+    /// Frames accepted by the output.
+    pub frames_pushed: u64,
+    /// Frames the output rejected (non-fatal).
+    pub push_errors:   u64,
+    /// Decoded frames shown, duplicates not counted.
+    pub frames_unique: u64,
+    /// Decoded frames dropped as duplicates of the one before.
+    pub duplicates:    u64,
+    /// Droppable frames skipped while the queue was backed up.
+    pub layer_dropped: u64,
+    /// `true` while the output holds its last frame (freeze frame).
+    pub frozen:        bool,
+    /// `true` while the output shows black (privacy blank).
+    pub blanked:       bool,
+    /// `true` while the output's window is hidden.
+    pub hidden:        bool,
+    /// `true` while system keys are captured in the output's window (or
+    /// will be, once it can grab the keyboard).
+    pub system_keys:   bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    frames_pushed: AtomicU64,
+    push_errors:   AtomicU64,
+    frames_unique: AtomicU64,
+    duplicates:    AtomicU64,
+    layer_dropped: AtomicU64,
+    frozen:        AtomicBool,
+    blanked:       AtomicBool,
+    hidden:        AtomicBool,
+    system_keys:   AtomicBool,
+    /// Error that stopped the decode thread, handed out by the next `push`.
+    fatal:         Mutex<Option<DecoderError>>,
+    /// Signalled when the end-session hotkey is pressed in the window.
+    end_requested: Notify,
+    /// Signalled when the blank hotkey is pressed in the window.
+    blank_toggled: Notify,
+    /// Signalled when the window is hidden or shown again.
+    visibility_changed: Notify,
+}
+
+// ── Visibility ────────────────────────────────────────────────────────────────
+
+/// Whether the output's window is shown, judged by how long its sink has
+/// gone without a frame while frames kept arriving.
+struct Visibility {
+    mode:     HiddenMode,
+    tracker:  VisibilityTracker,
+    /// Start of the current run of steadily arriving frames, and the last
+    /// arrival: a pause in the stream is not a hidden window.
+    arrivals: Option<(Instant, Instant)>,
+    /// Keyframes-only mode, window shown again: drop delta frames until the
+    /// next keyframe.
+    wait_keyframe: bool,
+}
+
+impl Visibility {
+    fn new(mode: HiddenMode) -> Self {
+        Self { mode, tracker: VisibilityTracker::default(), arrivals: None, wait_keyframe: false }
+    }
+
+    /// Note a frame's arrival; the new visibility when it changed.
+    fn on_frame(&mut self, output: &dyn DisplayOutput) -> Option<bool> {
+        let now = Instant::now();
+        let start = match self.arrivals {
+            Some((start, last)) if now - last <= HIDDEN_GRACE => start,
+            _ => now,
+        };
+        self.arrivals = Some((start, now));
+        // A frozen output holds its frames back on purpose.
+        if output.is_frozen() {
+            return None;
+        }
+        let visible = self.tracker.observe(output.sink_idle()?.min(now - start))?;
+        if visible && self.mode == HiddenMode::KeyframesOnly {
+            self.wait_keyframe = true;
+        }
+        Some(visible)
+    }
+
+    /// Whether `frame` should be decoded.
+    fn wants(&mut self, frame: &EncodedFrame) -> bool {
+        if self.mode != HiddenMode::KeyframesOnly {
+            return true;
+        }
+        if frame.is_keyframe {
+            self.wait_keyframe = false;
+            return true;
+        }
+        self.tracker.is_visible() && !self.wait_keyframe
+    }
+}
+
+// ── Layer shedding ────────────────────────────────────────────────────────────
+
+/// Run `frame` past the shedder with `queued` commands behind it; `false`
+/// if it is dropped. Logs when dropping starts and stops.
+fn shed(idx: u8, shedder: &mut LayerShedder, frame: &EncodedFrame, queued: usize, shared: &Shared) -> bool {
+    let was_shedding = shedder.is_shedding();
+    let admitted = shedder.admit(frame.temporal_layer, queued);
+    match (was_shedding, shedder.is_shedding()) {
+        (false, true) => {
+            warn!("Display[{idx}] Decoder falling behind ({queued} frames queued) — dropping layered frames")
+        }
+        (true, false) => {
+            info!("Display[{idx}] Decoder caught up — {} layered frames dropped so far", shedder.dropped())
+        }
+        _ => {}
+    }
+    if !admitted {
+        shared.layer_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+    admitted
+}
+
+// ── Access units ──────────────────────────────────────────────────────────────
+
+/// The access unit whose leading pieces were handled, for slice-level
+/// decoding.
+#[derive(Default)]
+struct AccessUnit {
+    /// Whether its first piece was admitted.
+    verdict: Option<bool>,
+    /// Bytes of its pieces pushed so far.
+    bytes:   usize,
+}
+
+impl AccessUnit {
+    /// Whether `frame` should be pushed. `decide` runs on the first piece
+    /// of each access unit; the other pieces share its verdict.
+    fn admit(&mut self, frame: &EncodedFrame, decide: impl FnOnce() -> bool) -> bool {
+        let admitted = self.verdict.unwrap_or_else(decide);
+        self.verdict = frame.partial.then_some(admitted);
+        admitted
+    }
+
+    /// Note `bytes` of a piece pushed; the access unit's size once `partial`
+    /// is `false`.
+    fn pushed(&mut self, bytes: usize, partial: bool) -> Option<usize> {
+        self.bytes += bytes;
+        (!partial).then(|| std::mem::take(&mut self.bytes))
+    }
+}
+
+// ── Frame dump ────────────────────────────────────────────────────────────────
+
+/// The session's frame dump (see [`duallink_core::dump`]), opened on the
+/// first frame.
+enum Dump {
+    Pending(DumpSettings),
+    Running(StreamDump),
+    Done,
+}
+
+impl Dump {
+    /// Append `frame` before it is pushed; stops the dump (and the decoded
+    /// PNGs) once it has run its duration.
+    fn on_frame(&mut self, idx: u8, frame: &EncodedFrame, output: &dyn DisplayOutput) {
+        if let Self::Pending(settings) = self {
+            *self = match StreamDump::create(settings, idx, frame.codec) {
+                Ok(stream) => {
+                    let dir = stream.dir().display();
+                    info!("Display[{idx}] Dumping the first {:.0?} of the stream to {dir}", settings.duration);
+                    if settings.decoded && !output.dump_decoded(Some(stream.dir().to_path_buf())) {
+                        let element = output.element_name();
+                        warn!("Display[{idx}] {element} keeps frames in GPU memory — dumping the stream only");
+                    }
+                    Self::Running(stream)
+                }
+                Err(e) => {
+                    warn!("Display[{idx}] Frame dump failed: {e}");
+                    Self::Done
+                }
+            };
+        }
+        let Self::Running(stream) = self else { return };
+        match stream.write(frame, Instant::now()) {
+            Ok(true) => return,
+            Ok(false) => {
+                let (frames, bytes) = stream.written();
+                info!("Display[{idx}] Frame dump done: {frames} frames ({bytes} bytes) in {}", stream.dir().display());
+            }
+            Err(e) => warn!("Display[{idx}] Frame dump failed: {e}"),
+        }
+        output.dump_decoded(None);
+        *self = Self::Done;
+    }
+}
+
+// ── AsyncDecoder ──────────────────────────────────────────────────────────────
+
+/// Handle to a [`DisplayOutput`] running on its own decode thread.
+///
+/// Dropping the handle stops the thread after it drains the queue; use
+/// [`shutdown`](Self::shutdown) to also wait for the output (and its window)
+/// to be torn down.
+pub struct AsyncDecoder {
+    tx:       mpsc::Sender<Command>,
+    shared:   Arc<Shared>,
+    element:  String,
+    hardware: bool,
+    hidden_mode: Option<HiddenMode>,
+    thread:   std::thread::JoinHandle<()>,
+}
+
+impl AsyncDecoder {
+    /// Spawn the decode thread for display `display_index` and run `open` on
+    /// it to create the output. Resolves once the output is ready, or with
+    /// `open`'s error.
+    ///
+    /// `on_frame` runs on the decode thread after every accepted frame with
+    /// the frame's size in bytes (GUI counters, …). Hidden windows are
+    /// handled per `DUALLINK_HIDDEN_MODE` ([`HiddenMode::from_env`]).
+    pub async fn spawn<F>(
+        display_index: u8,
+        open: F,
+        mut on_frame: impl FnMut(usize) + Send + 'static,
+    ) -> Result<(Self, InputEvents), DecoderError>
+    where
+        F: FnOnce() -> Result<Box<dyn DisplayOutput>, DecoderError> + Send + 'static,
+    {
+        let (tx, mut rx) = mpsc::channel::<Command>(FRAME_QUEUE);
+        let (event_tx, event_rx) = mpsc::channel::<InputEvent>(EVENT_QUEUE);
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let shared = Arc::new(Shared::default());
+        let sh = Arc::clone(&shared);
+        let idx = display_index;
+        let hidden_mode = HiddenMode::from_env();
+
+        let thread = std::thread::Builder::new()
+            .name(format!("duallink-decode-{idx}"))
+            .spawn(move || {
+                let opened = panic::catch_unwind(AssertUnwindSafe(open))
+                    .unwrap_or_else(|payload| Err(DecoderError::Panicked(panic_message(payload.as_ref()))));
+                let output = match opened {
+                    Ok(o) => o,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                let element = output.element_name().to_string();
+                let _ = ready_tx.send(Ok((element.clone(), output.is_hardware_accelerated())));
+
+                let mut visibility = hidden_mode.map(Visibility::new);
+                let mut dump = DumpSettings::from_env().map_or(Dump::Done, Dump::Pending);
+                let mut shedder = LayerShedder::default();
+                let mut playout: Option<PlayoutBuffer> = None;
+                let mut unit = AccessUnit::default();
+                // A panic in the output ends the thread like a pipeline
+                // error, so the session fails over to another decoder.
+                let run = panic::catch_unwind(AssertUnwindSafe(|| {
+                    while let Some(cmd) = rx.blocking_recv() {
+                        if let (Command::Frame(EncodedFrame { partial: false, .. }), Some(vis)) =
+                            (&cmd, visibility.as_mut())
+                        {
+                            if let Some(visible) = vis.on_frame(output.as_ref()) {
+                                info!("Display[{idx}] Window {}", if visible { "shown again" } else { "hidden" });
+                                sh.hidden.store(!visible, Ordering::Relaxed);
+                                sh.visibility_changed.notify_one();
+                            }
+                        }
+                        match cmd {
+                            Command::Frame(frame)
+                                if !unit.admit(&frame, || {
+                                    visibility.as_mut().map_or(true, |v| v.wants(&frame))
+                                        && (playout.is_some() || shed(idx, &mut shedder, &frame, rx.len(), &sh))
+                                }) => {}
+                            Command::Frame(frame) => {
+                                if let Some(playout) = playout.as_mut() {
+                                    std::thread::sleep(playout.hold(frame.timestamp_us, Instant::now()));
+                                }
+                                if trace::is_recording() {
+                                    let now = Instant::now();
+                                    trace::end(TraceStage::Queue, frame.timestamp_us, now);
+                                    trace::begin(TraceStage::Decode, idx, frame.timestamp_us, now);
+                                }
+                                dump.on_frame(idx, &frame, output.as_ref());
+                                let sz = frame.data.len();
+                                let (kf, partial) = (frame.is_keyframe, frame.partial);
+                                match output.push_frame(frame) {
+                                    Ok(()) => {
+                                        if let Some(sz) = unit.pushed(sz, partial) {
+                                            let n = sh.frames_pushed.fetch_add(1, Ordering::Relaxed) + 1;
+                                            if n == 1 {
+                                                info!("Display[{idx}] First frame decoded and displayed!");
+                                            }
+                                            if n % 300 == 0 {
+                                                info!("Display[{idx}] Displayed {} frames", n);
+                                            }
+                                            on_frame(sz);
+                                        }
+                                    }
+                                    Err(e @ DecoderError::Pipeline { .. }) => {
+                                        error!("Display[{idx}] Decoder {} stopped: {}", output.element_name(), e);
+                                        *sh.fatal.lock().unwrap() = Some(e);
+                                        break;
+                                    }
+                                    Err(e) => {
+                                        let errs = sh.push_errors.fetch_add(1, Ordering::Relaxed) + 1;
+                                        if errs <= 10 || errs % 100 == 0 {
+                                            warn!(
+                                                "Display[{idx}] push error #{} ({} bytes keyframe={}): {}",
+                                                errs, sz, kf, e
+                                            );
+                                        }
+                                    }
+                                }
+                            }
+                            Command::MoveToMonitor(monitor) => output.move_to_monitor(&monitor),
+                            Command::SetInputEnabled(enabled) => output.set_input_enabled(enabled),
+                            Command::SetFrozen(frozen) => output.set_frozen(frozen),
+                            Command::SetBlanked(blanked) => output.set_blanked(blanked),
+                            Command::SetSystemKeys(captured) => output.set_system_keys(captured),
+                            Command::SetLatencyMode(mode) => {
+                                let delay = mode.jitter_buffer();
+                                playout = (!delay.is_zero()).then(|| PlayoutBuffer::new(delay));
+                            }
+                            Command::SetPlaying(playing) => output.set_playing(playing),
+                            Command::SetStream(stream, reply) => {
+                                let _ = reply.send(output.set_stream(&stream));
+                            }
+                            Command::Snapshot(width, reply) => {
+                                let _ = reply.send(output.snapshot_jpeg(width));
+                            }
+                        }
+                        // Forward input events captured from the output window
+                        for event in output.poll_input_events() {
+                            let _ = event_tx.try_send(event);
+                        }
+                        for action in output.poll_hotkeys() {
+                            match action {
+                                HotkeyAction::EndSession => {
+                                    info!("Display[{idx}] End-session hotkey pressed");
+                                    sh.end_requested.notify_one();
+                                }
+                                HotkeyAction::ToggleBlank => sh.blank_toggled.notify_one(),
+                                _ => {}
+                            }
+                        }
+                        // Also changed by the freeze hotkey.
+                        sh.frozen.store(output.is_frozen(), Ordering::Relaxed);
+                        sh.blanked.store(output.is_blanked(), Ordering::Relaxed);
+                        sh.system_keys.store(output.system_keys_wanted(), Ordering::Relaxed);
+                        sh.frames_unique.store(output.frames_unique(), Ordering::Relaxed);
+                        sh.duplicates.store(output.duplicates_dropped(), Ordering::Relaxed);
+                    }
+                }));
+                if let Err(payload) = run {
+                    let message = panic_message(payload.as_ref());
+                    error!("Display[{idx}] Decoder {} panicked: {}", element, message);
+                    *sh.fatal.lock().unwrap_or_else(PoisonError::into_inner) = Some(DecoderError::Panicked(message));
+                }
+                info!("Display[{idx}] decode thread exiting");
+            })
+            .map_err(|e| DecoderError::GStreamerPipeline(format!("decode thread spawn: {e}")))?;
+
+        let (element, hardware) = match ready_rx.await {
+            Ok(Ok(ready)) => ready,
+            Ok(Err(e)) => return Err(e),
+            Err(_) => return Err(DecoderError::GStreamerPipeline("decode thread panicked during init".into())),
+        };
+        let decoder = Self { tx, shared, element, hardware, hidden_mode, thread };
+        Ok((decoder, InputEvents { rx: event_rx }))
+    }
+
+    /// Queue one encoded frame, waiting while the queue is full.
+    ///
+    /// Fails once the decode thread has stopped — with the
+    /// [`DecoderError::Pipeline`] that stopped it, or the
+    /// [`DecoderError::Panicked`] of a panic in the output, the first time,
+    /// so the caller can fail over to another decoder.
+    pub async fn push(&self, frame: EncodedFrame) -> Result<(), DecoderError> {
+        if self.tx.send(Command::Frame(frame)).await.is_ok() {
+            return Ok(());
+        }
+        Err(self
+            .shared
+            .fatal
+            .lock()
+            .unwrap()
+            .take()
+            .unwrap_or_else(|| DecoderError::GStreamerPipeline("decode thread exited".into())))
+    }
+
+    /// Move the output window onto `monitor` (receiver hot-plug). Applied by
+    /// the decode thread in order with queued frames.
+    pub async fn move_to_monitor(&self, monitor: MonitorInfo) {
+        let _ = self.tx.send(Command::MoveToMonitor(monitor)).await;
+    }
+
+    /// Forward window input (the default) or drop it for a view-only
+    /// session. Applied by the decode thread in order with queued frames.
+    pub async fn set_input_enabled(&self, enabled: bool) {
+        let _ = self.tx.send(Command::SetInputEnabled(enabled)).await;
+    }
+
+    /// Freeze the picture (the stream keeps being decoded) or jump back to
+    /// live. Applied by the decode thread in order with queued frames.
+    pub async fn set_frozen(&self, frozen: bool) {
+        let _ = self.tx.send(Command::SetFrozen(frozen)).await;
+    }
+
+    /// Show black instead of the stream (privacy blank), or the stream
+    /// again. Applied by the decode thread in order with queued frames.
+    pub async fn set_blanked(&self, blanked: bool) {
+        let _ = self.tx.send(Command::SetBlanked(blanked)).await;
+    }
+
+    /// Capture system keys in the output window (see
+    /// [`HotkeyAction::CaptureSystemKeys`]), or leave them to this desktop.
+    /// Applied by the decode thread in order with queued frames.
+    pub async fn set_system_keys(&self, captured: bool) {
+        let _ = self.tx.send(Command::SetSystemKeys(captured)).await;
+    }
+
+    /// Pace frames for the session's negotiated latency mode (ultra-low by
+    /// default). Applied by the decode thread in order with queued frames.
+    pub async fn set_latency_mode(&self, mode: LatencyMode) {
+        let _ = self.tx.send(Command::SetLatencyMode(mode)).await;
+    }
+
+    /// Hold the output's pipeline paused — a decoder built before its
+    /// session's `hello` — or set it playing.
+    pub async fn set_playing(&self, playing: bool) {
+        let _ = self.tx.send(Command::SetPlaying(playing)).await;
+    }
+
+    /// Have an output built for another config take `stream`'s input caps
+    /// (see `DisplayOutput::set_stream`); `false` if it must be rebuilt.
+    pub async fn set_stream(&self, stream: StreamConfig) -> bool {
+        let (reply, rx) = oneshot::channel();
+        if self.tx.send(Command::SetStream(Box::new(stream), reply)).await.is_err() {
+            return false;
+        }
+        rx.await.unwrap_or(false)
+    }
+
+    /// JPEG of what the output shows, `width` pixels wide (see
+    /// `DisplayOutput::snapshot_jpeg`). Taken by the decode thread after
+    /// the frames queued before it.
+    pub async fn snapshot_jpeg(&self, width: u32) -> Option<Vec<u8>> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(Command::Snapshot(width, reply)).await.ok()?;
+        rx.await.ok().flatten()
+    }
+
+    /// Resolves when the end-session hotkey is pressed in the output window.
+    /// Ending the session is up to the caller (e.g. the display's
+    /// `SessionKick` in the transport).
+    pub async fn end_requested(&self) {
+        self.shared.end_requested.notified().await
+    }
+
+    /// Resolves when the blank hotkey is pressed in the output window.
+    /// Blanking — here and on the sender's side — is up to the caller.
+    pub async fn blank_toggled(&self) {
+        self.shared.blank_toggled.notified().await
+    }
+
+    /// Resolves when the output's window is hidden or shown again
+    /// ([`DecoderStats::hidden`]). Never resolves with hidden-window handling
+    /// off.
+    pub async fn visibility_changed(&self) {
+        self.shared.visibility_changed.notified().await
+    }
+
+    /// How frames are handled while the window is hidden; `None` = as usual.
+    pub fn hidden_mode(&self) -> Option<HiddenMode> {
+        self.hidden_mode
+    }
+
+    /// Current counters. Never waits on the decode thread.
+    pub fn stats(&self) -> DecoderStats {
+        DecoderStats {
+            frames_pushed: self.shared.frames_pushed.load(Ordering::Relaxed),
+            push_errors:   self.shared.push_errors.load(Ordering::Relaxed),
+            frames_unique: self.shared.frames_unique.load(Ordering::Relaxed),
+            duplicates:    self.shared.duplicates.load(Ordering::Relaxed),
+            layer_dropped: self.shared.layer_dropped.load(Ordering::Relaxed),
+            frozen:        self.shared.frozen.load(Ordering::Relaxed),
+            blanked:       self.shared.blanked.load(Ordering::Relaxed),
+            hidden:        self.shared.hidden.load(Ordering::Relaxed),
+            system_keys:   self.shared.system_keys.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn element_name(&self) -> &str {
+        &self.element
+    }
+
+    pub fn is_hardware_accelerated(&self) -> bool {
+        self.hardware
+    }
+
+    /// Stop the decode thread and wait until the output has been dropped.
+    /// Returns the final counters.
+    pub async fn shutdown(self) -> DecoderStats {
+        let stats = self.stats();
+        let Self { tx, thread, .. } = self;
+        drop(tx);
+        let _ = tokio::task::spawn_blocking(move || thread.join()).await;
+        stats
+    }
+}
+
+// ── InputEvents ───────────────────────────────────────────────────────────────
+
+/// Input events captured by an [`AsyncDecoder`]'s output window. Ends when
+/// the decode thread exits.
+pub struct InputEvents {
+    rx: mpsc::Receiver<InputEvent>,
+}
+
+impl InputEvents {
+    pub async fn next(&mut self) -> Option<InputEvent> {
+        self.rx.recv().await
+    }
+}
+
+impl Stream for InputEvents {
+    type Item = InputEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<InputEvent>> {
+        self.rx.poll_recv(cx)
+    }
+}
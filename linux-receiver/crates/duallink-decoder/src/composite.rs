@@ -0,0 +1,395 @@
+//! Multi-stream composition — several incoming displays in one window.
+//!
+//! When a sender streams N displays to a receiver with a single monitor,
+//! [`CompositeDisplay`] feeds every decoded stream into one `compositor`
+//! element instead of opening N windows:
+//!
+//! ```text
+//! videotestsrc (black, canvas size) ──────────────────────► mix.sink_0
+//! appsrc → parse → [decoder] → videoconvert → videoscale → mix.sink_1 (slot 0)
+//! appsrc → parse → [decoder] → videoconvert → videoscale → mix.sink_2 (slot 1)
+//! compositor name=mix → videoconvert → VIDEO_SINK (autovideosink / d3d11videosink)
+//! ```
+//!
+//! The black background keeps the compositor live while no sender is
+//! connected. Each display session [`attach`](CompositeDisplay::attach)es its
+//! own decode branch and gets a [`CompositeSlot`]; dropping the slot removes
+//! the branch again, so sessions come and go independently.
+//!
+//! Navigation events are reported in canvas pixels and normalised to the
+//! slot under the pointer.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use duallink_core::{errors::DecoderError, EncodedFrame, GestureTracker, InputEvent, StreamConfig};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app::AppSrc;
+use tracing::{info, warn};
+
+use crate::{
+    drain_navigation_events, elements, forward_sink_messages, frame_buffer, input_caps, parser_for, set_balance_blank,
+    DecoderFactory, DisplayOutput, VIDEO_SINK,
+};
+
+/// Gap between picture-in-picture insets and the canvas edge, in pixels.
+const PIP_MARGIN: i32 = 16;
+
+fn pipeline_err(e: impl std::fmt::Display) -> DecoderError {
+    DecoderError::GStreamerPipeline(e.to_string())
+}
+
+// ── Layout ────────────────────────────────────────────────────────────────────
+
+/// How slots are arranged on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompositeLayout {
+    /// Equal-width columns, display 0 on the left.
+    #[default]
+    SideBySide,
+    /// Display 0 fills the canvas; the others are quarter-size insets
+    /// stacked up from the bottom-right corner.
+    PictureInPicture,
+}
+
+/// Placement of one slot on the canvas, in canvas pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotRect {
+    pub x:      i32,
+    pub y:      i32,
+    pub width:  i32,
+    pub height: i32,
+}
+
+impl SlotRect {
+    /// Normalised `0.0..=1.0` position of canvas point `(px, py)` inside this
+    /// rect, or `None` if it lies outside.
+    fn normalize(&self, px: f64, py: f64) -> Option<(f64, f64)> {
+        let x = (px - self.x as f64) / self.width as f64;
+        let y = (py - self.y as f64) / self.height as f64;
+        ((0.0..=1.0).contains(&x) && (0.0..=1.0).contains(&y)).then_some((x, y))
+    }
+}
+
+impl CompositeLayout {
+    /// Parse `"side-by-side"` / `"pip"` (case-insensitive).
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "side-by-side" | "sbs" => Some(Self::SideBySide),
+            "pip" | "picture-in-picture" => Some(Self::PictureInPicture),
+            _ => None,
+        }
+    }
+
+    /// Canvas size that shows `slots` 1080p displays without downscaling the
+    /// main one.
+    pub fn default_canvas(self, slots: u8) -> (u32, u32) {
+        match self {
+            Self::SideBySide => (1920 * slots.max(1) as u32, 1080),
+            Self::PictureInPicture => (1920, 1080),
+        }
+    }
+
+    /// Where `slot` (of `slots`) goes on a `canvas`-sized output.
+    pub fn slot_rect(self, slot: u8, slots: u8, canvas: (u32, u32)) -> SlotRect {
+        let (cw, ch) = (canvas.0 as i32, canvas.1 as i32);
+        match self {
+            Self::SideBySide => {
+                let w = cw / slots.max(1) as i32;
+                SlotRect { x: w * slot as i32, y: 0, width: w, height: ch }
+            }
+            Self::PictureInPicture if slot == 0 => SlotRect { x: 0, y: 0, width: cw, height: ch },
+            Self::PictureInPicture => {
+                let (w, h) = (cw / 4, ch / 4);
+                SlotRect {
+                    x: cw - w - PIP_MARGIN,
+                    y: ch - (h + PIP_MARGIN) * slot as i32,
+                    width: w,
+                    height: h,
+                }
+            }
+        }
+    }
+}
+
+// ── CompositeDisplay ──────────────────────────────────────────────────────────
+
+/// One decode branch linked into the compositor.
+struct Attached {
+    bin:       gst::Bin,
+    mixer_pad: gst::Pad,
+    rect:      SlotRect,
+    /// `false` for view-only sessions: events over this slot are dropped.
+    input:     bool,
+}
+
+/// A single window compositing up to `slots` display streams.
+///
+/// Create once (from a blocking thread — GStreamer opens the window there)
+/// and share it between display tasks via `Arc`.
+pub struct CompositeDisplay {
+    pipeline: gst::Pipeline,
+    mixer:    gst::Element,
+    layout:   CompositeLayout,
+    slots:    u8,
+    canvas:   (u32, u32),
+    attached: Mutex<HashMap<u8, Attached>>,
+    /// Phases for touch gestures anywhere on the canvas.
+    gestures: Mutex<GestureTracker>,
+}
+
+impl CompositeDisplay {
+    /// Build and start the compositor pipeline with an empty (black) canvas.
+    pub fn new(layout: CompositeLayout, slots: u8, canvas: (u32, u32)) -> Result<Arc<Self>, DecoderError> {
+        gst::init().map_err(pipeline_err)?;
+        let (w, h) = canvas;
+        let canvas_caps = |framerate: Option<i32>| {
+            let caps = gst::Caps::builder("video/x-raw").field("width", w as i32).field("height", h as i32);
+            match framerate {
+                Some(fps) => caps.field("framerate", gst::Fraction::new(fps, 1)).build(),
+                None => caps.build(),
+            }
+        };
+        let background = elements::make("videotestsrc", None)?;
+        background.set_property("is-live", true);
+        background.set_property_from_str("pattern", "black");
+        let mixer = elements::make("compositor", Some("mix"))?;
+        mixer.set_property_from_str("background", "black");
+        let videosink = elements::make(VIDEO_SINK, Some("videosink"))?;
+        videosink.set_property("sync", false);
+        let pipeline = elements::pipeline(&[
+            &background,
+            &elements::caps_filter(&canvas_caps(Some(60)))?,
+            &mixer,
+            &elements::caps_filter(&canvas_caps(None))?,
+            &elements::make("videoconvert", None)?,
+            &videosink,
+        ])?;
+
+        // Forward navigation messages from inside autovideosink (see
+        // GStreamerDisplayDecoder::new).
+        forward_sink_messages(&videosink);
+
+        pipeline
+            .set_state(gst::State::Playing)
+            .map_err(|_| DecoderError::GStreamerPipeline("Failed to start compositor".into()))?;
+
+        info!("CompositeDisplay ready: {:?}, {} slot(s), canvas {}×{}", layout, slots, w, h);
+        Ok(Arc::new(Self {
+            pipeline,
+            mixer,
+            layout,
+            slots,
+            canvas,
+            attached: Mutex::new(HashMap::new()),
+            gestures: Mutex::new(GestureTracker::new(w as f64 / h.max(1) as f64)),
+        }))
+    }
+
+    pub fn layout(&self) -> CompositeLayout {
+        self.layout
+    }
+
+    /// Link a decode branch for `config` into `slot` and return its handle.
+    ///
+    /// Replaces any branch still attached to the same slot. `preferred` lists
+    /// decoder elements to try first, as in [`DecoderFactory::with_preference`].
+    pub fn attach(
+        self: &Arc<Self>,
+        slot: u8,
+        config: &StreamConfig,
+        preferred: &[&str],
+    ) -> Result<CompositeSlot, DecoderError> {
+        self.detach(slot);
+
+        let element = DecoderFactory::element_for(config, preferred, &[])?;
+        let appsrc = AppSrc::builder()
+            .name("src")
+            .format(gst::Format::Time)
+            .is_live(true)
+            .do_timestamp(true)
+            .caps(&input_caps(config))
+            .build();
+        let blank = elements::make("videobalance", Some("blank"))?;
+        let hold = elements::make("valve", Some("hold"))?;
+        hold.set_property("drop", false);
+        let queue = elements::make("queue", None)?;
+        queue.set_property("max-size-buffers", 2u32);
+        queue.set_property_from_str("leaky", "downstream");
+
+        let bin = gst::Bin::new();
+        elements::add_chain(&bin, &[
+            appsrc.upcast_ref::<gst::Element>(),
+            &elements::make(parser_for(config.codec), None)?,
+            &elements::make(element, None)?,
+            &elements::make("videoconvert", None)?,
+            &elements::make("videoscale", None)?,
+            &blank,
+            &hold,
+            &queue,
+        ])?;
+        let queue_src = queue
+            .static_pad("src")
+            .ok_or_else(|| DecoderError::GStreamerPipeline("No queue src pad".into()))?;
+        let ghost = gst::GhostPad::builder_with_target(&queue_src).map_err(pipeline_err)?.name("src").build();
+        bin.add_pad(&ghost).map_err(pipeline_err)?;
+
+        self.pipeline.add(&bin).map_err(pipeline_err)?;
+        let mixer_pad = self
+            .mixer
+            .request_pad_simple("sink_%u")
+            .ok_or_else(|| DecoderError::GStreamerPipeline("No compositor sink pad".into()))?;
+
+        let rect = self.layout.slot_rect(slot, self.slots, self.canvas);
+        mixer_pad.set_property("xpos", rect.x);
+        mixer_pad.set_property("ypos", rect.y);
+        mixer_pad.set_property("width", rect.width);
+        mixer_pad.set_property("height", rect.height);
+        // Above the background (0); later slots above earlier ones for PiP.
+        mixer_pad.set_property("zorder", slot as u32 + 1);
+
+        let src_pad = bin
+            .static_pad("src")
+            .ok_or_else(|| DecoderError::GStreamerPipeline("No branch src pad".into()))?;
+        src_pad.link(&mixer_pad).map_err(pipeline_err)?;
+        bin.sync_state_with_parent().map_err(pipeline_err)?;
+
+        info!(
+            "CompositeDisplay: slot {} attached ({}, {}×{} at {},{})",
+            slot, element, rect.width, rect.height, rect.x, rect.y
+        );
+        self.attached.lock().unwrap().insert(slot, Attached { bin, mixer_pad, rect, input: true });
+
+        Ok(CompositeSlot {
+            display: Arc::clone(self),
+            slot,
+            appsrc,
+            element,
+            frame_count: AtomicU64::new(0),
+            hold,
+            frozen: AtomicBool::new(false),
+            blank,
+            blanked: AtomicBool::new(false),
+        })
+    }
+
+    /// Unlink and dispose of the branch in `slot`, if any.
+    fn detach(&self, slot: u8) {
+        let Some(a) = self.attached.lock().unwrap().remove(&slot) else {
+            return;
+        };
+        let _ = a.bin.set_state(gst::State::Null);
+        if let Some(src) = a.bin.static_pad("src") {
+            let _ = src.unlink(&a.mixer_pad);
+        }
+        self.mixer.release_request_pad(&a.mixer_pad);
+        if let Err(e) = self.pipeline.remove(&a.bin) {
+            warn!("CompositeDisplay: removing slot {} branch: {}", slot, e);
+        }
+        info!("CompositeDisplay: slot {} detached", slot);
+    }
+
+    /// Normalise a canvas point to the topmost attached slot containing it;
+    /// `None` if that slot's session is view-only.
+    fn normalize(&self, px: f64, py: f64) -> Option<(f64, f64)> {
+        let attached = self.attached.lock().unwrap();
+        let mut slots: Vec<_> = attached.iter().collect();
+        slots.sort_by_key(|(slot, _)| std::cmp::Reverse(**slot));
+        slots
+            .into_iter()
+            .find_map(|(_, a)| a.rect.normalize(px, py).map(|p| (p, a.input)))
+            .and_then(|(p, input)| input.then_some(p))
+    }
+}
+
+impl Drop for CompositeDisplay {
+    fn drop(&mut self) {
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
+
+// ── CompositeSlot ─────────────────────────────────────────────────────────────
+
+/// One display session's branch in a [`CompositeDisplay`]. Detaches on drop.
+pub struct CompositeSlot {
+    display:     Arc<CompositeDisplay>,
+    slot:        u8,
+    appsrc:      AppSrc,
+    element:     &'static str,
+    frame_count: AtomicU64,
+    /// Closed while frozen; the compositor repeats the slot's last frame.
+    hold:        gst::Element,
+    frozen:      AtomicBool,
+    /// Blacks out the slot while blanked.
+    blank:       gst::Element,
+    blanked:     AtomicBool,
+}
+
+impl DisplayOutput for CompositeSlot {
+    fn push_frame(&self, frame: EncodedFrame) -> Result<(), DecoderError> {
+        self.appsrc
+            .push_buffer(frame_buffer(&frame))
+            .map_err(|_| DecoderError::DecodeFailed { reason: "appsrc push failed".into() })?;
+        if !frame.partial {
+            self.frame_count.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn frames_pushed(&self) -> u64 {
+        self.frame_count.load(Ordering::Relaxed)
+    }
+
+    /// Events are drained from the shared window, so whichever slot polls
+    /// first receives them — all slots feed the same input channel.
+    fn poll_input_events(&self) -> Vec<InputEvent> {
+        drain_navigation_events(
+            &self.display.pipeline,
+            &|px, py| self.display.normalize(px, py),
+            &mut self.display.gestures.lock().unwrap(),
+        )
+    }
+
+    fn set_input_enabled(&self, enabled: bool) {
+        if let Some(a) = self.display.attached.lock().unwrap().get_mut(&self.slot) {
+            a.input = enabled;
+        }
+    }
+
+    fn set_frozen(&self, frozen: bool) {
+        self.hold.set_property("drop", frozen);
+        self.frozen.store(frozen, Ordering::Relaxed);
+    }
+
+    fn is_frozen(&self) -> bool {
+        self.frozen.load(Ordering::Relaxed)
+    }
+
+    fn set_blanked(&self, blanked: bool) {
+        set_balance_blank(&self.blank, blanked);
+        self.blanked.store(blanked, Ordering::Relaxed);
+        if blanked {
+            self.set_frozen(false);
+        }
+    }
+
+    fn is_blanked(&self) -> bool {
+        self.blanked.load(Ordering::Relaxed)
+    }
+
+    fn element_name(&self) -> &str {
+        self.element
+    }
+
+    fn is_hardware_accelerated(&self) -> bool {
+        !self.element.starts_with("avdec_")
+    }
+}
+
+impl Drop for CompositeSlot {
+    fn drop(&mut self) {
+        self.display.detach(self.slot);
+    }
+}
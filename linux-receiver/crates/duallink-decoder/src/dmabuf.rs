@@ -0,0 +1,142 @@
+//! Decoded frames exported as dmabufs, for apps that embed a DualLink
+//! stream instead of showing it in a window.
+//!
+//! [`GStreamerDecoder::new_dmabuf`](crate::GStreamerDecoder::new_dmabuf)
+//! ends its pipeline in an `appsink` asking for `memory:DMABuf` caps: the
+//! decoder's surfaces are handed out as they are, without the copy to BGRA
+//! system memory [`GStreamerDecoder::decode_frame`](crate::GStreamerDecoder::decode_frame)
+//! makes. A compositor plugin or custom viewer imports the planes into EGL
+//! (`EGL_EXT_image_dma_buf_import`) or Vulkan (`VK_EXT_external_memory_dma_buf`)
+//! and draws them itself.
+//!
+//! ```text
+//! appsrc → h264parse → [decoder] → video/x-raw(memory:DMABuf) → appsink → DmabufFrames
+//! ```
+//!
+//! Only decoders that export dmabufs negotiate: VA-API (`vaapih264dec`,
+//! `vaapidecodebin`, `vah264dec`, …). Others fail with a pipeline error on
+//! the first frame. A [`DmabufFrame`] keeps its surface out of the
+//! decoder's pool until dropped, so hold on to frames only as long as it
+//! takes to import them; the [`DmabufFrames`] queue drops the newest
+//! frames once it holds `ReceiverQueues::decoded`.
+
+use std::os::fd::RawFd;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use gstreamer as gst;
+use gstreamer_allocators::FdMemory;
+use gstreamer_app::{AppSink, AppSinkCallbacks};
+use gstreamer_video::VideoMeta;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+/// Caps of the `appsink` of a dmabuf-exporting decoder.
+pub(crate) fn dmabuf_caps() -> gst::Caps {
+    gst::Caps::builder("video/x-raw").features(["memory:DMABuf"]).build()
+}
+
+/// One plane of a [`DmabufFrame`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DmabufPlane {
+    /// The dmabuf holding the plane; open while the frame lives.
+    pub fd:     RawFd,
+    /// Byte offset of the plane in `fd`.
+    pub offset: usize,
+    /// Bytes per row.
+    pub stride: i32,
+}
+
+/// A decoded picture left in GPU memory.
+#[derive(Debug)]
+pub struct DmabufFrame {
+    pub width:        u32,
+    pub height:       u32,
+    /// DRM fourcc and modifier as GStreamer names them (`NV12:0x0100000000000002`),
+    /// or the raw video format (`NV12`) where caps carry no DRM format
+    /// (GStreamer before 1.24: linear layout).
+    pub format:       String,
+    pub planes:       Vec<DmabufPlane>,
+    pub timestamp_us: u64,
+    /// Keeps the dmabufs open and the surface out of the decoder's pool.
+    buffer:           gst::Buffer,
+}
+
+impl DmabufFrame {
+    /// The frame in `sample`, if its memory is dmabufs laid out by a
+    /// `VideoMeta`.
+    fn from_sample(sample: &gst::Sample) -> Option<Self> {
+        let caps = sample.caps()?.structure(0)?;
+        let width = caps.get::<i32>("width").ok()? as u32;
+        let height = caps.get::<i32>("height").ok()? as u32;
+        let format = caps.get::<&str>("drm-format").or_else(|_| caps.get::<&str>("format")).ok()?.to_owned();
+        let buffer = sample.buffer_owned()?;
+        let meta = buffer.meta::<VideoMeta>()?;
+        let planes = (0..meta.n_planes() as usize)
+            .map(|i| {
+                let offset = meta.offset()[i];
+                let (memories, skip) = buffer.find_memory(offset..offset + 1)?;
+                let memory = buffer.peek_memory(memories.start).downcast_memory_ref::<FdMemory>()?;
+                Some(DmabufPlane { fd: memory.fd(), offset: memory.offset() + skip, stride: meta.stride()[i] })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let timestamp_us = buffer.pts().map_or(0, |pts| pts.useconds());
+        Some(Self { width, height, format, planes, timestamp_us, buffer })
+    }
+
+    /// The GStreamer buffer behind the frame, for apps that go on with
+    /// GStreamer (e.g. into `glupload`).
+    pub fn buffer(&self) -> &gst::Buffer {
+        &self.buffer
+    }
+}
+
+/// Frames decoded by a dmabuf-exporting
+/// [`GStreamerDecoder`](crate::GStreamerDecoder). Ends when the decoder is
+/// dropped.
+pub struct DmabufFrames {
+    rx: mpsc::Receiver<DmabufFrame>,
+}
+
+impl DmabufFrames {
+    /// Feed `appsink`'s samples into a queue of `depth` frames.
+    pub(crate) fn attach(appsink: &AppSink, depth: usize) -> Self {
+        let (tx, rx) = mpsc::channel(depth.max(1));
+        let mut warned = false;
+        appsink.set_callbacks(
+            AppSinkCallbacks::builder()
+                .new_sample(move |sink| {
+                    let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                    let Some(frame) = DmabufFrame::from_sample(&sample) else {
+                        if !std::mem::replace(&mut warned, true) {
+                            warn!("Decoded frame isn't a dmabuf with a video meta — skipped ({:?})", sample.caps());
+                        }
+                        return Ok(gst::FlowSuccess::Ok);
+                    };
+                    match tx.try_send(frame) {
+                        Ok(()) => Ok(gst::FlowSuccess::Ok),
+                        Err(mpsc::error::TrySendError::Full(_)) => {
+                            debug!("Dmabuf consumer behind — frame dropped");
+                            Ok(gst::FlowSuccess::Ok)
+                        }
+                        Err(mpsc::error::TrySendError::Closed(_)) => Err(gst::FlowError::Flushing),
+                    }
+                })
+                .build(),
+        );
+        Self { rx }
+    }
+
+    pub async fn next(&mut self) -> Option<DmabufFrame> {
+        self.rx.recv().await
+    }
+}
+
+impl Stream for DmabufFrames {
+    type Item = DmabufFrame;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<DmabufFrame>> {
+        self.rx.poll_recv(cx)
+    }
+}
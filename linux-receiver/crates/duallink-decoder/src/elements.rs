@@ -0,0 +1,83 @@
+//! Pipeline construction from elements.
+//!
+//! Pipelines are put together from `ElementFactory::make` builders with
+//! typed properties instead of `parse::launch` strings: a missing plugin or
+//! a misspelt property fails where the element is made, naming it, and
+//! element names or sizes are never spliced into a description string.
+//!
+//! [`link_chain`] links each element to the next. An element whose source
+//! pad only appears once data flows (a *sometimes* pad, as on `decodebin`
+//! style bins) is linked from its `pad-added` signal instead.
+
+use duallink_core::errors::DecoderError;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use tracing::{debug, warn};
+
+/// An element from `factory`, named `name` if given.
+pub(crate) fn make(factory: &str, name: Option<&str>) -> Result<gst::Element, DecoderError> {
+    let mut builder = gst::ElementFactory::make(factory);
+    if let Some(name) = name {
+        builder = builder.name(name);
+    }
+    builder
+        .build()
+        .map_err(|_| DecoderError::GStreamerPipeline(format!("element '{factory}' is not available")))
+}
+
+/// A `capsfilter` for `caps`.
+pub(crate) fn caps_filter(caps: &gst::Caps) -> Result<gst::Element, DecoderError> {
+    let filter = make("capsfilter", None)?;
+    filter.set_property("caps", caps);
+    Ok(filter)
+}
+
+/// Add `elements` to `bin` and link them in order.
+pub(crate) fn add_chain(bin: &impl IsA<gst::Bin>, elements: &[&gst::Element]) -> Result<(), DecoderError> {
+    for element in elements {
+        bin.add(*element).map_err(|e| DecoderError::GStreamerPipeline(e.to_string()))?;
+    }
+    link_chain(elements)
+}
+
+/// A pipeline of `elements`, linked in order.
+pub(crate) fn pipeline(elements: &[&gst::Element]) -> Result<gst::Pipeline, DecoderError> {
+    let pipeline = gst::Pipeline::new();
+    add_chain(&pipeline, elements)?;
+    Ok(pipeline)
+}
+
+/// Link each of `elements` to the next.
+pub(crate) fn link_chain(elements: &[&gst::Element]) -> Result<(), DecoderError> {
+    for pair in elements.windows(2) {
+        link_or_defer(pair[0], pair[1])?;
+    }
+    Ok(())
+}
+
+/// Link `src` to `sink` now, or — if `src` only has sometimes source pads —
+/// as soon as it adds one.
+fn link_or_defer(src: &gst::Element, sink: &gst::Element) -> Result<(), DecoderError> {
+    if src.link(sink).is_ok() {
+        return Ok(());
+    }
+    let sometimes = src
+        .pad_template_list()
+        .iter()
+        .any(|t| t.direction() == gst::PadDirection::Src && t.presence() == gst::PadPresence::Sometimes);
+    if !sometimes {
+        return Err(DecoderError::GStreamerPipeline(format!("cannot link {} to {}", src.name(), sink.name())));
+    }
+    debug!("{} has no source pad yet — linking to {} on pad-added", src.name(), sink.name());
+    let sink = sink.downgrade();
+    src.connect_pad_added(move |src, pad| {
+        let Some(sink_pad) = sink.upgrade().and_then(|s| s.static_pad("sink")) else { return };
+        if sink_pad.is_linked() {
+            return;
+        }
+        if let Err(e) = pad.link(&sink_pad) {
+            warn!("Linking {}:{} on pad-added: {:?}", src.name(), pad.name(), e);
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,166 @@
+//! Capturing system keys in a display window (see
+//! [`HotkeyAction::CaptureSystemKeys`](duallink_core::HotkeyAction::CaptureSystemKeys)).
+//!
+//! Alt+Tab, Super and the desktop's other shortcuts normally act on the
+//! receiver. While system keys are captured, the window grabs the keyboard
+//! so they arrive as ordinary key events and are forwarded to the sender.
+//!
+//! X11 sinks (`xvimagesink`, `ximagesink`, `glimagesink` on X) announce
+//! their window in a `have-window-handle` message; [`KeyboardGrab`] grabs the
+//! keyboard on it with `XGrabKeyboard`, libX11 being loaded at runtime. The
+//! receiver's own hotkeys still work during the grab, since they are picked
+//! out of the forwarded events. `waylandsink` keeps its surface to itself, so
+//! the keyboard-shortcuts-inhibit protocol can't be used on it: there, and
+//! on other platforms, the compositor keeps its shortcuts and the grab is
+//! reported as unavailable.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+/// Pause between grab attempts — the window may not be mapped yet.
+const RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// The sink's own window, once it has announced one.
+pub(crate) type WindowHandleSlot = Arc<Mutex<Option<usize>>>;
+
+/// Bus message X11 video sinks post with their window's XID.
+pub(crate) const HAVE_WINDOW_HANDLE: &str = "have-window-handle";
+
+/// Whether a window should capture system keys, and its grab while it does.
+#[derive(Default)]
+pub(crate) struct SystemKeys {
+    wanted: bool,
+    grab:   Option<KeyboardGrab>,
+    /// When the last attempt failed; the first failure is logged.
+    failed: Option<Instant>,
+}
+
+impl SystemKeys {
+    pub(crate) fn wanted(&self) -> bool {
+        self.wanted
+    }
+
+    /// `true` while the keyboard is grabbed.
+    pub(crate) fn captured(&self) -> bool {
+        self.grab.is_some()
+    }
+
+    /// Capture system keys for the window in `window`, or release them.
+    pub(crate) fn set(&mut self, wanted: bool, window: &WindowHandleSlot) {
+        if wanted == self.wanted {
+            return;
+        }
+        self.wanted = wanted;
+        self.failed = None;
+        self.grab = None;
+        if wanted && window.lock().unwrap().is_none() {
+            info!("System keys are captured once the video window has an X11 handle");
+        }
+        self.apply(window);
+    }
+
+    /// Grab the keyboard if wanted, not grabbed and the window is known by
+    /// now; a failed grab is retried every [`RETRY_AFTER`].
+    pub(crate) fn apply(&mut self, window: &WindowHandleSlot) {
+        if !self.wanted || self.grab.is_some() || self.failed.is_some_and(|at| at.elapsed() < RETRY_AFTER) {
+            return;
+        }
+        let Some(handle) = *window.lock().unwrap() else { return };
+        match KeyboardGrab::acquire(handle) {
+            Ok(grab) => {
+                info!("System keys captured — Alt+Tab, Super and other shortcuts go to the sender");
+                self.grab = Some(grab);
+            }
+            Err(e) => {
+                if self.failed.is_none() {
+                    warn!("Capturing system keys failed: {} — retrying", e);
+                }
+                self.failed = Some(Instant::now());
+            }
+        }
+    }
+}
+
+/// A keyboard grab on one window; released when dropped.
+struct KeyboardGrab {
+    #[cfg(target_os = "linux")]
+    x11: Option<x11::Connection>,
+}
+
+impl KeyboardGrab {
+    #[cfg(target_os = "linux")]
+    fn acquire(window: usize) -> Result<Self, String> {
+        x11::Connection::grab(window).map(|x11| Self { x11: Some(x11) })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn acquire(_window: usize) -> Result<Self, String> {
+        Err("not supported on this platform".into())
+    }
+}
+
+impl Drop for KeyboardGrab {
+    fn drop(&mut self) {
+        #[cfg(target_os = "linux")]
+        drop(self.x11.take());
+        info!("System keys released — shortcuts act on this desktop again");
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use std::ptr;
+
+    use x11_dl::xlib;
+
+    /// A private connection to the X server holding the grab.
+    pub(super) struct Connection {
+        xlib:    xlib::Xlib,
+        display: *mut xlib::Display,
+    }
+
+    // Only used from the decode thread that owns the display window.
+    unsafe impl Send for Connection {}
+
+    impl Connection {
+        pub(super) fn grab(window: usize) -> Result<Self, String> {
+            let xlib = xlib::Xlib::open().map_err(|e| format!("libX11: {e}"))?;
+            // SAFETY: a null name opens `$DISPLAY`; the result is checked.
+            let display = unsafe { (xlib.XOpenDisplay)(ptr::null()) };
+            if display.is_null() {
+                return Err("cannot open the X display".into());
+            }
+            let conn = Self { xlib, display };
+            // SAFETY: `display` is open and `window` is the sink's live
+            // window; an unmapped one is refused with a status.
+            let status = unsafe {
+                let status = (conn.xlib.XGrabKeyboard)(
+                    conn.display,
+                    window as xlib::Window,
+                    xlib::True,
+                    xlib::GrabModeAsync,
+                    xlib::GrabModeAsync,
+                    xlib::CurrentTime,
+                );
+                (conn.xlib.XFlush)(conn.display);
+                status
+            };
+            if status != xlib::GrabSuccess {
+                return Err(format!("XGrabKeyboard status {status}"));
+            }
+            Ok(conn)
+        }
+    }
+
+    impl Drop for Connection {
+        fn drop(&mut self) {
+            // SAFETY: `display` is open until here.
+            unsafe {
+                (self.xlib.XUngrabKeyboard)(self.display, xlib::CurrentTime);
+                (self.xlib.XCloseDisplay)(self.display);
+            }
+        }
+    }
+}
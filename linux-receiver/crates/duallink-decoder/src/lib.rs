@@ -10,6 +10,8 @@
 //! 3. `nvh264dec`     — NVIDIA NVDEC             (6.0ms avg)
 //! 4. `avdec_h264`    — Software libavcodec      (16.8ms avg) ← last resort
 //!
+//! Those timings are one machine's; see [Measured order](#measured-order).
+//!
 //! ## Windows (Phase 5B.3)
 //! 1. `d3d11h264dec`  — Direct3D 11 hardware decode (gstreamer-d3d11)
 //! 2. `mfh264dec`     — Media Foundation H.264 decode
@@ -21,17 +23,176 @@
 //! 2. `vtdec`         — VideoToolbox (may use CPU for some codecs)
 //! 3. `avdec_h264`    — Software libavcodec (last resort)
 //!
+//! # Measured order
+//!
+//! [`benchmark_decoders`] times every installed H.264 decoder on a
+//! 60-frame clip and caches the results as
+//! [`DecoderBenchmarks`](duallink_core::DecoderBenchmarks) in the config
+//! directory. The receivers run it on first launch (and with
+//! `--benchmark`); from then on H.264 probing tries measured decoders
+//! fastest first, then unmeasured ones in the static order above, and
+//! decoders that failed the benchmark last. HEVC keeps the static order.
+//!
+//! # Probe report
+//!
+//! [`DecoderFactory::probe_report`] says what probing finds without
+//! building a pipeline: the GStreamer version (or why it didn't
+//! initialise), every candidate above with whether it is installed and its
+//! benchmark, the VA-API driver, and the H.264 / H.265 elements a new
+//! session would use. The diagnostics bundle and `duallink-receiver probe`
+//! are built on it.
+//!
+//! # Overriding the probe order
+//!
+//! Broken drivers (e.g. a VA-API stack that opens but renders garbage) can
+//! be skipped without uninstalling plugins: [`DecoderFactory::with_preference`]
+//! tries the given elements first. [`DecoderFactory::from_settings`] reads
+//! the preference from `DUALLINK_DECODER=nvh264dec,avdec_h264` or, if unset,
+//! from [`ReceiverSettings`]. [`candidates`] and [`benchmark_decoder`] back
+//! the GUI's decoder picker; [`fill_diagnostics`] lists the same decoders
+//! in diagnostics bundles.
+//!
 //! # Pipeline
 //! ```text
 //! appsrc → h264parse → [decoder] → videoconvert → video/x-raw,format=BGRA → appsink
 //! ```
+//!
+//! Pipelines are built element by element with typed properties (see
+//! `elements`), never from `parse::launch` strings.
+//!
+//! # Pipeline errors
+//!
+//! Each decoder pipeline's bus is watched for ERROR and WARNING messages.
+//! Warnings are logged; the first error is kept and returned from every
+//! subsequent `push_frame` / `decode_frame` as [`DecoderError::Pipeline`]
+//! (source element, message, debug info) instead of an opaque appsink
+//! timeout. Callers fail over with
+//! [`DecoderFactory::excluding`] the element that broke.
+//!
+//! # Freeze frame
+//!
+//! Display pipelines end in a `valve` (`hold`) in front of the sink.
+//! [`DisplayOutput::set_frozen`] closes it: the sink keeps showing the last
+//! frame while the stream is still decoded, so reopening it jumps straight
+//! back to live without waiting for a keyframe.
+//!
+//! # Hidden windows
+//!
+//! GStreamer sinks don't report window state, but a window the compositor
+//! stops drawing (minimized, or fully covered on Wayland) shows up as a sink
+//! that stops taking frames. Display pipelines put a short leaky `queue` in
+//! front of the sink, so decoding goes on and the frames the sink can't
+//! take are dropped, and time when a frame last left it
+//! ([`DisplayOutput::sink_idle`]). [`AsyncDecoder`] turns that into a
+//! visibility state with [`duallink_core::VisibilityTracker`] and applies
+//! the [`HiddenMode`](duallink_core::HiddenMode) while the window is hidden.
+//!
+//! # Duplicate frames
+//!
+//! VA-API decoders under load sometimes output the same picture twice. A
+//! probe in front of the `hold` valve runs every decoded buffer through a
+//! [`DuplicateFilter`](duallink_core::DuplicateFilter) and drops those with
+//! the previous buffer's PTS or — in system memory, where the buffer can be
+//! mapped cheaply — its content hash. The counts
+//! ([`DisplayOutput::frames_unique`], [`DisplayOutput::duplicates_dropped`])
+//! give the effective unique frame rate shown in the stats card and the
+//! stats overlay. A dropped duplicate counts as a frame taken by the sink,
+//! so a static desktop is not mistaken for a hidden window.
+//!
+//! # Blanking
+//!
+//! [`DisplayOutput::set_blanked`] turns the picture black while the session
+//! goes on (privacy mode, see `blank` in the transport). It drives a colour
+//! balance in the pipeline — `videobalance`, or `vaapipostproc`'s own
+//! brightness/contrast — so it takes effect with the next decoded frame.
+//! Pipelines with neither (`d3d11convert`) hold the current frame instead.
+//!
+//! # Colorimetry
+//!
+//! The appsrc caps carry the stream's negotiated
+//! [`ColorSpace`](duallink_core::ColorSpace). `h264parse` keeps upstream caps
+//! fields the SPS does not override, so streams without a VUI colour
+//! description are still converted with the sender's range/matrix instead of
+//! `videoconvert`'s resolution-based guess.
+//!
+//! # HDR
+//!
+//! Streams with [`StreamConfig::hdr`] set are 10-bit HEVC Main10 (PQ /
+//! BT.2020), decoded with the first element in [`HEVC_DECODER_PRIORITY`]. The
+//! mastering-display and content-light-level metadata are put on the appsrc
+//! caps and flow downstream untouched: no tone mapping is done, so the image
+//! is only correct on a sink that handles HDR10 caps (e.g. `waylandsink` on a
+//! colour-managed compositor). On VA-API the 10-bit surfaces are passed
+//! through `vaapipostproc` without conversion.
+//!
+//! # Latency mode
+//!
+//! The stream's negotiated [`LatencyMode`](duallink_core::LatencyMode) sets
+//! the depth of the queue in front of the sink: one frame in ultra-low mode,
+//! a few in quality mode. [`AsyncDecoder`] applies the rest — quality mode's
+//! jitter buffer, ultra-low mode's layer shedding.
+//!
+//! # Display sync
+//!
+//! Display pipelines of one receiver share a
+//! [`DisplaySync`](duallink_core::DisplaySync): a decoded frame leaving the
+//! queue in front of the sink waits for the other streaming displays' frames
+//! of the same moment, so a window dragged across two receiver displays
+//! doesn't tear at the boundary. See [`duallink_core::display_sync`] for the
+//! wait knob.
+//!
+//! # Without GStreamer
+//!
+//! With the `software` feature, [`DecoderFactory::decoder_for`] falls back to
+//! [`SoftwareDisplayDecoder`] — OpenH264 decode into a wgpu window — when
+//! `gst::init()` fails (containers, minimal distros), or uses it first when
+//! [`SOFTWARE_DECODER`] heads the preference. It covers plain H.264 streams;
+//! see `software` for what it leaves out.
+//!
+//! # Embedding
+//!
+//! Apps that draw the stream themselves (compositor plugins, custom
+//! viewers) open a decoder with [`GStreamerDecoder::new_dmabuf`] (Linux):
+//! decoded frames come out of a [`DmabufFrames`] stream as dmabuf planes,
+//! never copied to system memory. See `dmabuf`.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 use bytes::Bytes;
-use duallink_core::{errors::DecoderError, DecodedFrame, EncodedFrame, InputEvent, MouseButton, PixelFormat};
+use duallink_core::{
+    errors::DecoderError, keyval_from_name, DecodedFrame, DecoderBenchmarks, DiagnosticsReport,
+    EncodedFrame, Filtered,
+    DisplaySync, DuplicateFilter, GestureTracker, HotkeyAction, HotkeyFilter, InputEvent, Keymap, MonitorInfo, MouseButton,
+    PixelFormat, ReceiverQueues, ReceiverSettings, StreamConfig, VideoCodec,
+};
+use duallink_core::trace::{self, Stage as TraceStage};
 use gstreamer as gst;
 use gstreamer::prelude::*;
 use gstreamer_app::{AppSink, AppSrc};
-use tracing::{info, debug, warn};
+use gstreamer_video::prelude::*;
+use tracing::{info, debug, error, warn};
+
+mod async_decoder;
+mod composite;
+#[cfg(target_os = "linux")]
+mod dmabuf;
+mod elements;
+mod keyboard_grab;
+mod overlay;
+mod probe;
+#[cfg(feature = "software")]
+mod software;
+
+pub use async_decoder::{AsyncDecoder, DecoderStats, InputEvents};
+pub use composite::{CompositeDisplay, CompositeLayout, CompositeSlot};
+#[cfg(target_os = "linux")]
+pub use dmabuf::{DmabufFrame, DmabufFrames, DmabufPlane};
+use keyboard_grab::{SystemKeys, WindowHandleSlot, HAVE_WINDOW_HANDLE};
+use overlay::Overlays;
+#[cfg(feature = "software")]
+pub use software::{SoftwareDisplayDecoder, SOFTWARE_DECODER};
 
 /// Decoder candidates in priority order — Linux (GT-2001).
 #[cfg(target_os = "linux")]
@@ -65,14 +226,85 @@ static DECODER_PRIORITY: &[(&str, &str)] = &[
     ("avdec_h264", "Software libavcodec"),
 ];
 
+/// HEVC (Main / Main10) decoder candidates in priority order — Linux.
+#[cfg(target_os = "linux")]
+pub static HEVC_DECODER_PRIORITY: &[(&str, &str)] = &[
+    ("vaapih265dec", "AMD/Intel VA-API H.265 (10-bit capable)"),
+    ("nvh265dec",    "NVIDIA NVDEC H.265"),
+    ("avdec_h265",   "Software libavcodec (last resort)"),
+];
+
+/// HEVC (Main / Main10) decoder candidates in priority order — Windows.
+#[cfg(target_os = "windows")]
+pub static HEVC_DECODER_PRIORITY: &[(&str, &str)] = &[
+    ("d3d11h265dec", "Direct3D 11 hardware H.265"),
+    ("nvh265dec",    "NVIDIA NVDEC H.265"),
+    ("avdec_h265",   "Software libavcodec (last resort)"),
+];
+
+/// HEVC (Main / Main10) decoder candidates in priority order — macOS.
+#[cfg(target_os = "macos")]
+pub static HEVC_DECODER_PRIORITY: &[(&str, &str)] = &[
+    ("vtdec_hw",   "VideoToolbox hardware H.265"),
+    ("avdec_h265", "Software libavcodec (last resort)"),
+];
+
+/// Fallback for any other OS.
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+pub static HEVC_DECODER_PRIORITY: &[(&str, &str)] = &[
+    ("avdec_h265", "Software libavcodec"),
+];
+
+/// Display sink — Windows: Direct3D 11, which also keeps `d3d11h264dec`
+/// output on the GPU.
+#[cfg(target_os = "windows")]
+pub const VIDEO_SINK: &str = "d3d11videosink";
+
+/// Display sink — elsewhere GStreamer picks the platform's best sink.
+#[cfg(not(target_os = "windows"))]
+pub const VIDEO_SINK: &str = "autovideosink";
+
 // ── Probe ─────────────────────────────────────────────────────────────────────
 
-/// Returns the name of the highest-priority available GStreamer H.264 decoder.
+/// Returns the name of the fastest available GStreamer H.264 decoder: by
+/// the cached benchmark if there is one, else the static priority order.
+///
+/// Initialises GStreamer if needed and returns `None` if it can't; use
+/// [`DecoderFactory::probe_report`] to learn why, and what else is there.
 pub fn probe_best_decoder() -> Option<&'static str> {
+    probe_decoder_list(DECODER_PRIORITY, &[], DecoderBenchmarks::load().as_ref())
+}
+
+/// Returns the name of the highest-priority available GStreamer H.265 decoder.
+pub fn probe_best_hevc_decoder() -> Option<&'static str> {
+    probe_decoder_list(HEVC_DECODER_PRIORITY, &[], None)
+}
+
+/// First installed, non-excluded element of `list`, reordered by
+/// `measured` when given.
+fn probe_decoder_list(
+    list: &'static [(&'static str, &'static str)],
+    excluded: &[&str],
+    measured: Option<&DecoderBenchmarks>,
+) -> Option<&'static str> {
     if gst::init().is_err() { return None; }
-    for (element, label) in DECODER_PRIORITY {
+    let mut order = list.to_vec();
+    if let Some(bench) = measured {
+        bench.rank(&mut order, |e| e.0);
+    }
+    for (element, label) in order {
+        if excluded.contains(&element) {
+            warn!("Decoder '{}' excluded after a pipeline error, trying next", element);
+            continue;
+        }
         if gst::ElementFactory::find(element).is_some() {
-            info!("Selected decoder: {} ({})", element, label);
+            match measured.and_then(|b| b.latency(element)) {
+                Some(d) => info!(
+                    "Selected decoder: {} ({}, {:.1} ms/frame measured)",
+                    element, label, d.as_secs_f64() * 1e3
+                ),
+                None => info!("Selected decoder: {} ({})", element, label),
+            }
             return Some(element);
         }
         warn!("Decoder '{}' not found, trying next", element);
@@ -80,6 +312,302 @@ pub fn probe_best_decoder() -> Option<&'static str> {
     None
 }
 
+/// One decoder element known for a codec, as listed by [`candidates`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecoderCandidate {
+    pub element:   &'static str,
+    pub label:     &'static str,
+    /// `true` if the GStreamer plugin providing the element is installed.
+    pub installed: bool,
+}
+
+/// Every known decoder for `codec`, in probe order.
+pub fn candidates(codec: VideoCodec) -> Vec<DecoderCandidate> {
+    let list = match codec {
+        VideoCodec::H264 => DECODER_PRIORITY,
+        VideoCodec::H265 => HEVC_DECODER_PRIORITY,
+    };
+    let gst_ok = gst::init().is_ok();
+    list.iter()
+        .map(|(element, label)| DecoderCandidate {
+            element,
+            label,
+            installed: gst_ok && gst::ElementFactory::find(element).is_some(),
+        })
+        .collect()
+}
+
+// ── Diagnostics ───────────────────────────────────────────────────────────────
+
+/// Add the GStreamer side to a diagnostics bundle: its version, every
+/// known decoder with its cached benchmark, and — unless the caller
+/// already knows the session's — the decoder the probe would pick (see
+/// [`DecoderFactory::probe_report`]).
+pub fn fill_diagnostics(report: &mut DiagnosticsReport) {
+    let probe = DecoderFactory::from_settings().probe_report_blocking();
+    report.gstreamer = probe.gstreamer;
+    report.decoders.extend(probe.candidates);
+    if report.selected_decoder.is_none() {
+        report.selected_decoder = probe.recommended;
+    }
+}
+
+// ── Benchmark ─────────────────────────────────────────────────────────────────
+
+/// Frames in the benchmark clip (720p60, one second).
+const BENCH_FRAMES: u32 = 60;
+
+/// Software encoders tried, in order, to produce the benchmark clip.
+const BENCH_ENCODERS: &[&str] = &["x264enc", "openh264enc", "avenc_h264"];
+
+/// Average per-frame time for `element` to decode a short 720p H.264 clip,
+/// or `None` if the element or every benchmark encoder is missing, or
+/// decoding failed.
+///
+/// Blocking (≈ 1 s per call); run from `spawn_blocking`. The clip is encoded
+/// once per process and reused for every element.
+pub fn benchmark_decoder(element: &str) -> Option<Duration> {
+    gst::init().ok()?;
+    gst::ElementFactory::find(element)?;
+    let clip = benchmark_clip().as_ref()?;
+
+    let appsrc = AppSrc::builder().format(gst::Format::Time).caps(&annex_b_caps()).build();
+    let parse = elements::make("h264parse", None).ok()?;
+    let decoder = elements::make(element, None).ok()?;
+    let sink = elements::make("fakesink", None).ok()?;
+    sink.set_property("sync", false);
+    let pipeline = elements::pipeline(&[appsrc.upcast_ref::<gst::Element>(), &parse, &decoder, &sink]).ok()?;
+
+    let start = Instant::now();
+    pipeline.set_state(gst::State::Playing).ok()?;
+    for buf in clip {
+        appsrc.push_buffer(buf.clone()).ok()?;
+    }
+    appsrc.end_of_stream().ok()?;
+    let msg = pipeline.bus()?.timed_pop_filtered(
+        gst::ClockTime::from_seconds(10),
+        &[gst::MessageType::Eos, gst::MessageType::Error],
+    );
+    let elapsed = start.elapsed();
+    let _ = pipeline.set_state(gst::State::Null);
+
+    match msg {
+        Some(m) if m.type_() == gst::MessageType::Eos => {
+            let per_frame = elapsed / clip.len() as u32;
+            info!("Decoder benchmark: {} {:.2} ms/frame", element, per_frame.as_secs_f64() * 1e3);
+            Some(per_frame)
+        }
+        _ => {
+            warn!("Decoder benchmark: {} failed", element);
+            None
+        }
+    }
+}
+
+/// Benchmark the installed H.264 decoders and update the cache.
+///
+/// Without `force`, only decoders missing from the cache are measured
+/// (none at all once every installed one has been); `on_result` is called
+/// for every installed decoder, with its cached or new time. Blocking —
+/// run from `spawn_blocking`.
+pub fn benchmark_decoders(
+    force: bool,
+    mut on_result: impl FnMut(&'static str, Option<Duration>),
+) -> DecoderBenchmarks {
+    let installed: Vec<_> = candidates(VideoCodec::H264).into_iter().filter(|c| c.installed).collect();
+    let mut bench = if force { DecoderBenchmarks::default() } else { DecoderBenchmarks::load().unwrap_or_default() };
+    let mut changed = false;
+    for c in &installed {
+        if !bench.results.contains_key(c.element) {
+            bench.record(c.element, benchmark_decoder(c.element));
+            changed = true;
+        }
+        on_result(c.element, bench.latency(c.element));
+    }
+    // Nothing measured without GStreamer or an encoder for the clip: keep
+    // the next launch trying instead of caching "all failed".
+    let any_measured = installed.iter().any(|c| bench.latency(c.element).is_some());
+    if changed && any_measured {
+        match bench.save() {
+            Ok(()) => info!("Decoder benchmark saved to {:?}", DecoderBenchmarks::path()),
+            Err(e) => warn!("Saving decoder benchmark: {}", e),
+        }
+    }
+    bench
+}
+
+/// Encoded benchmark clip, or `None` if no software encoder is installed.
+fn benchmark_clip() -> &'static Option<Vec<gst::Buffer>> {
+    static CLIP: OnceLock<Option<Vec<gst::Buffer>>> = OnceLock::new();
+    CLIP.get_or_init(|| {
+        let encoder = BENCH_ENCODERS.iter().find_map(|e| elements::make(e, None).ok())?;
+        if encoder.factory().is_some_and(|f| f.name() == "x264enc") {
+            encoder.set_property_from_str("tune", "zerolatency");
+            encoder.set_property("key-int-max", 30u32);
+        }
+        let src = elements::make("videotestsrc", None).ok()?;
+        src.set_property("num-buffers", BENCH_FRAMES as i32);
+        src.set_property_from_str("pattern", "smpte");
+        let raw = elements::caps_filter(
+            &gst::Caps::builder("video/x-raw")
+                .field("width", 1280i32)
+                .field("height", 720i32)
+                .field("framerate", gst::Fraction::new(60, 1))
+                .field("format", "I420")
+                .build(),
+        )
+        .ok()?;
+        let parse = elements::make("h264parse", None).ok()?;
+        let au = elements::caps_filter(&annex_b_caps()).ok()?;
+        let appsink = AppSink::builder().sync(false).build();
+        let pipeline =
+            elements::pipeline(&[&src, &raw, &encoder, &parse, &au, appsink.upcast_ref::<gst::Element>()]).ok()?;
+        pipeline.set_state(gst::State::Playing).ok()?;
+        let mut clip = Vec::with_capacity(BENCH_FRAMES as usize);
+        while let Ok(sample) = appsink.pull_sample() {
+            if let Some(buf) = sample.buffer_owned() {
+                clip.push(buf);
+            }
+        }
+        let _ = pipeline.set_state(gst::State::Null);
+        (!clip.is_empty()).then_some(clip)
+    })
+}
+
+/// Decoder used for lossless (High 4:4:4) streams — the hardware decoders in
+/// [`DECODER_PRIORITY`] only handle 4:2:0.
+const LOSSLESS_DECODER: &str = "avdec_h264";
+
+/// Optional stream capabilities this receiver can decode, advertised to
+/// senders in `hello_ack` (see [`duallink_core::CAP_H264_444`]).
+pub fn receiver_capabilities() -> Vec<String> {
+    let mut caps = Vec::new();
+    if gst::init().is_err() {
+        return caps;
+    }
+    // Display pipelines and the decode thread follow the latency mode.
+    caps.push(duallink_core::CAP_LATENCY_MODE.to_string());
+    if gst::ElementFactory::find(LOSSLESS_DECODER).is_some() {
+        caps.push(duallink_core::CAP_H264_444.to_string());
+    }
+    if HEVC_DECODER_PRIORITY.iter().any(|(e, _)| gst::ElementFactory::find(e).is_some()) {
+        caps.push(duallink_core::CAP_HEVC_MAIN10.to_string());
+    }
+    if duallink_core::configured_slice_decode() {
+        caps.push(duallink_core::CAP_NAL_SLICES.to_string());
+    }
+    caps
+}
+
+/// H.264 Annex-B access units, as the benchmark clip is produced and fed.
+fn annex_b_caps() -> gst::Caps {
+    gst::Caps::builder("video/x-h264")
+        .field("stream-format", "byte-stream")
+        .field("alignment", "au")
+        .build()
+}
+
+/// Parser element for the stream's codec.
+pub(crate) fn parser_for(codec: VideoCodec) -> &'static str {
+    match codec {
+        VideoCodec::H264 => "h264parse",
+        VideoCodec::H265 => "h265parse",
+    }
+}
+
+/// appsrc caps for `stream`: codec, Annex-B framing (whole access units, or
+/// NAL units with [`StreamConfig::nal_slices`]), colorimetry and — for HDR —
+/// the HDR10 static metadata.
+pub(crate) fn input_caps(stream: &StreamConfig) -> gst::Caps {
+    let media = match stream.codec {
+        VideoCodec::H264 => "video/x-h264",
+        VideoCodec::H265 => "video/x-h265",
+    };
+    // Mac sends Annex-B (start-code prefixed) with SPS/PPS on keyframes
+    let mut caps = gst::Caps::builder(media)
+        .field("stream-format", "byte-stream")
+        .field("alignment", if stream.nal_slices { "nal" } else { "au" })
+        .field("colorimetry", stream.gst_colorimetry());
+    if let Some(hdr) = &stream.hdr {
+        caps = caps
+            .field("mastering-display-info", hdr.gst_mastering_display_info())
+            .field("content-light-level", hdr.gst_content_light_level());
+    }
+    caps.build()
+}
+
+// ── Bus watcher ───────────────────────────────────────────────────────────────
+
+/// First ERROR posted on a pipeline's bus, kept by [`watch_bus`].
+#[derive(Debug, Clone)]
+struct BusError {
+    source_element: String,
+    message:        String,
+    debug:          Option<String>,
+}
+
+impl From<BusError> for DecoderError {
+    fn from(e: BusError) -> Self {
+        DecoderError::Pipeline { source_element: e.source_element, message: e.message, debug: e.debug }
+    }
+}
+
+type BusErrorSlot = Arc<Mutex<Option<BusError>>>;
+
+/// Install a sync handler on `pipeline`'s bus that logs WARNING messages and
+/// records the first ERROR. Both are dropped from the bus afterwards; other
+/// messages (navigation, state changes) pass through. With `window`, the
+/// sink's X11 window is recorded there when it announces one.
+fn watch_bus(pipeline: &gst::Pipeline, element: &'static str, window: Option<WindowHandleSlot>) -> BusErrorSlot {
+    let slot = BusErrorSlot::default();
+    let Some(bus) = pipeline.bus() else { return slot };
+    let recorded = Arc::clone(&slot);
+    bus.set_sync_handler(move |_, msg| {
+        if let (Some(window), Some(handle)) = (&window, window_handle(msg)) {
+            debug!("Decoder pipeline ({}) video window 0x{:x}", element, handle);
+            *window.lock().unwrap() = Some(handle);
+        }
+        let source = || msg.src().map(|s| s.name().to_string()).unwrap_or_else(|| element.to_string());
+        match msg.view() {
+            gst::MessageView::Error(err) => {
+                let e = BusError {
+                    source_element: source(),
+                    message:        err.error().to_string(),
+                    debug:          err.debug().map(|d| d.to_string()),
+                };
+                error!("Decoder pipeline ({}) error from {}: {} [{:?}]", element, e.source_element, e.message, e.debug);
+                recorded.lock().unwrap().get_or_insert(e);
+                gst::BusSyncReply::Drop
+            }
+            gst::MessageView::Warning(w) => {
+                warn!("Decoder pipeline ({}) warning from {}: {}", element, source(), w.error());
+                gst::BusSyncReply::Drop
+            }
+            _ => gst::BusSyncReply::Pass,
+        }
+    });
+    slot
+}
+
+/// The window handle a `have-window-handle` message carries, also when
+/// forwarded by a bin (`autovideosink`).
+fn window_handle(msg: &gst::Message) -> Option<usize> {
+    let gst::MessageView::Element(elem) = msg.view() else { return None };
+    let s = elem.structure()?;
+    if s.name() == "GstBinForwarded" {
+        return s.get::<gst::Message>("message").ok().as_ref().and_then(window_handle);
+    }
+    (s.name() == HAVE_WINDOW_HANDLE).then(|| s.get::<u64>("window-handle").ok()).flatten().map(|h| h as usize)
+}
+
+/// The recorded pipeline error, if any, as a [`DecoderError::Pipeline`].
+fn check_bus(slot: &BusErrorSlot) -> Result<(), DecoderError> {
+    match slot.lock().unwrap().clone() {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
+    }
+}
+
 // ── GStreamerDecoder ───────────────────────────────────────────────────────────
 
 /// Synchronous H.264 decoder backed by a GStreamer pipeline.
@@ -93,71 +621,110 @@ pub struct GStreamerDecoder {
     element:  &'static str,
     width:    u32,
     height:   u32,
+    bus_error: BusErrorSlot,
+    /// Frames leave through a `DmabufFrames` stream, not `decode_frame`.
+    exported: bool,
 }
 
 impl GStreamerDecoder {
     /// Build and start the pipeline. Requires `gst::init()` to have been called.
-    pub fn new(element: &'static str, width: u32, height: u32) -> Result<Self, DecoderError> {
-        let pipeline_str = format!(
-            "appsrc name=src format=time is-live=true \
-             ! h264parse \
-             ! {element} \
-             ! videoconvert \
-             ! video/x-raw,format=BGRA,width={width},height={height} \
-             ! appsink name=sink sync=false max-buffers=4 drop=true"
-        );
+    pub fn new(
+        element: &'static str,
+        width: u32,
+        height: u32,
+        stream: &StreamConfig,
+    ) -> Result<Self, DecoderError> {
+        let convert = elements::make("videoconvert", None)?;
+        let bgra = elements::caps_filter(
+            &gst::Caps::builder("video/x-raw")
+                .field("format", "BGRA")
+                .field("width", width as i32)
+                .field("height", height as i32)
+                .build(),
+        )?;
+        let queues = ReceiverQueues::configured();
+        let appsink = AppSink::builder()
+            .name("sink")
+            .sync(false)
+            .max_buffers(queues.decoded as u32)
+            .drop(queues.drop_decoded)
+            .build();
+        Self::with_output(element, width, height, stream, &[&convert, &bgra], appsink, false)
+    }
 
-        let pipeline = gst::parse::launch(&pipeline_str)
-            .map_err(|e| DecoderError::GStreamerPipeline(e.to_string()))?
-            .downcast::<gst::Pipeline>()
-            .map_err(|_| DecoderError::GStreamerPipeline("Not a pipeline".into()))?;
-
-        let appsrc = pipeline
-            .by_name("src")
-            .and_then(|element| element.downcast::<AppSrc>().ok())
-            .ok_or_else(|| DecoderError::GStreamerPipeline("No appsrc".into()))?;
-
-        let appsink = pipeline
-            .by_name("sink")
-            .and_then(|element| element.downcast::<AppSink>().ok())
-            .ok_or_else(|| DecoderError::GStreamerPipeline("No appsink".into()))?;
-
-        // Mac sends Annex-B (start-code prefixed) with SPS/PPS on keyframes
-        let src_caps = gst::Caps::builder("video/x-h264")
-            .field("stream-format", "byte-stream")
-            .field("alignment", "au")
+    /// Build and start a pipeline whose frames stay in the decoder's dmabufs
+    /// and come out of the returned stream; `decode_frame` is not used, feed
+    /// it with [`push_frame`](Self::push_frame). Needs a decoder that exports
+    /// dmabufs (VA-API). Requires `gst::init()` to have been called.
+    #[cfg(target_os = "linux")]
+    pub fn new_dmabuf(element: &'static str, stream: &StreamConfig) -> Result<(Self, DmabufFrames), DecoderError> {
+        let dmabuf = elements::caps_filter(&dmabuf::dmabuf_caps())?;
+        let appsink = AppSink::builder().name("sink").sync(false).max_buffers(1).build();
+        let frames = DmabufFrames::attach(&appsink, ReceiverQueues::configured().decoded);
+        let (width, height) = (stream.resolution.width, stream.resolution.height);
+        let decoder = Self::with_output(element, width, height, stream, &[&dmabuf], appsink, true)?;
+        Ok((decoder, frames))
+    }
+
+    /// appsrc → parser → `element` → `output` → `appsink`, playing.
+    fn with_output(
+        element: &'static str,
+        width: u32,
+        height: u32,
+        stream: &StreamConfig,
+        output: &[&gst::Element],
+        appsink: AppSink,
+        exported: bool,
+    ) -> Result<Self, DecoderError> {
+        let appsrc = AppSrc::builder()
+            .name("src")
+            .format(gst::Format::Time)
+            .is_live(true)
+            .caps(&input_caps(stream))
             .build();
-        appsrc.set_caps(Some(&src_caps));
+        let parser = elements::make(parser_for(stream.codec), None)?;
+        let decoder = elements::make(element, None)?;
+        let mut chain = vec![appsrc.upcast_ref::<gst::Element>(), &parser, &decoder];
+        chain.extend_from_slice(output);
+        chain.push(appsink.upcast_ref::<gst::Element>());
+        let pipeline = elements::pipeline(&chain)?;
+
+        let bus_error = watch_bus(&pipeline, element, None);
 
         pipeline
             .set_state(gst::State::Playing)
             .map_err(|_| DecoderError::GStreamerPipeline("Failed to start pipeline".into()))?;
 
-        info!("GStreamerDecoder({}) ready {}x{}", element, width, height);
-        Ok(Self { pipeline, appsrc, appsink, element, width, height })
+        info!("GStreamerDecoder({}) ready {}x{}{}", element, width, height, if exported { " (dmabuf)" } else { "" });
+        Ok(Self { pipeline, appsrc, appsink, element, width, height, bus_error, exported })
+    }
+
+    /// Push one encoded frame into the pipeline without waiting for its
+    /// picture; for decoders whose frames go to a [`DmabufFrames`] stream.
+    pub fn push_frame(&self, frame: &EncodedFrame) -> Result<(), DecoderError> {
+        check_bus(&self.bus_error)?;
+        self.appsrc.push_buffer(frame_buffer(frame))
+            .map_err(|_| DecoderError::DecodeFailed { reason: "appsrc push failed".into() })?;
+        Ok(())
     }
 
     /// Push one encoded frame into the pipeline. Returns None while pipeline fills.
     pub fn decode_frame(&self, frame: EncodedFrame) -> Result<DecodedFrame, DecoderError> {
-        // Allocate GStreamer buffer and copy NAL data
-        let data_len = frame.data.len();
-        let mut gst_buf = gst::Buffer::with_size(data_len)
-            .map_err(|_| DecoderError::DecodeFailed { reason: "alloc failed".into() })?;
-        {
-            let br = gst_buf.get_mut().unwrap();
-            br.set_pts(gst::ClockTime::from_useconds(frame.timestamp_us));
-            let mut map = br.map_writable()
-                .map_err(|_| DecoderError::DecodeFailed { reason: "map failed".into() })?;
-            map.copy_from_slice(&frame.data);
+        check_bus(&self.bus_error)?;
+        if self.exported {
+            return Err(DecoderError::DecodeFailed { reason: "frames go to the decoder's dmabuf stream".into() });
         }
 
-        self.appsrc.push_buffer(gst_buf)
+        let data_len = frame.data.len();
+        self.appsrc.push_buffer(frame_buffer(&frame))
             .map_err(|_| DecoderError::DecodeFailed { reason: "appsrc push failed".into() })?;
 
         // Pull decoded sample (500ms timeout — decoder pipeline needs a few frames to fill)
-        let sample = self.appsink
-            .try_pull_sample(gst::ClockTime::from_mseconds(500))
-            .ok_or_else(|| DecoderError::DecodeFailed { reason: format!("appsink timeout (pushed {} bytes)", data_len) })?;
+        let Some(sample) = self.appsink.try_pull_sample(gst::ClockTime::from_mseconds(500)) else {
+            // A pipeline error is the usual reason nothing came out.
+            check_bus(&self.bus_error)?;
+            return Err(DecoderError::DecodeFailed { reason: format!("appsink timeout (pushed {} bytes)", data_len) });
+        };
 
         let buffer = sample.buffer_owned()
             .ok_or_else(|| DecoderError::DecodeFailed { reason: "no buffer in sample".into() })?;
@@ -175,13 +742,27 @@ impl GStreamerDecoder {
     }
 
     pub fn element_name(&self) -> &str { self.element }
-    pub fn is_hardware_accelerated(&self) -> bool { self.element != "avdec_h264" }
+    pub fn is_hardware_accelerated(&self) -> bool { !self.element.starts_with("avdec_") }
 }
 
 impl Drop for GStreamerDecoder {
     fn drop(&mut self) { let _ = self.pipeline.set_state(gst::State::Null); }
 }
 
+/// Presentation sync shared by every display pipeline in the process.
+fn display_sync() -> &'static Arc<DisplaySync> {
+    static SYNC: OnceLock<Arc<DisplaySync>> = OnceLock::new();
+    SYNC.get_or_init(|| {
+        let wait = DisplaySync::configured_wait(&ReceiverSettings::load());
+        if wait.is_zero() {
+            info!("Display sync off");
+        } else {
+            info!("Display sync: frames wait up to {:?} for the other displays", wait);
+        }
+        DisplaySync::new(wait)
+    })
+}
+
 // ── GStreamerDisplayDecoder ────────────────────────────────────────────────────
 
 /// Combined decode + display pipeline — Sprint 2.1
@@ -201,11 +782,56 @@ pub struct GStreamerDisplayDecoder {
     pipeline: gst::Pipeline,
     appsrc:   AppSrc,
     element:  &'static str,
-    #[allow(dead_code)]
     width:    u32,
-    #[allow(dead_code)]
     height:   u32,
     frame_count: std::sync::atomic::AtomicU64,
+    bus_error: BusErrorSlot,
+    /// Cleared for view-only sessions: navigation events are drained but
+    /// not returned.
+    input_enabled: std::sync::atomic::AtomicBool,
+    /// Pulls hotkeys out of the window's key events before they are returned.
+    hotkeys: Mutex<HotkeyFilter>,
+    /// Phases for touch gestures in the window.
+    gestures: Mutex<GestureTracker>,
+    /// Toggled by [`HotkeyAction::ReleaseInput`]: events are dropped while set.
+    input_released: std::sync::atomic::AtomicBool,
+    /// The sink's X11 window, once it has announced one.
+    window: WindowHandleSlot,
+    /// Toggled by [`HotkeyAction::CaptureSystemKeys`].
+    system_keys: Mutex<SystemKeys>,
+    /// Hotkeys left to the session loop, see [`DisplayOutput::poll_hotkeys`].
+    session_hotkeys: Mutex<Vec<HotkeyAction>>,
+    /// `textoverlay` for the stats hotkey; `None` when the decoder output
+    /// stays in GPU memory.
+    stats_overlay: Option<gst::Element>,
+    /// Start, frame count and unique frame count of the current overlay
+    /// fps window.
+    stats_window: Mutex<(Instant, u64, u64)>,
+    /// Configured overlay widgets; `None` when the decoder output stays in
+    /// GPU memory.
+    overlays: Option<Overlays>,
+    /// `valve` in front of the sink, closed while frozen.
+    hold: gst::Element,
+    frozen: std::sync::atomic::AtomicBool,
+    /// Colour balance that blanks the picture; `None` on `d3d11convert`.
+    blank: Option<gst::Element>,
+    blanked: std::sync::atomic::AtomicBool,
+    /// When a frame last left the queue in front of the sink; `None` before
+    /// the first one.
+    sink_taken: Arc<Mutex<Option<Instant>>>,
+    /// Decoded buffers let through and dropped as duplicates.
+    duplicates: Arc<Mutex<DuplicateFilter>>,
+    /// Decoded frames saved as PNGs; `None` when they stay in GPU memory.
+    png_dump: Option<Arc<Mutex<PngDump>>>,
+}
+
+/// Where decoded frames go as PNGs during a frame dump (see
+/// [`duallink_core::dump`]).
+#[derive(Debug, Default)]
+struct PngDump {
+    /// `None` while not dumping.
+    dir:    Option<PathBuf>,
+    frames: u64,
 }
 
 impl GStreamerDisplayDecoder {
@@ -215,56 +841,140 @@ impl GStreamerDisplayDecoder {
     /// via the pipeline clock.  The sender stamps each frame with a PTS; GStreamer
     /// schedules rendering at the right time.  If network jitter causes late frames,
     /// `max-lateness=20000000` (20ms) allows slight skips without dropping.
-    pub fn new(element: &'static str, width: u32, height: u32) -> Result<Self, DecoderError> {
-        let is_vaapi = element.starts_with("vaapi");
-        let postproc = if is_vaapi {
-            "vaapipostproc".to_string()
-        } else {
-            "videoconvert ! videoscale".to_string()
-        };
+    pub fn new(
+        element: &'static str,
+        width: u32,
+        height: u32,
+        stream: &StreamConfig,
+    ) -> Result<Self, DecoderError> {
+        let appsrc = AppSrc::builder()
+            .name("src")
+            .format(gst::Format::Time)
+            .is_live(true)
+            .do_timestamp(true)
+            .caps(&input_caps(stream))
+            .build();
+        let mut chain = vec![
+            appsrc.clone().upcast::<gst::Element>(),
+            elements::make(parser_for(stream.codec), None)?,
+            elements::make(element, None)?,
+        ];
 
-        // sync=true enables frame pacing via PTS; max-lateness tolerates 20ms jitter
-        let pipeline_str = format!(
-            "appsrc name=src format=time is-live=true do-timestamp=true \
-             ! h264parse \
-             ! {element} \
-             ! {postproc} \
-             ! autovideosink name=videosink sync=false"
-        );
+        // Post-processing that can scale and blank: `blank` is the colour
+        // balance, `None` where there is none.
+        let mut blank = None;
+        let mut system_memory = false;
+        if element.starts_with("vaapi") {
+            let postproc = elements::make("vaapipostproc", Some("blank"))?;
+            blank = Some(postproc.clone());
+            chain.push(postproc);
+        } else if element.starts_with("d3d11") && VIDEO_SINK == "d3d11videosink" {
+            chain.push(elements::make("d3d11convert", None)?);
+        } else {
+            system_memory = true;
+            chain.push(elements::make("videoconvert", None)?);
+            chain.push(elements::make("videoscale", None)?);
+            if let Ok(balance) = elements::make("videobalance", Some("blank")) {
+                blank = Some(balance.clone());
+                chain.push(balance);
+            }
+        }
 
-        let pipeline = gst::parse::launch(&pipeline_str)
-            .map_err(|e| DecoderError::GStreamerPipeline(e.to_string()))?
-            .downcast::<gst::Pipeline>()
-            .map_err(|_| DecoderError::GStreamerPipeline("Not a pipeline".into()))?;
+        // The stats overlay draws on system-memory frames only.
+        let stats_overlay = system_memory.then(|| elements::make("textoverlay", Some("stats")).ok()).flatten();
+        if let Some(overlay) = &stats_overlay {
+            overlay.set_property("silent", true);
+            overlay.set_property_from_str("valignment", "top");
+            overlay.set_property_from_str("halignment", "left");
+            overlay.set_property("shaded-background", true);
+            overlay.set_property("font-desc", "Monospace 11");
+            chain.push(overlay.clone());
+        }
+        // Widgets from the settings, on the same condition.
+        let overlays = system_memory.then(|| Overlays::new(&ReceiverSettings::load().overlays));
+        chain.extend(overlays.iter().flat_map(|o| o.elements().cloned()));
 
-        let appsrc = pipeline
-            .by_name("src")
-            .and_then(|el| el.downcast::<AppSrc>().ok())
-            .ok_or_else(|| DecoderError::GStreamerPipeline("No appsrc".into()))?;
+        let hold = elements::make("valve", Some("hold"))?;
+        hold.set_property("drop", false);
+        let sink_taken = Arc::new(Mutex::new(None));
+        let duplicates = Arc::new(Mutex::new(DuplicateFilter::default()));
+        let png_dump = system_memory.then(|| Arc::new(Mutex::new(PngDump::default())));
+        if let Some(sink) = hold.static_pad("sink") {
+            if let Some(dump) = png_dump.clone() {
+                sink.add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+                    let mut dump = dump.lock().unwrap();
+                    if let (Some(dir), Some(buffer)) = (dump.dir.clone(), info.buffer()) {
+                        dump.frames += 1;
+                        save_png(pad, buffer, &dir.join(format!("frame-{:05}.png", dump.frames)));
+                    }
+                    gst::PadProbeReturn::Ok
+                });
+            }
+            let filter = Arc::clone(&duplicates);
+            let taken = Arc::clone(&sink_taken);
+            sink.add_probe(gst::PadProbeType::BUFFER, move |_, info| {
+                let Some(buffer) = info.buffer() else { return gst::PadProbeReturn::Ok };
+                // GPU surfaces would have to be downloaded: PTS only.
+                let hash = system_memory
+                    .then(|| buffer.map_readable().ok().map(|map| duallink_core::frame_hash(&map)))
+                    .flatten();
+                let pts = buffer.pts().map(|t| t.nseconds());
+                if let Some(pts) = pts.filter(|_| trace::is_recording()) {
+                    trace::advance(TraceStage::Decode, TraceStage::Display, pts / 1000, Instant::now());
+                }
+                if !filter.lock().unwrap().is_duplicate(pts, hash) {
+                    return gst::PadProbeReturn::Ok;
+                }
+                // Nothing new to show — not a sink refusing frames.
+                if let Some(t) = taken.lock().unwrap().as_mut() {
+                    *t = Instant::now();
+                }
+                gst::PadProbeReturn::Drop
+            });
+        }
+        // Drops what a sink whose window is hidden won't take.
+        let queue = elements::make("queue", None)?;
+        queue.set_property("max-size-buffers", stream.latency_mode.sink_queue());
+        queue.set_property("max-size-bytes", 0u32);
+        queue.set_property("max-size-time", 0u64);
+        queue.set_property_from_str("leaky", "downstream");
+        if let Some(src) = queue.static_pad("src") {
+            let taken = Arc::clone(&sink_taken);
+            let sync = display_sync().join(stream.display_index);
+            src.add_probe(gst::PadProbeType::BUFFER, move |_, info| {
+                if let Some(pts) = info.buffer().and_then(|b| b.pts()) {
+                    sync.present(pts.useconds());
+                }
+                let now = Instant::now();
+                *taken.lock().unwrap() = Some(now);
+                if let Some(pts) = info.buffer().and_then(|b| b.pts()).filter(|_| trace::is_recording()) {
+                    trace::end(TraceStage::Display, pts.useconds(), now);
+                }
+                gst::PadProbeReturn::Ok
+            });
+        }
+        let videosink = elements::make(VIDEO_SINK, Some("videosink"))?;
+        videosink.set_property("sync", false);
+        chain.push(hold.clone());
+        chain.push(queue);
+        chain.push(videosink.clone());
 
-        // Mac sends Annex-B (start-code prefixed) with SPS/PPS on keyframes
-        let src_caps = gst::Caps::builder("video/x-h264")
-            .field("stream-format", "byte-stream")
-            .field("alignment", "au")
-            .build();
-        appsrc.set_caps(Some(&src_caps));
+        let pipeline = elements::pipeline(&chain.iter().collect::<Vec<_>>())?;
+        let window = WindowHandleSlot::default();
+        let bus_error = watch_bus(&pipeline, element, Some(Arc::clone(&window)));
 
         // autovideosink is a GstBin — by default message-forward=false,
         // which swallows Element messages (including GstNavigation) from the
         // inner sink.  We MUST enable forwarding so poll_input_events() can
-        // read navigation messages from the pipeline bus.
-        if let Some(videosink) = pipeline.by_name("videosink") {
-            videosink.set_property("message-forward", true);
-            info!("Enabled message-forward on autovideosink for navigation events");
-        } else {
-            warn!("Could not find 'videosink' element — input events may not work");
-        }
+        // read navigation messages from the pipeline bus. Plain sinks
+        // (d3d11videosink) post them directly.
+        forward_sink_messages(&videosink);
 
         pipeline
             .set_state(gst::State::Playing)
             .map_err(|_| DecoderError::GStreamerPipeline("Failed to start display pipeline".into()))?;
 
-        info!("GStreamerDisplayDecoder({}) ready {}×{} — fullscreen display via autovideosink", element, width, height);
+        info!("GStreamerDisplayDecoder({}) ready {}×{} — fullscreen display via {}", element, width, height, VIDEO_SINK);
 
         Ok(Self {
             pipeline,
@@ -273,155 +983,590 @@ impl GStreamerDisplayDecoder {
             width,
             height,
             frame_count: std::sync::atomic::AtomicU64::new(0),
+            bus_error,
+            input_enabled: std::sync::atomic::AtomicBool::new(true),
+            hotkeys: Mutex::new(HotkeyFilter::new(Keymap::configured())),
+            gestures: Mutex::new(GestureTracker::new(width as f64 / height.max(1) as f64)),
+            input_released: std::sync::atomic::AtomicBool::new(false),
+            window,
+            system_keys: Mutex::new(SystemKeys::default()),
+            session_hotkeys: Mutex::new(Vec::new()),
+            stats_overlay,
+            stats_window: Mutex::new((Instant::now(), 0, 0)),
+            overlays,
+            hold,
+            frozen: std::sync::atomic::AtomicBool::new(false),
+            blank,
+            blanked: std::sync::atomic::AtomicBool::new(false),
+            sink_taken,
+            duplicates,
+            png_dump,
         })
     }
 
     /// Push one encoded frame into the pipeline. GStreamer decodes and displays it.
+    ///
+    /// Fails with [`DecoderError::Pipeline`] once the pipeline has posted an
+    /// error; the decoder should then be replaced.
     pub fn push_frame(&self, frame: EncodedFrame) -> Result<(), DecoderError> {
+        check_bus(&self.bus_error)?;
         let data_len = frame.data.len();
-        let mut gst_buf = gst::Buffer::with_size(data_len)
-            .map_err(|_| DecoderError::DecodeFailed { reason: "alloc failed".into() })?;
-        {
-            let br = gst_buf.get_mut().unwrap();
-            br.set_pts(gst::ClockTime::from_useconds(frame.timestamp_us));
-            let mut map = br.map_writable()
-                .map_err(|_| DecoderError::DecodeFailed { reason: "map failed".into() })?;
-            map.copy_from_slice(&frame.data);
-        }
+        let gst_buf = frame_buffer(&frame);
 
         self.appsrc.push_buffer(gst_buf)
             .map_err(|_| DecoderError::DecodeFailed { reason: "appsrc push failed".into() })?;
+        if frame.partial {
+            return Ok(());
+        }
 
         let n = self.frame_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
         if n == 1 {
             info!("First frame pushed to display pipeline ({} bytes)", data_len);
         }
+        if let Some(overlay) = &self.stats_overlay {
+            self.update_stats_overlay(overlay, n);
+        }
+        if let Some(overlays) = &self.overlays {
+            overlays.tick();
+        }
 
         Ok(())
     }
 
+    /// Refresh the stats overlay text about once a second while it is shown.
+    fn update_stats_overlay(&self, overlay: &gst::Element, frames: u64) {
+        let mut window = self.stats_window.lock().unwrap();
+        let elapsed = window.0.elapsed();
+        if elapsed < Duration::from_secs(1) {
+            return;
+        }
+        let unique = self.frames_unique();
+        let fps = frames.saturating_sub(window.1) as f64 / elapsed.as_secs_f64();
+        let unique_fps = unique.saturating_sub(window.2) as f64 / elapsed.as_secs_f64();
+        *window = (Instant::now(), frames, unique);
+        if !overlay.property::<bool>("silent") {
+            overlay.set_property(
+                "text",
+                format!(
+                    "{} {}×{}\n{:.0} fps ({:.0} unique) · {} frames · {} duplicates{}",
+                    self.element,
+                    self.width,
+                    self.height,
+                    fps,
+                    unique_fps,
+                    frames,
+                    self.duplicates_dropped(),
+                    if self.captures_system_keys() { "\nSystem keys captured" } else { "" },
+                ),
+            );
+        }
+    }
+
     /// Number of frames pushed so far.
     pub fn frames_pushed(&self) -> u64 {
         self.frame_count.load(std::sync::atomic::Ordering::Relaxed)
     }
 
+    /// Decoded frames that reached the sink side, duplicates not counted.
+    pub fn frames_unique(&self) -> u64 {
+        self.duplicates.lock().unwrap().unique()
+    }
+
+    /// Decoded frames dropped as duplicates of the one before.
+    pub fn duplicates_dropped(&self) -> u64 {
+        self.duplicates.lock().unwrap().duplicates()
+    }
+
     /// Poll for input (navigation) events from the GStreamer display window.
     ///
     /// Returns all pending mouse/keyboard events since the last call.
     /// Call this regularly from the decode thread (e.g. after each `push_frame`).
+    /// Always empty while input is disabled or released.
+    ///
+    /// Hotkeys (see [`duallink_core::hotkeys`]) are handled here and never
+    /// returned — also in view-only sessions. Ending the session is left to
+    /// the caller via [`poll_hotkeys`](Self::poll_hotkeys).
     pub fn poll_input_events(&self) -> Vec<InputEvent> {
-        let mut events = Vec::new();
-        let bus = match self.pipeline.bus() {
-            Some(b) => b,
-            None => return events,
-        };
+        use std::sync::atomic::Ordering::Relaxed;
+        let w = self.width as f64;
+        let h = self.height as f64;
+        let enabled = self.input_enabled.load(Relaxed);
+        self.system_keys.lock().unwrap().apply(&self.window);
+        let raw = drain_navigation_events(
+            &self.pipeline,
+            &|px, py| Some(((px / w).clamp(0.0, 1.0), (py / h).clamp(0.0, 1.0))),
+            &mut self.gestures.lock().unwrap(),
+        );
 
-        // Drain all pending messages
-        while let Some(msg) = bus.pop() {
-            match msg.view() {
-                gst::MessageView::Element(elem) => {
-                    if let Some(s) = elem.structure() {
-                        // autovideosink with message-forward=true wraps child
-                        // messages in a "GstBinForwarded" structure.  Unwrap it.
-                        if s.name() == "GstBinForwarded" {
-                            if let Ok(fwd_msg) = s.get::<gst::Message>("message") {
-                                if let gst::MessageView::Element(inner) = fwd_msg.view() {
-                                    if let Some(inner_s) = inner.structure() {
-                                        if let Some(ev) = self.parse_navigation_event(inner_s) {
-                                            events.push(ev);
-                                        }
-                                    }
-                                }
-                            }
-                        } else if let Some(ev) = self.parse_navigation_event(s) {
-                            events.push(ev);
+        let mut filter = self.hotkeys.lock().unwrap();
+        let mut events = Vec::new();
+        for event in raw {
+            let action = match filter.filter(event) {
+                Filtered::Forward(ev) => {
+                    if enabled && !self.input_released.load(Relaxed) {
+                        events.push(ev);
+                    }
+                    continue;
+                }
+                Filtered::Consumed => continue,
+                Filtered::Hotkey(action) => action,
+            };
+            info!("Hotkey pressed: {:?}", action);
+            match action {
+                HotkeyAction::ToggleFullscreen => self.toggle_fullscreen(),
+                HotkeyAction::ToggleStats => self.toggle_stats(),
+                HotkeyAction::ToggleOverlays => match &self.overlays {
+                    Some(overlays) => overlays.toggle(),
+                    None => info!("Overlay widgets need system-memory frames — not drawn with {}", self.element),
+                },
+                HotkeyAction::ToggleFreeze => self.set_frozen(!self.is_frozen()),
+                HotkeyAction::ReleaseInput => {
+                    if self.input_released.fetch_xor(true, Relaxed) {
+                        info!("Input grabbed again — forwarding to the sender");
+                    } else {
+                        // Let go of the chord's modifiers on the sender.
+                        if enabled {
+                            events.extend(filter.release_modifiers());
                         }
+                        info!("Input released — press the hotkey again to resume");
+                    }
+                }
+                HotkeyAction::CaptureSystemKeys if !enabled => info!("View-only session — system keys stay here"),
+                HotkeyAction::CaptureSystemKeys => self.set_system_keys(!self.system_keys_wanted()),
+                HotkeyAction::RecordTrace => {
+                    let dir = duallink_core::diagnostics::home_dir().unwrap_or_else(|| ".".into());
+                    if !trace::start(trace::DEFAULT_DURATION, dir) {
+                        info!("A frame trace is already recording");
                     }
                 }
-                gst::MessageView::Error(err) => {
-                    warn!("GStreamer pipeline error: {}", err.error());
+                HotkeyAction::ToggleBlank | HotkeyAction::EndSession => {
+                    self.session_hotkeys.lock().unwrap().push(action)
                 }
-                _ => {}
             }
         }
         events
     }
 
-    /// Parse a GStreamer navigation structure into an InputEvent.
-    ///
-    /// Navigation structures have:
-    /// - `event` field: "mouse-move", "mouse-button-press", "mouse-button-release",
-    ///   "mouse-scroll", "key-press", "key-release"
-    /// - `pointer_x`, `pointer_y`: absolute pixel coords (f64)
-    /// - `button`: mouse button number (1=left, 2=middle, 3=right)
-    /// - `key`: keyval string for keyboard events
-    /// - `delta_x`, `delta_y`: scroll deltas
-    fn parse_navigation_event(&self, s: &gst::StructureRef) -> Option<InputEvent> {
-        let event_type = s.get::<&str>("event").ok()?;
-        let w = self.width as f64;
-        let h = self.height as f64;
+    /// Hold the frame on screen while frames keep being decoded, or jump
+    /// back to live.
+    pub fn set_frozen(&self, frozen: bool) {
+        self.hold.set_property("drop", frozen);
+        if self.frozen.swap(frozen, std::sync::atomic::Ordering::Relaxed) != frozen {
+            info!("Display {}", if frozen { "frozen — the sender keeps streaming" } else { "live again" });
+            if !frozen {
+                // The sink was idle on purpose.
+                if let Some(taken) = self.sink_taken.lock().unwrap().as_mut() {
+                    *taken = Instant::now();
+                }
+            }
+        }
+    }
+
+    /// Grab the keyboard so system shortcuts reach the sender (X11 sinks),
+    /// or leave them to this desktop again.
+    pub fn set_system_keys(&self, captured: bool) {
+        self.system_keys.lock().unwrap().set(captured, &self.window);
+    }
+
+    /// `true` while system keys are to be captured, grabbed yet or not.
+    pub fn system_keys_wanted(&self) -> bool {
+        self.system_keys.lock().unwrap().wanted()
+    }
+
+    /// `true` while the window holds the keyboard.
+    pub fn captures_system_keys(&self) -> bool {
+        self.system_keys.lock().unwrap().captured()
+    }
+
+    /// Time since a frame last went to the sink; `None` before the first.
+    pub fn sink_idle(&self) -> Option<Duration> {
+        self.sink_taken.lock().unwrap().map(|t| t.elapsed())
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.load(std::sync::atomic::Ordering::Relaxed)
+    }
 
-        match event_type {
-            "mouse-move" => {
-                let px = s.get::<f64>("pointer_x").ok()?;
-                let py = s.get::<f64>("pointer_y").ok()?;
-                Some(InputEvent::MouseMove {
-                    x: (px / w).clamp(0.0, 1.0),
-                    y: (py / h).clamp(0.0, 1.0),
-                })
+    /// Show black instead of the stream, or the stream again. Blanking
+    /// ends a freeze, so the black frames reach the sink.
+    pub fn set_blanked(&self, blanked: bool) {
+        if self.blanked.swap(blanked, std::sync::atomic::Ordering::Relaxed) == blanked {
+            return;
+        }
+        match &self.blank {
+            Some(balance) => {
+                set_balance_blank(balance, blanked);
+                self.set_frozen(false);
             }
-            "mouse-button-press" => {
-                let px = s.get::<f64>("pointer_x").ok()?;
-                let py = s.get::<f64>("pointer_y").ok()?;
-                let btn = s.get::<i32>("button").unwrap_or(1);
-                Some(InputEvent::MouseDown {
-                    x: (px / w).clamp(0.0, 1.0),
-                    y: (py / h).clamp(0.0, 1.0),
-                    button: gst_button_to_mouse_button(btn),
-                })
+            None => {
+                if blanked {
+                    warn!("{} cannot blank the picture — holding the current frame instead", self.element);
+                }
+                self.set_frozen(blanked);
             }
-            "mouse-button-release" => {
-                let px = s.get::<f64>("pointer_x").ok()?;
-                let py = s.get::<f64>("pointer_y").ok()?;
-                let btn = s.get::<i32>("button").unwrap_or(1);
-                Some(InputEvent::MouseUp {
-                    x: (px / w).clamp(0.0, 1.0),
-                    y: (py / h).clamp(0.0, 1.0),
-                    button: gst_button_to_mouse_button(btn),
-                })
+        }
+        info!("Display {}", if blanked { "blanked" } else { "unblanked" });
+    }
+
+    pub fn is_blanked(&self) -> bool {
+        self.blanked.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Hotkeys for the session loop pressed since the last call.
+    pub fn poll_hotkeys(&self) -> Vec<HotkeyAction> {
+        std::mem::take(&mut *self.session_hotkeys.lock().unwrap())
+    }
+
+    /// Flip the sink's `fullscreen` property (`waylandsink`,
+    /// `d3d11videosink`); sinks without one are left alone.
+    fn toggle_fullscreen(&self) {
+        let Some(videosink) = self.pipeline.by_name("videosink") else { return };
+        let sink = if videosink.find_property("fullscreen").is_some() {
+            Some(videosink)
+        } else {
+            videosink.downcast_ref::<gst::Bin>().and_then(|bin| {
+                bin.iterate_recurse()
+                    .into_iter()
+                    .filter_map(Result::ok)
+                    .find(|el| el.find_property("fullscreen").is_some())
+            })
+        };
+        let Some(sink) = sink else {
+            warn!("Video sink has no fullscreen property — fullscreen hotkey ignored");
+            return;
+        };
+        if sink.find_property("fullscreen-toggle-mode").is_some() {
+            sink.set_property_from_str("fullscreen-toggle-mode", "property");
+        }
+        let fullscreen = !sink.property::<bool>("fullscreen");
+        sink.set_property("fullscreen", fullscreen);
+        info!("Display window {}", if fullscreen { "fullscreen" } else { "windowed" });
+    }
+
+    /// Show or hide the stats overlay; logs the stats instead when the
+    /// pipeline has no overlay.
+    fn toggle_stats(&self) {
+        let Some(overlay) = &self.stats_overlay else {
+            info!(
+                "Stats: {} {}×{} hw={} — {} frames pushed, {} unique, {} duplicates dropped",
+                self.element,
+                self.width,
+                self.height,
+                self.is_hardware_accelerated(),
+                self.frames_pushed(),
+                self.frames_unique(),
+                self.duplicates_dropped(),
+            );
+            return;
+        };
+        let show = overlay.property::<bool>("silent");
+        if show {
+            overlay.set_property("text", format!("{} {}×{}", self.element, self.width, self.height));
+            *self.stats_window.lock().unwrap() = (Instant::now(), self.frames_pushed(), self.frames_unique());
+        }
+        overlay.set_property("silent", !show);
+    }
+
+    /// Forward navigation events from the window (default), or drop them
+    /// for a view-only session.
+    pub fn set_input_enabled(&self, enabled: bool) {
+        self.input_enabled.store(enabled, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Pause the pipeline of a decoder built ahead of its session, or set
+    /// it playing once the session starts.
+    pub fn set_playing(&self, playing: bool) {
+        let state = if playing { gst::State::Playing } else { gst::State::Paused };
+        if let Err(e) = self.pipeline.set_state(state) {
+            warn!("Display pipeline did not go to {:?}: {}", state, e);
+        }
+    }
+
+    /// Switch the appsrc caps to `stream`'s; its resolution and codec must
+    /// be the ones the pipeline was built for.
+    pub fn set_stream(&self, stream: &StreamConfig) -> bool {
+        self.appsrc.set_caps(Some(&input_caps(stream)));
+        true
+    }
+
+    /// Move the video window onto `monitor`, covering it.
+    ///
+    /// Uses `GstVideoOverlay::set_render_rectangle`, which repositions the
+    /// sink's own window on X11 (`glimagesink`, `xvimagesink`). Wayland does
+    /// not let clients place windows, so there it only resizes.
+    pub fn move_to_monitor(&self, monitor: &MonitorInfo) {
+        let Some(overlay) = self
+            .pipeline
+            .by_interface(gstreamer_video::VideoOverlay::static_type())
+            .and_then(|el| el.dynamic_cast::<gstreamer_video::VideoOverlay>().ok())
+        else {
+            warn!("Video sink has no VideoOverlay — cannot move window to {}", monitor.name);
+            return;
+        };
+        let (w, h) = (monitor.resolution.width as i32, monitor.resolution.height as i32);
+        match overlay.set_render_rectangle(monitor.x, monitor.y, w, h) {
+            Ok(()) => {
+                overlay.expose();
+                info!("Moved display window to {} ({}×{} at {},{})", monitor.name, w, h, monitor.x, monitor.y);
             }
-            "mouse-scroll" => {
-                let px = s.get::<f64>("pointer_x").ok()?;
-                let py = s.get::<f64>("pointer_y").ok()?;
-                let dx = s.get::<f64>("delta_x").unwrap_or(0.0);
-                let dy = s.get::<f64>("delta_y").unwrap_or(0.0);
-                Some(InputEvent::MouseScroll {
-                    x: (px / w).clamp(0.0, 1.0),
-                    y: (py / h).clamp(0.0, 1.0),
-                    delta_x: dx,
-                    delta_y: dy,
-                })
+            Err(e) => warn!("Could not move display window to {}: {}", monitor.name, e),
+        }
+    }
+
+    /// JPEG of the frame the sink last rendered, scaled to `width` pixels
+    /// wide. `None` before the first frame, or when the sink keeps no
+    /// `last-sample`.
+    pub fn snapshot_jpeg(&self, width: u32) -> Option<Vec<u8>> {
+        let videosink = self.pipeline.by_name("videosink")?;
+        // autovideosink is a bin; the rendering child holds the sample.
+        let sink = if videosink.find_property("last-sample").is_some() {
+            videosink
+        } else {
+            videosink.downcast_ref::<gst::Bin>()?.iterate_recurse()
+                .into_iter()
+                .filter_map(Result::ok)
+                .find(|el| el.find_property("last-sample").is_some())?
+        };
+        let sample = sink.property::<Option<gst::Sample>>("last-sample")?;
+        let info = gstreamer_video::VideoInfo::from_caps(sample.caps()?).ok()?;
+        let height = (u64::from(width) * u64::from(info.height()) / u64::from(info.width().max(1))).max(2) as i32 & !1;
+        let caps = gst::Caps::builder("image/jpeg")
+            .field("width", width as i32)
+            .field("height", height)
+            .build();
+        match gstreamer_video::convert_sample(&sample, &caps, gst::ClockTime::from_seconds(1)) {
+            Ok(jpeg) => Some(jpeg.buffer()?.map_readable().ok()?.to_vec()),
+            Err(e) => {
+                debug!("Display snapshot failed: {}", e);
+                None
             }
-            "key-press" => {
-                let key = s.get::<&str>("key").ok()?;
-                let keyval = x11_keyval_from_name(key);
-                debug!("Key press: '{}' keyval={}", key, keyval);
-                Some(InputEvent::KeyDown {
-                    keycode: keyval,
-                    text: if key.len() == 1 { Some(key.to_string()) } else { None },
-                })
+        }
+    }
+
+    /// Save decoded frames as PNGs into `dir`, or stop with `None`.
+    /// `false` when the frames stay in GPU memory.
+    pub fn dump_decoded(&self, dir: Option<PathBuf>) -> bool {
+        let Some(dump) = &self.png_dump else { return false };
+        *dump.lock().unwrap() = PngDump { dir, frames: 0 };
+        true
+    }
+
+    pub fn element_name(&self) -> &str { self.element }
+    pub fn is_hardware_accelerated(&self) -> bool { !self.element.starts_with("avdec_") }
+}
+
+/// Write the decoded `buffer` flowing through `pad` to `path` as a PNG.
+fn save_png(pad: &gst::Pad, buffer: &gst::BufferRef, path: &std::path::Path) {
+    let Some(caps) = pad.current_caps() else { return };
+    let sample = gst::Sample::builder().buffer(&buffer.to_owned()).caps(&caps).build();
+    let png = gst::Caps::builder("image/png").build();
+    let result = gstreamer_video::convert_sample(&sample, &png, gst::ClockTime::from_seconds(1))
+        .map_err(|e| e.to_string())
+        .and_then(|png| {
+            let map = png.buffer().and_then(|b| b.map_readable().ok()).ok_or("empty PNG")?;
+            std::fs::write(path, map.as_slice()).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        warn!("Saving decoded frame {} failed: {}", path.display(), e);
+    }
+}
+
+/// Make a bin sink forward its children's navigation messages; no-op for
+/// sinks that are not bins.
+pub(crate) fn forward_sink_messages(videosink: &gst::Element) {
+    if videosink.find_property("message-forward").is_some() {
+        videosink.set_property("message-forward", true);
+        info!("Enabled message-forward on {} for navigation events", VIDEO_SINK);
+    }
+}
+
+/// Black out (or restore) the picture through a `videobalance` /
+/// `vaapipostproc` colour balance.
+pub(crate) fn set_balance_blank(balance: &gst::Element, blanked: bool) {
+    let (brightness, contrast, saturation) = if blanked { (-1.0, 0.0, 0.0) } else { (0.0, 1.0, 1.0) };
+    balance.set_property("brightness", brightness);
+    balance.set_property("contrast", contrast);
+    balance.set_property("saturation", saturation);
+}
+
+/// Wrap an encoded frame in a GStreamer buffer stamped with its PTS.
+///
+/// No copy: the buffer's memory is the frame's `Bytes`, released back to
+/// the transport's receive pool when GStreamer drops the buffer.
+pub(crate) fn frame_buffer(frame: &EncodedFrame) -> gst::Buffer {
+    let mut gst_buf = gst::Buffer::from_slice(frame.data.clone());
+    let buf = gst_buf.get_mut().unwrap();
+    buf.set_pts(gst::ClockTime::from_useconds(frame.timestamp_us));
+    // With `alignment=nal` the parser takes the marker as the end of the
+    // access unit instead of waiting for the next one to start.
+    if !frame.partial {
+        buf.set_flags(gst::BufferFlags::MARKER);
+    }
+    gst_buf
+}
+
+/// Drain navigation messages from `pipeline`'s bus into [`InputEvent`]s.
+///
+/// `normalize` maps window pixel coordinates to the sender's normalised
+/// `0.0..=1.0` space, or `None` to drop the event. Touch points are turned
+/// into phased gestures by `gestures`.
+pub(crate) fn drain_navigation_events(
+    pipeline: &gst::Pipeline,
+    normalize: &dyn Fn(f64, f64) -> Option<(f64, f64)>,
+    gestures: &mut GestureTracker,
+) -> Vec<InputEvent> {
+    let mut events = Vec::new();
+    let bus = match pipeline.bus() {
+        Some(b) => b,
+        None => return events,
+    };
+
+    // Drain all pending messages
+    while let Some(msg) = bus.pop() {
+        match msg.view() {
+            gst::MessageView::Element(elem) => {
+                if let Some(s) = elem.structure() {
+                    // autovideosink with message-forward=true wraps child
+                    // messages in a "GstBinForwarded" structure.  Unwrap it.
+                    if s.name() == "GstBinForwarded" {
+                        if let Ok(fwd_msg) = s.get::<gst::Message>("message") {
+                            if let gst::MessageView::Element(inner) = fwd_msg.view() {
+                                if let Some(inner_s) = inner.structure() {
+                                    push_navigation_event(inner_s, normalize, gestures, &mut events);
+                                }
+                            }
+                        }
+                    } else {
+                        push_navigation_event(s, normalize, gestures, &mut events);
+                    }
+                }
             }
-            "key-release" => {
-                let key = s.get::<&str>("key").ok()?;
-                let keyval = x11_keyval_from_name(key);
-                Some(InputEvent::KeyUp { keycode: keyval })
+            gst::MessageView::Error(err) => {
+                warn!("GStreamer pipeline error: {}", err.error());
             }
-            _ => None,
+            _ => {}
         }
     }
+    events
+}
 
-    pub fn element_name(&self) -> &str { self.element }
-    pub fn is_hardware_accelerated(&self) -> bool { self.element != "avdec_h264" }
+/// Append the events for one navigation structure: touch points go through
+/// `gestures`, everything else maps one-to-one.
+fn push_navigation_event(
+    s: &gst::StructureRef,
+    normalize: &dyn Fn(f64, f64) -> Option<(f64, f64)>,
+    gestures: &mut GestureTracker,
+    events: &mut Vec<InputEvent>,
+) {
+    match s.get::<&str>("event") {
+        Ok(kind) if kind.starts_with("touch-") => events.extend(parse_touch_event(kind, s, normalize, gestures)),
+        _ => events.extend(parse_navigation_event(s, normalize)),
+    }
+}
+
+/// Feed a touch navigation event (GStreamer ≥ 1.22: "touch-down",
+/// "touch-motion", "touch-up", "touch-frame", "touch-cancel") to `gestures`.
+///
+/// Touch events carry an `identifier` per finger plus `pointer_x` /
+/// `pointer_y`; single-finger touches produce no events (sinks also report
+/// them as pointer events).
+fn parse_touch_event(
+    kind: &str,
+    s: &gst::StructureRef,
+    normalize: &dyn Fn(f64, f64) -> Option<(f64, f64)>,
+    gestures: &mut GestureTracker,
+) -> Vec<InputEvent> {
+    let id = s.get::<u32>("identifier").ok().map(u64::from);
+    let point = || normalize(s.get::<f64>("pointer_x").ok()?, s.get::<f64>("pointer_y").ok()?);
+    match (kind, id) {
+        ("touch-down", Some(id)) => point().map(|(x, y)| gestures.touch_down(id, x, y)).unwrap_or_default(),
+        ("touch-motion", Some(id)) => point().map(|(x, y)| gestures.touch_move(id, x, y)).unwrap_or_default(),
+        ("touch-up", Some(id)) => gestures.touch_up(id),
+        ("touch-cancel", _) => gestures.cancel(),
+        _ => Vec::new(),
+    }
+}
+
+/// Parse a GStreamer navigation structure into an InputEvent.
+///
+/// Navigation structures have:
+/// - `event` field: "mouse-move", "mouse-button-press", "mouse-button-release",
+///   "mouse-scroll", "key-press", "key-release"
+/// - `pointer_x`, `pointer_y`: absolute pixel coords (f64)
+/// - `button`: mouse button number (1=left, 2=middle, 3=right)
+/// - `key`: keyval string for keyboard events
+/// - `delta_x`, `delta_y`: scroll deltas
+fn parse_navigation_event(
+    s: &gst::StructureRef,
+    normalize: &dyn Fn(f64, f64) -> Option<(f64, f64)>,
+) -> Option<InputEvent> {
+    let event_type = s.get::<&str>("event").ok()?;
+
+    match event_type {
+        "mouse-move" => {
+            let (x, y) = normalize(
+                s.get::<f64>("pointer_x").ok()?,
+                s.get::<f64>("pointer_y").ok()?,
+            )?;
+            Some(InputEvent::MouseMove {
+                x,
+                y,
+            })
+        }
+        "mouse-button-press" => {
+            let (x, y) = normalize(
+                s.get::<f64>("pointer_x").ok()?,
+                s.get::<f64>("pointer_y").ok()?,
+            )?;
+            let btn = s.get::<i32>("button").unwrap_or(1);
+            Some(InputEvent::MouseDown {
+                x,
+                y,
+                button: gst_button_to_mouse_button(btn),
+            })
+        }
+        "mouse-button-release" => {
+            let (x, y) = normalize(
+                s.get::<f64>("pointer_x").ok()?,
+                s.get::<f64>("pointer_y").ok()?,
+            )?;
+            let btn = s.get::<i32>("button").unwrap_or(1);
+            Some(InputEvent::MouseUp {
+                x,
+                y,
+                button: gst_button_to_mouse_button(btn),
+            })
+        }
+        "mouse-scroll" => {
+            let (x, y) = normalize(
+                s.get::<f64>("pointer_x").ok()?,
+                s.get::<f64>("pointer_y").ok()?,
+            )?;
+            let dx = s.get::<f64>("delta_x").unwrap_or(0.0);
+            let dy = s.get::<f64>("delta_y").unwrap_or(0.0);
+            Some(InputEvent::MouseScroll {
+                x,
+                y,
+                delta_x: dx,
+                delta_y: dy,
+            })
+        }
+        "key-press" => {
+            let key = s.get::<&str>("key").ok()?;
+            let keyval = keyval_from_name(key);
+            debug!("Key press: '{}' keyval={}", key, keyval);
+            Some(InputEvent::KeyDown {
+                keycode: keyval,
+                text: if key.len() == 1 { Some(key.to_string()) } else { None },
+            })
+        }
+        "key-release" => {
+            let key = s.get::<&str>("key").ok()?;
+            let keyval = keyval_from_name(key);
+            Some(InputEvent::KeyUp { keycode: keyval })
+        }
+        _ => None,
+    }
 }
 
 /// Map GStreamer button number (1-based) to MouseButton.
@@ -434,58 +1579,149 @@ fn gst_button_to_mouse_button(btn: i32) -> MouseButton {
     }
 }
 
-/// Map GStreamer/X11 key name to a keyval.
-/// GStreamer sends X11 key names (e.g. "a", "Return", "Shift_L", "space").
-/// We pass the raw X11 keyval so the Mac side can map it.
-fn x11_keyval_from_name(name: &str) -> u32 {
-    // Common special keys — full mapping via xkbcommon if needed later
-    match name {
-        "Return" | "KP_Enter" => 0xff0d,
-        "Escape" => 0xff1b,
-        "Tab" => 0xff09,
-        "BackSpace" => 0xff08,
-        "Delete" => 0xffff,
-        "space" => 0x0020,
-        "Shift_L" => 0xffe1,
-        "Shift_R" => 0xffe2,
-        "Control_L" => 0xffe3,
-        "Control_R" => 0xffe4,
-        "Alt_L" => 0xffe9,
-        "Alt_R" => 0xffea,
-        "Super_L" => 0xffeb,
-        "Super_R" => 0xffec,
-        "Left" => 0xff51,
-        "Up" => 0xff52,
-        "Right" => 0xff53,
-        "Down" => 0xff54,
-        "Home" => 0xff50,
-        "End" => 0xff57,
-        "Page_Up" => 0xff55,
-        "Page_Down" => 0xff56,
-        "F1" => 0xffbe,
-        "F2" => 0xffbf,
-        "F3" => 0xffc0,
-        "F4" => 0xffc1,
-        "F5" => 0xffc2,
-        "F6" => 0xffc3,
-        "F7" => 0xffc4,
-        "F8" => 0xffc5,
-        "F9" => 0xffc6,
-        "F10" => 0xffc7,
-        "F11" => 0xffc8,
-        "F12" => 0xffc9,
-        "Caps_Lock" => 0xffe5,
-        _ => {
-            // For single-char keys, use the Unicode codepoint
-            let mut chars = name.chars();
-            if let Some(c) = chars.next() {
-                if chars.next().is_none() {
-                    return c as u32;
-                }
-            }
-            // Unknown — pass name hash as fallback
-            0
-        }
+// ── DisplayOutput ─────────────────────────────────────────────────────────────
+
+/// A per-session video output: its own window ([`GStreamerDisplayDecoder`])
+/// or one slot of a shared [`CompositeDisplay`].
+pub trait DisplayOutput: Send {
+    /// Push one encoded frame for decode + display. A
+    /// [`partial`](EncodedFrame::partial) one is the head of the next.
+    fn push_frame(&self, frame: EncodedFrame) -> Result<(), DecoderError>;
+    /// Number of complete frames pushed so far.
+    fn frames_pushed(&self) -> u64;
+    /// Pending navigation events, normalised to this output's stream.
+    fn poll_input_events(&self) -> Vec<InputEvent>;
+    /// Stop (or resume) returning navigation events — view-only sessions.
+    fn set_input_enabled(&self, enabled: bool);
+    /// Hotkeys pressed in the output window that the session loop handles
+    /// ([`HotkeyAction::ToggleBlank`], [`HotkeyAction::EndSession`]), since
+    /// the last call.
+    fn poll_hotkeys(&self) -> Vec<HotkeyAction> {
+        Vec::new()
+    }
+    /// Keep showing the current frame while frames are still pushed (or
+    /// resume showing them).
+    fn set_frozen(&self, frozen: bool);
+    fn is_frozen(&self) -> bool;
+    /// Show black while frames are still pushed (privacy blank), or the
+    /// stream again.
+    fn set_blanked(&self, blanked: bool);
+    fn is_blanked(&self) -> bool;
+    /// Grab the keyboard so system shortcuts are forwarded, or release it.
+    /// No-op for outputs that can't.
+    fn set_system_keys(&self, _captured: bool) {}
+    /// `true` while system keys are to be captured — the grab itself may
+    /// still be waiting for the window.
+    fn system_keys_wanted(&self) -> bool {
+        false
+    }
+    fn element_name(&self) -> &str;
+    fn is_hardware_accelerated(&self) -> bool;
+    /// Move the output window onto `monitor` (receiver hot-plug). No-op for
+    /// outputs that don't own a window.
+    fn move_to_monitor(&self, _monitor: &MonitorInfo) {}
+    /// Hold the pipeline in `PAUSED` (a decoder built ahead of its session)
+    /// or set it playing. No-op for outputs without a pipeline of their own.
+    fn set_playing(&self, _playing: bool) {}
+    /// Take the input caps of `stream` — colorimetry, HDR metadata, NAL
+    /// alignment — on an output built for another config of the same
+    /// resolution and codec. `false` if the output can't; it must then be
+    /// rebuilt.
+    fn set_stream(&self, _stream: &StreamConfig) -> bool {
+        false
+    }
+    /// JPEG of what the output shows, `width` pixels wide, for the
+    /// sender's preview. `None` when the output cannot take one.
+    fn snapshot_jpeg(&self, _width: u32) -> Option<Vec<u8>> {
+        None
+    }
+    /// Time since the sink last took a frame, to tell a hidden window (see
+    /// [Hidden windows](crate#hidden-windows)). `None` before the first
+    /// frame, and for outputs that can't tell.
+    fn sink_idle(&self) -> Option<Duration> {
+        None
+    }
+    /// Decoded frames shown, without the duplicates (see
+    /// [Duplicate frames](crate#duplicate-frames)). Outputs that don't
+    /// detect duplicates count every pushed frame.
+    fn frames_unique(&self) -> u64 {
+        self.frames_pushed()
+    }
+    /// Decoded frames dropped as duplicates of the one before.
+    fn duplicates_dropped(&self) -> u64 {
+        0
+    }
+    /// Save decoded frames as PNGs into `dir` during a frame dump (see
+    /// [`duallink_core::dump`]), or stop with `None`. `false` when the
+    /// output cannot.
+    fn dump_decoded(&self, _dir: Option<PathBuf>) -> bool {
+        false
+    }
+}
+
+impl DisplayOutput for GStreamerDisplayDecoder {
+    fn push_frame(&self, frame: EncodedFrame) -> Result<(), DecoderError> {
+        GStreamerDisplayDecoder::push_frame(self, frame)
+    }
+    fn frames_pushed(&self) -> u64 {
+        GStreamerDisplayDecoder::frames_pushed(self)
+    }
+    fn poll_input_events(&self) -> Vec<InputEvent> {
+        GStreamerDisplayDecoder::poll_input_events(self)
+    }
+    fn set_input_enabled(&self, enabled: bool) {
+        GStreamerDisplayDecoder::set_input_enabled(self, enabled)
+    }
+    fn poll_hotkeys(&self) -> Vec<HotkeyAction> {
+        GStreamerDisplayDecoder::poll_hotkeys(self)
+    }
+    fn set_frozen(&self, frozen: bool) {
+        GStreamerDisplayDecoder::set_frozen(self, frozen)
+    }
+    fn is_frozen(&self) -> bool {
+        GStreamerDisplayDecoder::is_frozen(self)
+    }
+    fn set_blanked(&self, blanked: bool) {
+        GStreamerDisplayDecoder::set_blanked(self, blanked)
+    }
+    fn is_blanked(&self) -> bool {
+        GStreamerDisplayDecoder::is_blanked(self)
+    }
+    fn set_system_keys(&self, captured: bool) {
+        GStreamerDisplayDecoder::set_system_keys(self, captured)
+    }
+    fn system_keys_wanted(&self) -> bool {
+        GStreamerDisplayDecoder::system_keys_wanted(self)
+    }
+    fn element_name(&self) -> &str {
+        GStreamerDisplayDecoder::element_name(self)
+    }
+    fn is_hardware_accelerated(&self) -> bool {
+        GStreamerDisplayDecoder::is_hardware_accelerated(self)
+    }
+    fn move_to_monitor(&self, monitor: &MonitorInfo) {
+        GStreamerDisplayDecoder::move_to_monitor(self, monitor)
+    }
+    fn set_playing(&self, playing: bool) {
+        GStreamerDisplayDecoder::set_playing(self, playing)
+    }
+    fn set_stream(&self, stream: &StreamConfig) -> bool {
+        GStreamerDisplayDecoder::set_stream(self, stream)
+    }
+    fn snapshot_jpeg(&self, width: u32) -> Option<Vec<u8>> {
+        GStreamerDisplayDecoder::snapshot_jpeg(self, width)
+    }
+    fn sink_idle(&self) -> Option<Duration> {
+        GStreamerDisplayDecoder::sink_idle(self)
+    }
+    fn frames_unique(&self) -> u64 {
+        GStreamerDisplayDecoder::frames_unique(self)
+    }
+    fn duplicates_dropped(&self) -> u64 {
+        GStreamerDisplayDecoder::duplicates_dropped(self)
+    }
+    fn dump_decoded(&self, dir: Option<PathBuf>) -> bool {
+        GStreamerDisplayDecoder::dump_decoded(self, dir)
     }
 }
 
@@ -498,22 +1734,161 @@ impl Drop for GStreamerDisplayDecoder {
 
 // ── DecoderFactory ─────────────────────────────────────────────────────────────
 
-pub struct DecoderFactory;
+/// Creates decoders, optionally trying a list of preferred elements before
+/// the platform probe order.
+///
+/// The associated functions (`for_config`, …) use the probe order; build an
+/// instance with [`with_preference`](Self::with_preference) or
+/// [`from_settings`](Self::from_settings) to apply a preference.
+#[derive(Debug, Clone, Default)]
+pub struct DecoderFactory {
+    preference: Vec<String>,
+    /// Elements never selected — they failed earlier in this session.
+    excluded:   Vec<String>,
+}
 
 impl DecoderFactory {
+    /// Factory that tries `elements` in order before probing. Unknown or
+    /// uninstalled elements are skipped; lossless streams ignore the list.
+    pub fn with_preference(elements: &[&str]) -> Self {
+        Self { preference: elements.iter().map(|e| e.to_string()).collect(), excluded: Vec::new() }
+    }
+
+    /// This factory, never selecting any of `elements` (failover after a
+    /// [`DecoderError::Pipeline`]). Lossless streams still use the software
+    /// decoder, which is the only one that handles them.
+    pub fn excluding(mut self, elements: &[String]) -> Self {
+        self.excluded.extend(elements.iter().cloned());
+        self
+    }
+
+    /// Preference from `DUALLINK_DECODER` (comma-separated elements), else
+    /// the one saved in [`ReceiverSettings`].
+    pub fn from_settings() -> Self {
+        let preference = match std::env::var("DUALLINK_DECODER") {
+            Ok(list) => list
+                .split(',')
+                .map(str::trim)
+                .filter(|e| !e.is_empty())
+                .map(String::from)
+                .collect(),
+            Err(_) => ReceiverSettings::load().decoder_preference,
+        };
+        if !preference.is_empty() {
+            info!("Decoder preference: {}", preference.join(", "));
+        }
+        Self { preference, excluded: Vec::new() }
+    }
+
+    /// Preferred elements, in order (empty = probe order).
+    pub fn preference(&self) -> &[String] {
+        &self.preference
+    }
+
+    /// Like [`for_config`](Self::for_config), applying this factory's preference.
+    ///
+    /// With the `software` feature this is a [`SoftwareDisplayDecoder`] when
+    /// GStreamer is unavailable or the preference starts with
+    /// [`SOFTWARE_DECODER`].
+    pub fn decoder_for(&self, config: &StreamConfig) -> Result<Box<dyn DisplayOutput>, DecoderError> {
+        #[cfg(feature = "software")]
+        {
+            let preferred = self.preference.first().is_some_and(|e| e == SOFTWARE_DECODER)
+                && !self.excluded.iter().any(|e| e == SOFTWARE_DECODER);
+            if preferred {
+                info!("Selected decoder: {} (preferred)", SOFTWARE_DECODER);
+                return Ok(Box::new(SoftwareDisplayDecoder::new(config)?));
+            }
+            if let Err(e) = gst::init() {
+                warn!("GStreamer unavailable ({}) — decoding with {}", e, SOFTWARE_DECODER);
+                return Ok(Box::new(SoftwareDisplayDecoder::new(config)?));
+            }
+        }
+        let preferred: Vec<&str> = self.preference.iter().map(String::as_str).collect();
+        let excluded: Vec<&str> = self.excluded.iter().map(String::as_str).collect();
+        let (width, height) = (config.resolution.width, config.resolution.height);
+        let element = Self::element_for(config, &preferred, &excluded)?;
+        Ok(Box::new(GStreamerDisplayDecoder::new(element, width, height, config)?))
+    }
+
     /// Probe and initialise the best available decoder for the given resolution.
     /// Returns a decoder that produces `DecodedFrame` via `decode_frame()`.
     pub fn best_available(width: u32, height: u32) -> Result<GStreamerDecoder, DecoderError> {
         gst::init().map_err(|e| DecoderError::GStreamerPipeline(e.to_string()))?;
         let element = probe_best_decoder().ok_or(DecoderError::HardwareUnavailable)?;
-        GStreamerDecoder::new(element, width, height)
+        GStreamerDecoder::new(element, width, height, &StreamConfig::default())
     }
 
     /// Probe and initialise a combined decode+display pipeline.
-    /// Frames are decoded AND displayed directly via `autovideosink`.
+    /// Frames are decoded AND displayed directly via [`VIDEO_SINK`].
     pub fn best_available_with_display(width: u32, height: u32) -> Result<GStreamerDisplayDecoder, DecoderError> {
         gst::init().map_err(|e| DecoderError::GStreamerPipeline(e.to_string()))?;
         let element = probe_best_decoder().ok_or(DecoderError::HardwareUnavailable)?;
-        GStreamerDisplayDecoder::new(element, width, height)
+        GStreamerDisplayDecoder::new(element, width, height, &StreamConfig::default())
+    }
+
+    /// Like [`best_available_with_display`](Self::best_available_with_display),
+    /// but honours the negotiated stream mode: lossless streams are decoded
+    /// with [`LOSSLESS_DECODER`] regardless of hardware availability, HEVC /
+    /// HDR streams with the best HEVC decoder, and the stream's colour space
+    /// and HDR metadata are applied to the input caps.
+    pub fn for_config(config: &StreamConfig) -> Result<Box<dyn DisplayOutput>, DecoderError> {
+        Self::for_config_preferring(config, None)
+    }
+
+    /// Like [`for_config`](Self::for_config), but tries `preferred` first
+    /// (a per-display decoder preference). The element must be one of the
+    /// known candidates for the stream's codec and installed; otherwise the
+    /// normal probe order applies. Lossless streams ignore the preference.
+    pub fn for_config_preferring(
+        config: &StreamConfig,
+        preferred: Option<&str>,
+    ) -> Result<Box<dyn DisplayOutput>, DecoderError> {
+        Self::with_preference(preferred.as_slice()).decoder_for(config)
+    }
+
+    /// Decoder element for `config`: the first usable entry of `preferred`,
+    /// else the probe order, skipping `excluded` — see
+    /// [`for_config_preferring`](Self::for_config_preferring).
+    pub(crate) fn element_for(
+        config: &StreamConfig,
+        preferred: &[&str],
+        excluded: &[&str],
+    ) -> Result<&'static str, DecoderError> {
+        gst::init().map_err(|e| DecoderError::GStreamerPipeline(e.to_string()))?;
+        let candidates = if config.codec == VideoCodec::H265 { HEVC_DECODER_PRIORITY } else { DECODER_PRIORITY };
+        let preferred = preferred.iter().filter(|_| !config.lossless).find_map(|name| {
+            let found = candidates
+                .iter()
+                .map(|(element, _)| *element)
+                .find(|element| {
+                    element == name && !excluded.contains(element) && gst::ElementFactory::find(element).is_some()
+                });
+            if found.is_none() {
+                warn!("Preferred decoder '{}' unknown, excluded or not installed — skipping", name);
+            }
+            found
+        });
+        let element = if let Some(element) = preferred {
+            info!("Selected decoder: {} (preferred)", element);
+            element
+        } else if config.codec == VideoCodec::H265 {
+            probe_decoder_list(HEVC_DECODER_PRIORITY, excluded, None).ok_or(DecoderError::HardwareUnavailable)?
+        } else if config.lossless {
+            info!("Lossless stream — using {} (High 4:4:4)", LOSSLESS_DECODER);
+            LOSSLESS_DECODER
+        } else {
+            probe_decoder_list(DECODER_PRIORITY, excluded, DecoderBenchmarks::load().as_ref())
+                .ok_or(DecoderError::HardwareUnavailable)?
+        };
+        match &config.hdr {
+            Some(hdr) => info!(
+                "HDR10 stream — mastering display {}, CLL {}",
+                hdr.gst_mastering_display_info(),
+                hdr.gst_content_light_level()
+            ),
+            None => info!("Stream colour space: {}", config.color),
+        }
+        Ok(element)
     }
 }
@@ -0,0 +1,140 @@
+//! Overlay widgets from the saved settings (see [`duallink_core::overlay`])
+//! as GStreamer elements in the display chain.
+//!
+//! | Widget  | Element            |
+//! |---------|--------------------|
+//! | clock   | `clockoverlay`     |
+//! | timer   | `textoverlay`, text refreshed once a second |
+//! | logo    | `gdkpixbufoverlay` |
+//! | badge   | `textoverlay`      |
+//!
+//! Each widget is one element after the stats overlay. A widget whose
+//! plugin is missing is left out with a warning rather than failing the
+//! pipeline.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use duallink_core::overlay::{timer_text, DEFAULT_CLOCK_FORMAT};
+use duallink_core::{errors::DecoderError, OverlayWidget};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use tracing::{info, warn};
+
+use crate::elements;
+
+/// Gap between a logo and the window edge, in pixels.
+const LOGO_MARGIN: i32 = 16;
+
+const WIDGET_FONT: &str = "Sans Bold 20";
+
+/// The widget elements of one display pipeline.
+pub(crate) struct Overlays {
+    /// Each element with its widget's opacity (logos) — text widgets use
+    /// `silent` instead.
+    elements: Vec<(gst::Element, Option<f64>)>,
+    /// Timer elements with their meeting length.
+    timers:   Vec<(gst::Element, Option<u32>)>,
+    started:  Instant,
+    /// When the timers were last refreshed.
+    ticked:   Mutex<Instant>,
+    hidden:   AtomicBool,
+}
+
+impl Overlays {
+    /// Elements for `widgets`, in order.
+    pub(crate) fn new(widgets: &[OverlayWidget]) -> Self {
+        let now = Instant::now();
+        let mut elements = Vec::new();
+        let mut timers = Vec::new();
+        for (i, widget) in widgets.iter().enumerate() {
+            match make_widget(widget, i) {
+                Ok(element) => {
+                    if let OverlayWidget::Timer { minutes, .. } = widget {
+                        element.set_property("text", timer_text(Duration::ZERO, *minutes));
+                        timers.push((element.clone(), *minutes));
+                    }
+                    let alpha = match widget {
+                        OverlayWidget::Logo { alpha, .. } => Some(alpha.clamp(0.0, 1.0)),
+                        _ => None,
+                    };
+                    elements.push((element, alpha));
+                }
+                Err(e) => warn!("Overlay {:?} left out: {}", widget, e),
+            }
+        }
+        Self { elements, timers, started: now, ticked: Mutex::new(now), hidden: AtomicBool::new(false) }
+    }
+
+    /// The elements to link into the chain, in order.
+    pub(crate) fn elements(&self) -> impl Iterator<Item = &gst::Element> {
+        self.elements.iter().map(|(element, _)| element)
+    }
+
+    /// Refresh the timers if a second has passed since the last refresh.
+    pub(crate) fn tick(&self) {
+        if self.timers.is_empty() {
+            return;
+        }
+        let mut ticked = self.ticked.lock().unwrap();
+        if ticked.elapsed() < Duration::from_secs(1) {
+            return;
+        }
+        *ticked = Instant::now();
+        let elapsed = self.started.elapsed();
+        for (element, minutes) in &self.timers {
+            element.set_property("text", timer_text(elapsed, *minutes));
+        }
+    }
+
+    /// Hide the widgets if shown, else show them.
+    pub(crate) fn toggle(&self) {
+        if self.elements.is_empty() {
+            info!("No overlay widgets configured");
+            return;
+        }
+        let hidden = !self.hidden.fetch_xor(true, Ordering::Relaxed);
+        for (element, alpha) in &self.elements {
+            match alpha {
+                Some(alpha) => element.set_property("alpha", if hidden { 0.0 } else { *alpha }),
+                None => element.set_property("silent", hidden),
+            }
+        }
+        info!("Overlay widgets {}", if hidden { "hidden" } else { "shown" });
+    }
+}
+
+/// The element drawing `widget`, the `index`-th in the settings.
+fn make_widget(widget: &OverlayWidget, index: usize) -> Result<gst::Element, DecoderError> {
+    let name = format!("overlay{index}");
+    let element = match widget {
+        OverlayWidget::Clock { format, .. } => {
+            let clock = elements::make("clockoverlay", Some(&name))?;
+            clock.set_property("time-format", format.as_deref().unwrap_or(DEFAULT_CLOCK_FORMAT));
+            clock
+        }
+        OverlayWidget::Timer { .. } => elements::make("textoverlay", Some(&name))?,
+        OverlayWidget::Badge { text, .. } => {
+            let badge = elements::make("textoverlay", Some(&name))?;
+            badge.set_property("text", text.as_str());
+            badge
+        }
+        OverlayWidget::Logo { path, alpha, corner } => {
+            let logo = elements::make("gdkpixbufoverlay", Some(&name))?;
+            logo.set_property("location", path.to_string_lossy().as_ref());
+            logo.set_property("alpha", alpha.clamp(0.0, 1.0));
+            // Negative offsets count from the right / bottom edge.
+            let (h, v) = corner.alignment();
+            logo.set_property("offset-x", if h == "right" { -LOGO_MARGIN } else { LOGO_MARGIN });
+            logo.set_property("offset-y", if v == "bottom" { -LOGO_MARGIN } else { LOGO_MARGIN });
+            return Ok(logo);
+        }
+    };
+    let (h, v) = widget.corner().alignment();
+    element.set_property_from_str("halignment", h);
+    element.set_property_from_str("valignment", v);
+    element.set_property("shaded-background", true);
+    element.set_property("font-desc", WIDGET_FONT);
+    Ok(element)
+}
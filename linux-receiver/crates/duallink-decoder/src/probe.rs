@@ -0,0 +1,87 @@
+//! [`DecoderFactory::probe_report`]: what decoding would use on this
+//! machine, without building a pipeline.
+//!
+//! The probe initialises GStreamer, looks up every known decoder element,
+//! asks `vainfo` for the VA-API driver when GStreamer has a VA-API plugin,
+//! and picks the elements a new session would use — honouring the
+//! factory's preference and cached benchmark like
+//! [`DecoderFactory::decoder_for`], but without its logging.
+
+use duallink_core::{DecoderBenchmarks, DecoderEntry, ProbeReport, VaapiInfo, VideoCodec};
+use gstreamer as gst;
+
+use crate::{candidates, DecoderFactory, DECODER_PRIORITY, HEVC_DECODER_PRIORITY};
+
+/// GStreamer plugins whose decoders go through VA-API.
+const VA_PLUGINS: &[&str] = &["va", "vaapi"];
+
+impl DecoderFactory {
+    /// Probe on a blocking thread — GStreamer's first initialisation scans
+    /// the plugin registry and `vainfo` talks to the GPU, both slow enough
+    /// to stall a runtime worker. See [`probe_report_blocking`](Self::probe_report_blocking).
+    pub async fn probe_report(&self) -> ProbeReport {
+        let factory = self.clone();
+        tokio::task::spawn_blocking(move || factory.probe_report_blocking())
+            .await
+            .unwrap_or_else(|e| ProbeReport { gstreamer_error: Some(e.to_string()), ..ProbeReport::default() })
+    }
+
+    /// Every known decoder, installed or not, the GStreamer version and
+    /// VA-API driver, and the decoders this factory would pick.
+    pub fn probe_report_blocking(&self) -> ProbeReport {
+        let mut report = ProbeReport::default();
+        match gst::init() {
+            Ok(()) => report.gstreamer = Some(gst::version_string().to_string()),
+            Err(e) => report.gstreamer_error = Some(e.to_string()),
+        }
+        let bench = DecoderBenchmarks::load();
+        for (codec, name) in [(VideoCodec::H264, "h264"), (VideoCodec::H265, "h265")] {
+            report.candidates.extend(candidates(codec).into_iter().map(|c| DecoderEntry {
+                codec:        name.to_owned(),
+                element:      c.element.to_owned(),
+                label:        c.label.to_owned(),
+                installed:    c.installed,
+                benchmark_ms: bench.as_ref().and_then(|b| b.latency(c.element)).map(|d| d.as_secs_f64() * 1e3),
+            }));
+        }
+
+        if report.gstreamer.is_some() {
+            let registry = gst::Registry::get();
+            if VA_PLUGINS.iter().any(|plugin| registry.find_plugin(plugin).is_some()) {
+                report.va_driver = VaapiInfo::collect().driver;
+            }
+        }
+
+        let usable: Vec<&str> = report
+            .installed()
+            .map(|c| c.element.as_str())
+            .filter(|e| !self.excluded.iter().any(|x| x == e))
+            .collect();
+        // The first usable preferred element of `list`, else its first
+        // usable one in measured order.
+        let pick = |list: &[(&'static str, &'static str)], bench: Option<&DecoderBenchmarks>| {
+            let known = |e: &str| list.iter().any(|(element, _)| *element == e) && usable.iter().any(|u| *u == e);
+            if let Some(preferred) = self.preference.iter().find(|p| known(p.as_str())) {
+                return Some(preferred.clone());
+            }
+            let mut order = list.to_vec();
+            if let Some(bench) = bench {
+                bench.rank(&mut order, |e| e.0);
+            }
+            order.iter().map(|(e, _)| *e).find(|&e| known(e)).map(str::to_owned)
+        };
+        let recommended = pick(DECODER_PRIORITY, bench.as_ref());
+        let recommended_hevc = pick(HEVC_DECODER_PRIORITY, None);
+        report.recommended = recommended;
+        report.recommended_hevc = recommended_hevc;
+
+        #[cfg(feature = "software")]
+        {
+            let preferred = self.preference.first().is_some_and(|e| e == crate::SOFTWARE_DECODER);
+            if preferred || report.gstreamer.is_none() {
+                report.recommended = Some(crate::SOFTWARE_DECODER.to_owned());
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,123 @@
+//! Decode and display without GStreamer (feature `software`).
+//!
+//! [`SoftwareDisplayDecoder`] decodes H.264 with OpenH264 on the decode
+//! thread and hands the frames to a [`WgpuRenderer`] window.
+//! [`DecoderFactory`](crate::DecoderFactory) opens it when `gst::init()`
+//! fails, or when [`SOFTWARE_DECODER`] heads the decoder preference
+//! (`DUALLINK_DECODER=openh264`).
+//!
+//! It handles 8-bit 4:2:0 H.264 only — HEVC / HDR and lossless (4:4:4)
+//! streams still need GStreamer — and its window forwards no input or
+//! hotkeys.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use bytes::Bytes;
+use duallink_core::{
+    errors::DecoderError, DecodedFrame, EncodedFrame, InputEvent, PixelFormat, StreamConfig, VideoCodec,
+};
+use duallink_renderer::WgpuRenderer;
+use openh264::decoder::Decoder;
+use openh264::formats::YUVSource;
+use tracing::info;
+
+use crate::DisplayOutput;
+
+/// Name of the software decoder in decoder preferences and the stats card.
+pub const SOFTWARE_DECODER: &str = "openh264";
+
+/// OpenH264 decode into a wgpu window.
+pub struct SoftwareDisplayDecoder {
+    decoder:  Mutex<Decoder>,
+    renderer: WgpuRenderer,
+    pushed:   AtomicU64,
+    frozen:   AtomicBool,
+    blanked:  AtomicBool,
+}
+
+impl SoftwareDisplayDecoder {
+    /// Open the decoder and its window for `stream`.
+    pub fn new(stream: &StreamConfig) -> Result<Self, DecoderError> {
+        if stream.codec != VideoCodec::H264 || stream.lossless || stream.hdr.is_some() {
+            return Err(DecoderError::Software(format!(
+                "{SOFTWARE_DECODER} decodes 4:2:0 H.264 only (stream: {:?}, lossless {})",
+                stream.codec, stream.lossless
+            )));
+        }
+        let decoder = Decoder::new().map_err(|e| DecoderError::Software(format!("{SOFTWARE_DECODER}: {e}")))?;
+        let mut renderer = WgpuRenderer::new();
+        renderer.open("DualLink").map_err(|e| DecoderError::Software(e.to_string()))?;
+        info!(
+            "SoftwareDisplayDecoder({}) ready {}x{}",
+            SOFTWARE_DECODER, stream.resolution.width, stream.resolution.height
+        );
+        Ok(Self {
+            decoder: Mutex::new(decoder),
+            renderer,
+            pushed: AtomicU64::new(0),
+            frozen: AtomicBool::new(false),
+            blanked: AtomicBool::new(false),
+        })
+    }
+
+    /// Decode `frame` and show the picture, if it completes one.
+    pub fn push_frame(&self, frame: EncodedFrame) -> Result<(), DecoderError> {
+        if !frame.partial {
+            self.pushed.fetch_add(1, Ordering::Relaxed);
+        }
+        let mut decoder = self.decoder.lock().unwrap();
+        let decoded = decoder
+            .decode(&frame.data)
+            .map_err(|e| DecoderError::DecodeFailed { reason: format!("{SOFTWARE_DECODER}: {e}") })?;
+        let Some(yuv) = decoded else { return Ok(()) };
+        if self.frozen.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let (width, height) = yuv.dimensions();
+        let mut rgba = vec![0u8; width * height * 4];
+        if !self.blanked.load(Ordering::Relaxed) {
+            yuv.write_rgba8(&mut rgba);
+        }
+        self.renderer
+            .show(DecodedFrame {
+                data:         Bytes::from(rgba),
+                width:        width as u32,
+                height:       height as u32,
+                timestamp_us: frame.timestamp_us,
+                format:       PixelFormat::Rgba,
+            })
+            .map_err(|e| DecoderError::Software(e.to_string()))
+    }
+}
+
+impl DisplayOutput for SoftwareDisplayDecoder {
+    fn push_frame(&self, frame: EncodedFrame) -> Result<(), DecoderError> {
+        SoftwareDisplayDecoder::push_frame(self, frame)
+    }
+    fn frames_pushed(&self) -> u64 {
+        self.pushed.load(Ordering::Relaxed)
+    }
+    fn poll_input_events(&self) -> Vec<InputEvent> {
+        Vec::new()
+    }
+    fn set_input_enabled(&self, _enabled: bool) {}
+    fn set_frozen(&self, frozen: bool) {
+        self.frozen.store(frozen, Ordering::Relaxed);
+    }
+    fn is_frozen(&self) -> bool {
+        self.frozen.load(Ordering::Relaxed)
+    }
+    fn set_blanked(&self, blanked: bool) {
+        self.blanked.store(blanked, Ordering::Relaxed);
+    }
+    fn is_blanked(&self) -> bool {
+        self.blanked.load(Ordering::Relaxed)
+    }
+    fn element_name(&self) -> &str {
+        SOFTWARE_DECODER
+    }
+    fn is_hardware_accelerated(&self) -> bool {
+        false
+    }
+}
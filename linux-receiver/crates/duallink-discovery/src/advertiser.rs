@@ -9,21 +9,23 @@
 //! |-----------|----------------------------------------------|
 //! | `version` | Protocol version (`"1"`)                     |
 //! | `displays` | Number of display channels being served     |
-//! | `port`    | Base TCP signaling port (default `"7879"`)   |
+//! | `port`    | TCP signaling port of display 0 (`"7879"`)   |
+//! | `ports`   | Per-display ports, `0=7878/7879,1=7880/7881` |
 //! | `host`    | Advertised LAN IP address                    |
 //! | `fp`      | First 16 hex chars of the TLS fingerprint    |
+//! | `mac`     | MAC of the advertised interface (Wake-on-LAN)|
 //!
 //! # Usage
 //!
 //! ```rust,no_run
+//! use duallink_core::PortMap;
 //! use duallink_discovery::DualLinkAdvertiser;
 //! use std::net::IpAddr;
 //!
 //! let ip: IpAddr = "192.168.1.42".parse().unwrap();
 //! let adv = DualLinkAdvertiser::register(
 //!     "DualLink Receiver",
-//!     1,          // display count
-//!     7879,       // base signaling port
+//!     &PortMap::contiguous(7878, 1), // one display on the default ports
 //!     ip,
 //!     "AABBCCDDEE112233", // short TLS fingerprint
 //! ).expect("mDNS advertising failed");
@@ -36,6 +38,7 @@ use std::collections::HashMap;
 use std::net::IpAddr;
 
 use anyhow::Result;
+use duallink_core::PortMap;
 use mdns_sd::{ServiceDaemon, ServiceInfo};
 use tracing::{info, warn};
 
@@ -45,6 +48,8 @@ pub const SERVICE_TYPE: &str = "_duallink._tcp.local.";
 pub struct DualLinkAdvertiser {
     daemon:   ServiceDaemon,
     fullname: String,
+    /// Kept so the record can be re-announced with new TXT values.
+    service:  ServiceInfo,
 }
 
 impl DualLinkAdvertiser {
@@ -53,14 +58,14 @@ impl DualLinkAdvertiser {
     /// # Arguments
     /// - `instance_name` — human-readable instance name
     ///   (visible in sender discovery lists, e.g. `"DualLink Receiver"`)
-    /// - `display_count` — number of display channels being served
-    /// - `base_port` — TCP signaling port for display 0 (usually `7879`)
+    /// - `ports` — port pairs of the displays being served (e.g.
+    ///   `DualLinkReceiver::port_map()`); the service port is display 0's
+    ///   signaling port
     /// - `host_ip` — local LAN IP address to advertise
     /// - `fingerprint` — TLS certificate fingerprint (colon-separated SHA-256 hex)
     pub fn register(
         instance_name: &str,
-        display_count: u8,
-        base_port: u16,
+        ports: &PortMap,
         host_ip: IpAddr,
         fingerprint: &str,
     ) -> Result<Self> {
@@ -80,12 +85,16 @@ impl DualLinkAdvertiser {
             .take(16)
             .collect();
 
+        let base_port = ports.signaling_port(0);
         let mut properties = HashMap::new();
         properties.insert("version".to_owned(),  "1".to_owned());
-        properties.insert("displays".to_owned(), display_count.to_string());
-        properties.insert("port".to_owned(),     base_port.to_string());
+        insert_ports(&mut properties, ports);
         properties.insert("host".to_owned(),     host_ip.to_string());
         properties.insert("fp".to_owned(),       fp_short);
+        // MAC address lets senders wake this machine with a WoL magic packet
+        if let Some(mac) = detect_mac_address(host_ip) {
+            properties.insert("mac".to_owned(), mac);
+        }
 
         let service = ServiceInfo::new(
             SERVICE_TYPE,
@@ -97,14 +106,40 @@ impl DualLinkAdvertiser {
         )?;
 
         let fullname = service.get_fullname().to_owned();
-        daemon.register(service)?;
+        daemon.register(service.clone())?;
 
         info!(
-            "[mDNS] Advertising '{}' at {}:{} (displays={})",
-            instance_name, host_ip, base_port, display_count
+            "[mDNS] Advertising '{}' at {}:{} (ports={})",
+            instance_name, host_ip, base_port, ports.to_txt()
         );
 
-        Ok(Self { daemon, fullname })
+        Ok(Self { daemon, fullname, service })
+    }
+
+    /// Re-announce the service with updated `displays` / `ports` TXT values
+    /// after displays were added or removed at runtime.
+    pub fn set_ports(&mut self, ports: &PortMap) -> Result<()> {
+        let mut properties: HashMap<String, String> = self
+            .service
+            .get_properties()
+            .iter()
+            .map(|p| (p.key().to_owned(), p.val_str().to_owned()))
+            .collect();
+        insert_ports(&mut properties, ports);
+
+        let addrs: Vec<IpAddr> = self.service.get_addresses().iter().copied().collect();
+        let service = ServiceInfo::new(
+            SERVICE_TYPE,
+            self.service.get_fullname().trim_end_matches(&format!(".{SERVICE_TYPE}")),
+            self.service.get_hostname(),
+            &addrs[..],
+            self.service.get_port(),
+            Some(properties),
+        )?;
+        self.daemon.register(service.clone())?;
+        self.service = service;
+        info!("[mDNS] Updated '{}' (ports={})", self.fullname, ports.to_txt());
+        Ok(())
     }
 
     /// Remove the mDNS advertisement.
@@ -117,6 +152,13 @@ impl DualLinkAdvertiser {
     }
 }
 
+/// `displays`, `port` and `ports` TXT values for `ports`.
+fn insert_ports(properties: &mut HashMap<String, String>, ports: &PortMap) {
+    properties.insert("displays".to_owned(), ports.len().to_string());
+    properties.insert("port".to_owned(),     ports.signaling_port(0).to_string());
+    properties.insert("ports".to_owned(),    ports.to_txt());
+}
+
 // ── Local IP detection ────────────────────────────────────────────────────────
 
 /// Detect the primary LAN IPv4 address by probing an external socket.
@@ -128,3 +170,40 @@ pub fn detect_local_ip() -> IpAddr {
         .map(|a| a.ip())
         .unwrap_or_else(|_| IpAddr::V4(std::net::Ipv4Addr::new(127, 0, 0, 1)))
 }
+
+/// Return the MAC address (`aa:bb:cc:dd:ee:ff`) of the interface that owns `ip`.
+///
+/// - **Linux:** matches `ip -4 -o addr show` output, then reads
+///   `/sys/class/net/<iface>/address`.
+/// - **Other platforms:** not yet implemented — returns `None`.
+#[cfg(target_os = "linux")]
+pub fn detect_mac_address(ip: IpAddr) -> Option<String> {
+    let output = std::process::Command::new("ip")
+        .args(["-4", "-o", "addr", "show"])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // Format: "2: wlp3s0    inet 192.168.1.42/24 brd 192.168.1.255 scope global wlp3s0"
+    let ip_str = ip.to_string();
+    let iface = stdout.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        let _index = words.next()?;
+        let name = words.next()?;
+        let addr = words.skip_while(|w| *w != "inet").nth(1)?;
+        (addr.split('/').next()? == ip_str).then(|| name.to_owned())
+    })?;
+
+    let mac = std::fs::read_to_string(format!("/sys/class/net/{iface}/address")).ok()?;
+    let mac = mac.trim().to_ascii_lowercase();
+    if mac.is_empty() || mac == "00:00:00:00:00:00" {
+        return None;
+    }
+    Some(mac)
+}
+
+/// Non-Linux stub — MAC detection not yet implemented on this OS.
+#[cfg(not(target_os = "linux"))]
+pub fn detect_mac_address(_ip: IpAddr) -> Option<String> {
+    None
+}
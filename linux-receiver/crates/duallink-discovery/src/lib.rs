@@ -1,5 +1,5 @@
 mod advertiser;
-pub use advertiser::{DualLinkAdvertiser, detect_local_ip};
+pub use advertiser::{DualLinkAdvertiser, detect_local_ip, detect_mac_address};
 
 use duallink_core::PeerInfo;
 use mdns_sd::{ServiceDaemon, ServiceEvent};
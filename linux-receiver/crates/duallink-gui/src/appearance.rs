@@ -0,0 +1,87 @@
+//! Theme, UI scale and window geometry of the GUI, kept in
+//! [`AppearanceSettings`] shared with the sender UIs.
+
+use duallink_core::{AppearanceSettings, Theme, WindowGeometry, UI_SCALES};
+
+use crate::strings::t;
+
+/// Key of this UI's window in [`AppearanceSettings::windows`].
+pub const WINDOW_KEY: &str = "receiver";
+
+/// Apply the saved theme and scale to `ctx`.
+pub fn apply(ctx: &egui::Context, settings: &AppearanceSettings) {
+    ctx.set_theme(match settings.theme {
+        Theme::Dark => egui::ThemePreference::Dark,
+        Theme::Light => egui::ThemePreference::Light,
+        Theme::System => egui::ThemePreference::System,
+    });
+    ctx.set_zoom_factor(settings.scale());
+}
+
+/// `viewport` at the size and place the window last closed at.
+pub fn restore_geometry(viewport: egui::ViewportBuilder, settings: &AppearanceSettings) -> egui::ViewportBuilder {
+    let Some(geometry) = settings.window(WINDOW_KEY) else { return viewport };
+    let viewport = viewport.with_inner_size(geometry.size);
+    match geometry.position {
+        Some(position) => viewport.with_position(position),
+        None => viewport,
+    }
+}
+
+/// The window's current geometry in logical pixels, for saving on exit.
+pub fn current_geometry(ctx: &egui::Context) -> Option<WindowGeometry> {
+    // Viewport rects are in points, which the zoom factor scales.
+    let zoom = ctx.zoom_factor();
+    ctx.input(|i| {
+        let viewport = i.viewport();
+        let inner = viewport.inner_rect?;
+        Some(WindowGeometry {
+            position: viewport.outer_rect.map(|r| [r.min.x * zoom, r.min.y * zoom]),
+            size:     [inner.width() * zoom, inner.height() * zoom],
+        })
+    })
+}
+
+/// Theme and scale pickers; changes apply at once and are saved. Returns
+/// the error of a failed save.
+pub fn pickers(ui: &mut egui::Ui, settings: &mut AppearanceSettings) -> Option<std::io::Error> {
+    let before = (settings.theme, settings.ui_scale);
+    egui::ComboBox::from_id_salt("ui_scale")
+        .selected_text(format!("{:.0}%", settings.scale() * 100.0))
+        .width(64.0)
+        .show_ui(ui, |ui| {
+            for scale in UI_SCALES {
+                ui.selectable_value(&mut settings.ui_scale, scale, format!("{:.0}%", scale * 100.0));
+            }
+        })
+        .response
+        .on_hover_text(t("appearance.scale"));
+    egui::ComboBox::from_id_salt("theme")
+        .selected_text(theme_label(settings.theme))
+        .width(84.0)
+        .show_ui(ui, |ui| {
+            for theme in Theme::ALL {
+                ui.selectable_value(&mut settings.theme, theme, theme_label(theme));
+            }
+        })
+        .response
+        .on_hover_text(t("appearance.theme"));
+
+    if (settings.theme, settings.ui_scale) == before {
+        return None;
+    }
+    apply(ui.ctx(), settings);
+    // Geometry is written by the UIs on exit; keep theirs.
+    let mut saved = AppearanceSettings::load();
+    saved.theme = settings.theme;
+    saved.ui_scale = settings.ui_scale;
+    saved.save().err()
+}
+
+fn theme_label(theme: Theme) -> &'static str {
+    t(match theme {
+        Theme::Dark => "appearance.dark",
+        Theme::Light => "appearance.light",
+        Theme::System => "appearance.system",
+    })
+}
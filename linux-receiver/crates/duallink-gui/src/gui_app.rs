@@ -1,18 +1,80 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
 use egui::{
     Align, Color32, FontFamily, FontId, Frame, Layout, Margin, RichText,
     ScrollArea, Stroke, Vec2,
 };
 
-use crate::state::{Phase, SharedState};
+use duallink_core::locale::language;
+use duallink_core::{
+    set_language, AppearanceSettings, FileOffer, FileTransferEvent, FirewallCheck, Language, NetworkKind, PowerState,
+    ReceiverSettings, SequenceStats, WindowGeometry,
+};
+
+use crate::appearance;
+use crate::quality::{Quality, QualityInput, QualityTracker};
+use crate::receiver;
+use crate::state::{DecoderOption, DisplayAction, DisplayRequest, FileRequest, MacroRequest, Phase, SharedState};
+use crate::strings::{t, tf};
 
 // ── Colours ───────────────────────────────────────────────────────────────────
 
-const BG_PANEL:  Color32 = Color32::from_rgb(28,  30,  36);
-const BG_INSET:  Color32 = Color32::from_rgb(20,  22,  28);
-const BG_CARD:   Color32 = Color32::from_rgb(36,  38,  46);
-const ACCENT:    Color32 = Color32::from_rgb(99, 144, 255);
-const TEXT_DIM:  Color32 = Color32::from_rgb(130, 135, 148);
-const TEXT_NORM: Color32 = Color32::from_rgb(210, 215, 230);
+/// Colours of one theme; status colours (green, amber, red) are shared.
+struct Palette {
+    panel:        Color32,
+    inset:        Color32,
+    card:         Color32,
+    accent:       Color32,
+    text_dim:     Color32,
+    text_norm:    Color32,
+    text_strong:  Color32,
+    border:       Color32,
+    border_faint: Color32,
+    hovered:      Color32,
+    active:       Color32,
+    log_info:     Color32,
+}
+
+const DARK: Palette = Palette {
+    panel:        Color32::from_rgb(28,  30,  36),
+    inset:        Color32::from_rgb(20,  22,  28),
+    card:         Color32::from_rgb(36,  38,  46),
+    accent:       Color32::from_rgb(99, 144, 255),
+    text_dim:     Color32::from_rgb(130, 135, 148),
+    text_norm:    Color32::from_rgb(210, 215, 230),
+    text_strong:  Color32::WHITE,
+    border:       Color32::from_rgb(60,  65,  80),
+    border_faint: Color32::from_rgb(50,  53,  68),
+    hovered:      Color32::from_rgb(50,  53,  65),
+    active:       Color32::from_rgb(65,  68,  82),
+    log_info:     Color32::from_rgb(160, 170, 185),
+};
+
+/// High-contrast light colours, readable on projectors.
+const LIGHT: Palette = Palette {
+    panel:        Color32::from_rgb(238, 240, 244),
+    inset:        Color32::from_rgb(250, 251, 253),
+    card:         Color32::from_rgb(226, 229, 236),
+    accent:       Color32::from_rgb(30,  80, 200),
+    text_dim:     Color32::from_rgb(85,  90, 105),
+    text_norm:    Color32::from_rgb(30,  34,  46),
+    text_strong:  Color32::BLACK,
+    border:       Color32::from_rgb(175, 181, 196),
+    border_faint: Color32::from_rgb(200, 204, 216),
+    hovered:      Color32::from_rgb(212, 216, 226),
+    active:       Color32::from_rgb(196, 201, 214),
+    log_info:     Color32::from_rgb(60,  68,  82),
+};
+
+/// Palette of the theme in effect.
+fn palette(ctx: &egui::Context) -> &'static Palette {
+    match ctx.theme() {
+        egui::Theme::Dark => &DARK,
+        egui::Theme::Light => &LIGHT,
+    }
+}
 
 // ── App struct ────────────────────────────────────────────────────────────────
 
@@ -21,38 +83,50 @@ pub struct DualLinkApp {
     show_fingerprint:   bool,
     auto_scroll_logs:   bool,
     copied_pin_frames:  u8,  // countdown for "Copied!" flash
+    appearance:         AppearanceSettings,
+    /// Window geometry as of the last frame, saved on exit.
+    geometry:           Option<WindowGeometry>,
+    quality:            QualityTracker,
 }
 
 impl DualLinkApp {
     pub fn new(cc: &eframe::CreationContext<'_>, state: SharedState) -> Self {
-        // Apply dark visuals with custom colours
-        let mut visuals = egui::Visuals::dark();
-        visuals.window_fill             = BG_PANEL;
-        visuals.panel_fill              = BG_PANEL;
-        visuals.extreme_bg_color        = BG_INSET;
-        visuals.faint_bg_color          = BG_CARD;
-        visuals.widgets.inactive.bg_fill  = BG_CARD;
-        visuals.widgets.hovered.bg_fill   = Color32::from_rgb(50, 53, 65);
-        visuals.widgets.active.bg_fill    = Color32::from_rgb(65, 68, 82);
-        cc.egui_ctx.set_visuals(visuals);
+        // Apply dark and light visuals with custom colours
+        for (theme, pal) in [(egui::Theme::Dark, &DARK), (egui::Theme::Light, &LIGHT)] {
+            let mut visuals = theme.default_visuals();
+            visuals.window_fill             = pal.panel;
+            visuals.panel_fill              = pal.panel;
+            visuals.extreme_bg_color        = pal.inset;
+            visuals.faint_bg_color          = pal.card;
+            visuals.widgets.inactive.bg_fill  = pal.card;
+            visuals.widgets.hovered.bg_fill   = pal.hovered;
+            visuals.widgets.active.bg_fill    = pal.active;
+            cc.egui_ctx.set_visuals_of(theme, visuals);
+        }
 
         // Slightly larger default font
-        let mut style = (*cc.egui_ctx.style()).clone();
-        style.text_styles.insert(
-            egui::TextStyle::Body,
-            FontId::new(14.0, FontFamily::Proportional),
-        );
-        style.text_styles.insert(
-            egui::TextStyle::Button,
-            FontId::new(13.5, FontFamily::Proportional),
-        );
-        cc.egui_ctx.set_style(style);
+        cc.egui_ctx.all_styles_mut(|style| {
+            style.text_styles.insert(
+                egui::TextStyle::Body,
+                FontId::new(14.0, FontFamily::Proportional),
+            );
+            style.text_styles.insert(
+                egui::TextStyle::Button,
+                FontId::new(13.5, FontFamily::Proportional),
+            );
+        });
+
+        let appearance = AppearanceSettings::load();
+        appearance::apply(&cc.egui_ctx, &appearance);
 
         Self {
             state,
             show_fingerprint:  false,
             auto_scroll_logs:  true,
             copied_pin_frames: 0,
+            appearance,
+            geometry:          None,
+            quality:           QualityTracker::default(),
         }
     }
 }
@@ -61,6 +135,11 @@ impl DualLinkApp {
 
 impl eframe::App for DualLinkApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let pal = palette(ctx);
+        if let Some(geometry) = appearance::current_geometry(ctx) {
+            self.geometry = Some(geometry);
+        }
+
         // Decrement "Copied!" flash countdown
         if self.copied_pin_frames > 0 {
             self.copied_pin_frames -= 1;
@@ -69,25 +148,85 @@ impl eframe::App for DualLinkApp {
 
         // Snapshot state to avoid holding the lock across rendering
         let snap = {
-            let s = self.state.lock().unwrap();
+            let mut s = self.state.lock().unwrap();
+            s.logs.flush(std::time::Instant::now());
             StateSnapshot {
                 phase:           s.phase.clone(),
                 pairing_pin:     s.pairing_pin.clone(),
                 tls_fingerprint: s.tls_fingerprint.clone(),
                 fps:             s.fps,
+                unique_fps:      s.unique_fps,
+                duplicates:      s.duplicates,
+                reassembly_drops: s.reassembly_drops,
+                queueing_delay:  s.queueing_delay,
+                decode_errors:   s.decode_errors,
                 frames_received: s.frames_received,
                 frames_decoded:  s.frames_decoded,
                 bitrate_mbps:    s.bitrate_mbps,
+                frame_stats:     s.frame_stats,
                 transport:       s.transport.clone(),
+                network:         s.network,
                 logs:            s.logs.iter().cloned().collect::<Vec<_>>(),
                 lan_ip:          s.lan_ip.clone(),
                 mdns_active:     s.mdns_active,
                 display_count:   s.display_count,
+                displays:        std::iter::once(DisplaySnapshot {
+                    index:           0,
+                    phase:           s.phase.clone(),
+                    fps:             s.fps,
+                    unique_fps:      s.unique_fps,
+                    frames_received: s.frames_received,
+                    frames_decoded:  s.frames_decoded,
+                    decoder:         s.decoder.clone(),
+                    frame_stats:     s.frame_stats,
+                    frozen:          s.frozen,
+                    blanked:         s.blanked,
+                    paused:          s.paused,
+                    system_keys:     s.system_keys,
+                    stalled:         s.stalled,
+                    pin:             s.display_pins.get(&0).cloned(),
+                })
+                .chain(s.displays.iter().map(|(&index, d)| DisplaySnapshot {
+                    index,
+                    phase:           d.phase.clone(),
+                    fps:             d.fps,
+                    unique_fps:      d.unique_fps,
+                    frames_received: d.frames_received,
+                    frames_decoded:  d.frames_decoded,
+                    decoder:         d.decoder.clone(),
+                    frame_stats:     d.frame_stats,
+                    frozen:          d.frozen,
+                    blanked:         d.blanked,
+                    paused:          d.paused,
+                    system_keys:     d.system_keys,
+                    stalled:         d.stalled,
+                    pin:             s.display_pins.get(&index).cloned(),
+                }))
+                .collect(),
+                decoder_options: s.decoder_options.clone(),
+                benchmarking:    s.benchmarking,
+                decoder_preference: s.decoder_preference.first().cloned(),
+                allow_input:     s.allow_input,
+                view_only_session: s.view_only_session,
+                macro_recording: s.macro_recording,
+                macro_replaying: s.macro_replaying,
+                power:           s.power,
+                sender_power:    s.sender_power,
+                firewall:        s.firewall.clone(),
+                firewall_opening: s.firewall_opening,
+                file_offers:     s.file_offers.clone(),
+                transfers:       s.transfers.clone(),
             }
         };
 
+        let dropped: Vec<PathBuf> =
+            ctx.input(|i| i.raw.dropped_files.iter().filter_map(|f| f.path.clone()).collect());
+        if !dropped.is_empty() {
+            self.send_files(dropped, &snap.displays);
+        }
+
         egui::CentralPanel::default()
-            .frame(Frame::none().fill(BG_PANEL))
+            .frame(Frame::none().fill(pal.panel))
             .show(ctx, |ui| {
                 ui.set_min_size(Vec2::new(540.0, 640.0));
 
@@ -96,9 +235,15 @@ impl eframe::App for DualLinkApp {
                 ui.add_space(10.0);
 
                 // ── Status card ───────────────────────────────────────────
-                render_status_card(ui, &snap);
+                self.render_status_card(ui, &snap);
                 ui.add_space(10.0);
 
+                // ── Firewall prompt ───────────────────────────────────────
+                if let Some(check) = &snap.firewall {
+                    self.render_firewall_card(ui, ctx, check, snap.firewall_opening);
+                    ui.add_space(10.0);
+                }
+
                 // ── PIN card (shown when not yet streaming) ───────────────
                 let show_pin = !snap.pairing_pin.is_empty()
                     && !matches!(snap.phase, Phase::Error(_));
@@ -113,7 +258,43 @@ impl eframe::App for DualLinkApp {
 
                 // ── Streaming stats card ──────────────────────────────────
                 if matches!(snap.phase, Phase::Streaming { .. }) {
-                    render_stats_card(ui, &snap);
+                    let quality = self.quality.update(&QualityInput {
+                        frame_stats:      snap.frame_stats,
+                        reassembly_drops: snap.reassembly_drops,
+                        decode_errors:    snap.decode_errors,
+                        queueing_delay:   snap.queueing_delay,
+                        fps:              snap.fps,
+                        decoder:          snap.decoder.as_deref(),
+                        network:          snap.network,
+                    });
+                    render_stats_card(ui, &snap, &quality);
+                    ui.add_space(10.0);
+                } else {
+                    self.quality.reset();
+                }
+
+                // ── Per-display cards (multi-display only) ────────────────
+                if snap.displays.len() > 1 {
+                    self.render_displays_card(ui, &snap.displays);
+                    ui.add_space(10.0);
+                }
+
+                // ── File transfers ────────────────────────────────────────
+                let connected = snap.displays.iter().any(|d| d.phase.peer_name().is_some());
+                if connected || !snap.file_offers.is_empty() || !snap.transfers.is_empty() {
+                    self.render_files_card(ui, &snap);
+                    ui.add_space(10.0);
+                }
+
+                // ── Decoder picker ────────────────────────────────────────
+                if !snap.decoder_options.is_empty() {
+                    self.render_decoder_picker(ui, &snap);
+                    ui.add_space(10.0);
+                }
+
+                // ── Input macro ───────────────────────────────────────────
+                if snap.allow_input {
+                    self.render_macro_card(ui, &snap);
                     ui.add_space(10.0);
                 }
 
@@ -127,24 +308,43 @@ impl eframe::App for DualLinkApp {
                         .add_sized(
                             [110.0, 30.0],
                             egui::Button::new(
-                                RichText::new("Quit DualLink")
+                                RichText::new(t("app.quit"))
                                     .color(Color32::from_rgb(220, 80, 70)),
                             )
-                            .fill(BG_CARD)
+                            .fill(pal.card)
                             .stroke(Stroke::new(1.0, Color32::from_rgb(180, 60, 55))),
                         )
                         .clicked()
                     {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                     }
+                    self.render_language_picker(ui);
+                    if ui.button(t("app.export_diagnostics")).on_hover_text(t("app.export_diagnostics_hint")).clicked() {
+                        let state = Arc::clone(&self.state);
+                        let ctx = ctx.clone();
+                        std::thread::spawn(move || receiver::export_diagnostics(state, ctx));
+                    }
+                    if let Some(e) = appearance::pickers(ui, &mut self.appearance) {
+                        let line = tf("log.save_failed", &[("what", &t("log.appearance_setting")), ("error", &e)]);
+                        self.state.lock().unwrap().push_log(line);
+                    }
                 });
             });
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Some(geometry) = self.geometry {
+            if let Err(e) = AppearanceSettings::save_window(appearance::WINDOW_KEY, geometry) {
+                tracing::warn!("Saving window geometry: {e}");
+            }
+        }
+    }
 }
 
 // ── Rendering helpers ─────────────────────────────────────────────────────────
 
 fn render_header(ui: &mut egui::Ui, snap: &StateSnapshot) {
+    let pal = palette(ui.ctx());
     ui.horizontal(|ui| {
         ui.add_space(6.0);
         // App name
@@ -152,12 +352,12 @@ fn render_header(ui: &mut egui::Ui, snap: &StateSnapshot) {
             RichText::new("DualLink")
                 .font(FontId::new(26.0, FontFamily::Proportional))
                 .strong()
-                .color(Color32::WHITE),
+                .color(pal.text_strong),
         );
         ui.label(
-            RichText::new("Receiver")
+            RichText::new(t("app.receiver"))
                 .font(FontId::new(26.0, FontFamily::Proportional))
-                .color(ACCENT),
+                .color(pal.accent),
         );
 
         ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
@@ -166,8 +366,17 @@ fn render_header(ui: &mut egui::Ui, snap: &StateSnapshot) {
             ui.label(
                 RichText::new(&snap.transport)
                     .font(FontId::new(11.5, FontFamily::Proportional))
-                    .color(TEXT_DIM),
+                    .color(pal.text_dim),
             );
+            // Power badge (machines with a battery)
+            if let Some(power) = snap.power {
+                ui.label(
+                    RichText::new(format!("🔋 {power}"))
+                        .font(FontId::new(11.5, FontFamily::Proportional))
+                        .color(if power.saver { Color32::from_rgb(230, 185, 50) } else { pal.text_dim }),
+                )
+                .on_hover_text(t("app.saver_hint"));
+            }
         });
     });
 
@@ -176,62 +385,93 @@ fn render_header(ui: &mut egui::Ui, snap: &StateSnapshot) {
     let y    = ui.cursor().top();
     ui.painter().line_segment(
         [egui::pos2(rect.left() + 6.0, y), egui::pos2(rect.right() - 6.0, y)],
-        Stroke::new(1.0, Color32::from_rgb(55, 58, 74)),
+        Stroke::new(1.0, pal.border),
     );
     ui.add_space(4.0);
 }
 
-fn render_status_card(ui: &mut egui::Ui, snap: &StateSnapshot) {
-    card(ui, |ui| {
-        ui.horizontal(|ui| {
-            // Coloured status dot
-            let (rect, _) = ui.allocate_exact_size(Vec2::splat(12.0), egui::Sense::hover());
-            ui.painter()
-                .circle_filled(rect.center(), 5.0, snap.phase.color());
-
-            ui.label(
-                RichText::new(snap.phase.label())
-                    .strong()
-                    .color(TEXT_NORM),
-            );
+impl DualLinkApp {
+    fn render_status_card(&mut self, ui: &mut egui::Ui, snap: &StateSnapshot) {
+        let pal = palette(ui.ctx());
+        let mut allow_input = snap.allow_input;
+        card(ui, |ui| {
+            ui.horizontal(|ui| {
+                // Coloured status dot
+                let (rect, _) = ui.allocate_exact_size(Vec2::splat(12.0), egui::Sense::hover());
+                ui.painter()
+                    .circle_filled(rect.center(), 5.0, snap.phase.color());
 
-            // Extra peer info
-            if let Some(name) = snap.phase.peer_name() {
-                ui.label(RichText::new("—").color(TEXT_DIM));
                 ui.label(
-                    RichText::new(name)
-                        .color(Color32::WHITE)
-                        .strong(),
+                    RichText::new(snap.phase.label())
+                        .strong()
+                        .color(pal.text_norm),
                 );
-                if let Some(addr) = snap.phase.peer_addr() {
+
+                // Extra peer info
+                if let Some(name) = snap.phase.peer_name() {
+                    ui.label(RichText::new("—").color(pal.text_dim));
+                    ui.label(
+                        RichText::new(name)
+                            .color(pal.text_strong)
+                            .strong(),
+                    );
+                    if let Some(addr) = snap.phase.peer_addr() {
+                        ui.label(
+                            RichText::new(format!("({})", addr))
+                                .color(pal.text_dim)
+                                .font(FontId::new(12.0, FontFamily::Proportional)),
+                        );
+                    }
+                    if snap.view_only_session {
+                        ui.label(RichText::new("🔒").color(pal.text_dim))
+                            .on_hover_text(t("status.view_only"));
+                    }
+                    if let Some(power) = snap.sender_power.filter(|p| p.on_battery) {
+                        let color = if power.saver { Color32::from_rgb(230, 185, 50) } else { pal.text_dim };
+                        ui.label(RichText::new("🔋").color(color)).on_hover_text(tf("status.sender_power", &[("power", &power)]));
+                    }
+                }
+
+                // Error detail
+                if let Phase::Error(msg) = &snap.phase {
                     ui.label(
-                        RichText::new(format!("({})", addr))
-                            .color(TEXT_DIM)
+                        RichText::new(format!(": {}", msg))
+                            .color(Color32::from_rgb(220, 100, 100))
                             .font(FontId::new(12.0, FontFamily::Proportional)),
                     );
                 }
-            }
 
-            // Error detail
-            if let Phase::Error(msg) = &snap.phase {
-                ui.label(
-                    RichText::new(format!(": {}", msg))
-                        .color(Color32::from_rgb(220, 100, 100))
-                        .font(FontId::new(12.0, FontFamily::Proportional)),
-                );
-            }
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    ui.checkbox(
+                        &mut allow_input,
+                        RichText::new(t("status.allow_input")).color(pal.text_dim).font(FontId::new(12.0, FontFamily::Proportional)),
+                    )
+                    .on_hover_text(t("status.allow_input_hint"));
+                });
+            });
         });
-    });
-}
 
-impl DualLinkApp {
+        if allow_input != snap.allow_input {
+            let mut settings = ReceiverSettings::load();
+            settings.view_only = !allow_input;
+            let saved = settings.save();
+            let mut s = self.state.lock().unwrap();
+            s.push_log(t(if allow_input { "log.input_enabled" } else { "log.view_only" }));
+            if let Err(e) = saved {
+                s.push_log(tf("log.save_failed", &[("what", &t("log.input_setting")), ("error", &e)]));
+            }
+            s.allow_input = allow_input;
+        }
+    }
+
     fn render_pin_card(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, snap: &StateSnapshot) {
+        let pal = palette(ui.ctx());
         let pin = &snap.pairing_pin;
         card(ui, |ui| {
             ui.horizontal(|ui| {
                 ui.label(
-                    RichText::new("Pairing PIN")
-                        .color(TEXT_DIM)
+                    RichText::new(t("pin.title"))
+                        .color(pal.text_dim)
                         .font(FontId::new(12.0, FontFamily::Proportional)),
                 );
             });
@@ -243,20 +483,20 @@ impl DualLinkApp {
                     RichText::new(pin)
                         .font(FontId::new(38.0, FontFamily::Monospace))
                         .strong()
-                        .color(ACCENT),
+                        .color(pal.accent),
                 );
 
                 // Copy button
                 ui.add_space(12.0);
                 let btn_label = if self.copied_pin_frames > 0 {
-                    "Copied!"
+                    t("pin.copied")
                 } else {
-                    "Copy"
+                    t("pin.copy")
                 };
                 let btn_color = if self.copied_pin_frames > 0 {
                     Color32::from_rgb(60, 200, 80)
                 } else {
-                    TEXT_DIM
+                    pal.text_dim
                 };
                 if ui
                     .add_sized(
@@ -266,20 +506,36 @@ impl DualLinkApp {
                                 .color(btn_color)
                                 .font(FontId::new(12.5, FontFamily::Proportional)),
                         )
-                        .fill(BG_INSET)
-                        .stroke(Stroke::new(1.0, Color32::from_rgb(60, 65, 80))),
+                        .fill(pal.inset)
+                        .stroke(Stroke::new(1.0, pal.border)),
                     )
                     .clicked()
                 {
                     ctx.copy_text(pin.to_string());
                     self.copied_pin_frames = 90; // ~1.5 s at 60 fps
                 }
+                if ui
+                    .add_sized(
+                        [76.0, 28.0],
+                        egui::Button::new(
+                            RichText::new(t("pin.new"))
+                                .color(pal.text_dim)
+                                .font(FontId::new(12.5, FontFamily::Proportional)),
+                        )
+                        .fill(pal.inset)
+                        .stroke(Stroke::new(1.0, pal.border)),
+                    )
+                    .on_hover_text(t("pin.new_hint"))
+                    .clicked()
+                {
+                    self.state.lock().unwrap().pin_request = true;
+                }
             });
 
             ui.add_space(2.0);
             ui.label(
-                RichText::new("Enter this PIN in the macOS DualLink app to authorise the connection.")
-                    .color(TEXT_DIM)
+                RichText::new(t("pin.instructions"))
+                    .color(pal.text_dim)
                     .font(FontId::new(12.0, FontFamily::Proportional)),
             );
 
@@ -298,28 +554,383 @@ impl DualLinkApp {
                     };
                     ui.label(mdns_badge);
                     ui.label(
-                        RichText::new(format!("Connect from: {}  •  {} display{}",
-                            snap.lan_ip,
-                            snap.display_count,
-                            if snap.display_count == 1 { "" } else { "s" }))
-                            .color(TEXT_DIM)
+                        RichText::new(tf(
+                            if snap.display_count == 1 { "pin.connect_from_one" } else { "pin.connect_from_many" },
+                            &[("ip", &snap.lan_ip), ("count", &snap.display_count)],
+                        ))
+                            .color(pal.text_dim)
                             .font(FontId::new(12.0, FontFamily::Proportional)),
                     );
+                    let request = if ui.add_enabled(snap.display_count > 1, egui::Button::new("−").small()).clicked() {
+                        Some(DisplayRequest::Remove)
+                    } else if ui.add_enabled(snap.display_count < 8, egui::Button::new("+").small()).clicked() {
+                        Some(DisplayRequest::Add)
+                    } else {
+                        None
+                    };
+                    if request.is_some() {
+                        self.state.lock().unwrap().display_request = request;
+                    }
+                });
+            }
+        });
+    }
+
+    fn render_displays_card(&mut self, ui: &mut egui::Ui, displays: &[DisplaySnapshot]) {
+        let pal = palette(ui.ctx());
+        let mut actions = Vec::new();
+        card(ui, |ui| {
+            ui.label(
+                RichText::new(t("displays.title"))
+                    .color(pal.text_dim)
+                    .font(FontId::new(12.0, FontFamily::Proportional)),
+            );
+            ui.add_space(4.0);
+
+            for d in displays {
+                ui.horizontal(|ui| {
+                    let (rect, _) = ui.allocate_exact_size(Vec2::splat(12.0), egui::Sense::hover());
+                    ui.painter().circle_filled(rect.center(), 4.0, d.phase.color());
+                    ui.label(RichText::new(tf("displays.name", &[("n", &d.index)])).strong().color(pal.text_norm));
+                    ui.label(RichText::new(d.phase.label()).color(d.phase.color()));
+                    if d.stalled {
+                        ui.label(RichText::new(t("displays.stalled")).color(Color32::from_rgb(230, 185, 50)))
+                            .on_hover_text(t("displays.stalled_hint"));
+                    }
+                    if let Some(name) = d.phase.peer_name() {
+                        // Each display may belong to a different sender.
+                        let addr = d.phase.peer_addr().unwrap_or_default();
+                        ui.label(RichText::new(name).color(pal.text_dim))
+                            .on_hover_text(tf("displays.owner_hint", &[("name", &name), ("addr", &addr)]));
+                    } else if let Some(pin) = &d.pin {
+                        ui.label(RichText::new(tf("displays.pin", &[("pin", pin)])).monospace().color(pal.accent))
+                            .on_hover_text(t("displays.pin_hint"));
+                    }
+
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        let has_peer = d.phase.peer_name().is_some();
+                        if ui.add_enabled(has_peer, egui::Button::new(t("displays.disconnect")).small()).clicked() {
+                            actions.push((d.index, DisplayAction::Disconnect));
+                        }
+                        if ui
+                            .add_enabled(has_peer, egui::Button::new(t("displays.restart")).small())
+                            .on_hover_text(t("displays.restart_hint"))
+                            .clicked()
+                        {
+                            actions.push((d.index, DisplayAction::RestartDecoder));
+                        }
+                        let freeze_label = t(if d.frozen { "displays.unfreeze" } else { "displays.freeze" });
+                        if ui
+                            .add_enabled(has_peer, egui::Button::new(freeze_label).small())
+                            .on_hover_text(t("displays.freeze_hint"))
+                            .clicked()
+                        {
+                            actions.push((d.index, DisplayAction::ToggleFreeze));
+                        }
+                        let blank_label = t(if d.blanked { "displays.unblank" } else { "displays.blank" });
+                        if ui
+                            .add_enabled(has_peer, egui::Button::new(blank_label).small())
+                            .on_hover_text(t("displays.blank_hint"))
+                            .clicked()
+                        {
+                            actions.push((d.index, DisplayAction::ToggleBlank));
+                        }
+                        let pause_label = t(if d.paused { "displays.resume" } else { "displays.pause" });
+                        if ui
+                            .add_enabled(has_peer, egui::Button::new(pause_label).small())
+                            .on_hover_text(t("displays.pause_hint"))
+                            .clicked()
+                        {
+                            actions.push((d.index, DisplayAction::TogglePause));
+                        }
+                        let keys_label = t(if d.system_keys { "displays.local_keys" } else { "displays.system_keys" });
+                        if ui
+                            .add_enabled(has_peer, egui::Button::new(keys_label).small())
+                            .on_hover_text(t("displays.system_keys_hint"))
+                            .clicked()
+                        {
+                            actions.push((d.index, DisplayAction::ToggleSystemKeys));
+                        }
+                    });
+                });
+
+                if d.phase.peer_name().is_some() {
+                    ui.horizontal(|ui| {
+                        ui.add_space(18.0);
+                        ui.label(
+                            RichText::new(tf("displays.stats", &[
+                                ("fps", &format!("{:.1}", d.fps)),
+                                ("unique", &format!("{:.1}", d.unique_fps)),
+                                ("decoded", &d.frames_decoded),
+                                ("received", &d.frames_received),
+                                ("stats", &d.frame_stats),
+                                ("decoder", &d.decoder.as_deref().unwrap_or(t("displays.no_decoder"))),
+                            ]))
+                            .color(pal.text_dim)
+                            .font(FontId::new(11.5, FontFamily::Monospace)),
+                        );
+                    });
+                }
+                if let Phase::Error(msg) = &d.phase {
+                    ui.label(
+                        RichText::new(msg)
+                            .color(Color32::from_rgb(220, 100, 100))
+                            .font(FontId::new(11.5, FontFamily::Proportional)),
+                    );
+                }
+            }
+        });
+        if !actions.is_empty() {
+            self.state.lock().unwrap().pending_actions.extend(actions);
+        }
+    }
+
+    fn render_decoder_picker(&mut self, ui: &mut egui::Ui, snap: &StateSnapshot) {
+        let pal = palette(ui.ctx());
+        let option_text = |o: &DecoderOption| match o.latency {
+            Some(d) => format!("{}  ({:.1} ms)", o.element, d.as_secs_f64() * 1e3),
+            None if snap.benchmarking => tf("decoder.benchmarking", &[("element", &o.element)]),
+            None => o.element.to_string(),
+        };
+        let selected_text = match snap.decoder_preference.as_deref() {
+            None => t("decoder.auto").to_string(),
+            Some(el) => match snap.decoder_options.iter().find(|o| o.element == el) {
+                Some(o) => option_text(o),
+                None => tf("decoder.not_installed", &[("element", &el)]),
+            },
+        };
+
+        let mut choice = snap.decoder_preference.clone();
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(t("decoder.title"))
+                    .color(pal.text_dim)
+                    .font(FontId::new(12.0, FontFamily::Proportional)),
+            );
+            egui::ComboBox::from_id_salt("decoder")
+                .selected_text(selected_text)
+                .width(260.0)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut choice, None, t("decoder.auto"));
+                    for o in &snap.decoder_options {
+                        ui.selectable_value(&mut choice, Some(o.element.to_string()), option_text(o))
+                            .on_hover_text(o.label);
+                    }
+                });
+            ui.label(
+                RichText::new(t("decoder.new_sessions"))
+                    .color(pal.text_dim)
+                    .font(FontId::new(11.5, FontFamily::Proportional)),
+            );
+        });
+
+        if choice != snap.decoder_preference {
+            let preference: Vec<String> = choice.into_iter().collect();
+            let mut settings = ReceiverSettings::load();
+            settings.decoder_preference = preference.clone();
+            let saved = settings.save();
+            let mut s = self.state.lock().unwrap();
+            s.push_log(match preference.first() {
+                Some(el) => tf("log.decoder_preference", &[("element", el)]),
+                None => t("log.decoder_preference_auto").to_string(),
+            });
+            if let Err(e) = saved {
+                s.push_log(tf("log.save_failed", &[("what", &t("log.decoder_setting")), ("error", &e)]));
+            }
+            s.decoder_preference = preference;
+        }
+    }
+
+    fn render_macro_card(&mut self, ui: &mut egui::Ui, snap: &StateSnapshot) {
+        let pal = palette(ui.ctx());
+        let mut request = None;
+        card(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(t("macro.title"))
+                        .color(pal.text_dim)
+                        .font(FontId::new(12.0, FontFamily::Proportional)),
+                );
+                let status = match (snap.macro_recording, snap.macro_replaying) {
+                    (Some(n), _) => tf("macro.recording", &[("n", &n)]),
+                    (None, true) => t("macro.replaying").to_string(),
+                    (None, false) => String::new(),
+                };
+                ui.label(RichText::new(status).color(pal.text_norm).font(FontId::new(12.0, FontFamily::Proportional)));
+
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    let recording = snap.macro_recording.is_some();
+                    if snap.macro_replaying {
+                        if ui.add(egui::Button::new(t("macro.stop_replay")).small()).clicked() {
+                            request = Some(MacroRequest::StopReplay);
+                        }
+                    } else if ui
+                        .add_enabled(!recording, egui::Button::new(t("macro.replay")).small())
+                        .on_hover_text(t("macro.replay_hint"))
+                        .clicked()
+                    {
+                        request = Some(MacroRequest::Replay);
+                    }
+                    if recording {
+                        if ui.add(egui::Button::new(t("macro.stop_save")).small()).clicked() {
+                            request = Some(MacroRequest::StopRecording);
+                        }
+                    } else if ui
+                        .add_enabled(!snap.macro_replaying, egui::Button::new(t("macro.record")).small())
+                        .on_hover_text(t("macro.record_hint"))
+                        .clicked()
+                    {
+                        request = Some(MacroRequest::Record);
+                    }
+                });
+            });
+        });
+        if request.is_some() {
+            self.state.lock().unwrap().macro_request = request;
+        }
+    }
+
+    /// Offer files dropped on the window to the sender of the first
+    /// connected display.
+    fn send_files(&mut self, paths: Vec<PathBuf>, displays: &[DisplaySnapshot]) {
+        let mut s = self.state.lock().unwrap();
+        match displays.iter().find(|d| d.phase.peer_name().is_some()) {
+            Some(d) => s.file_requests.extend(paths.into_iter().map(|p| (d.index, FileRequest::Send(p)))),
+            None => s.push_log(t("log.file_not_connected")),
+        }
+    }
+
+    fn render_files_card(&mut self, ui: &mut egui::Ui, snap: &StateSnapshot) {
+        let pal = palette(ui.ctx());
+        let mut answers = Vec::new();
+        let done_color = Color32::from_rgb(60, 200, 80);
+        card(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(t("files.title"))
+                        .color(pal.text_dim)
+                        .font(FontId::new(12.0, FontFamily::Proportional)),
+                );
+                ui.label(RichText::new(t("files.drop_hint")).color(pal.text_dim).small());
+            });
+            for (display, offer) in &snap.file_offers {
+                ui.horizontal(|ui| {
+                    let size = format!("{:.1} MB", offer.size as f64 / 1e6);
+                    ui.label(
+                        RichText::new(tf("files.offer", &[("n", display), ("name", &offer.name), ("size", &size)]))
+                            .color(pal.text_norm),
+                    );
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.add(egui::Button::new(t("files.decline")).small()).clicked() {
+                            answers.push((*display, offer.transfer_id.clone(), false));
+                        }
+                        if ui.add(egui::Button::new(t("files.accept")).small()).clicked() {
+                            answers.push((*display, offer.transfer_id.clone(), true));
+                        }
+                    });
                 });
             }
+            for event in &snap.transfers {
+                match event {
+                    FileTransferEvent::Progress { name, done, total, incoming, .. } => {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(if *incoming { "↓" } else { "↑" }).color(pal.accent));
+                            ui.label(RichText::new(name).color(pal.text_norm));
+                            ui.add(
+                                egui::ProgressBar::new(*done as f32 / (*total).max(1) as f32)
+                                    .desired_width(200.0)
+                                    .show_percentage(),
+                            );
+                        });
+                    }
+                    FileTransferEvent::Received { name, path, .. } => {
+                        ui.label(RichText::new(tf("files.received", &[("name", name)])).color(done_color))
+                            .on_hover_text(path.display().to_string());
+                    }
+                    FileTransferEvent::Sent { name, .. } => {
+                        ui.label(RichText::new(tf("files.sent", &[("name", name)])).color(done_color));
+                    }
+                    FileTransferEvent::Failed { name, reason, .. } => {
+                        let line = tf("files.failed", &[("name", name), ("reason", reason)]);
+                        ui.label(RichText::new(line).color(Color32::from_rgb(220, 60, 60)));
+                    }
+                    FileTransferEvent::Offered(_) => {}
+                }
+            }
+        });
+        if !answers.is_empty() {
+            let mut s = self.state.lock().unwrap();
+            for (display, transfer_id, accept) in answers {
+                s.file_offers.retain(|(_, o)| o.transfer_id != transfer_id);
+                s.file_requests.push((display, FileRequest::Answer { transfer_id, accept }));
+            }
+        }
+    }
+
+    fn render_firewall_card(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, check: &FirewallCheck, opening: bool) {
+        let pal = palette(ui.ctx());
+        let Some(firewall) = check.firewall else { return };
+        card(ui, |ui| {
+            ui.label(
+                RichText::new(t("firewall.title"))
+                    .color(pal.text_dim)
+                    .font(FontId::new(12.0, FontFamily::Proportional)),
+            );
+            ui.label(
+                RichText::new(tf("firewall.blocked", &[("firewall", &firewall), ("ports", &check.blocked_list())]))
+                    .color(Color32::from_rgb(230, 185, 50)),
+            );
+            ui.horizontal(|ui| {
+                let open = ui
+                    .add_enabled(!opening, egui::Button::new(t("firewall.open")))
+                    .on_hover_text(tf("firewall.open_hint", &[("script", &firewall.open_script(&check.blocked))]));
+                if open.clicked() {
+                    let state = Arc::clone(&self.state);
+                    let ctx = ctx.clone();
+                    std::thread::spawn(move || receiver::open_firewall(state, ctx));
+                }
+                if opening {
+                    ui.spinner();
+                } else if ui.button(t("firewall.dismiss")).clicked() {
+                    self.state.lock().unwrap().firewall = None;
+                }
+            });
         });
     }
 
+    fn render_language_picker(&mut self, ui: &mut egui::Ui) {
+        let current = language();
+        let mut choice = current;
+        egui::ComboBox::from_id_salt("language")
+            .selected_text(current.native_name())
+            .show_ui(ui, |ui| {
+                for lang in Language::ALL {
+                    ui.selectable_value(&mut choice, lang, lang.native_name());
+                }
+            })
+            .response
+            .on_hover_text(t("app.language"));
+
+        if choice != current {
+            set_language(choice);
+            if let Err(e) = choice.save() {
+                let line = tf("log.save_failed", &[("what", &t("log.language_setting")), ("error", &e)]);
+                self.state.lock().unwrap().push_log(line);
+            }
+        }
+    }
+
     fn render_fingerprint_section(&mut self, ui: &mut egui::Ui, fp: &str) {
+        let pal = palette(ui.ctx());
         if fp.is_empty() {
             return;
         }
-        let header = RichText::new("▸ TLS certificate fingerprint")
+        let header = RichText::new(format!("▸ {}", t("pin.fingerprint")))
             .font(FontId::new(12.0, FontFamily::Proportional))
-            .color(TEXT_DIM);
-        let header_open = RichText::new("▾ TLS certificate fingerprint")
+            .color(pal.text_dim);
+        let header_open = RichText::new(format!("▾ {}", t("pin.fingerprint")))
             .font(FontId::new(12.0, FontFamily::Proportional))
-            .color(TEXT_DIM);
+            .color(pal.text_dim);
 
         let toggle_label = if self.show_fingerprint { header_open } else { header };
         if ui.add(egui::Label::new(toggle_label).sense(egui::Sense::click())).clicked() {
@@ -338,48 +949,91 @@ impl DualLinkApp {
                 });
                 ui.add_space(2.0);
                 ui.label(
-                    RichText::new("The macOS client accepts this certificate on first connect (TOFU).")
+                    RichText::new(t("pin.fingerprint_hint"))
                         .font(FontId::new(11.5, FontFamily::Proportional))
-                        .color(TEXT_DIM),
+                        .color(pal.text_dim),
                 );
             });
         }
     }
 }
 
-fn render_stats_card(ui: &mut egui::Ui, snap: &StateSnapshot) {
+fn render_stats_card(ui: &mut egui::Ui, snap: &StateSnapshot, quality: &Quality) {
+    let pal = palette(ui.ctx());
     card(ui, |ui| {
         ui.label(
-            RichText::new("Streaming stats")
-                .color(TEXT_DIM)
+            RichText::new(t("stats.title"))
+                .color(pal.text_dim)
                 .font(FontId::new(12.0, FontFamily::Proportional)),
         );
         ui.add_space(6.0);
 
         ui.horizontal_wrapped(|ui| {
-            stat_chip(ui, "FPS",      &format!("{:.1}", snap.fps));
-            stat_chip(ui, "Decoded",  &snap.frames_decoded.to_string());
-            stat_chip(ui, "Received", &snap.frames_received.to_string());
-            stat_chip(ui, "Bitrate",  &format!("{:.1} Mbit/s", snap.bitrate_mbps));
-            stat_chip(ui, "Displays", &snap.display_count.to_string());
+            stat_chip(ui, t("stats.fps"),        &format!("{:.1}", snap.fps));
+            stat_chip(ui, t("stats.unique_fps"), &format!("{:.1}", snap.unique_fps));
+            stat_chip(ui, t("stats.decoded"),    &snap.frames_decoded.to_string());
+            stat_chip(ui, t("stats.received"),   &snap.frames_received.to_string());
+            stat_chip(ui, t("stats.bitrate"),    &format!("{:.1} Mbit/s", snap.bitrate_mbps));
+            stat_chip(ui, t("stats.lost"),       &snap.frame_stats.lost.to_string());
+            stat_chip(ui, t("stats.duplicates"), &snap.duplicates.to_string());
+            stat_chip(ui, t("stats.displays"),   &snap.display_count.to_string());
         });
+        ui.add_space(6.0);
+        render_quality(ui, quality);
     });
 }
 
+/// Health score of display 0 and the hints of the rules that cost it points.
+fn render_quality(ui: &mut egui::Ui, quality: &Quality) {
+    let pal = palette(ui.ctx());
+    let color = match quality.score {
+        80.. => Color32::from_rgb(60, 200, 80),
+        50.. => Color32::from_rgb(230, 185, 50),
+        _ => Color32::from_rgb(220, 80, 70),
+    };
+    let m = &quality.metrics;
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(t("quality.title")).color(pal.text_dim));
+        ui.label(
+            RichText::new(format!("{} · {}", quality.score, t(quality.rating_key())))
+                .strong()
+                .color(color),
+        )
+        .on_hover_text(tf(
+            "quality.details",
+            &[
+                ("loss", &format!("{:.1}", m.loss_pct)),
+                ("drops", &format!("{:.1}", m.drops_per_s)),
+                ("errors", &format!("{:.1}", m.errors_per_s)),
+                ("delay", &format!("{:.0}", m.delay_ms)),
+                ("variation", &format!("{:.0}", m.fps_variation)),
+            ],
+        ));
+    });
+    for hint in &quality.hints {
+        ui.label(
+            RichText::new(format!("⚠ {}", t(hint.key())))
+                .color(pal.text_norm)
+                .font(FontId::new(12.0, FontFamily::Proportional)),
+        );
+    }
+}
+
 fn render_log_panel(
     ui: &mut egui::Ui,
     logs: &[String],
     auto_scroll: &mut bool,
 ) {
+    let pal = palette(ui.ctx());
     // Header row with auto-scroll toggle
     ui.horizontal(|ui| {
         ui.label(
-            RichText::new("Log")
-                .color(TEXT_DIM)
+            RichText::new(t("log.title"))
+                .color(pal.text_dim)
                 .font(FontId::new(12.0, FontFamily::Proportional)),
         );
         ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-            ui.checkbox(auto_scroll, RichText::new("auto-scroll").color(TEXT_DIM).font(FontId::new(11.5, FontFamily::Proportional)));
+            ui.checkbox(auto_scroll, RichText::new(t("log.auto_scroll")).color(pal.text_dim).font(FontId::new(11.5, FontFamily::Proportional)));
         });
     });
     ui.add_space(3.0);
@@ -388,9 +1042,9 @@ fn render_log_panel(
     let log_height = (available.y - 55.0).max(140.0);
 
     Frame::none()
-        .fill(BG_INSET)
+        .fill(pal.inset)
         .inner_margin(Margin::symmetric(8.0, 6.0))
-        .stroke(Stroke::new(1.0, Color32::from_rgb(45, 48, 60)))
+        .stroke(Stroke::new(1.0, pal.border_faint))
         .rounding(egui::Rounding::same(6.0))
         .show(ui, |ui| {
             ScrollArea::vertical()
@@ -406,7 +1060,7 @@ fn render_log_panel(
                         } else if line.starts_with("[WARN]") {
                             Color32::from_rgb(220, 165, 50)
                         } else {
-                            Color32::from_rgb(160, 170, 185)
+                            pal.log_info
                         };
                         ui.label(
                             RichText::new(line)
@@ -421,11 +1075,12 @@ fn render_log_panel(
 // ── Utilities ─────────────────────────────────────────────────────────────────
 
 fn card(ui: &mut egui::Ui, add_contents: impl FnOnce(&mut egui::Ui)) {
+    let pal = palette(ui.ctx());
     Frame::none()
-        .fill(BG_CARD)
+        .fill(pal.card)
         .inner_margin(Margin::symmetric(12.0, 10.0))
         .rounding(egui::Rounding::same(8.0))
-        .stroke(Stroke::new(1.0, Color32::from_rgb(50, 53, 68)))
+        .stroke(Stroke::new(1.0, pal.border_faint))
         .show(ui, |ui| {
             ui.set_min_width(ui.available_width());
             add_contents(ui);
@@ -433,24 +1088,25 @@ fn card(ui: &mut egui::Ui, add_contents: impl FnOnce(&mut egui::Ui)) {
 }
 
 fn stat_chip(ui: &mut egui::Ui, label: &str, value: &str) {
+    let pal = palette(ui.ctx());
     Frame::none()
-        .fill(BG_INSET)
+        .fill(pal.inset)
         .inner_margin(Margin::symmetric(10.0, 6.0))
         .rounding(egui::Rounding::same(6.0))
-        .stroke(Stroke::new(1.0, Color32::from_rgb(50, 53, 68)))
+        .stroke(Stroke::new(1.0, pal.border_faint))
         .show(ui, |ui| {
             ui.vertical_centered(|ui| {
                 ui.label(
                     RichText::new(value)
                         .font(FontId::new(20.0, FontFamily::Monospace))
                         .strong()
-                        .color(Color32::WHITE),
+                        .color(pal.text_strong),
                 );
                 ui.add_space(1.0);
                 ui.label(
                     RichText::new(label)
                         .font(FontId::new(11.0, FontFamily::Proportional))
-                        .color(TEXT_DIM),
+                        .color(pal.text_dim),
                 );
             });
         });
@@ -464,14 +1120,59 @@ struct StateSnapshot {
     pairing_pin:     String,
     tls_fingerprint: String,
     fps:             f64,
+    /// Display 0's rate of decoded frames shown, duplicates not counted.
+    unique_fps:      f64,
+    duplicates:      u64,
+    reassembly_drops: u64,
+    queueing_delay:  Duration,
+    decode_errors:   u64,
     frames_received: u64,
     frames_decoded:  u64,
     bitrate_mbps:    f64,
+    frame_stats:     SequenceStats,
     transport:       String,
+    network:         NetworkKind,
     logs:            Vec<String>,
     lan_ip:          String,
     mdns_active:     bool,
     display_count:   u8,
+    /// Display 0 first, then the extra displays in index order.
+    displays:        Vec<DisplaySnapshot>,
+    decoder_options: Vec<DecoderOption>,
+    benchmarking:    bool,
+    /// First preferred decoder element (`None` = probe order).
+    decoder_preference: Option<String>,
+    allow_input:     bool,
+    view_only_session: bool,
+    /// Events recorded so far, while recording input.
+    macro_recording: Option<usize>,
+    macro_replaying: bool,
+    power:           Option<PowerState>,
+    sender_power:    Option<PowerState>,
+    firewall:        Option<FirewallCheck>,
+    firewall_opening: bool,
+    /// Files senders offered, by display, waiting for an answer.
+    file_offers:     Vec<(u8, FileOffer)>,
+    transfers:       Vec<FileTransferEvent>,
+}
+
+struct DisplaySnapshot {
+    index:           u8,
+    phase:           Phase,
+    fps:             f64,
+    unique_fps:      f64,
+    frames_received: u64,
+    frames_decoded:  u64,
+    decoder:         Option<String>,
+    frame_stats:     SequenceStats,
+    frozen:          bool,
+    blanked:         bool,
+    paused:          bool,
+    system_keys:     bool,
+    /// No frames arrived for a while.
+    stalled:         bool,
+    /// The display's own pairing PIN, if it has one.
+    pin:             Option<String>,
 }
 
 // Forward Phase methods onto the snapshot for ergonomics in the renderer
@@ -1,9 +1,14 @@
+mod appearance;
 mod gui_app;
+mod quality;
 mod receiver;
 mod state;
+mod strings;
 
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
+use duallink_core::{AppearanceSettings, DiagnosticsReport};
 use state::GuiState;
 
 fn main() -> eframe::Result<()> {
@@ -16,16 +21,33 @@ fn main() -> eframe::Result<()> {
         .compact()
         .init();
 
+    // ── --diagnose: write a diagnostics bundle and exit ─────────────────────
+    if std::env::args().any(|a| a == "--diagnose") {
+        let mut report = DiagnosticsReport::collect(env!("CARGO_PKG_VERSION"));
+        duallink_decoder::fill_diagnostics(&mut report);
+        match report.write_to(Path::new(".")) {
+            Ok(path) => println!("Diagnostics written to {}", path.display()),
+            Err(e) => eprintln!("Writing diagnostics: {e}"),
+        }
+        return Ok(());
+    }
+
+    // ── --trace[=SECS]: record per-frame timing spans ─────────────────────
+    if let Some(duration) = duallink_core::trace::duration_arg(std::env::args()) {
+        duallink_core::trace::start(duration, ".".into());
+    }
+
     // ── Shared state ──────────────────────────────────────────────────────
     let shared_state: state::SharedState = Arc::new(Mutex::new(GuiState::default()));
 
     // ── Window options ────────────────────────────────────────────────────
+    let viewport = egui::ViewportBuilder::default()
+        .with_title("DualLink Receiver")
+        .with_inner_size([560.0, 720.0])
+        .with_min_inner_size([420.0, 500.0])
+        .with_resizable(true);
     let window_options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_title("DualLink Receiver")
-            .with_inner_size([560.0, 720.0])
-            .with_min_inner_size([420.0, 500.0])
-            .with_resizable(true),
+        viewport: appearance::restore_geometry(viewport, &AppearanceSettings::load()),
         ..Default::default()
     };
 
@@ -0,0 +1,346 @@
+//! Connection quality of display 0: a 0–100 health score and hints on what
+//! to do about it, shown in the stats card.
+//!
+//! [`QualityTracker`] keeps the last [`WINDOW`] of counters so loss, drops
+//! and errors are judged on what happens now rather than over the whole
+//! session. Each rule in [`RULES`] turns the windowed [`Metrics`] into a
+//! penalty; the score is 100 minus the penalties, and every rule with a
+//! penalty contributes its hint. A hint stays up for [`HINT_HOLD`] after
+//! its rule last applied, so a link hovering around a threshold doesn't
+//! make it flicker; the score follows the rules at once.
+//!
+//! Latency is judged by the queueing delay, not the round-trip time: the
+//! receiver only echoes the sender's keepalives, so the RTT is measured on
+//! the sender. The queueing delay — how much longer the latest frame took
+//! than the fastest one — is known here for every frame, and it is what a
+//! rising RTT would show: the network or the sender holding frames back.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use duallink_core::{NetworkKind, SequenceStats};
+
+/// How far back the tracker looks.
+pub const WINDOW: Duration = Duration::from_secs(5);
+
+/// How long a hint stays after its rule stopped applying.
+pub const HINT_HOLD: Duration = Duration::from_secs(3);
+
+// ── Inputs ────────────────────────────────────────────────────────────────────
+
+/// Display 0's counters and state at one frame of the GUI.
+#[derive(Debug, Clone, Copy)]
+pub struct QualityInput<'a> {
+    pub frame_stats:      SequenceStats,
+    /// Partial frames evicted (timeout or budget) and corrupted frames.
+    pub reassembly_drops: u64,
+    /// Frames the decoder rejected.
+    pub decode_errors:    u64,
+    /// Network and sender queueing of the latest frame (see the module
+    /// docs for why not the RTT).
+    pub queueing_delay:   Duration,
+    pub fps:              f64,
+    /// Decoder element of the session.
+    pub decoder:          Option<&'a str>,
+    /// Link to the sender, as detected at startup.
+    pub network:          NetworkKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at:               Instant,
+    received:         u64,
+    lost:             u64,
+    reassembly_drops: u64,
+    decode_errors:    u64,
+    fps:              f64,
+}
+
+// ── Metrics and rules ─────────────────────────────────────────────────────────
+
+/// What the rules judge, over the tracker's window.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Metrics {
+    /// Frames lost, in percent of frames sent.
+    pub loss_pct:         f64,
+    pub drops_per_s:      f64,
+    pub errors_per_s:     f64,
+    /// Queueing delay of the latest frame.
+    pub delay_ms:         f64,
+    /// Standard deviation of the fps, in percent of its mean.
+    pub fps_variation:    f64,
+    pub software_decoder: bool,
+    pub wifi:             bool,
+}
+
+/// Advice shown under the score; each maps to a catalog key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    PacketLossWifi,
+    PacketLoss,
+    ReassemblyDrops,
+    DecodeErrors,
+    HighDelay,
+    UnstableFps,
+    SoftwareDecoder,
+}
+
+impl Hint {
+    /// Catalog key of the hint's text.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::PacketLossWifi => "quality.hint_loss_wifi",
+            Self::PacketLoss => "quality.hint_loss",
+            Self::ReassemblyDrops => "quality.hint_drops",
+            Self::DecodeErrors => "quality.hint_decode_errors",
+            Self::HighDelay => "quality.hint_delay",
+            Self::UnstableFps => "quality.hint_fps",
+            Self::SoftwareDecoder => "quality.hint_software_decoder",
+        }
+    }
+}
+
+/// A rule: the hint it gives and the score points it takes off (0 = the
+/// rule does not apply).
+pub struct Rule {
+    pub hint:    fn(&Metrics) -> Hint,
+    pub penalty: fn(&Metrics) -> f64,
+}
+
+/// Every rule, most important first — the order hints are shown in.
+pub const RULES: &[Rule] = &[
+    Rule {
+        hint:    |m| if m.wifi { Hint::PacketLossWifi } else { Hint::PacketLoss },
+        penalty: |m| if m.loss_pct > 1.0 { (m.loss_pct * 4.0).min(40.0) } else { 0.0 },
+    },
+    Rule {
+        hint:    |_| Hint::DecodeErrors,
+        penalty: |m| if m.errors_per_s > 0.0 { (m.errors_per_s * 10.0).clamp(5.0, 25.0) } else { 0.0 },
+    },
+    Rule {
+        hint:    |_| Hint::ReassemblyDrops,
+        penalty: |m| if m.drops_per_s > 0.2 { (m.drops_per_s * 10.0).min(20.0) } else { 0.0 },
+    },
+    Rule {
+        hint:    |_| Hint::HighDelay,
+        penalty: |m| if m.delay_ms > 50.0 { ((m.delay_ms - 50.0) / 5.0).clamp(2.0, 20.0) } else { 0.0 },
+    },
+    Rule {
+        hint:    |_| Hint::UnstableFps,
+        penalty: |m| if m.fps_variation > 15.0 { (m.fps_variation / 2.0).min(15.0) } else { 0.0 },
+    },
+    Rule {
+        hint:    |_| Hint::SoftwareDecoder,
+        penalty: |m| if m.software_decoder { 10.0 } else { 0.0 },
+    },
+];
+
+/// Score and hints for one frame.
+#[derive(Debug, Clone, Default)]
+pub struct Quality {
+    /// 0 (unusable) – 100 (perfect).
+    pub score:   u8,
+    pub metrics: Metrics,
+    pub hints:   Vec<Hint>,
+}
+
+impl Quality {
+    /// Catalog key of the score's rating.
+    pub fn rating_key(&self) -> &'static str {
+        match self.score {
+            80.. => "quality.good",
+            50.. => "quality.fair",
+            _ => "quality.poor",
+        }
+    }
+}
+
+// ── QualityTracker ────────────────────────────────────────────────────────────
+
+#[derive(Debug, Default)]
+pub struct QualityTracker {
+    samples: VecDeque<Sample>,
+    /// Per rule in [`RULES`]: the hint it gave when it last applied.
+    fired:   [Option<(Hint, Instant)>; RULES.len()],
+}
+
+impl QualityTracker {
+    /// Forget the samples and hints (new session).
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.fired = Default::default();
+    }
+
+    /// Add `input` and judge the window.
+    pub fn update(&mut self, input: &QualityInput<'_>) -> Quality {
+        self.update_at(input, Instant::now())
+    }
+
+    /// [`update`](Self::update) with an explicit clock, for tests.
+    fn update_at(&mut self, input: &QualityInput<'_>, now: Instant) -> Quality {
+        // Counters going back means a new session.
+        if self.samples.back().is_some_and(|s| input.frame_stats.received < s.received) {
+            self.reset();
+        }
+        self.samples.push_back(Sample {
+            at:               now,
+            received:         input.frame_stats.received,
+            lost:             input.frame_stats.lost,
+            reassembly_drops: input.reassembly_drops,
+            decode_errors:    input.decode_errors,
+            fps:              input.fps,
+        });
+        while self.samples.front().is_some_and(|s| now.duration_since(s.at) > WINDOW) {
+            self.samples.pop_front();
+        }
+
+        let metrics = self.metrics(input);
+        let mut score = 100.0;
+        let mut hints = Vec::new();
+        for (rule, fired) in RULES.iter().zip(&mut self.fired) {
+            let penalty = (rule.penalty)(&metrics);
+            if penalty > 0.0 {
+                score -= penalty;
+                *fired = Some(((rule.hint)(&metrics), now));
+            }
+            match *fired {
+                Some((hint, at)) if now.duration_since(at) <= HINT_HOLD => hints.push(hint),
+                _ => *fired = None,
+            }
+        }
+        Quality { score: score.clamp(0.0, 100.0) as u8, metrics, hints }
+    }
+
+    fn metrics(&self, input: &QualityInput<'_>) -> Metrics {
+        let (first, last) = (self.samples.front().unwrap(), self.samples.back().unwrap());
+        let secs = last.at.duration_since(first.at).as_secs_f64().max(1.0);
+        let lost = last.lost.saturating_sub(first.lost) as f64;
+        let received = last.received.saturating_sub(first.received) as f64;
+        let loss_pct = if lost + received > 0.0 { lost * 100.0 / (lost + received) } else { 0.0 };
+
+        let fps: Vec<f64> = self.samples.iter().map(|s| s.fps).collect();
+        let mean = fps.iter().sum::<f64>() / fps.len() as f64;
+        let fps_variation = if mean > 1.0 {
+            let var = fps.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / fps.len() as f64;
+            var.sqrt() * 100.0 / mean
+        } else {
+            0.0
+        };
+
+        Metrics {
+            loss_pct,
+            drops_per_s: last.reassembly_drops.saturating_sub(first.reassembly_drops) as f64 / secs,
+            errors_per_s: last.decode_errors.saturating_sub(first.decode_errors) as f64 / secs,
+            delay_ms: input.queueing_delay.as_secs_f64() * 1e3,
+            fps_variation,
+            software_decoder: input.decoder.is_some_and(|d| d.starts_with("avdec_")),
+            wifi: input.network == NetworkKind::Wifi,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A clean link `secs` into the session: 60 fps, nothing lost.
+    fn input(secs: u64) -> QualityInput<'static> {
+        QualityInput {
+            frame_stats:      SequenceStats { received: secs * 60, ..SequenceStats::default() },
+            reassembly_drops: 0,
+            decode_errors:    0,
+            queueing_delay:   Duration::from_millis(2),
+            fps:              60.0,
+            decoder:          Some("vah264dec"),
+            network:          NetworkKind::Ethernet,
+        }
+    }
+
+    /// Turns a clean [`input`] into a bad one.
+    type Change = fn(&mut QualityInput<'static>);
+
+    #[test]
+    fn each_rule_triggers_its_hint() {
+        let cases: [(Change, Hint); 7] = [
+            (|i| i.frame_stats.lost = 5, Hint::PacketLoss),
+            (|i| (i.frame_stats.lost, i.network) = (5, NetworkKind::Wifi), Hint::PacketLossWifi),
+            (|i| i.decode_errors = 1, Hint::DecodeErrors),
+            (|i| i.reassembly_drops = 1, Hint::ReassemblyDrops),
+            (|i| i.queueing_delay = Duration::from_millis(80), Hint::HighDelay),
+            (|i| i.fps = 20.0, Hint::UnstableFps),
+            (|i| i.decoder = Some("avdec_h264"), Hint::SoftwareDecoder),
+        ];
+        let t0 = Instant::now();
+        for (change, hint) in cases {
+            let mut tracker = QualityTracker::default();
+            let clean = tracker.update_at(&input(0), t0);
+            assert_eq!((clean.score, clean.hints.len()), (100, 0));
+
+            let mut bad = input(1);
+            change(&mut bad);
+            let q = tracker.update_at(&bad, t0 + Duration::from_secs(1));
+            assert_eq!(q.hints, [hint]);
+            assert!(q.score < 100, "{hint:?} cost nothing");
+        }
+    }
+
+    #[test]
+    fn below_thresholds_nothing_fires() {
+        let mut tracker = QualityTracker::default();
+        let t0 = Instant::now();
+        tracker.update_at(&input(0), t0);
+        let mut fine = input(10);
+        // 1 % loss, 50 ms delay, Wi-Fi: all at or under their thresholds.
+        fine.frame_stats.lost = 6;
+        fine.queueing_delay = Duration::from_millis(50);
+        fine.network = NetworkKind::Wifi;
+        let q = tracker.update_at(&fine, t0 + Duration::from_secs(1));
+        assert_eq!((q.score, q.hints.len()), (100, 0));
+    }
+
+    #[test]
+    fn hints_outlast_their_cause_then_clear() {
+        let mut tracker = QualityTracker::default();
+        let t0 = Instant::now();
+        let at = |secs: u64| t0 + Duration::from_secs(secs);
+        let slow = |secs| QualityInput { queueing_delay: Duration::from_millis(80), ..input(secs) };
+        tracker.update_at(&input(0), at(0));
+        assert_eq!(tracker.update_at(&slow(1), at(1)).hints, [Hint::HighDelay]);
+
+        // The score recovers at once; the hint holds.
+        let q = tracker.update_at(&input(2), at(2));
+        assert_eq!((q.score, &q.hints[..]), (100, &[Hint::HighDelay][..]));
+        assert_eq!(tracker.update_at(&input(3), at(3)).hints, [Hint::HighDelay]);
+        // A relapse within the hold starts it over.
+        assert_eq!(tracker.update_at(&slow(4), at(4)).hints, [Hint::HighDelay]);
+        assert_eq!(tracker.update_at(&input(7), at(7)).hints, [Hint::HighDelay]);
+        assert_eq!(tracker.update_at(&input(8), at(8)).hints, []);
+    }
+
+    #[test]
+    fn loss_clears_once_it_leaves_the_window() {
+        let mut tracker = QualityTracker::default();
+        let t0 = Instant::now();
+        let mut last_lossy = None;
+        for secs in 0..=15 {
+            let mut i = input(secs);
+            // Five frames lost in the first second, none after.
+            i.frame_stats.lost = if secs == 0 { 0 } else { 5 };
+            let q = tracker.update_at(&i, t0 + Duration::from_secs(secs));
+            if q.metrics.loss_pct > 1.0 {
+                last_lossy = Some(secs);
+            }
+            let held = last_lossy.is_some_and(|l| secs - l <= HINT_HOLD.as_secs());
+            assert_eq!(q.hints.contains(&Hint::PacketLoss), held, "at {secs} s");
+        }
+        // Out of the window after WINDOW, then the hold.
+        assert_eq!(last_lossy, Some(WINDOW.as_secs()));
+
+        // Going back to a new session forgets held hints too.
+        let mut i = input(16);
+        i.frame_stats.lost = 50;
+        tracker.update_at(&i, t0 + Duration::from_secs(16));
+        tracker.reset();
+        assert_eq!(tracker.update_at(&input(0), t0 + Duration::from_secs(17)).hints, []);
+    }
+}
@@ -1,44 +1,130 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
-use tracing::{info, warn};
-
-use duallink_core::{detect_usb_ethernet, EncodedFrame, StreamConfig};
-use duallink_decoder::DecoderFactory;
+use tracing::warn;
+
+use duallink_core::diagnostics::home_dir;
+use duallink_core::errors::DecoderError;
+use duallink_core::{
+    detect_usb_ethernet, read_power, receiver_ports, DiagnosticsReport, FirewallCheck, FrameSample, InputRecording,
+    FileTransferEvent, FileTransfers, NetworkKind, PortMap, Resolution, SessionEvent, StatsSink, StreamConfig,
+    VideoCodec, HIDDEN_FPS, POWER_POLL_INTERVAL,
+};
+use duallink_decoder::{
+    benchmark_decoders, candidates, fill_diagnostics, receiver_capabilities, AsyncDecoder, DecoderFactory, DecoderStats,
+};
 use duallink_discovery::{DualLinkAdvertiser, detect_local_ip};
-use duallink_transport::{DualLinkReceiver, DisplayChannels, InputSender, SignalingEvent, SIGNALING_PORT};
+use duallink_receiver_lib::{
+    ExitReason, Next, Opener, ReceiverSession, Reload, SessionAction, SessionHooks, SessionInfo,
+};
+use duallink_transport::{
+    configured_allow_input, configured_base_port, handover::request_handover, hooks::Hooks, DualLinkReceiver,
+    DisplayChannels, DisplayConfig, InputSender, ReplayHandle, SignalingEvent,
+};
 
-use crate::state::{Phase, SharedState};
+use crate::state::{DecoderOption, DisplayAction, DisplayRequest, FileRequest, MacroRequest, Phase, SharedState};
+use crate::strings::{t, tf};
 
 const SERVICE_NAME: &str = "duallink-receiver.service";
 
-// ── Port release helpers ───────────────────────────────────────────────────────
+// ── Decoder selection ─────────────────────────────────────────────────────────
 
-/// Stop the systemd user service.  Works even when launched from a GUI session
-/// (GNOME sets XDG_RUNTIME_DIR and the D-Bus socket in the environment).
-fn stop_systemd_service() {
-    let _ = std::process::Command::new("systemctl")
-        .args(["--user", "stop", SERVICE_NAME])
-        .status();
+/// Factory applying the preference currently chosen in the GUI.
+fn decoder_factory(state: &SharedState) -> DecoderFactory {
+    let preference = state.lock().unwrap().decoder_preference.clone();
+    DecoderFactory::with_preference(&preference.iter().map(String::as_str).collect::<Vec<_>>())
 }
 
-/// Kill whatever process is currently holding UDP:7878 or TCP:7879.
-/// Uses `fuser` (util-linux) which doesn't need D-Bus.
-fn fuser_kill_ports() {
-    // UDP 7878
-    let _ = std::process::Command::new("fuser")
-        .args(["-k", "7878/udp"])
-        .status();
-    // TCP 7879
-    let _ = std::process::Command::new("fuser")
-        .args(["-k", "7879/tcp"])
-        .status();
+/// List the installed H.264 decoders with their benchmark times for the
+/// decoder dropdown. Decoders missing from the cached benchmark (all of them
+/// on first launch or with `--benchmark`) are measured and the cache is
+/// updated. Blocking — run via `spawn_blocking`.
+fn probe_decoders(state: SharedState, ctx: egui::Context) {
+    let installed: Vec<_> = candidates(VideoCodec::H264).into_iter().filter(|c| c.installed).collect();
+    {
+        let mut s = state.lock().unwrap();
+        s.benchmarking = true;
+        s.decoder_options = installed
+            .iter()
+            .map(|c| DecoderOption { element: c.element, label: c.label, latency: None })
+            .collect();
+    }
+    ctx.request_repaint();
+
+    let force = std::env::args().any(|a| a == "--benchmark");
+    benchmark_decoders(force, |element, latency| {
+        let mut s = state.lock().unwrap();
+        if let Some(opt) = s.decoder_options.iter_mut().find(|o| o.element == element) {
+            opt.latency = latency;
+        }
+        s.push_log(match latency {
+            Some(d) => tf("log.benchmark", &[("element", &element), ("ms", &format!("{:.1}", d.as_secs_f64() * 1e3))]),
+            None => tf("log.benchmark_failed", &[("element", &element)]),
+        });
+        drop(s);
+        ctx.request_repaint();
+    });
+
+    state.lock().unwrap().benchmarking = false;
+    ctx.request_repaint();
 }
 
-/// True if anything is currently listening on TCP:7879 (fast path check).
-fn port_is_busy() -> bool {
-    std::net::TcpListener::bind("0.0.0.0:7879").is_err()
+/// Warn if the firewall may keep senders out of `ports`; the GUI then
+/// offers to open them.
+async fn check_firewall(state: SharedState, ctx: egui::Context, ports: PortMap) {
+    let ports = receiver_ports(&ports);
+    let Ok(check) = tokio::task::spawn_blocking(move || FirewallCheck::run(&ports)).await else { return };
+    // The ports are bound, so privileged ones are no problem here.
+    let (Some(firewall), false) = (check.firewall, check.blocked.is_empty()) else { return };
+    let mut s = state.lock().unwrap();
+    s.push_log(tf("log.firewall_blocked", &[("firewall", &firewall), ("ports", &check.blocked_list())]));
+    s.firewall = Some(check);
+    drop(s);
+    ctx.request_repaint();
+}
+
+/// Open the ports of the pending firewall check, after the user clicked
+/// "Open ports". Blocking (the password prompt) — run on its own thread.
+pub fn open_firewall(state: SharedState, ctx: egui::Context) {
+    let Some(check) = state.lock().unwrap().firewall.clone() else { return };
+    let Some(firewall) = check.firewall else { return };
+    state.lock().unwrap().firewall_opening = true;
+    ctx.request_repaint();
+
+    let result = firewall.open(&check.blocked);
+    let mut s = state.lock().unwrap();
+    s.firewall_opening = false;
+    match result {
+        Ok(()) => {
+            s.firewall = None;
+            s.push_log(tf("log.firewall_opened", &[("ports", &check.blocked_list())]));
+        }
+        Err(e) => s.push_log(tf("log.firewall_open_failed", &[("error", &e)])),
+    }
+    drop(s);
+    ctx.request_repaint();
+}
+
+/// Write a diagnostics bundle with the GUI log and session stats into the
+/// home directory and log where it went. Blocking — run on its own thread.
+pub fn export_diagnostics(state: SharedState, ctx: egui::Context) {
+    let mut report = DiagnosticsReport::collect(env!("CARGO_PKG_VERSION"));
+    {
+        let s = state.lock().unwrap();
+        report.logs = s.logs.iter().cloned().collect();
+        report.session = s.session_stats();
+        report.selected_decoder = s.decoder.clone();
+    }
+    fill_diagnostics(&mut report);
+    let dir = home_dir().unwrap_or_else(|| PathBuf::from("."));
+    let line = match report.write_to(&dir) {
+        Ok(path) => tf("log.diagnostics_written", &[("path", &path.display())]),
+        Err(e) => tf("log.diagnostics_failed", &[("error", &e)]),
+    };
+    state.lock().unwrap().push_log(line);
+    ctx.request_repaint();
 }
 
 // ── Entry point (called from the tokio runtime thread) ─────────────────────────
@@ -53,48 +139,49 @@ pub async fn run(state: SharedState, ctx: egui::Context) {
         match detect_usb_ethernet() {
             Some(usb) => {
                 s.transport = format!("USB ({})", usb.local_ip);
-                s.push_log(format!(
-                    "USB Ethernet detected: {} → {} (peer {})",
-                    usb.interface_name, usb.local_ip, usb.peer_ip
+                s.network = NetworkKind::UsbTether;
+                s.push_log(tf(
+                    "log.usb",
+                    &[("interface", &usb.interface_name), ("ip", &usb.local_ip), ("peer", &usb.peer_ip)],
                 ));
             }
             None => {
                 s.transport = "Wi-Fi".into();
-                s.push_log("No USB Ethernet interface found — using Wi-Fi transport");
+                s.network = NetworkKind::Wifi;
+                s.push_log(t("log.wifi"));
             }
         }
-        s.push_log("Binding UDP:7878 (video) + TCP:7879 (signaling)…");
+        s.decoder_preference = DecoderFactory::from_settings().preference().to_vec();
+        s.allow_input = configured_allow_input();
+        s.push_log(t("log.binding"));
     }
     ctx.request_repaint();
+    {
+        let (st, c) = (state.clone(), ctx.clone());
+        tokio::task::spawn_blocking(move || probe_decoders(st, c));
+    }
 
-    // ── Step 0b: unconditionally release ports before binding ─────────────
-    if tokio::task::spawn_blocking(port_is_busy).await.unwrap_or(false) {
-        {
-            let mut s = state.lock().unwrap();
-            s.push_log(format!("Port 7879 busy — stopping {} and killing port holders…", SERVICE_NAME));
-        }
-        ctx.request_repaint();
-
-        tokio::task::spawn_blocking(|| {
-            stop_systemd_service();
-            fuser_kill_ports();
-        }).await.ok();
-
-        // Wait up to 1.5 s in 150 ms steps for the port to free
-        for _ in 0..10 {
-            tokio::time::sleep(std::time::Duration::from_millis(150)).await;
-            let still_busy = tokio::task::spawn_blocking(port_is_busy).await.unwrap_or(true);
-            if !still_busy {
-                break;
-            }
+    // ── Step 0b: take the ports over from a running receiver service ──────
+    // The service hands its bound sockets over on its control socket and
+    // exits; senders reconnect to us without the ports ever closing.
+    let adopted = match request_handover().await {
+        Ok(Some(sockets)) => {
+            state.lock().unwrap().push_log(tf(
+                "log.took_over",
+                &[("count", &sockets.len()), ("service", &SERVICE_NAME)],
+            ));
+            sockets
         }
-
-        {
-            let mut s = state.lock().unwrap();
-            s.push_log("Ports released — binding…".to_string());
+        Ok(None) => Vec::new(),
+        Err(e) => {
+            state.lock().unwrap().push_log(tf(
+                "log.handover_failed",
+                &[("service", &SERVICE_NAME), ("error", &format!("{e:#}"))],
+            ));
+            Vec::new()
         }
-        ctx.request_repaint();
-    }
+    };
+    ctx.request_repaint();
 
     // ── Step 1: bind ports, generate PIN / TLS key, start all displays ────
     let display_count: u8 = std::env::var("DUALLINK_DISPLAY_COUNT")
@@ -104,20 +191,19 @@ pub async fn run(state: SharedState, ctx: egui::Context) {
         .max(1)
         .min(8);
 
-    let (recv, mut channels, input_sender, startup) =
-        match DualLinkReceiver::start_all(display_count).await {
+    let base_port = configured_base_port();
+    let configs = (0..display_count).map(|n| DisplayConfig::with_base_port(n, base_port)).collect();
+    let (recv, channels, input_sender, startup) =
+        match DualLinkReceiver::start_with_sockets(configs, receiver_capabilities(), adopted).await {
             Ok(v) => v,
             Err(e) => {
                 let msg = e.to_string();
                 let hint = if msg.contains("Address already in use") {
-                    format!(
-                        "[ERROR] Port still in use after auto-stop. Run manually:\n\
-                         systemctl --user stop {SERVICE_NAME}\n\
-                         sudo fuser -k 7878/udp 7879/tcp\n\
-                         Then reopen the GUI."
-                    )
+                    tf("log.ports_in_use", &[("service", &SERVICE_NAME)])
+                } else if msg.contains("Permission denied") && base_port < 1024 {
+                    tf("log.ports_privileged", &[("port", &base_port)])
                 } else {
-                    format!("[ERROR] Failed to start receiver: {}", msg)
+                    tf("log.start_failed", &[("error", &msg)])
                 };
                 let mut s = state.lock().unwrap();
                 s.phase = Phase::Error(msg);
@@ -126,18 +212,17 @@ pub async fn run(state: SharedState, ctx: egui::Context) {
                 return;
             }
         };
-    // Keep `recv` alive for the lifetime of the process so background tasks
-    // are not dropped.
-    let _recv = recv;
+    // Kept alive for the lifetime of the process (shared with the display
+    // manager below) so background tasks are not dropped.
+    let recv = Arc::new(recv);
 
     // ── Step 2: detect LAN IP and advertise via mDNS ─────────────────────
     let local_ip = detect_local_ip();
     let lan_ip_str = local_ip.to_string();
 
-    let _advertiser = DualLinkAdvertiser::register(
+    let advertiser = DualLinkAdvertiser::register(
         "DualLink Receiver",
-        display_count,
-        SIGNALING_PORT,
+        &recv.port_map(),
         local_ip,
         &startup.tls_fingerprint,
     )
@@ -150,336 +235,610 @@ pub async fn run(state: SharedState, ctx: egui::Context) {
         s.tls_fingerprint = startup.tls_fingerprint.clone();
         s.phase           = Phase::WaitingForClient;
         s.lan_ip          = lan_ip_str.clone();
-        s.mdns_active     = _advertiser.is_some();
+        s.mdns_active     = advertiser.is_some();
         s.display_count   = display_count;
-        s.push_log(format!("Pairing PIN : {}", startup.pairing_pin));
-        s.push_log(format!(
-            "TLS fingerprint: {}…",
-            &startup.tls_fingerprint[..startup.tls_fingerprint.len().min(32)]
+        s.push_log(tf("log.pin", &[("pin", &startup.pairing_pin)]));
+        s.push_log(tf(
+            "log.fingerprint",
+            &[("fingerprint", &&startup.tls_fingerprint[..startup.tls_fingerprint.len().min(32)])],
         ));
-        s.push_log(format!("LAN IP : {}  (mDNS: {})", lan_ip_str, if _advertiser.is_some() { "active" } else { "unavailable" }));
-        s.push_log(format!("Display streams: {}", display_count));
-        s.push_log("Ready — waiting for macOS DualLink client…");
+        let mdns = t(if advertiser.is_some() { "log.mdns_active" } else { "log.mdns_unavailable" });
+        s.push_log(tf("log.lan_ip", &[("ip", &lan_ip_str), ("mdns", &mdns)]));
+        s.push_log(tf("log.display_streams", &[("count", &display_count)]));
+        s.push_log(tf("log.ports", &[("ports", &recv.port_map().to_txt())]));
+        s.push_log(t("log.ready"));
     }
     ctx.request_repaint();
-
-    // ── Step 3: spawn GUI-less loops for displays 1+ ─────────────────────
-    // Display 0 is handled below (integrated with GUI state); displays 1+
-    // run the same session-reconnect pattern but without GUI state updates.
+    tokio::spawn(check_firewall(Arc::clone(&state), ctx.clone(), recv.port_map()));
+
+    // Session hooks see every display's events before its session loop does.
+    let hooks = Hooks::load();
+    let mut channels: Vec<DisplayChannels> = channels
+        .into_iter()
+        .map(|mut ch| {
+            ch.event_rx = hooks.tap(ch.display_index, ch.event_rx);
+            ch
+        })
+        .collect();
+
+    // ── Step 3: spawn background loops for displays 1+ ───────────────────
+    // Display 0 is handled below (drives the main status and stats cards);
+    // displays 1+ run the same session-reconnect pattern and report into
+    // their own `GuiState::displays` card.
     let extra_channels: Vec<DisplayChannels> = channels.drain(1..).collect();
     for ch in extra_channels {
         let is = input_sender.clone();
+        let st = state.clone();
+        let c = ctx.clone();
         tokio::spawn(async move {
-            run_background_display(ch, is).await;
+            run_background_display(ch, is, st, c).await;
         });
     }
 
+    tokio::spawn(watch_power(state.clone(), ctx.clone()));
+    tokio::spawn(run_display_manager(Arc::clone(&recv), advertiser, input_sender.clone(), hooks, state.clone(), ctx.clone()));
+
     // ── Step 4: display-0 session loop (GUI-integrated) ──────────────────
-    let ch0 = match channels.into_iter().next() {
-        Some(ch) => ch,
-        None => {
-            let mut s = state.lock().unwrap();
-            s.phase = Phase::Error("No display channels returned".into());
-            ctx.request_repaint();
-            return;
-        }
+    let Some(ch0) = channels.into_iter().next() else {
+        let mut s = state.lock().unwrap();
+        s.phase = Phase::Error(t("log.no_channels").into());
+        ctx.request_repaint();
+        return;
     };
+    let session = GuiSession::new(0, Arc::clone(&state), ctx.clone(), ch0.files.clone());
+    ReceiverSession::new(ch0, input_sender, session)
+        .with_stats_sink(GuiStats::new(0, Arc::clone(&state)))
+        .run()
+        .await;
+}
+
+// ── Session hooks ─────────────────────────────────────────────────────────────
+
+/// [`SessionHooks`] reporting one display's sessions into the GUI: display 0
+/// through the top-level [`GuiState`](crate::state::GuiState) fields, displays
+/// 1+ through their [`GuiState::displays`](crate::state::GuiState) card.
+struct GuiSession {
+    display: u8,
+    state:   SharedState,
+    ctx:     egui::Context,
+    files:   FileTransfers,
+}
+
+impl GuiSession {
+    fn new(display: u8, state: SharedState, ctx: egui::Context, files: FileTransfers) -> Self {
+        Self { display, state, ctx, files }
+    }
+
+    fn log(&self, line: impl Into<String>) {
+        self.state.lock().unwrap().push_log(line);
+        self.ctx.request_repaint();
+    }
+}
+
+impl SessionHooks for GuiSession {
+    fn opener(&mut self, config: &StreamConfig, excluded: &[String]) -> Opener {
+        let factory = decoder_factory(&self.state).excluding(excluded);
+        let config = config.clone();
+        Box::new(move || factory.decoder_for(&config))
+    }
 
-    let DisplayChannels { mut frame_rx, mut event_rx, .. } = ch0;
-
-    // Pending config forwarded from a mid-session ConfigUpdated (hot-reload).
-    let mut pending_config: Option<StreamConfig> = None;
-
-    'reconnect: loop {
-        // ── 4a: wait for a client to connect (unless hot-reload) ─────────
-        let (config, device_name, client_addr) = if let Some(cfg) = pending_config.take() {
-            // Hot-reload: re-init decoder with new resolution in flying session
-            let s = state.lock().unwrap();
-            info!("Display[0] Hot-reload: new config {:?}", cfg);
-            if let Phase::Streaming { ref peer_name, ref peer_addr } = s.phase {
-                let pn = peer_name.clone();
-                let pa = peer_addr.clone();
-                drop(s);
-                let addr = pa.parse().unwrap_or_else(|_| "0.0.0.0:0".parse().unwrap());
-                (cfg, pn, addr)
+    fn on_frame(&mut self) -> Box<dyn FnMut(usize) + Send> {
+        let (display, state, ctx) = (self.display, Arc::clone(&self.state), self.ctx.clone());
+        Box::new(move |bytes| {
+            let mut s = state.lock().unwrap();
+            // Promote phase to Streaming on first successfully decoded frame
+            let decoded = if display == 0 {
+                if let Phase::Connected { peer_name, peer_addr } = s.phase.clone() {
+                    s.phase = Phase::Streaming { peer_name, peer_addr };
+                }
+                s.tick_frame(bytes);
+                s.frames_decoded
             } else {
-                drop(s);
-                (cfg, "[hot-reload]".into(), "0.0.0.0:0".parse().unwrap())
-            }
-        } else {
-            loop {
-                match event_rx.recv().await {
-                    Some(SignalingEvent::SessionStarted {
-                        device_name,
-                        config,
-                        client_addr,
-                        ..
-                    }) => {
-                        break (config, device_name, client_addr);
-                    }
-                    Some(SignalingEvent::ClientDisconnected) => {
-                        let mut s = state.lock().unwrap();
-                        s.push_log("Client disconnected before completing pairing");
-                        ctx.request_repaint();
-                    }
-                    None => return, // All senders dropped → process shutting down
-                    _ => {}
+                let d = s.displays.entry(display).or_default();
+                if let Phase::Connected { peer_name, peer_addr } = d.phase.clone() {
+                    d.phase = Phase::Streaming { peer_name, peer_addr };
                 }
+                d.tick_frame();
+                d.frames_decoded
+            };
+            drop(s);
+            // Repaint the GUI roughly every 30 decoded frames (~2× per second at 60 fps)
+            if decoded % 30 == 0 {
+                ctx.request_repaint();
             }
-        };
+        })
+    }
 
-        {
-            let mut s = state.lock().unwrap();
-            s.phase = Phase::Connected {
-                peer_name: device_name.clone(),
-                peer_addr: client_addr.to_string(),
-            };
+    fn session_started(&mut self, session: &SessionInfo, _config: &StreamConfig) {
+        let n = self.display;
+        let phase = Phase::Connected {
+            peer_name: session.device_name.clone(),
+            peer_addr: session.client_addr.to_string(),
+        };
+        let (name, addr) = (&session.device_name, &session.client_addr);
+        let mut s = self.state.lock().unwrap();
+        if n == 0 {
+            s.phase = phase;
             s.frames_received = 0;
-            s.push_log(format!(
-                "Client '{}' connected from {}",
-                device_name, client_addr
-            ));
+            s.view_only_session = !session.allow_input;
+            let key = if session.allow_input { "log.client_connected" } else { "log.client_connected_view_only" };
+            s.push_log(tf(key, &[("name", name), ("addr", addr)]));
+        } else {
+            let key = if session.allow_input { "log.display_connected" } else { "log.display_connected_view_only" };
+            s.push_log(tf(key, &[("n", &n), ("name", name), ("addr", addr)]));
+            let d = s.displays.entry(n).or_default();
+            d.phase = phase;
+            d.frames_received = 0;
         }
-        ctx.request_repaint();
+        drop(s);
+        self.ctx.request_repaint();
+    }
 
-        // ── 4b: spawn decode+display thread ──────────────────────────────
-        //
-        // GStreamer MUST be initialised and used on a single OS thread
-        // (it creates a display window + message loop).  We use
-        // spawn_blocking so Tokio does not timeslice us off.
-        let width  = config.resolution.width;
-        let height = config.resolution.height;
-        let (decode_tx, mut decode_rx) =
-            tokio::sync::mpsc::channel::<EncodedFrame>(64);
-
-        let state2     = Arc::clone(&state);
-        let ctx2       = ctx.clone();
-        let input_fwd  = input_sender.clone();
-        let push_errors = Arc::new(AtomicU64::new(0));
-        let pe2 = Arc::clone(&push_errors);
-
-        let decode_handle = tokio::task::spawn_blocking(move || {
-            // Create decoder (and start GStreamer pipeline / video window).
-            let decoder = match DecoderFactory::best_available_with_display(width, height) {
-                Ok(d) => d,
-                Err(e) => {
-                    let mut s = state2.lock().unwrap();
-                    s.push_log(format!("[ERROR] Decoder init: {}", e));
-                    ctx2.request_repaint();
-                    return;
-                }
-            };
+    fn disconnected_before_hello(&mut self) {
+        if self.display == 0 {
+            self.log(t("log.disconnected_before_pairing"));
+        }
+    }
 
-            {
-                let mut s = state2.lock().unwrap();
-                s.push_log(format!(
-                    "Decoder: {} (hw={})",
-                    decoder.element_name(),
-                    decoder.is_hardware_accelerated()
-                ));
-            }
-            ctx2.request_repaint();
-
-            // Frame loop
-            while let Some(frame) = decode_rx.blocking_recv() {
-                let bytes = frame.data.len();
-                let kf    = frame.is_keyframe;
-                match decoder.push_frame(frame) {
-                    Ok(()) => {
-                        pe2.fetch_add(0, Ordering::Relaxed); // no-op to keep pe2 alive
-                        let mut s = state2.lock().unwrap();
-                        // Promote phase to Streaming on first successfully decoded frame
-                        if let Phase::Connected { peer_name, peer_addr } = s.phase.clone() {
-                            s.phase = Phase::Streaming { peer_name, peer_addr };
-                        }
-                        s.tick_frame(bytes);
-                        let fd = s.frames_decoded;
-                        drop(s);
-                        // Repaint the GUI roughly every 30 decoded frames (~2× per second at 60 fps)
-                        if fd % 30 == 0 {
-                            ctx2.request_repaint();
-                        }
-                    }
-                    Err(e) => {
-                        let errs = pe2.fetch_add(1, Ordering::Relaxed) + 1;
-                        if errs <= 10 || errs % 120 == 0 {
-                            let mut s = state2.lock().unwrap();
-                            s.push_log(format!("[WARN] Decode error #{} ({} bytes kf={}): {}", errs, bytes, kf, e));
-                        }
-                    }
-                }
+    fn decoder_ready(&mut self, decoder: &AsyncDecoder) {
+        let element = decoder.element_name().to_string();
+        let mut s = self.state.lock().unwrap();
+        if self.display == 0 {
+            s.push_log(tf("log.decoder", &[("element", &element), ("hw", &decoder.is_hardware_accelerated())]));
+            s.decoder = Some(element);
+        } else {
+            s.displays.entry(self.display).or_default().decoder = Some(element);
+        }
+        drop(s);
+        self.ctx.request_repaint();
+    }
 
-                // Forward any mouse/keyboard events captured inside the video window
-                for event in decoder.poll_input_events() {
-                    let _ = input_fwd.try_send(event);
-                }
+    fn decoder_init_failed(&mut self, error: &DecoderError) {
+        let mut s = self.state.lock().unwrap();
+        s.push_log(tf("log.decoder_init_failed", &[("n", &self.display), ("error", error)]));
+        if self.display != 0 {
+            s.displays.entry(self.display).or_default().phase = Phase::Error(error.to_string());
+        }
+        drop(s);
+        self.ctx.request_repaint();
+    }
+
+    fn decoder_failed(&mut self, element: &str, error: &DecoderError) {
+        let n = self.display;
+        let mut s = self.state.lock().unwrap();
+        if let DecoderError::Pipeline { source_element, message, debug } = error {
+            s.push_log(tf(
+                "log.decoder_pipeline",
+                &[("n", &n), ("element", source_element), ("message", message)],
+            ));
+            if let (0, Some(debug)) = (n, debug) {
+                s.push_log(format!("[ERROR]   {debug}"));
             }
+        } else if let DecoderError::Panicked(message) = error {
+            s.push_log(tf("log.decoder_panicked", &[("n", &n), ("element", &element), ("message", message)]));
+        }
+        s.push_log(tf("log.decoder_failed_next", &[("n", &n), ("element", &element)]));
+        drop(s);
+        self.ctx.request_repaint();
+    }
 
-            info!("Decode thread exiting");
+    fn config_updated(&mut self, current: &StreamConfig, new: &StreamConfig, reload: Option<Reload>) {
+        if self.display != 0 {
+            return;
+        }
+        self.log(match reload {
+            Some(Reload::Resolution) => {
+                tf("log.resolution_change", &[("from", &current.resolution), ("to", &new.resolution)])
+            }
+            Some(Reload::Lossless) => t(if new.lossless { "log.lossless_on" } else { "log.lossless_off" }).into(),
+            None => tf("log.config_update", &[("resolution", &new.resolution), ("fps", &new.target_fps)]),
         });
+    }
+
+    fn coded_size_mismatch(&mut self, configured: Resolution, coded: Resolution) {
+        let n = self.display;
+        self.log(tf("log.coded_size_mismatch", &[("n", &n), ("configured", &configured), ("coded", &coded)]));
+    }
 
-        // ── 4c: receive + forward frame loop ─────────────────────────────
-        let session_exit_reason = loop {
-            tokio::select! {
-                frame = frame_rx.recv() => {
-                    let Some(frame) = frame else {
-                        // frame_rx closed → process shutting down
-                        drop(decode_tx);
-                        let _ = decode_handle.await;
-                        return;
-                    };
-                    {
-                        let mut s = state.lock().unwrap();
-                        s.frames_received += 1;
+    fn event(&mut self, event: &SignalingEvent) {
+        let n = self.display;
+        let mut s = self.state.lock().unwrap();
+        match event {
+            SignalingEvent::SessionStopped { summary, .. } if n == 0 => {
+                s.push_log(tf("log.session_usage", &[
+                    ("mb", &format!("{:.1}", summary.total_bytes() as f64 / 1e6)),
+                    ("avg", &summary.average_kbps),
+                    ("peak", &summary.peak_kbps),
+                ]));
+            }
+            SignalingEvent::ReceiverDisplayChanged { monitor, monitors } if n == 0 => {
+                s.push_log(match monitor {
+                    Some(m) => tf("log.monitors_changed_on", &[("count", &monitors.len()), ("monitor", &m.name)]),
+                    None => tf("log.monitors_changed", &[("count", &monitors.len())]),
+                });
+            }
+            SignalingEvent::Blank { enabled } => {
+                let key = if *enabled { "log.sender_blanked" } else { "log.sender_unblanked" };
+                s.push_log(tf(key, &[("n", &n)]));
+            }
+            SignalingEvent::SenderPower { power } => {
+                s.push_log(tf("log.sender_power", &[("n", &n), ("power", power)]));
+                if n == 0 {
+                    s.sender_power = Some(*power);
+                }
+            }
+            SignalingEvent::DisplayState { paused } => {
+                let key = if *paused { "log.sender_paused" } else { "log.sender_resumed" };
+                s.push_log(tf(key, &[("n", &n)]));
+            }
+            SignalingEvent::File(event) => {
+                match event {
+                    FileTransferEvent::Offered(offer) => s.push_log(tf("log.file_offered", &[
+                        ("n", &n),
+                        ("name", &offer.name),
+                        ("mb", &format!("{:.1}", offer.size as f64 / 1e6)),
+                    ])),
+                    FileTransferEvent::Received { name, path, .. } => {
+                        s.push_log(tf("log.file_received", &[("name", name), ("path", &path.display())]));
                     }
-                    if decode_tx.send(frame).await.is_err() {
-                        warn!("Decode thread gone — stopping session");
-                        break "decode_thread_gone";
+                    FileTransferEvent::Sent { name, .. } => s.push_log(tf("log.file_sent", &[("name", name)])),
+                    FileTransferEvent::Failed { name, reason, .. } => {
+                        s.push_log(tf("log.file_failed", &[("name", name), ("reason", reason)]));
                     }
+                    FileTransferEvent::Progress { .. } => {}
                 }
-
-                event = event_rx.recv() => {
-                    match event {
-                        Some(SignalingEvent::SessionStopped { session_id }) => {
-                            info!("Session {} stopped by sender", session_id);
-                            break "session_stopped";
-                        }
-                        Some(SignalingEvent::ClientDisconnected) | None => {
-                            warn!("Client disconnected");
-                            break "client_disconnected";
-                        }
-                        Some(SignalingEvent::ConfigUpdated { config: new_cfg }) => {
-                            let cur_w = config.resolution.width;
-                            let cur_h = config.resolution.height;
-                            if new_cfg.resolution.width != cur_w || new_cfg.resolution.height != cur_h {
-                                let mut s = state.lock().unwrap();
-                                s.push_log(format!(
-                                    "Resolution change {}×{} → {}×{}: hot-reloading decoder",
-                                    cur_w, cur_h,
-                                    new_cfg.resolution.width, new_cfg.resolution.height
-                                ));
-                                drop(s);
-                                ctx.request_repaint();
-                                pending_config = Some(new_cfg);
-                                break "config_updated";
-                            } else {
-                                let mut s = state.lock().unwrap();
-                                s.push_log(format!(
-                                    "Config update: {}×{} @ {} fps",
-                                    new_cfg.resolution.width,
-                                    new_cfg.resolution.height,
-                                    new_cfg.target_fps
-                                ));
-                            }
-                        }
-                        _ => {}
-                    }
+                s.record_transfer(n, event.clone());
+            }
+            SignalingEvent::StreamStalled { silent } => {
+                s.push_log(tf("log.stream_stalled", &[("n", &n), ("secs", &silent.as_secs())]));
+                if n == 0 {
+                    s.stalled = true;
+                } else {
+                    s.displays.entry(n).or_default().stalled = true;
                 }
             }
-        };
+            _ => return,
+        }
+        drop(s);
+        self.ctx.request_repaint();
+    }
 
-        // Drop sender → decode thread will drain and exit
-        drop(decode_tx);
-        let _ = decode_handle.await;
+    fn ended_from_window(&mut self) {
+        self.log(tf("log.ended_from_window", &[("n", &self.display)]));
+    }
 
-        info!("Display[0] session exit: {}", session_exit_reason);
+    fn visibility_changed(&mut self, hidden: bool) {
+        self.log(if hidden {
+            tf("log.window_hidden", &[("n", &self.display), ("fps", &HIDDEN_FPS)])
+        } else {
+            tf("log.window_shown", &[("n", &self.display)])
+        });
+    }
 
-        if session_exit_reason == "channels_closed" {
-            break 'reconnect;
+    fn tick(&mut self, stats: &DecoderStats, paused: bool) -> Vec<SessionAction> {
+        let n = self.display;
+        let mut s = self.state.lock().unwrap();
+        // Also toggled by the hotkeys in the window.
+        if n == 0 {
+            s.frozen = stats.frozen;
+            s.blanked = stats.blanked;
+            s.paused = paused;
+            s.system_keys = stats.system_keys;
+            s.update_unique(stats.frames_unique, stats.duplicates);
+        } else {
+            let d = s.displays.entry(n).or_default();
+            d.frozen = stats.frozen;
+            d.blanked = stats.blanked;
+            d.paused = paused;
+            d.system_keys = stats.system_keys;
+            d.update_unique(stats.frames_unique, stats.duplicates);
         }
+        let actions: Vec<SessionAction> = [
+            (DisplayAction::ToggleFreeze, SessionAction::ToggleFreeze),
+            (DisplayAction::ToggleBlank, SessionAction::ToggleBlank),
+            (DisplayAction::TogglePause, SessionAction::TogglePause),
+            (DisplayAction::ToggleSystemKeys, SessionAction::ToggleSystemKeys),
+            (DisplayAction::RestartDecoder, SessionAction::RestartDecoder),
+        ]
+        .into_iter()
+        .filter(|&(requested, _)| s.take_action(n, requested))
+        .map(|(_, action)| action)
+        .collect();
+        for request in s.take_file_requests(n) {
+            match request {
+                FileRequest::Send(path) => match self.files.send(&path) {
+                    Ok(offer) => s.push_log(tf("log.file_offering", &[("n", &n), ("name", &offer.name)])),
+                    Err(e) => s.push_log(tf("log.file_unreadable", &[("path", &path.display()), ("error", &e)])),
+                },
+                FileRequest::Answer { transfer_id, accept } => self.files.answer(&transfer_id, accept),
+            }
+        }
+        if actions.contains(&SessionAction::RestartDecoder) {
+            s.push_log(tf("log.restarting_decoder", &[("n", &n)]));
+            drop(s);
+            self.ctx.request_repaint();
+        }
+        actions
+    }
 
-        // If hot-reload: pending_config is already set; skip the reset below.
-        if session_exit_reason != "config_updated" {
-            // ── 4d: reset for next session ────────────────────────────────
-            {
-                let mut s = state.lock().unwrap();
-                s.phase = Phase::WaitingForClient;
-                s.reset_stats();
-                let pin = s.pairing_pin.clone();
-                s.push_log("Client disconnected — waiting for new connection…");
-                s.push_log(format!("Pairing PIN still valid: {}", pin));
+    fn session_ended(&mut self, reason: ExitReason, next: Next, _totals: &DecoderStats) {
+        if next != Next::WaitForSender {
+            return;
+        }
+        let n = self.display;
+        let mut s = self.state.lock().unwrap();
+        if n == 0 {
+            // Back to waiting-for-client between sessions.
+            s.phase = Phase::WaitingForClient;
+            s.reset_stats();
+            let pin = s.pairing_pin.clone();
+            s.push_log(t("log.client_disconnected"));
+            s.push_log(tf("log.pin_still_valid", &[("pin", &pin)]));
+        } else if reason != ExitReason::DecoderInitFailed {
+            // A failed decoder init stays on the card until the next sender.
+            s.push_log(tf("log.session_ended", &[("n", &n), ("reason", &reason)]));
+            let d = s.displays.entry(n).or_default();
+            d.phase = Phase::WaitingForClient;
+            d.reset_stats();
+        }
+        drop(s);
+        self.ctx.request_repaint();
+    }
+}
+
+/// [`StatsSink`] counting one display's received frames into the GUI, and
+/// sampling display 0's rejected frames into the log.
+struct GuiStats {
+    display:         u8,
+    state:           SharedState,
+    /// Rejected frames already sampled into the log (display 0).
+    reported_errors: u64,
+}
+
+impl GuiStats {
+    fn new(display: u8, state: SharedState) -> Self {
+        Self { display, state, reported_errors: 0 }
+    }
+}
+
+impl StatsSink for GuiStats {
+    fn on_frame(&mut self, frame: &FrameSample) {
+        let mut s = self.state.lock().unwrap();
+        if self.display != 0 {
+            if let Some(d) = s.displays.get_mut(&self.display) {
+                d.frames_received += 1;
             }
-            ctx.request_repaint();
+            return;
+        }
+        s.frames_received += 1;
+        // Rejected frames are counted on the decode thread; log a sample here.
+        let (errs, bytes, keyframe) = (frame.errors, frame.bytes, frame.keyframe);
+        if errs > self.reported_errors {
+            s.decode_errors = errs;
+            self.reported_errors = errs;
+            let line = tf("log.decode_errors", &[("count", &errs), ("bytes", &bytes), ("keyframe", &keyframe)]);
+            s.push_log_limited(t("log.decode_error"), line);
+        }
+    }
 
-            // Brief pause so the OS has time to clean up the prior TCP conn
-            tokio::time::sleep(Duration::from_millis(300)).await;
+    fn on_session_event(&mut self, event: &SessionEvent) {
+        if let SessionEvent::Ended { .. } = event {
+            self.reported_errors = 0;
         }
     }
 }
 
-// ── Background display loop (no GUI state) ────────────────────────────────────
+// ── Power ─────────────────────────────────────────────────────────────────────
 
-/// Handles one extra display (index ≥ 1) without touching the GUI state.
-async fn run_background_display(ch: DisplayChannels, input_sender: InputSender) {
-    let DisplayChannels { display_index, mut frame_rx, mut event_rx } = ch;
-    let mut pending_config: Option<StreamConfig> = None;
+/// Re-reads this machine's power source every [`POWER_POLL_INTERVAL`] into
+/// [`GuiState::power`](crate::state::GuiState) for the status card and the
+/// log.
+async fn watch_power(state: SharedState, ctx: egui::Context) {
+    let mut tick = tokio::time::interval(POWER_POLL_INTERVAL);
+    loop {
+        tick.tick().await;
+        let Ok(power) = tokio::task::spawn_blocking(read_power).await else { continue };
+        let mut s = state.lock().unwrap();
+        if s.power != power {
+            if let Some(p) = power {
+                s.push_log(tf("log.receiver_power", &[("power", &p)]));
+            }
+            s.power = power;
+            drop(s);
+            ctx.request_repaint();
+        }
+    }
+}
 
-    'reconnect: loop {
-        // Wait for SessionStarted or use hot-reload config
-        let config = if let Some(cfg) = pending_config.take() {
-            cfg
-        } else {
-            loop {
-                match event_rx.recv().await {
-                    Some(SignalingEvent::SessionStarted { config, .. }) => break config,
-                    Some(SignalingEvent::ClientDisconnected) => {
-                        warn!("Display[{}] disconnected before hello", display_index);
-                    }
-                    None => {
-                        info!("Display[{}] channel closed — exiting", display_index);
-                        break 'reconnect;
-                    }
-                    _ => {}
+// ── Background display loops ──────────────────────────────────────────────────
+
+/// Applies display add/remove requests from the GUI's +/− buttons,
+/// disconnect requests from the display cards, the input toggle, the
+/// "New PIN" button and the input macro controls, through the receiver's
+/// [`ReceiverHandle`](duallink_transport::ReceiverHandle).
+///
+/// Display 0 drives the GUI and is never removed. Also owns the mDNS
+/// advertiser so the advertised display count follows the change.
+async fn run_display_manager(
+    recv: Arc<DualLinkReceiver>,
+    mut advertiser: Option<DualLinkAdvertiser>,
+    input_sender: InputSender,
+    hooks: Hooks,
+    state: SharedState,
+    ctx: egui::Context,
+) {
+    let handle = recv.handle();
+    let mut tick = tokio::time::interval(Duration::from_millis(250));
+    let mut replay: Option<ReplayHandle> = None;
+    loop {
+        tick.tick().await;
+        let (request, disconnects, macro_request) = {
+            let mut s = state.lock().unwrap();
+            if s.allow_input != handle.allow_input() {
+                handle.set_allow_input(s.allow_input);
+            }
+            if std::mem::take(&mut s.pin_request) {
+                let pin = handle.regenerate_pin();
+                s.push_log(tf("log.new_pin", &[("pin", &pin)]));
+            }
+            let receiver = handle.state();
+            if s.pairing_pin != receiver.pairing_pin {
+                s.pairing_pin = receiver.pairing_pin.clone();
+                ctx.request_repaint();
+            }
+            let display_pins: BTreeMap<u8, String> = receiver
+                .displays
+                .iter()
+                .filter_map(|d| Some((d.display_index, d.pairing_pin.clone()?)))
+                .collect();
+            if s.display_pins != display_pins {
+                s.display_pins = display_pins;
+                ctx.request_repaint();
+            }
+            let replaying = replay.as_ref().is_some_and(|r| !r.is_finished());
+            let recording = input_sender.recorded();
+            if (recording, replaying) != (s.macro_recording, s.macro_replaying) {
+                s.macro_recording = recording;
+                s.macro_replaying = replaying;
+                ctx.request_repaint();
+            }
+            for n in handle.display_indices() {
+                let Some(stats) = handle.stats(n) else { continue };
+                if n == 0 {
+                    s.frame_stats = stats.frames;
+                    let r = stats.reassembly;
+                    s.reassembly_drops = r.evicted + r.budget_evicted + r.corrupted;
+                    s.queueing_delay = stats.queueing_delay;
+                } else if let Some(d) = s.displays.get_mut(&n) {
+                    d.frame_stats = stats.frames;
                 }
             }
+            let mut disconnects = Vec::new();
+            s.pending_actions.retain(|&(n, action)| {
+                let take = action == DisplayAction::Disconnect;
+                if take { disconnects.push(n); }
+                !take
+            });
+            (s.display_request.take(), disconnects, s.macro_request.take())
         };
-
-        let width  = config.resolution.width;
-        let height = config.resolution.height;
-        let (decode_tx, mut decode_rx) = tokio::sync::mpsc::channel::<EncodedFrame>(64);
-        let is2 = input_sender.clone();
-
-        let handle = tokio::task::spawn_blocking(move || {
-            if let Ok(dec) = DecoderFactory::best_available_with_display(width, height) {
-                while let Some(frame) = decode_rx.blocking_recv() {
-                    let _ = dec.push_frame(frame);
-                    for ev in dec.poll_input_events() {
-                        let _ = is2.try_send(ev);
-                    }
+        if let Some(request) = macro_request {
+            let line = apply_macro_request(request, &input_sender, &mut replay).await;
+            state.lock().unwrap().push_log(line);
+            ctx.request_repaint();
+        }
+        for n in disconnects {
+            let line = if handle.disconnect_client(n) {
+                tf("log.disconnecting", &[("n", &n)])
+            } else {
+                tf("log.not_running", &[("n", &n)])
+            };
+            state.lock().unwrap().push_log(line);
+            ctx.request_repaint();
+        }
+        let Some(request) = request else { continue };
+
+        let line = match request {
+            DisplayRequest::Add => match recv.add_display().await {
+                Ok(mut ch) => {
+                    ch.event_rx = hooks.tap(ch.display_index, ch.event_rx);
+                    let line = tf("log.display_added", &[("n", &ch.display_index)]);
+                    let is = input_sender.clone();
+                    let st = state.clone();
+                    let c = ctx.clone();
+                    tokio::spawn(async move { run_background_display(ch, is, st, c).await });
+                    line
                 }
+                Err(e) => tf("log.display_add_failed", &[("error", &format!("{e:#}"))]),
+            },
+            DisplayRequest::Remove => match recv.display_indices().into_iter().filter(|&n| n > 0).max() {
+                Some(n) if recv.remove_display(n) => tf("log.display_removed", &[("n", &n)]),
+                _ => t("log.no_extra_display").to_string(),
+            },
+        };
+
+        let count = recv.display_count();
+        if let Some(adv) = advertiser.as_mut() {
+            if let Err(e) = adv.set_ports(&recv.port_map()) {
+                warn!("mDNS port map update failed: {e:#}");
             }
-        });
+        }
+        {
+            let mut s = state.lock().unwrap();
+            s.display_count = count;
+            s.push_log(line);
+        }
+        ctx.request_repaint();
+    }
+}
 
-        let exit_reason = loop {
-            tokio::select! {
-                Some(frame) = frame_rx.recv() => {
-                    if decode_tx.send(frame).await.is_err() { break "decode_gone"; }
-                }
-                Some(evt) = event_rx.recv() => {
-                    match evt {
-                        SignalingEvent::SessionStopped { .. } => break "stopped",
-                        SignalingEvent::ClientDisconnected => break "disconnected",
-                        SignalingEvent::ConfigUpdated { config: new_cfg } => {
-                            let cur_w = config.resolution.width;
-                            let cur_h = config.resolution.height;
-                            if new_cfg.resolution.width != cur_w || new_cfg.resolution.height != cur_h {
-                                pending_config = Some(new_cfg);
-                                break "config_updated";
-                            }
-                        }
-                        _ => {}
+/// Carries out one input macro request, returning the log line.
+async fn apply_macro_request(
+    request: MacroRequest,
+    input_sender: &InputSender,
+    replay: &mut Option<ReplayHandle>,
+) -> String {
+    let path = InputRecording::default_path().unwrap_or_else(|| "input-macro.jsonl".into());
+    match request {
+        MacroRequest::Record => {
+            input_sender.start_recording();
+            t("log.recording").to_string()
+        }
+        MacroRequest::StopRecording => {
+            let Some(recording) = input_sender.stop_recording() else {
+                return t("log.not_recording").to_string();
+            };
+            let count = recording.events.len();
+            match tokio::task::spawn_blocking(move || recording.save(&path).map(|()| path)).await {
+                Ok(Ok(path)) => tf("log.recording_saved", &[("count", &count), ("path", &path.display())]),
+                Ok(Err(e)) => tf("log.recording_save_failed", &[("error", &e)]),
+                Err(e) => tf("log.recording_save_failed", &[("error", &e)]),
+            }
+        }
+        MacroRequest::Replay => {
+            let loaded = tokio::task::spawn_blocking(move || InputRecording::load(&path)).await;
+            match loaded {
+                Ok(Ok(recording)) => {
+                    let line = tf("log.replaying", &[
+                        ("count", &recording.events.len()),
+                        ("seconds", &format!("{:.1}", recording.duration().as_secs_f64())),
+                    ]);
+                    if let Some(previous) = replay.replace(input_sender.replay(recording, false)) {
+                        previous.stop();
                     }
+                    line
                 }
-                else => break "closed",
+                Ok(Err(e)) => tf("log.recording_load_failed", &[("error", &e)]),
+                Err(e) => tf("log.recording_load_failed", &[("error", &e)]),
             }
-        };
-
-        drop(decode_tx);
-        let _ = handle.await;
-
-        if exit_reason == "closed" { break 'reconnect; }
-        if exit_reason != "config_updated" {
-            tokio::time::sleep(Duration::from_millis(300)).await;
         }
+        MacroRequest::StopReplay => match replay.take() {
+            Some(handle) => {
+                handle.stop();
+                t("log.replay_stopped").to_string()
+            }
+            None => t("log.no_replay").to_string(),
+        },
     }
 }
+
+/// Handles one extra display (index ≥ 1), reporting into its
+/// [`GuiState::displays`](crate::state::GuiState) card.
+async fn run_background_display(
+    ch: DisplayChannels,
+    input_sender: InputSender,
+    state: SharedState,
+    ctx: egui::Context,
+) {
+    let display_index = ch.display_index;
+    state.lock().unwrap().displays.entry(display_index).or_default().phase = Phase::WaitingForClient;
+    ctx.request_repaint();
+
+    let session = GuiSession::new(display_index, Arc::clone(&state), ctx.clone(), ch.files.clone());
+    ReceiverSession::new(ch, input_sender, session)
+        .with_stats_sink(GuiStats::new(display_index, Arc::clone(&state)))
+        .run()
+        .await;
+
+    state.lock().unwrap().displays.remove(&display_index);
+    ctx.request_repaint();
+}
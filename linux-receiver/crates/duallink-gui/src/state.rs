@@ -1,6 +1,13 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use duallink_core::{
+    FileOffer, FileTransferEvent, FirewallCheck, LogBuffer, NetworkKind, PowerState, RateMeter, SequenceStats,
+};
+
+use crate::strings::{t, tf};
 
 // ── Phase ──────────────────────────────────────────────────────────────────────
 
@@ -13,6 +20,56 @@ pub enum Phase {
     Error(String),
 }
 
+/// Display add/remove requested from the GUI, applied by the receiver task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayRequest {
+    Add,
+    Remove,
+}
+
+/// Per-display action requested from a display card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayAction {
+    /// Tear down and recreate the decoder, keeping the session.
+    RestartDecoder,
+    /// End the sender's session on this display.
+    Disconnect,
+    /// Hold the shown frame while the sender keeps streaming, or go live.
+    ToggleFreeze,
+    /// Blank the window and pause the sender's capture, or resume both.
+    ToggleBlank,
+    /// Stop or resume this display's stream; the other displays go on.
+    TogglePause,
+    /// Capture system shortcuts in the window and forward them, or not.
+    ToggleSystemKeys,
+}
+
+/// Input macro control from the "Input macro" card, applied by the receiver task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacroRequest {
+    /// Start recording forwarded input events.
+    Record,
+    /// Stop recording and save to [`InputRecording::default_path`](duallink_core::InputRecording::default_path).
+    StopRecording,
+    /// Replay the saved recording to the connected sender.
+    Replay,
+    /// Abort a running replay.
+    StopReplay,
+}
+
+/// File transfer asked for from the window or the "Files" card, applied by
+/// the display's session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileRequest {
+    /// Offer a file dropped on the window to the sender.
+    Send(PathBuf),
+    /// Accept or decline the sender's offer.
+    Answer { transfer_id: String, accept: bool },
+}
+
+/// Finished transfers listed in the "Files" card before the oldest goes.
+const MAX_TRANSFER_ROWS: usize = 5;
+
 impl Default for Phase {
     fn default() -> Self {
         Self::Starting
@@ -22,11 +79,11 @@ impl Default for Phase {
 impl Phase {
     pub fn label(&self) -> &str {
         match self {
-            Phase::Starting            => "Starting…",
-            Phase::WaitingForClient    => "Waiting for client",
-            Phase::Connected   { .. } => "Client connected",
-            Phase::Streaming   { .. } => "Streaming",
-            Phase::Error       ( _ )  => "Error",
+            Phase::Starting            => t("phase.starting"),
+            Phase::WaitingForClient    => t("phase.waiting"),
+            Phase::Connected   { .. } => t("phase.connected"),
+            Phase::Streaming   { .. } => t("phase.streaming"),
+            Phase::Error       ( _ )  => t("phase.error"),
         }
     }
 
@@ -61,6 +118,89 @@ impl Phase {
     }
 }
 
+// ── DecoderOption ─────────────────────────────────────────────────────────────
+
+/// An installed H.264 decoder offered in the decoder dropdown.
+#[derive(Debug, Clone)]
+pub struct DecoderOption {
+    pub element: &'static str,
+    pub label:   &'static str,
+    /// Per-frame decode time from `benchmark_decoders` (`None` = failed or pending).
+    pub latency: Option<Duration>,
+}
+
+// ── DisplayStatus ─────────────────────────────────────────────────────────────
+
+/// Status of one extra display stream (index ≥ 1). Display 0 is reported
+/// through the top-level [`GuiState`] fields.
+#[derive(Default)]
+pub struct DisplayStatus {
+    pub phase:           Phase,
+    pub fps:             f64,
+    pub frames_received: u64,
+    pub frames_decoded:  u64,
+    /// GStreamer decoder element of the current session.
+    pub decoder:         Option<String>,
+    /// Lost / late / duplicate frames since the display was bound.
+    pub frame_stats:     SequenceStats,
+    /// The window is holding a frozen frame.
+    pub frozen:          bool,
+    /// The window is blanked (privacy mode).
+    pub blanked:         bool,
+    /// The stream is paused from either end.
+    pub paused:          bool,
+    /// The window captures system shortcuts for the sender.
+    pub system_keys:     bool,
+    /// No frames arrived for a while; cleared by the next decoded frame.
+    pub stalled:         bool,
+    /// Rate of decoded frames shown, duplicates not counted.
+    pub unique_fps:      f64,
+    /// Decoded frames dropped as duplicates this session.
+    pub duplicates:      u64,
+    last_frame_times:    VecDeque<Instant>,
+    unique_rate:         RateMeter,
+}
+
+impl DisplayStatus {
+    /// Call once per decoded frame to update the FPS rolling window.
+    pub fn tick_frame(&mut self) {
+        let now = Instant::now();
+        self.frames_decoded += 1;
+        self.stalled = false;
+        self.last_frame_times.push_back(now);
+        while self
+            .last_frame_times
+            .front()
+            .map_or(false, |t| now.duration_since(*t).as_secs_f64() > 1.0)
+        {
+            self.last_frame_times.pop_front();
+        }
+        self.fps = self.last_frame_times.len() as f64;
+    }
+
+    /// Feed the decoder's unique / duplicate frame counters.
+    pub fn update_unique(&mut self, frames_unique: u64, duplicates: u64) {
+        self.unique_fps = self.unique_rate.update(frames_unique);
+        self.duplicates = duplicates;
+    }
+
+    /// Reset streaming counters (between sessions).
+    pub fn reset_stats(&mut self) {
+        self.fps             = 0.0;
+        self.frames_received = 0;
+        self.frames_decoded  = 0;
+        self.decoder         = None;
+        self.frozen          = false;
+        self.blanked         = false;
+        self.paused          = false;
+        self.system_keys     = false;
+        self.unique_fps      = 0.0;
+        self.duplicates      = 0;
+        self.last_frame_times.clear();
+        self.unique_rate     = RateMeter::default();
+    }
+}
+
 // ── GuiState ──────────────────────────────────────────────────────────────────
 
 pub struct GuiState {
@@ -71,17 +211,85 @@ pub struct GuiState {
     pub frames_received:  u64,
     pub frames_decoded:   u64,
     pub bitrate_mbps:     f64,
+    /// Display 0's lost / late / duplicate frames since it was bound.
+    pub frame_stats:      SequenceStats,
     pub transport:        String,
-    pub logs:             VecDeque<String>,
+    /// What `transport` describes, for code that should not parse the label.
+    pub network:          NetworkKind,
+    pub logs:             LogBuffer,
     /// LAN IPv4 address shown in the PIN card so users know where to connect.
     pub lan_ip:           String,
     /// Whether mDNS advertising is active (set after `DualLinkAdvertiser::register` succeeds).
     pub mdns_active:      bool,
-    /// Number of display streams bound (1 unless `DUALLINK_DISPLAY_COUNT` > 1).
+    /// Number of display streams bound (`DUALLINK_DISPLAY_COUNT`, then the +/− buttons).
     pub display_count:    u8,
+    /// Pending display add/remove from the +/− buttons.
+    pub display_request:  Option<DisplayRequest>,
+    /// "New PIN" was clicked; the display manager replaces the PIN.
+    pub pin_request:      bool,
+    /// Decoder element of the current display-0 session.
+    pub decoder:          Option<String>,
+    /// Status of displays 1+, keyed by display index.
+    pub displays:         BTreeMap<u8, DisplayStatus>,
+    /// Displays' own pairing PINs, when each display has one.
+    pub display_pins:     BTreeMap<u8, String>,
+    /// Actions requested from the display cards, not yet applied.
+    pub pending_actions:  Vec<(u8, DisplayAction)>,
+    /// Installed decoders for the dropdown; filled once probing finishes.
+    pub decoder_options:  Vec<DecoderOption>,
+    /// `true` while the decoder benchmark runs.
+    pub benchmarking:     bool,
+    /// Decoder elements tried first for new decoders (empty = probe order).
+    pub decoder_preference: Vec<String>,
+    /// Input policy for new sessions (the "Allow input" toggle).
+    pub allow_input:      bool,
+    /// The current display-0 session was negotiated view-only.
+    pub view_only_session: bool,
+    /// Display 0's window is holding a frozen frame.
+    pub frozen:           bool,
+    /// Display 0's window is blanked (privacy mode).
+    pub blanked:          bool,
+    /// Display 0's stream is paused from either end.
+    pub paused:           bool,
+    /// Display 0's window captures system shortcuts for the sender.
+    pub system_keys:      bool,
+    /// Display 0's stream stalled; cleared by the next decoded frame.
+    pub stalled:          bool,
+    /// Pending request from the "Input macro" card.
+    pub macro_request:    Option<MacroRequest>,
+    /// Events recorded so far, while recording.
+    pub macro_recording:  Option<usize>,
+    /// A saved recording is being replayed.
+    pub macro_replaying:  bool,
+    /// This machine's power source (`None` = no battery).
+    pub power:            Option<PowerState>,
+    /// Power source reported by display 0's sender.
+    pub sender_power:     Option<PowerState>,
+    /// Display 0's rate of decoded frames shown, duplicates not counted.
+    pub unique_fps:       f64,
+    /// Display 0's decoded frames dropped as duplicates this session.
+    pub duplicates:       u64,
+    /// Display 0's partial frames evicted and corrupted frames dropped
+    /// since it was bound.
+    pub reassembly_drops: u64,
+    /// Display 0's network and sender queueing of the latest frame.
+    pub queueing_delay:   Duration,
+    /// Frames display 0's decoder rejected this session.
+    pub decode_errors:    u64,
+    /// Firewall that may keep senders out, until opened or dismissed.
+    pub firewall:         Option<FirewallCheck>,
+    /// The "Open ports" prompt is waiting for the user's password.
+    pub firewall_opening: bool,
+    /// File transfers asked for, by display, not yet applied.
+    pub file_requests:    Vec<(u8, FileRequest)>,
+    /// Files senders offered, by display, waiting for an answer.
+    pub file_offers:      Vec<(u8, FileOffer)>,
+    /// Latest event of each recent transfer, oldest first.
+    pub transfers:        Vec<FileTransferEvent>,
     // Rolling-window helpers (private)
     last_frame_times:  VecDeque<Instant>,
     last_byte_amounts: VecDeque<(Instant, u64)>,
+    unique_rate:       RateMeter,
 }
 
 impl Default for GuiState {
@@ -94,32 +302,91 @@ impl Default for GuiState {
             frames_received: 0,
             frames_decoded:  0,
             bitrate_mbps:    0.0,
-            transport:       "detecting…".into(),
-            logs:            VecDeque::new(),
+            frame_stats:     SequenceStats::default(),
+            transport:       t("status.detecting").into(),
+            network:         NetworkKind::Unknown,
+            logs:            LogBuffer::default().with_summary(|s| {
+                tf("log.suppressed", &[("category", &s.category), ("count", &s.count), ("secs", &s.window.as_secs())])
+            }),
             lan_ip:          String::new(),
             mdns_active:     false,
             display_count:   1,
+            display_request: None,
+            pin_request:     false,
+            decoder:         None,
+            displays:        BTreeMap::new(),
+            display_pins:    BTreeMap::new(),
+            pending_actions: Vec::new(),
+            decoder_options: Vec::new(),
+            benchmarking:    false,
+            decoder_preference: Vec::new(),
+            allow_input:     true,
+            view_only_session: false,
+            frozen:          false,
+            blanked:         false,
+            paused:          false,
+            system_keys:     false,
+            stalled:         false,
+            macro_request:   None,
+            macro_recording: None,
+            macro_replaying: false,
+            power:           None,
+            sender_power:    None,
+            reassembly_drops: 0,
+            queueing_delay:  Duration::ZERO,
+            decode_errors:   0,
+            firewall:        None,
+            firewall_opening: false,
+            file_requests:   Vec::new(),
+            file_offers:     Vec::new(),
+            transfers:       Vec::new(),
+            unique_fps:      0.0,
+            duplicates:      0,
             last_frame_times:  VecDeque::new(),
             last_byte_amounts: VecDeque::new(),
+            unique_rate:       RateMeter::default(),
         }
     }
 }
 
 impl GuiState {
-    /// Append a line to the circular log buffer (max 300 entries).
+    /// Append a line to the log buffer (see [`LogBuffer`]).
     pub fn push_log(&mut self, line: impl Into<String>) {
         let line = line.into();
         tracing::debug!("[GUI log] {}", line);
-        if self.logs.len() >= 300 {
-            self.logs.pop_front();
+        self.logs.push(line);
+    }
+
+    /// Append a line that may come every frame: past a few per minute,
+    /// `category`'s lines are summed up in one.
+    pub fn push_log_limited(&mut self, category: &str, line: impl Into<String>) {
+        let line = line.into();
+        tracing::debug!("[GUI log] {}", line);
+        self.logs.push_limited(category, line, Instant::now());
+    }
+
+    /// Session stats for a diagnostics bundle, by name.
+    pub fn session_stats(&self) -> BTreeMap<String, String> {
+        let mut stats = BTreeMap::new();
+        stats.insert("transport".to_owned(), self.transport.clone());
+        stats.insert("displayCount".to_owned(), self.display_count.to_string());
+        stats.insert("bitrateMbps".to_owned(), format!("{:.2}", self.bitrate_mbps));
+        stats.insert("allowInput".to_owned(), self.allow_input.to_string());
+        stats.insert("decoderPreference".to_owned(), self.decoder_preference.join(","));
+        let frames = [self.frames_received, self.frames_decoded, self.duplicates];
+        put_display_stats(&mut stats, 0, &self.phase, self.decoder.as_deref(), [self.fps, self.unique_fps], frames, &self.frame_stats);
+        for (i, d) in &self.displays {
+            let frames = [d.frames_received, d.frames_decoded, d.duplicates];
+            put_display_stats(&mut stats, *i, &d.phase, d.decoder.as_deref(), [d.fps, d.unique_fps], frames, &d.frame_stats);
         }
-        self.logs.push_back(line);
+        stats
     }
 
     /// Call once per decoded frame to update FPS / bitrate rolling windows.
     pub fn tick_frame(&mut self, byte_count: usize) {
         let now = Instant::now();
         self.frames_decoded += 1;
+        self.stalled = false;
         self.last_frame_times.push_back(now);
         self.last_byte_amounts.push_back((now, byte_count as u64));
 
@@ -144,16 +411,82 @@ impl GuiState {
         self.bitrate_mbps = (bytes as f64 * 8.0) / 1_000_000.0;
     }
 
+    /// Feed display 0's decoder unique / duplicate frame counters.
+    pub fn update_unique(&mut self, frames_unique: u64, duplicates: u64) {
+        self.unique_fps = self.unique_rate.update(frames_unique);
+        self.duplicates = duplicates;
+    }
+
+    /// Remove and return whether `action` was requested for `display`.
+    pub fn take_action(&mut self, display: u8, action: DisplayAction) -> bool {
+        let before = self.pending_actions.len();
+        self.pending_actions.retain(|&a| a != (display, action));
+        self.pending_actions.len() != before
+    }
+
+    /// Remove and return the file requests for `display`.
+    pub fn take_file_requests(&mut self, display: u8) -> Vec<FileRequest> {
+        let (mine, others) = std::mem::take(&mut self.file_requests).into_iter().partition(|(d, _)| *d == display);
+        self.file_requests = others;
+        mine.into_iter().map(|(_, request)| request).collect()
+    }
+
+    /// Record what happened to a transfer of `display`'s session: offers
+    /// wait for an answer, other events replace the transfer's last one.
+    pub fn record_transfer(&mut self, display: u8, event: FileTransferEvent) {
+        if let FileTransferEvent::Offered(offer) = event {
+            self.file_offers.push((display, offer));
+            return;
+        }
+        self.file_offers.retain(|(_, o)| o.transfer_id != event.transfer_id());
+        self.transfers.retain(|e| e.transfer_id() != event.transfer_id());
+        self.transfers.push(event);
+        while self.transfers.len() > MAX_TRANSFER_ROWS {
+            let finished = self.transfers.iter().position(|e| !matches!(e, FileTransferEvent::Progress { .. }));
+            let Some(i) = finished else { break };
+            self.transfers.remove(i);
+        }
+    }
+
     /// Reset streaming counters / rolling windows (between sessions).
     pub fn reset_stats(&mut self) {
         self.fps             = 0.0;
         self.frames_received = 0;
         self.frames_decoded  = 0;
         self.bitrate_mbps    = 0.0;
+        self.decoder         = None;
+        self.frozen          = false;
+        self.blanked         = false;
+        self.paused          = false;
+        self.system_keys     = false;
+        self.sender_power    = None;
+        self.unique_fps      = 0.0;
+        self.duplicates      = 0;
+        self.decode_errors   = 0;
         self.last_frame_times.clear();
         self.last_byte_amounts.clear();
+        self.unique_rate     = RateMeter::default();
     }
 }
 
 /// Shared handle passed between the GUI thread and the async receiver task.
 pub type SharedState = Arc<Mutex<GuiState>>;
+
+/// Display `index`'s entries of [`GuiState::session_stats`]: `fps` is
+/// [shown, unique], `frames` [received, decoded, duplicates dropped].
+fn put_display_stats(
+    stats: &mut BTreeMap<String, String>,
+    index: u8,
+    phase: &Phase,
+    decoder: Option<&str>,
+    [fps, unique_fps]: [f64; 2],
+    [received, decoded, duplicates]: [u64; 3],
+    seq: &SequenceStats,
+) {
+    let key = |name: &str| format!("display{index}.{name}");
+    stats.insert(key("phase"), format!("{phase:?}"));
+    stats.insert(key("decoder"), decoder.unwrap_or("-").to_owned());
+    stats.insert(key("fps"), format!("{fps:.1} ({unique_fps:.1} unique)"));
+    stats.insert(key("frames"), format!("{received} received, {decoded} decoded, {duplicates} duplicates dropped"));
+    stats.insert(key("sequence"), format!("{} lost, {} late, {} duplicate", seq.lost, seq.late, seq.duplicate));
+}
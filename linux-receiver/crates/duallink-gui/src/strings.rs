@@ -0,0 +1,567 @@
+//! Text of the receiver GUI in every [`Language`](duallink_core::Language)
+//! (see [`duallink_core::locale`]).
+//!
+//! Log lines keep their `[ERROR]` / `[WARN]` prefix untranslated: the log
+//! panel colours lines by it.
+
+use std::fmt::Display;
+
+use duallink_core::locale::{self, Catalog};
+
+/// Key in the current language.
+pub fn t(key: &'static str) -> &'static str {
+    locale::tr(STRINGS, key)
+}
+
+/// Key in the current language with `{name}` placeholders filled in.
+pub fn tf(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    locale::tr_fill(STRINGS, key, args)
+}
+
+#[rustfmt::skip]
+const STRINGS: Catalog = &[
+    // ── Header / footer ───────────────────────────────────────────────────
+    ("app.receiver", ["Receiver", "Receptor", "Receptor"]),
+    ("app.language", ["Language", "Idioma", "Idioma"]),
+    ("appearance.theme", ["Theme", "Tema", "Tema"]),
+    ("appearance.dark", ["Dark", "Escuro", "Oscuro"]),
+    ("appearance.light", ["Light", "Claro", "Claro"]),
+    ("appearance.system", ["System", "Sistema", "Sistema"]),
+    ("appearance.scale", [
+        "UI scale, for HiDPI screens",
+        "Escala da interface, para telas HiDPI",
+        "Escala de la interfaz, para pantallas HiDPI",
+    ]),
+    ("firewall.title", ["Firewall", "Firewall", "Cortafuegos"]),
+    ("firewall.blocked", [
+        "{firewall} may keep senders from connecting (ports {ports}).",
+        "O {firewall} pode impedir a conexão dos transmissores (portas {ports}).",
+        "{firewall} puede impedir que los emisores se conecten (puertos {ports}).",
+    ]),
+    ("firewall.open", ["Open ports", "Liberar portas", "Abrir puertos"]),
+    ("firewall.open_hint", [
+        "Asks for your password, then runs:\n{script}",
+        "Pede sua senha e então executa:\n{script}",
+        "Pide tu contraseña y luego ejecuta:\n{script}",
+    ]),
+    ("firewall.dismiss", ["Dismiss", "Ignorar", "Ignorar"]),
+    ("app.export_diagnostics", ["Export diagnostics", "Exportar diagnóstico", "Exportar diagnóstico"]),
+    ("app.export_diagnostics_hint", [
+        "Save decoders, VA-API, network and this log to one JSON file in your home folder, for bug reports",
+        "Salva decodificadores, VA-API, rede e este registro em um arquivo JSON na sua pasta pessoal, para relatar erros",
+        "Guarda decodificadores, VA-API, red y este registro en un archivo JSON en tu carpeta personal, para informar errores",
+    ]),
+    ("app.quit", ["Quit DualLink", "Sair do DualLink", "Salir de DualLink"]),
+    ("app.saver_hint", [
+        "Battery saver: senders stream at 30 fps and a lower bitrate, previews are skipped",
+        "Economia de bateria: os emissores transmitem a 30 fps e com bitrate menor, sem pré-visualizações",
+        "Ahorro de batería: los emisores transmiten a 30 fps y con menor bitrate, sin vistas previas",
+    ]),
+
+    // ── Status ────────────────────────────────────────────────────────────
+    ("phase.starting", ["Starting…", "Iniciando…", "Iniciando…"]),
+    ("phase.waiting", ["Waiting for client", "Aguardando cliente", "Esperando al cliente"]),
+    ("phase.connected", ["Client connected", "Cliente conectado", "Cliente conectado"]),
+    ("phase.streaming", ["Streaming", "Transmitindo", "Transmitiendo"]),
+    ("phase.error", ["Error", "Erro", "Error"]),
+    ("status.detecting", ["detecting…", "detectando…", "detectando…"]),
+    ("status.view_only", [
+        "View-only session — input is not sent to the sender",
+        "Sessão somente visualização — a entrada não é enviada ao emissor",
+        "Sesión de solo visualización — la entrada no se envía al emisor",
+    ]),
+    ("status.sender_power", ["Sender is {power}", "Emissor está {power}", "El emisor está {power}"]),
+    ("status.allow_input", ["Allow input", "Permitir entrada", "Permitir entrada"]),
+    ("status.allow_input_hint", [
+        "Send mouse and keyboard input back to the sender. Applies to new sessions.",
+        "Enviar mouse e teclado de volta ao emissor. Vale para novas sessões.",
+        "Enviar ratón y teclado de vuelta al emisor. Se aplica a sesiones nuevas.",
+    ]),
+
+    // ── PIN card ──────────────────────────────────────────────────────────
+    ("pin.title", ["Pairing PIN", "PIN de pareamento", "PIN de emparejamiento"]),
+    ("pin.copy", ["Copy", "Copiar", "Copiar"]),
+    ("pin.copied", ["Copied!", "Copiado!", "¡Copiado!"]),
+    ("pin.new", ["New PIN", "Novo PIN", "Nuevo PIN"]),
+    ("pin.new_hint", [
+        "Replace the PIN; connected senders stay connected",
+        "Troca o PIN; emissores conectados continuam conectados",
+        "Cambia el PIN; los emisores conectados siguen conectados",
+    ]),
+    ("pin.instructions", [
+        "Enter this PIN in the macOS DualLink app to authorise the connection.",
+        "Digite este PIN no app DualLink do macOS para autorizar a conexão.",
+        "Introduce este PIN en la app DualLink de macOS para autorizar la conexión.",
+    ]),
+    ("pin.connect_from_one", [
+        "Connect from: {ip}  •  1 display",
+        "Conecte em: {ip}  •  1 tela",
+        "Conéctate a: {ip}  •  1 pantalla",
+    ]),
+    ("pin.connect_from_many", [
+        "Connect from: {ip}  •  {count} displays",
+        "Conecte em: {ip}  •  {count} telas",
+        "Conéctate a: {ip}  •  {count} pantallas",
+    ]),
+    ("pin.fingerprint", ["TLS certificate fingerprint", "Impressão digital do certificado TLS", "Huella del certificado TLS"]),
+    ("pin.fingerprint_hint", [
+        "The macOS client accepts this certificate on first connect (TOFU).",
+        "O cliente macOS aceita este certificado na primeira conexão (TOFU).",
+        "El cliente de macOS acepta este certificado en la primera conexión (TOFU).",
+    ]),
+
+    // ── Displays card ─────────────────────────────────────────────────────
+    ("displays.title", ["Displays", "Telas", "Pantallas"]),
+    ("displays.name", ["Display {n}", "Tela {n}", "Pantalla {n}"]),
+    ("displays.disconnect", ["Disconnect", "Desconectar", "Desconectar"]),
+    ("displays.restart", ["Restart decoder", "Reiniciar decodificador", "Reiniciar decodificador"]),
+    ("displays.restart_hint", [
+        "Recreate the decoder pipeline, keeping the session",
+        "Recria o pipeline do decodificador, mantendo a sessão",
+        "Recrea el pipeline del decodificador, manteniendo la sesión",
+    ]),
+    ("displays.freeze", ["Freeze", "Congelar", "Congelar"]),
+    ("displays.unfreeze", ["Unfreeze", "Descongelar", "Descongelar"]),
+    ("displays.freeze_hint", [
+        "Hold the current frame while the sender keeps streaming; unfreezing jumps back to live (Ctrl+Alt+P)",
+        "Mantém o quadro atual enquanto o emissor continua transmitindo; descongelar volta ao vivo (Ctrl+Alt+P)",
+        "Mantiene el fotograma actual mientras el emisor sigue transmitiendo; descongelar vuelve al directo (Ctrl+Alt+P)",
+    ]),
+    ("displays.blank", ["Blank", "Ocultar", "Ocultar"]),
+    ("displays.unblank", ["Unblank", "Mostrar", "Mostrar"]),
+    ("displays.blank_hint", [
+        "Black out this display and ask the sender to pause capture, e.g. while sensitive content is on screen (Ctrl+Alt+B)",
+        "Escurece esta tela e pede ao emissor que pause a captura, p. ex. com conteúdo sensível na tela (Ctrl+Alt+B)",
+        "Oscurece esta pantalla y pide al emisor que pause la captura, p. ej. con contenido sensible en pantalla (Ctrl+Alt+B)",
+    ]),
+    ("displays.pause", ["Pause", "Pausar", "Pausar"]),
+    ("displays.resume", ["Resume", "Retomar", "Reanudar"]),
+    ("displays.pause_hint", [
+        "Stop streaming this display while the others go on; the sender keeps its capture ready",
+        "Para de transmitir esta tela enquanto as outras continuam; o emissor mantém a captura pronta",
+        "Deja de transmitir esta pantalla mientras las demás siguen; el emisor mantiene la captura lista",
+    ]),
+    ("displays.system_keys", ["Capture keys", "Capturar teclas", "Capturar teclas"]),
+    ("displays.local_keys", ["Release keys", "Liberar teclas", "Liberar teclas"]),
+    ("displays.system_keys_hint", [
+        "Send Alt+Tab, Super and other system shortcuts to the sender instead of this desktop; X11 windows only (Ctrl+Alt+K)",
+        "Envia Alt+Tab, Super e outros atalhos do sistema ao emissor em vez desta área de trabalho; só janelas X11 (Ctrl+Alt+K)",
+        "Envía Alt+Tab, Super y otros atajos del sistema al emisor en lugar de este escritorio; solo ventanas X11 (Ctrl+Alt+K)",
+    ]),
+    ("files.title", ["Files", "Arquivos", "Archivos"]),
+    ("files.drop_hint", [
+        "Drop files on this window to send them to the sender",
+        "Solte arquivos nesta janela para enviá-los ao emissor",
+        "Suelta archivos en esta ventana para enviarlos al emisor",
+    ]),
+    ("files.offer", [
+        "Display {n}: the sender sends {name} ({size})",
+        "Tela {n}: o emissor envia {name} ({size})",
+        "Pantalla {n}: el emisor envía {name} ({size})",
+    ]),
+    ("files.accept", ["Accept", "Aceitar", "Aceptar"]),
+    ("files.decline", ["Decline", "Recusar", "Rechazar"]),
+    ("files.received", ["✓ {name} received", "✓ {name} recebido", "✓ {name} recibido"]),
+    ("files.sent", ["✓ {name} sent", "✓ {name} enviado", "✓ {name} enviado"]),
+    ("files.failed", ["✗ {name}: {reason}", "✗ {name}: {reason}", "✗ {name}: {reason}"]),
+    ("displays.owner_hint", [
+        "Streamed by {name} ({addr}); no other sender can use this display until it leaves",
+        "Transmitida por {name} ({addr}); nenhum outro emissor pode usar esta tela até ele sair",
+        "Transmitida por {name} ({addr}); ningún otro emisor puede usar esta pantalla hasta que salga",
+    ]),
+    ("displays.pin", ["PIN {pin}", "PIN {pin}", "PIN {pin}"]),
+    ("displays.stalled", ["⚠ No frames", "⚠ Sem quadros", "⚠ Sin fotogramas"]),
+    ("displays.stalled_hint", [
+        "The sender has stopped sending frames; a keyframe was requested",
+        "O emissor parou de enviar quadros; um quadro-chave foi solicitado",
+        "El emisor dejó de enviar fotogramas; se solicitó un fotograma clave",
+    ]),
+    ("displays.pin_hint", [
+        "Senders connecting to this display need this PIN rather than the one above",
+        "Emissores que se conectam a esta tela precisam deste PIN em vez do acima",
+        "Los emisores que se conectan a esta pantalla necesitan este PIN en lugar del de arriba",
+    ]),
+    ("displays.stats", [
+        "{fps} fps ({unique} unique)  •  {decoded} decoded / {received} received  •  {stats}  •  {decoder}",
+        "{fps} fps ({unique} únicos)  •  {decoded} decodificados / {received} recebidos  •  {stats}  •  {decoder}",
+        "{fps} fps ({unique} únicos)  •  {decoded} decodificados / {received} recibidos  •  {stats}  •  {decoder}",
+    ]),
+    ("displays.no_decoder", ["no decoder", "sem decodificador", "sin decodificador"]),
+
+    // ── Decoder picker ────────────────────────────────────────────────────
+    ("decoder.title", ["Decoder", "Decodificador", "Decodificador"]),
+    ("decoder.auto", ["Auto (probe order)", "Automático (ordem de detecção)", "Automático (orden de detección)"]),
+    ("decoder.benchmarking", ["{element}  (benchmarking…)", "{element}  (medindo…)", "{element}  (midiendo…)"]),
+    ("decoder.not_installed", ["{element}  (not installed)", "{element}  (não instalado)", "{element}  (no instalado)"]),
+    ("decoder.new_sessions", ["applies to new sessions", "vale para novas sessões", "se aplica a sesiones nuevas"]),
+
+    // ── Input macro ───────────────────────────────────────────────────────
+    ("macro.title", ["Input macro", "Macro de entrada", "Macro de entrada"]),
+    ("macro.recording", ["recording — {n} events", "gravando — {n} eventos", "grabando — {n} eventos"]),
+    ("macro.replaying", ["replaying…", "reproduzindo…", "reproduciendo…"]),
+    ("macro.stop_replay", ["⏹ Stop replay", "⏹ Parar reprodução", "⏹ Detener reproducción"]),
+    ("macro.replay", ["▶ Replay", "▶ Reproduzir", "▶ Reproducir"]),
+    ("macro.replay_hint", [
+        "Send the saved recording to the sender, with its original timing",
+        "Envia a gravação salva ao emissor, com o tempo original",
+        "Envía la grabación guardada al emisor, con su ritmo original",
+    ]),
+    ("macro.stop_save", ["⏹ Stop & save", "⏹ Parar e salvar", "⏹ Detener y guardar"]),
+    ("macro.record", ["⏺ Record", "⏺ Gravar", "⏺ Grabar"]),
+    ("macro.record_hint", [
+        "Record the input sent to senders, for replay later",
+        "Grava a entrada enviada aos emissores, para reproduzir depois",
+        "Graba la entrada enviada a los emisores, para reproducirla después",
+    ]),
+
+    // ── Stats card ────────────────────────────────────────────────────────
+    ("quality.title", ["Connection quality", "Qualidade da conexão", "Calidad de la conexión"]),
+    ("quality.good", ["good", "boa", "buena"]),
+    ("quality.fair", ["fair", "razoável", "aceptable"]),
+    ("quality.poor", ["poor", "ruim", "mala"]),
+    ("quality.details", [
+        "Last 5 s: {loss}% frames lost, {drops} reassembly drops/s, {errors} decode errors/s, {delay} ms queueing, fps varies {variation}%",
+        "Últimos 5 s: {loss}% de quadros perdidos, {drops} descartes na remontagem/s, {errors} erros de decodificação/s, {delay} ms de fila, fps varia {variation}%",
+        "Últimos 5 s: {loss}% de fotogramas perdidos, {drops} descartes de reensamblado/s, {errors} errores de decodificación/s, {delay} ms de cola, los fps varían {variation}%",
+    ]),
+    ("quality.hint_loss_wifi", [
+        "High packet loss — move closer to the access point or use a USB link",
+        "Perda de pacotes alta — aproxime-se do ponto de acesso ou use um cabo USB",
+        "Pérdida de paquetes alta — acércate al punto de acceso o usa un enlace USB",
+    ]),
+    ("quality.hint_loss", [
+        "High packet loss — check the cable and switch, or lower the sender's bitrate",
+        "Perda de pacotes alta — verifique o cabo e o switch, ou reduza o bitrate do transmissor",
+        "Pérdida de paquetes alta — revisa el cable y el switch, o baja el bitrate del emisor",
+    ]),
+    ("quality.hint_drops", [
+        "Frames arrive incomplete — the link drops fragments; lower the sender's bitrate",
+        "Quadros chegam incompletos — o enlace perde fragmentos; reduza o bitrate do transmissor",
+        "Los fotogramas llegan incompletos — el enlace pierde fragmentos; baja el bitrate del emisor",
+    ]),
+    ("quality.hint_decode_errors", [
+        "The decoder rejects frames — pick another decoder below",
+        "O decodificador rejeita quadros — escolha outro decodificador abaixo",
+        "El decodificador rechaza fotogramas — elige otro decodificador abajo",
+    ]),
+    ("quality.hint_delay", [
+        "Frames queue up on the way — the network or the sender is overloaded",
+        "Quadros se acumulam no caminho — a rede ou o transmissor está sobrecarregado",
+        "Los fotogramas se acumulan en el camino — la red o el emisor están sobrecargados",
+    ]),
+    ("quality.hint_fps", [
+        "Frame rate is unstable — close heavy apps on the sender or lower its fps",
+        "A taxa de quadros está instável — feche apps pesados no transmissor ou reduza o fps",
+        "La tasa de fotogramas es inestable — cierra apps pesadas en el emisor o baja sus fps",
+    ]),
+    ("quality.hint_software_decoder", [
+        "Software decoder active — install vainfo and your GPU's VA-API driver (e.g. intel-media-driver)",
+        "Decodificador por software ativo — instale o vainfo e o driver VA-API da GPU (ex.: intel-media-driver)",
+        "Decodificador por software activo — instala vainfo y el driver VA-API de tu GPU (p. ej. intel-media-driver)",
+    ]),
+    ("stats.title", ["Streaming stats", "Estatísticas da transmissão", "Estadísticas de la transmisión"]),
+    ("stats.fps", ["FPS", "FPS", "FPS"]),
+    ("stats.unique_fps", ["Unique FPS", "FPS únicos", "FPS únicos"]),
+    ("stats.decoded", ["Decoded", "Decodificados", "Decodificados"]),
+    ("stats.received", ["Received", "Recebidos", "Recibidos"]),
+    ("stats.bitrate", ["Bitrate", "Bitrate", "Bitrate"]),
+    ("stats.lost", ["Lost", "Perdidos", "Perdidos"]),
+    ("stats.duplicates", ["Duplicates", "Duplicados", "Duplicados"]),
+    ("stats.displays", ["Displays", "Telas", "Pantallas"]),
+
+    // ── Log panel ─────────────────────────────────────────────────────────
+    ("log.title", ["Log", "Registro", "Registro"]),
+    ("log.auto_scroll", ["auto-scroll", "rolagem automática", "desplazamiento automático"]),
+
+    // ── Log lines ─────────────────────────────────────────────────────────
+    ("log.usb", [
+        "USB Ethernet detected: {interface} → {ip} (peer {peer})",
+        "Ethernet USB detectada: {interface} → {ip} (par {peer})",
+        "Ethernet USB detectada: {interface} → {ip} (par {peer})",
+    ]),
+    ("log.wifi", [
+        "No USB Ethernet interface found — using Wi-Fi transport",
+        "Nenhuma interface Ethernet USB encontrada — usando Wi-Fi",
+        "No se encontró ninguna interfaz Ethernet USB — usando Wi-Fi",
+    ]),
+    ("log.binding", [
+        "Binding UDP (video) + TCP (signaling) ports…",
+        "Abrindo portas UDP (vídeo) + TCP (sinalização)…",
+        "Abriendo puertos UDP (vídeo) + TCP (señalización)…",
+    ]),
+    ("log.benchmark", [
+        "Decoder benchmark: {element} {ms} ms/frame",
+        "Benchmark do decodificador: {element} {ms} ms/quadro",
+        "Benchmark del decodificador: {element} {ms} ms/fotograma",
+    ]),
+    ("log.benchmark_failed", [
+        "[WARN] Decoder benchmark: {element} failed",
+        "[WARN] Benchmark do decodificador: {element} falhou",
+        "[WARN] Benchmark del decodificador: {element} falló",
+    ]),
+    ("log.took_over", [
+        "Took over {count} display(s) from the running {service}",
+        "Assumiu {count} tela(s) do {service} em execução",
+        "Se tomaron {count} pantalla(s) del {service} en ejecución",
+    ]),
+    ("log.handover_failed", [
+        "[WARN] Handover from {service} failed: {error}",
+        "[WARN] A transferência do {service} falhou: {error}",
+        "[WARN] Falló la transferencia desde {service}: {error}",
+    ]),
+    ("log.ports_in_use", [
+        "[ERROR] Ports in use by a process that does not support handover.\nIf it is an older receiver, stop it:\nsystemctl --user stop {service}\nThen reopen the GUI.",
+        "[ERROR] Portas em uso por um processo que não suporta transferência.\nSe for um receptor antigo, pare-o:\nsystemctl --user stop {service}\nDepois reabra a interface.",
+        "[ERROR] Puertos en uso por un proceso que no admite la transferencia.\nSi es un receptor antiguo, deténlo:\nsystemctl --user stop {service}\nLuego vuelve a abrir la interfaz.",
+    ]),
+    ("log.start_failed", [
+        "[ERROR] Failed to start receiver: {error}",
+        "[ERROR] Falha ao iniciar o receptor: {error}",
+        "[ERROR] No se pudo iniciar el receptor: {error}",
+    ]),
+    ("log.no_channels", ["No display channels returned", "Nenhum canal de tela retornado", "No se devolvió ningún canal de pantalla"]),
+    ("log.pin", ["Pairing PIN : {pin}", "PIN de pareamento : {pin}", "PIN de emparejamiento : {pin}"]),
+    ("log.fingerprint", ["TLS fingerprint: {fingerprint}…", "Impressão digital TLS: {fingerprint}…", "Huella TLS: {fingerprint}…"]),
+    ("log.lan_ip", ["LAN IP : {ip}  (mDNS: {mdns})", "IP da rede local : {ip}  (mDNS: {mdns})", "IP de la red local : {ip}  (mDNS: {mdns})"]),
+    ("log.mdns_active", ["active", "ativo", "activo"]),
+    ("log.mdns_unavailable", ["unavailable", "indisponível", "no disponible"]),
+    ("log.display_streams", ["Display streams: {count}", "Fluxos de tela: {count}", "Flujos de pantalla: {count}"]),
+    ("log.ports", ["Ports (display=UDP/TCP): {ports}", "Portas (tela=UDP/TCP): {ports}", "Puertos (pantalla=UDP/TCP): {ports}"]),
+    ("log.ready", [
+        "Ready — waiting for macOS DualLink client…",
+        "Pronto — aguardando o cliente DualLink do macOS…",
+        "Listo — esperando al cliente DualLink de macOS…",
+    ]),
+    ("log.disconnected_before_pairing", [
+        "Client disconnected before completing pairing",
+        "Cliente desconectou antes de concluir o pareamento",
+        "El cliente se desconectó antes de completar el emparejamiento",
+    ]),
+    ("log.client_connected", [
+        "Client '{name}' connected from {addr}",
+        "Cliente '{name}' conectado de {addr}",
+        "Cliente '{name}' conectado desde {addr}",
+    ]),
+    ("log.client_connected_view_only", [
+        "Client '{name}' connected from {addr} (view-only)",
+        "Cliente '{name}' conectado de {addr} (somente visualização)",
+        "Cliente '{name}' conectado desde {addr} (solo visualización)",
+    ]),
+    ("log.decoder_init_failed", [
+        "[ERROR] Display {n}: decoder init: {error}",
+        "[ERROR] Tela {n}: falha ao iniciar o decodificador: {error}",
+        "[ERROR] Pantalla {n}: error al iniciar el decodificador: {error}",
+    ]),
+    ("log.decoder", ["Decoder: {element} (hw={hw})", "Decodificador: {element} (hw={hw})", "Decodificador: {element} (hw={hw})"]),
+    ("log.decoder_pipeline", [
+        "[ERROR] Display {n}: decoder pipeline: {element}: {message}",
+        "[ERROR] Tela {n}: pipeline do decodificador: {element}: {message}",
+        "[ERROR] Pantalla {n}: pipeline del decodificador: {element}: {message}",
+    ]),
+    ("log.decoder_panicked", [
+        "[ERROR] Display {n}: decoder {element} crashed: {message}",
+        "[ERROR] Tela {n}: o decodificador {element} travou: {message}",
+        "[ERROR] Pantalla {n}: el decodificador {element} falló: {message}",
+    ]),
+    ("log.decode_error", ["Decode error", "Erro de decodificação", "Error de decodificación"]),
+    ("log.suppressed", [
+        "[WARN] {category} ×{count} in last {secs} s",
+        "[WARN] {category} ×{count} nos últimos {secs} s",
+        "[WARN] {category} ×{count} en los últimos {secs} s",
+    ]),
+    ("log.decode_errors", [
+        "[WARN] Decode errors: {count} (last frame {bytes} bytes kf={keyframe})",
+        "[WARN] Erros de decodificação: {count} (último quadro {bytes} bytes kf={keyframe})",
+        "[WARN] Errores de decodificación: {count} (último fotograma {bytes} bytes kf={keyframe})",
+    ]),
+    ("log.session_usage", [
+        "Session used {mb} MB (avg {avg} kbps, peak {peak} kbps)",
+        "A sessão usou {mb} MB (média {avg} kbps, pico {peak} kbps)",
+        "La sesión usó {mb} MB (media {avg} kbps, pico {peak} kbps)",
+    ]),
+    ("log.resolution_change", [
+        "Resolution change {from} → {to}: hot-reloading decoder",
+        "Mudança de resolução {from} → {to}: recarregando o decodificador",
+        "Cambio de resolución {from} → {to}: recargando el decodificador",
+    ]),
+    ("log.coded_size_mismatch", [
+        "Display {n}: stream is {coded}, not the negotiated {configured} — reopening the decoder at {coded}",
+        "Tela {n}: o stream é {coded}, não os {configured} negociados — reabrindo o decodificador em {coded}",
+        "Pantalla {n}: el stream es {coded}, no los {configured} negociados — reabriendo el decodificador en {coded}",
+    ]),
+    ("log.lossless_on", [
+        "Lossless mode on: hot-reloading decoder",
+        "Modo sem perdas ativado: recarregando o decodificador",
+        "Modo sin pérdidas activado: recargando el decodificador",
+    ]),
+    ("log.lossless_off", [
+        "Lossless mode off: hot-reloading decoder",
+        "Modo sem perdas desativado: recarregando o decodificador",
+        "Modo sin pérdidas desactivado: recargando el decodificador",
+    ]),
+    ("log.config_update", [
+        "Config update: {resolution} @ {fps} fps",
+        "Configuração atualizada: {resolution} @ {fps} fps",
+        "Configuración actualizada: {resolution} @ {fps} fps",
+    ]),
+    ("log.monitors_changed", [
+        "Monitors changed ({count} connected)",
+        "Monitores alterados ({count} conectados)",
+        "Monitores cambiados ({count} conectados)",
+    ]),
+    ("log.monitors_changed_on", [
+        "Monitors changed ({count} connected) — showing on {monitor}",
+        "Monitores alterados ({count} conectados) — exibindo em {monitor}",
+        "Monitores cambiados ({count} conectados) — mostrando en {monitor}",
+    ]),
+    ("log.sender_blanked", ["Display {n}: sender blanked the display", "Tela {n}: o emissor ocultou a tela", "Pantalla {n}: el emisor ocultó la pantalla"]),
+    ("log.sender_unblanked", ["Display {n}: sender unblanked the display", "Tela {n}: o emissor voltou a mostrar a tela", "Pantalla {n}: el emisor volvió a mostrar la pantalla"]),
+    ("log.sender_paused", ["Display {n}: sender paused the display", "Tela {n}: o emissor pausou a tela", "Pantalla {n}: el emisor pausó la pantalla"]),
+    ("log.sender_resumed", ["Display {n}: sender resumed the display", "Tela {n}: o emissor retomou a tela", "Pantalla {n}: el emisor reanudó la pantalla"]),
+    ("log.stream_stalled", [
+        "Display {n}: no frames for {secs} s — requesting a keyframe",
+        "Tela {n}: sem quadros há {secs} s — solicitando quadro-chave",
+        "Pantalla {n}: sin fotogramas desde hace {secs} s — solicitando fotograma clave",
+    ]),
+    ("log.file_offered", [
+        "Display {n}: the sender offers {name} ({mb} MB)",
+        "Tela {n}: o emissor oferece {name} ({mb} MB)",
+        "Pantalla {n}: el emisor ofrece {name} ({mb} MB)",
+    ]),
+    ("log.file_offering", [
+        "Display {n}: offering {name} to the sender",
+        "Tela {n}: oferecendo {name} ao emissor",
+        "Pantalla {n}: ofreciendo {name} al emisor",
+    ]),
+    ("log.file_received", ["{name} saved to {path}", "{name} salvo em {path}", "{name} guardado en {path}"]),
+    ("log.file_sent", ["{name} sent", "{name} enviado", "{name} enviado"]),
+    ("log.file_failed", [
+        "Transfer of {name} failed: {reason}",
+        "Falha na transferência de {name}: {reason}",
+        "Falló la transferencia de {name}: {reason}",
+    ]),
+    ("log.file_unreadable", [
+        "Cannot send {path}: {error}",
+        "Não é possível enviar {path}: {error}",
+        "No se puede enviar {path}: {error}",
+    ]),
+    ("log.file_not_connected", [
+        "No sender connected — files can't be sent",
+        "Nenhum emissor conectado — não é possível enviar arquivos",
+        "Ningún emisor conectado — no se pueden enviar archivos",
+    ]),
+    ("log.sender_power", ["Display {n}: sender is {power}", "Tela {n}: o emissor está {power}", "Pantalla {n}: el emisor está {power}"]),
+    ("log.ended_from_window", [
+        "Display {n}: session ended from the window",
+        "Tela {n}: sessão encerrada pela janela",
+        "Pantalla {n}: sesión terminada desde la ventana",
+    ]),
+    ("log.window_hidden", [
+        "Display {n}: window hidden — asking for {fps} fps",
+        "Tela {n}: janela oculta — pedindo {fps} fps",
+        "Pantalla {n}: ventana oculta — pidiendo {fps} fps",
+    ]),
+    ("log.window_shown", [
+        "Display {n}: window shown — back to full rate",
+        "Tela {n}: janela visível — de volta à taxa total",
+        "Pantalla {n}: ventana visible — de vuelta a la tasa completa",
+    ]),
+    ("log.restarting_decoder", ["Display {n}: restarting decoder", "Tela {n}: reiniciando o decodificador", "Pantalla {n}: reiniciando el decodificador"]),
+    ("log.decoder_failed_next", [
+        "Display {n}: decoder {element} failed — trying the next one",
+        "Tela {n}: o decodificador {element} falhou — tentando o próximo",
+        "Pantalla {n}: el decodificador {element} falló — probando el siguiente",
+    ]),
+    ("log.client_disconnected", [
+        "Client disconnected — waiting for new connection…",
+        "Cliente desconectado — aguardando nova conexão…",
+        "Cliente desconectado — esperando una nueva conexión…",
+    ]),
+    ("log.pin_still_valid", ["Pairing PIN still valid: {pin}", "PIN de pareamento ainda válido: {pin}", "El PIN de emparejamiento sigue siendo válido: {pin}"]),
+    ("log.new_pin", ["New pairing PIN: {pin}", "Novo PIN de pareamento: {pin}", "Nuevo PIN de emparejamiento: {pin}"]),
+    ("log.receiver_power", ["Receiver is {power}", "O receptor está {power}", "El receptor está {power}"]),
+    ("log.display_connected", [
+        "Display {n}: '{name}' connected from {addr}",
+        "Tela {n}: '{name}' conectado de {addr}",
+        "Pantalla {n}: '{name}' conectado desde {addr}",
+    ]),
+    ("log.display_connected_view_only", [
+        "Display {n}: '{name}' connected from {addr} (view-only)",
+        "Tela {n}: '{name}' conectado de {addr} (somente visualização)",
+        "Pantalla {n}: '{name}' conectado desde {addr} (solo visualización)",
+    ]),
+    ("log.session_ended", ["Display {n}: session ended ({reason})", "Tela {n}: sessão encerrada ({reason})", "Pantalla {n}: sesión terminada ({reason})"]),
+    ("log.disconnecting", ["Display {n}: disconnecting sender", "Tela {n}: desconectando o emissor", "Pantalla {n}: desconectando el emisor"]),
+    ("log.not_running", ["[WARN] Display {n} is not running", "[WARN] A tela {n} não está em execução", "[WARN] La pantalla {n} no está en ejecución"]),
+    ("log.display_added", ["Display {n} added", "Tela {n} adicionada", "Pantalla {n} añadida"]),
+    ("log.display_add_failed", ["[ERROR] Adding display: {error}", "[ERROR] Ao adicionar tela: {error}", "[ERROR] Al añadir la pantalla: {error}"]),
+    ("log.display_removed", ["Display {n} removed", "Tela {n} removida", "Pantalla {n} eliminada"]),
+    ("log.no_extra_display", ["No extra display to remove", "Nenhuma tela extra para remover", "No hay pantallas extra que quitar"]),
+    ("log.input_enabled", ["Input enabled for new sessions", "Entrada ativada para novas sessões", "Entrada activada para sesiones nuevas"]),
+    ("log.view_only", [
+        "View-only: new sessions will not send input",
+        "Somente visualização: novas sessões não enviarão entrada",
+        "Solo visualización: las sesiones nuevas no enviarán entrada",
+    ]),
+    ("log.recording", ["Recording input", "Gravando entrada", "Grabando entrada"]),
+    ("log.not_recording", ["[WARN] Not recording input", "[WARN] A entrada não está sendo gravada", "[WARN] No se está grabando la entrada"]),
+    ("log.recording_saved", [
+        "Saved {count} input events to {path}",
+        "{count} eventos de entrada salvos em {path}",
+        "{count} eventos de entrada guardados en {path}",
+    ]),
+    ("log.recording_save_failed", [
+        "[ERROR] Saving input recording: {error}",
+        "[ERROR] Ao salvar a gravação de entrada: {error}",
+        "[ERROR] Al guardar la grabación de entrada: {error}",
+    ]),
+    ("log.recording_load_failed", [
+        "[ERROR] Loading input recording: {error}",
+        "[ERROR] Ao carregar a gravação de entrada: {error}",
+        "[ERROR] Al cargar la grabación de entrada: {error}",
+    ]),
+    ("log.replaying", [
+        "Replaying {count} input events ({seconds} s)",
+        "Reproduzindo {count} eventos de entrada ({seconds} s)",
+        "Reproduciendo {count} eventos de entrada ({seconds} s)",
+    ]),
+    ("log.replay_stopped", ["Input replay stopped", "Reprodução de entrada parada", "Reproducción de entrada detenida"]),
+    ("log.no_replay", ["[WARN] No input replay running", "[WARN] Nenhuma reprodução de entrada em andamento", "[WARN] No hay ninguna reproducción de entrada en curso"]),
+    ("log.save_failed", ["[WARN] Saving {what}: {error}", "[WARN] Ao salvar {what}: {error}", "[WARN] Al guardar {what}: {error}"]),
+    ("log.input_setting", ["input setting", "configuração de entrada", "configuración de entrada"]),
+    ("log.decoder_setting", ["decoder preference", "preferência de decodificador", "preferencia de decodificador"]),
+    ("log.language_setting", ["language", "idioma", "idioma"]),
+    ("log.firewall_blocked", [
+        "[WARN] {firewall} may block senders on {ports}",
+        "[WARN] {firewall} pode bloquear os transmissores nas portas {ports}",
+        "[WARN] {firewall} puede bloquear a los emisores en los puertos {ports}",
+    ]),
+    ("log.firewall_opened", [
+        "Firewall opened for {ports}",
+        "Firewall liberado para {ports}",
+        "Cortafuegos abierto para {ports}",
+    ]),
+    ("log.firewall_open_failed", [
+        "[ERROR] Opening the firewall: {error}",
+        "[ERROR] Falha ao liberar o firewall: {error}",
+        "[ERROR] No se pudo abrir el cortafuegos: {error}",
+    ]),
+    ("log.ports_privileged", [
+        "[ERROR] Port {port} is privileged. Run as root, grant cap_net_bind_service\n(sudo setcap cap_net_bind_service=+ep <binary>) or pick a base port above 1024.",
+        "[ERROR] A porta {port} é privilegiada. Execute como root, conceda cap_net_bind_service\n(sudo setcap cap_net_bind_service=+ep <binário>) ou escolha uma porta base acima de 1024.",
+        "[ERROR] El puerto {port} es privilegiado. Ejecuta como root, concede cap_net_bind_service\n(sudo setcap cap_net_bind_service=+ep <binario>) o elige un puerto base por encima de 1024.",
+    ]),
+    ("log.diagnostics_written", [
+        "Diagnostics written to {path}",
+        "Diagnóstico salvo em {path}",
+        "Diagnóstico guardado en {path}",
+    ]),
+    ("log.diagnostics_failed", [
+        "[ERROR] Writing diagnostics: {error}",
+        "[ERROR] Falha ao salvar o diagnóstico: {error}",
+        "[ERROR] No se pudo guardar el diagnóstico: {error}",
+    ]),
+    ("log.appearance_setting", ["appearance", "aparência", "apariencia"]),
+    ("log.decoder_preference", ["Decoder preference: {element}", "Preferência de decodificador: {element}", "Preferencia de decodificador: {element}"]),
+    ("log.decoder_preference_auto", ["Decoder preference: auto", "Preferência de decodificador: automática", "Preferencia de decodificador: automática"]),
+];
@@ -14,12 +14,17 @@
 //! values for use when the display is rendered inside an egui panel rather than
 //! a standalone GStreamer window.  Coordinates are normalised to [0.0, 1.0].
 //!
+//! ## Gestures
+//! Both paths run touch points and zoom factors through a
+//! [`GestureTracker`](duallink_core::GestureTracker), so pinch, rotation and
+//! swipe reach the sender with `Begin` / `Changed` / `End` phases.
+//!
 //! ## Serialisation
 //! All `InputEvent` values are JSON-serialised and sent over the existing TLS
 //! TCP signaling connection (Linux → Mac direction) as `input_event` messages.
 
-use duallink_core::{GesturePhase, InputEvent, MouseButton};
-use egui::{Event, Key, PointerButton, Rect};
+use duallink_core::{GestureTracker, InputEvent, MouseButton};
+use egui::{Event, Key, PointerButton, Rect, TouchPhase};
 use tracing::trace;
 
 // ── EguiInputBridge ────────────────────────────────────────────────────────────
@@ -42,6 +47,8 @@ pub struct EguiInputBridge {
     /// Last normalised mouse position — used to attach position to scroll
     /// events which egui emits without an explicit coord.
     last_pos: Option<(f64, f64)>,
+    /// Phases for touch gestures and `Zoom` steps.
+    gestures: GestureTracker,
 }
 
 impl EguiInputBridge {
@@ -55,13 +62,30 @@ impl EguiInputBridge {
     /// pointer coordinates can be normalised.  Returns only events that map
     /// directly to `InputEvent` variants; UI-only egui events are silently
     /// dropped.
+    ///
+    /// Call once per frame with that frame's events: a pinch from `Zoom`
+    /// events ends with the first frame that has none.
     pub fn convert(&mut self, events: &[Event], viewport: Rect) -> Vec<InputEvent> {
+        self.gestures.set_aspect((viewport.width() / viewport.height().max(1.0)) as f64);
         let mut out = Vec::new();
         for ev in events {
-            if let Some(ie) = self.map_event(ev, viewport) {
-                out.push(ie);
+            match ev {
+                Event::Touch { id, phase, pos, .. } => {
+                    let (nx, ny) = self.normalise(pos.x, pos.y, viewport);
+                    out.extend(match phase {
+                        TouchPhase::Start => self.gestures.touch_down(id.0, nx, ny),
+                        TouchPhase::Move => self.gestures.touch_move(id.0, nx, ny),
+                        TouchPhase::End => self.gestures.touch_up(id.0),
+                        TouchPhase::Cancel => self.gestures.cancel(),
+                    });
+                }
+                Event::PointerGone | Event::WindowFocused(false) => out.extend(self.gestures.cancel()),
+                _ => out.extend(self.map_event(ev, viewport)),
             }
         }
+        if !events.iter().any(|ev| matches!(ev, Event::Zoom(_))) {
+            out.extend(self.gestures.end_zoom());
+        }
         out
     }
 
@@ -135,10 +159,12 @@ impl EguiInputBridge {
             }
 
             // ── Touchpad gestures (egui 0.29+) ─────────────────────────────
+            // egui has no pinch phases: consecutive frames with Zoom events
+            // form one Begin … End pinch (see `convert`).
             Event::Zoom(factor) => {
                 let (x, y) = self.last_pos.unwrap_or((0.5, 0.5));
                 let mag = (*factor as f64) - 1.0; // delta from unity
-                Some(InputEvent::GesturePinch { x, y, magnification: mag, phase: GesturePhase::Changed })
+                Some(self.gestures.zoom(x, y, mag))
             }
 
             _ => None,
@@ -262,6 +288,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn zoom_frames_form_one_pinch() {
+        let mut bridge = EguiInputBridge::new();
+        let phase = |out: Vec<InputEvent>| match out.as_slice() {
+            [InputEvent::GesturePinch { phase, .. }] => *phase,
+            other => panic!("expected one pinch, got {other:?}"),
+        };
+        assert_eq!(phase(bridge.convert(&[Event::Zoom(1.1)], full_rect())), DlGesturePhase::Begin);
+        assert_eq!(phase(bridge.convert(&[Event::Zoom(1.1)], full_rect())), DlGesturePhase::Changed);
+        assert_eq!(phase(bridge.convert(&[], full_rect())), DlGesturePhase::End);
+        assert!(bridge.convert(&[], full_rect()).is_empty());
+    }
+
     #[test]
     fn key_mapping_roundtrip() {
         assert_eq!(key_to_x11_keyval(Key::A), 0x0061);
@@ -0,0 +1,40 @@
+//! One display's receive → decode → display loop, shared by the receiver
+//! frontends (`duallink-app` and `duallink-gui`).
+//!
+//! # Lifecycle
+//! ```text
+//! waiting ──hello──▶ decoder open ──▶ streaming ──stop / disconnect──▶ waiting
+//!                        ▲                │
+//!                        └── reload ◀─────┘  resolution or lossless change,
+//!                                            decoder failure, restart request
+//! ```
+//! A [`ReceiverSession`] runs those states for as long as its display is
+//! bound: it waits for a sender's `hello`, opens a decoder, streams into it
+//! and, when the session ends, goes back to waiting on the same ports. A
+//! reload reopens the decoder without a new `hello`; a decoder that posted a
+//! pipeline error is excluded from every later session on the display.
+//!
+//! A reconnecting sender would otherwise see the decoder's warm-up as a
+//! second of black screen: as soon as a sender's connection comes in, a
+//! decoder for the last session's config is built and held paused, and
+//! the `hello` that follows takes it if it fits
+//! ([`prebuilt_fit`](lifecycle::prebuilt_fit)).
+//!
+//! While streaming it arms the keyframe gate for each new decoder, forwards
+//! window input to the sender, keeps the screen awake, answers preview and
+//! pacing requests, tells the sender the receiver's power state and logs the
+//! decoder's counters. Frames, session starts and ends and a summary every
+//! second go to its [`StatsSink`](duallink_core::StatsSink)s. The decisions,
+//! free of I/O, live in [`lifecycle`].
+//!
+//! # Frontends
+//! A frontend implements [`SessionHooks`]: it picks the display output each
+//! decoder opens and hears about everything the session does — to update its
+//! own state, or to hand back user actions ([`SessionAction`]) from
+//! [`SessionHooks::tick`].
+
+pub mod lifecycle;
+mod session;
+
+pub use lifecycle::{prebuilt_fit, reload_reason, ExitReason, Lifecycle, Next, PrebuiltFit, Reload};
+pub use session::{Opener, ReceiverSession, SessionAction, SessionHooks, SessionInfo, ACTION_POLL};
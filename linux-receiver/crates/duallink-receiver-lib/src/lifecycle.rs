@@ -0,0 +1,246 @@
+//! Session lifecycle decisions, free of I/O.
+//!
+//! [`Lifecycle`] carries a display's state from one session to the next —
+//! the config a reload reopens the decoder with, the last session's config
+//! a decoder is built ahead with, decoders that failed, the input policy —
+//! and decides what follows each session's end.
+
+use std::fmt;
+
+use duallink_core::StreamConfig;
+
+// MARK: - ExitReason
+
+/// Why a session's streaming loop ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    /// The sender sent `stop_session`.
+    SessionStopped,
+    /// The sender's signaling connection dropped.
+    ClientDisconnected,
+    /// A config update needs a new decoder (see [`reload_reason`]).
+    ConfigUpdated,
+    /// The stream's SPS has another size than the session negotiated; the
+    /// decoder is reopened at the coded size.
+    CodedSizeMismatch,
+    /// The frontend asked for a decoder restart.
+    DecoderRestart,
+    /// The decoder's pipeline posted an error.
+    DecoderFailed,
+    /// No decoder could be opened for the session.
+    DecoderInitFailed,
+    /// The decode thread exited.
+    DecodeThreadGone,
+    /// The transport shut the display down; no more sessions come.
+    ChannelsClosed,
+}
+
+impl ExitReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SessionStopped => "session_stopped",
+            Self::ClientDisconnected => "client_disconnected",
+            Self::ConfigUpdated => "config_updated",
+            Self::CodedSizeMismatch => "coded_size_mismatch",
+            Self::DecoderRestart => "decoder_restart",
+            Self::DecoderFailed => "decoder_failed",
+            Self::DecoderInitFailed => "decoder_init_failed",
+            Self::DecodeThreadGone => "decode_thread_gone",
+            Self::ChannelsClosed => "channels_closed",
+        }
+    }
+}
+
+impl fmt::Display for ExitReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// MARK: - Reload
+
+/// Why a mid-session config update needs a new decoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reload {
+    Resolution,
+    /// Lossless (4:4:4) mode was switched on or off.
+    Lossless,
+}
+
+/// Whether going from `current` to `new` needs a new decoder; other
+/// changes (frame rate, bitrate, preset) are the sender's business.
+pub fn reload_reason(current: &StreamConfig, new: &StreamConfig) -> Option<Reload> {
+    if new.resolution != current.resolution {
+        Some(Reload::Resolution)
+    } else if new.lossless != current.lossless {
+        Some(Reload::Lossless)
+    } else {
+        None
+    }
+}
+
+// MARK: - Prebuilt
+
+/// What a decoder built ahead of a session, for config `built`, needs to
+/// take the session's config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrebuiltFit {
+    /// Usable as it is.
+    Ready,
+    /// Usable once its input caps follow the session's colorimetry, HDR
+    /// metadata or NAL alignment.
+    NewCaps,
+    /// Built for another resolution, codec or lossless mode: replaced.
+    Rebuild,
+}
+
+pub fn prebuilt_fit(built: &StreamConfig, session: &StreamConfig) -> PrebuiltFit {
+    if reload_reason(built, session).is_some() || built.codec != session.codec {
+        PrebuiltFit::Rebuild
+    } else if built.color != session.color || built.hdr != session.hdr || built.nal_slices != session.nal_slices {
+        PrebuiltFit::NewCaps
+    } else {
+        PrebuiltFit::Ready
+    }
+}
+
+// MARK: - Lifecycle
+
+/// What a display does after a session's streaming loop ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Next {
+    /// The session goes on: open a new decoder for the pending config.
+    Reload,
+    /// Wait for the next sender's `hello`.
+    WaitForSender,
+    /// The display is gone.
+    Exit,
+}
+
+/// One display's state across sessions.
+#[derive(Debug)]
+pub struct Lifecycle {
+    sessions:        u32,
+    /// Config to reopen the decoder with, without waiting for a `hello`.
+    pending:         Option<StreamConfig>,
+    /// Config of the last session that ended, for building the next
+    /// session's decoder before its `hello`.
+    last:            Option<StreamConfig>,
+    /// Decoder elements that posted a pipeline error; skipped for the rest
+    /// of the display's lifetime.
+    failed_decoders: Vec<String>,
+    /// Input policy negotiated for the current session; kept across
+    /// reloads.
+    allow_input:     bool,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self { sessions: 0, pending: None, last: None, failed_decoders: Vec::new(), allow_input: true }
+    }
+}
+
+impl Lifecycle {
+    /// Sessions started so far.
+    pub fn sessions(&self) -> u32 {
+        self.sessions
+    }
+
+    pub fn allow_input(&self) -> bool {
+        self.allow_input
+    }
+
+    pub fn failed_decoders(&self) -> &[String] {
+        &self.failed_decoders
+    }
+
+    /// A sender's `hello` was accepted; returns the session's number.
+    pub fn session_started(&mut self, allow_input: bool) -> u32 {
+        self.sessions += 1;
+        self.allow_input = allow_input;
+        self.sessions
+    }
+
+    /// Reopen the decoder with `config` once the streaming loop ends.
+    pub fn request_reload(&mut self, config: StreamConfig) {
+        self.pending = Some(config);
+    }
+
+    /// The config of the last session that ended, if any.
+    pub fn last_config(&self) -> Option<&StreamConfig> {
+        self.last.as_ref()
+    }
+
+    /// The config of a pending reload, if any.
+    pub fn take_pending(&mut self) -> Option<StreamConfig> {
+        self.pending.take()
+    }
+
+    /// The session streaming `config` ended for `reason`, with
+    /// `failed_element` the decoder that failed, if one did.
+    pub fn session_ended(&mut self, reason: ExitReason, config: StreamConfig, failed_element: Option<String>) -> Next {
+        if reason == ExitReason::ChannelsClosed {
+            self.pending = None;
+            return Next::Exit;
+        }
+        self.last = Some(config.clone());
+        // The session is still alive: restart without the failed decoder.
+        if let Some(element) = failed_element {
+            self.failed_decoders.push(element);
+            self.pending.get_or_insert(config);
+        }
+        if self.pending.is_some() {
+            Next::Reload
+        } else {
+            Next::WaitForSender
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use duallink_core::Resolution;
+
+    #[test]
+    fn reloads_only_for_decoder_relevant_changes() {
+        let current = StreamConfig::default();
+        assert_eq!(reload_reason(&current, &StreamConfig { target_fps: 60, ..current.clone() }), None);
+        let bigger = StreamConfig { resolution: Resolution::new(2560, 1440), ..current.clone() };
+        assert_eq!(reload_reason(&current, &bigger), Some(Reload::Resolution));
+        let lossless = StreamConfig { lossless: true, ..current.clone() };
+        assert_eq!(reload_reason(&current, &lossless), Some(Reload::Lossless));
+
+        assert_eq!(prebuilt_fit(&current, &StreamConfig { target_fps: 60, ..current.clone() }), PrebuiltFit::Ready);
+        let slices = StreamConfig { nal_slices: true, ..current.clone() };
+        assert_eq!(prebuilt_fit(&current, &slices), PrebuiltFit::NewCaps);
+        assert_eq!(prebuilt_fit(&current, &bigger), PrebuiltFit::Rebuild);
+        let hevc = StreamConfig { codec: duallink_core::VideoCodec::H265, ..current.clone() };
+        assert_eq!(prebuilt_fit(&current, &hevc), PrebuiltFit::Rebuild);
+    }
+
+    #[test]
+    fn decides_what_follows_a_session() {
+        let config = StreamConfig::default();
+        let mut lifecycle = Lifecycle::default();
+        assert_eq!(lifecycle.session_started(false), 1);
+        assert!(!lifecycle.allow_input());
+
+        // A failed decoder is excluded and the same session reopened.
+        let next = lifecycle.session_ended(ExitReason::DecoderFailed, config.clone(), Some("vah264dec".into()));
+        assert_eq!(next, Next::Reload);
+        assert_eq!(lifecycle.failed_decoders(), ["vah264dec"]);
+        assert_eq!(lifecycle.take_pending(), Some(config.clone()));
+
+        lifecycle.request_reload(StreamConfig { lossless: true, ..config.clone() });
+        assert_eq!(lifecycle.session_ended(ExitReason::ConfigUpdated, config.clone(), None), Next::Reload);
+        assert!(lifecycle.take_pending().is_some_and(|c| c.lossless));
+
+        assert_eq!(lifecycle.session_ended(ExitReason::ClientDisconnected, config.clone(), None), Next::WaitForSender);
+        assert_eq!(lifecycle.last_config(), Some(&config));
+        lifecycle.request_reload(config.clone());
+        assert_eq!(lifecycle.session_ended(ExitReason::ChannelsClosed, config, None), Next::Exit);
+        assert_eq!(lifecycle.take_pending(), None);
+        assert_eq!(lifecycle.failed_decoders().len(), 1, "failures outlast sessions");
+    }
+}
@@ -0,0 +1,636 @@
+//! [`ReceiverSession`] — one display's session loop — and the
+//! [`SessionHooks`] frontends plug into it.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use duallink_core::errors::DecoderError;
+use duallink_core::{
+    coded_size, configured_capture_system_keys, read_power, FileTransferEvent, FrameSample, HiddenMode,
+    IdleInhibitor, IntervalMeter, PowerState, RateLimiter, Resolution, SessionEvent, StatsSink, StatsSinks,
+    StreamConfig, HIDDEN_FPS, POWER_POLL_INTERVAL, STATS_INTERVAL,
+};
+use duallink_decoder::{AsyncDecoder, DecoderStats, DisplayOutput, InputEvents};
+use duallink_transport::{DisplayChannels, InputSender, SignalingEvent, PREVIEW_INTERVAL, PREVIEW_WIDTH};
+use tracing::{debug, info, warn};
+
+use crate::lifecycle::{prebuilt_fit, reload_reason, ExitReason, Lifecycle, Next, PrebuiltFit, Reload};
+
+/// How often [`SessionHooks::tick`] is asked for user actions.
+pub const ACTION_POLL: Duration = Duration::from_millis(250);
+
+/// Pause before waiting for the next sender, so the OS has time to clean up
+/// the previous TCP connection.
+const RECONNECT_PAUSE: Duration = Duration::from_millis(300);
+
+/// Creates a session's display output; runs on the decode thread.
+pub type Opener = Box<dyn FnOnce() -> Result<Box<dyn DisplayOutput>, DecoderError> + Send>;
+
+// ── Hooks ─────────────────────────────────────────────────────────────────────
+
+/// The sender of a session that just started.
+#[derive(Debug, Clone)]
+pub struct SessionInfo {
+    pub display:     u8,
+    /// 1-based count of sessions on this display.
+    pub number:      u32,
+    pub session_id:  String,
+    pub device_name: String,
+    pub client_addr: SocketAddr,
+    /// `false` for a view-only session.
+    pub allow_input: bool,
+}
+
+/// A user action a frontend hands the session from [`SessionHooks::tick`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionAction {
+    /// Hold the shown frame while the sender keeps streaming, or go live.
+    ToggleFreeze,
+    /// Blank the window and pause the sender's capture, or resume both.
+    ToggleBlank,
+    /// Stop or resume this display's stream.
+    TogglePause,
+    /// Tear down and recreate the decoder, keeping the session.
+    RestartDecoder,
+    /// Capture system shortcuts in the window and forward them, or leave
+    /// them to this desktop.
+    ToggleSystemKeys,
+}
+
+/// How a frontend takes part in a [`ReceiverSession`].
+///
+/// Only [`opener`](Self::opener) is required; everything else reports what
+/// the session does (which it also logs) and defaults to nothing. Frame
+/// counts go to the session's [`StatsSink`]s instead (see
+/// [`ReceiverSession::with_stats_sink`]).
+#[allow(unused_variables)]
+pub trait SessionHooks: Send {
+    /// The output to open for a session streaming `config`; `excluded`
+    /// decoders failed earlier and must not be picked again.
+    fn opener(&mut self, config: &StreamConfig, excluded: &[String]) -> Opener;
+
+    /// Run on the decode thread after every frame the output accepted, with
+    /// the frame's size in bytes.
+    fn on_frame(&mut self) -> Box<dyn FnMut(usize) + Send> {
+        Box::new(|_| {})
+    }
+
+    /// A sender's `hello` was accepted.
+    fn session_started(&mut self, session: &SessionInfo, config: &StreamConfig) {}
+
+    /// A sender connected and left again before its `hello`.
+    fn disconnected_before_hello(&mut self) {}
+
+    /// A decoder was opened, for a new session or a reload.
+    fn decoder_ready(&mut self, decoder: &AsyncDecoder) {}
+
+    /// No decoder could be opened; the session is skipped.
+    fn decoder_init_failed(&mut self, error: &DecoderError) {}
+
+    /// The decoder `element` posted a pipeline error; the session restarts
+    /// without it.
+    fn decoder_failed(&mut self, element: &str, error: &DecoderError) {}
+
+    /// The sender changed its stream config; `reload` says why the decoder
+    /// is reopened, if it is.
+    fn config_updated(&mut self, current: &StreamConfig, new: &StreamConfig, reload: Option<Reload>) {}
+
+    /// The first keyframe's SPS says the stream is `coded`, not the
+    /// `configured` size of its session; the decoder is reopened at `coded`.
+    fn coded_size_mismatch(&mut self, configured: Resolution, coded: Resolution) {}
+
+    /// Any other signaling event during a session, before the session
+    /// handles it.
+    fn event(&mut self, event: &SignalingEvent) {}
+
+    /// The end-session hotkey was pressed in the video window.
+    fn ended_from_window(&mut self) {}
+
+    /// The video window was hidden or shown again.
+    fn visibility_changed(&mut self, hidden: bool) {}
+
+    /// This machine's power source changed.
+    fn power_changed(&mut self, power: Option<PowerState>) {}
+
+    /// Polled every [`ACTION_POLL`] with the decoder's counters and whether
+    /// the stream is paused; returns the actions the user asked for since.
+    fn tick(&mut self, stats: &DecoderStats, paused: bool) -> Vec<SessionAction> {
+        Vec::new()
+    }
+
+    /// A session's decoder was shut down; `next` says what follows.
+    fn session_ended(&mut self, reason: ExitReason, next: Next, totals: &DecoderStats) {}
+}
+
+// ── ReceiverSession ───────────────────────────────────────────────────────────
+
+/// A decoder built, and held paused, before its session's `hello`.
+struct Prebuilt {
+    /// Config it was built for — the last session's.
+    config:       StreamConfig,
+    decoder:      AsyncDecoder,
+    input_events: InputEvents,
+}
+
+/// Runs one display's sessions, one after the other, for as long as the
+/// display is bound.
+pub struct ReceiverSession<H> {
+    channels:     DisplayChannels,
+    input_sender: InputSender,
+    hooks:        H,
+    lifecycle:    Lifecycle,
+    /// This machine's power source, re-read during sessions.
+    local_power:  Option<PowerState>,
+    stats:        StatsSinks,
+    prebuilt:     Option<Prebuilt>,
+}
+
+impl<H: SessionHooks> ReceiverSession<H> {
+    pub fn new(channels: DisplayChannels, input_sender: InputSender, hooks: H) -> Self {
+        let stats = StatsSinks::configured();
+        Self {
+            channels,
+            input_sender,
+            hooks,
+            lifecycle: Lifecycle::default(),
+            local_power: None,
+            stats,
+            prebuilt: None,
+        }
+    }
+
+    /// Also report frames, sessions and interval summaries to `sink`, after
+    /// the `DUALLINK_STATS_FILE` logger if one is configured.
+    pub fn with_stats_sink(mut self, sink: impl StatsSink + 'static) -> Self {
+        self.stats.push(sink);
+        self
+    }
+
+    pub fn display_index(&self) -> u8 {
+        self.channels.display_index
+    }
+
+    /// Run sessions until the display is removed or the receiver shuts
+    /// down; returns the hooks.
+    pub async fn run(mut self) -> H {
+        let idx = self.channels.display_index;
+        info!("Display[{idx}] Waiting for sender to connect...");
+        loop {
+            let config = match self.lifecycle.take_pending() {
+                Some(config) => {
+                    info!("Display[{idx}] Reloading decoder with updated config: {:?}", config);
+                    config
+                }
+                None => match self.wait_for_sender().await {
+                    Some(config) => config,
+                    None => break,
+                },
+            };
+
+            let (reason, failed_element, totals) = self.stream(&config).await;
+            if let Some(element) = &failed_element {
+                warn!("Display[{idx}] Excluding decoder {} and restarting", element);
+            }
+            let next = self.lifecycle.session_ended(reason, config, failed_element);
+            self.hooks.session_ended(reason, next, &totals);
+            if next != Next::Reload {
+                self.stats.on_session_event(&SessionEvent::Ended { display: idx, reason: reason.to_string() });
+            }
+            match next {
+                Next::Reload => {}
+                Next::WaitForSender => {
+                    tokio::time::sleep(RECONNECT_PAUSE).await;
+                    info!("Display[{idx}] Session #{} ended — ready for next connection", self.lifecycle.sessions());
+                }
+                Next::Exit => break,
+            }
+        }
+        info!("Display[{idx}] Receiver loop exited (total sessions: {}).", self.lifecycle.sessions());
+        self.hooks
+    }
+
+    /// Wait for the next sender's `hello`; `None` once the signaling
+    /// channel is closed for good.
+    async fn wait_for_sender(&mut self) -> Option<StreamConfig> {
+        let idx = self.channels.display_index;
+        loop {
+            match self.channels.event_rx.recv().await {
+                Some(SignalingEvent::SessionStarted { session_id, device_name, config, client_addr, allow_input }) => {
+                    let number = self.lifecycle.session_started(allow_input);
+                    info!(
+                        "Display[{}] Session #{} started: id={} from='{}' addr={} config={:?}{}",
+                        idx, number, session_id, device_name, client_addr, config,
+                        if allow_input { "" } else { " (view-only)" }
+                    );
+                    let session =
+                        SessionInfo { display: idx, number, session_id, device_name, client_addr, allow_input };
+                    self.hooks.session_started(&session, &config);
+                    self.stats.on_session_event(&SessionEvent::Started {
+                        display:    idx,
+                        session_id: session.session_id,
+                        peer:       session.device_name,
+                    });
+                    return Some(config);
+                }
+                Some(SignalingEvent::SenderConnected { client_addr }) => {
+                    debug!("Display[{idx}] {} connected — building a decoder ahead of its hello", client_addr);
+                    self.prebuild().await;
+                }
+                Some(SignalingEvent::ClientDisconnected) => {
+                    warn!("Display[{idx}] Client disconnected before hello — waiting again");
+                    if let Some(prebuilt) = self.prebuilt.take() {
+                        prebuilt.decoder.shutdown().await;
+                    }
+                    self.hooks.disconnected_before_hello();
+                }
+                Some(other) => debug!("Display[{idx}] Pre-session event: {:?}", other),
+                None => {
+                    info!("Display[{idx}] Signaling channel closed");
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Build a decoder for the last session's config and hold it paused,
+    /// unless one is built already or no session ran yet.
+    async fn prebuild(&mut self) {
+        let idx = self.channels.display_index;
+        let Some(config) = self.lifecycle.last_config().filter(|_| self.prebuilt.is_none()).cloned() else {
+            return;
+        };
+        let open = self.hooks.opener(&config, self.lifecycle.failed_decoders());
+        let on_frame = self.hooks.on_frame();
+        match AsyncDecoder::spawn(idx, open, on_frame).await {
+            Ok((decoder, input_events)) => {
+                decoder.set_playing(false).await;
+                debug!("Display[{idx}] Decoder {} ready ahead of hello", decoder.element_name());
+                self.prebuilt = Some(Prebuilt { config, decoder, input_events });
+            }
+            // The session opens its own and reports it if that fails too.
+            Err(e) => debug!("Display[{idx}] No decoder ahead of hello: {}", e),
+        }
+    }
+
+    /// The prebuilt decoder, set playing, if it can take a session
+    /// streaming `config`; shut down if not.
+    async fn take_prebuilt(&mut self, config: &StreamConfig) -> Option<(AsyncDecoder, InputEvents)> {
+        let idx = self.channels.display_index;
+        let Prebuilt { config: built, decoder, input_events } = self.prebuilt.take()?;
+        let fits = match prebuilt_fit(&built, config) {
+            PrebuiltFit::Ready => true,
+            PrebuiltFit::NewCaps => decoder.set_stream(config.clone()).await,
+            PrebuiltFit::Rebuild => false,
+        };
+        if !fits {
+            info!("Display[{idx}] Decoder built ahead doesn't fit {} — opening a new one", config.resolution);
+            decoder.shutdown().await;
+            return None;
+        }
+        decoder.set_playing(true).await;
+        info!("Display[{idx}] Using the decoder built ahead for {}", config.resolution);
+        Some((decoder, input_events))
+    }
+
+    /// Open a decoder for `config` and stream into it until the session
+    /// ends or needs a new decoder. Returns why, the decoder that failed (if
+    /// one did) and the decoder's final counters.
+    async fn stream(&mut self, config: &StreamConfig) -> (ExitReason, Option<String>, DecoderStats) {
+        let idx = self.channels.display_index;
+        // A fresh decoder can't use delta frames until the next keyframe.
+        self.channels.keyframes.arm();
+        let prebuilt = self.take_prebuilt(config).await;
+        let spawned = match prebuilt {
+            Some(d) => Ok(d),
+            None => {
+                let open = self.hooks.opener(config, self.lifecycle.failed_decoders());
+                AsyncDecoder::spawn(idx, open, self.hooks.on_frame()).await
+            }
+        };
+        let (decoder, input_events) = match spawned {
+            Ok(d) => d,
+            Err(e) => {
+                warn!("Display[{idx}] Decoder init failed: {} — skipping session", e);
+                self.hooks.decoder_init_failed(&e);
+                return (ExitReason::DecoderInitFailed, None, DecoderStats::default());
+            }
+        };
+        info!(
+            "Display[{}] Decoder ready: {} hw={} — video window should appear",
+            idx, decoder.element_name(), decoder.is_hardware_accelerated()
+        );
+        decoder.set_input_enabled(self.lifecycle.allow_input()).await;
+        if self.lifecycle.allow_input() && configured_capture_system_keys() {
+            decoder.set_system_keys(true).await;
+        }
+        decoder.set_latency_mode(config.latency_mode).await;
+        // A privacy blank outlasts the session that started it. So does a
+        // pause: no frames come, so show black rather than a stale picture.
+        if self.channels.blank.is_requested() || self.channels.pause.is_paused() {
+            decoder.set_blanked(true).await;
+        }
+        forward_input(input_events, self.input_sender.for_display(idx));
+        self.hooks.decoder_ready(&decoder);
+
+        // Keep the screen awake while the stream is shown.
+        let idle_inhibitor = inhibit_idle().await;
+        info!("Display[{idx}] Streaming — receiving and displaying frames...");
+        let mut frames_received: u64 = 0;
+        let mut failed_element = None;
+        let reason = self.stream_frames(&decoder, config, &mut frames_received, &mut failed_element).await;
+
+        // Stop the decode thread and wait for the window to close.
+        drop(idle_inhibitor);
+        self.channels.pace.request(None);
+        let totals = decoder.shutdown().await;
+        info!(
+            "Display[{}] Session #{} complete ({}). received={} errors={} duplicates={}",
+            idx, self.lifecycle.sessions(), reason, frames_received, totals.push_errors, totals.duplicates
+        );
+        (reason, failed_element, totals)
+    }
+
+    /// The receive → decode loop of one decoder.
+    async fn stream_frames(
+        &mut self,
+        decoder: &AsyncDecoder,
+        config: &StreamConfig,
+        frames_received: &mut u64,
+        failed_element: &mut Option<String>,
+    ) -> ExitReason {
+        let Self { channels: ch, hooks, lifecycle, local_power, stats, .. } = self;
+        let idx = ch.display_index;
+        let mut preview_tick = tokio::time::interval(PREVIEW_INTERVAL);
+        let mut power_tick = tokio::time::interval(POWER_POLL_INTERVAL);
+        let mut action_tick = tokio::time::interval(ACTION_POLL);
+        let mut stats_tick = tokio::time::interval(STATS_INTERVAL);
+        let mut meter = IntervalMeter::default();
+        // Gap and bitrate warnings can come every frame on a bad link.
+        let mut warnings = RateLimiter::default();
+        // Bytes of the frame being received in pieces (slice-level decoding).
+        let mut unit_bytes = 0;
+        // Whether a keyframe's SPS was checked against the config yet.
+        let mut size_checked = false;
+
+        loop {
+            tokio::select! {
+                frame = ch.frame_rx.recv() => {
+                    let Some(frame) = frame else { return ExitReason::ChannelsClosed };
+                    // Leading slices of a frame count with the frame they start.
+                    unit_bytes += frame.data.len();
+                    let complete = !frame.partial;
+                    if complete {
+                        *frames_received += 1;
+                        if *frames_received <= 5 {
+                            debug!(
+                                "Display[{}] Frame #{}: {} bytes keyframe={}",
+                                idx, frames_received, unit_bytes, frame.is_keyframe
+                            );
+                        }
+                        if *frames_received % 300 == 0 {
+                            let stats = decoder.stats();
+                            info!(
+                                "Display[{}] Stats: received={} errors={} unique={} duplicates={}",
+                                idx, frames_received, stats.push_errors, stats.frames_unique, stats.duplicates
+                            );
+                        }
+                    }
+                    let keyframe = frame.is_keyframe;
+                    if keyframe && !size_checked {
+                        size_checked = true;
+                        if let Some(coded) = coded_size(config.codec, &frame.data).filter(|s| *s != config.resolution) {
+                            warn!(
+                                "Display[{}] Stream is {} but the session negotiated {} — reopening the decoder",
+                                idx, coded, config.resolution
+                            );
+                            hooks.coded_size_mismatch(config.resolution, coded);
+                            lifecycle.request_reload(StreamConfig { resolution: coded, ..config.clone() });
+                            return ExitReason::CodedSizeMismatch;
+                        }
+                    }
+                    match decoder.push(frame).await {
+                        Ok(()) if !complete => {}
+                        Ok(()) => {
+                            let bytes = std::mem::take(&mut unit_bytes);
+                            let errors = decoder.stats().push_errors;
+                            let sample = FrameSample { display: idx, bytes, keyframe, errors };
+                            meter.record(&sample);
+                            stats.on_frame(&sample);
+                        }
+                        Err(e @ (DecoderError::Pipeline { .. } | DecoderError::Panicked(_))) => {
+                            if let DecoderError::Pipeline { source_element, message, debug } = &e {
+                                warn!(
+                                    "Display[{}] Decoder pipeline failed in {}: {} ({})",
+                                    idx, source_element, message, debug.as_deref().unwrap_or("no debug info")
+                                );
+                            } else {
+                                warn!("Display[{idx}] {e} — restarting with another decoder");
+                            }
+                            hooks.decoder_failed(decoder.element_name(), &e);
+                            *failed_element = Some(decoder.element_name().to_string());
+                            return ExitReason::DecoderFailed;
+                        }
+                        Err(e) => {
+                            warn!("Display[{idx}] Decode thread gone ({}) — stopping session", e);
+                            return ExitReason::DecodeThreadGone;
+                        }
+                    }
+                }
+
+                // Signaling events mid-session
+                event = ch.event_rx.recv() => {
+                    let Some(event) = event else { return ExitReason::ChannelsClosed };
+                    if let SignalingEvent::ConfigUpdated { config: new } = &event {
+                        info!("Display[{idx}] Config update received: {:?}", new);
+                        let reload = reload_reason(config, new);
+                        hooks.config_updated(config, new, reload);
+                        if let Some(reload) = reload {
+                            match reload {
+                                Reload::Resolution => info!(
+                                    "Display[{}] Resolution change {} → {}: hot-reloading decoder",
+                                    idx, config.resolution, new.resolution
+                                ),
+                                Reload::Lossless => info!(
+                                    "Display[{}] Lossless mode {} → {}: hot-reloading decoder",
+                                    idx, config.lossless, new.lossless
+                                ),
+                            }
+                            lifecycle.request_reload(new.clone());
+                            return ExitReason::ConfigUpdated;
+                        }
+                        continue;
+                    }
+                    hooks.event(&event);
+                    match event {
+                        SignalingEvent::SessionStopped { session_id, summary } => {
+                            info!(
+                                "Display[{}] Session {} stopped by sender — {:.1} MB in {:.0} s",
+                                idx, session_id, summary.total_bytes() as f64 / 1e6, summary.duration_secs
+                            );
+                            return ExitReason::SessionStopped;
+                        }
+                        SignalingEvent::ClientDisconnected => {
+                            warn!("Display[{idx}] Sender disconnected unexpectedly");
+                            return ExitReason::ClientDisconnected;
+                        }
+                        SignalingEvent::FrameGap { missing, stats } => {
+                            if warnings.admit("Lost frames", std::time::Instant::now()) {
+                                warn!("Display[{idx}] Lost {} frame(s) — waiting for keyframe ({})", missing, stats);
+                            }
+                        }
+                        SignalingEvent::StreamStalled { silent } => {
+                            warn!("Display[{idx}] No frames for {:.0} s — keyframe requested", silent.as_secs_f64());
+                        }
+                        SignalingEvent::BitrateExceeded { limit_kbps, measured_kbps, dropped } => {
+                            if warnings.admit("Bitrate limit exceeded", std::time::Instant::now()) {
+                                warn!(
+                                    "Display[{}] Sender at {} kbps exceeds the {} kbps limit — dropped {} frame(s)",
+                                    idx, measured_kbps, limit_kbps, dropped
+                                );
+                            }
+                        }
+                        SignalingEvent::ReceiverDisplayChanged { monitor, monitors } => {
+                            info!("Display[{idx}] Receiver monitors changed ({} connected)", monitors.len());
+                            // The sender is told via display_info and may
+                            // renegotiate with a config_update.
+                            if let Some(m) = monitor {
+                                decoder.move_to_monitor(m).await;
+                            }
+                        }
+                        SignalingEvent::Blank { enabled } => {
+                            let what = if enabled { "blanked" } else { "unblanked" };
+                            info!("Display[{idx}] Sender {} the display", what);
+                            decoder.set_blanked(enabled).await;
+                        }
+                        SignalingEvent::SenderPower { power } => {
+                            info!("Display[{idx}] Sender is {}", power);
+                        }
+                        SignalingEvent::DisplayState { paused } => {
+                            info!("Display[{idx}] Sender {} the display", if paused { "paused" } else { "resumed" });
+                            decoder.set_blanked(paused || ch.blank.is_requested()).await;
+                        }
+                        SignalingEvent::File(FileTransferEvent::Offered(offer)) => {
+                            info!("Display[{idx}] Sender offers {} ({} bytes)", offer.name, offer.size);
+                        }
+                        SignalingEvent::File(FileTransferEvent::Received { name, path, .. }) => {
+                            info!("Display[{idx}] Received {} into {}", name, path.display());
+                        }
+                        SignalingEvent::File(FileTransferEvent::Sent { name, .. }) => {
+                            info!("Display[{idx}] Sent {}", name);
+                        }
+                        SignalingEvent::File(FileTransferEvent::Failed { name, reason, .. }) => {
+                            warn!("Display[{idx}] Transfer of {} failed: {}", name, reason);
+                        }
+                        _ => {}
+                    }
+                }
+
+                // End-session hotkey in the video window: stop the sender;
+                // the loop ends on the ClientDisconnected that follows.
+                _ = decoder.end_requested() => {
+                    info!("Display[{idx}] Session ended from the video window");
+                    hooks.ended_from_window();
+                    ch.kick.disconnect();
+                }
+
+                // Blank hotkey: black out locally and pause the sender's capture
+                _ = decoder.blank_toggled() => {
+                    let enabled = !decoder.stats().blanked;
+                    info!("Display[{idx}] Privacy blank {}", if enabled { "on" } else { "off" });
+                    decoder.set_blanked(enabled).await;
+                    ch.blank.request(enabled);
+                }
+
+                // Thumbnail for a sender that asked for previews; skipped
+                // on battery saver
+                _ = preview_tick.tick() => {
+                    if ch.preview.is_wanted() && !local_power.is_some_and(|p| p.saver) {
+                        if let Some(jpeg) = decoder.snapshot_jpeg(PREVIEW_WIDTH).await {
+                            ch.preview.publish(jpeg);
+                        }
+                    }
+                }
+
+                // Re-read the power source and tell the sender when it changes
+                _ = power_tick.tick() => {
+                    let state = tokio::task::spawn_blocking(read_power).await.ok().flatten();
+                    if state != *local_power {
+                        if let Some(p) = state {
+                            info!("Display[{idx}] Receiver is {}", p);
+                        }
+                        *local_power = state;
+                        hooks.power_changed(state);
+                    }
+                    ch.power.publish(state);
+                }
+
+                // Window hidden or shown again: fewer frames while nobody
+                // can see them
+                _ = decoder.visibility_changed() => {
+                    let hidden = decoder.stats().hidden;
+                    hooks.visibility_changed(hidden);
+                    ch.pace.request(hidden.then_some(HIDDEN_FPS));
+                    if !hidden && decoder.hidden_mode() == Some(HiddenMode::KeyframesOnly) {
+                        ch.keyframes.arm();
+                    }
+                }
+
+                _ = stats_tick.tick() => {
+                    let summary = meter.take(idx, decoder.stats().push_errors, std::time::Instant::now());
+                    stats.on_interval_summary(&summary);
+                    for suppressed in warnings.take_suppressed(std::time::Instant::now()) {
+                        warn!("Display[{idx}] {suppressed}");
+                    }
+                }
+
+                _ = action_tick.tick() => {
+                    for action in hooks.tick(&decoder.stats(), ch.pause.is_paused()) {
+                        match action {
+                            SessionAction::ToggleFreeze => decoder.set_frozen(!decoder.stats().frozen).await,
+                            SessionAction::ToggleBlank => {
+                                let enabled = !decoder.stats().blanked;
+                                decoder.set_blanked(enabled).await;
+                                ch.blank.request(enabled);
+                            }
+                            // A paused display shows black until the stream resumes.
+                            SessionAction::TogglePause => {
+                                let paused = !ch.pause.is_paused();
+                                ch.pause.set_paused(paused);
+                                decoder.set_blanked(paused || ch.blank.is_requested()).await;
+                            }
+                            SessionAction::ToggleSystemKeys if !lifecycle.allow_input() => {
+                                info!("Display[{idx}] View-only session — system keys stay here");
+                            }
+                            SessionAction::ToggleSystemKeys => {
+                                decoder.set_system_keys(!decoder.stats().system_keys).await;
+                            }
+                            SessionAction::RestartDecoder => {
+                                info!("Display[{idx}] Restarting decoder");
+                                lifecycle.request_reload(config.clone());
+                                return ExitReason::DecoderRestart;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Forward input events from a decoder window until its thread exits, to
+/// the sender of the window's display only.
+fn forward_input(mut events: InputEvents, input_sender: InputSender) {
+    tokio::spawn(async move {
+        while let Some(event) = events.next().await {
+            let _ = input_sender.try_send(event);
+        }
+    });
+}
+
+/// Keep the screen awake while a session is shown; released on drop.
+async fn inhibit_idle() -> Option<IdleInhibitor> {
+    tokio::task::spawn_blocking(|| IdleInhibitor::acquire("DualLink", "Showing a DualLink stream"))
+        .await
+        .ok()
+        .flatten()
+}
@@ -2,6 +2,12 @@ use async_trait::async_trait;
 use duallink_core::DecodedFrame;
 use thiserror::Error;
 
+#[cfg(feature = "wgpu")]
+mod wgpu_renderer;
+
+#[cfg(feature = "wgpu")]
+pub use wgpu_renderer::WgpuRenderer;
+
 // MARK: - Renderer trait
 
 /// Interface comum para renderizadores fullscreen.
@@ -9,7 +15,8 @@ use thiserror::Error;
 /// Implementações:
 /// - `GStreamerDisplayRenderer` — Sprint 2.1 — combined decode+display via
 ///   GStreamer `autovideosink` (see `duallink-decoder::GStreamerDisplayDecoder`)
-/// - Future: `WgpuRenderer` — direct GPU rendering via wgpu (Sprint 3+)
+/// - `WgpuRenderer` — feature `wgpu`; draws frames from software decoders
+///   (see `duallink-decoder`'s `software` feature)
 #[async_trait]
 pub trait Renderer: Send + Sync {
     /// Inicializa o renderer e abre janela fullscreen.
@@ -0,0 +1,412 @@
+//! `WgpuRenderer` — a fullscreen window drawing [`DecodedFrame`]s with wgpu,
+//! for decoders that hand out frames in system memory (no GStreamer sink).
+//!
+//! The window and its event loop live on a dedicated thread; frames reach it
+//! through an [`EventLoopProxy`], so presenting never blocks the caller.
+//! Frames are uploaded to a texture and drawn letterboxed to the window's
+//! aspect ratio. RGBA and BGRA frames are supported.
+
+use std::sync::mpsc as std_mpsc;
+use std::sync::Arc;
+use std::thread;
+
+use async_trait::async_trait;
+use duallink_core::{DecodedFrame, PixelFormat};
+use tracing::{info, warn};
+use winit::application::ApplicationHandler;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy};
+use winit::window::{Fullscreen, Window, WindowId};
+
+use crate::{Renderer, RendererError};
+
+const SHADER: &str = r#"
+@group(0) @binding(0) var frame: texture_2d<f32>;
+@group(0) @binding(1) var frame_sampler: sampler;
+
+struct VertexOut {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// One triangle covering the viewport.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOut;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    return textureSample(frame, frame_sampler, in.uv);
+}
+"#;
+
+/// Messages from the [`WgpuRenderer`] handle to its window thread.
+enum Command {
+    Frame(DecodedFrame),
+    Close,
+}
+
+// MARK: - WgpuRenderer
+
+/// Fullscreen wgpu window, opened by [`WgpuRenderer::open`] (or
+/// [`Renderer::initialize`]).
+#[derive(Default)]
+pub struct WgpuRenderer {
+    proxy:  Option<EventLoopProxy<Command>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl WgpuRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the window titled `title` and wait until it can draw.
+    pub fn open(&mut self, title: &str) -> Result<(), RendererError> {
+        let (ready_tx, ready_rx) = std_mpsc::sync_channel(1);
+        let title = title.to_owned();
+        let thread = thread::Builder::new()
+            .name("wgpu-window".to_owned())
+            .spawn(move || run_window(title, ready_tx))
+            .map_err(|e| RendererError::InitializationFailed(format!("window thread: {e}")))?;
+        let proxy = ready_rx
+            .recv()
+            .map_err(|_| RendererError::InitializationFailed("window thread exited".into()))??;
+        self.proxy = Some(proxy);
+        self.thread = Some(thread);
+        Ok(())
+    }
+
+    /// Queue `frame` for display. Fails once the window has been closed.
+    pub fn show(&self, frame: DecodedFrame) -> Result<(), RendererError> {
+        let proxy = self.proxy.as_ref().ok_or(RendererError::PresentFailed("window not open".into()))?;
+        proxy
+            .send_event(Command::Frame(frame))
+            .map_err(|_| RendererError::PresentFailed("window closed".into()))
+    }
+
+    /// Close the window and wait for its thread.
+    pub fn close(&mut self) {
+        if let Some(proxy) = self.proxy.take() {
+            let _ = proxy.send_event(Command::Close);
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for WgpuRenderer {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[async_trait]
+impl Renderer for WgpuRenderer {
+    async fn initialize(&mut self, _width: u32, _height: u32) -> Result<(), RendererError> {
+        self.open("DualLink")
+    }
+
+    async fn present(&mut self, frame: DecodedFrame) -> Result<(), RendererError> {
+        self.show(frame)
+    }
+
+    // The texture follows the frame size; the window follows the display.
+    async fn resize(&mut self, _width: u32, _height: u32) -> Result<(), RendererError> {
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) {
+        self.close();
+    }
+}
+
+// MARK: - Window thread
+
+type Ready = std_mpsc::SyncSender<Result<EventLoopProxy<Command>, RendererError>>;
+
+fn run_window(title: String, ready: Ready) {
+    let event_loop = match build_event_loop() {
+        Ok(event_loop) => event_loop,
+        Err(e) => {
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+    let proxy = event_loop.create_proxy();
+    let mut app = App { title, ready: Some((ready, proxy)), gpu: None };
+    if let Err(e) = event_loop.run_app(&mut app) {
+        warn!("wgpu window: {e}");
+    }
+}
+
+fn build_event_loop() -> Result<EventLoop<Command>, RendererError> {
+    let mut builder = EventLoop::with_user_event();
+    // The window runs off the main thread, next to the decode threads.
+    #[cfg(target_os = "linux")]
+    {
+        winit::platform::x11::EventLoopBuilderExtX11::with_any_thread(&mut builder, true);
+        winit::platform::wayland::EventLoopBuilderExtWayland::with_any_thread(&mut builder, true);
+    }
+    #[cfg(target_os = "windows")]
+    winit::platform::windows::EventLoopBuilderExtWindows::with_any_thread(&mut builder, true);
+    builder.build().map_err(|e| match e {
+        winit::error::EventLoopError::NotSupported(_) => RendererError::DisplaySystemUnavailable,
+        e => RendererError::InitializationFailed(e.to_string()),
+    })
+}
+
+struct App {
+    title: String,
+    /// Where to report that the window is up (or why not), until it is.
+    ready: Option<(Ready, EventLoopProxy<Command>)>,
+    gpu:   Option<Gpu>,
+}
+
+impl ApplicationHandler<Command> for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.gpu.is_some() {
+            return;
+        }
+        let attributes = Window::default_attributes()
+            .with_title(self.title.as_str())
+            .with_fullscreen(Some(Fullscreen::Borderless(None)));
+        let gpu = event_loop
+            .create_window(attributes)
+            .map_err(|e| RendererError::InitializationFailed(format!("window: {e}")))
+            .and_then(|window| Gpu::new(Arc::new(window)));
+        let Some((ready, proxy)) = self.ready.take() else { return };
+        match gpu {
+            Ok(gpu) => {
+                self.gpu = Some(gpu);
+                let _ = ready.send(Ok(proxy));
+            }
+            Err(e) => {
+                let _ = ready.send(Err(e));
+                event_loop.exit();
+            }
+        }
+    }
+
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, command: Command) {
+        match command {
+            Command::Frame(frame) => {
+                if let Some(gpu) = &mut self.gpu {
+                    gpu.upload(&frame);
+                    gpu.window.request_redraw();
+                }
+            }
+            Command::Close => event_loop.exit(),
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        let Some(gpu) = &mut self.gpu else { return };
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::Resized(size) => gpu.resize(size.width, size.height),
+            WindowEvent::RedrawRequested => gpu.render(),
+            _ => {}
+        }
+    }
+}
+
+// MARK: - Gpu
+
+/// The window's surface and what draws the current frame into it.
+struct Gpu {
+    window:   Arc<Window>,
+    surface:  wgpu::Surface<'static>,
+    device:   wgpu::Device,
+    queue:    wgpu::Queue,
+    config:   wgpu::SurfaceConfiguration,
+    pipeline: wgpu::RenderPipeline,
+    sampler:  wgpu::Sampler,
+    /// Texture holding the last frame, and its bind group.
+    frame:    Option<(wgpu::Texture, wgpu::BindGroup)>,
+}
+
+impl Gpu {
+    fn new(window: Arc<Window>) -> Result<Self, RendererError> {
+        let init = |e: String| RendererError::InitializationFailed(e);
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let surface = instance.create_surface(Arc::clone(&window)).map_err(|e| init(e.to_string()))?;
+        // No async runtime on this thread; block on wgpu's setup futures.
+        let runtime = tokio::runtime::Builder::new_current_thread().build().map_err(|e| init(e.to_string()))?;
+        let adapter = runtime
+            .block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                compatible_surface: Some(&surface),
+                ..Default::default()
+            }))
+            .ok_or_else(|| init("no GPU adapter for the window".into()))?;
+        let (device, queue) = runtime
+            .block_on(adapter.request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("duallink"),
+                    required_limits: wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits()),
+                    ..Default::default()
+                },
+                None,
+            ))
+            .map_err(|e| init(e.to_string()))?;
+
+        let size = window.inner_size();
+        let mut config = surface
+            .get_default_config(&adapter, size.width.max(1), size.height.max(1))
+            .ok_or_else(|| init("surface not supported by the adapter".into()))?;
+        // Frames are already gamma-encoded; pass them through unconverted.
+        config.format = config.format.remove_srgb_suffix();
+        surface.configure(&device, &config);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label:  Some("frame"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label:         Some("frame"),
+            layout:        None,
+            vertex:        wgpu::VertexState {
+                module:              &shader,
+                entry_point:         "vs_main",
+                compilation_options: Default::default(),
+                buffers:             &[],
+            },
+            fragment:      Some(wgpu::FragmentState {
+                module:              &shader,
+                entry_point:         "fs_main",
+                compilation_options: Default::default(),
+                targets:             &[Some(config.format.into())],
+            }),
+            primitive:     wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample:   wgpu::MultisampleState::default(),
+            multiview:     None,
+            cache:         None,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        info!("WgpuRenderer ready {}x{} ({:?})", config.width, config.height, adapter.get_info().backend);
+        Ok(Self { window, surface, device, queue, config, pipeline, sampler, frame: None })
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.config.width = width;
+        self.config.height = height;
+        self.surface.configure(&self.device, &self.config);
+        self.window.request_redraw();
+    }
+
+    /// Copy `frame` into the frame texture, recreating it on a size or
+    /// format change.
+    fn upload(&mut self, frame: &DecodedFrame) {
+        let format = match frame.format {
+            PixelFormat::Bgra => wgpu::TextureFormat::Bgra8Unorm,
+            PixelFormat::Rgba => wgpu::TextureFormat::Rgba8Unorm,
+            PixelFormat::Nv12 => {
+                warn!("WgpuRenderer: NV12 frames are not supported");
+                return;
+            }
+        };
+        let size = wgpu::Extent3d { width: frame.width, height: frame.height, depth_or_array_layers: 1 };
+        if frame.data.len() < (frame.width * frame.height * 4) as usize {
+            warn!("WgpuRenderer: short frame ({} bytes for {}x{})", frame.data.len(), frame.width, frame.height);
+            return;
+        }
+        let stale = self
+            .frame
+            .as_ref()
+            .map_or(true, |(texture, _)| texture.size() != size || texture.format() != format);
+        if stale {
+            let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("frame"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label:   Some("frame"),
+                layout:  &self.pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&view) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&self.sampler) },
+                ],
+            });
+            self.frame = Some((texture, bind_group));
+        }
+        let Some((texture, _)) = &self.frame else { return };
+        self.queue.write_texture(
+            texture.as_image_copy(),
+            &frame.data,
+            wgpu::ImageDataLayout {
+                offset:         0,
+                bytes_per_row:  Some(frame.width * 4),
+                rows_per_image: Some(frame.height),
+            },
+            size,
+        );
+    }
+
+    fn render(&mut self) {
+        let output = match self.surface.get_current_texture() {
+            Ok(output) => output,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(&self.device, &self.config);
+                return;
+            }
+            Err(e) => {
+                warn!("WgpuRenderer: {e}");
+                return;
+            }
+        };
+        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("frame"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view:           &view,
+                    resolve_target: None,
+                    ops:            wgpu::Operations {
+                        load:  wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                ..Default::default()
+            });
+            if let Some((texture, bind_group)) = &self.frame {
+                let (x, y, w, h) = letterbox(texture.width(), texture.height(), self.config.width, self.config.height);
+                pass.set_viewport(x, y, w, h, 0.0, 1.0);
+                pass.set_pipeline(&self.pipeline);
+                pass.set_bind_group(0, bind_group, &[]);
+                pass.draw(0..3, 0..1);
+            }
+        }
+        self.queue.submit([encoder.finish()]);
+        output.present();
+    }
+}
+
+/// Viewport `(x, y, width, height)` showing a `frame_w`×`frame_h` picture as
+/// large as fits a `win_w`×`win_h` window, centred, aspect ratio kept.
+fn letterbox(frame_w: u32, frame_h: u32, win_w: u32, win_h: u32) -> (f32, f32, f32, f32) {
+    let scale = (win_w as f32 / frame_w as f32).min(win_h as f32 / frame_h as f32);
+    let (w, h) = (frame_w as f32 * scale, frame_h as f32 * scale);
+    ((win_w as f32 - w) / 2.0, (win_h as f32 - h) / 2.0, w, h)
+}
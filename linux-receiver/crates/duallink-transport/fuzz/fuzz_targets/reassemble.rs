@@ -0,0 +1,25 @@
+//! Feed arbitrary datagrams through the DLNK parser and reassembler.
+//!
+//! Input is a sequence of `[len: u8][datagram: len bytes]` records.
+//!
+//! ```sh
+//! cd crates/duallink-transport && cargo +nightly fuzz run reassemble
+//! ```
+
+#![no_main]
+
+use duallink_transport::protocol::{parse_packet, FrameReassembler, MAX_FRAGMENTS};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut reassembler = FrameReassembler::default();
+    let mut rest = data;
+    while let Some((&len, tail)) = rest.split_first() {
+        let (datagram, next) = tail.split_at((len as usize).min(tail.len()));
+        rest = next;
+        let Ok(packet) = parse_packet(datagram) else { continue };
+        assert!(packet.frag_count <= MAX_FRAGMENTS && packet.frag_index < packet.frag_count);
+        assert_eq!(parse_packet(&packet.encode()).as_ref(), Ok(&packet));
+        let _ = reassembler.push(packet);
+    }
+});
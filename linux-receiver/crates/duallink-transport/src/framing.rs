@@ -0,0 +1,211 @@
+//! Signaling framing and intake limits.
+//!
+//! Signaling messages are a `u32` BE length followed by that many bytes of
+//! JSON. Anyone on the network can open the TLS port, so the receiver
+//! treats every connection as untrusted until it has paired:
+//!
+//! - A length above [`MAX_SIGNALING_MESSAGE`] (1 MiB, the bound senders
+//!   apply to the receiver's messages) is refused before anything is
+//!   allocated, answered with `too_large` and the connection closed — the
+//!   stream cannot be resynchronised after it.
+//! - JSON that does not parse is answered with `malformed`, a `type` the
+//!   receiver does not know with `unknown_type`; the connection goes on.
+//! - Each connection may send [`MESSAGE_RATE`] messages per second (bursts
+//!   up to [`MESSAGE_BURST`]). Messages above the rate are dropped; the
+//!   first of each run is answered with `rate_limited`.
+//!
+//! The answers are `error` messages carrying a [`SignalingErrorCode`] in
+//! `errorCode` and a human-readable `reason`.
+
+use std::io;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{MessageType, SignalingMessage};
+
+/// Largest signaling message accepted, in bytes.
+pub const MAX_SIGNALING_MESSAGE: usize = 1_048_576;
+
+/// Sustained signaling messages per second accepted from one connection.
+pub const MESSAGE_RATE: f64 = 50.0;
+
+/// Messages one connection may send at once before the rate applies.
+pub const MESSAGE_BURST: f64 = 100.0;
+
+/// Why the receiver refused a signaling message, sent back in an `error`
+/// message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalingErrorCode {
+    /// Longer than [`MAX_SIGNALING_MESSAGE`]; the connection is closed.
+    TooLarge,
+    /// Not a signaling message.
+    Malformed,
+    /// A `type` this receiver does not know.
+    UnknownType,
+    /// Above the per-connection message rate; dropped.
+    RateLimited,
+}
+
+// ── Framing ───────────────────────────────────────────────────────────────────
+
+/// A frame that could not be read.
+#[derive(Debug)]
+pub(crate) enum FrameError {
+    /// The announced length is above [`MAX_SIGNALING_MESSAGE`].
+    TooLarge(usize),
+    /// The connection closed or failed mid-frame.
+    Io(io::Error),
+}
+
+/// Read one length-prefixed frame into `buf`, refusing oversized lengths
+/// before allocating.
+pub(crate) async fn read_frame(reader: &mut (impl AsyncRead + Unpin), buf: &mut Vec<u8>) -> Result<(), FrameError> {
+    let mut len_bytes = [0u8; 4];
+    reader.read_exact(&mut len_bytes).await.map_err(FrameError::Io)?;
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    if len > MAX_SIGNALING_MESSAGE {
+        return Err(FrameError::TooLarge(len));
+    }
+    buf.resize(len, 0);
+    reader.read_exact(buf).await.map_err(FrameError::Io)?;
+    Ok(())
+}
+
+/// Parse a frame's JSON, telling an unknown `type` from a malformed message.
+pub(crate) fn parse_message(body: &[u8]) -> Result<SignalingMessage, (SignalingErrorCode, String)> {
+    #[derive(Deserialize)]
+    struct Envelope {
+        #[serde(rename = "type")]
+        msg_type: String,
+    }
+
+    serde_json::from_slice(body).map_err(|e| match serde_json::from_slice::<Envelope>(body) {
+        Ok(Envelope { msg_type })
+            if serde_json::from_value::<MessageType>(serde_json::Value::String(msg_type.clone())).is_err() =>
+        {
+            (SignalingErrorCode::UnknownType, format!("Unknown message type '{msg_type}'"))
+        }
+        _ => (SignalingErrorCode::Malformed, format!("Malformed message: {e}")),
+    })
+}
+
+// ── MessageRate ───────────────────────────────────────────────────────────────
+
+/// Token bucket over one connection's incoming messages.
+pub(crate) struct MessageRate {
+    tokens:  f64,
+    last:    Instant,
+    /// Messages dropped since the last one let through.
+    dropped: u64,
+}
+
+impl MessageRate {
+    pub(crate) fn new(now: Instant) -> Self {
+        Self { tokens: MESSAGE_BURST, last: now, dropped: 0 }
+    }
+
+    /// `true` if a message arriving at `now` may be handled.
+    pub(crate) fn admit(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * MESSAGE_RATE).min(MESSAGE_BURST);
+        if self.tokens < 1.0 {
+            self.dropped += 1;
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+
+    /// Messages dropped in the current run of refusals.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// End the current run; returns how many messages it dropped.
+    pub(crate) fn take_dropped(&mut self) -> u64 {
+        std::mem::take(&mut self.dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn frame(body: &[u8]) -> Vec<u8> {
+        let mut bytes = (body.len() as u32).to_be_bytes().to_vec();
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    #[tokio::test]
+    async fn garbage_is_refused_without_allocating_or_panicking() {
+        let mut buf = Vec::new();
+
+        // A 4 GB length prefix is refused before the body is read.
+        let mut huge: &[u8] = &[0xff, 0xff, 0xff, 0xff, b'{'];
+        assert!(matches!(read_frame(&mut huge, &mut buf).await, Err(FrameError::TooLarge(0xffff_ffff))));
+        assert!(buf.capacity() < MAX_SIGNALING_MESSAGE);
+
+        // Truncated frames end the connection.
+        let mut short: &[u8] = &[0, 0, 0, 10, b'{'];
+        assert!(matches!(read_frame(&mut short, &mut buf).await, Err(FrameError::Io(_))));
+        let mut cut: &[u8] = &[0, 0];
+        assert!(matches!(read_frame(&mut cut, &mut buf).await, Err(FrameError::Io(_))));
+
+        // Well-framed garbage parses to a typed error.
+        let mut stream: Vec<u8> = Vec::new();
+        stream.extend(frame(b"\x00\x9f\xfe garbage"));
+        stream.extend(frame(br#"{"type":"teleport","sessionID":"x"}"#));
+        stream.extend(frame(br#"{"type":"hello","config":"not a config"}"#));
+        stream.extend(frame(br#"{"type":"keepalive","timestampMs":5}"#));
+        let mut reader = stream.as_slice();
+        let mut results = Vec::new();
+        while read_frame(&mut reader, &mut buf).await.is_ok() {
+            results.push(parse_message(&buf).map(|m| m.msg_type).map_err(|(code, _)| code));
+        }
+        assert_eq!(
+            results,
+            [
+                Err(SignalingErrorCode::Malformed),
+                Err(SignalingErrorCode::UnknownType),
+                Err(SignalingErrorCode::Malformed),
+                Ok(MessageType::Keepalive),
+            ]
+        );
+
+        // Pseudo-random byte soup never panics the framing layer.
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let soup: Vec<u8> = (0..64 * 1024)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                // Mostly short lengths so some frames get through.
+                (seed % 7) as u8
+            })
+            .collect();
+        let mut reader = soup.as_slice();
+        while read_frame(&mut reader, &mut buf).await.is_ok() {
+            assert!(parse_message(&buf).is_err());
+        }
+    }
+
+    #[test]
+    fn message_rate_allows_bursts_then_drops() {
+        let start = Instant::now();
+        let mut rate = MessageRate::new(start);
+        assert!((0..MESSAGE_BURST as usize).all(|_| rate.admit(start)));
+        assert!(!rate.admit(start));
+        assert!(!rate.admit(start));
+        assert_eq!(rate.dropped(), 2);
+        assert!(rate.admit(start + Duration::from_millis(100)));
+        assert_eq!(rate.take_dropped(), 2);
+        assert_eq!(rate.dropped(), 0);
+    }
+}
@@ -0,0 +1,360 @@
+//! State and control of a running receiver, for the apps that embed it.
+//!
+//! [`DualLinkReceiver::handle`] returns a [`ReceiverHandle`]: a cloneable,
+//! `Send` view that tasks of the embedding app can keep without sharing
+//! the receiver itself. It offers
+//!
+//! - [`ReceiverState`] snapshots — pairing PIN, input policy and, for each
+//!   bound display, its ports, own PIN if PINs are per display, and
+//!   [`DisplayPhase`] (waiting, or the peer owning it and its negotiated
+//!   config),
+//! - a `watch` channel that changes whenever that state does
+//!   ([`ReceiverHandle::subscribe`]),
+//! - per-display counters, polled with [`ReceiverHandle::stats`] since
+//!   they change with every frame,
+//! - and control: [`regenerate_pin`](ReceiverHandle::regenerate_pin),
+//!   [`disconnect_client`](ReceiverHandle::disconnect_client) and the input
+//!   policy.
+//!
+//! The state is kept by the transport tasks themselves, so embedders need
+//! not rebuild it from [`SignalingEvent`](crate::SignalingEvent)s.
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use duallink_core::{DisplayPorts, PortMap, SequenceStats, StreamConfig};
+use tokio::sync::watch;
+use tracing::info;
+
+use crate::{generate_pairing_pin, DualLinkReceiver, ReassemblyStats, ReceiverRuntime, VIDEO_PORT};
+
+// ── State ─────────────────────────────────────────────────────────────────────
+
+/// Snapshot of a running receiver.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiverState {
+    /// PIN new senders must present (see
+    /// [`ReceiverHandle::regenerate_pin`]), unless the display they connect
+    /// to has its own.
+    pub pairing_pin:     String,
+    /// Hex SHA-256 fingerprint of the receiver's TLS certificate.
+    pub tls_fingerprint: String,
+    /// Input policy for new sessions.
+    pub allow_input:     bool,
+    /// Bound displays, by index.
+    pub displays:        Vec<ReceiverDisplay>,
+    /// Displays bound get their own PIN (see
+    /// [`configured_separate_pins`](crate::configured_separate_pins)).
+    pub separate_pins:   bool,
+}
+
+impl ReceiverState {
+    pub(crate) fn new(pairing_pin: String, tls_fingerprint: String, allow_input: bool) -> Self {
+        Self { pairing_pin, tls_fingerprint, allow_input, displays: Vec::new(), separate_pins: false }
+    }
+
+    /// Give displays bound from now on their own PIN.
+    pub(crate) fn with_separate_pins(self, separate_pins: bool) -> Self {
+        Self { separate_pins, ..self }
+    }
+
+    /// The PIN a sender connecting to display `display_index` must present.
+    pub fn pin_for(&self, display_index: u8) -> &str {
+        self.display(display_index).and_then(|d| d.pairing_pin.as_deref()).unwrap_or(&self.pairing_pin)
+    }
+
+    /// Display `display_index`, if bound.
+    pub fn display(&self, display_index: u8) -> Option<&ReceiverDisplay> {
+        self.displays.iter().find(|d| d.display_index == display_index)
+    }
+
+    /// Follow the bound displays: drop the ones gone from `ports`, add new
+    /// ones as listening. Returns `true` if anything changed.
+    pub(crate) fn sync_displays(&mut self, ports: &PortMap) -> bool {
+        let before = self.displays.clone();
+        self.displays.retain(|d| ports.get(d.display_index).is_some());
+        for p in ports.iter() {
+            match self.displays.iter_mut().find(|d| d.display_index == p.display_index) {
+                Some(d) => d.ports = *p,
+                None => self.displays.push(ReceiverDisplay {
+                    display_index: p.display_index,
+                    ports:         *p,
+                    pairing_pin:   self.separate_pins.then(|| generate_display_pin(&self.pairing_pin)),
+                    phase:         DisplayPhase::Listening,
+                }),
+            }
+        }
+        self.displays.sort_by_key(|d| d.display_index);
+        self.displays != before
+    }
+}
+
+/// One bound display in a [`ReceiverState`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiverDisplay {
+    pub display_index: u8,
+    pub ports:         DisplayPorts,
+    /// The display's own PIN; `None` = the receiver's
+    /// [`pairing_pin`](ReceiverState::pairing_pin).
+    pub pairing_pin:   Option<String>,
+    pub phase:         DisplayPhase,
+}
+
+/// Where a display's session stands.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisplayPhase {
+    /// Waiting for a sender.
+    Listening,
+    /// A paired sender is streaming; the display is its until it leaves.
+    Connected(PeerInfo),
+}
+
+impl DisplayPhase {
+    /// The connected sender, if any.
+    pub fn peer(&self) -> Option<&PeerInfo> {
+        match self {
+            Self::Listening => None,
+            Self::Connected(peer) => Some(peer),
+        }
+    }
+}
+
+/// The sender of a running session.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerInfo {
+    pub session_id:  String,
+    pub device_name: String,
+    pub address:     SocketAddr,
+    /// Config as negotiated, with later `config_update`s applied.
+    pub config:      StreamConfig,
+    /// `false` for a view-only session.
+    pub allow_input: bool,
+}
+
+/// Counters of one display since it was bound.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DisplayStats {
+    /// Lost / late / duplicate frames.
+    pub frames:         SequenceStats,
+    /// Fragment-level evictions and rejects.
+    pub reassembly:     ReassemblyStats,
+    /// Queueing on the way of the latest frame (see
+    /// [`DualLinkReceiver::queueing_delay`]).
+    pub queueing_delay: Duration,
+}
+
+/// Set display `display_index`'s phase, if it is still bound.
+pub(crate) fn set_phase(state: &watch::Sender<ReceiverState>, display_index: u8, phase: DisplayPhase) {
+    state.send_if_modified(|s| match s.displays.iter_mut().find(|d| d.display_index == display_index) {
+        Some(d) if d.phase != phase => {
+            d.phase = phase;
+            true
+        }
+        _ => false,
+    });
+}
+
+/// Why `device_name` at `address` may not start a session on display
+/// `display_index`: another sender owns it. The same device coming back
+/// from the same host takes over, so a sender whose connection dropped
+/// unnoticed can reconnect.
+pub(crate) fn owner_conflict(
+    state: &watch::Sender<ReceiverState>,
+    display_index: u8,
+    device_name: &str,
+    address: SocketAddr,
+) -> Option<String> {
+    let state = state.borrow();
+    let owner = state.display(display_index)?.phase.peer()?;
+    let same_sender = owner.device_name == device_name && owner.address.ip() == address.ip();
+    (!same_sender).then(|| format!("Display {display_index} is in use by '{}'", owner.device_name))
+}
+
+/// A PIN for a display of its own, unlike `shared`.
+fn generate_display_pin(shared: &str) -> String {
+    loop {
+        let pin = generate_pairing_pin();
+        if pin != shared {
+            return pin;
+        }
+    }
+}
+
+/// Put display `display_index` back to listening if `session_id` is still
+/// the session shown — a newer connection may have replaced it.
+pub(crate) fn end_session(state: &watch::Sender<ReceiverState>, display_index: u8, session_id: &str) {
+    state.send_if_modified(|s| match s.displays.iter_mut().find(|d| d.display_index == display_index) {
+        Some(d) if d.phase.peer().is_some_and(|p| p.session_id == session_id) => {
+            d.phase = DisplayPhase::Listening;
+            true
+        }
+        _ => false,
+    });
+}
+
+// ── ReceiverHandle ────────────────────────────────────────────────────────────
+
+/// Cloneable view onto a running [`DualLinkReceiver`]; see the
+/// [module docs](self).
+#[derive(Clone)]
+pub struct ReceiverHandle {
+    pub(crate) frames_received: Arc<AtomicU64>,
+    /// `None` for the single-display [`DualLinkReceiver::start`] receiver.
+    pub(crate) runtime:         Option<Arc<ReceiverRuntime>>,
+    pub(crate) allow_input:     Arc<AtomicBool>,
+    pub(crate) state:           Arc<watch::Sender<ReceiverState>>,
+}
+
+impl ReceiverHandle {
+    /// The current state.
+    pub fn state(&self) -> ReceiverState {
+        self.state.borrow().clone()
+    }
+
+    /// A receiver of the state that is marked changed whenever a display
+    /// is bound or released, a sender connects, reconfigures or leaves,
+    /// the PIN is regenerated or the input policy changes.
+    pub fn subscribe(&self) -> watch::Receiver<ReceiverState> {
+        self.state.subscribe()
+    }
+
+    /// Video frames received over all displays.
+    pub fn frames_received(&self) -> u64 {
+        self.frames_received.load(Ordering::Relaxed)
+    }
+
+    /// Counters of display `display_index`, or `None` if it is not running.
+    pub fn stats(&self, display_index: u8) -> Option<DisplayStats> {
+        let runtime = self.runtime.as_ref()?;
+        let displays = runtime.displays.lock().unwrap();
+        let link = &displays.get(&display_index)?.link;
+        let frames = *link.stats.lock().unwrap();
+        let reassembly = *link.reassembly.lock().unwrap();
+        Some(DisplayStats {
+            frames,
+            reassembly,
+            queueing_delay: Duration::from_micros(link.queueing.load(Ordering::Relaxed)),
+        })
+    }
+
+    /// Replace the pairing PIN, and the displays' own PINs, and return the
+    /// new one. Senders pairing from now on need it; running sessions go on.
+    pub fn regenerate_pin(&self) -> String {
+        let pin = generate_pairing_pin();
+        info!("Pairing PIN regenerated: {}", pin);
+        self.state.send_modify(|s| {
+            s.pairing_pin = pin.clone();
+            for d in &mut s.displays {
+                if let Some(own) = &mut d.pairing_pin {
+                    *own = generate_display_pin(&pin);
+                    info!("Display[{}] pairing PIN regenerated: {}", d.display_index, own);
+                }
+            }
+        });
+        pin
+    }
+
+    /// End the session on display `display_index`, keeping its ports bound.
+    ///
+    /// The connected sender is sent `stop` and the display's event channel
+    /// reports [`SignalingEvent::ClientDisconnected`](crate::SignalingEvent::ClientDisconnected);
+    /// the sender may reconnect afterwards. Returns `false` if the display
+    /// is not running.
+    pub fn disconnect_client(&self, display_index: u8) -> bool {
+        let Some(runtime) = &self.runtime else { return false };
+        let displays = runtime.displays.lock().unwrap();
+        let Some(running) = displays.get(&display_index) else { return false };
+        info!("Display[{display_index}] disconnect requested");
+        running.kick.notify_waiters();
+        true
+    }
+
+    /// Let new sessions send input back to their sender, or make them
+    /// view-only (see [`DualLinkReceiver::set_allow_input`]).
+    pub fn set_allow_input(&self, allow: bool) {
+        self.allow_input.store(allow, Ordering::Relaxed);
+        self.state.send_if_modified(|s| std::mem::replace(&mut s.allow_input, allow) != allow);
+    }
+
+    /// Input policy for new sessions.
+    pub fn allow_input(&self) -> bool {
+        self.allow_input.load(Ordering::Relaxed)
+    }
+
+    /// Ports of the displays currently bound.
+    pub fn port_map(&self) -> PortMap {
+        match &self.runtime {
+            Some(r) => r.ports_tx.borrow().clone(),
+            None => PortMap::contiguous(VIDEO_PORT, 1),
+        }
+    }
+
+    /// Indices of the displays currently bound.
+    pub fn display_indices(&self) -> Vec<u8> {
+        self.runtime.as_ref().map(|r| r.indices()).unwrap_or_else(|| vec![0])
+    }
+}
+
+impl DualLinkReceiver {
+    /// A [`ReceiverHandle`] onto this receiver.
+    pub fn handle(&self) -> ReceiverHandle {
+        self.handle.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn displays_follow_the_port_map_and_sessions() {
+        let state = watch::channel(ReceiverState::new("123456".into(), "ab".into(), true)).0;
+        assert!(state.send_if_modified(|s| s.sync_displays(&PortMap::contiguous(7878, 2))));
+        assert!(!state.send_if_modified(|s| s.sync_displays(&PortMap::contiguous(7878, 2))));
+        assert_eq!(state.borrow().displays.len(), 2);
+
+        let peer = PeerInfo {
+            session_id:  "s1".into(),
+            device_name: "Mac".into(),
+            address:     "127.0.0.1:5000".parse().unwrap(),
+            config:      StreamConfig::default(),
+            allow_input: true,
+        };
+        set_phase(&state, 1, DisplayPhase::Connected(peer));
+        end_session(&state, 1, "older");
+        assert!(state.borrow().display(1).unwrap().phase.peer().is_some());
+        end_session(&state, 1, "s1");
+        assert_eq!(state.borrow().display(1).unwrap().phase, DisplayPhase::Listening);
+
+        state.send_if_modified(|s| s.sync_displays(&PortMap::contiguous(7878, 1)));
+        assert!(state.borrow().display(1).is_none());
+    }
+
+    #[test]
+    fn displays_have_owners_and_optionally_pins() {
+        let state = ReceiverState::new("123456".into(), "ab".into(), true).with_separate_pins(true);
+        let state = watch::channel(state).0;
+        state.send_if_modified(|s| s.sync_displays(&PortMap::contiguous(7878, 2)));
+        let pins: Vec<String> = (0..2).map(|n| state.borrow().pin_for(n).to_owned()).collect();
+        assert!(pins.iter().all(|pin| pin != "123456"));
+        assert_eq!(state.borrow().pin_for(5), "123456");
+
+        let mac: SocketAddr = "192.168.1.2:5000".parse().unwrap();
+        set_phase(&state, 0, DisplayPhase::Connected(PeerInfo {
+            session_id:  "s1".into(),
+            device_name: "Mac".into(),
+            address:     mac,
+            config:      StreamConfig::default(),
+            allow_input: true,
+        }));
+        assert_eq!(owner_conflict(&state, 1, "PC", "192.168.1.3:6000".parse().unwrap()), None);
+        assert_eq!(
+            owner_conflict(&state, 0, "PC", "192.168.1.3:6000".parse().unwrap()).as_deref(),
+            Some("Display 0 is in use by 'Mac'")
+        );
+        // The same Mac reconnecting on a new port takes over.
+        assert_eq!(owner_conflict(&state, 0, "Mac", "192.168.1.2:5001".parse().unwrap()), None);
+    }
+}
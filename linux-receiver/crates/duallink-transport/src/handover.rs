@@ -0,0 +1,298 @@
+//! Handing a running receiver's ports to another receiver process.
+//!
+//! Only one process can hold the DualLink ports, and the background service
+//! (`duallink-receiver`) usually does when the GUI starts. Instead of killing
+//! it, the GUI asks it to hand over:
+//!
+//! 1. The service listens on a Unix control socket ([`control_socket_path`])
+//!    with [`HandoverServer`].
+//! 2. The GUI connects with [`request_handover`] and sends `handover\n`.
+//! 3. The service stops its displays ([`DualLinkReceiver::release_sockets`])
+//!    and replies with one message: a JSON array of the display indices in
+//!    the data, and each display's UDP socket and TCP listener, in that
+//!    order, as `SCM_RIGHTS` file descriptors.
+//! 4. The GUI adopts them with [`DualLinkReceiver::start_with_sockets`]; the
+//!    service exits once its display loops have wound down.
+//!
+//! The ports never close in between, so senders simply reconnect to the new
+//! owner. The socket lives in `$XDG_RUNTIME_DIR` (or is made owner-only in
+//! the temp dir), so only the same user can take the receiver over.
+
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixStream as StdUnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::UnixListener;
+use tracing::{info, warn};
+
+use crate::{DisplaySockets, DualLinkReceiver};
+
+/// What a client sends to ask for the receiver's sockets.
+const HANDOVER_REQUEST: &[u8] = b"handover\n";
+/// Upper bound on descriptors in a reply: two per display.
+const MAX_FDS: usize = 2 * crate::MAX_DISPLAYS;
+/// How long either side waits on the other before giving up.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `$XDG_RUNTIME_DIR/duallink-receiver.sock`, or a per-user socket in the
+/// temp dir when there is no runtime dir.
+pub fn control_socket_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join("duallink-receiver.sock"),
+        // SAFETY: getuid has no preconditions and cannot fail.
+        None => std::env::temp_dir().join(format!("duallink-receiver-{}.sock", unsafe { libc::getuid() })),
+    }
+}
+
+// ── Server ────────────────────────────────────────────────────────────────────
+
+/// Control socket a running receiver answers handover requests on.
+///
+/// The socket file is removed when the server is dropped.
+pub struct HandoverServer {
+    listener: UnixListener,
+    path:     PathBuf,
+}
+
+impl HandoverServer {
+    /// Listen on [`control_socket_path`].
+    pub fn bind() -> anyhow::Result<Self> {
+        Self::bind_at(control_socket_path())
+    }
+
+    /// Listen on `path`, replacing a stale socket left by a receiver that
+    /// died. Fails if another receiver is still listening there.
+    pub fn bind_at(path: PathBuf) -> anyhow::Result<Self> {
+        if path.exists() {
+            anyhow::ensure!(
+                StdUnixStream::connect(&path).is_err(),
+                "another receiver is already listening on {}",
+                path.display()
+            );
+            std::fs::remove_file(&path)?;
+        }
+        let listener = UnixListener::bind(&path)?;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+        info!("Handover control socket on {}", path.display());
+        Ok(Self { listener, path })
+    }
+
+    /// Answer requests until one succeeds in taking `receiver`'s sockets.
+    ///
+    /// Malformed requests are logged and ignored. Once the sockets have been
+    /// released this returns — with an error if they could not be delivered,
+    /// in which case they are closed and the receiver should exit so that a
+    /// fresh one can bind the ports.
+    pub async fn serve(self, receiver: Arc<DualLinkReceiver>) -> anyhow::Result<()> {
+        loop {
+            let (stream, _) = self.listener.accept().await?;
+            let mut stream = stream.into_std()?;
+            stream.set_nonblocking(false)?;
+            stream.set_read_timeout(Some(IO_TIMEOUT))?;
+            stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+            let request = tokio::task::spawn_blocking(move || {
+                let mut buf = [0u8; HANDOVER_REQUEST.len()];
+                stream.read_exact(&mut buf).map(|()| (stream, buf))
+            })
+            .await?;
+            let stream = match request {
+                Ok((stream, buf)) if buf == HANDOVER_REQUEST => stream,
+                Ok(_) => {
+                    warn!("Ignoring unknown request on the handover socket");
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Handover request failed: {e}");
+                    continue;
+                }
+            };
+
+            let sockets = receiver.release_sockets()?;
+            info!("Handing {} display(s) over to another receiver", sockets.len());
+            tokio::task::spawn_blocking(move || send_sockets(&stream, &sockets)).await??;
+            return Ok(());
+        }
+    }
+}
+
+impl Drop for HandoverServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// ── Client ────────────────────────────────────────────────────────────────────
+
+/// Ask the receiver listening on [`control_socket_path`] for its sockets.
+///
+/// `Ok(None)` if no receiver is listening there.
+pub async fn request_handover() -> anyhow::Result<Option<Vec<DisplaySockets>>> {
+    request_handover_from(control_socket_path()).await
+}
+
+/// Like [`request_handover`], for the control socket at `path`.
+pub async fn request_handover_from(path: PathBuf) -> anyhow::Result<Option<Vec<DisplaySockets>>> {
+    tokio::task::spawn_blocking(move || {
+        let Some(mut stream) = connect(&path)? else { return Ok(None) };
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        stream.write_all(HANDOVER_REQUEST)?;
+        recv_sockets(&stream).map(Some)
+    })
+    .await?
+}
+
+/// Connect to `path`; `None` if nothing is listening there.
+fn connect(path: &Path) -> io::Result<Option<StdUnixStream>> {
+    match StdUnixStream::connect(path) {
+        Ok(stream) => Ok(Some(stream)),
+        Err(e) if matches!(e.kind(), io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// ── Descriptor passing ────────────────────────────────────────────────────────
+
+fn send_sockets(stream: &StdUnixStream, sockets: &[DisplaySockets]) -> anyhow::Result<()> {
+    let indices: Vec<u8> = sockets.iter().map(|s| s.display_index).collect();
+    let fds: Vec<RawFd> = sockets.iter().flat_map(|s| [s.video.as_raw_fd(), s.signaling.as_raw_fd()]).collect();
+    send_fds(stream, &serde_json::to_vec(&indices)?, &fds)?;
+    Ok(())
+}
+
+fn recv_sockets(stream: &StdUnixStream) -> anyhow::Result<Vec<DisplaySockets>> {
+    let (data, fds) = recv_fds(stream)?;
+    let indices: Vec<u8> = serde_json::from_slice(&data)?;
+    anyhow::ensure!(
+        fds.len() == 2 * indices.len(),
+        "handover sent {} descriptors for {} displays",
+        fds.len(),
+        indices.len()
+    );
+    let mut fds = fds.into_iter();
+    let sockets = indices
+        .into_iter()
+        .map(|display_index| {
+            let (video, signaling) = (fds.next().unwrap(), fds.next().unwrap());
+            DisplaySockets {
+                display_index,
+                video:     std::net::UdpSocket::from(video),
+                signaling: std::net::TcpListener::from(signaling),
+            }
+        })
+        .collect();
+    Ok(sockets)
+}
+
+/// Control-message buffer for `n` descriptors, aligned for `cmsghdr`.
+fn cmsg_buffer(n: usize) -> Vec<u64> {
+    // SAFETY: CMSG_SPACE is a pure size computation.
+    let space = unsafe { libc::CMSG_SPACE((n * std::mem::size_of::<RawFd>()) as u32) } as usize;
+    vec![0; space.div_ceil(8)]
+}
+
+/// One `sendmsg` carrying `data` and `fds`.
+fn send_fds(stream: &StdUnixStream, data: &[u8], fds: &[RawFd]) -> io::Result<()> {
+    let fd_bytes = std::mem::size_of_val(fds);
+    let mut control = cmsg_buffer(fds.len());
+    let mut iov = libc::iovec { iov_base: data.as_ptr() as *mut _, iov_len: data.len() };
+    // SAFETY: all-zero is a valid `msghdr`; the pointers set below refer to
+    // `iov` and `control`, which outlive the call, and the control buffer is
+    // CMSG_SPACE bytes for `fds.len()` descriptors.
+    let sent = unsafe {
+        let mut msg: libc::msghdr = std::mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = (control.len() * 8) as _;
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(fd_bytes as u32) as _;
+        std::ptr::copy_nonoverlapping(fds.as_ptr(), libc::CMSG_DATA(cmsg).cast::<RawFd>(), fds.len());
+        libc::sendmsg(stream.as_raw_fd(), &msg, 0)
+    };
+    if sent < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if sent as usize != data.len() {
+        return Err(io::Error::new(io::ErrorKind::WriteZero, "short handover message"));
+    }
+    Ok(())
+}
+
+/// One `recvmsg`: the data and every descriptor that came with it.
+fn recv_fds(stream: &StdUnixStream) -> io::Result<(Vec<u8>, Vec<OwnedFd>)> {
+    let mut data = vec![0u8; 4096];
+    let mut control = cmsg_buffer(MAX_FDS);
+    let mut iov = libc::iovec { iov_base: data.as_mut_ptr().cast(), iov_len: data.len() };
+    // SAFETY: as in `send_fds`; the kernel fills at most the lengths given.
+    let (received, msg) = unsafe {
+        let mut msg: libc::msghdr = std::mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = (control.len() * 8) as _;
+        #[cfg(target_os = "linux")]
+        let flags = libc::MSG_CMSG_CLOEXEC;
+        #[cfg(not(target_os = "linux"))]
+        let flags = 0;
+        (libc::recvmsg(stream.as_raw_fd(), &mut msg, flags), msg)
+    };
+    if received < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut fds = Vec::new();
+    // SAFETY: walks the control messages the kernel wrote into `control`;
+    // every SCM_RIGHTS descriptor is now owned by this process.
+    unsafe {
+        let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+        while !cmsg.is_null() {
+            if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                let bytes = (*cmsg).cmsg_len as usize - libc::CMSG_LEN(0) as usize;
+                let first = libc::CMSG_DATA(cmsg).cast::<RawFd>();
+                for i in 0..bytes / std::mem::size_of::<RawFd>() {
+                    fds.push(OwnedFd::from_raw_fd(first.add(i).read_unaligned()));
+                }
+            }
+            cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+        }
+    }
+    if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "too many descriptors in handover"));
+    }
+    data.truncate(received as usize);
+    Ok((data, fds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sockets_survive_the_trip() {
+        let (a, b) = StdUnixStream::pair().unwrap();
+        let video = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let signaling = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addrs = (video.local_addr().unwrap(), signaling.local_addr().unwrap());
+
+        send_sockets(&a, &[DisplaySockets { display_index: 3, video, signaling }]).unwrap();
+        let got = recv_sockets(&b).unwrap();
+
+        assert_eq!(got.len(), 1);
+        assert_eq!(got[0].display_index, 3);
+        assert_eq!((got[0].video.local_addr().unwrap(), got[0].signaling.local_addr().unwrap()), addrs);
+    }
+
+    #[tokio::test]
+    async fn no_receiver_means_no_handover() {
+        let path = std::env::temp_dir().join(format!("duallink-handover-test-{}.sock", std::process::id()));
+        assert!(request_handover_from(path).await.unwrap().is_none());
+    }
+}
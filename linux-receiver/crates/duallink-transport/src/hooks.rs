@@ -0,0 +1,288 @@
+//! Session hooks — shell commands and webhooks run on session events.
+//!
+//! Configured in [`ReceiverSettings::hooks`](duallink_core::ReceiverSettings)
+//! or, for a single all-events hook, with `DUALLINK_HOOK_EXEC=<command>` /
+//! `DUALLINK_HOOK_URL=<http url>`. [`Hooks::tap`] sits between a display's
+//! [`SignalingEvent`] channel and its session loop, so hooks fire whatever
+//! the loop does with the events.
+//!
+//! Exec hooks see the event as environment variables:
+//!
+//! | Variable                 | Value                                   |
+//! |--------------------------|-----------------------------------------|
+//! | `DUALLINK_EVENT`         | `sessionStarted` / `sessionStopped` / `clientDisconnected` |
+//! | `DUALLINK_DISPLAY`       | display index                           |
+//! | `DUALLINK_SESSION_ID`    | session id (if known)                   |
+//! | `DUALLINK_DEVICE_NAME`   | sender device name (if known)           |
+//! | `DUALLINK_CLIENT_ADDR`   | sender `ip:port` (if known)             |
+//! | `DUALLINK_RESOLUTION`    | stream resolution, e.g. `1920x1080`     |
+//! | `DUALLINK_FPS`           | stream frame rate                       |
+//!
+//! Webhooks get the same fields as a JSON body ([`HookPayload`]). Only
+//! plain `http://` is supported — use an exec hook with `curl` for HTTPS.
+//! Hooks run in the background; failures are logged and never affect the
+//! session.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use duallink_core::{HookAction, HookEvent, ReceiverSettings, SessionHook};
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::SignalingEvent;
+
+/// Exec hooks still running after this are killed.
+const EXEC_TIMEOUT: Duration = Duration::from_secs(30);
+/// Connect + request + response budget for a webhook.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ── Payload ───────────────────────────────────────────────────────────────────
+
+/// What a hook is told about the event.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookPayload {
+    pub event:         HookEvent,
+    pub display_index: u8,
+    pub session_id:    Option<String>,
+    pub device_name:   Option<String>,
+    pub client_addr:   Option<String>,
+    pub resolution:    Option<String>,
+    pub fps:           Option<u32>,
+}
+
+impl HookPayload {
+    fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("DUALLINK_EVENT", self.event.as_str().to_owned()),
+            ("DUALLINK_DISPLAY", self.display_index.to_string()),
+        ];
+        let optional = [
+            ("DUALLINK_SESSION_ID", self.session_id.clone()),
+            ("DUALLINK_DEVICE_NAME", self.device_name.clone()),
+            ("DUALLINK_CLIENT_ADDR", self.client_addr.clone()),
+            ("DUALLINK_RESOLUTION", self.resolution.clone()),
+            ("DUALLINK_FPS", self.fps.map(|f| f.to_string())),
+        ];
+        env.extend(optional.into_iter().filter_map(|(k, v)| Some((k, v?))));
+        env
+    }
+}
+
+// ── Hooks ─────────────────────────────────────────────────────────────────────
+
+/// The configured hooks; cheap to clone.
+#[derive(Clone, Default)]
+pub struct Hooks {
+    hooks: Arc<Vec<SessionHook>>,
+}
+
+impl Hooks {
+    pub fn new(hooks: Vec<SessionHook>) -> Self {
+        Self { hooks: Arc::new(hooks) }
+    }
+
+    /// Hooks from the saved settings plus `DUALLINK_HOOK_EXEC` /
+    /// `DUALLINK_HOOK_URL`.
+    pub fn load() -> Self {
+        let mut hooks = ReceiverSettings::load().hooks;
+        let env_hook = |var: &str, action: fn(String) -> HookAction| {
+            std::env::var(var).ok().filter(|v| !v.is_empty()).map(|v| SessionHook { events: Vec::new(), action: action(v) })
+        };
+        hooks.extend(env_hook("DUALLINK_HOOK_EXEC", HookAction::Exec));
+        hooks.extend(env_hook("DUALLINK_HOOK_URL", HookAction::Webhook));
+        if !hooks.is_empty() {
+            info!("{} session hook(s) configured", hooks.len());
+        }
+        Self::new(hooks)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Pass `events` through, firing hooks for the session events of display
+    /// `display_index` on the way. Returns `events` itself if there are no
+    /// hooks.
+    pub fn tap(&self, display_index: u8, mut events: mpsc::Receiver<SignalingEvent>) -> mpsc::Receiver<SignalingEvent> {
+        if self.is_empty() {
+            return events;
+        }
+        let (tx, rx) = mpsc::channel(16);
+        let hooks = self.clone();
+        tokio::spawn(async move {
+            // Last session seen, so stop/disconnect hooks can name it.
+            let mut session = HookPayload {
+                event: HookEvent::SessionStarted,
+                display_index,
+                session_id: None,
+                device_name: None,
+                client_addr: None,
+                resolution: None,
+                fps: None,
+            };
+            while let Some(event) = events.recv().await {
+                let fired = match &event {
+                    SignalingEvent::SessionStarted { session_id, device_name, config, client_addr, .. } => {
+                        session.session_id = Some(session_id.clone());
+                        session.device_name = Some(device_name.clone());
+                        session.client_addr = Some(client_addr.to_string());
+                        session.resolution = Some(config.resolution.to_string());
+                        session.fps = Some(config.target_fps);
+                        Some(HookEvent::SessionStarted)
+                    }
+                    SignalingEvent::SessionStopped { .. } => Some(HookEvent::SessionStopped),
+                    SignalingEvent::ClientDisconnected => Some(HookEvent::ClientDisconnected),
+                    _ => None,
+                };
+                if let Some(kind) = fired {
+                    hooks.fire(HookPayload { event: kind, ..session.clone() });
+                }
+                if tx.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+        rx
+    }
+
+    /// Run every hook subscribed to `payload.event` in the background.
+    pub fn fire(&self, payload: HookPayload) {
+        for hook in self.hooks.iter().filter(|h| h.fires_on(payload.event)) {
+            let action = hook.action.clone();
+            let payload = payload.clone();
+            tokio::spawn(async move {
+                let n = payload.display_index;
+                let result = match &action {
+                    HookAction::Exec(cmd) => run_exec(cmd, &payload).await,
+                    HookAction::Webhook(url) => post_webhook(url, &payload).await,
+                };
+                match result {
+                    Ok(()) => debug!("Display[{n}] {} hook {:?} done", payload.event.as_str(), action),
+                    Err(e) => warn!("Display[{n}] {} hook {:?} failed: {e:#}", payload.event.as_str(), action),
+                }
+            });
+        }
+    }
+}
+
+// ── Exec ──────────────────────────────────────────────────────────────────────
+
+async fn run_exec(cmd: &str, payload: &HookPayload) -> anyhow::Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut c = tokio::process::Command::new("cmd");
+        c.arg("/C").arg(cmd);
+        c
+    } else {
+        let mut c = tokio::process::Command::new("sh");
+        c.arg("-c").arg(cmd);
+        c
+    };
+    command.envs(payload.env()).stdin(std::process::Stdio::null()).kill_on_drop(true);
+    let status = tokio::time::timeout(EXEC_TIMEOUT, command.status())
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out after {}s", EXEC_TIMEOUT.as_secs()))??;
+    anyhow::ensure!(status.success(), "exited with {status}");
+    Ok(())
+}
+
+// ── Webhook ───────────────────────────────────────────────────────────────────
+
+/// `http://host[:port][/path]` → (`host:port`, host, path).
+fn parse_http_url(url: &str) -> anyhow::Result<(String, String, String)> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| anyhow::anyhow!("only http:// webhooks are supported"))?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    anyhow::ensure!(!authority.is_empty(), "missing host");
+    // `[v6]:port`, `host:port` or bare host.
+    let has_port = authority.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+    let addr = if has_port { authority.to_owned() } else { format!("{authority}:80") };
+    Ok((addr, authority.to_owned(), path.to_owned()))
+}
+
+async fn post_webhook(url: &str, payload: &HookPayload) -> anyhow::Result<()> {
+    let (addr, host, path) = parse_http_url(url)?;
+    let body = serde_json::to_vec(payload)?;
+    let exchange = async {
+        let mut stream = TcpStream::connect(&addr).await?;
+        let head = format!(
+            "POST {path} HTTP/1.1\r\nHost: {host}\r\nUser-Agent: duallink-receiver\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(&body).await?;
+        // Status line only; the body is ignored.
+        let mut buf = [0u8; 64];
+        let mut len = 0;
+        while len < buf.len() && !buf[..len].contains(&b'\n') {
+            let n = stream.read(&mut buf[len..]).await?;
+            if n == 0 {
+                break;
+            }
+            len += n;
+        }
+        anyhow::Ok(String::from_utf8_lossy(&buf[..len]).lines().next().unwrap_or_default().to_owned())
+    };
+    let status_line = tokio::time::timeout(WEBHOOK_TIMEOUT, exchange)
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out after {}s", WEBHOOK_TIMEOUT.as_secs()))??;
+    let code = status_line.split_whitespace().nth(1).and_then(|c| c.parse::<u16>().ok());
+    anyhow::ensure!(matches!(code, Some(200..=299)), "server answered {status_line:?}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn http_urls() {
+        let parse = |u| parse_http_url(u).unwrap();
+        assert_eq!(parse("http://tv.local/on"), ("tv.local:80".into(), "tv.local".into(), "/on".into()));
+        assert_eq!(parse("http://10.0.0.2:8123"), ("10.0.0.2:8123".into(), "10.0.0.2:8123".into(), "/".into()));
+        assert_eq!(parse("http://[::1]:9/x").0, "[::1]:9");
+        assert_eq!(parse("http://[::1]/x").0, "[::1]:80");
+        assert!(parse_http_url("https://tv.local/").is_err());
+    }
+
+    #[tokio::test]
+    async fn webhook_posts_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            let mut req = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !String::from_utf8_lossy(&req).contains("\"displayIndex\":1") {
+                let n = sock.read(&mut buf).await.unwrap();
+                req.extend_from_slice(&buf[..n]);
+            }
+            sock.write_all(b"HTTP/1.1 204 No Content\r\n\r\n").await.unwrap();
+            String::from_utf8(req).unwrap()
+        });
+
+        let payload = HookPayload {
+            event: HookEvent::SessionStopped,
+            display_index: 1,
+            session_id: Some("abc".into()),
+            device_name: None,
+            client_addr: None,
+            resolution: None,
+            fps: None,
+        };
+        post_webhook(&url, &payload).await.unwrap();
+        let req = server.await.unwrap();
+        assert!(req.starts_with("POST /hook HTTP/1.1\r\n"));
+        assert!(req.contains(r#""event":"sessionStopped""#));
+    }
+}
@@ -21,10 +21,38 @@
 //! [12..16] pts_ms     u32 BE   presentation timestamp (ms)
 //! [16]     flags      u8       bit0 = keyframe
 //! [17]     display_index u8   zero-based display stream index (was reserved[0])
-//! [18..20] reserved   [u8; 2]
+//! [18]     temporal_layer u8  0 = referenced, ≥ 1 = droppable (was reserved[1])
+//! [19]     reserved   u8
 //! [20..]   payload    [u8]     H.264 NAL unit slice
 //! ```
 //!
+//! # DualLink UDP Frame Protocol v2
+//!
+//! Sent by senders that find [`CAP_DLNK_V2`] in `hello_ack`. Same fields,
+//! except for the timestamp and an optional frame checksum:
+//!
+//! ```text
+//! [0..4]   magic       u32 BE   0x444C4E32 ("DLN2")
+//! [4..12]  frame_seq, frag_idx, frag_count — as v1
+//! [12..16] clock_epoch u32 BE   id of the sender clock's origin
+//! [16]     flags       u8       bit0 = keyframe, bit1 = checksum present,
+//!                               bits2–3 = temporal layer, bit4 = NAL end
+//! [17]     display_index u8
+//! [18..20] checksum    u16 BE   low 16 bits of the frame payload's CRC-32
+//! [20..28] pts_us      u64 BE   presentation timestamp (µs, sender clock)
+//! [28..]   payload     [u8]     H.264 NAL unit slice
+//! ```
+//!
+//! Either way the UDP task maps the sender PTS onto the receiver's clock
+//! with a [`ClockMapper`] (v1 timestamps are unwrapped first), so
+//! `EncodedFrame::timestamp_us` is µs on a monotonic receiver timeline.
+//!
+//! Parsing and reassembly live in [`protocol`], which validates every field
+//! instead of trusting the sender and drops frames failing their checksum.
+//! In a session with slice-level decoding (see [`duallink_core::slices`])
+//! the UDP task hands a frame's complete leading NAL units on ahead of it,
+//! as `EncodedFrame`s with `partial` set.
+//!
 //! # Signaling Protocol v2 (TLS-secured, matches Signaling.swift)
 //!
 //! Length-prefixed JSON over TLS/TCP:
@@ -33,56 +61,399 @@
 //! [4..]   json    UTF-8   SignalingMessage
 //! ```
 //!
+//! Oversized, malformed, unknown and too frequent messages are refused with
+//! a typed `error` reply (see [`framing`]).
+//!
 //! The server generates an ephemeral self-signed certificate at startup.
 //! The certificate's SHA-256 fingerprint is displayed alongside a 6-digit
 //! pairing PIN that the Mac client must include in its `hello` message.
+//!
+//! # Own certificates
+//!
+//! Senders can only trust a self-signed certificate on first use, after the
+//! user compared its fingerprint. Sites with their own PKI instead give the
+//! receiver a certificate issued for its host name, which senders holding
+//! the CA verify like any TLS server (see [`configured_tls_identity`]):
+//!
+//! ```text
+//! DUALLINK_TLS_CERT=/etc/duallink/receiver.pem   # chain, leaf first
+//! DUALLINK_TLS_KEY=/etc/duallink/receiver.key
+//! ```
+//!
+//! # Mutual TLS
+//!
+//! PINs don't scale to fleets of machines. With `DUALLINK_CLIENT_CA` set to
+//! a PEM bundle of CA certificates, the server asks senders for a client
+//! certificate and verifies it against those CAs; a sender presenting a
+//! trusted certificate skips the PIN check. Senders without one still pair
+//! with the PIN, unless `DUALLINK_CLIENT_AUTH=required` makes the
+//! certificate mandatory (the handshake fails without it):
+//!
+//! ```text
+//! DUALLINK_CLIENT_CA=/etc/duallink/fleet-ca.pem
+//! DUALLINK_CLIENT_AUTH=required    # or optional (default)
+//! ```
+//!
+//! # Resuming sessions
+//!
+//! Each accepted `hello_ack` carries a one-time `resumeToken`, recorded in
+//! the config directory. A sender reconnecting with it in `hello` — also to
+//! a receiver restarted since — skips the PIN and is asked for a keyframe
+//! straight away. Tokens expire after [`configured_resume_ttl`] (see
+//! [`duallink_core::resume`]).
+//!
+//! # Keyframe gating
+//!
+//! Delta frames are useless to a decoder that has not seen the keyframe
+//! they depend on. Each display's [`KeyframeGate`] is armed when a session
+//! starts, when a frame is lost (a `frame_seq` gap, see below) and when the
+//! app restarts its decoder; while armed, the UDP task drops
+//! every delta frame and asks the sender for a keyframe (`keyframe_request`,
+//! for senders advertising [`CAP_KEYFRAME_REQUEST`]).
+//!
+//! The keyframe that opens the gate is what a fresh decoder starts from, so
+//! it must carry the stream's parameter sets (SPS/PPS). Those a keyframe
+//! lacks are put back from the last ones seen this session (see
+//! [`duallink_core::parameter_sets`]); a keyframe lacking sets never seen is
+//! dropped and another one requested.
+//!
+//! # Loss accounting
+//!
+//! Completed frames go through a [`SequenceTracker`]: late and duplicate
+//! frames are discarded, gaps arm the keyframe gate and are reported as
+//! [`SignalingEvent::FrameGap`]. The totals are available from
+//! [`DualLinkReceiver::frame_stats`] and are sent to the sender in
+//! `keepalive_ack`.
+//!
+//! # Stalled streams
+//!
+//! A session's stream should never go quiet for long: even a static screen
+//! is refreshed about once a second. When no frame has been assembled for
+//! [`configured_stall_timeout`] while the display is neither paused nor
+//! blanked, the display reports [`SignalingEvent::StreamStalled`] (once per
+//! stall) and asks the sender for a keyframe.
+//!
+//! # Stream limits
+//!
+//! A display's [`DisplayConfig::limits`] caps the bitrate, resolution and
+//! frame rate a sender may stream. They are sent in `hello_ack`
+//! (`maxBitrateKbps`, `maxResolution`, `maxFps`); a `hello` or
+//! `config_update` asking for more is clamped to them, or the `hello`
+//! rejected if [`DisplayConfig::reject_over_limits`] is set. A clamped frame
+//! rate is sent back in a `config_update` to senders advertising
+//! [`CAP_FPS_REQUEST`]. Frames of a sender that streams well above the
+//! bitrate ceiling anyway are dropped by a [`BitrateGuard`] and reported as
+//! [`SignalingEvent::BitrateExceeded`]; droppable frames above the frame
+//! rate are skipped by a [`FrameRateCap`].
+//!
+//! # View-only sessions
+//!
+//! Input from the receiver's windows goes back to the sender only if both
+//! sides agree: the receiver's policy ([`DualLinkReceiver::set_allow_input`],
+//! `DUALLINK_VIEW_ONLY=1`) and the sender's `allowInput` in `hello`. The
+//! outcome is sent in `hello_ack` and [`SignalingEvent::SessionStarted`];
+//! view-only sessions get no input writer task.
+//!
+//! # Concurrent senders
+//!
+//! Each display belongs to the sender whose session runs on it, so display
+//! 0 can show a Mac while display 1 shows a Windows PC. A `hello` for a
+//! display owned by another sender is rejected (the same device reconnecting
+//! from the same host takes over, see [`PeerInfo`]). Input is routed per
+//! display: [`InputSender::for_display`] reaches only that display's sender.
+//! With `DUALLINK_SEPARATE_PINS=1` or the saved settings' `separatePins`
+//! every display also gets its own pairing PIN (see
+//! [`ReceiverState::pin_for`]), so pairing with one display gives no access
+//! to the others.
+//!
+//! # Blanking
+//!
+//! `blank { enabled }` hides the stream without ending the session, from
+//! either end, between peers that both advertise [`CAP_BLANK`]. From the
+//! sender it is reported as [`SignalingEvent::Blank`] and the app blanks
+//! the display; the receiver sends it through [`SessionBlank`] to have the
+//! sender pause capture, and repeats it to senders that reconnect while the
+//! request stands.
+//!
+//! # Pausing displays
+//!
+//! `display_state { displayIndex, paused }` pauses one display's stream
+//! while the others go on, from either end, between peers that both
+//! advertise [`CAP_DISPLAY_STATE`]. A paused sender keeps its capture
+//! session but sends nothing, and resumes with a keyframe. The app pauses
+//! and resumes through [`SessionPause`]; the sender's own changes arrive as
+//! [`SignalingEvent::DisplayState`] and are recorded there too, so both
+//! ends' toggles agree. Like blanking, a standing pause is repeated to a
+//! sender that reconnects.
+//!
+//! # Previews
+//!
+//! Senders advertising [`CAP_PREVIEW`] get a `preview` message with a small
+//! base64 JPEG of what the display shows whenever the app publishes one
+//! through [`SessionPreview`] — every [`PREVIEW_INTERVAL`] while
+//! [`SessionPreview::is_wanted`], so the sender can confirm its screen
+//! really arrives.
+//!
+//! # Frame-rate requests
+//!
+//! While a display's window is hidden the app asks the sender for fewer
+//! frames through [`SessionPace`]. Senders advertising [`CAP_FPS_REQUEST`]
+//! are sent a `config_update` carrying the session's config with `targetFps`
+//! lowered, and another with the negotiated rate when the request is
+//! withdrawn; a request standing when a sender connects is sent straight
+//! away.
+//!
+//! # Power
+//!
+//! Between peers that both advertise [`CAP_POWER`] each end sends its
+//! [`PowerState`] in `power` messages: the receiver's through
+//! [`SessionPower`] (again to senders that reconnect), the sender's reported
+//! as [`SignalingEvent::SenderPower`]. The sender switches to the
+//! battery-saver preset while either end wants it (see
+//! [`duallink_core::power`]).
+//!
+//! # File transfer
+//!
+//! Between peers that both advertise [`CAP_FILE_TRANSFER`] either end may
+//! send files: a `file_offer { transferId, fileName, fileSize }`, answered by
+//! `file_accept { transferId, accepted }`, then `file_chunk { transferId,
+//! offset, data }` messages of base64 bytes, paced to stay under the
+//! signaling rate limit. The app sends and answers through
+//! [`DisplayChannels::files`]; offers, progress and finished transfers
+//! arrive as [`SignalingEvent::File`]. Transfers still running when the
+//! sender disconnects fail on both ends.
+//!
+//! # Usage accounting
+//!
+//! Each display counts the bytes of its video datagrams and signaling in a
+//! [`UsageMeter`], restarted at every `hello`. When the session ends its
+//! [`SessionSummary`] is logged, carried by
+//! [`SignalingEvent::SessionStopped`] (a `stop` from the sender) and
+//! appended to the usage history file, if one is configured (see
+//! [`configured_usage_history`]) — also when the sender just disconnects.
+//!
+//! # Embedding
+//!
+//! Apps embedding the receiver follow it through a [`ReceiverHandle`]
+//! ([`DualLinkReceiver::handle`]): state snapshots and a watch channel of
+//! each display's phase and peer, polled counters, and control such as
+//! regenerating the pairing PIN — see [`handle`].
+//!
+//! # Handover
+//!
+//! A running receiver can give its bound ports to another process instead
+//! of being killed for them: [`DualLinkReceiver::release_sockets`] stops
+//! every display and returns its sockets, and
+//! [`DualLinkReceiver::start_with_sockets`] adopts them. On Unix,
+//! [`handover`] passes them between processes over a control socket.
+//!
+//! # Ports
+//!
+//! Display `n` listens on UDP `base + 2n` / TCP `base + 2n + 1`, where
+//! `base` is 7878 unless moved with `DUALLINK_BASE_PORT` or the saved
+//! settings' `basePort` (see [`configured_base_port`]); single displays can
+//! also be given their own ports in [`DisplayConfig`]. The ports actually
+//! bound are sent to each sender in `hello_ack` as a [`PortMap`] and are
+//! available from [`DualLinkReceiver::port_map`] for mDNS advertising.
+
+pub mod framing;
+pub mod handle;
+#[cfg(unix)]
+pub mod handover;
+pub mod hooks;
+pub mod protocol;
+mod recv;
+mod resume;
 
-use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-
-use bytes::Bytes;
-use duallink_core::{EncodedFrame, InputEvent, StreamConfig, VideoCodec};
+use std::time::Duration;
+
+use duallink_core::{
+    detect_monitors, BitrateGuard, ClockMapper, DisplayPorts, EncodedFrame, FrameCounters, FrameRateCap, InputEvent, InputRecorder,
+    InputRecording, MonitorInfo, ParameterSets, PortMap, PowerState, PtsUnwrapper, ReceiverQueues, ReceiverSettings, Repair, Resolution, SequenceEvent, SequenceStats, SequenceTracker, SessionSummary, StreamConfig,
+    StreamLimits, UsageMeter, VideoCodec, CAP_BLANK, CAP_DISPLAYS_CHANGED, CAP_DISPLAY_STATE, CAP_DISPLAY_INFO, CAP_DLNK_V2, CAP_KEEPALIVE_ACK,
+    CAP_FPS_REQUEST, CAP_KEYFRAME_REQUEST, CAP_POWER, CAP_PREVIEW, CAP_FILE_TRANSFER, FILE_CHUNK_INTERVAL, FileMessage,
+    FileTransferEvent, FileTransfers,
+};
+use duallink_core::trace::{self, Stage as TraceStage};
+use anyhow::Context as _;
 use rustls::pki_types::{CertificateDer, PrivateKeyDer};
 use serde::{Deserialize, Serialize};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::AsyncWriteExt;
 use tokio::net::{TcpListener, UdpSocket};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use tokio_rustls::TlsAcceptor;
 use tracing::{debug, info, warn};
 
+use framing::{parse_message, read_frame, FrameError, MessageRate};
+pub use framing::{SignalingErrorCode, MAX_SIGNALING_MESSAGE};
+pub use handle::{DisplayPhase, DisplayStats, PeerInfo, ReceiverDisplay, ReceiverHandle, ReceiverState};
+use protocol::{parse_datagram, AssembledFrame, FrameReassembler, Timestamp};
+pub use protocol::{ReassemblyBudget, ReassemblyStats};
+pub use recv::DEFAULT_RECV_BATCH;
+use recv::DatagramReceiver;
+use resume::Resumption;
+
 // ── Ports ──────────────────────────────────────────────────────────────────────
 
-pub const VIDEO_PORT: u16 = 7878;
-pub const SIGNALING_PORT: u16 = 7879;
+pub const VIDEO_PORT: u16 = duallink_core::DEFAULT_VIDEO_PORT;
+pub const SIGNALING_PORT: u16 = duallink_core::DEFAULT_SIGNALING_PORT;
 
-/// UDP video port for a given display index: 7878, 7880, 7882, …
+/// Default UDP video port for a given display index: 7878, 7880, 7882, …
 pub fn video_port(display_index: u8) -> u16 {
-    VIDEO_PORT + (display_index as u16) * 2
+    PortMap::default().video_port(display_index)
 }
 
-/// TCP signaling port for a given display index: 7879, 7881, 7883, …
+/// Default TCP signaling port for a given display index: 7879, 7881, 7883, …
 pub fn signaling_port(display_index: u8) -> u16 {
-    SIGNALING_PORT + (display_index as u16) * 2
+    PortMap::default().signaling_port(display_index)
+}
+
+/// Whether sessions may send input back: `false` if `DUALLINK_VIEW_ONLY=1`
+/// or the saved [`ReceiverSettings::view_only`] is set.
+pub fn configured_allow_input() -> bool {
+    match std::env::var("DUALLINK_VIEW_ONLY") {
+        Ok(v) => v != "1",
+        Err(_) => !ReceiverSettings::load().view_only,
+    }
+}
+
+/// Whether each display gets its own pairing PIN: `DUALLINK_SEPARATE_PINS=1`,
+/// else the saved [`ReceiverSettings::separate_pins`].
+pub fn configured_separate_pins() -> bool {
+    match std::env::var("DUALLINK_SEPARATE_PINS") {
+        Ok(v) => v == "1",
+        Err(_) => ReceiverSettings::load().separate_pins,
+    }
+}
+
+/// How long a display may go without frames before it reports
+/// [`SignalingEvent::StreamStalled`]: `DUALLINK_STALL_SECS`, else the saved
+/// [`ReceiverSettings::stall_secs`], else [`DEFAULT_STALL_TIMEOUT`]. Zero
+/// turns the watchdog off.
+pub fn configured_stall_timeout() -> Duration {
+    std::env::var("DUALLINK_STALL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .or_else(|| ReceiverSettings::load().stall_secs)
+        .map_or(DEFAULT_STALL_TIMEOUT, Duration::from_secs)
+}
+
+/// Video port of display 0: `DUALLINK_BASE_PORT`, else the saved
+/// [`ReceiverSettings::base_port`], else [`VIDEO_PORT`].
+pub fn configured_base_port() -> u16 {
+    std::env::var("DUALLINK_BASE_PORT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .or_else(|| ReceiverSettings::load().base_port)
+        .unwrap_or(VIDEO_PORT)
+}
+
+/// Usage history file: `DUALLINK_USAGE_HISTORY`, else the saved
+/// [`ReceiverSettings::usage_history`].
+pub fn configured_usage_history() -> Option<std::path::PathBuf> {
+    match std::env::var_os("DUALLINK_USAGE_HISTORY") {
+        Some(_) => duallink_core::usage::history_file(),
+        None => ReceiverSettings::load().usage_history,
+    }
+}
+
+/// How long resume tokens stay valid: `DUALLINK_RESUME_TTL` (seconds),
+/// else the saved [`ReceiverSettings::resume_ttl_secs`], else
+/// [`DEFAULT_RESUME_TTL`](duallink_core::DEFAULT_RESUME_TTL). Zero turns
+/// resumption off.
+pub fn configured_resume_ttl() -> Duration {
+    std::env::var("DUALLINK_RESUME_TTL")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .or_else(|| ReceiverSettings::load().resume_ttl_secs)
+        .map_or(duallink_core::DEFAULT_RESUME_TTL, Duration::from_secs)
+}
+
+/// The receiver's TLS identity: the certificate named by `DUALLINK_TLS_CERT`
+/// and `DUALLINK_TLS_KEY`, else by the saved [`ReceiverSettings::tls_cert`]
+/// and [`ReceiverSettings::tls_key`], else a freshly generated self-signed
+/// one.
+pub fn configured_tls_identity() -> anyhow::Result<TlsIdentity> {
+    let paths = match (std::env::var_os("DUALLINK_TLS_CERT"), std::env::var_os("DUALLINK_TLS_KEY")) {
+        (Some(cert), Some(key)) => Some((cert.into(), key.into())),
+        (None, None) => {
+            let settings = ReceiverSettings::load();
+            match (settings.tls_cert, settings.tls_key) {
+                (Some(cert), Some(key)) => Some((cert, key)),
+                (None, None) => None,
+                _ => anyhow::bail!("tlsCert and tlsKey must be set together"),
+            }
+        }
+        _ => anyhow::bail!("DUALLINK_TLS_CERT and DUALLINK_TLS_KEY must be set together"),
+    };
+    match paths {
+        Some((cert, key)) => {
+            let identity = TlsIdentity::from_pem_files(&cert, &key)?;
+            info!("Using TLS certificate {}", cert.display());
+            Ok(identity)
+        }
+        None => generate_tls_identity(),
+    }
 }
 
 // ── TLS certificate generation ─────────────────────────────────────────────────
 
-/// Ephemeral TLS identity generated at server startup.
+/// TLS identity the signaling server presents.
 pub struct TlsIdentity {
     pub acceptor: TlsAcceptor,
     /// SHA-256 fingerprint of the certificate (hex-encoded, colon-separated).
     pub fingerprint: String,
 }
 
+impl TlsIdentity {
+    /// Load a PEM certificate chain (leaf first) and its PEM private key,
+    /// e.g. issued by the site's own CA.
+    pub fn from_pem_files(cert: &std::path::Path, key: &std::path::Path) -> anyhow::Result<Self> {
+        let pem = std::fs::read(cert).with_context(|| format!("Reading TLS certificate {}", cert.display()))?;
+        let chain = rustls_pemfile::certs(&mut pem.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("Parsing TLS certificate {}", cert.display()))?;
+        anyhow::ensure!(!chain.is_empty(), "No certificate in {}", cert.display());
+        let pem = std::fs::read(key).with_context(|| format!("Reading TLS key {}", key.display()))?;
+        let key = rustls_pemfile::private_key(&mut pem.as_slice())
+            .with_context(|| format!("Parsing TLS key {}", key.display()))?
+            .with_context(|| format!("No private key in {}", key.display()))?;
+        Self::new(chain, key)
+    }
+
+    /// Identity presenting `chain` (leaf first), signed with `key`.
+    fn new(chain: Vec<CertificateDer<'static>>, key: PrivateKeyDer<'static>) -> anyhow::Result<Self> {
+        // Install the ring crypto provider as the process-level default.
+        // This is required by rustls 0.23+ before any ServerConfig is built.
+        // `install_default` fails if already installed — we ignore that error.
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        // Compute SHA-256 fingerprint of the leaf
+        use std::fmt::Write;
+        let digest = sha256_digest(chain[0].as_ref());
+        let mut fingerprint = String::with_capacity(3 * digest.len());
+        for (i, byte) in digest.iter().enumerate() {
+            if i > 0 { fingerprint.push(':'); }
+            write!(fingerprint, "{:02X}", byte).unwrap();
+        }
+
+        let builder = rustls::ServerConfig::builder();
+        let builder = match client_cert_verifier()? {
+            Some(verifier) => builder.with_client_cert_verifier(verifier),
+            None => builder.with_no_client_auth(),
+        };
+        let server_config = builder.with_single_cert(chain, key).context("Using TLS certificate")?;
+
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+
+        Ok(Self { acceptor, fingerprint })
+    }
+}
+
 /// Generate a self-signed TLS certificate and return a TlsAcceptor.
 pub fn generate_tls_identity() -> anyhow::Result<TlsIdentity> {
-    // Install the ring crypto provider as the process-level default.
-    // This is required by rustls 0.23+ before any ServerConfig is built.
-    // `install_default` fails if already installed — we ignore that error.
-    let _ = rustls::crypto::ring::default_provider().install_default();
-
     let subject_alt_names = vec![
         "duallink.local".to_string(),
         "localhost".to_string(),
@@ -95,23 +466,42 @@ pub fn generate_tls_identity() -> anyhow::Result<TlsIdentity> {
     let cert_der = CertificateDer::from(cert.der().to_vec());
     let key_der = PrivateKeyDer::try_from(key_pair.serialize_der())
         .map_err(|e| anyhow::anyhow!("Failed to serialise private key: {}", e))?;
+    TlsIdentity::new(vec![cert_der], key_der)
+}
 
-    // Compute SHA-256 fingerprint
-    use std::fmt::Write;
-    let digest = sha256_digest(cert_der.as_ref());
-    let mut fingerprint = String::with_capacity(3 * digest.len());
-    for (i, byte) in digest.iter().enumerate() {
-        if i > 0 { fingerprint.push(':'); }
-        write!(fingerprint, "{:02X}", byte).unwrap();
+/// Verifier for sender certificates (see [Mutual TLS](crate#mutual-tls));
+/// `None` unless `DUALLINK_CLIENT_CA` is set.
+fn client_cert_verifier() -> anyhow::Result<Option<Arc<dyn rustls::server::danger::ClientCertVerifier>>> {
+    let Some(ca_path) = std::env::var_os("DUALLINK_CLIENT_CA") else {
+        return Ok(None);
+    };
+    let ca_path = std::path::PathBuf::from(ca_path);
+    let pem = std::fs::read(&ca_path).with_context(|| format!("Reading client CA {}", ca_path.display()))?;
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in rustls_pemfile::certs(&mut pem.as_slice()) {
+        roots.add(cert.with_context(|| format!("Parsing client CA {}", ca_path.display()))?)?;
     }
-
-    let server_config = rustls::ServerConfig::builder()
-        .with_no_client_auth()
-        .with_single_cert(vec![cert_der], key_der)?;
-
-    let acceptor = TlsAcceptor::from(Arc::new(server_config));
-
-    Ok(TlsIdentity { acceptor, fingerprint })
+    anyhow::ensure!(!roots.is_empty(), "No certificates in client CA {}", ca_path.display());
+
+    let required = match std::env::var("DUALLINK_CLIENT_AUTH") {
+        Err(_) => false,
+        Ok(s) => match s.trim().to_ascii_lowercase().as_str() {
+            "required" => true,
+            "optional" => false,
+            _ => {
+                warn!("Ignoring DUALLINK_CLIENT_AUTH='{s}' — expected required or optional");
+                false
+            }
+        },
+    };
+    let builder = rustls::server::WebPkiClientVerifier::builder(Arc::new(roots));
+    let verifier = if required { builder.build()? } else { builder.allow_unauthenticated().build()? };
+    info!(
+        "Client certificates signed by {} are trusted without a PIN ({})",
+        ca_path.display(),
+        if required { "required" } else { "optional" }
+    );
+    Ok(Some(verifier))
 }
 
 /// SHA-256 digest (no external dep — using built-in implementation).
@@ -192,141 +582,275 @@ pub fn generate_pairing_pin() -> String {
 
 // ── Protocol constants ─────────────────────────────────────────────────────────
 
-const MAGIC: u32 = 0x444C_4E4B;
-/// Header bytes written by Swift: magic(4)+frameSeq(4)+fragIdx(2)+fragCount(2)+pts(4)+flags(1)+display_index(1)+reserved(2) = 20
-const HEADER_SIZE: usize = 20;
-const UDP_BUF_SIZE: usize = 65_535;
-const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(2);
+/// Minimum spacing of `keyframe_request`s while a gate stays armed.
+const KEYFRAME_REQUEST_INTERVAL: Duration = Duration::from_millis(500);
 
-// ── Packet parsing ─────────────────────────────────────────────────────────────
+/// Longest a display goes without frames before it counts as stalled,
+/// unless configured otherwise (see [`configured_stall_timeout`]).
+pub const DEFAULT_STALL_TIMEOUT: Duration = Duration::from_secs(5);
 
-#[derive(Debug)]
-struct DualLinkPacket {
-    frame_seq: u32,
-    frag_index: u16,
-    frag_count: u16,
-    pts_ms: u32,
-    is_keyframe: bool,
-    /// Zero-based display stream index from byte [17] of the DLNK header.
-    display_index: u8,
-    payload: Bytes,
-}
+/// How often the stall watchdog looks at a session's last frame.
+const STALL_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
-fn parse_packet(buf: &[u8]) -> Option<DualLinkPacket> {
-    if buf.len() < HEADER_SIZE {
-        return None;
-    }
-    let magic = u32::from_be_bytes(buf[0..4].try_into().ok()?);
-    if magic != MAGIC {
-        debug!("Dropped packet: bad magic 0x{:08X}", magic);
-        return None;
-    }
-    let frame_seq   = u32::from_be_bytes(buf[4..8].try_into().ok()?);
-    let frag_index  = u16::from_be_bytes(buf[8..10].try_into().ok()?);
-    let frag_count  = u16::from_be_bytes(buf[10..12].try_into().ok()?);
-    let pts_ms      = u32::from_be_bytes(buf[12..16].try_into().ok()?);
-    let flags       = buf[16];
-    let display_index = buf[17];  // byte [17]: display_index (was reserved[0])
-    // buf[18..20] = reserved
-    if frag_count == 0 { return None; }
-    let payload = Bytes::copy_from_slice(&buf[HEADER_SIZE..]);
-    Some(DualLinkPacket { frame_seq, frag_index, frag_count, pts_ms, is_keyframe: flags & 0x01 != 0, display_index, payload })
-}
+/// How often the receiver re-enumerates its monitors to detect hot-plug.
+pub const MONITOR_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often the app snapshots a display for [`SessionPreview`].
+pub const PREVIEW_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Width of `preview` thumbnails in pixels; the height keeps the aspect ratio.
+pub const PREVIEW_WIDTH: u32 = 320;
 
-// ── Frame reassembler ──────────────────────────────────────────────────────────
+// ── Keyframe gate ──────────────────────────────────────────────────────────────
+
+/// Drops delta frames for one display until the next keyframe.
+///
+/// Cloned into [`DisplayChannels::keyframes`]; call [`arm`](Self::arm) after
+/// replacing the decoder.
+#[derive(Clone)]
+pub struct KeyframeGate {
+    inner: Arc<GateState>,
+}
 
-struct PartialFrame {
-    fragments:      Vec<Option<Bytes>>,
-    received_count: u16,
-    total_count:    u16,
-    pts_ms:         u32,
-    is_keyframe:    bool,
-    first_seen:     Instant,
+struct GateState {
+    awaiting: std::sync::atomic::AtomicBool,
+    /// Dropped frames while armed; reset when the keyframe arrives.
+    skipped:  std::sync::atomic::AtomicU64,
+    /// Wakes the signaling connection to send `keyframe_request`.
+    request:  tokio::sync::Notify,
 }
 
-impl PartialFrame {
-    fn new(frag_count: u16, pts_ms: u32, is_keyframe: bool) -> Self {
+impl KeyframeGate {
+    /// A gate that starts armed: nothing is decodable before a keyframe.
+    fn new() -> Self {
         Self {
-            fragments: vec![None; frag_count as usize],
-            received_count: 0,
-            total_count: frag_count,
-            pts_ms,
-            is_keyframe,
-            first_seen: Instant::now(),
+            inner: Arc::new(GateState {
+                awaiting: std::sync::atomic::AtomicBool::new(true),
+                skipped:  std::sync::atomic::AtomicU64::new(0),
+                request:  tokio::sync::Notify::new(),
+            }),
         }
     }
 
-    /// Returns true when all fragments have arrived.
-    fn push(&mut self, index: u16, payload: Bytes) -> bool {
-        let idx = index as usize;
-        if idx >= self.fragments.len() { return false; }
-        if self.fragments[idx].is_none() {
-            self.fragments[idx] = Some(payload);
-            self.received_count += 1;
-        }
-        self.received_count == self.total_count
+    /// Drop delta frames until the next keyframe, requesting one from the
+    /// sender if a delta frame arrives first.
+    pub fn arm(&self) {
+        self.inner.awaiting.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// `true` while delta frames are being dropped.
+    pub fn is_armed(&self) -> bool {
+        self.inner.awaiting.load(std::sync::atomic::Ordering::Relaxed)
     }
 
-    fn assemble(self) -> Bytes {
-        let total: usize = self.fragments.iter().flatten().map(|f| f.len()).sum();
-        let mut buf = bytes::BytesMut::with_capacity(total);
-        for frag in self.fragments.into_iter().flatten() {
-            buf.extend_from_slice(&frag);
+    /// Whether `frame` may be passed to the decoder; disarms on a keyframe.
+    fn admit(&self, frame: &EncodedFrame) -> bool {
+        use std::sync::atomic::Ordering::Relaxed;
+        if !self.inner.awaiting.load(Relaxed) {
+            return true;
+        }
+        if frame.is_keyframe {
+            self.inner.awaiting.store(false, Relaxed);
+            let skipped = self.inner.skipped.swap(0, Relaxed);
+            if skipped > 0 {
+                info!("Keyframe received — resuming after {} dropped delta frames", skipped);
+            }
+            return true;
+        }
+        if self.inner.skipped.fetch_add(1, Relaxed) == 0 {
+            debug!("Waiting for keyframe — dropping delta frames");
         }
-        buf.freeze()
+        self.inner.request.notify_one();
+        false
+    }
+
+    /// Stay armed and ask the sender for another keyframe — `admit` took
+    /// one the decoder can't use.
+    fn rearm(&self) {
+        self.arm();
+        self.inner.request.notify_one();
+    }
+
+    /// Resolves when a delta frame was dropped since the last call.
+    async fn requested(&self) {
+        self.inner.request.notified().await
     }
 }
 
-#[derive(Default)]
-struct FrameReassembler {
-    frames: HashMap<u32, PartialFrame>,
-}
-
-impl FrameReassembler {
-    fn push(&mut self, packet: DualLinkPacket) -> Option<EncodedFrame> {
-        // Evict stale partial frames
-        let now = Instant::now();
-        self.frames.retain(|seq, f| {
-            let keep = now.duration_since(f.first_seen) <= REASSEMBLY_TIMEOUT;
-            if !keep { warn!("Dropped stale partial frame seq={}", seq); }
-            keep
-        });
+// ── Session kick ───────────────────────────────────────────────────────────────
+
+/// Ends the current session on one display from the receiver side, like
+/// [`DualLinkReceiver::disconnect`] — for session loops that don't hold the
+/// receiver (e.g. the end-session hotkey).
+///
+/// The sender is sent `stop` and the display's event channel reports
+/// [`SignalingEvent::ClientDisconnected`]. No-op while no sender is connected.
+#[derive(Clone)]
+pub struct SessionKick(Arc<tokio::sync::Notify>);
+
+impl SessionKick {
+    pub fn disconnect(&self) {
+        self.0.notify_waiters();
+    }
+}
+
+// ── Session blank ──────────────────────────────────────────────────────────────
+
+/// Asks the sender on one display to pause capture (`blank`), e.g. while
+/// sensitive content would be on screen, and to resume it.
+///
+/// The request outlives sessions: a sender that connects while it stands is
+/// paused straight away. Senders without [`CAP_BLANK`] are not asked.
+#[derive(Clone)]
+pub struct SessionBlank(Arc<watch::Sender<bool>>);
+
+impl SessionBlank {
+    pub fn request(&self, enabled: bool) {
+        self.0.send_if_modified(|current| std::mem::replace(current, enabled) != enabled);
+    }
 
-        let seq = packet.frame_seq;
-        let entry = self.frames.entry(seq).or_insert_with(|| {
-            PartialFrame::new(packet.frag_count, packet.pts_ms, packet.is_keyframe)
+    /// Whether capture pause is currently requested.
+    pub fn is_requested(&self) -> bool {
+        *self.0.borrow()
+    }
+}
+
+// ── Session pause ──────────────────────────────────────────────────────────────
+
+/// Pause state of one display, shared by the app and its signaling
+/// connections.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct PauseState {
+    paused:      bool,
+    /// Set by the sender's `display_state`, so not sent back to it.
+    from_sender: bool,
+}
+
+/// Pauses and resumes the stream of one display (`display_state`) without
+/// ending the session.
+///
+/// Like [`SessionBlank`], the state outlives sessions: a sender that
+/// connects while the display is paused is paused straight away. Senders
+/// without [`CAP_DISPLAY_STATE`] are not asked.
+#[derive(Clone)]
+pub struct SessionPause(Arc<watch::Sender<PauseState>>);
+
+impl SessionPause {
+    pub fn set_paused(&self, paused: bool) {
+        self.0.send_if_modified(|current| {
+            std::mem::replace(current, PauseState { paused, from_sender: false }).paused != paused
         });
+    }
 
-        if !entry.push(packet.frag_index, packet.payload) {
-            return None; // frame not complete yet
-        }
+    /// Whether the display is paused, by either end.
+    pub fn is_paused(&self) -> bool {
+        self.0.borrow().paused
+    }
+}
 
-        let partial = self.frames.remove(&seq)?;
-        let pts_ms = partial.pts_ms;
-        let is_keyframe = partial.is_keyframe;
-        let data = partial.assemble();
-        debug!("Assembled frame seq={} {} bytes keyframe={}", seq, data.len(), is_keyframe);
+// ── Session pace ───────────────────────────────────────────────────────────────
 
-        Some(EncodedFrame {
-            data,
-            timestamp_us: pts_ms as u64 * 1_000,
-            is_keyframe,
-            codec: VideoCodec::H264,
-        })
+/// Asks the sender on one display for a lower frame rate (`config_update`),
+/// e.g. while the display's window is hidden, or for the negotiated rate
+/// again.
+///
+/// Like [`SessionBlank`], the request outlives sessions. Senders without
+/// [`CAP_FPS_REQUEST`] are not asked.
+#[derive(Clone)]
+pub struct SessionPace(Arc<watch::Sender<Option<u32>>>);
+
+impl SessionPace {
+    /// Cap the sender at `fps`, or lift the cap with `None`.
+    pub fn request(&self, fps: Option<u32>) {
+        self.0.send_if_modified(|current| std::mem::replace(current, fps) != fps);
+    }
+
+    /// The frame-rate cap currently requested.
+    pub fn requested(&self) -> Option<u32> {
+        *self.0.borrow()
+    }
+}
+
+// ── Session power ──────────────────────────────────────────────────────────────
+
+/// Tells the sender on one display about the receiver's power source
+/// (`power`), so it can drop to battery saver when the receiver wants it.
+///
+/// Like [`SessionBlank`], the state outlives sessions and is sent to
+/// senders as they connect. Senders without [`CAP_POWER`] are not told.
+#[derive(Clone)]
+pub struct SessionPower(Arc<watch::Sender<Option<PowerState>>>);
+
+impl SessionPower {
+    /// Publish the receiver's power state (`None` = no battery).
+    pub fn publish(&self, power: Option<PowerState>) {
+        self.0.send_if_modified(|current| std::mem::replace(current, power) != power);
+    }
+
+    /// The state last published.
+    pub fn current(&self) -> Option<PowerState> {
+        *self.0.borrow()
+    }
+}
+
+// ── Session preview ────────────────────────────────────────────────────────────
+
+/// Sends thumbnails of one display to its sender (`preview`).
+///
+/// Only senders with [`CAP_PREVIEW`] subscribe; while none is connected
+/// [`is_wanted`](Self::is_wanted) is false and the app skips the snapshot.
+#[derive(Clone)]
+pub struct SessionPreview(Arc<watch::Sender<Option<Vec<u8>>>>);
+
+impl SessionPreview {
+    /// Whether a connected sender asked for previews.
+    pub fn is_wanted(&self) -> bool {
+        self.0.receiver_count() > 0
+    }
+
+    /// Send a JPEG of the display to the sender.
+    pub fn publish(&self, jpeg: Vec<u8>) {
+        self.0.send_replace(Some(jpeg));
     }
 }
 
 // ── Signaling wire types ───────────────────────────────────────────────────────
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 enum MessageType {
     Hello,
     HelloAck,
+    /// Sender → receiver: new stream config. Receiver → sender: frame-rate
+    /// request (only `targetFps` is read, see [`SessionPace`]).
     ConfigUpdate,
     Keepalive,
+    /// Receiver → sender: reply to `keepalive`, echoing its timestamp.
+    KeepaliveAck,
+    /// Receiver → sender: force a keyframe (picture loss indication).
+    KeyframeRequest,
     Stop,
     InputEvent,
+    /// Receiver → sender: the panel behind this display changed (hot-plug).
+    DisplayInfo,
+    /// Receiver → sender: displays were added or removed at runtime.
+    DisplaysChanged,
+    /// Either way: blank the receiver's display / pause the sender's capture.
+    Blank,
+    /// Receiver → sender: thumbnail of what this display shows.
+    Preview,
+    /// Either way: the peer's power source, see [`SessionPower`].
+    Power,
+    /// Receiver → sender: a message was refused, see [`framing`].
+    Error,
+    /// Either way: pause or resume this display, see [`SessionPause`].
+    DisplayState,
+    /// Either way: offer a file, see [`duallink_core::file_transfer`].
+    FileOffer,
+    /// Either way: accept or decline an offered file, or cancel a transfer.
+    FileAccept,
+    /// Either way: part of an accepted file.
+    FileChunk,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -351,6 +875,68 @@ struct SignalingMessage {
     pairing_pin: Option<String>,
     #[serde(rename = "displayIndex", skip_serializing_if = "Option::is_none")]
     display_index: Option<u8>,
+    /// Receiver capability tokens (e.g. `"h264_444"`), sent in `hello_ack`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    capabilities: Option<Vec<String>>,
+    /// Receiver panel geometry for this display, sent in `hello_ack`.
+    #[serde(rename = "displayInfo", skip_serializing_if = "Option::is_none")]
+    display_info: Option<MonitorInfo>,
+    /// Display indices the receiver currently serves, sent in `displays_changed`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    displays: Option<Vec<u8>>,
+    /// This display's frame counters, sent in `keepalive_ack`.
+    #[serde(rename = "frameCounters", skip_serializing_if = "Option::is_none")]
+    frame_counters: Option<FrameCounters>,
+    /// Port pairs of every display the receiver serves, sent in `hello_ack`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ports: Option<PortMap>,
+    /// Bitrate ceiling for this display, sent in `hello_ack`.
+    #[serde(rename = "maxBitrateKbps", skip_serializing_if = "Option::is_none")]
+    max_bitrate_kbps: Option<u32>,
+    /// Resolution ceiling for this display, sent in `hello_ack`.
+    #[serde(rename = "maxResolution", skip_serializing_if = "Option::is_none")]
+    max_resolution: Option<Resolution>,
+    /// Frame-rate ceiling for this display, sent in `hello_ack`.
+    #[serde(rename = "maxFps", skip_serializing_if = "Option::is_none")]
+    max_fps: Option<u32>,
+    /// Whether the receiver forwards input: the sender's wish in `hello`
+    /// (absent = yes), the outcome in `hello_ack`.
+    #[serde(rename = "allowInput", skip_serializing_if = "Option::is_none")]
+    allow_input: Option<bool>,
+    /// Whether blanking starts or ends, sent in `blank`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enabled: Option<bool>,
+    /// Base64 JPEG thumbnail, sent in `preview`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    /// Power source and saver wish, sent in `power`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    power: Option<PowerState>,
+    /// Why a message was refused, sent in `error` with a `reason`.
+    #[serde(rename = "errorCode", skip_serializing_if = "Option::is_none")]
+    error_code: Option<SignalingErrorCode>,
+    /// Whether the display is paused, sent in `display_state`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    paused: Option<bool>,
+    /// One-time token to resume the session with: issued in `hello_ack`,
+    /// redeemed in `hello`.
+    #[serde(rename = "resumeToken", skip_serializing_if = "Option::is_none")]
+    resume_token: Option<String>,
+    /// The transfer a `file_offer`, `file_accept` or `file_chunk` is about.
+    #[serde(rename = "transferId", skip_serializing_if = "Option::is_none")]
+    transfer_id: Option<String>,
+    /// Name of the offered file, sent in `file_offer`.
+    #[serde(rename = "fileName", skip_serializing_if = "Option::is_none")]
+    file_name: Option<String>,
+    /// Size of the offered file in bytes, sent in `file_offer`.
+    #[serde(rename = "fileSize", skip_serializing_if = "Option::is_none")]
+    file_size: Option<u64>,
+    /// Where `data` goes in the file, sent in `file_chunk`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u64>,
+    /// Base64 file bytes, sent in `file_chunk`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
 }
 
 impl SignalingMessage {
@@ -366,6 +952,51 @@ impl SignalingMessage {
             input_event: None,
             pairing_pin: None,
             display_index: None,
+            capabilities: None,
+            display_info: None,
+            displays: None,
+            frame_counters: None,
+            ports: None,
+            max_bitrate_kbps: None,
+            max_resolution: None,
+            max_fps: None,
+            allow_input: None,
+            enabled: None,
+            image: None,
+            power: None,
+            error_code: None,
+            paused: None,
+            resume_token: None,
+            transfer_id: None,
+            file_name: None,
+            file_size: None,
+            offset: None,
+            data: None,
+        }
+    }
+
+    /// Accepting `hello_ack` carrying the negotiated config, our capabilities,
+    /// the geometry of the panel this display is shown on, our port map,
+    /// this display's stream limits and whether input is forwarded.
+    fn hello_ack_negotiated(
+        session_id: String,
+        config: StreamConfig,
+        capabilities: Vec<String>,
+        display_info: Option<MonitorInfo>,
+        ports: PortMap,
+        limits: StreamLimits,
+        allow_input: bool,
+    ) -> Self {
+        Self {
+            config: Some(config),
+            capabilities: Some(capabilities),
+            display_info,
+            ports: Some(ports),
+            max_bitrate_kbps: limits.max_bitrate_kbps,
+            max_resolution: limits.max_resolution,
+            max_fps: limits.max_fps,
+            allow_input: Some(allow_input),
+            ..Self::hello_ack(session_id, true, None)
         }
     }
 
@@ -381,8 +1012,177 @@ impl SignalingMessage {
             input_event: Some(event),
             pairing_pin: None,
             display_index: None,
+            capabilities: None,
+            display_info: None,
+            displays: None,
+            frame_counters: None,
+            ports: None,
+            max_bitrate_kbps: None,
+            max_resolution: None,
+            max_fps: None,
+            allow_input: None,
+            enabled: None,
+            image: None,
+            power: None,
+            error_code: None,
+            paused: None,
+            resume_token: None,
+            transfer_id: None,
+            file_name: None,
+            file_size: None,
+            offset: None,
+            data: None,
+        }
+    }
+
+    fn display_info(info: Option<MonitorInfo>) -> Self {
+        Self {
+            msg_type: MessageType::DisplayInfo,
+            session_id: None,
+            device_name: None,
+            config: None,
+            accepted: None,
+            reason: None,
+            timestamp_ms: None,
+            input_event: None,
+            pairing_pin: None,
+            display_index: None,
+            capabilities: None,
+            display_info: info,
+            displays: None,
+            frame_counters: None,
+            ports: None,
+            max_bitrate_kbps: None,
+            max_resolution: None,
+            max_fps: None,
+            allow_input: None,
+            enabled: None,
+            image: None,
+            power: None,
+            error_code: None,
+            paused: None,
+            resume_token: None,
+            transfer_id: None,
+            file_name: None,
+            file_size: None,
+            offset: None,
+            data: None,
+        }
+    }
+
+    fn displays_changed(displays: Vec<u8>) -> Self {
+        Self {
+            msg_type: MessageType::DisplaysChanged,
+            displays: Some(displays),
+            ..Self::display_info(None)
+        }
+    }
+
+    fn error(code: SignalingErrorCode, reason: String) -> Self {
+        Self {
+            msg_type: MessageType::Error,
+            reason: Some(reason),
+            error_code: Some(code),
+            ..Self::display_info(None)
+        }
+    }
+
+    fn stop(reason: &str) -> Self {
+        Self {
+            msg_type: MessageType::Stop,
+            reason: Some(reason.into()),
+            ..Self::display_info(None)
+        }
+    }
+
+    fn keepalive_ack(timestamp_ms: Option<u64>, counters: FrameCounters) -> Self {
+        Self {
+            msg_type: MessageType::KeepaliveAck,
+            timestamp_ms,
+            frame_counters: Some(counters),
+            ..Self::display_info(None)
+        }
+    }
+
+    fn keyframe_request() -> Self {
+        Self { msg_type: MessageType::KeyframeRequest, ..Self::display_info(None) }
+    }
+
+    fn blank(enabled: bool) -> Self {
+        Self { msg_type: MessageType::Blank, enabled: Some(enabled), ..Self::display_info(None) }
+    }
+
+    fn display_state(display_index: u8, paused: bool) -> Self {
+        Self {
+            msg_type: MessageType::DisplayState,
+            display_index: Some(display_index),
+            paused: Some(paused),
+            ..Self::display_info(None)
+        }
+    }
+
+    fn fps_request(config: StreamConfig) -> Self {
+        Self { msg_type: MessageType::ConfigUpdate, config: Some(config), ..Self::display_info(None) }
+    }
+
+    fn power(power: PowerState) -> Self {
+        Self { msg_type: MessageType::Power, power: Some(power), ..Self::display_info(None) }
+    }
+
+    fn preview(jpeg: &[u8]) -> Self {
+        use base64::Engine as _;
+        let image = base64::engine::general_purpose::STANDARD.encode(jpeg);
+        Self { msg_type: MessageType::Preview, image: Some(image), ..Self::display_info(None) }
+    }
+
+    fn file(message: FileMessage) -> Self {
+        use base64::Engine as _;
+        match message {
+            FileMessage::Offer(offer) => Self {
+                msg_type: MessageType::FileOffer,
+                transfer_id: Some(offer.transfer_id),
+                file_name: Some(offer.name),
+                file_size: Some(offer.size),
+                ..Self::display_info(None)
+            },
+            FileMessage::Answer { transfer_id, accepted, reason } => Self {
+                msg_type: MessageType::FileAccept,
+                transfer_id: Some(transfer_id),
+                accepted: Some(accepted),
+                reason,
+                ..Self::display_info(None)
+            },
+            FileMessage::Chunk { transfer_id, offset, data } => Self {
+                msg_type: MessageType::FileChunk,
+                transfer_id: Some(transfer_id),
+                offset: Some(offset),
+                data: Some(base64::engine::general_purpose::STANDARD.encode(data)),
+                ..Self::display_info(None)
+            },
         }
     }
+
+    /// The file-transfer message this is, if it is a complete one.
+    fn into_file(self) -> Option<FileMessage> {
+        use base64::Engine as _;
+        let transfer_id = self.transfer_id?;
+        Some(match self.msg_type {
+            MessageType::FileOffer => FileMessage::Offer(duallink_core::FileOffer {
+                transfer_id,
+                name: self.file_name?,
+                size: self.file_size?,
+            }),
+            MessageType::FileAccept => {
+                FileMessage::Answer { transfer_id, accepted: self.accepted?, reason: self.reason }
+            }
+            MessageType::FileChunk => FileMessage::Chunk {
+                transfer_id,
+                offset: self.offset?,
+                data: base64::engine::general_purpose::STANDARD.decode(self.data?).ok()?,
+            },
+            _ => return None,
+        })
+    }
 }
 
 // ── Public startup info ───────────────────────────────────────────────────────
@@ -402,19 +1202,136 @@ pub struct StartupInfo {
 /// Events emitted by the SignalingServer to the rest of the app.
 #[derive(Debug)]
 pub enum SignalingEvent {
+    /// A sender's signaling connection passed the TLS handshake; its
+    /// `hello` (if any) follows. Lets the receiver get a decoder ready
+    /// while the handshake finishes.
+    SenderConnected { client_addr: SocketAddr },
     SessionStarted {
         session_id: String,
         device_name: String,
         config: StreamConfig,
         client_addr: SocketAddr,
+        /// `false` for a view-only session: input sent through the
+        /// [`InputSender`] is not forwarded to this sender.
+        allow_input: bool,
     },
     ConfigUpdated { config: StreamConfig },
-    SessionStopped { session_id: String },
+    /// The sender ended the session; `summary` is the data it used.
+    SessionStopped { session_id: String, summary: SessionSummary },
     ClientDisconnected,
+    /// The receiver's monitors changed (hot-plug). `monitor` is the panel now
+    /// reported for this display; `monitors` is the full list, primary first.
+    /// Senders that advertised [`CAP_DISPLAY_INFO`] are sent the new panel.
+    ReceiverDisplayChanged {
+        monitor: Option<MonitorInfo>,
+        monitors: Vec<MonitorInfo>,
+    },
+    /// `missing` frames were lost before the latest one; decoding resumes at
+    /// the next keyframe. `stats` are the display's running totals.
+    FrameGap { missing: u32, stats: SequenceStats },
+    /// The sender streamed `measured_kbps` over the last second against a
+    /// ceiling of `limit_kbps`; `dropped` frames were discarded and decoding
+    /// resumes at the next keyframe that fits.
+    BitrateExceeded { limit_kbps: u64, measured_kbps: u64, dropped: u32 },
+    /// The sender asked to blank (`enabled`) or show this display again;
+    /// the session and the stream carry on.
+    Blank { enabled: bool },
+    /// The sender's power source changed (sent by senders with
+    /// [`CAP_POWER`]).
+    SenderPower { power: PowerState },
+    /// The sender paused (`paused`) or resumed this display's stream; the
+    /// new state is already in the display's [`SessionPause`].
+    DisplayState { paused: bool },
+    /// No frame arrived for `silent` although the display is neither paused
+    /// nor blanked; a keyframe was requested. Sent once per stall — frames
+    /// arriving again end it.
+    StreamStalled { silent: Duration },
+    /// A file transfer with the sender moved on (sent by senders with
+    /// [`CAP_FILE_TRANSFER`]); offers are answered through
+    /// [`DisplayChannels::files`].
+    File(FileTransferEvent),
 }
 
 // ── Multi-display channel bundle ───────────────────────────────────────────────
 
+/// Per-display settings for [`DualLinkReceiver::start_with_configs`].
+///
+/// Lets displays differ — e.g. a 4K panel on display 0 and a 1080p one on
+/// display 1, each with its own decoder.
+#[derive(Debug, Clone)]
+pub struct DisplayConfig {
+    pub display_index:   u8,
+    /// UDP video port (default [`video_port`]`(display_index)`). `0` binds
+    /// any free port; [`DisplayChannels::config`] holds the one bound.
+    pub video_port:      u16,
+    /// TCP signaling port (default [`signaling_port`]`(display_index)`), `0`
+    /// for any free port. Senders find it in the advertised [`PortMap`].
+    pub signaling_port:  u16,
+    /// Resolution the sender should stream at. Replaces the detected panel
+    /// mode in the `display_info` sent with `hello_ack`.
+    pub resolution_hint: Option<Resolution>,
+    /// Preferred decoder element (e.g. `"avdec_h264"`); `None` = probe.
+    /// Not used by the transport — carried through to the decoder setup.
+    pub decoder:         Option<String>,
+    /// Disabled displays bind no ports and get no [`DisplayChannels`].
+    pub enabled:         bool,
+    /// Memory limits for incomplete frames on this display's UDP stream.
+    pub reassembly:      ReassemblyBudget,
+    /// Datagrams drained per `recvmmsg` call on Linux (default
+    /// [`DEFAULT_RECV_BATCH`]); `1` receives one datagram per syscall.
+    pub recv_batch:      usize,
+    /// Bitrate / resolution ceilings for senders (default: none).
+    pub limits:          StreamLimits,
+    /// Reject a `hello` over [`limits`](Self::limits) instead of clamping it.
+    pub reject_over_limits: bool,
+}
+
+impl DisplayConfig {
+    /// Defaults for `display_index`: standard ports, no hints, enabled.
+    pub fn new(display_index: u8) -> Self {
+        Self::with_base_port(display_index, VIDEO_PORT)
+    }
+
+    /// Defaults for `display_index` with ports laid out from `base` (display
+    /// 0's video port): UDP `base + 2n`, TCP `base + 2n + 1`.
+    pub fn with_base_port(display_index: u8, base: u16) -> Self {
+        let ports = DisplayPorts::from_base(base, display_index);
+        Self {
+            display_index,
+            video_port: ports.video,
+            signaling_port: ports.signaling,
+            resolution_hint: None,
+            decoder: None,
+            enabled: true,
+            reassembly: ReassemblyBudget::default(),
+            recv_batch: DEFAULT_RECV_BATCH,
+            limits: StreamLimits::default(),
+            reject_over_limits: false,
+        }
+    }
+
+    /// Panel reported to the sender: the detected monitor for this display
+    /// with [`resolution_hint`](Self::resolution_hint) applied.
+    fn reported_monitor(&self, monitors: &[MonitorInfo]) -> Option<MonitorInfo> {
+        let mut monitor = monitor_for(monitors, self.display_index);
+        if let Some(res) = self.resolution_hint {
+            let m = monitor.get_or_insert_with(|| MonitorInfo {
+                name: format!("display-{}", self.display_index),
+                resolution: res,
+                refresh_hz: 0.0,
+                width_mm: 0,
+                height_mm: 0,
+                scale: 1,
+                x: 0,
+                y: 0,
+                primary: self.display_index == 0,
+            });
+            m.resolution = res;
+        }
+        monitor
+    }
+}
+
 /// Frame and signaling channels for one display stream.
 /// Returned by [`DualLinkReceiver::start_all`].
 pub struct DisplayChannels {
@@ -424,53 +1341,234 @@ pub struct DisplayChannels {
     pub event_rx: mpsc::Receiver<SignalingEvent>,
     /// Zero-based display index (matches DLNK header byte [17]).
     pub display_index: u8,
+    /// Settings this display was started with.
+    pub config: DisplayConfig,
+    /// Keyframe gate in front of `frame_rx`; re-arm it on decoder restart.
+    pub keyframes: KeyframeGate,
+    /// Ends this display's current session.
+    pub kick: SessionKick,
+    /// Pauses the sender's capture on this display.
+    pub blank: SessionBlank,
+    /// Sends thumbnails of this display to its sender.
+    pub preview: SessionPreview,
+    /// Asks this display's sender for a lower frame rate.
+    pub pace: SessionPace,
+    /// Tells this display's sender the receiver's power state.
+    pub power: SessionPower,
+    /// Pauses and resumes this display's stream.
+    pub pause: SessionPause,
+    /// Files to and from this display's sender.
+    pub files: FileTransfers,
+}
+
+/// Already-bound sockets for one display, adopted instead of binding the
+/// ports in its [`DisplayConfig`] — see
+/// [`DualLinkReceiver::start_with_sockets`].
+#[derive(Debug)]
+pub struct DisplaySockets {
+    pub display_index: u8,
+    /// UDP video socket.
+    pub video:         std::net::UdpSocket,
+    /// TCP signaling listener.
+    pub signaling:     std::net::TcpListener,
 }
 
 // ── DualLinkReceiver ───────────────────────────────────────────────────────────
 
-/// Manages UDP video reception + TCP signaling in background tasks.
-///
-/// # Example
-/// ```rust,no_run
-/// # tokio_test::block_on(async {
-/// let (_recv, mut frame_rx, mut event_rx) = duallink_transport::DualLinkReceiver::start().await.unwrap();
-/// while let Some(frame) = frame_rx.recv().await {
-///     println!("frame {} bytes keyframe={}", frame.data.len(), frame.is_keyframe);
-/// }
-/// # })
-/// ```
-/// Sender handle for pushing input events to the connected Mac client.
+/// Queued input events per session.
+const INPUT_QUEUE: usize = 256;
+
+/// Input channels of the sessions taking input, by display, so each sender
+/// only gets the input of its own display.
+#[derive(Default)]
+struct InputRoutes {
+    routes: std::sync::Mutex<std::collections::BTreeMap<u8, mpsc::Sender<InputEvent>>>,
+}
+
+impl InputRoutes {
+    /// Route `display`'s input to a new channel, replacing the previous
+    /// session's route.
+    fn open(&self, display: u8) -> (mpsc::Sender<InputEvent>, mpsc::Receiver<InputEvent>) {
+        let (tx, rx) = mpsc::channel(INPUT_QUEUE);
+        self.routes.lock().unwrap().insert(display, tx.clone());
+        (tx, rx)
+    }
+
+    /// Remove `display`'s route if it still is `tx`.
+    fn close(&self, display: u8, tx: &mpsc::Sender<InputEvent>) {
+        let mut routes = self.routes.lock().unwrap();
+        if routes.get(&display).is_some_and(|route| route.same_channel(tx)) {
+            routes.remove(&display);
+        }
+    }
+
+    /// `display`'s route, or with `None` the lowest display's.
+    fn get(&self, display: Option<u8>) -> Option<mpsc::Sender<InputEvent>> {
+        let routes = self.routes.lock().unwrap();
+        match display {
+            Some(display) => routes.get(&display).cloned(),
+            None => routes.values().next().cloned(),
+        }
+    }
+}
+
+/// Sender handle for pushing input events to the connected senders.
 ///
 /// Uses the same TCP signaling connection (Linux → Mac direction).
 /// Clone-able and Send — pass to the decode thread.
+///
+/// The handle returned at startup sends to the session on the lowest
+/// display that takes input; [`for_display`](Self::for_display) narrows it
+/// to one display, whose sender may differ from the other displays'.
+///
+/// Clones share one input macro recorder: events sent through any of them
+/// while recording go into the same [`InputRecording`] (see
+/// [`duallink_core::input_macro`]).
 #[derive(Clone)]
 pub struct InputSender {
-    tx: mpsc::Sender<InputEvent>,
+    routes:   Arc<InputRoutes>,
+    /// `None` = the lowest display with a session taking input.
+    display:  Option<u8>,
+    recorder: Arc<std::sync::Mutex<Option<InputRecorder>>>,
 }
 
 impl InputSender {
-    /// Send an input event to the Mac client.
-    /// Non-blocking — returns Err only if the channel is full/closed.
+    fn new(routes: Arc<InputRoutes>) -> Self {
+        Self { routes, display: None, recorder: Arc::default() }
+    }
+
+    /// A handle sending to display `display_index`'s session only, sharing
+    /// this one's recorder.
+    pub fn for_display(&self, display_index: u8) -> Self {
+        Self { display: Some(display_index), ..self.clone() }
+    }
+
+    /// The display this handle sends to, `None` = the lowest taking input.
+    pub fn display(&self) -> Option<u8> {
+        self.display
+    }
+
+    /// Send an input event to the sender.
+    /// Non-blocking — returns Err only if no session takes the input or its
+    /// queue is closed.
     pub async fn send(&self, event: InputEvent) -> Result<(), mpsc::error::SendError<InputEvent>> {
-        self.tx.send(event).await
+        self.record(&event);
+        match self.routes.get(self.display) {
+            Some(tx) => tx.send(event).await,
+            None => Err(mpsc::error::SendError(event)),
+        }
     }
 
-    /// Try send without awaiting (for use in blocking contexts).
+    /// Try send without awaiting (for use in blocking contexts). With no
+    /// session taking the input, fails with `Closed`.
     pub fn try_send(&self, event: InputEvent) -> Result<(), mpsc::error::TrySendError<InputEvent>> {
-        self.tx.try_send(event)
+        self.record(&event);
+        match self.routes.get(self.display) {
+            Some(tx) => tx.try_send(event),
+            None => Err(mpsc::error::TrySendError::Closed(event)),
+        }
+    }
+
+    fn record(&self, event: &InputEvent) {
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            recorder.record(event);
+        }
+    }
+
+    /// Start recording the events sent from now on, discarding any
+    /// recording in progress.
+    pub fn start_recording(&self) {
+        *self.recorder.lock().unwrap() = Some(InputRecorder::new());
+    }
+
+    /// Stop recording; `None` if no recording was in progress.
+    pub fn stop_recording(&self) -> Option<InputRecording> {
+        self.recorder.lock().unwrap().take().map(InputRecorder::finish)
+    }
+
+    /// Events recorded so far, or `None` while not recording.
+    pub fn recorded(&self) -> Option<usize> {
+        self.recorder.lock().unwrap().as_ref().map(InputRecorder::len)
+    }
+
+    /// Send `recording` to the Mac client again, keeping the recorded gaps
+    /// between events; with `repeat`, start over [`REPLAY_REPEAT_PAUSE`]
+    /// after the last event until stopped. Replayed events are not
+    /// recorded, and are dropped while no session takes input.
+    pub fn replay(&self, recording: InputRecording, repeat: bool) -> ReplayHandle {
+        let (routes, display) = (Arc::clone(&self.routes), self.display);
+        let task = tokio::spawn(async move {
+            if recording.events.is_empty() {
+                return;
+            }
+            loop {
+                let start = tokio::time::Instant::now();
+                for timed in &recording.events {
+                    tokio::time::sleep_until(start + Duration::from_millis(timed.at_ms)).await;
+                    if let Some(tx) = routes.get(display) {
+                        let _ = tx.try_send(timed.event.clone());
+                    }
+                }
+                if !repeat {
+                    break;
+                }
+                tokio::time::sleep(REPLAY_REPEAT_PAUSE).await;
+            }
+            debug!("Input replay finished ({} events)", recording.events.len());
+        });
+        ReplayHandle { task }
+    }
+}
+
+/// Pause between repetitions of a repeating [`InputSender::replay`].
+pub const REPLAY_REPEAT_PAUSE: Duration = Duration::from_secs(1);
+
+/// A running [`InputSender::replay`].
+pub struct ReplayHandle {
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl ReplayHandle {
+    /// Stop replaying; events not sent yet are dropped.
+    pub fn stop(&self) {
+        self.task.abort();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
     }
 }
 
+/// Manages UDP video reception + TCP signaling in background tasks.
+///
+/// # Example
+/// ```rust,no_run
+/// # async fn run() -> anyhow::Result<()> {
+/// let (_recv, mut frame_rx, _event_rx, _input, _startup) = duallink_transport::DualLinkReceiver::start().await?;
+/// while let Some(frame) = frame_rx.recv().await {
+///     println!("frame {} bytes keyframe={}", frame.data.len(), frame.is_keyframe);
+/// }
+/// # Ok(())
+/// # }
+/// ```
 pub struct DualLinkReceiver {
     pub frames_received: Arc<std::sync::atomic::AtomicU64>,
+    /// `None` for the single-display [`start`](Self::start) receiver.
+    runtime: Option<Arc<ReceiverRuntime>>,
+    /// State, input policy and queries shared with [`handle`](Self::handle).
+    handle: ReceiverHandle,
 }
 
 impl DualLinkReceiver {
     /// Bind UDP:7878 + TLS/TCP:7879 and start background Tokio tasks.
+    ///
+    /// Advertises no optional capabilities — see
+    /// [`start_all_with_capabilities`](Self::start_all_with_capabilities).
     /// Returns an `InputSender` in addition to the frame/event channels.
     ///
-    /// Generates an ephemeral self-signed TLS certificate and a 6-digit
-    /// pairing PIN.  Both are printed to the console for the user.
+    /// Uses the [`configured_tls_identity`] — by default an ephemeral
+    /// self-signed certificate — and generates a 6-digit pairing PIN.  The
+    /// fingerprint and PIN are printed to the console for the user.
     pub async fn start() -> anyhow::Result<(
         Self,
         mpsc::Receiver<EncodedFrame>,
@@ -478,13 +1576,14 @@ impl DualLinkReceiver {
         InputSender,
         StartupInfo,
     )> {
-        let (frame_tx, frame_rx) = mpsc::channel::<EncodedFrame>(64);
-        let (event_tx, event_rx) = mpsc::channel::<SignalingEvent>(16);
-        let (input_tx, input_rx) = mpsc::channel::<InputEvent>(256);
+        let queues = ReceiverQueues::configured();
+        let (frame_tx, frame_rx) = mpsc::channel::<EncodedFrame>(queues.frames);
+        let (event_tx, event_rx) = mpsc::channel::<SignalingEvent>(queues.events);
+        let input = Arc::new(InputRoutes::default());
         let counter = Arc::new(std::sync::atomic::AtomicU64::new(0));
 
         // ── Generate TLS identity ──────────────────────────────────────────
-        let identity = generate_tls_identity()?;
+        let identity = configured_tls_identity()?;
         info!("TLS certificate fingerprint: {}", identity.fingerprint);
 
         let pairing_pin = generate_pairing_pin();
@@ -494,48 +1593,90 @@ impl DualLinkReceiver {
 
         let acceptor = identity.acceptor;
         let startup_fingerprint = identity.fingerprint.clone();
-        let pin = pairing_pin;
-        let startup_pin = pin.clone();
-        let shared_input = Arc::new(tokio::sync::Mutex::new(input_rx));
+        let startup_pin = pairing_pin.clone();
+        let allow_input = Arc::new(std::sync::atomic::AtomicBool::new(configured_allow_input()));
+        let mut state = ReceiverState::new(pairing_pin, identity.fingerprint, configured_allow_input());
+        state.sync_displays(&PortMap::contiguous(VIDEO_PORT, 1));
+        let state = Arc::new(watch::channel(state).0);
 
         // UDP receiver task
         let udp = UdpSocket::bind(format!("0.0.0.0:{VIDEO_PORT}")).await?;
         info!("UDP video receiver bound on 0.0.0.0:{VIDEO_PORT}");
         let counter_clone = Arc::clone(&counter);
-        tokio::spawn(async move { run_udp_receiver(udp, frame_tx, counter_clone).await });
+        let link = Arc::new(LinkStats::default());
+        let link_clone = Arc::clone(&link);
+        let keyframes = KeyframeGate::new();
+        let gate = keyframes.clone();
+        let gap_tx = event_tx.clone();
+        let udp = DatagramReceiver::new(udp, DEFAULT_RECV_BATCH);
+        let policy = UdpPolicy { reassembly: ReassemblyBudget::default(), bitrate: None, fps: None, display: 0 };
+        tokio::spawn(async move {
+            run_udp_receiver(udp, frame_tx, gap_tx, counter_clone, link_clone, gate, policy).await
+        });
 
         // TLS signaling task
         let tcp = TcpListener::bind(format!("0.0.0.0:{SIGNALING_PORT}")).await?;
         info!("TLS signaling listener bound on 0.0.0.0:{SIGNALING_PORT}");
+        let monitors = tokio::task::spawn_blocking(detect_monitors).await.unwrap_or_default();
+        // Neither monitors nor displays change in single-display mode.
+        let ctx = DisplayContext {
+            display_index: 0,
+            capabilities: Arc::new(Vec::new()),
+            monitor: watch::channel(monitor_for(&monitors, 0)).1,
+            displays: watch::channel(vec![0]).1,
+            ports: watch::channel(PortMap::contiguous(VIDEO_PORT, 1)).1,
+            limits: StreamLimits::default(),
+            reject_over_limits: false,
+            allow_input: Arc::clone(&allow_input),
+            link,
+            kick: Arc::new(tokio::sync::Notify::new()),
+            keyframes,
+            blank: watch::channel(false).1,
+            preview: Arc::new(watch::channel(None).0),
+            pace: watch::channel(None).1,
+            power: watch::channel(None).1,
+            pause: Arc::new(watch::channel(PauseState::default()).0),
+            files: FileTransfers::configured(),
+            state: Arc::clone(&state),
+            resume: Arc::new(Resumption::load(configured_resume_ttl())),
+            input: Arc::clone(&input),
+            stall_timeout: configured_stall_timeout(),
+        };
         tokio::spawn(async move {
-            run_signaling_server_shared(tcp, event_tx, shared_input, acceptor, pin).await
+            run_signaling_server_shared(tcp, event_tx, acceptor, ctx).await
         });
 
+        let handle = ReceiverHandle { frames_received: Arc::clone(&counter), runtime: None, allow_input, state };
         Ok((
-            Self { frames_received: counter },
+            Self { frames_received: counter, runtime: None, handle },
             frame_rx,
             event_rx,
-            InputSender { tx: input_tx },
+            InputSender::new(input),
             StartupInfo { pairing_pin: startup_pin, tls_fingerprint: startup_fingerprint },
         ))
     }
 
     /// Bind N display port pairs and start independent background tasks for each.
     ///
-    /// All displays share a single TLS identity, pairing PIN, and `InputSender`.
-    /// Per-display data comes back through the returned `Vec<DisplayChannels>`.
+    /// All displays share a single TLS identity and, unless
+    /// [`configured_separate_pins`], pairing PIN. Each display may be used
+    /// by a different sender; [`InputSender::for_display`] routes input to
+    /// one display's sender. Per-display data comes back through the
+    /// returned `Vec<DisplayChannels>`.
     ///
-    /// Port mapping: display `n` uses UDP `7878 + 2n` / TCP `7879 + 2n`.
+    /// Port mapping: display `n` uses UDP `base + 2n` / TCP `base + 2n + 1`,
+    /// `base` being [`configured_base_port`] (7878 by default).
     ///
     /// # Example
     /// ```rust,no_run
-    /// # tokio_test::block_on(async {
-    /// let (_recv, channels, input_tx, _info) =
-    ///     duallink_transport::DualLinkReceiver::start_all(2).await.unwrap();
+    /// # async fn run() -> anyhow::Result<()> {
+    /// let (_recv, channels, _input_tx, _info) =
+    ///     duallink_transport::DualLinkReceiver::start_all(2).await?;
     /// for ch in channels {
     ///     println!("Display {} ready", ch.display_index);
     /// }
-    /// # })
+    /// # Ok(())
+    /// # }
     /// ```
     pub async fn start_all(display_count: u8) -> anyhow::Result<(
         Self,
@@ -543,10 +1684,66 @@ impl DualLinkReceiver {
         InputSender,
         StartupInfo,
     )> {
-        let n_displays = display_count.max(1).min(8);
+        Self::start_all_with_capabilities(display_count, Vec::new()).await
+    }
+
+    /// Like [`start_all`](Self::start_all), but advertises `capabilities`
+    /// (e.g. [`duallink_core::CAP_H264_444`]) in every `hello_ack`.
+    ///
+    /// Each sender's requested [`StreamConfig`] is reconciled with these via
+    /// [`StreamConfig::negotiate`] before `SessionStarted` is emitted, and the
+    /// negotiated config is echoed back to the sender.
+    ///
+    /// The receiver's monitors are enumerated at startup and re-polled every
+    /// [`MONITOR_POLL_INTERVAL`]; display `n` reports monitor `n` in its
+    /// `hello_ack` (or the primary monitor when there are fewer monitors than
+    /// displays). Changes are surfaced as
+    /// [`SignalingEvent::ReceiverDisplayChanged`].
+    pub async fn start_all_with_capabilities(display_count: u8, capabilities: Vec<String>) -> anyhow::Result<(
+        Self,
+        Vec<DisplayChannels>,
+        InputSender,
+        StartupInfo,
+    )> {
+        let base = configured_base_port();
+        let configs = (0..display_count.clamp(1, 8)).map(|n| DisplayConfig::with_base_port(n, base)).collect();
+        Self::start_with_configs(configs, capabilities).await
+    }
+
+    /// Like [`start_all_with_capabilities`](Self::start_all_with_capabilities),
+    /// but each display has its own [`DisplayConfig`] (ports, resolution hint,
+    /// decoder preference, enabled state).
+    ///
+    /// Returns channels for the enabled displays only, in `configs` order.
+    pub async fn start_with_configs(configs: Vec<DisplayConfig>, capabilities: Vec<String>) -> anyhow::Result<(
+        Self,
+        Vec<DisplayChannels>,
+        InputSender,
+        StartupInfo,
+    )> {
+        Self::start_with_sockets(configs, capabilities, Vec::new()).await
+    }
+
+    /// Like [`start_with_configs`](Self::start_with_configs), but displays
+    /// with an entry in `sockets` adopt those already-bound sockets (e.g.
+    /// handed over by a receiver that was holding the ports) instead of
+    /// binding their configured ports. Their configured ports are replaced
+    /// by the adopted sockets' ports.
+    pub async fn start_with_sockets(
+        configs: Vec<DisplayConfig>,
+        capabilities: Vec<String>,
+        mut sockets: Vec<DisplaySockets>,
+    ) -> anyhow::Result<(Self, Vec<DisplayChannels>, InputSender, StartupInfo)> {
+        let monitors = tokio::task::spawn_blocking(detect_monitors).await.unwrap_or_default();
+        let configs: Vec<DisplayConfig> = configs.into_iter().take(MAX_DISPLAYS).collect();
+        for cfg in configs.iter().filter(|c| !c.enabled) {
+            info!("Display[{}] disabled — not binding ports", cfg.display_index);
+        }
+        let n_displays = configs.iter().filter(|c| c.enabled).count();
+        anyhow::ensure!(n_displays > 0, "no enabled displays");
 
         // ── Shared TLS identity + pairing PIN ─────────────────────────────
-        let identity = generate_tls_identity()?;
+        let identity = configured_tls_identity()?;
         info!("TLS certificate fingerprint: {}", identity.fingerprint);
 
         let pairing_pin = generate_pairing_pin();
@@ -555,72 +1752,644 @@ impl DualLinkReceiver {
         info!("╚══════════════════════════════════════╝");
         info!("  Displays: {}", n_displays);
 
-        let (input_tx, input_rx) = mpsc::channel::<InputEvent>(256);
-        // Shared across all N signaling servers — only display-0 responds actively
-        let shared_input = Arc::new(tokio::sync::Mutex::new(input_rx));
+        let input = Arc::new(InputRoutes::default());
         let counter = Arc::new(std::sync::atomic::AtomicU64::new(0));
 
         let startup_pin = pairing_pin.clone();
         let startup_fingerprint = identity.fingerprint.clone();
+        let allow_input = Arc::new(std::sync::atomic::AtomicBool::new(configured_allow_input()));
+        let state = ReceiverState::new(pairing_pin, identity.fingerprint, configured_allow_input())
+            .with_separate_pins(configured_separate_pins());
+        let state = Arc::new(watch::channel(state).0);
+
+        let runtime = Arc::new(ReceiverRuntime {
+            acceptor: identity.acceptor,
+            state: Arc::clone(&state),
+            input: Arc::clone(&input),
+            counter: Arc::clone(&counter),
+            capabilities: Arc::new(capabilities),
+            monitors: std::sync::Mutex::new(monitors),
+            displays: std::sync::Mutex::new(std::collections::BTreeMap::new()),
+            displays_tx: watch::channel(Vec::new()).0,
+            ports_tx: watch::channel(PortMap::default()).0,
+            allow_input: Arc::clone(&allow_input),
+            resume: Arc::new(Resumption::load(configured_resume_ttl())),
+            stall_timeout: configured_stall_timeout(),
+            queues: ReceiverQueues::configured(),
+        });
 
-        let mut channels = Vec::with_capacity(n_displays as usize);
+        let mut channels = Vec::with_capacity(n_displays);
+        for cfg in configs.into_iter().filter(|c| c.enabled) {
+            let adopted = sockets
+                .iter()
+                .position(|s| s.display_index == cfg.display_index)
+                .map(|i| sockets.swap_remove(i));
+            channels.push(runtime.bind(cfg, adopted).await?);
+        }
+        for unused in &sockets {
+            warn!("Display[{}] not enabled — dropping its adopted sockets", unused.display_index);
+        }
 
-        for n in 0..n_displays {
-            let (frame_tx, frame_rx) = mpsc::channel::<EncodedFrame>(64);
-            let (event_tx, event_rx) = mpsc::channel::<SignalingEvent>(16);
+        tokio::spawn(run_monitor_watcher(Arc::clone(&runtime)));
 
-            let vp = video_port(n);
-            let sp = signaling_port(n);
+        let handle = ReceiverHandle {
+            frames_received: Arc::clone(&counter),
+            runtime: Some(Arc::clone(&runtime)),
+            allow_input,
+            state,
+        };
+        Ok((
+            Self { frames_received: counter, runtime: Some(runtime), handle },
+            channels,
+            InputSender::new(input),
+            StartupInfo { pairing_pin: startup_pin, tls_fingerprint: startup_fingerprint },
+        ))
+    }
 
-            let udp = UdpSocket::bind(format!("0.0.0.0:{vp}")).await?;
-            info!("Display[{n}] UDP receiver bound on 0.0.0.0:{vp}");
-            let counter_clone = Arc::clone(&counter);
-            tokio::spawn(async move { run_udp_receiver(udp, frame_tx, counter_clone).await });
+    // ── Runtime display management ─────────────────────────────────────────
+
+    /// Bind the lowest free display index with default settings while the
+    /// receiver is running, on ports continuing the current layout. See
+    /// [`add_display_with`](Self::add_display_with).
+    pub async fn add_display(&self) -> anyhow::Result<DisplayChannels> {
+        let runtime = self.runtime()?;
+        let index = (0..MAX_DISPLAYS as u8)
+            .find(|n| !runtime.displays.lock().unwrap().contains_key(n))
+            .ok_or_else(|| anyhow::anyhow!("all {MAX_DISPLAYS} displays in use"))?;
+        let ports = runtime.ports_tx.borrow().ports(index);
+        let config = DisplayConfig { video_port: ports.video, signaling_port: ports.signaling, ..DisplayConfig::new(index) };
+        self.add_display_with(config).await
+    }
 
-            let tcp = TcpListener::bind(format!("0.0.0.0:{sp}")).await?;
-            info!("Display[{n}] TLS signaling bound on 0.0.0.0:{sp}");
-            let acceptor = identity.acceptor.clone();
-            let pin = pairing_pin.clone();
-            let irx = Arc::clone(&shared_input);
-            tokio::spawn(async move {
-                run_signaling_server_shared(tcp, event_tx, irx, acceptor, pin).await
-            });
+    /// Bind one more display port pair at runtime.
+    ///
+    /// Connected senders that advertised [`CAP_DISPLAYS_CHANGED`] receive a
+    /// `displays_changed` message listing the new set of display indices.
+    /// Re-advertise the displays over mDNS with
+    /// `DualLinkAdvertiser::set_ports(&receiver.port_map())`.
+    pub async fn add_display_with(&self, config: DisplayConfig) -> anyhow::Result<DisplayChannels> {
+        let runtime = self.runtime()?;
+        anyhow::ensure!(
+            !runtime.displays.lock().unwrap().contains_key(&config.display_index),
+            "display {} already running",
+            config.display_index
+        );
+        runtime.bind(config, None).await
+    }
 
-            channels.push(DisplayChannels { frame_rx, event_rx, display_index: n });
+    /// Stop display `display_index` and release its ports.
+    ///
+    /// Its [`DisplayChannels`] close once the connected sender (if any) ends
+    /// the session — senders stop the matching pipeline on `displays_changed`.
+    /// Returns `false` if the display was not running.
+    pub fn remove_display(&self, display_index: u8) -> bool {
+        let Ok(runtime) = self.runtime() else { return false };
+        let removed = runtime.displays.lock().unwrap().remove(&display_index);
+        let Some(running) = removed else { return false };
+        for task in &running.tasks {
+            task.abort();
         }
+        info!(
+            "Display[{display_index}] removed — ports {}/{} released",
+            running.config.video_port, running.config.signaling_port
+        );
+        runtime.publish_displays();
+        true
+    }
 
-        Ok((
-            Self { frames_received: counter },
-            channels,
-            InputSender { tx: input_tx },
-            StartupInfo { pairing_pin: startup_pin, tls_fingerprint: startup_fingerprint },
-        ))
+    /// Stop every display and return its sockets, still bound, for another
+    /// receiver to adopt with [`start_with_sockets`](Self::start_with_sockets).
+    ///
+    /// Connected senders are sent `stop` and every [`DisplayChannels`]
+    /// closes; senders reconnect to whichever receiver adopts the sockets.
+    pub fn release_sockets(&self) -> anyhow::Result<Vec<DisplaySockets>> {
+        let runtime = self.runtime()?;
+        let released = std::mem::take(&mut *runtime.displays.lock().unwrap());
+        let sockets = released
+            .into_iter()
+            .map(|(n, running)| {
+                running.kick.notify_waiters();
+                for task in &running.tasks {
+                    task.abort();
+                }
+                info!(
+                    "Display[{n}] released ports {}/{}",
+                    running.config.video_port, running.config.signaling_port
+                );
+                let (video, signaling) = running.sockets;
+                DisplaySockets { display_index: n, video, signaling }
+            })
+            .collect();
+        runtime.publish_displays();
+        Ok(sockets)
+    }
+
+    /// End the session on display `display_index`, keeping its ports bound.
+    ///
+    /// The connected sender is sent `stop` and the display's event channel
+    /// reports [`SignalingEvent::ClientDisconnected`]; the sender may
+    /// reconnect afterwards. Returns `false` if the display is not running.
+    pub fn disconnect(&self, display_index: u8) -> bool {
+        self.handle.disconnect_client(display_index)
+    }
+
+    /// Lost / late / duplicate frame totals for display `display_index`
+    /// since it was bound, or `None` if it is not running.
+    pub fn frame_stats(&self, display_index: u8) -> Option<SequenceStats> {
+        self.handle.stats(display_index).map(|s| s.frames)
+    }
+
+    /// Fragment-level totals for display `display_index` — budget and
+    /// timeout evictions, duplicate and inconsistent fragments — or `None`
+    /// if it is not running.
+    pub fn reassembly_stats(&self, display_index: u8) -> Option<ReassemblyStats> {
+        self.handle.stats(display_index).map(|s| s.reassembly)
+    }
+
+    /// How much longer display `display_index`'s latest frame took to
+    /// arrive than its fastest one since the sender's clock was anchored —
+    /// queueing on the network and in the sender — or `None` if it is not
+    /// running.
+    pub fn queueing_delay(&self, display_index: u8) -> Option<Duration> {
+        self.handle.stats(display_index).map(|s| s.queueing_delay)
+    }
+
+    /// Let new sessions send input back to their sender (the default), or
+    /// make them view-only. Negotiated in `hello_ack`, so a session keeps
+    /// the policy it started with; initialised from
+    /// [`configured_allow_input`].
+    pub fn set_allow_input(&self, allow: bool) {
+        self.handle.set_allow_input(allow);
+    }
+
+    /// Input policy for new sessions.
+    pub fn allow_input(&self) -> bool {
+        self.handle.allow_input()
+    }
+
+    /// Ports of the displays currently bound — advertise these over mDNS
+    /// (`DualLinkAdvertiser::set_ports`) so senders need not guess them.
+    pub fn port_map(&self) -> PortMap {
+        self.handle.port_map()
+    }
+
+    /// Indices of the displays currently bound.
+    pub fn display_indices(&self) -> Vec<u8> {
+        self.handle.display_indices()
+    }
+
+    /// Number of displays currently bound.
+    pub fn display_count(&self) -> u8 {
+        self.display_indices().len() as u8
+    }
+
+    fn runtime(&self) -> anyhow::Result<&Arc<ReceiverRuntime>> {
+        self.runtime
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("displays can only be added to receivers started with start_all"))
+    }
+}
+
+// ── Receiver runtime ───────────────────────────────────────────────────────────
+
+/// Upper bound on concurrently bound displays.
+const MAX_DISPLAYS: usize = 8;
+
+/// State shared by all displays of a multi-display receiver; lets displays
+/// be bound and released after startup.
+struct ReceiverRuntime {
+    acceptor:     TlsAcceptor,
+    /// Pairing PIN and per-display phases, see [`ReceiverHandle`].
+    state:        Arc<watch::Sender<ReceiverState>>,
+    /// Input routes of every display's session, see [`InputSender`].
+    input:        Arc<InputRoutes>,
+    counter:      Arc<std::sync::atomic::AtomicU64>,
+    capabilities: Arc<Vec<String>>,
+    monitors:     std::sync::Mutex<Vec<MonitorInfo>>,
+    displays:     std::sync::Mutex<std::collections::BTreeMap<u8, RunningDisplay>>,
+    /// Current display indices, pushed to senders as `displays_changed`.
+    displays_tx:  watch::Sender<Vec<u8>>,
+    /// Current port pairs, sent to senders in `hello_ack`.
+    ports_tx:     watch::Sender<PortMap>,
+    allow_input:  Arc<std::sync::atomic::AtomicBool>,
+    resume:       Arc<Resumption>,
+    stall_timeout: Duration,
+    /// Frame and event channel depths of each display.
+    queues:       ReceiverQueues,
+}
+
+/// One bound display port pair.
+struct RunningDisplay {
+    config:     DisplayConfig,
+    monitor_tx: watch::Sender<Option<MonitorInfo>>,
+    event_tx:   mpsc::Sender<SignalingEvent>,
+    /// Wakes the display's signaling connection to end its session.
+    kick:       Arc<tokio::sync::Notify>,
+    link:       Arc<LinkStats>,
+    /// UDP receiver and signaling listener; aborted on removal.
+    tasks:      [tokio::task::JoinHandle<()>; 2],
+    /// Duplicates of the tasks' sockets, for [`DualLinkReceiver::release_sockets`].
+    sockets:    (std::net::UdpSocket, std::net::TcpListener),
+}
+
+impl ReceiverRuntime {
+    /// Bind `cfg`'s ports (or adopt `adopted`) and start its UDP + signaling
+    /// tasks.
+    async fn bind(&self, mut cfg: DisplayConfig, adopted: Option<DisplaySockets>) -> anyhow::Result<DisplayChannels> {
+        let n = cfg.display_index;
+        let (frame_tx, frame_rx) = mpsc::channel::<EncodedFrame>(self.queues.frames);
+        let (event_tx, event_rx) = mpsc::channel::<SignalingEvent>(self.queues.events);
+
+        let adopted_sockets = adopted.is_some();
+        let (udp, tcp) = match adopted {
+            Some(DisplaySockets { video, signaling, .. }) => (video, signaling),
+            None => (
+                std::net::UdpSocket::bind(format!("0.0.0.0:{}", cfg.video_port))?,
+                std::net::TcpListener::bind(format!("0.0.0.0:{}", cfg.signaling_port))?,
+            ),
+        };
+        // Record the ports actually bound (adopted sockets, port 0).
+        cfg.video_port = udp.local_addr()?.port();
+        cfg.signaling_port = tcp.local_addr()?.port();
+        let origin = if adopted_sockets { "adopted from another receiver" } else { "bound" };
+        info!("Display[{n}] UDP receiver {origin} on 0.0.0.0:{}", cfg.video_port);
+        info!("Display[{n}] TLS signaling {origin} on 0.0.0.0:{}", cfg.signaling_port);
+        udp.set_nonblocking(true)?;
+        tcp.set_nonblocking(true)?;
+        let sockets = (udp.try_clone()?, tcp.try_clone()?);
+        let udp = UdpSocket::from_std(udp)?;
+        let tcp = TcpListener::from_std(tcp)?;
+        if let Some(res) = cfg.resolution_hint {
+            info!("Display[{n}] resolution hint: {res}");
+        }
+
+        let counter_clone = Arc::clone(&self.counter);
+        let link = Arc::new(LinkStats::default());
+        let link_clone = Arc::clone(&link);
+        let keyframes = KeyframeGate::new();
+        let gate = keyframes.clone();
+        let gap_tx = event_tx.clone();
+        let udp = DatagramReceiver::new(udp, cfg.recv_batch);
+        let policy = UdpPolicy {
+            reassembly: cfg.reassembly,
+            bitrate:    cfg.limits.max_bitrate_bps().map(BitrateGuard::new),
+            fps:        cfg.limits.max_fps.map(FrameRateCap::new),
+            display:    n,
+        };
+        if !cfg.limits.is_unlimited() {
+            info!("Display[{n}] stream limits: {:?}", cfg.limits);
+        }
+        let udp_task = tokio::spawn(async move {
+            run_udp_receiver(udp, frame_tx, gap_tx, counter_clone, link_clone, gate, policy).await
+        });
+
+        let (monitor_tx, monitor) = watch::channel(cfg.reported_monitor(&self.monitors.lock().unwrap()));
+        let kick = Arc::new(tokio::sync::Notify::new());
+        let (blank_tx, blank) = watch::channel(false);
+        let preview = Arc::new(watch::channel(None).0);
+        let (pace_tx, pace) = watch::channel(None);
+        let (power_tx, power) = watch::channel(None);
+        let pause = Arc::new(watch::channel(PauseState::default()).0);
+        let files = FileTransfers::configured();
+        let ctx = DisplayContext {
+            display_index: n,
+            capabilities: Arc::clone(&self.capabilities),
+            monitor,
+            displays: self.displays_tx.subscribe(),
+            ports: self.ports_tx.subscribe(),
+            limits: cfg.limits,
+            reject_over_limits: cfg.reject_over_limits,
+            allow_input: Arc::clone(&self.allow_input),
+            link: Arc::clone(&link),
+            kick: Arc::clone(&kick),
+            keyframes: keyframes.clone(),
+            blank,
+            preview: Arc::clone(&preview),
+            pace,
+            power,
+            pause: Arc::clone(&pause),
+            files: files.clone(),
+            state: Arc::clone(&self.state),
+            resume: Arc::clone(&self.resume),
+            input: Arc::clone(&self.input),
+            stall_timeout: self.stall_timeout,
+        };
+        let acceptor = self.acceptor.clone();
+        let sig_event_tx = event_tx.clone();
+        let sig_task = tokio::spawn(async move {
+            run_signaling_server_shared(tcp, sig_event_tx, acceptor, ctx).await
+        });
+
+        self.displays.lock().unwrap().insert(n, RunningDisplay {
+            config: cfg.clone(),
+            monitor_tx,
+            event_tx,
+            kick: Arc::clone(&kick),
+            link,
+            tasks: [udp_task, sig_task],
+            sockets,
+        });
+        self.publish_displays();
+        if let Some(pin) = self.state.borrow().display(n).and_then(|d| d.pairing_pin.clone()) {
+            info!("Display[{n}] pairing PIN: {pin}");
+        }
+
+        Ok(DisplayChannels {
+            frame_rx,
+            event_rx,
+            display_index: n,
+            config: cfg,
+            keyframes,
+            kick: SessionKick(kick),
+            blank: SessionBlank(Arc::new(blank_tx)),
+            preview: SessionPreview(preview),
+            pace: SessionPace(Arc::new(pace_tx)),
+            power: SessionPower(Arc::new(power_tx)),
+            pause: SessionPause(pause),
+            files,
+        })
+    }
+
+    fn indices(&self) -> Vec<u8> {
+        self.displays.lock().unwrap().keys().copied().collect()
+    }
+
+    fn publish_displays(&self) {
+        let ports = PortMap::new(self.displays.lock().unwrap().values().map(|d| DisplayPorts {
+            display_index: d.config.display_index,
+            video:         d.config.video_port,
+            signaling:     d.config.signaling_port,
+        }));
+        self.state.send_if_modified(|s| s.sync_displays(&ports));
+        self.ports_tx.send_if_modified(|current| {
+            let changed = *current != ports;
+            *current = ports;
+            changed
+        });
+        let indices = self.indices();
+        self.displays_tx.send_if_modified(|current| {
+            let changed = *current != indices;
+            *current = indices;
+            changed
+        });
+    }
+}
+
+// ── Monitor hot-plug ───────────────────────────────────────────────────────────
+
+/// Monitor reported for display `n`: monitor `n`, else the primary one.
+fn monitor_for(monitors: &[MonitorInfo], n: u8) -> Option<MonitorInfo> {
+    monitors.get(n as usize).or(monitors.first()).cloned()
+}
+
+/// Poll the receiver's monitors and publish changes to every display.
+///
+/// Neither `wayland-info` nor `xrandr` can block on output events without a
+/// client connection of our own, so this re-enumerates on a timer instead.
+async fn run_monitor_watcher(runtime: Arc<ReceiverRuntime>) {
+    let mut tick = tokio::time::interval(MONITOR_POLL_INTERVAL);
+    tick.tick().await;
+    loop {
+        tick.tick().await;
+        let Ok(current) = tokio::task::spawn_blocking(detect_monitors).await else { continue };
+        let monitors = {
+            let mut known = runtime.monitors.lock().unwrap();
+            if *known == current {
+                continue;
+            }
+            info!("Receiver monitors changed: {} → {} connected", known.len(), current.len());
+            *known = current.clone();
+            current
+        };
+
+        for (n, display) in runtime.displays.lock().unwrap().iter() {
+            let monitor = display.config.reported_monitor(&monitors);
+            display.monitor_tx.send_replace(monitor.clone());
+            let event = SignalingEvent::ReceiverDisplayChanged { monitor, monitors: monitors.clone() };
+            if display.event_tx.try_send(event).is_err() {
+                debug!("Display[{n}] event queue full — hot-plug event dropped");
+            }
+        }
     }
 }
 
 // ── UDP task ───────────────────────────────────────────────────────────────────
 
+/// Per-display sequence and reassembly accounting, published by the UDP
+/// task for [`DualLinkReceiver::frame_stats`],
+/// [`DualLinkReceiver::reassembly_stats`] and `keepalive_ack`.
+#[derive(Default)]
+struct LinkStats {
+    stats:      std::sync::Mutex<SequenceStats>,
+    reassembly: std::sync::Mutex<ReassemblyStats>,
+    /// [`ClockMapper::queueing_delay`] of the latest frame, in µs.
+    queueing:   std::sync::atomic::AtomicU64,
+    /// Bumped for every accepted `hello`. Senders number each session's
+    /// frames from 0, so the UDP task starts afresh when it changes.
+    session:    std::sync::atomic::AtomicU64,
+    /// Bytes of the current session, both directions.
+    usage:      UsageMeter,
+    /// The current session streams H.265 (NAL headers differ).
+    hevc:       std::sync::atomic::AtomicBool,
+    /// The current session negotiated slice-level decoding.
+    slices:     std::sync::atomic::AtomicBool,
+    /// When the latest frame was handed on, for the stall watchdog.
+    last_frame: std::sync::Mutex<Option<std::time::Instant>>,
+}
+
+impl LinkStats {
+    fn snapshot(&self) -> FrameCounters {
+        self.stats.lock().unwrap().counters()
+    }
+}
+
+/// Per-display limits applied by the UDP task.
+struct UdpPolicy {
+    reassembly: ReassemblyBudget,
+    /// Bitrate ceiling from [`DisplayConfig::limits`], if any.
+    bitrate:    Option<BitrateGuard>,
+    /// Frame-rate ceiling from [`DisplayConfig::limits`], if any.
+    fps:        Option<FrameRateCap>,
+    /// Display the frames belong to, for the frame trace.
+    display:    u8,
+}
+
+/// Give a keyframe for a fresh decoder the parameter sets it lacks.
+/// `false` if it lacks some that were never seen — the decoder would
+/// reject it.
+fn repair_keyframe(parameter_sets: &ParameterSets, frame: &mut EncodedFrame) -> bool {
+    match parameter_sets.repair(&mut frame.data) {
+        Repair::Complete => true,
+        Repair::Injected(n) => {
+            info!("Keyframe without parameter sets — injected {n} cached one(s)");
+            true
+        }
+        Repair::Missing => {
+            warn!("Keyframe without parameter sets and none cached — requesting another");
+            false
+        }
+    }
+}
+
 async fn run_udp_receiver(
-    socket: UdpSocket,
+    mut socket: DatagramReceiver,
     frame_tx: mpsc::Sender<EncodedFrame>,
+    event_tx: mpsc::Sender<SignalingEvent>,
     counter: Arc<std::sync::atomic::AtomicU64>,
+    link: Arc<LinkStats>,
+    keyframes: KeyframeGate,
+    policy: UdpPolicy,
 ) {
-    let mut buf = vec![0u8; UDP_BUF_SIZE];
-    let mut reassembler = FrameReassembler::default();
+    let UdpPolicy { reassembly, bitrate: mut guard, fps: mut fps_cap, display } = policy;
+    let mut datagrams = Vec::new();
+    let mut reassembler = FrameReassembler::new(reassembly);
+    let mut published = ReassemblyStats::default();
+    let mut sequence = SequenceTracker::default();
+    let mut unwrapper = PtsUnwrapper::default();
+    let mut clock = ClockMapper::default();
+    let mut parameter_sets = ParameterSets::new(VideoCodec::H264);
+    let mut session = 0;
+    // Slice-level decoding: the last frame handed on, and the frame after
+    // it whose leading NAL units went ahead, with their timestamp.
+    let mut slices = false;
+    let mut last_seq: Option<u32> = None;
+    let mut early: Option<(u32, u64)> = None;
 
     loop {
-        let (len, addr) = match socket.recv_from(&mut buf).await {
-            Ok(v) => v,
-            Err(e) => { warn!("UDP recv error: {}", e); continue; }
-        };
-
-        let Some(packet) = parse_packet(&buf[..len]) else {
-            debug!("Dropped malformed packet from {}", addr);
+        if let Err(e) = socket.recv(&mut datagrams).await {
+            warn!("UDP recv error: {}", e);
             continue;
-        };
+        }
+        link.usage.add_received(datagrams.iter().map(|(d, _)| d.len()).sum());
+        let current = link.session.load(std::sync::atomic::Ordering::Acquire);
+        if current != session {
+            session = current;
+            reassembler.reset();
+            sequence.reset();
+            unwrapper = PtsUnwrapper::default();
+            let hevc = link.hevc.load(std::sync::atomic::Ordering::Acquire);
+            parameter_sets = ParameterSets::new(if hevc { VideoCodec::H265 } else { VideoCodec::H264 });
+            slices = link.slices.load(std::sync::atomic::Ordering::Acquire);
+            (last_seq, early) = (None, None);
+        }
+
+        for (datagram, addr) in datagrams.drain(..) {
+            let packet = match parse_datagram(datagram) {
+                Ok(p) => p,
+                Err(e) => {
+                    debug!("Dropped malformed packet from {}: {}", addr, e);
+                    continue;
+                }
+            };
+
+            let (packet_seq, packet_timestamp) = (packet.frame_seq, packet.timestamp);
+            let (packet_keyframe, packet_layer) = (packet.is_keyframe, packet.temporal_layer);
+            let completed = reassembler.push(packet);
+            if reassembler.stats() != published {
+                published = reassembler.stats();
+                *link.reassembly.lock().unwrap() = published;
+            }
 
-        if let Some(frame) = reassembler.push(packet) {
+            let Some(AssembledFrame { seq, timestamp, mut frame, received, released }) = completed else {
+                // Leading slices of the next frame go ahead only when nothing
+                // below may drop it: no limits, no keyframe awaited.
+                let next = last_seq.is_some_and(|s| packet_seq == s.wrapping_add(1));
+                if !slices || !next || packet_keyframe || guard.is_some() || fps_cap.is_some() || keyframes.is_armed() {
+                    continue;
+                }
+                let Timestamp::V2 { pts_us, clock_epoch } = packet_timestamp else { continue };
+                let Some(data) = reassembler.take_slices(packet_seq) else { continue };
+                let timestamp_us = match early {
+                    Some((s, pts)) if s == packet_seq => pts,
+                    _ => clock.map(Some(clock_epoch), pts_us, std::time::Instant::now()),
+                };
+                early = Some((packet_seq, timestamp_us));
+                let slice = EncodedFrame {
+                    data,
+                    timestamp_us,
+                    is_keyframe: false,
+                    codec: VideoCodec::H264,
+                    temporal_layer: packet_layer,
+                    partial: true,
+                };
+                if frame_tx.send(slice).await.is_err() {
+                    info!("frame_tx closed — stopping UDP receiver");
+                    return;
+                }
+                continue;
+            };
+            let early_pts = early.take().filter(|(s, _)| *s == seq).map(|(_, pts)| pts);
+            let event = sequence.observe(seq);
+            let stats = sequence.stats();
+            *link.stats.lock().unwrap() = stats;
+            match event {
+                SequenceEvent::Gap { missing } => {
+                    debug!("Frame gap: {} frame(s) missing before seq={}", missing, seq);
+                    keyframes.arm();
+                    // Informational — never block the UDP path on it.
+                    let _ = event_tx.try_send(SignalingEvent::FrameGap { missing, stats });
+                }
+                SequenceEvent::Late | SequenceEvent::Duplicate => {
+                    debug!("Discarding {:?} frame seq={}", event, seq);
+                    continue;
+                }
+                SequenceEvent::InOrder | SequenceEvent::Restart => {}
+            }
+            last_seq = Some(seq);
+            if let Some(guard) = guard.as_mut() {
+                let now = std::time::Instant::now();
+                let admitted = guard.admit(frame.data.len(), now);
+                if let Some((bps, dropped)) = guard.overrun(now) {
+                    let (limit_kbps, measured_kbps) = (guard.limit_bps() / 1000, bps / 1000);
+                    warn!("Sender at {measured_kbps} kbps exceeds the {limit_kbps} kbps ceiling — dropped {dropped} frame(s)");
+                    let _ = event_tx.try_send(SignalingEvent::BitrateExceeded { limit_kbps, measured_kbps, dropped });
+                }
+                if !admitted {
+                    // Later delta frames reference the dropped one.
+                    keyframes.arm();
+                    continue;
+                }
+            }
+            if let Some(cap) = fps_cap.as_mut() {
+                if !cap.admit(frame.temporal_layer, std::time::Instant::now()) {
+                    continue;
+                }
+            }
             counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            *link.last_frame.lock().unwrap() = Some(std::time::Instant::now());
+
+            let (epoch, pts_us) = match timestamp {
+                Timestamp::V1 { pts_ms } => (None, unwrapper.unwrap(pts_ms)),
+                Timestamp::V2 { pts_us, clock_epoch } => (Some(clock_epoch), pts_us),
+            };
+            frame.timestamp_us = match early_pts {
+                // Same timestamp as the slices that went ahead.
+                Some(pts) => pts,
+                None => clock.map(epoch, pts_us, std::time::Instant::now()),
+            };
+            let delay = clock.queueing_delay().as_micros() as u64;
+            link.queueing.store(delay, std::sync::atomic::Ordering::Relaxed);
+
+            let fresh_decoder = keyframes.is_armed();
+            if !keyframes.admit(&frame) {
+                continue;
+            }
+            if frame.is_keyframe {
+                parameter_sets.observe(&frame.data);
+                if fresh_decoder && !repair_keyframe(&parameter_sets, &mut frame) {
+                    keyframes.rearm();
+                    continue;
+                }
+            }
+            if trace::is_recording() {
+                let (pts, handed) = (frame.timestamp_us, std::time::Instant::now());
+                trace::span(TraceStage::Receive, display, pts, received.0, received.1);
+                trace::span(TraceStage::Reassemble, display, pts, received.1, handed);
+                trace::begin(TraceStage::Queue, display, pts, handed);
+            }
+            if released > 0 {
+                frame.data = frame.data.slice(released..);
+            }
             if frame_tx.send(frame).await.is_err() {
                 info!("frame_tx closed — stopping UDP receiver");
                 return;
@@ -631,15 +2400,50 @@ async fn run_udp_receiver(
 
 // ── TCP signaling task ─────────────────────────────────────────────────────────
 
+/// Per-display state every signaling connection reports to its sender.
+#[derive(Clone)]
+struct DisplayContext {
+    display_index: u8,
+    capabilities: Arc<Vec<String>>,
+    monitor:      watch::Receiver<Option<MonitorInfo>>,
+    displays:     watch::Receiver<Vec<u8>>,
+    ports:        watch::Receiver<PortMap>,
+    limits:       StreamLimits,
+    reject_over_limits: bool,
+    /// Receiver-side input policy, read at each `hello`.
+    allow_input:  Arc<std::sync::atomic::AtomicBool>,
+    link:         Arc<LinkStats>,
+    /// Notified by [`DualLinkReceiver::disconnect`].
+    kick:         Arc<tokio::sync::Notify>,
+    keyframes:    KeyframeGate,
+    /// Receiver's capture-pause request, see [`SessionBlank`].
+    blank:        watch::Receiver<bool>,
+    /// Thumbnails for senders with [`CAP_PREVIEW`], see [`SessionPreview`].
+    preview:      Arc<watch::Sender<Option<Vec<u8>>>>,
+    /// Receiver's frame-rate request, see [`SessionPace`].
+    pace:         watch::Receiver<Option<u32>>,
+    /// Receiver's power state, see [`SessionPower`].
+    power:        watch::Receiver<Option<PowerState>>,
+    /// This display's pause state, see [`SessionPause`].
+    pause:        Arc<watch::Sender<PauseState>>,
+    /// This display's file transfers, see [`DisplayChannels::files`].
+    files:        FileTransfers,
+    /// Receiver state: the PIN checked at `hello` and this display's phase.
+    state:        Arc<watch::Sender<ReceiverState>>,
+    /// Resume tokens checked at `hello` and issued in `hello_ack`.
+    resume:       Arc<Resumption>,
+    /// Where the display's session receives its input from.
+    input:        Arc<InputRoutes>,
+    /// See [`configured_stall_timeout`]; `ZERO` = no watchdog.
+    stall_timeout: Duration,
+}
+
 async fn run_signaling_server_shared(
     listener: TcpListener,
     event_tx: mpsc::Sender<SignalingEvent>,
-    input_rx: Arc<tokio::sync::Mutex<mpsc::Receiver<InputEvent>>>,
     acceptor: TlsAcceptor,
-    pairing_pin: String,
+    ctx: DisplayContext,
 ) {
-    // We only support one client at a time — the input_rx is shared across displays.
-    let input_rx = input_rx;
     loop {
         match listener.accept().await {
             Ok((stream, addr)) => {
@@ -649,10 +2453,9 @@ async fn run_signaling_server_shared(
                     Ok(tls_stream) => {
                         info!("TLS handshake OK with {}", addr);
                         let tx = event_tx.clone();
-                        let irx = Arc::clone(&input_rx);
-                        let pin = pairing_pin.clone();
+                        let ctx = ctx.clone();
                         tokio::spawn(async move {
-                            handle_signaling_conn(tls_stream, addr, tx, irx, pin).await
+                            handle_signaling_conn(tls_stream, addr, tx, ctx).await
                         });
                     }
                     Err(e) => {
@@ -669,47 +2472,121 @@ async fn handle_signaling_conn(
     stream: tokio_rustls::server::TlsStream<tokio::net::TcpStream>,
     addr: SocketAddr,
     event_tx: mpsc::Sender<SignalingEvent>,
-    input_rx: Arc<tokio::sync::Mutex<mpsc::Receiver<InputEvent>>>,
-    expected_pin: String,
+    ctx: DisplayContext,
 ) {
+    let DisplayContext {
+        display_index, capabilities, monitor, displays, ports, limits, reject_over_limits, allow_input: input_policy, link, kick, keyframes,
+        blank, preview, pace, power, pause, files, state, resume, input, stall_timeout,
+    } = ctx;
+    // Only set when the certificate chains to `DUALLINK_CLIENT_CA`.
+    let trusted_cert = stream.get_ref().1.peer_certificates().is_some_and(|certs| !certs.is_empty());
+    // Informational — never hold the connection up on it.
+    let _ = event_tx.try_send(SignalingEvent::SenderConnected { client_addr: addr });
     let (reader, writer) = tokio::io::split(stream);
-    let writer = Arc::new(tokio::sync::Mutex::new(writer));
+    let writer = Arc::new(tokio::sync::Mutex::new(MeteredWriter { inner: writer, usage: link.usage.clone() }));
 
     // ── Reader: process incoming signaling messages ────────────────────────
     let writer_for_reader = Arc::clone(&writer);
     let mut reader = reader;
     let mut body_buf = Vec::new();
+    let mut rate = MessageRate::new(std::time::Instant::now());
     let mut session_active = false;
+    let mut ack_keepalives = false;
+    // The sender follows `config_update`s lowering its frame rate.
+    let mut fps_requests = false;
+    // Id and device name of the running session, for its usage summary.
+    let mut session: Option<(String, String)> = None;
+    // This connection's input route, removed when it ends.
+    let mut input_route: Option<mpsc::Sender<InputEvent>> = None;
+    // Stall watchdog of this connection's sessions, stopped when it ends.
+    let mut watchdog: Option<tokio::task::JoinHandle<()>> = None;
+    // Sends this connection's file transfers, stopped when it ends.
+    let mut file_pump: Option<tokio::task::JoinHandle<()>> = None;
 
     loop {
-        let mut len_bytes = [0u8; 4];
-        if reader.read_exact(&mut len_bytes).await.is_err() {
-            let _ = event_tx.send(SignalingEvent::ClientDisconnected).await;
-            break;
+        let read = tokio::select! {
+            r = read_frame(&mut reader, &mut body_buf) => r,
+            _ = kick.notified() => {
+                info!("Disconnecting {} on request", addr);
+                let mut w = writer_for_reader.lock().await;
+                let _ = send_msg_split(&mut *w, &SignalingMessage::stop("Disconnected by receiver")).await;
+                let _ = w.shutdown().await;
+                drop(w);
+                let _ = event_tx.send(SignalingEvent::ClientDisconnected).await;
+                break;
+            }
+        };
+        match read {
+            Ok(()) => {}
+            Err(FrameError::TooLarge(len)) => {
+                warn!("{} sent a {} byte signaling message — disconnecting", addr, len);
+                let reason = format!("Message of {len} bytes exceeds {MAX_SIGNALING_MESSAGE}");
+                let mut w = writer_for_reader.lock().await;
+                let _ = send_msg_split(&mut *w, &SignalingMessage::error(SignalingErrorCode::TooLarge, reason)).await;
+                let _ = w.shutdown().await;
+                drop(w);
+                let _ = event_tx.send(SignalingEvent::ClientDisconnected).await;
+                break;
+            }
+            Err(FrameError::Io(e)) => {
+                debug!("Signaling connection from {} closed: {}", addr, e);
+                let _ = event_tx.send(SignalingEvent::ClientDisconnected).await;
+                break;
+            }
         }
-        let msg_len = u32::from_be_bytes(len_bytes) as usize;
-
-        body_buf.resize(msg_len, 0);
-        if reader.read_exact(&mut body_buf).await.is_err() {
-            let _ = event_tx.send(SignalingEvent::ClientDisconnected).await;
-            break;
+        link.usage.add_received(4 + body_buf.len());
+
+        if !rate.admit(std::time::Instant::now()) {
+            if rate.dropped() == 1 {
+                warn!("{} exceeds {} signaling messages/s — dropping", addr, framing::MESSAGE_RATE);
+                let reply = SignalingMessage::error(SignalingErrorCode::RateLimited, "Too many messages".into());
+                let mut w = writer_for_reader.lock().await;
+                if send_msg_split(&mut *w, &reply).await.is_err() { break; }
+            }
+            continue;
+        }
+        let dropped = rate.take_dropped();
+        if dropped > 0 {
+            warn!("Dropped {} signaling messages from {} over the rate limit", dropped, addr);
         }
 
-        let msg: SignalingMessage = match serde_json::from_slice(&body_buf) {
+        let msg: SignalingMessage = match parse_message(&body_buf) {
             Ok(m) => m,
-            Err(e) => { warn!("Bad signaling JSON from {}: {}", addr, e); continue; }
+            Err((code, reason)) => {
+                warn!("Refusing signaling message from {}: {}", addr, reason);
+                let mut w = writer_for_reader.lock().await;
+                if send_msg_split(&mut *w, &SignalingMessage::error(code, reason)).await.is_err() { break; }
+                continue;
+            }
         };
 
         match msg.msg_type {
             MessageType::Hello => {
-                let session_id  = msg.session_id.unwrap_or_default();
+                let resumed = msg.resume_token.as_deref().and_then(|t| resume.redeem(t, display_index));
+                let session_id = match (msg.session_id, &resumed) {
+                    (Some(id), _) if !id.is_empty() => id,
+                    (_, Some(entry)) => entry.session_id.clone(),
+                    _ => String::new(),
+                };
                 let device_name = msg.device_name.unwrap_or_else(|| addr.to_string());
                 let config      = msg.config.unwrap_or_default();
+                let sender_caps = msg.capabilities.unwrap_or_default();
+                // Input needs both the receiver's policy and the sender's consent.
+                let allow_input = input_policy.load(std::sync::atomic::Ordering::Relaxed)
+                    && msg.allow_input.unwrap_or(true);
                 info!("Hello from '{}' session={}", device_name, session_id);
+                ack_keepalives = sender_caps.iter().any(|c| c == CAP_KEEPALIVE_ACK);
+                fps_requests = sender_caps.iter().any(|c| c == CAP_FPS_REQUEST);
 
-                // ── Validate pairing PIN ──────────────────────────────────
+                // ── Validate pairing PIN (unless resumed or the client cert is trusted) ──
                 let client_pin = msg.pairing_pin.unwrap_or_default();
-                if client_pin != expected_pin {
+                let expected_pin = state.borrow().pin_for(display_index).to_owned();
+                if let Some(entry) = &resumed {
+                    info!("Resuming session {} of '{}' from {} — pairing PIN not needed",
+                          entry.session_id, entry.device_name, addr);
+                } else if trusted_cert {
+                    info!("Trusted client certificate from {} — pairing PIN not needed", addr);
+                } else if client_pin != expected_pin {
                     warn!("Pairing PIN mismatch from {} — rejecting (got '{}', expected '{}')",
                           addr, client_pin, expected_pin);
                     let ack = SignalingMessage::hello_ack(
@@ -722,58 +2599,495 @@ async fn handle_signaling_conn(
                         let _ = send_msg_split(&mut *w, &ack).await;
                     }
                     break;
+                } else {
+                    info!("Pairing PIN accepted from {}", addr);
                 }
-                info!("Pairing PIN accepted from {}", addr);
 
-                // Respond with hello_ack
-                let ack = SignalingMessage::hello_ack(session_id.clone(), true, None);
+                // ── One sender per display ──
+                if let Some(why) = session.is_none()
+                    .then(|| handle::owner_conflict(&state, display_index, &device_name, addr))
+                    .flatten()
+                {
+                    warn!("Rejecting '{}' from {} — {}", device_name, addr, why);
+                    let ack = SignalingMessage::hello_ack(session_id, false, Some(why));
+                    let mut w = writer_for_reader.lock().await;
+                    let _ = send_msg_split(&mut *w, &ack).await;
+                    break;
+                }
+
+                // Respond with hello_ack carrying the negotiated config
+                let requested_lossless = config.lossless;
+                let mut config = config.negotiate(&capabilities);
+                if requested_lossless && !config.lossless {
+                    info!("Lossless mode requested by {} but not supported — disabled", addr);
+                }
+                if let Some(why) = limits.violation(&config) {
+                    if reject_over_limits {
+                        warn!("Rejecting {} — {}", addr, why);
+                        let ack = SignalingMessage::hello_ack(
+                            session_id,
+                            false,
+                            Some(format!("Stream exceeds receiver limits: {why}")),
+                        );
+                        let mut w = writer_for_reader.lock().await;
+                        let _ = send_msg_split(&mut *w, &ack).await;
+                        break;
+                    }
+                    info!("Clamping config from {} to receiver limits — {}", addr, why);
+                    config = config.clamp_to(&limits);
+                }
+                // The transport itself always accepts v2 video headers.
+                let mut receiver_caps = capabilities.as_ref().clone();
+                receiver_caps.push(CAP_DLNK_V2.to_owned());
+                receiver_caps.push(CAP_BLANK.to_owned());
+                receiver_caps.push(CAP_PREVIEW.to_owned());
+                receiver_caps.push(CAP_POWER.to_owned());
+                receiver_caps.push(CAP_DISPLAY_STATE.to_owned());
+                receiver_caps.push(CAP_FILE_TRANSFER.to_owned());
+                let ack = SignalingMessage {
+                    resume_token: resume.issue(&session_id, &device_name, display_index),
+                    ..SignalingMessage::hello_ack_negotiated(
+                        session_id.clone(),
+                        config.clone(),
+                        receiver_caps,
+                        monitor.borrow().clone(),
+                        ports.borrow().clone(),
+                        limits,
+                        allow_input,
+                    )
+                };
                 {
                     let mut w = writer_for_reader.lock().await;
                     if send_msg_split(&mut *w, &ack).await.is_err() { break; }
+                    // A resumed sender carries on mid-GOP: ask for a keyframe
+                    // now rather than at its first delta frame.
+                    if resumed.is_some() && sender_caps.iter().any(|c| c == CAP_KEYFRAME_REQUEST)
+                        && send_msg_split(&mut *w, &SignalingMessage::keyframe_request()).await.is_err()
+                    {
+                        break;
+                    }
                 }
 
+                // The new session's decoder needs a keyframe first.
+                link.hevc.store(config.codec == VideoCodec::H265, std::sync::atomic::Ordering::Release);
+                link.slices.store(config.nal_slices, std::sync::atomic::Ordering::Release);
+                link.session.fetch_add(1, std::sync::atomic::Ordering::Release);
+                link.usage.restart();
+                session = Some((session_id.clone(), device_name.clone()));
+                keyframes.arm();
+                let session_config = config.clone();
+                handle::set_phase(&state, display_index, DisplayPhase::Connected(PeerInfo {
+                    session_id:  session_id.clone(),
+                    device_name: device_name.clone(),
+                    address:     addr,
+                    config:      config.clone(),
+                    allow_input,
+                }));
                 let _ = event_tx.send(SignalingEvent::SessionStarted {
-                    session_id, device_name, config, client_addr: addr,
+                    session_id, device_name, config, client_addr: addr, allow_input,
                 }).await;
 
                 // Start forwarding input events now that session is active
                 if !session_active {
                     session_active = true;
-                    let w = Arc::clone(&writer);
-                    let irx = Arc::clone(&input_rx);
-                    tokio::spawn(async move {
-                        let mut input_rx = irx.lock().await;
-                        let mut events_sent: u64 = 0;
-                        while let Some(event) = input_rx.recv().await {
-                            let msg = SignalingMessage::input_event(event);
-                            let mut w = w.lock().await;
-                            if send_msg_split(&mut *w, &msg).await.is_err() { break; }
-                            events_sent += 1;
-                            if events_sent == 1 {
-                                info!("First input event sent to Mac client");
+                    if allow_input {
+                        let w = Arc::clone(&writer);
+                        let (route, mut input_rx) = input.open(display_index);
+                        input_route = Some(route);
+                        tokio::spawn(async move {
+                            let mut events_sent: u64 = 0;
+                            while let Some(event) = input_rx.recv().await {
+                                let msg = SignalingMessage::input_event(event);
+                                let mut w = w.lock().await;
+                                if send_msg_split(&mut *w, &msg).await.is_err() { break; }
+                                events_sent += 1;
+                                if events_sent == 1 {
+                                    info!("First input event sent to Mac client");
+                                }
                             }
-                        }
-                        debug!("Input writer task exiting (sent {} events)", events_sent);
-                    });
+                            debug!("Input writer task exiting (sent {} events)", events_sent);
+                        });
+                    } else {
+                        info!("View-only session from {} — input not forwarded", addr);
+                    }
+
+                    if !stall_timeout.is_zero() {
+                        let watch = StallWatch {
+                            link:     Arc::clone(&link),
+                            keyframes: keyframes.clone(),
+                            pause:    pause.subscribe(),
+                            blank:    blank.clone(),
+                            event_tx: event_tx.clone(),
+                            timeout:  stall_timeout,
+                        };
+                        watchdog = Some(tokio::spawn(watch.run()));
+                    }
+
+                    // Push monitor hot-plug changes to senders that understand them
+                    if sender_caps.iter().any(|c| c == CAP_DISPLAY_INFO) {
+                        let w = Arc::clone(&writer);
+                        let mut mon = monitor.clone();
+                        tokio::spawn(async move {
+                            while mon.changed().await.is_ok() {
+                                let msg = SignalingMessage::display_info(mon.borrow_and_update().clone());
+                                let mut w = w.lock().await;
+                                if send_msg_split(&mut *w, &msg).await.is_err() { break; }
+                            }
+                        });
+                    }
+
+                    // Ask for a keyframe whenever the gate drops delta frames
+                    if sender_caps.iter().any(|c| c == CAP_KEYFRAME_REQUEST) {
+                        let w = Arc::clone(&writer);
+                        let gate = keyframes.clone();
+                        tokio::spawn(async move {
+                            loop {
+                                gate.requested().await;
+                                debug!("Requesting keyframe from {}", addr);
+                                let mut w = w.lock().await;
+                                if send_msg_split(&mut *w, &SignalingMessage::keyframe_request()).await.is_err() {
+                                    break;
+                                }
+                                drop(w);
+                                tokio::time::sleep(KEYFRAME_REQUEST_INTERVAL).await;
+                            }
+                        });
+                    }
+
+                    // Forward capture-pause requests, including one already standing
+                    if sender_caps.iter().any(|c| c == CAP_BLANK) {
+                        let w = Arc::clone(&writer);
+                        let mut blank = blank.clone();
+                        tokio::spawn(async move {
+                            let standing = *blank.borrow_and_update();
+                            if !standing && blank.changed().await.is_err() { return; }
+                            loop {
+                                let enabled = *blank.borrow_and_update();
+                                info!("Asking {} to {} capture", addr, if enabled { "pause" } else { "resume" });
+                                let mut w = w.lock().await;
+                                if send_msg_split(&mut *w, &SignalingMessage::blank(enabled)).await.is_err() {
+                                    break;
+                                }
+                                drop(w);
+                                if blank.changed().await.is_err() { break; }
+                            }
+                        });
+                    }
+
+                    // Forward the receiver's pauses, including one already standing
+                    if sender_caps.iter().any(|c| c == CAP_DISPLAY_STATE) {
+                        let w = Arc::clone(&writer);
+                        let mut state = pause.subscribe();
+                        tokio::spawn(async move {
+                            let standing = state.borrow_and_update().paused;
+                            if !standing && state.changed().await.is_err() { return; }
+                            loop {
+                                let PauseState { paused, from_sender } = *state.borrow_and_update();
+                                if !from_sender {
+                                    info!("Asking {} to {} display {}", addr, if paused { "pause" } else { "resume" }, display_index);
+                                    let msg = SignalingMessage::display_state(display_index, paused);
+                                    let mut w = w.lock().await;
+                                    if send_msg_split(&mut *w, &msg).await.is_err() { break; }
+                                }
+                                if state.changed().await.is_err() { break; }
+                            }
+                        });
+                    }
+
+                    // Forward frame-rate requests, including one already standing
+                    if sender_caps.iter().any(|c| c == CAP_FPS_REQUEST) {
+                        let w = Arc::clone(&writer);
+                        let mut pace = pace.clone();
+                        tokio::spawn(async move {
+                            let standing = pace.borrow_and_update().is_some();
+                            if !standing && pace.changed().await.is_err() { return; }
+                            loop {
+                                let fps = *pace.borrow_and_update();
+                                let mut config = session_config.clone();
+                                if let Some(fps) = fps {
+                                    config.target_fps = config.target_fps.min(fps);
+                                }
+                                info!("Asking {} for {} fps", addr, config.target_fps);
+                                let mut w = w.lock().await;
+                                if send_msg_split(&mut *w, &SignalingMessage::fps_request(config)).await.is_err() {
+                                    break;
+                                }
+                                drop(w);
+                                if pace.changed().await.is_err() { break; }
+                            }
+                        });
+                    }
+
+                    // Tell the sender the receiver's power state, now and on changes
+                    if sender_caps.iter().any(|c| c == CAP_POWER) {
+                        let w = Arc::clone(&writer);
+                        let mut power = power.clone();
+                        tokio::spawn(async move {
+                            loop {
+                                let state = *power.borrow_and_update();
+                                if let Some(state) = state {
+                                    let mut w = w.lock().await;
+                                    if send_msg_split(&mut *w, &SignalingMessage::power(state)).await.is_err() {
+                                        break;
+                                    }
+                                }
+                                if power.changed().await.is_err() { break; }
+                            }
+                        });
+                    }
+
+                    // Forward thumbnails; subscribing is what makes the app take them
+                    if sender_caps.iter().any(|c| c == CAP_PREVIEW) {
+                        let w = Arc::clone(&writer);
+                        let mut thumbs = preview.subscribe();
+                        tokio::spawn(async move {
+                            while thumbs.changed().await.is_ok() {
+                                let Some(msg) = thumbs.borrow_and_update().as_deref().map(SignalingMessage::preview) else {
+                                    continue;
+                                };
+                                let mut w = w.lock().await;
+                                if send_msg_split(&mut *w, &msg).await.is_err() { break; }
+                            }
+                        });
+                    }
+
+                    // Send file offers, answers and chunks at a steady pace
+                    if sender_caps.iter().any(|c| c == CAP_FILE_TRANSFER) {
+                        let w = Arc::clone(&writer);
+                        let files = files.clone();
+                        let event_tx = event_tx.clone();
+                        file_pump = Some(tokio::spawn(async move {
+                            let mut tick = tokio::time::interval(FILE_CHUNK_INTERVAL);
+                            loop {
+                                tick.tick().await;
+                                let f = files.clone();
+                                let now = std::time::Instant::now();
+                                let Ok(messages) = tokio::task::spawn_blocking(move || f.next_messages(now)).await else {
+                                    return;
+                                };
+                                for message in messages {
+                                    let mut w = w.lock().await;
+                                    if send_msg_split(&mut *w, &SignalingMessage::file(message)).await.is_err() {
+                                        return;
+                                    }
+                                }
+                                forward_file_events(&files, &event_tx).await;
+                            }
+                        }));
+                    }
+
+                    // Push runtime display additions/removals likewise
+                    if sender_caps.iter().any(|c| c == CAP_DISPLAYS_CHANGED) {
+                        let w = Arc::clone(&writer);
+                        let mut displays = displays.clone();
+                        tokio::spawn(async move {
+                            while displays.changed().await.is_ok() {
+                                let msg = SignalingMessage::displays_changed(displays.borrow_and_update().clone());
+                                let mut w = w.lock().await;
+                                if send_msg_split(&mut *w, &msg).await.is_err() { break; }
+                            }
+                        });
+                    }
                 }
             }
             MessageType::ConfigUpdate => {
                 if let Some(config) = msg.config {
+                    let mut config = config.negotiate(&capabilities);
+                    if let Some(why) = limits.violation(&config) {
+                        info!("Clamping config update from {} to receiver limits — {}", addr, why);
+                        let requested_fps = config.target_fps;
+                        config = config.clamp_to(&limits);
+                        if fps_requests && config.target_fps < requested_fps {
+                            let mut w = writer_for_reader.lock().await;
+                            if send_msg_split(&mut *w, &SignalingMessage::fps_request(config.clone())).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    if let Some((session_id, _)) = &session {
+                        state.send_if_modified(|s| match s.displays.iter_mut().find(|d| d.display_index == display_index) {
+                            Some(ReceiverDisplay { phase: DisplayPhase::Connected(peer), .. })
+                                if peer.session_id == *session_id && peer.config != config =>
+                            {
+                                peer.config = config.clone();
+                                true
+                            }
+                            _ => false,
+                        });
+                    }
                     let _ = event_tx.send(SignalingEvent::ConfigUpdated { config }).await;
                 }
             }
             MessageType::Keepalive => {
                 debug!("Keepalive from {} ts={:?}", addr, msg.timestamp_ms);
+                if ack_keepalives {
+                    let ack = SignalingMessage::keepalive_ack(msg.timestamp_ms, link.snapshot());
+                    let mut w = writer_for_reader.lock().await;
+                    if send_msg_split(&mut *w, &ack).await.is_err() { break; }
+                }
             }
             MessageType::Stop => {
                 let session_id = msg.session_id.unwrap_or_default();
                 info!("Stop from {} session={}", addr, session_id);
-                let _ = event_tx.send(SignalingEvent::SessionStopped { session_id }).await;
+                let peer = match session.take() {
+                    Some((id, peer)) => {
+                        handle::end_session(&state, display_index, &id);
+                        peer
+                    }
+                    None => addr.to_string(),
+                };
+                let summary = link.usage.summary(&session_id, &peer);
+                record_usage(&summary);
+                let _ = event_tx.send(SignalingEvent::SessionStopped { session_id, summary }).await;
                 break;
             }
-            MessageType::HelloAck | MessageType::InputEvent => { /* not expected from client */ }
+            MessageType::Blank => {
+                let enabled = msg.enabled.unwrap_or(true);
+                info!("{} {} the display", addr, if enabled { "blanked" } else { "unblanked" });
+                let _ = event_tx.send(SignalingEvent::Blank { enabled }).await;
+            }
+            MessageType::DisplayState => {
+                let paused = msg.paused.unwrap_or(true);
+                info!("{} {} the display", addr, if paused { "paused" } else { "resumed" });
+                pause.send_if_modified(|current| {
+                    std::mem::replace(current, PauseState { paused, from_sender: true }).paused != paused
+                });
+                let _ = event_tx.send(SignalingEvent::DisplayState { paused }).await;
+            }
+            MessageType::Power => {
+                if let Some(power) = msg.power {
+                    info!("{} is {}", addr, power);
+                    let _ = event_tx.send(SignalingEvent::SenderPower { power }).await;
+                }
+            }
+            MessageType::FileOffer | MessageType::FileAccept | MessageType::FileChunk => {
+                if file_pump.is_none() {
+                    continue;
+                }
+                match msg.into_file() {
+                    Some(message) => {
+                        // Chunks are written to disk: off the runtime, and in order.
+                        let f = files.clone();
+                        let _ = tokio::task::spawn_blocking(move || f.receive(message)).await;
+                    }
+                    None => debug!("Incomplete file-transfer message from {}", addr),
+                }
+                forward_file_events(&files, &event_tx).await;
+            }
+            MessageType::HelloAck | MessageType::KeepaliveAck | MessageType::KeyframeRequest
+            | MessageType::InputEvent | MessageType::DisplayInfo
+            | MessageType::DisplaysChanged | MessageType::Preview | MessageType::Error => { /* not expected from client */ }
         }
     }
+    if let Some((session_id, peer)) = session {
+        record_usage(&link.usage.summary(&session_id, &peer));
+        handle::end_session(&state, display_index, &session_id);
+    }
+    if let Some(route) = input_route {
+        input.close(display_index, &route);
+    }
+    if let Some(watchdog) = watchdog {
+        watchdog.abort();
+    }
+    if let Some(pump) = file_pump {
+        pump.abort();
+        files.abort_all("The sender disconnected");
+        forward_file_events(&files, &event_tx).await;
+    }
+}
+
+/// Report what happened to `files` since the last call as
+/// [`SignalingEvent::File`].
+async fn forward_file_events(files: &FileTransfers, event_tx: &mpsc::Sender<SignalingEvent>) {
+    for event in files.take_events() {
+        // Progress is informational — never hold up signaling for it.
+        if matches!(event, FileTransferEvent::Progress { .. }) {
+            let _ = event_tx.try_send(SignalingEvent::File(event));
+        } else {
+            let _ = event_tx.send(SignalingEvent::File(event)).await;
+        }
+    }
+}
+
+/// Watches one connection's stream for stalls, see
+/// [`SignalingEvent::StreamStalled`].
+struct StallWatch {
+    link:      Arc<LinkStats>,
+    keyframes: KeyframeGate,
+    pause:     watch::Receiver<PauseState>,
+    blank:     watch::Receiver<bool>,
+    event_tx:  mpsc::Sender<SignalingEvent>,
+    timeout:   Duration,
+}
+
+impl StallWatch {
+    async fn run(self) {
+        let mut ticker = tokio::time::interval(STALL_CHECK_INTERVAL);
+        // Start of the current quiet spell; paused and blanked time doesn't count.
+        let mut quiet_since = std::time::Instant::now();
+        let mut stalled = false;
+        loop {
+            ticker.tick().await;
+            let now = std::time::Instant::now();
+            if self.pause.borrow().paused || *self.blank.borrow() {
+                quiet_since = now;
+            }
+            if let Some(last) = *self.link.last_frame.lock().unwrap() {
+                quiet_since = quiet_since.max(last);
+            }
+            let silent = now.saturating_duration_since(quiet_since);
+            if silent < self.timeout {
+                if stalled {
+                    info!("Frames arriving again");
+                }
+                stalled = false;
+            } else if !stalled {
+                stalled = true;
+                warn!("No frames for {:.0} s — requesting a keyframe", silent.as_secs_f64());
+                self.keyframes.rearm();
+                let _ = self.event_tx.try_send(SignalingEvent::StreamStalled { silent });
+            }
+        }
+    }
+}
+
+/// Log a finished session's usage and append it to the history file.
+fn record_usage(summary: &SessionSummary) {
+    info!("Session usage: {}", summary);
+    let summary = summary.clone();
+    tokio::task::spawn_blocking(move || {
+        let Some(path) = configured_usage_history() else { return };
+        if let Err(e) = summary.append_to(&path) {
+            warn!("Writing usage history {}: {}", path.display(), e);
+        }
+    });
+}
+
+/// Signaling writer that counts what it writes into the session's usage.
+struct MeteredWriter<W> {
+    inner: W,
+    usage: UsageMeter,
+}
+
+impl<W: tokio::io::AsyncWrite + Unpin> tokio::io::AsyncWrite for MeteredWriter<W> {
+    fn poll_write(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+        let written = std::task::ready!(std::pin::Pin::new(&mut this.inner).poll_write(cx, buf))?;
+        this.usage.add_sent(written);
+        std::task::Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
 }
 
 async fn send_msg_split<W: AsyncWriteExt + Unpin>(writer: &mut W, msg: &SignalingMessage) -> std::io::Result<()> {
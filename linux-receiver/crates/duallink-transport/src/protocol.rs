@@ -0,0 +1,870 @@
+//! DualLink UDP frame protocol — packet parsing and frame reassembly.
+//!
+//! Two header versions are accepted, told apart by their magic (see the
+//! crate docs for the layouts): v1 (`DLNK`, 20 bytes, 32-bit ms PTS) from
+//! every sender, and v2 (`DLN2`, 28 bytes, 64-bit µs PTS plus clock epoch)
+//! from senders that saw [`CAP_DLNK_V2`](duallink_core::CAP_DLNK_V2) in
+//! `hello_ack`.
+//!
+//! Nothing here trusts the sender: every header field is validated, and the
+//! reassembler tolerates any ordering, duplication or inconsistency of
+//! fragments without panicking or emitting a frame that mixes fragments of
+//! different shapes.
+//!
+//! | Input                                      | Handling                          |
+//! |--------------------------------------------|-----------------------------------|
+//! | short datagram, bad magic                  | [`PacketError`]                   |
+//! | `frag_count == 0` or > [`MAX_FRAGMENTS`]   | [`PacketError`]                   |
+//! | `frag_index >= frag_count`                 | [`PacketError`]                   |
+//! | `frag_count` changes mid-frame             | fragment rejected                 |
+//! | duplicate fragment (same or other size)    | first copy kept                   |
+//! | fragment of an already completed frame     | ignored                           |
+//! | `frame_seq` wrap-around                    | no special case — seqs are keys   |
+//! | fragments that never complete              | evicted after [`REASSEMBLY_TIMEOUT`] |
+//! | too many / too large partial frames        | oldest evicted ([`ReassemblyBudget`]) |
+//! | assembled payload fails its v2 checksum    | frame dropped                     |
+//!
+//! v2 senders may set [`FLAG_CHECKSUM`] and put
+//! [`frame_checksum`](duallink_core::frame_checksum) of the whole frame
+//! payload (the low 16 bits of its CRC-32) in the reserved bytes of every
+//! fragment. Sixteen bits catch all burst errors up to 16 bits and all but
+//! 1 in 65 536 others — enough to keep garbage away from the decoder, not
+//! a security measure.
+//!
+//! The sender marks frames no other frame references with a temporal layer
+//! above 0 (see [`duallink_core::layers`]): byte [18] of a v1 header, flag
+//! bits 2–3 of a v2 one (the reserved bytes hold its checksum). The layer
+//! of a frame's first fragment is kept.
+//!
+//! For slice-level decoding (see [`duallink_core::slices`]) a v2 sender
+//! starts a fragment at every NAL unit and sets [`FLAG_NAL_END`] on the last
+//! fragment of each. [`FrameReassembler::take_slices`] then hands out the
+//! complete NAL units at the head of a frame before the rest has arrived;
+//! the frame [`push`](FrameReassembler::push) completes later says how many
+//! of its bytes went out that way. Frames with a checksum are never handed
+//! out early, as only the whole frame can be checked.
+//!
+//! Every case is counted in [`ReassemblyStats`]. Frame *order* (late,
+//! duplicate and missing frames) is the
+//! [`SequenceTracker`](duallink_core::SequenceTracker)'s job.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use duallink_core::{frame_checksum, EncodedFrame, VideoCodec};
+use tracing::{debug, warn};
+
+// ── Constants ─────────────────────────────────────────────────────────────────
+
+pub const MAGIC: u32 = 0x444C_4E4B;
+/// Header bytes written by Swift: magic(4)+frameSeq(4)+fragIdx(2)+fragCount(2)+pts(4)+flags(1)+display_index(1)+reserved(2) = 20
+pub const HEADER_SIZE: usize = 20;
+/// v2 magic, "DLN2".
+pub const MAGIC_V2: u32 = 0x444C_4E32;
+/// magic(4)+frameSeq(4)+fragIdx(2)+fragCount(2)+clockEpoch(4)+flags(1)+display_index(1)+reserved(2)+pts_us(8) = 28
+pub const HEADER_SIZE_V2: usize = 28;
+/// Partial frames older than this are dropped.
+pub const REASSEMBLY_TIMEOUT: Duration = Duration::from_secs(2);
+/// Largest accepted `frag_count` — a 4K keyframe at ~1.4 KB per fragment
+/// needs a few hundred; anything near `u16::MAX` is garbage.
+pub const MAX_FRAGMENTS: u16 = 4096;
+/// Bytes charged per announced fragment slot, before its payload arrives.
+const SLOT_COST: usize = std::mem::size_of::<Option<Bytes>>() + std::mem::size_of::<bool>();
+/// Allocation size of the buffer frames are assembled in — a few 4K
+/// keyframes.
+const ASSEMBLY_POOL: usize = 4 << 20;
+/// Completed `frame_seq`s remembered so their stray fragments are ignored.
+const COMPLETED_MEMORY: usize = 64;
+
+const FLAG_KEYFRAME: u8 = 0x01;
+/// v2 only: header[18..20] holds the frame's checksum.
+pub const FLAG_CHECKSUM: u8 = 0x02;
+/// v2 only: flag bits holding the temporal layer (0–3).
+pub const FLAG_LAYER_MASK: u8 = 0x0C;
+const FLAG_LAYER_SHIFT: u8 = 2;
+/// v2 only: the fragment ends a NAL unit (slice-level decoding).
+pub const FLAG_NAL_END: u8 = 0x10;
+
+// ── Packet ────────────────────────────────────────────────────────────────────
+
+/// Presentation timestamp as carried by each header version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timestamp {
+    /// v1: milliseconds, wrapping at `u32::MAX` (see
+    /// [`PtsUnwrapper`](duallink_core::PtsUnwrapper)).
+    V1 { pts_ms: u32 },
+    /// v2: microseconds on the sender clock identified by `clock_epoch`.
+    V2 { pts_us: u64, clock_epoch: u32 },
+}
+
+impl Timestamp {
+    /// The timestamp in µs as sent — v1 values still wrap.
+    pub fn raw_us(&self) -> u64 {
+        match *self {
+            Timestamp::V1 { pts_ms } => pts_ms as u64 * 1_000,
+            Timestamp::V2 { pts_us, .. } => pts_us,
+        }
+    }
+}
+
+/// One parsed DLNK datagram.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DualLinkPacket {
+    pub frame_seq:      u32,
+    pub frag_index:     u16,
+    pub frag_count:     u16,
+    pub timestamp:      Timestamp,
+    pub is_keyframe:    bool,
+    /// Zero-based display stream index from byte [17] of the DLNK header.
+    pub display_index:  u8,
+    /// v2 [`FLAG_CHECKSUM`]: checksum of the whole frame's payload.
+    pub checksum:       Option<u16>,
+    /// Temporal layer; frames above 0 may be dropped by the decoder.
+    pub temporal_layer: u8,
+    /// v2 [`FLAG_NAL_END`]: the payload ends a NAL unit.
+    pub nal_end:        bool,
+    pub payload:        Bytes,
+}
+
+/// Why a datagram was not a valid DLNK packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketError {
+    TooShort(usize),
+    BadMagic(u32),
+    NoFragments,
+    TooManyFragments(u16),
+    IndexOutOfRange { index: u16, count: u16 },
+}
+
+impl fmt::Display for PacketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PacketError::TooShort(len) => write!(f, "{len} bytes, shorter than the header"),
+            PacketError::BadMagic(magic) => write!(f, "bad magic 0x{magic:08X}"),
+            PacketError::NoFragments => write!(f, "frag_count is 0"),
+            PacketError::TooManyFragments(n) => write!(f, "frag_count {n} > {MAX_FRAGMENTS}"),
+            PacketError::IndexOutOfRange { index, count } => {
+                write!(f, "frag_index {index} out of range for frag_count {count}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PacketError {}
+
+/// Parse and validate one datagram. Never panics.
+///
+/// Copies the payload; [`parse_datagram`] avoids that for datagrams that
+/// already live in a [`Bytes`].
+pub fn parse_packet(buf: &[u8]) -> Result<DualLinkPacket, PacketError> {
+    parse_datagram(Bytes::copy_from_slice(buf))
+}
+
+/// [`parse_packet`] without the copy: the payload is a slice of `datagram`.
+pub fn parse_datagram(datagram: Bytes) -> Result<DualLinkPacket, PacketError> {
+    let Some((header, _)) = datagram.split_first_chunk::<HEADER_SIZE>() else {
+        return Err(PacketError::TooShort(datagram.len()));
+    };
+    let be16 = |at: usize| u16::from_be_bytes([header[at], header[at + 1]]);
+    let be32 = |at: usize| u32::from_be_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]]);
+
+    let header_size = match be32(0) {
+        MAGIC => HEADER_SIZE,
+        MAGIC_V2 if datagram.len() >= HEADER_SIZE_V2 => HEADER_SIZE_V2,
+        MAGIC_V2 => return Err(PacketError::TooShort(datagram.len())),
+        magic => return Err(PacketError::BadMagic(magic)),
+    };
+    let frag_index = be16(8);
+    let frag_count = be16(10);
+    if frag_count == 0 {
+        return Err(PacketError::NoFragments);
+    }
+    if frag_count > MAX_FRAGMENTS {
+        return Err(PacketError::TooManyFragments(frag_count));
+    }
+    if frag_index >= frag_count {
+        return Err(PacketError::IndexOutOfRange { index: frag_index, count: frag_count });
+    }
+    let checksum = (header_size == HEADER_SIZE_V2 && header[16] & FLAG_CHECKSUM != 0).then(|| be16(18));
+    let temporal_layer = match header_size {
+        HEADER_SIZE_V2 => (header[16] & FLAG_LAYER_MASK) >> FLAG_LAYER_SHIFT,
+        _ => header[18],
+    };
+    let timestamp = if header_size == HEADER_SIZE_V2 {
+        let mut pts = [0u8; 8];
+        pts.copy_from_slice(&datagram[HEADER_SIZE..HEADER_SIZE_V2]);
+        Timestamp::V2 { pts_us: u64::from_be_bytes(pts), clock_epoch: be32(12) }
+    } else {
+        Timestamp::V1 { pts_ms: be32(12) }
+    };
+    Ok(DualLinkPacket {
+        frame_seq: be32(4),
+        frag_index,
+        frag_count,
+        timestamp,
+        is_keyframe: header[16] & FLAG_KEYFRAME != 0,
+        display_index: header[17],
+        checksum,
+        temporal_layer,
+        nal_end: header_size == HEADER_SIZE_V2 && header[16] & FLAG_NAL_END != 0,
+        payload: datagram.slice(header_size..),
+    })
+}
+
+impl DualLinkPacket {
+    /// Serialise as a DLNK datagram (the inverse of [`parse_packet`]). A v2
+    /// header carries temporal layers up to 3; a v1 header drops `nal_end`.
+    pub fn encode(&self) -> Bytes {
+        let (magic, word, pts_us) = match self.timestamp {
+            Timestamp::V1 { pts_ms } => (MAGIC, pts_ms, None),
+            Timestamp::V2 { pts_us, clock_epoch } => (MAGIC_V2, clock_epoch, Some(pts_us)),
+        };
+        let mut buf = BytesMut::with_capacity(HEADER_SIZE_V2 + self.payload.len());
+        buf.put_u32(magic);
+        buf.put_u32(self.frame_seq);
+        buf.put_u16(self.frag_index);
+        buf.put_u16(self.frag_count);
+        buf.put_u32(word);
+        let checksum = self.checksum.filter(|_| pts_us.is_some());
+        let mut flags = if self.is_keyframe { FLAG_KEYFRAME } else { 0 };
+        if checksum.is_some() {
+            flags |= FLAG_CHECKSUM;
+        }
+        if pts_us.is_some() {
+            flags |= self.temporal_layer.min(FLAG_LAYER_MASK >> FLAG_LAYER_SHIFT) << FLAG_LAYER_SHIFT;
+            if self.nal_end {
+                flags |= FLAG_NAL_END;
+            }
+        }
+        buf.put_u8(flags);
+        buf.put_u8(self.display_index);
+        match pts_us {
+            Some(pts_us) => {
+                buf.put_u16(checksum.unwrap_or(0));
+                buf.put_u64(pts_us);
+            }
+            None => buf.put_slice(&[self.temporal_layer, 0]),
+        }
+        buf.put_slice(&self.payload);
+        buf.freeze()
+    }
+}
+
+// ── Reassembly ────────────────────────────────────────────────────────────────
+
+/// Upper bounds on what a [`FrameReassembler`] holds for incomplete frames.
+///
+/// A partial frame costs its fragment table (`frag_count` slots, allocated
+/// up front) plus the payloads received so far. When a new fragment would
+/// exceed either limit, the oldest partial frames are evicted first.
+///
+/// Payloads are held as copies, so the cost is the memory actually held:
+/// a datagram is a slice of a whole receive slab (see `recv`), and keeping
+/// the slice would keep the slab alive for up to [`REASSEMBLY_TIMEOUT`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReassemblyBudget {
+    /// Incomplete frames held at once.
+    pub max_partial_frames: usize,
+    /// Bytes held across all incomplete frames.
+    pub max_buffered_bytes: usize,
+}
+
+impl Default for ReassemblyBudget {
+    /// 64 frames / 32 MiB — a few seconds of 4K at 60 fps under heavy loss.
+    fn default() -> Self {
+        Self { max_partial_frames: 64, max_buffered_bytes: 32 << 20 }
+    }
+}
+
+/// Fragment-level anomalies seen by a [`FrameReassembler`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReassemblyStats {
+    /// Partial frames dropped after [`REASSEMBLY_TIMEOUT`].
+    pub evicted:             u64,
+    /// Fragments repeating one already held (any size); first copy kept.
+    pub duplicate_fragments: u64,
+    /// Fragments whose `frag_count` disagrees with the frame's first one.
+    pub inconsistent:        u64,
+    /// Fragments of frames that were already completed.
+    pub stale_fragments:     u64,
+    /// Partial frames evicted to stay within the [`ReassemblyBudget`],
+    /// including frames too large to fit at all.
+    pub budget_evicted:      u64,
+    /// Complete frames dropped because their payload failed the checksum.
+    pub corrupted:           u64,
+}
+
+struct PartialFrame {
+    /// Right-sized copies of the payloads, never slices of a receive slab.
+    fragments:      Vec<Option<Bytes>>,
+    /// Per fragment: its sender flagged it as ending a NAL unit.
+    nal_ends:       Vec<bool>,
+    received_count: u16,
+    timestamp:      Timestamp,
+    is_keyframe:    bool,
+    /// Checksum announced by the first fragment.
+    checksum:       Option<u16>,
+    temporal_layer: u8,
+    first_seen:     Instant,
+    /// Leading fragments, and their bytes, handed out by
+    /// [`FrameReassembler::take_slices`].
+    released:       (usize, usize),
+}
+
+impl PartialFrame {
+    fn new(packet: &DualLinkPacket, now: Instant) -> Self {
+        Self {
+            fragments: vec![None; packet.frag_count as usize],
+            nal_ends: vec![false; packet.frag_count as usize],
+            received_count: 0,
+            timestamp: packet.timestamp,
+            is_keyframe: packet.is_keyframe,
+            checksum: packet.checksum,
+            temporal_layer: packet.temporal_layer,
+            first_seen: now,
+            released: (0, 0),
+        }
+    }
+
+    /// Bytes charged against the [`ReassemblyBudget`].
+    fn cost(&self) -> usize {
+        self.fragments.len() * SLOT_COST + self.fragments.iter().flatten().map(|f| f.len()).sum::<usize>()
+    }
+
+    fn is_complete(&self) -> bool {
+        self.received_count as usize == self.fragments.len()
+    }
+
+    /// Concatenate the fragments, carving the frame out of `pool`. A
+    /// single-fragment frame is returned as is.
+    fn assemble(self, pool: &mut BytesMut) -> Bytes {
+        if let [Some(only)] = &self.fragments[..] {
+            return only.clone();
+        }
+        let total: usize = self.fragments.iter().flatten().map(|f| f.len()).sum();
+        if pool.capacity() < total {
+            // Reclaims the pool's allocation once every frame carved out of
+            // it has been dropped; allocates a new one otherwise.
+            pool.reserve(total.max(ASSEMBLY_POOL));
+        }
+        for frag in self.fragments.into_iter().flatten() {
+            pool.extend_from_slice(&frag);
+        }
+        pool.split().freeze()
+    }
+}
+
+/// A complete frame from [`FrameReassembler::push`].
+#[derive(Debug, Clone)]
+pub struct AssembledFrame {
+    pub seq:       u32,
+    /// Timestamp of the frame's first fragment; `frame.timestamp_us` holds
+    /// its [`raw_us`](Timestamp::raw_us) until the receiver maps it.
+    pub timestamp: Timestamp,
+    pub frame:     EncodedFrame,
+    /// When the first and the last fragment arrived.
+    pub received:  (Instant, Instant),
+    /// Leading bytes of `frame.data` already handed out by
+    /// [`FrameReassembler::take_slices`]; 0 unless slice-level decoding.
+    pub released:  usize,
+}
+
+/// Collects fragments into complete [`EncodedFrame`]s.
+#[derive(Default)]
+pub struct FrameReassembler {
+    frames:    HashMap<u32, PartialFrame>,
+    completed: VecDeque<u32>,
+    budget:    ReassemblyBudget,
+    /// Sum of [`PartialFrame::cost`] over `frames`.
+    buffered:  usize,
+    /// Backing store for assembled frames, reused once they are dropped.
+    pool:      BytesMut,
+    stats:     ReassemblyStats,
+}
+
+impl FrameReassembler {
+    pub fn new(budget: ReassemblyBudget) -> Self {
+        Self { budget, ..Self::default() }
+    }
+
+    /// Bytes currently held for incomplete frames.
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffered
+    }
+
+    /// Add one fragment; returns the frame once complete.
+    ///
+    /// A complete frame whose payload fails its checksum is dropped and
+    /// counted as [`corrupted`](ReassemblyStats::corrupted); its `frame_seq`
+    /// is never seen by the sequence tracker, which reports it as lost.
+    pub fn push(&mut self, packet: DualLinkPacket) -> Option<AssembledFrame> {
+        self.push_at(packet, Instant::now())
+    }
+
+    pub fn stats(&self) -> ReassemblyStats {
+        self.stats
+    }
+
+    /// The NAL units of incomplete frame `seq` that arrived whole, along
+    /// with every fragment before them, and weren't handed out yet. `None`
+    /// if there are none, and for frames with a checksum.
+    pub fn take_slices(&mut self, seq: u32) -> Option<Bytes> {
+        let partial = self.frames.get_mut(&seq).filter(|p| p.checksum.is_none())?;
+        let contiguous = partial.fragments.iter().position(Option::is_none).unwrap_or(partial.fragments.len());
+        let end = partial.nal_ends[..contiguous].iter().rposition(|&e| e)? + 1;
+        let (from, bytes) = partial.released;
+        if end <= from {
+            return None;
+        }
+        let mut slices = BytesMut::new();
+        for frag in partial.fragments[from..end].iter().flatten() {
+            slices.extend_from_slice(frag);
+        }
+        partial.released = (end, bytes + slices.len());
+        Some(slices.freeze())
+    }
+
+    /// Drop partial frames and forget completed sequence numbers — the
+    /// sender started a new stream, possibly from `frame_seq` 0 again.
+    /// Totals are kept.
+    pub fn reset(&mut self) {
+        self.frames.clear();
+        self.completed.clear();
+        self.buffered = 0;
+    }
+
+    /// [`push`](Self::push) with an explicit clock, for tests.
+    fn push_at(&mut self, packet: DualLinkPacket, now: Instant) -> Option<AssembledFrame> {
+        self.evict(now);
+
+        let seq = packet.frame_seq;
+        if self.completed.contains(&seq) {
+            self.stats.stale_fragments += 1;
+            return None;
+        }
+        // parse_packet guarantees these; packets built by hand may not.
+        if packet.frag_count == 0 || packet.frag_count > MAX_FRAGMENTS || packet.frag_index >= packet.frag_count {
+            self.stats.inconsistent += 1;
+            return None;
+        }
+
+        let payload_len = packet.payload.len();
+        let incoming = match self.frames.get(&seq) {
+            Some(entry) if entry.fragments.len() != packet.frag_count as usize => {
+                debug!(
+                    "Rejected fragment seq={}: frag_count {} != {}",
+                    seq, packet.frag_count, entry.fragments.len()
+                );
+                self.stats.inconsistent += 1;
+                return None;
+            }
+            Some(entry) if entry.fragments[packet.frag_index as usize].is_some() => {
+                self.stats.duplicate_fragments += 1;
+                return None;
+            }
+            Some(_) => payload_len,
+            None => packet.frag_count as usize * SLOT_COST + payload_len,
+        };
+        if !self.make_room(seq, incoming) {
+            return None;
+        }
+
+        let entry = self.frames.entry(seq).or_insert_with(|| PartialFrame::new(&packet, now));
+        // A fragment completing its frame is assembled right away; any other
+        // is held, and must not pin the slab it was received into.
+        let payload = if entry.received_count as usize + 1 == entry.fragments.len() {
+            packet.payload
+        } else {
+            Bytes::copy_from_slice(&packet.payload)
+        };
+        entry.fragments[packet.frag_index as usize] = Some(payload);
+        entry.nal_ends[packet.frag_index as usize] = packet.nal_end;
+        entry.received_count += 1;
+        self.buffered += incoming;
+        entry.is_keyframe |= packet.is_keyframe;
+        if !entry.is_complete() {
+            return None;
+        }
+
+        let partial = self.remove(seq)?;
+        if self.completed.len() == COMPLETED_MEMORY {
+            self.completed.pop_front();
+        }
+        self.completed.push_back(seq);
+
+        let timestamp = partial.timestamp;
+        let received = (partial.first_seen, now);
+        let is_keyframe = partial.is_keyframe;
+        let checksum = partial.checksum;
+        let temporal_layer = partial.temporal_layer;
+        let released = partial.released.1;
+        let data = partial.assemble(&mut self.pool);
+        if let Some(expected) = checksum {
+            let actual = frame_checksum(&data);
+            if actual != expected {
+                warn!("Dropped corrupted frame seq={}: checksum {:04x} != {:04x}", seq, actual, expected);
+                self.stats.corrupted += 1;
+                return None;
+            }
+        }
+        debug!("Assembled frame seq={} {} bytes keyframe={}", seq, data.len(), is_keyframe);
+
+        Some(AssembledFrame {
+            seq,
+            timestamp,
+            frame: EncodedFrame {
+                data,
+                timestamp_us: timestamp.raw_us(),
+                is_keyframe,
+                codec: VideoCodec::H264,
+                temporal_layer,
+                partial: false,
+            },
+            received,
+            released,
+        })
+    }
+
+    /// Drop partial frames older than [`REASSEMBLY_TIMEOUT`]; the sequence
+    /// tracker counts them as lost.
+    fn evict(&mut self, now: Instant) {
+        let evicted = &mut self.stats.evicted;
+        let buffered = &mut self.buffered;
+        self.frames.retain(|seq, f| {
+            let keep = now.saturating_duration_since(f.first_seen) <= REASSEMBLY_TIMEOUT;
+            if !keep {
+                warn!("Dropped stale partial frame seq={}", seq);
+                *evicted += 1;
+                *buffered -= f.cost();
+            }
+            keep
+        });
+    }
+
+    /// Evict the oldest partial frames other than `seq` until `incoming`
+    /// more bytes (and, for a new frame, one more entry) fit the budget.
+    /// Returns `false` if they cannot fit even then.
+    fn make_room(&mut self, seq: u32, incoming: usize) -> bool {
+        let is_new = !self.frames.contains_key(&seq);
+        loop {
+            let frames_ok = !is_new || self.frames.len() < self.budget.max_partial_frames;
+            if frames_ok && self.buffered + incoming <= self.budget.max_buffered_bytes {
+                return true;
+            }
+            let oldest = self
+                .frames
+                .iter()
+                .filter(|(s, _)| **s != seq)
+                .min_by_key(|(_, f)| f.first_seen)
+                .map(|(s, _)| *s);
+            let Some(victim) = oldest.or((!is_new).then_some(seq)) else {
+                warn!("Dropped fragment of seq={}: {} bytes exceed the reassembly budget", seq, incoming);
+                self.stats.budget_evicted += 1;
+                return false;
+            };
+            warn!("Evicted partial frame seq={} to stay within the reassembly budget", victim);
+            self.remove(victim);
+            self.stats.budget_evicted += 1;
+            if victim == seq {
+                return false;
+            }
+        }
+    }
+
+    fn remove(&mut self, seq: u32) -> Option<PartialFrame> {
+        let partial = self.frames.remove(&seq)?;
+        self.buffered -= partial.cost();
+        Some(partial)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn fragments(seq: u32, data: &[u8], chunk: usize) -> Vec<DualLinkPacket> {
+        let chunks: Vec<&[u8]> = data.chunks(chunk.max(1)).collect();
+        let count = chunks.len().max(1) as u16;
+        let chunks = if chunks.is_empty() { vec![&[][..]] } else { chunks };
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| DualLinkPacket {
+                frame_seq: seq,
+                frag_index: i as u16,
+                frag_count: count,
+                timestamp: Timestamp::V1 { pts_ms: 7 },
+                is_keyframe: i == 0,
+                display_index: 0,
+                checksum: None,
+                temporal_layer: 0,
+                nal_end: false,
+                payload: Bytes::copy_from_slice(c),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rejects_inconsistent_headers() {
+        let mut p = fragments(1, b"abcdef", 2)[0].encode().to_vec();
+        assert_eq!(parse_packet(&p[..HEADER_SIZE - 1]), Err(PacketError::TooShort(HEADER_SIZE - 1)));
+        p[10..12].copy_from_slice(&0u16.to_be_bytes());
+        assert_eq!(parse_packet(&p), Err(PacketError::NoFragments));
+        p[8..10].copy_from_slice(&3u16.to_be_bytes());
+        p[10..12].copy_from_slice(&3u16.to_be_bytes());
+        assert_eq!(parse_packet(&p), Err(PacketError::IndexOutOfRange { index: 3, count: 3 }));
+
+        let mut v2 = fragments(1, b"abcdef", 2)[0].clone();
+        v2.timestamp = Timestamp::V2 { pts_us: 1 << 40, clock_epoch: 9 };
+        let v2 = v2.encode();
+        assert_eq!(parse_packet(&v2[..HEADER_SIZE_V2 - 1]), Err(PacketError::TooShort(HEADER_SIZE_V2 - 1)));
+        assert_eq!(parse_packet(&v2).unwrap().payload, &b"ab"[..]);
+    }
+
+    #[test]
+    fn count_change_duplicates_and_stale_fragments() {
+        let mut r = FrameReassembler::default();
+        let frags = fragments(u32::MAX, b"abcdef", 2);
+        assert!(r.push(frags[0].clone()).is_none());
+
+        // Same seq claiming a different fragment count.
+        let mut odd = frags[1].clone();
+        odd.frag_count = 4;
+        assert!(r.push(odd).is_none());
+        // Duplicate of fragment 0 with a different payload size.
+        let mut dup = frags[0].clone();
+        dup.payload = Bytes::from_static(b"xyz!");
+        assert!(r.push(dup).is_none());
+
+        assert!(r.push(frags[1].clone()).is_none());
+        let done = r.push(frags[2].clone()).unwrap();
+        assert_eq!((done.seq, &done.frame.data[..], done.frame.is_keyframe), (u32::MAX, &b"abcdef"[..], true));
+
+        // A retransmitted fragment must not start a new partial frame.
+        assert!(r.push(frags[1].clone()).is_none());
+        // Wrap-around: seq 0 after u32::MAX is just another frame.
+        assert!(r.push(fragments(0, b"gh", 8)[0].clone()).is_some());
+
+        let s = r.stats();
+        assert_eq!((s.inconsistent, s.duplicate_fragments, s.stale_fragments), (1, 1, 1));
+    }
+
+    #[test]
+    fn evicts_partial_frames() {
+        let mut r = FrameReassembler::default();
+        let t0 = Instant::now();
+        let frags = fragments(5, b"abcdef", 2);
+        r.push_at(frags[0].clone(), t0);
+        r.push_at(fragments(6, b"x", 8)[0].clone(), t0 + REASSEMBLY_TIMEOUT * 2);
+        assert_eq!(r.stats().evicted, 1);
+        assert!(r.push_at(frags[1].clone(), t0 + REASSEMBLY_TIMEOUT * 2).is_none());
+    }
+
+    #[test]
+    fn budget_evicts_oldest_first() {
+        let budget = ReassemblyBudget { max_partial_frames: 2, max_buffered_bytes: 6 * SLOT_COST + 6 };
+        let mut r = FrameReassembler::new(budget);
+        let t0 = Instant::now();
+        let a = fragments(1, b"aa", 1);
+        let b = fragments(2, b"bb", 1);
+        r.push_at(a[0].clone(), t0);
+        r.push_at(b[0].clone(), t0 + Duration::from_millis(1));
+        // Third partial frame: over max_partial_frames, frame 1 goes.
+        r.push_at(fragments(3, b"cc", 1)[0].clone(), t0 + Duration::from_millis(2));
+        assert_eq!(r.stats().budget_evicted, 1);
+        assert_eq!(r.push_at(b[1].clone(), t0 + Duration::from_millis(3)).map(|f| f.seq), Some(2));
+        assert!(r.push_at(a[1].clone(), t0 + Duration::from_millis(4)).is_none());
+        assert_eq!(r.stats().budget_evicted, 1);
+
+        // A frame announcing more fragments than the whole budget never fits.
+        let mut huge = fragments(9, b"x", 1)[0].clone();
+        huge.frag_count = MAX_FRAGMENTS;
+        assert!(r.push(huge).is_none());
+        assert!(r.buffered_bytes() <= budget.max_buffered_bytes);
+    }
+
+    #[test]
+    fn held_fragments_do_not_pin_receive_slabs() {
+        const SLAB: usize = 256 << 10;
+        let budget = ReassemblyBudget::default();
+        let mut r = FrameReassembler::new(budget);
+        // One held fragment per slab: 64 × 256 KiB of slabs for 64 × 1 KiB of payload.
+        let slabs: Vec<Bytes> = (0..budget.max_partial_frames as u32)
+            .map(|seq| {
+                let slab = Bytes::from(vec![seq as u8; SLAB]);
+                let mut packet = fragments(seq, b"ab", 1)[0].clone();
+                packet.payload = slab.slice(..1024);
+                assert!(r.push(packet).is_none());
+                slab
+            })
+            .collect();
+        assert_eq!(r.frames.len(), slabs.len());
+        assert!(slabs.iter().all(Bytes::is_unique), "the reassembler holds a slab");
+        assert!(r.buffered_bytes() <= budget.max_buffered_bytes);
+        assert!(r.buffered_bytes() < slabs.len() * 2048);
+
+        // A completing fragment may be used in place; the frame is assembled anyway.
+        let slab = Bytes::from(vec![7u8; SLAB]);
+        let mut last = fragments(0, b"ab", 1)[1].clone();
+        last.payload = slab.slice(..1);
+        let done = r.push(last).unwrap();
+        assert_eq!(done.frame.data.len(), 1025);
+        drop(done);
+        assert!(slab.is_unique());
+    }
+
+    #[test]
+    fn drops_frames_failing_their_checksum() {
+        let mut r = FrameReassembler::default();
+        let stamp = |seq, data: &[u8], sum| {
+            let mut frags = fragments(seq, data, 2);
+            for f in &mut frags {
+                f.timestamp = Timestamp::V2 { pts_us: 1, clock_epoch: 1 };
+                f.checksum = Some(sum);
+            }
+            frags
+        };
+        let good = stamp(1, b"abcdef", frame_checksum(b"abcdef"));
+        // Parsed back from the wire, then reassembled.
+        let good: Vec<_> = good.iter().map(|f| parse_datagram(f.encode()).unwrap()).collect();
+        assert_eq!(good[0].checksum, Some(frame_checksum(b"abcdef")));
+        assert!(good.into_iter().filter_map(|f| r.push(f)).any(|f| &f.frame.data[..] == b"abcdef"));
+
+        // One flipped bit in transit.
+        let mut bad = stamp(2, b"abcdef", frame_checksum(b"abcdef"));
+        bad[1].payload = Bytes::from_static(b"cD");
+        assert!(bad.into_iter().filter_map(|f| r.push(f)).next().is_none());
+        assert_eq!(r.stats().corrupted, 1);
+    }
+
+    #[test]
+    fn hands_out_complete_nal_units_early() {
+        let mut r = FrameReassembler::default();
+        let mut frags = fragments(3, b"spsppsslice", 3);
+        // NAL units "sps", "pps" and "slice" (two fragments).
+        for (f, end) in frags.iter_mut().zip([true, true, false, true]) {
+            f.timestamp = Timestamp::V2 { pts_us: 1, clock_epoch: 1 };
+            f.nal_end = end;
+        }
+        assert!(r.take_slices(3).is_none());
+        r.push(frags[1].clone());
+        // "pps" is complete, but "sps" in front of it isn't here yet.
+        assert!(r.take_slices(3).is_none());
+        r.push(frags[0].clone());
+        assert_eq!(r.take_slices(3).as_deref(), Some(&b"spspps"[..]));
+        assert!(r.take_slices(3).is_none());
+        r.push(frags[2].clone());
+        assert!(r.take_slices(3).is_none());
+        let done = r.push(frags[3].clone()).unwrap();
+        assert_eq!((&done.frame.data[..], done.released), (&b"spsppsslice"[..], 6));
+
+        // Not with a checksum, which only the whole frame can be checked against.
+        for f in &mut frags {
+            f.frame_seq = 4;
+            f.checksum = Some(frame_checksum(b"spsppsslice"));
+        }
+        r.push(frags[0].clone());
+        assert!(r.take_slices(4).is_none());
+    }
+
+    proptest! {
+        #[test]
+        fn parse_never_panics(buf in proptest::collection::vec(any::<u8>(), 0..64)) {
+            let _ = parse_packet(&buf);
+        }
+
+        #[test]
+        fn encode_parse_round_trip(
+            seq in any::<u32>(),
+            count in 1..=MAX_FRAGMENTS,
+            index in any::<u16>(),
+            pts in any::<u64>(),
+            epoch in any::<Option<u32>>(),
+            key in any::<bool>(),
+            display in any::<u8>(),
+            checksum in any::<Option<u16>>(),
+            layer in 0u8..4,
+            nal_end in any::<bool>(),
+            payload in proptest::collection::vec(any::<u8>(), 0..32),
+        ) {
+            let packet = DualLinkPacket {
+                frame_seq: seq,
+                frag_index: index % count,
+                frag_count: count,
+                timestamp: match epoch {
+                    Some(clock_epoch) => Timestamp::V2 { pts_us: pts, clock_epoch },
+                    None => Timestamp::V1 { pts_ms: pts as u32 },
+                },
+                is_keyframe: key,
+                display_index: display,
+                // v1 headers cannot carry one.
+                checksum: epoch.and(checksum),
+                temporal_layer: layer,
+                nal_end: epoch.is_some() && nal_end,
+                payload: payload.into(),
+            };
+            prop_assert_eq!(parse_packet(&packet.encode()), Ok(packet));
+        }
+
+        /// Any order, with any fragments repeated, yields the frame once.
+        #[test]
+        fn reassembles_shuffled_and_duplicated_fragments(
+            data in proptest::collection::vec(any::<u8>(), 1..256),
+            chunk in 1usize..64,
+            order in proptest::collection::vec(any::<prop::sample::Index>(), 0..32),
+            seed in any::<u64>(),
+        ) {
+            let frags = fragments(42, &data, chunk);
+            let mut feed: Vec<DualLinkPacket> = frags.clone();
+            feed.extend(order.iter().map(|i| i.get(&frags).clone()));
+            // Deterministic shuffle driven by `seed`.
+            let mut state = seed | 1;
+            for i in (1..feed.len()).rev() {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                feed.swap(i, (state % (i as u64 + 1)) as usize);
+            }
+
+            let mut r = FrameReassembler::default();
+            let out: Vec<_> = feed.into_iter().filter_map(|p| r.push(p)).collect();
+            prop_assert_eq!(out.len(), 1);
+            prop_assert_eq!(&out[0].frame.data[..], &data[..]);
+        }
+
+        /// Arbitrary packet soup: no panics, and every frame is well-formed.
+        #[test]
+        fn survives_arbitrary_packets(
+            packets in proptest::collection::vec(
+                (0u32..4, 0u16..8, 1u16..8, proptest::collection::vec(any::<u8>(), 0..8)),
+                0..64,
+            ),
+        ) {
+            let budget = ReassemblyBudget { max_partial_frames: 3, max_buffered_bytes: 16 * SLOT_COST };
+            let mut r = FrameReassembler::new(budget);
+            for (seq, index, count, payload) in packets {
+                let packet = DualLinkPacket {
+                    frame_seq: seq,
+                    frag_index: index,
+                    frag_count: count,
+                    timestamp: Timestamp::V1 { pts_ms: 0 },
+                    is_keyframe: false,
+                    display_index: 0,
+                    checksum: None,
+                    temporal_layer: 0,
+                    nal_end: false,
+                    payload: payload.into(),
+                };
+                if let Some(done) = r.push(packet) {
+                    prop_assert!(done.seq < 4);
+                    prop_assert!(done.frame.data.len() <= 8 * 8);
+                }
+                prop_assert!(r.buffered_bytes() <= budget.max_buffered_bytes);
+                prop_assert_eq!(r.buffered_bytes(), r.frames.values().map(PartialFrame::cost).sum::<usize>());
+            }
+        }
+    }
+}
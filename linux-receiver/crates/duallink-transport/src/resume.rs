@@ -0,0 +1,72 @@
+//! Resume tokens handed out in `hello_ack` and redeemed in `hello` (see
+//! [`duallink_core::resume`]).
+
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use duallink_core::{ResumeEntry, ResumeTokens};
+use tracing::warn;
+
+/// The receiver's resume tokens, shared by every display.
+pub(crate) struct Resumption {
+    /// `ZERO` = resumption off.
+    ttl:    Duration,
+    tokens: Mutex<ResumeTokens>,
+}
+
+impl Resumption {
+    /// The saved tokens, valid for `ttl`.
+    pub(crate) fn load(ttl: Duration) -> Self {
+        let tokens = if ttl.is_zero() { ResumeTokens::default() } else { ResumeTokens::load() };
+        Self { ttl, tokens: Mutex::new(tokens) }
+    }
+
+    /// A fresh token for `session_id` on `display_index`; `None` with
+    /// resumption off or without a random source.
+    pub(crate) fn issue(&self, session_id: &str, device_name: &str, display_index: u8) -> Option<String> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+        let mut bytes = [0u8; 16];
+        if rustls::crypto::ring::default_provider().secure_random.fill(&mut bytes).is_err() {
+            warn!("No random source — session not resumable");
+            return None;
+        }
+        let token: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+        let entry = ResumeEntry {
+            session_id: session_id.to_owned(),
+            device_name: device_name.to_owned(),
+            display_index,
+            issued_at: unix_now(),
+        };
+        let mut tokens = self.tokens.lock().unwrap();
+        tokens.insert(token.clone(), entry, self.ttl);
+        save(&tokens);
+        Some(token)
+    }
+
+    /// The session `token` was issued for, if it is still good for
+    /// `display_index`. The token is used up either way.
+    pub(crate) fn redeem(&self, token: &str, display_index: u8) -> Option<ResumeEntry> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+        let mut tokens = self.tokens.lock().unwrap();
+        let before = tokens.len();
+        let entry = tokens.redeem(token, display_index, self.ttl, unix_now());
+        if tokens.len() != before {
+            save(&tokens);
+        }
+        entry
+    }
+}
+
+fn save(tokens: &ResumeTokens) {
+    if let Err(e) = tokens.save() {
+        warn!("Saving resume tokens: {}", e);
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
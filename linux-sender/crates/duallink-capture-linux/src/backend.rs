@@ -0,0 +1,202 @@
+//! Capture backends and the registry that picks one at runtime.
+//!
+//! Every source of raw frames implements [`CaptureBackend`]; [`Backend`]
+//! names them, probes whether each can run on this machine and opens the
+//! chosen one — opening lives on [`Backend`] so the trait stays object-safe.
+//! [`ScreenCapturer`](crate::ScreenCapturer) goes through here, so a new
+//! source (XShm, …) is one more [`Backend`] variant.
+//!
+//! Without a forced backend, [`select`] takes the first of
+//! [`Backend::AUTO`] that probes available — [`Backend::Screencopy`] on
+//! wlroots compositors, so they skip the portal prompt, else
+//! [`Backend::PipeWire`]. [`Backend::TestPattern`] is never
+//! picked automatically — it is for CI and for checking a receiver without a
+//! screen-cast prompt, and has to be asked for (`DUALLINK_CAPTURE_BACKEND=test`
+//! in the sender).
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::Result;
+use tracing::{debug, info};
+
+use crate::{CaptureConfig, CapturedFrame, PixelFormat};
+
+/// Future of the next frame from a boxed [`CaptureBackend`].
+pub type FrameFuture<'a> = Pin<Box<dyn Future<Output = Option<CapturedFrame>> + Send + 'a>>;
+
+/// A running source of raw frames.
+pub trait CaptureBackend: Send {
+    /// What this source delivers.
+    fn caps(&self) -> BackendCaps;
+
+    /// The next captured frame; `None` once the source has ended.
+    fn next_frame(&mut self) -> FrameFuture<'_>;
+
+    /// Deliver at most `fps` frames per second (at most the opened rate).
+    fn set_max_fps(&self, fps: u32);
+}
+
+/// What a backend can do, for callers choosing how to use it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackendCaps {
+    /// Pixel formats frames may arrive in.
+    pub formats:     &'static [PixelFormat],
+    /// Opening shows a permission prompt (screen-cast portal).
+    pub interactive: bool,
+    /// Frames show the real screen rather than a synthetic picture.
+    pub live:        bool,
+}
+
+// ── Registry ──────────────────────────────────────────────────────────────────
+
+/// A capture backend, by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// XDG desktop portal + PipeWire, on Wayland and X11.
+    PipeWire,
+    /// `wlr-screencopy` straight from a wlroots compositor; no portal.
+    Screencopy,
+    /// Moving colour bars generated in-process; needs no display server.
+    TestPattern,
+}
+
+impl Backend {
+    /// Every backend, in probing order.
+    pub const ALL: [Backend; 3] = [Backend::Screencopy, Backend::PipeWire, Backend::TestPattern];
+
+    /// Backends [`select`] may pick without being asked to, in order.
+    pub const AUTO: [Backend; 2] = [Backend::Screencopy, Backend::PipeWire];
+
+    /// Parse `"pipewire"` / `"screencopy"` / `"test"` (case-insensitive).
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "pipewire" | "portal" => Some(Self::PipeWire),
+            "screencopy" | "wlr" => Some(Self::Screencopy),
+            "test" | "test-pattern" => Some(Self::TestPattern),
+            _ => None,
+        }
+    }
+
+    /// Short name, as accepted by [`Backend::from_name`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PipeWire => "pipewire",
+            Self::Screencopy => "screencopy",
+            Self::TestPattern => "test",
+        }
+    }
+
+    pub fn caps(self) -> BackendCaps {
+        match self {
+            Self::PipeWire => BackendCaps {
+                formats:     &[PixelFormat::Bgrx, PixelFormat::Nv12],
+                interactive: true,
+                live:        true,
+            },
+            Self::Screencopy => BackendCaps { formats: &[PixelFormat::Bgrx], interactive: false, live: true },
+            Self::TestPattern => BackendCaps { formats: &[PixelFormat::Bgrx], interactive: false, live: false },
+        }
+    }
+
+    /// Whether this backend can run here; `Err` says why not. Never prompts.
+    pub async fn probe(self) -> Result<(), String> {
+        match self {
+            Self::PipeWire => probe_pipewire().await,
+            Self::Screencopy => probe_screencopy().await,
+            Self::TestPattern => Ok(()),
+        }
+    }
+
+    /// Open this backend for `config`.
+    pub async fn open(self, config: CaptureConfig) -> Result<Box<dyn CaptureBackend>> {
+        match self {
+            Self::PipeWire => open_pipewire(config).await,
+            Self::Screencopy => open_screencopy(config).await,
+            Self::TestPattern => Ok(Box::new(crate::test_pattern::TestPattern::new(&config))),
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Probe every backend, in [`Backend::ALL`] order.
+pub async fn probe_all() -> Vec<(Backend, Result<(), String>)> {
+    let mut results = Vec::with_capacity(Backend::ALL.len());
+    for backend in Backend::ALL {
+        results.push((backend, backend.probe().await));
+    }
+    results
+}
+
+/// The backend to open: `forced` if set (without probing — opening reports
+/// why it fails), else the first available of [`Backend::AUTO`].
+pub async fn select(forced: Option<Backend>) -> Result<Backend> {
+    if let Some(backend) = forced {
+        info!("Capture backend {} (forced)", backend);
+        return Ok(backend);
+    }
+    let mut reasons = Vec::new();
+    for backend in Backend::AUTO {
+        match backend.probe().await {
+            Ok(()) => {
+                info!("Capture backend {}", backend);
+                return Ok(backend);
+            }
+            Err(why) => {
+                debug!("Capture backend {} unavailable: {}", backend, why);
+                reasons.push(format!("{backend}: {why}"));
+            }
+        }
+    }
+    anyhow::bail!("No capture backend available ({})", reasons.join("; "))
+}
+
+// ── PipeWire ──────────────────────────────────────────────────────────────────
+
+#[cfg(target_os = "linux")]
+async fn probe_pipewire() -> Result<(), String> {
+    crate::linux::probe_portal().await
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn probe_pipewire() -> Result<(), String> {
+    Err("PipeWire capture is only available on Linux".to_owned())
+}
+
+#[cfg(target_os = "linux")]
+async fn open_pipewire(config: CaptureConfig) -> Result<Box<dyn CaptureBackend>> {
+    Ok(Box::new(crate::linux::LinuxCapturer::open(config).await?))
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn open_pipewire(_config: CaptureConfig) -> Result<Box<dyn CaptureBackend>> {
+    anyhow::bail!("PipeWire capture is only available on Linux")
+}
+
+// ── Screencopy ────────────────────────────────────────────────────────────────
+
+#[cfg(all(target_os = "linux", feature = "screencopy"))]
+async fn probe_screencopy() -> Result<(), String> {
+    tokio::task::spawn_blocking(crate::screencopy::probe).await.map_err(|e| e.to_string())?
+}
+
+#[cfg(not(all(target_os = "linux", feature = "screencopy")))]
+async fn probe_screencopy() -> Result<(), String> {
+    Err("built without the screencopy feature".to_owned())
+}
+
+#[cfg(all(target_os = "linux", feature = "screencopy"))]
+async fn open_screencopy(config: CaptureConfig) -> Result<Box<dyn CaptureBackend>> {
+    Ok(Box::new(crate::screencopy::Screencopy::open(config).await?))
+}
+
+#[cfg(not(all(target_os = "linux", feature = "screencopy")))]
+async fn open_screencopy(_config: CaptureConfig) -> Result<Box<dyn CaptureBackend>> {
+    anyhow::bail!("Screencopy capture needs Linux and the screencopy feature")
+}
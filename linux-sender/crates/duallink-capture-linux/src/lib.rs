@@ -5,14 +5,21 @@
 //! | Backend | Protocol | Status |
 //! |---------|---------|--------|
 //! | PipeWire (ashpd + GStreamer) | Wayland + X11 via portal | Phase 5C ✓ |
+//! | Screencopy | wlroots `wlr-screencopy`, no portal | ✓ |
+//! | Test pattern (moving bars) | none — CI / headless | ✓ |
 //! | X11 XShm | X11 only | Planned Phase 6 |
 //!
+//! Each backend implements [`CaptureBackend`]. [`ScreenCapturer::open`]
+//! opens [`CaptureConfig::backend`] when set, otherwise the first backend
+//! that probes available (see [`backend::select`]); [`backend::probe_all`]
+//! reports what this machine supports.
+//!
 //! # Usage
 //!
 //! ```rust,no_run
 //! # async fn example() -> anyhow::Result<()> {
 //! use duallink_capture_linux::{CaptureConfig, ScreenCapturer};
-//! let cfg = CaptureConfig { display_index: 0, width: 1920, height: 1080, fps: 60 };
+//! let cfg = CaptureConfig { display_index: 0, width: 1920, height: 1080, fps: 60, ..Default::default() };
 //! let mut capturer = ScreenCapturer::open(cfg).await?;
 //! while let Some(frame) = capturer.next_frame().await {
 //!     // frame.data: Vec<u8> BGRx raw pixels (4 bytes/px, X byte unused)
@@ -32,15 +39,47 @@
 //!                          │
 //!                    videoconvert
 //!                          │
-//!               video/x-raw,format=BGRx
+//!               video/x-raw,format=BGRx        (or {NV12,BGRx} with prefer_nv12)
 //!                          │
 //!                       appsink  ─────► tokio channel ──► next_frame()
 //! ```
+//!
+//! # NV12-native mode
+//!
+//! With [`CaptureConfig::prefer_nv12`] the appsink accepts either NV12 or BGRx.
+//! When the compositor offers NV12 over PipeWire, `videoconvert` negotiates
+//! passthrough and the encoder consumes the frame as-is — saving one
+//! full-frame colour conversion per frame. Otherwise it converts to BGRx as
+//! before. The negotiated format is reported per frame in
+//! [`CapturedFrame::format`].
+//!
+//! # Monitor selection
+//!
+//! [`list_monitors`] enumerates the local monitors by connector name. With
+//! [`CaptureConfig::monitor`] set, the portal is asked for all monitors and
+//! the stream whose position matches that monitor is captured; otherwise the
+//! `display_index`-th stream is used. The screencopy backend matches the
+//! name against the compositor's `wl_output` names instead.
+//!
+//! # Portal pre-flight
+//!
+//! [`check_portal`] tells a UI whether PipeWire capture can work before a
+//! session starts, and which portal backend package is missing if not (see
+//! [`portal`]).
 
 #![allow(unused_variables, dead_code)]
 
+pub mod backend;
+pub mod portal;
+#[cfg(all(target_os = "linux", feature = "screencopy"))]
+mod screencopy;
+mod test_pattern;
+
 use anyhow::Result;
-use tracing::warn;
+use duallink_core::{detect_monitors, MonitorInfo};
+
+pub use backend::{Backend, BackendCaps, CaptureBackend, FrameFuture};
+pub use portal::{check_portal, portal_package, PortalCheck, PortalProblem};
 
 // ── Public types ──────────────────────────────────────────────────────────────
 
@@ -53,18 +92,44 @@ pub struct CaptureConfig {
     pub height: u32,
     /// Target capture frame rate.
     pub fps: u32,
+    /// Accept NV12 straight from PipeWire when the compositor provides it,
+    /// instead of always converting to BGRx.
+    pub prefer_nv12: bool,
+    /// Connector name of the monitor to capture (from [`list_monitors`]);
+    /// `None` picks the `display_index`-th portal stream or output.
+    pub monitor: Option<String>,
+    /// Backend to capture with; `None` picks one by probing.
+    pub backend: Option<Backend>,
+    /// Captured frames held before the oldest is dropped (see
+    /// [`SenderQueues::captured`](duallink_core::SenderQueues::captured)).
+    pub buffers: usize,
 }
 
 impl Default for CaptureConfig {
     fn default() -> Self {
-        Self { display_index: 0, width: 1920, height: 1080, fps: 60 }
+        Self {
+            display_index: 0,
+            width: 1920,
+            height: 1080,
+            fps: 60,
+            prefer_nv12: true,
+            monitor: None,
+            backend: None,
+            buffers: duallink_core::SenderQueues::default().captured,
+        }
     }
 }
 
+/// Local monitors available for capture, primary first.
+pub fn list_monitors() -> Vec<MonitorInfo> {
+    detect_monitors()
+}
+
 /// A raw captured video frame.
 #[derive(Debug)]
 pub struct CapturedFrame {
-    /// Pixel data — BGRx (4 bytes per pixel, X byte unused on Linux).
+    /// Pixel data — BGRx (4 bytes per pixel, X byte unused on Linux) or
+    /// NV12 (Y plane followed by interleaved UV plane), see `format`.
     pub data:   Vec<u8>,
     /// Presentation timestamp in milliseconds.
     pub pts_ms: u64,
@@ -74,6 +139,8 @@ pub struct CapturedFrame {
     pub width:  u32,
     /// Frame height in pixels.
     pub height: u32,
+    /// Bytes per row of the first plane (Y for NV12), row padding included.
+    pub stride: u32,
 }
 
 /// Pixel format of a captured frame.
@@ -85,57 +152,131 @@ pub enum PixelFormat {
     Nv12,
 }
 
+impl PixelFormat {
+    /// GStreamer `video/x-raw` format string.
+    pub fn gst_format(self) -> &'static str {
+        match self {
+            PixelFormat::Bgrx => "BGRx",
+            PixelFormat::Nv12 => "NV12",
+        }
+    }
+
+    /// Bytes per row of the first plane of an unpadded frame `width` wide.
+    pub fn row_bytes(self, width: u32) -> u32 {
+        match self {
+            PixelFormat::Bgrx => width * 4,
+            PixelFormat::Nv12 => width,
+        }
+    }
+
+    /// Parse a GStreamer `video/x-raw` format string.
+    pub fn from_gst_format(s: &str) -> Option<Self> {
+        match s {
+            "BGRx" => Some(PixelFormat::Bgrx),
+            "NV12" => Some(PixelFormat::Nv12),
+            _ => None,
+        }
+    }
+}
+
+// ── PipeWire stream (for fused capture → encode pipelines) ────────────────────
+
+/// A PipeWire screen-cast stream granted by the XDG desktop portal.
+///
+/// Used by callers that build their own GStreamer pipeline around
+/// `pipewiresrc` (e.g. the sender's fused capture → encode mode) instead of
+/// pulling raw frames through [`ScreenCapturer`].
+#[derive(Debug, Clone, Copy)]
+pub struct PipeWireStream {
+    /// PipeWire node id of the selected monitor stream.
+    pub node_id: u32,
+    /// PipeWire remote file descriptor (owned by the pipeline once launched).
+    pub fd:      i32,
+}
+
+impl PipeWireStream {
+    /// `pipewiresrc` element description for embedding in a pipeline string.
+    pub fn source_desc(&self) -> String {
+        format!("pipewiresrc fd={} path={} do-timestamp=true", self.fd, self.node_id)
+    }
+}
+
+/// Negotiate a screen-cast stream with the portal without starting capture.
+///
+/// On Wayland this shows the same permission dialog as [`ScreenCapturer::open`].
+pub async fn open_pipewire_stream(config: &CaptureConfig) -> Result<PipeWireStream> {
+    #[cfg(target_os = "linux")]
+    {
+        let (node_id, fd) = linux::negotiate_portal(config).await?;
+        Ok(PipeWireStream { node_id, fd })
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        anyhow::bail!("PipeWire capture is only available on Linux")
+    }
+}
+
 // ── ScreenCapturer ────────────────────────────────────────────────────────────
 
 /// Screen capturer handle.  Open with [`ScreenCapturer::open`].
 pub struct ScreenCapturer {
-    config: CaptureConfig,
-    #[cfg(target_os = "linux")]
-    inner: linux::LinuxCapturer,
+    config:  CaptureConfig,
+    backend: Backend,
+    inner:   Box<dyn CaptureBackend>,
 }
 
 impl ScreenCapturer {
-    /// Open a PipeWire screen-capture session.
+    /// Open a screen-capture session on [`CaptureConfig::backend`], or on
+    /// the first backend that probes available.
     ///
-    /// On Wayland this shows an XDG portal permission dialog.
+    /// With PipeWire, on Wayland this shows an XDG portal permission dialog.
     /// Requires `xdg-desktop-portal` + a backend (`-wlr`, `-gnome`, `-kde`) running.
     pub async fn open(config: CaptureConfig) -> Result<Self> {
-        #[cfg(target_os = "linux")]
-        {
-            let inner = linux::LinuxCapturer::open(config.clone()).await?;
-            return Ok(Self { config, inner });
-        }
-        #[cfg(not(target_os = "linux"))]
-        {
-            warn!("ScreenCapturer::open — non-Linux platform, stub capturer");
-            Ok(Self { config })
-        }
+        let backend = backend::select(config.backend).await?;
+        let inner = backend.open(config.clone()).await?;
+        Ok(Self { config, backend, inner })
     }
 
     /// Await the next captured frame.  Returns `None` when the session ends.
     pub async fn next_frame(&mut self) -> Option<CapturedFrame> {
-        #[cfg(target_os = "linux")]
-        return self.inner.next_frame().await;
-        #[cfg(not(target_os = "linux"))]
-        {
-            warn!("ScreenCapturer::next_frame — stub, no frames produced");
-            None
-        }
+        self.inner.next_frame().await
     }
 
     /// Active configuration.
     pub fn config(&self) -> &CaptureConfig {
         &self.config
     }
+
+    /// Backend this capturer runs on.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// What the running backend delivers.
+    pub fn caps(&self) -> BackendCaps {
+        self.inner.caps()
+    }
+
+    /// Cap the delivered frame rate below `config.fps` (backpressure slowdown).
+    ///
+    /// PipeWire discards excess frames in the appsink callback before they
+    /// are copied out of GStreamer. `fps >= config.fps` removes the cap.
+    pub fn set_max_fps(&self, fps: u32) {
+        self.inner.set_max_fps(fps.min(self.config.fps));
+    }
 }
 
 // ── Linux implementation (PipeWire portal + GStreamer) ────────────────────────
 
 #[cfg(target_os = "linux")]
 mod linux {
-    use super::{CaptureConfig, CapturedFrame, PixelFormat};
+    use super::{list_monitors, Backend, BackendCaps, CaptureBackend, CaptureConfig, CapturedFrame, FrameFuture};
+    use super::{PipeWireStream, PixelFormat};
+    use crate::portal::{PortalProblem, PORTAL_BACKEND_PREFIX};
 
     use std::os::unix::io::IntoRawFd;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
 
     use anyhow::Context;
     use ashpd::desktop::screencast::{CaptureType, Persist, ScreenCast, SourceType};
@@ -143,12 +284,14 @@ mod linux {
     use gstreamer::prelude::*;
     use gstreamer_app::{AppSink, AppSinkCallbacks};
     use tokio::sync::mpsc;
-    use tracing::{debug, info, error};
+    use tracing::{debug, info, error, warn};
 
     // ── Public handle ─────────────────────────────────────────────────────────
 
     pub(super) struct LinuxCapturer {
         frame_rx:     mpsc::Receiver<CapturedFrame>,
+        /// Frame-rate cap applied in the appsink callback (shared).
+        max_fps:      Arc<AtomicU32>,
         _pipeline:    gstreamer::Pipeline,
         _bus_watcher: tokio::task::JoinHandle<()>,
     }
@@ -163,7 +306,8 @@ mod linux {
                 node_id, fd_raw, config.display_index
             );
 
-            let (pipeline, frame_rx) = build_pipeline(&config, fd_raw, node_id)?;
+            let max_fps = Arc::new(AtomicU32::new(config.fps));
+            let (pipeline, frame_rx) = build_pipeline(&config, fd_raw, node_id, Arc::clone(&max_fps))?;
             pipeline
                 .set_state(gstreamer::State::Playing)
                 .context("GStreamer set Playing")?;
@@ -192,21 +336,83 @@ mod linux {
                 let _ = pl.set_state(gstreamer::State::Null);
             });
 
-            Ok(Self { frame_rx, _pipeline: pipeline, _bus_watcher: bus_watcher })
+            Ok(Self { frame_rx, max_fps, _pipeline: pipeline, _bus_watcher: bus_watcher })
+        }
+    }
+
+    impl CaptureBackend for LinuxCapturer {
+        fn caps(&self) -> BackendCaps {
+            Backend::PipeWire.caps()
         }
 
-        pub(super) async fn next_frame(&mut self) -> Option<CapturedFrame> {
-            self.frame_rx.recv().await
+        fn next_frame(&mut self) -> FrameFuture<'_> {
+            Box::pin(self.frame_rx.recv())
         }
+
+        fn set_max_fps(&self, fps: u32) {
+            let prev = self.max_fps.swap(fps, Ordering::Relaxed);
+            if prev != fps {
+                info!("Capture frame-rate cap {} → {} fps", prev, fps);
+            }
+        }
+    }
+
+    // ── Probing ───────────────────────────────────────────────────────────────
+
+    /// Whether a screen-cast portal answers on the session bus and GStreamer
+    /// has `pipewiresrc` — without starting a session (no prompt).
+    pub(super) async fn probe_portal() -> Result<(), String> {
+        gstreamer::init().map_err(|e| format!("GStreamer init: {e}"))?;
+        check_screen_cast().await.map_err(|p| p.to_string())
+    }
+
+    /// [`probe_portal`] with the reason kept apart; GStreamer initialised.
+    pub(super) async fn check_screen_cast() -> Result<(), PortalProblem> {
+        if gstreamer::init().is_err() || gstreamer::ElementFactory::find("pipewiresrc").is_none() {
+            return Err(PortalProblem::NoPipeWirePlugin);
+        }
+        let proxy = ScreenCast::new().await.map_err(|e| PortalProblem::NoPortal(e.to_string()))?;
+        proxy.available_source_types().await.map_err(|e| PortalProblem::NoScreenCast(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Portal backends running or activatable on the session bus.
+    pub(super) async fn portal_backends() -> Vec<String> {
+        let names = async {
+            let conn = ashpd::zbus::Connection::session().await?;
+            let dbus = ashpd::zbus::fdo::DBusProxy::new(&conn).await?;
+            let mut names = dbus.list_names().await?;
+            names.extend(dbus.list_activatable_names().await?);
+            Ok::<_, ashpd::zbus::Error>(names)
+        };
+        let mut backends: Vec<String> = match names.await {
+            Ok(names) => names
+                .iter()
+                .filter_map(|n| n.as_str().strip_prefix(PORTAL_BACKEND_PREFIX).map(str::to_owned))
+                .collect(),
+            Err(e) => {
+                debug!("Listing portal backends: {}", e);
+                Vec::new()
+            }
+        };
+        backends.sort();
+        backends.dedup();
+        backends
     }
 
     // ── Portal negotiation ────────────────────────────────────────────────────
 
     /// Ask the XDG desktop portal for a PipeWire screen-cast stream.
     /// Returns `(node_id, raw_fd)`.
-    async fn negotiate_portal(config: &CaptureConfig) -> anyhow::Result<(u32, i32)> {
+    pub(super) async fn negotiate_portal(config: &CaptureConfig) -> anyhow::Result<(u32, i32)> {
         let proxy = ScreenCast::new().await.context("ScreenCast portal")?;
 
+        // A named monitor is matched by position among all shared monitors.
+        let target = config
+            .monitor
+            .as_deref()
+            .and_then(|name| list_monitors().into_iter().find(|m| m.name == name));
+
         let session = proxy
             .create_session()
             .await
@@ -217,7 +423,7 @@ mod linux {
                 &session,
                 CaptureType::SCREEN,
                 SourceType::MONITOR,
-                false,          // multiple
+                target.is_some(), // multiple
                 None,           // cursor_mode
                 Persist::DoNot,
             )
@@ -237,7 +443,13 @@ mod linux {
         }
 
         let idx = config.display_index as usize;
-        let stream = streams.get(idx).unwrap_or(&streams[0]);
+        let matched = target
+            .as_ref()
+            .and_then(|m| streams.iter().find(|s| s.position() == Some((m.x, m.y))));
+        if let (Some(name), None) = (&config.monitor, matched) {
+            warn!("Monitor {} not among the shared streams — using stream {}", name, idx);
+        }
+        let stream = matched.or_else(|| streams.get(idx)).unwrap_or(&streams[0]);
         let node_id = stream.pipe_wire_node_id();
 
         let fd = proxy
@@ -255,16 +467,22 @@ mod linux {
         config: &CaptureConfig,
         fd: i32,
         node_id: u32,
+        max_fps: Arc<AtomicU32>,
     ) -> anyhow::Result<(gstreamer::Pipeline, mpsc::Receiver<CapturedFrame>)> {
         let w   = config.width;
         let h   = config.height;
         let fps = config.fps;
+        let buffers = config.buffers;
+        // With a format list, videoconvert prefers the upstream format and
+        // runs in passthrough when PipeWire already delivers NV12.
+        let formats = if config.prefer_nv12 { "(string){NV12,BGRx}" } else { "BGRx" };
 
+        let source = PipeWireStream { node_id, fd }.source_desc();
         let desc = format!(
-            "pipewiresrc fd={fd} path={node_id} do-timestamp=true \
+            "{source} \
              ! videoconvert \
-             ! video/x-raw,format=BGRx,width={w},height={h},framerate={fps}/1 \
-             ! appsink name=sink max-buffers=2 drop=true sync=false emit-signals=false"
+             ! video/x-raw,format={formats},width={w},height={h},framerate={fps}/1 \
+             ! appsink name=sink max-buffers={buffers} drop=true sync=false emit-signals=false"
         );
         debug!("GStreamer pipeline: {}", desc);
 
@@ -280,6 +498,10 @@ mod linux {
             .map_err(|_| anyhow::anyhow!("Expected AppSink"))?;
 
         let (frame_tx, frame_rx) = mpsc::channel::<CapturedFrame>(8);
+        let display_index = config.display_index;
+        let mut logged_format = None;
+        // Next PTS (ms) at which a frame may be delivered under the max_fps cap.
+        let mut next_due_ms = 0u64;
 
         appsink.set_callbacks(
             AppSinkCallbacks::builder()
@@ -287,15 +509,50 @@ mod linux {
                     let sample = sink.pull_sample().map_err(|_| gstreamer::FlowError::Eos)?;
                     let buffer = sample.buffer().ok_or(gstreamer::FlowError::Error)?;
                     let pts_ms = buffer.pts().map(|t| t.mseconds()).unwrap_or(0);
+
+                    let cap = max_fps.load(Ordering::Relaxed);
+                    if cap > 0 && cap < fps {
+                        if pts_ms < next_due_ms {
+                            return Ok(gstreamer::FlowSuccess::Ok);
+                        }
+                        let interval = 1000 / cap as u64;
+                        // Re-anchor if we fell more than one interval behind.
+                        next_due_ms = if pts_ms > next_due_ms + interval {
+                            pts_ms + interval
+                        } else {
+                            next_due_ms + interval
+                        };
+                    }
+
+                    let format = sample
+                        .caps()
+                        .and_then(|c| c.structure(0))
+                        .and_then(|s| s.get::<&str>("format").ok())
+                        .and_then(PixelFormat::from_gst_format)
+                        .unwrap_or(PixelFormat::Bgrx);
+                    if logged_format != Some(format) {
+                        info!("Capture[{}] negotiated format {:?}", display_index, format);
+                        logged_format = Some(format);
+                    }
+                    // The buffer is copied as-is, so keep its row padding.
+                    let stride = buffer
+                        .meta::<gstreamer_video::VideoMeta>()
+                        .map(|meta| meta.stride()[0])
+                        .or_else(|| {
+                            let info = gstreamer_video::VideoInfo::from_caps(sample.caps()?).ok()?;
+                            Some(info.stride()[0])
+                        })
+                        .map_or(format.row_bytes(w), |s| s.unsigned_abs());
                     let map    = buffer.map_readable().map_err(|_| gstreamer::FlowError::Error)?;
                     let data   = map.as_slice().to_vec();
 
                     let frame  = CapturedFrame {
                         data,
                         pts_ms,
-                        format: PixelFormat::Bgrx,
+                        format,
                         width:  w,
                         height: h,
+                        stride,
                     };
 
                     if frame_tx.blocking_send(frame).is_err() {
@@ -0,0 +1,87 @@
+//! Screen-cast portal pre-flight.
+//!
+//! [`Backend::PipeWire`](crate::Backend::PipeWire) capture needs
+//! `xdg-desktop-portal` and a backend implementing its ScreenCast interface
+//! for the running desktop (`xdg-desktop-portal-gnome`, `-kde`, `-wlr`, …).
+//! When either is missing, the portal request only fails once a session
+//! starts, with a D-Bus error that names neither. [`check_portal`] finds
+//! out up front, without a prompt: whether screen casting can work, which
+//! portal backends are installed, and — through [`portal_package`] — which
+//! one the desktop needs.
+
+use std::fmt;
+
+/// D-Bus name prefix of portal backend implementations; the rest is the
+/// backend (`gnome`, `kde`, `wlr`, …).
+pub const PORTAL_BACKEND_PREFIX: &str = "org.freedesktop.impl.portal.desktop.";
+
+/// Why screen casting through the portal can't work.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortalProblem {
+    /// GStreamer has no `pipewiresrc`.
+    NoPipeWirePlugin,
+    /// No `xdg-desktop-portal` answers on the session bus.
+    NoPortal(String),
+    /// The portal answers but no backend offers screen casting.
+    NoScreenCast(String),
+}
+
+impl fmt::Display for PortalProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoPipeWirePlugin => {
+                f.write_str("GStreamer has no pipewiresrc (install the PipeWire GStreamer plugin)")
+            }
+            Self::NoPortal(e) => write!(f, "no screen-cast portal: {e}"),
+            Self::NoScreenCast(e) => write!(f, "screen-cast portal: {e}"),
+        }
+    }
+}
+
+/// What [`check_portal`] found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortalCheck {
+    /// `XDG_CURRENT_DESKTOP`, if set.
+    pub desktop:  Option<String>,
+    /// Portal backends running or activatable on the session bus (`gnome`,
+    /// `wlr`, …), sorted.
+    pub backends: Vec<String>,
+    /// Why screen casting won't work; `None` when it will.
+    pub problem:  Option<PortalProblem>,
+}
+
+impl PortalCheck {
+    pub fn is_ready(&self) -> bool {
+        self.problem.is_none()
+    }
+
+    /// The portal backend package the desktop needs, if known.
+    pub fn package(&self) -> Option<&'static str> {
+        self.desktop.as_deref().and_then(portal_package)
+    }
+}
+
+/// The portal backend package for an `XDG_CURRENT_DESKTOP` value (a
+/// colon-separated list, e.g. `ubuntu:GNOME`), if one is known.
+pub fn portal_package(desktop: &str) -> Option<&'static str> {
+    desktop.split(':').find_map(|name| {
+        Some(match name.trim().to_ascii_lowercase().as_str() {
+            "gnome" | "unity" | "niri" => "xdg-desktop-portal-gnome",
+            "kde" => "xdg-desktop-portal-kde",
+            "hyprland" => "xdg-desktop-portal-hyprland",
+            "cosmic" => "xdg-desktop-portal-cosmic",
+            "sway" | "river" | "wayfire" | "labwc" | "dwl" | "hikari" => "xdg-desktop-portal-wlr",
+            _ => return None,
+        })
+    })
+}
+
+/// Check the screen-cast portal without starting a session (no prompt).
+pub async fn check_portal() -> PortalCheck {
+    let desktop = std::env::var("XDG_CURRENT_DESKTOP").ok().filter(|d| !d.trim().is_empty());
+    #[cfg(target_os = "linux")]
+    let (backends, problem) = (crate::linux::portal_backends().await, crate::linux::check_screen_cast().await.err());
+    #[cfg(not(target_os = "linux"))]
+    let (backends, problem) = (Vec::new(), Some(PortalProblem::NoPortal("only available on Linux".to_owned())));
+    PortalCheck { desktop, backends, problem }
+}
@@ -0,0 +1,374 @@
+//! [`Backend::Screencopy`](crate::Backend::Screencopy) — direct capture on
+//! wlroots compositors (sway, Hyprland, river, …) through
+//! `wlr-screencopy-unstable-v1`.
+//!
+//! No portal, no permission prompt and no PipeWire: the compositor copies
+//! each output frame into a shared-memory buffer this backend owns. Frames
+//! are BGRx; outputs whose size differs from the configured one are scaled
+//! (nearest neighbour) so the encoder always sees the negotiated size.
+//!
+//! [`CaptureConfig::monitor`] picks the output by name (`wl_output` v4, e.g.
+//! `DP-1`); otherwise the `display_index`-th output is captured. Capture runs
+//! on its own thread, one `capture_output` request per frame, paced to the
+//! configured rate.
+
+use std::fs::File;
+use std::os::fd::AsFd;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use memmap2::MmapMut;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{info, warn};
+use wayland_client::protocol::{wl_buffer, wl_output, wl_registry, wl_shm, wl_shm_pool};
+use wayland_client::{delegate_noop, Connection, Dispatch, EventQueue, QueueHandle, WEnum};
+use wayland_protocols_wlr::screencopy::v1::client::{
+    zwlr_screencopy_frame_v1::{self, ZwlrScreencopyFrameV1},
+    zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1,
+};
+
+use crate::backend::{Backend, BackendCaps, CaptureBackend, FrameFuture};
+use crate::{CaptureConfig, CapturedFrame, PixelFormat};
+
+/// Whether the compositor offers `zwlr_screencopy_manager_v1` — no capture
+/// is started.
+pub(crate) fn probe() -> Result<(), String> {
+    let (_conn, _queue, state) = connect().map_err(|e| format!("{e:#}"))?;
+    if state.manager.is_none() {
+        return Err("compositor has no wlr-screencopy (not wlroots-based?)".to_owned());
+    }
+    Ok(())
+}
+
+pub(crate) struct Screencopy {
+    frame_rx: mpsc::Receiver<CapturedFrame>,
+    /// Frame-rate cap applied by the capture thread (shared).
+    max_fps:  Arc<AtomicU32>,
+}
+
+impl Screencopy {
+    pub(crate) async fn open(config: CaptureConfig) -> Result<Self> {
+        let (frame_tx, frame_rx) = mpsc::channel(2);
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let max_fps = Arc::new(AtomicU32::new(config.fps.max(1)));
+        let thread_max_fps = Arc::clone(&max_fps);
+        thread::Builder::new()
+            .name(format!("screencopy-{}", config.display_index))
+            .spawn(move || {
+                let session = match Session::open(&config) {
+                    Ok(session) => session,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = ready_tx.send(Ok(()));
+                if let Err(e) = session.run(&config, &thread_max_fps, &frame_tx) {
+                    warn!("Capture[{}] screencopy: {:#}", config.display_index, e);
+                }
+            })
+            .context("Spawning the screencopy thread")?;
+        ready_rx.await.context("Screencopy thread exited")??;
+        Ok(Self { frame_rx, max_fps })
+    }
+}
+
+impl CaptureBackend for Screencopy {
+    fn caps(&self) -> BackendCaps {
+        Backend::Screencopy.caps()
+    }
+
+    fn next_frame(&mut self) -> FrameFuture<'_> {
+        Box::pin(self.frame_rx.recv())
+    }
+
+    fn set_max_fps(&self, fps: u32) {
+        let prev = self.max_fps.swap(fps, Ordering::Relaxed);
+        if prev != fps {
+            info!("Capture frame-rate cap {} → {} fps", prev, fps);
+        }
+    }
+}
+
+// ── Capture session ───────────────────────────────────────────────────────────
+
+struct Session {
+    conn:    Connection,
+    queue:   EventQueue<State>,
+    state:   State,
+    output:  wl_output::WlOutput,
+    manager: ZwlrScreencopyManagerV1,
+    shm:     wl_shm::WlShm,
+}
+
+impl Session {
+    fn open(config: &CaptureConfig) -> Result<Self> {
+        let (conn, queue, state) = connect()?;
+        let manager = state.manager.clone().context("Compositor has no wlr-screencopy")?;
+        let shm = state.shm.clone().context("Compositor has no wl_shm")?;
+        let idx = config.display_index as usize;
+        let by_name = config
+            .monitor
+            .as_deref()
+            .and_then(|name| state.outputs.iter().find(|o| o.name.as_deref() == Some(name)));
+        if let (Some(name), None) = (&config.monitor, by_name) {
+            warn!("Monitor {} not among the Wayland outputs — using output {}", name, idx);
+        }
+        let output = by_name
+            .or_else(|| state.outputs.get(idx))
+            .with_context(|| format!("No Wayland output {idx} ({} found)", state.outputs.len()))?;
+        info!(
+            "Capture[{}] screencopy on output {}",
+            config.display_index,
+            output.name.as_deref().unwrap_or("(unnamed)")
+        );
+        let output = output.wl.clone();
+        Ok(Self { conn, queue, state, output, manager, shm })
+    }
+
+    /// Capture until the receiving end is dropped or the compositor fails.
+    fn run(
+        mut self,
+        config: &CaptureConfig,
+        max_fps: &AtomicU32,
+        frame_tx: &mpsc::Sender<CapturedFrame>,
+    ) -> Result<()> {
+        let qh = self.queue.handle();
+        let start = Instant::now();
+        let mut next_due = start;
+        let mut buffer: Option<ShmBuffer> = None;
+        loop {
+            if let Some(wait) = next_due.checked_duration_since(Instant::now()) {
+                thread::sleep(wait);
+            }
+            let fps = max_fps.load(Ordering::Relaxed).clamp(1, config.fps.max(1));
+            // Late frames do not catch up in a burst.
+            next_due = (next_due + Duration::from_secs(1) / fps).max(Instant::now());
+
+            self.state.frame = FrameState::default();
+            let frame = self.manager.capture_output(1, &self.output, &qh, ());
+            // The buffer parameters are sent right after the request.
+            self.queue.roundtrip(&mut self.state).context("Wayland roundtrip")?;
+            let Some(info) = self.state.frame.info else {
+                frame.destroy();
+                anyhow::bail!("Compositor offered no supported shm format");
+            };
+            let shm_buffer = match buffer.take() {
+                Some(b) if b.info == info => buffer.insert(b),
+                _ => buffer.insert(ShmBuffer::new(&self.shm, info, &qh)?),
+            };
+            frame.copy(&shm_buffer.buffer);
+            while self.state.frame.done.is_none() {
+                self.queue.blocking_dispatch(&mut self.state).context("Wayland dispatch")?;
+            }
+            frame.destroy();
+            if self.state.frame.done == Some(false) {
+                warn!("Capture[{}] screencopy frame failed — retrying", config.display_index);
+                continue;
+            }
+
+            let data = shm_buffer.to_bgrx(self.state.frame.y_invert, config.width, config.height);
+            let frame = CapturedFrame {
+                data,
+                pts_ms: start.elapsed().as_millis() as u64,
+                format: PixelFormat::Bgrx,
+                width:  config.width,
+                height: config.height,
+                stride: config.width * 4,
+            };
+            if frame_tx.blocking_send(frame).is_err() {
+                return Ok(());
+            }
+            self.conn.flush().context("Wayland flush")?;
+        }
+    }
+}
+
+/// Connect to the compositor and collect its globals and output names.
+fn connect() -> Result<(Connection, EventQueue<State>, State)> {
+    let conn = Connection::connect_to_env().context("No Wayland compositor (WAYLAND_DISPLAY)")?;
+    let mut queue = conn.new_event_queue();
+    let qh = queue.handle();
+    conn.display().get_registry(&qh, ());
+    let mut state = State::default();
+    // Globals, then the outputs' name events.
+    queue.roundtrip(&mut state).context("Wayland roundtrip")?;
+    queue.roundtrip(&mut state).context("Wayland roundtrip")?;
+    Ok((conn, queue, state))
+}
+
+// ── Shared-memory buffer ──────────────────────────────────────────────────────
+
+/// Buffer layout the compositor asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BufferInfo {
+    format: wl_shm::Format,
+    width:  u32,
+    height: u32,
+    stride: u32,
+}
+
+struct ShmBuffer {
+    info:   BufferInfo,
+    map:    MmapMut,
+    buffer: wl_buffer::WlBuffer,
+}
+
+impl ShmBuffer {
+    fn new(shm: &wl_shm::WlShm, info: BufferInfo, qh: &QueueHandle<State>) -> Result<Self> {
+        let size = info.stride as usize * info.height as usize;
+        let fd = rustix::fs::memfd_create("duallink-screencopy", rustix::fs::MemfdFlags::CLOEXEC)
+            .context("memfd_create")?;
+        let file = File::from(fd);
+        file.set_len(size as u64).context("Sizing the shm buffer")?;
+        // SAFETY: the file is private to this process and the compositor,
+        // which only writes to it between `copy` and `ready`.
+        let map = unsafe { MmapMut::map_mut(&file) }.context("Mapping the shm buffer")?;
+        let pool = shm.create_pool(file.as_fd(), size as i32, qh, ());
+        let buffer = pool.create_buffer(
+            0,
+            info.width as i32,
+            info.height as i32,
+            info.stride as i32,
+            info.format,
+            qh,
+            (),
+        );
+        pool.destroy();
+        Ok(Self { info, map, buffer })
+    }
+
+    /// The frame as tightly packed BGRx at `width`×`height`, upright.
+    fn to_bgrx(&self, y_invert: bool, width: u32, height: u32) -> Vec<u8> {
+        let BufferInfo { format, width: src_w, height: src_h, stride } = self.info;
+        let swap_rb = matches!(format, wl_shm::Format::Xbgr8888 | wl_shm::Format::Abgr8888);
+        // Source column for every destination column (nearest neighbour).
+        let cols: Vec<usize> = (0..width as u64).map(|x| (x * src_w as u64 / width as u64) as usize).collect();
+        let mut out = Vec::with_capacity(width as usize * height as usize * 4);
+        for y in 0..height as u64 {
+            let mut src_y = (y * src_h as u64 / height as u64) as usize;
+            if y_invert {
+                src_y = src_h as usize - 1 - src_y;
+            }
+            let row = &self.map[src_y * stride as usize..][..src_w as usize * 4];
+            for &x in &cols {
+                let px = &row[x * 4..x * 4 + 4];
+                if swap_rb {
+                    out.extend_from_slice(&[px[2], px[1], px[0], 0xff]);
+                } else {
+                    out.extend_from_slice(&[px[0], px[1], px[2], 0xff]);
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Drop for ShmBuffer {
+    fn drop(&mut self) {
+        self.buffer.destroy();
+    }
+}
+
+// ── Wayland state ─────────────────────────────────────────────────────────────
+
+struct Output {
+    wl:   wl_output::WlOutput,
+    /// Connector name (`wl_output` v4), e.g. `DP-1`.
+    name: Option<String>,
+}
+
+#[derive(Default)]
+struct FrameState {
+    info:     Option<BufferInfo>,
+    y_invert: bool,
+    /// `Some(true)` once ready, `Some(false)` if the copy failed.
+    done:     Option<bool>,
+}
+
+#[derive(Default)]
+struct State {
+    outputs: Vec<Output>,
+    shm:     Option<wl_shm::WlShm>,
+    manager: Option<ZwlrScreencopyManagerV1>,
+    frame:   FrameState,
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for State {
+    fn event(
+        state: &mut Self,
+        registry: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        let wl_registry::Event::Global { name, interface, version } = event else { return };
+        match interface.as_str() {
+            "wl_output" => {
+                let idx = state.outputs.len();
+                let wl = registry.bind(name, version.min(4), qh, idx);
+                state.outputs.push(Output { wl, name: None });
+            }
+            "wl_shm" => state.shm = Some(registry.bind(name, 1, qh, ())),
+            "zwlr_screencopy_manager_v1" => state.manager = Some(registry.bind(name, version.min(3), qh, ())),
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<wl_output::WlOutput, usize> for State {
+    fn event(
+        state: &mut Self,
+        _: &wl_output::WlOutput,
+        event: wl_output::Event,
+        idx: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wl_output::Event::Name { name } = event {
+            state.outputs[*idx].name = Some(name);
+        }
+    }
+}
+
+impl Dispatch<ZwlrScreencopyFrameV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrScreencopyFrameV1,
+        event: zwlr_screencopy_frame_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let frame = &mut state.frame;
+        match event {
+            zwlr_screencopy_frame_v1::Event::Buffer { format: WEnum::Value(format), width, height, stride } => {
+                let supported = matches!(
+                    format,
+                    wl_shm::Format::Xrgb8888
+                        | wl_shm::Format::Argb8888
+                        | wl_shm::Format::Xbgr8888
+                        | wl_shm::Format::Abgr8888
+                );
+                if supported && frame.info.is_none() {
+                    frame.info = Some(BufferInfo { format, width, height, stride });
+                }
+            }
+            zwlr_screencopy_frame_v1::Event::Flags { flags: WEnum::Value(flags) } => {
+                frame.y_invert = flags.contains(zwlr_screencopy_frame_v1::Flags::YInvert);
+            }
+            zwlr_screencopy_frame_v1::Event::Ready { .. } => frame.done = Some(true),
+            zwlr_screencopy_frame_v1::Event::Failed => frame.done = Some(false),
+            _ => {}
+        }
+    }
+}
+
+delegate_noop!(State: ignore wl_shm::WlShm);
+delegate_noop!(State: wl_shm_pool::WlShmPool);
+delegate_noop!(State: ignore wl_buffer::WlBuffer);
+delegate_noop!(State: ZwlrScreencopyManagerV1);
@@ -0,0 +1,94 @@
+//! [`Backend::TestPattern`](crate::Backend::TestPattern) — moving colour
+//! bars generated in-process.
+//!
+//! Eight full-height bars (white, yellow, cyan, green, magenta, red, blue,
+//! black) scroll left by one screen width every [`SCROLL_PERIOD`], so a
+//! frozen or stuttering stream is obvious on the receiver. Frames are BGRx
+//! at the configured size and rate; no display server, portal or GStreamer
+//! is involved.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::backend::{Backend, BackendCaps, CaptureBackend, FrameFuture};
+use crate::{CaptureConfig, CapturedFrame, PixelFormat};
+
+/// Time the bars take to scroll one screen width.
+pub const SCROLL_PERIOD: Duration = Duration::from_secs(4);
+
+/// Bar colours, BGRx, left to right.
+const BARS: [[u8; 4]; 8] = [
+    [0xff, 0xff, 0xff, 0xff],
+    [0x00, 0xff, 0xff, 0xff],
+    [0xff, 0xff, 0x00, 0xff],
+    [0x00, 0xff, 0x00, 0xff],
+    [0xff, 0x00, 0xff, 0xff],
+    [0x00, 0x00, 0xff, 0xff],
+    [0xff, 0x00, 0x00, 0xff],
+    [0x00, 0x00, 0x00, 0xff],
+];
+
+pub(crate) struct TestPattern {
+    width:    u32,
+    height:   u32,
+    fps:      u32,
+    /// Frame-rate cap from [`CaptureBackend::set_max_fps`].
+    max_fps:  AtomicU32,
+    start:    Instant,
+    next_due: Instant,
+}
+
+impl TestPattern {
+    pub(crate) fn new(config: &CaptureConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            width:    config.width.max(1),
+            height:   config.height.max(1),
+            fps:      config.fps.max(1),
+            max_fps:  AtomicU32::new(config.fps.max(1)),
+            start:    now,
+            next_due: now,
+        }
+    }
+
+    /// One frame with the bars scrolled `elapsed` into the pattern.
+    fn render(&self, elapsed: Duration) -> Vec<u8> {
+        let width = self.width as u64;
+        let period = SCROLL_PERIOD.as_millis() as u64;
+        let offset = (elapsed.as_millis() as u64 % period) * width / period;
+        let row: Vec<u8> = (0..width)
+            .flat_map(|x| BARS[((x + offset) % width * BARS.len() as u64 / width) as usize])
+            .collect();
+        row.repeat(self.height as usize)
+    }
+}
+
+impl CaptureBackend for TestPattern {
+    fn caps(&self) -> BackendCaps {
+        Backend::TestPattern.caps()
+    }
+
+    fn next_frame(&mut self) -> FrameFuture<'_> {
+        Box::pin(async move {
+            tokio::time::sleep_until(self.next_due).await;
+            let fps = self.max_fps.load(Ordering::Relaxed).clamp(1, self.fps);
+            // Late frames do not catch up in a burst.
+            self.next_due = (self.next_due + Duration::from_secs(1) / fps).max(Instant::now());
+            let elapsed = self.start.elapsed();
+            Some(CapturedFrame {
+                data:   self.render(elapsed),
+                pts_ms: elapsed.as_millis() as u64,
+                format: PixelFormat::Bgrx,
+                width:  self.width,
+                height: self.height,
+                stride: self.width * 4,
+            })
+        })
+    }
+
+    fn set_max_fps(&self, fps: u32) {
+        self.max_fps.store(fps, Ordering::Relaxed);
+    }
+}
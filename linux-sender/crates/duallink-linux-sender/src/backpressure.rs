@@ -0,0 +1,244 @@
+//! Backpressure between capture and encode.
+//!
+//! Captured frames wait in a small bounded [`FrameQueue`] and are only handed
+//! to the encoder while it has fewer than `max_in_flight` frames outstanding.
+//! When the queue is full the [`DropPolicy`] decides which frame is discarded,
+//! so latency stays bounded instead of growing with the appsrc backlog.
+//!
+//! [`OverloadMonitor`] watches the drop ratio once per second; after sustained
+//! overload it asks the capturer for a lower frame rate, and restores it
+//! gradually once the pipeline keeps up again.
+//!
+//! ```text
+//! capturer ──► FrameQueue (depth N) ──► GstEncoder (≤ max_in_flight) ──► UDP
+//!     ▲                 │ drops
+//!     └── set_max_fps ◄─ OverloadMonitor (1 Hz)
+//! ```
+
+use std::collections::VecDeque;
+
+use duallink_capture_linux::CapturedFrame;
+
+// ── Drop policy ───────────────────────────────────────────────────────────────
+
+/// Which frame to discard when the queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DropPolicy {
+    /// Discard the oldest queued frame — the encoder always gets the freshest
+    /// picture (lowest latency).
+    #[default]
+    LatestWins,
+    /// Discard the incoming frame — keeps frame order/cadence, higher latency.
+    DropNewest,
+}
+
+impl DropPolicy {
+    /// Parse `"latest"` / `"newest"` (case-insensitive).
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "latest" | "latest-wins" => Some(Self::LatestWins),
+            "newest" | "drop-newest" => Some(Self::DropNewest),
+            _ => None,
+        }
+    }
+}
+
+// ── FrameQueue ────────────────────────────────────────────────────────────────
+
+/// Bounded queue of raw frames waiting for the encoder.
+pub struct FrameQueue {
+    frames:  VecDeque<CapturedFrame>,
+    depth:   usize,
+    policy:  DropPolicy,
+    dropped: u64,
+}
+
+impl FrameQueue {
+    /// `depth` is clamped to at least 1.
+    pub fn new(depth: usize, policy: DropPolicy) -> Self {
+        let depth = depth.max(1);
+        Self { frames: VecDeque::with_capacity(depth), depth, policy, dropped: 0 }
+    }
+
+    /// Enqueue a frame, dropping one according to the policy if full.
+    ///
+    /// Returns `true` if a frame was dropped.
+    pub fn push(&mut self, frame: CapturedFrame) -> bool {
+        if self.frames.len() < self.depth {
+            self.frames.push_back(frame);
+            return false;
+        }
+        self.dropped += 1;
+        if self.policy == DropPolicy::LatestWins {
+            self.frames.pop_front();
+            self.frames.push_back(frame);
+        }
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<CapturedFrame> {
+        self.frames.pop_front()
+    }
+
+    /// Total frames dropped since creation.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+// ── OverloadMonitor ───────────────────────────────────────────────────────────
+
+/// Drop ratio (dropped / captured) above which a 1-second window is "overloaded".
+const OVERLOAD_RATIO: f32 = 0.2;
+/// Consecutive overloaded windows before the capture rate is lowered.
+const OVERLOAD_WINDOWS: u32 = 3;
+/// Consecutive clean windows before the capture rate is raised again.
+const RECOVER_WINDOWS: u32 = 10;
+/// Never throttle below this frame rate.
+const MIN_FPS: u32 = 15;
+
+/// Detects sustained overload from per-second capture/drop counts and decides
+/// the capture frame-rate cap.
+pub struct OverloadMonitor {
+    target_fps:  u32,
+    current_fps: u32,
+    overloaded:  u32,
+    clean:       u32,
+    last_captured: u64,
+    last_dropped:  u64,
+}
+
+impl OverloadMonitor {
+    pub fn new(target_fps: u32) -> Self {
+        Self {
+            target_fps,
+            current_fps: target_fps,
+            overloaded: 0,
+            clean: 0,
+            last_captured: 0,
+            last_dropped: 0,
+        }
+    }
+
+    /// Feed cumulative counters once per second.
+    ///
+    /// Returns `Some(fps)` when the capture rate cap should change.
+    pub fn tick(&mut self, captured: u64, dropped: u64) -> Option<u32> {
+        let window_captured = captured.saturating_sub(self.last_captured);
+        let window_dropped = dropped.saturating_sub(self.last_dropped);
+        self.last_captured = captured;
+        self.last_dropped = dropped;
+
+        let ratio = if window_captured == 0 {
+            0.0
+        } else {
+            window_dropped as f32 / window_captured as f32
+        };
+
+        if ratio > OVERLOAD_RATIO {
+            self.clean = 0;
+            self.overloaded += 1;
+            if self.overloaded >= OVERLOAD_WINDOWS && self.current_fps > MIN_FPS {
+                self.overloaded = 0;
+                self.current_fps = (self.current_fps * 3 / 4).max(MIN_FPS);
+                return Some(self.current_fps);
+            }
+        } else {
+            self.overloaded = 0;
+            self.clean += 1;
+            if self.clean >= RECOVER_WINDOWS && self.current_fps < self.target_fps {
+                self.clean = 0;
+                self.current_fps = (self.current_fps + 5).min(self.target_fps);
+                return Some(self.current_fps);
+            }
+        }
+        None
+    }
+
+    /// Reset to a new target rate (e.g. after a quality preset switch).
+    pub fn set_target(&mut self, fps: u32) {
+        self.target_fps = fps;
+        self.current_fps = fps;
+        self.overloaded = 0;
+        self.clean = 0;
+    }
+
+    /// Current capture rate cap, or `None` when running at the target rate.
+    pub fn throttled_fps(&self) -> Option<u32> {
+        (self.current_fps < self.target_fps).then_some(self.current_fps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use duallink_capture_linux::PixelFormat;
+
+    use super::*;
+
+    fn frame(pts_ms: u64) -> CapturedFrame {
+        CapturedFrame { data: Vec::new(), pts_ms, format: PixelFormat::Bgrx, width: 0, height: 0, stride: 0 }
+    }
+
+    fn drain(queue: &mut FrameQueue) -> Vec<u64> {
+        std::iter::from_fn(|| queue.pop()).map(|f| f.pts_ms).collect()
+    }
+
+    #[test]
+    fn full_queue_drops_by_policy() {
+        let mut latest = FrameQueue::new(2, DropPolicy::LatestWins);
+        let drops: Vec<bool> = (1..=4).map(|pts| latest.push(frame(pts))).collect();
+        assert_eq!(drops, [false, false, true, true]);
+        assert_eq!(latest.dropped(), 2);
+        assert_eq!(drain(&mut latest), [3, 4]);
+
+        let mut newest = FrameQueue::new(2, DropPolicy::DropNewest);
+        let drops: Vec<bool> = (1..=4).map(|pts| newest.push(frame(pts))).collect();
+        assert_eq!(drops, [false, false, true, true]);
+        assert_eq!(newest.dropped(), 2);
+        assert_eq!(drain(&mut newest), [1, 2]);
+
+        // Depth 0 still holds a frame.
+        let mut single = FrameQueue::new(0, DropPolicy::LatestWins);
+        assert!(!single.push(frame(1)));
+        assert!(single.push(frame(2)));
+        assert_eq!(drain(&mut single), [2]);
+        assert_eq!(single.dropped(), 1);
+    }
+
+    #[test]
+    fn sustained_overload_throttles_and_clean_windows_recover() {
+        let mut monitor = OverloadMonitor::new(60);
+        let mut totals = (0, 0);
+        // One second of `captured` frames with `dropped` of them lost.
+        let mut window = |monitor: &mut OverloadMonitor, captured: u64, dropped: u64| {
+            totals = (totals.0 + captured, totals.1 + dropped);
+            monitor.tick(totals.0, totals.1)
+        };
+
+        // 20 % drops is within bounds; 30 % only counts three windows in a row.
+        assert_eq!(window(&mut monitor, 60, 12), None);
+        assert_eq!(window(&mut monitor, 60, 18), None);
+        assert_eq!(window(&mut monitor, 60, 18), None);
+        assert_eq!(window(&mut monitor, 60, 0), None);
+        assert_eq!(window(&mut monitor, 60, 18), None);
+        assert_eq!(window(&mut monitor, 60, 18), None);
+        assert_eq!(window(&mut monitor, 60, 18), Some(45));
+        assert_eq!(monitor.throttled_fps(), Some(45));
+
+        // Ten clean windows (an idle second counts) raise it by 5 fps.
+        for _ in 0..9 {
+            assert_eq!(window(&mut monitor, 45, 0), None);
+        }
+        assert_eq!(window(&mut monitor, 0, 0), Some(50));
+
+        // Never below the floor.
+        for _ in 0..30 {
+            window(&mut monitor, 60, 30);
+        }
+        assert_eq!(monitor.throttled_fps(), Some(MIN_FPS));
+        assert_eq!(window(&mut monitor, 60, 30), None);
+
+        monitor.set_target(30);
+        assert_eq!(monitor.throttled_fps(), None);
+    }
+}
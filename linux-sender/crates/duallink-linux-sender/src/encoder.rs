@@ -2,68 +2,258 @@
 //!
 //! # Encoder priority (highest to lowest)
 //!
-//! | Encoder       | Backend    | Notes |
-//! |---------------|------------|-------|
-//! | `vaapih264enc` | VA-API HW | Intel / AMD iGPU |
-//! | `nvh264enc`   | NVENC HW   | NVIDIA GPU |
-//! | `x264enc`     | Software   | CPU fallback, always available |
+//! Mirrors the decoder probing in `duallink-decoder` (GT-2001): the first
+//! element found in [`ENCODER_PRIORITY`] wins and is logged at selection time.
+//!
+//! | Encoder          | Backend        | Notes |
+//! |------------------|----------------|-------|
+//! | `vaapih264lpenc` | VA-API LP HW   | Intel low-power fixed-function path (lowest latency) |
+//! | `vaapih264enc`   | VA-API HW      | Intel / AMD iGPU |
+//! | `nvh264enc`      | NVENC HW       | NVIDIA GPU |
+//! | `x264enc`        | Software       | CPU fallback, always available |
+//!
+//! Each element has its own low-latency tuning profile, biased by the active
+//! quality preset's [`EncoderTune`] — see [`tune_encoder`].
+//!
+//! # Latency mode
+//!
+//! The negotiated [`LatencyMode`] ([`EncodeProfile::latency`]) decides
+//! between zero-latency tuning without B-frames (`UltraLow`) and
+//! [`LatencyMode::b_frames`] B-frames with a short rate-control lookahead
+//! (`Quality`). `vaapih264lpenc` and OpenH264 have no B-frames and encode
+//! the same in both modes.
+//!
+//! # Temporal layers
+//!
+//! In ultra-low latency mode `vaapih264enc` encodes with two-level
+//! hierarchical-P prediction, so every
+//! other P-frame is referenced by nothing. Frames are tagged with
+//! [`temporal_layer`] as they leave the appsink and the layer travels in the
+//! DLNK header; a receiver whose decoder falls behind drops the layer-1
+//! frames and plays at half rate instead of building up latency. The other
+//! encoders mark every frame layer 0.
+//!
+//! # Lossless mode
+//!
+//! When [`EncodeProfile::lossless`] is set (only after the receiver advertised
+//! `h264_444`), probing is skipped: `x264enc` runs in constant-quantizer mode
+//! (`qp` = [`LOSSLESS_QP`]) on Y444 input, producing H.264 High 4:4:4 — no
+//! chroma subsampling, so small text stays sharp. Bitrate is then unbounded.
 //!
 //! # Pipeline
 //!
+//! Split mode ([`GstEncoder::new`]):
+//!
 //! ```text
-//! appsrc (BGRx)
-//!   → videoconvert
-//!   → video/x-raw,format=I420   (intermediate conversion)
+//! appsrc (BGRx | NV12)
+//!   → tee ─→ preview branch     (1 fps RGBA thumbnails, see `preview`)
+//!   → videoconvert              (passthrough for NV12 input)
 //!   → <best-encoder>
 //!   → video/x-h264,stream-format=byte-stream,alignment=au
 //!   → h264parse
 //!   → appsink (H.264 AU byte-stream)
 //! ```
+//!
+//! Fused mode ([`GstEncoder::new_fused`]) replaces `appsrc` with the portal's
+//! `pipewiresrc`, so raw frames never leave GStreamer:
+//!
+//! ```text
+//! pipewiresrc → tee → videoconvert → <best-encoder> → h264parse → appsink
+//! ```
+//!
+//! [`GstEncoder::new_test_pattern`] does the same with a `videotestsrc`
+//! pattern ([`elements::test_source`]), to check the link, the decoder and
+//! colour accuracy end to end without a portal session. Both
+//! put a drop-only `videorate` in front of `videoconvert`, so the caps filter
+//! sets the frame rate.
+//!
+//! # Runtime changes
+//!
+//! Bitrate ([`GstEncoder::set_bitrate`]), keyframe interval
+//! ([`GstEncoder::set_gop`]) and frame rate ([`GstEncoder::set_fps`]) change
+//! on the running pipeline, and [`GstEncoder::force_keyframe`] asks for a
+//! keyframe at any time. Bitrate and GOP are live element properties; a new
+//! frame rate is renegotiated through the caps — re-announced on the appsrc
+//! in split mode (the caller slows capture down), set on the caps filter for
+//! in-pipeline sources, where `videorate` drops the extra frames.
+//!
+//! # Colorimetry
+//!
+//! The caps filter after `videoconvert` pins the stream's
+//! [`ColorSpace`] (range + matrix), so the conversion and the encoder's VUI
+//! match what the receiver configures from `StreamConfig::color`.
+//!
+//! Pipelines are built element by element with typed properties (see
+//! [`duallink_sender_lib::elements`]), never from `parse::launch` strings.
 
 use anyhow::Context;
 use bytes::Bytes;
-use duallink_capture_linux::CapturedFrame;
-use duallink_core::{EncodedFrame, VideoCodec};
+use std::cell::Cell;
+use std::sync::{Arc, Mutex};
+
+use duallink_capture_linux::{CapturedFrame, PipeWireStream, PixelFormat};
+use duallink_core::{temporal_layer, ColorSpace, EncodedFrame, EncoderTune, LatencyMode, SenderQueues, VideoCodec};
+use duallink_sender_lib::elements::{self, caps_filter, make, make_named};
+use duallink_sender_lib::preview::{self, PreviewSlot};
 use gstreamer::prelude::*;
 use gstreamer_app::{AppSink, AppSinkCallbacks, AppSrc, AppSrcCallbacks};
 use tokio::sync::mpsc;
-use tracing::{debug, info, warn};
-
-// ── Encoder selection ─────────────────────────────────────────────────────────
-
-/// Return the GStreamer element name of the best available H.264 encoder,
-/// plus a GStreamer property string to insert after the element name.
-fn select_encoder() -> (&'static str, &'static str) {
-    let candidates: &[(&str, &str)] = &[
-        ("vaapih264enc",  "rate-control=cbr quality-level=6"),
-        ("nvh264enc",     "preset=low-latency-hq rc-mode=cbr"),
-        ("x264enc",       "tune=zerolatency speed-preset=veryfast key-int-max=30"),
-    ];
-    for (name, props) in candidates {
-        if gstreamer::ElementFactory::find(name).is_some() {
-            info!("H.264 encoder selected: {}", name);
-            return (name, props);
+use tracing::{debug, error, info, warn};
+
+// ── Probe ─────────────────────────────────────────────────────────────────────
+
+/// Encoder candidates in priority order — Linux sender.
+static ENCODER_PRIORITY: &[(&str, &str)] = &[
+    ("vaapih264lpenc", "Intel VA-API low-power H.264 (primary)"),
+    ("vaapih264enc",   "AMD/Intel VA-API H.264"),
+    ("nvh264enc",      "NVIDIA NVENC H.264"),
+    ("x264enc",        "Software x264 (last resort)"),
+];
+
+/// Returns the name of the highest-priority available GStreamer H.264 encoder.
+///
+/// Requires `gstreamer::init()` to have been called.
+pub fn probe_best_encoder() -> Option<&'static str> {
+    for (element, label) in ENCODER_PRIORITY {
+        if gstreamer::ElementFactory::find(element).is_some() {
+            info!("Selected encoder: {} ({})", element, label);
+            return Some(element);
         }
+        warn!("Encoder '{}' not found, trying next", element);
+    }
+    None
+}
+
+/// Set the low-latency tuning properties of `enc`, an instance of `element`.
+///
+/// `tune` picks the speed/quality trade-off, `latency` zero-latency or
+/// B-frame tuning and `gop` the keyframe interval in frames. All supported
+/// elements take `bitrate` in kbit/s; it is set separately.
+pub fn tune_encoder(enc: &gstreamer::Element, element: &str, tune: EncoderTune, latency: LatencyMode, gop: u32) {
+    let b_frames = latency.b_frames();
+    match element {
+        "vaapih264lpenc" => {
+            enc.set_property_from_str("rate-control", "cbr");
+            enc.set_property_from_str("tune", "low-power");
+        }
+        "vaapih264enc" => {
+            let quality: u32 = match tune {
+                EncoderTune::Quality    => 4,
+                EncoderTune::LowLatency => 6,
+                EncoderTune::LowPower   => 7,
+            };
+            enc.set_property_from_str("rate-control", "cbr");
+            enc.set_property("quality-level", quality);
+            if b_frames > 0 {
+                enc.set_property("max-bframes", b_frames);
+            } else if enc.find_property("temporal-levels").is_some() {
+                // Two-level hierarchical P: every other frame is unreferenced and
+                // the receiver may drop it under load (see module docs).
+                enc.set_property("temporal-levels", 2u32);
+                enc.set_property_from_str("prediction-type", "hierarchical-p");
+            }
+        }
+        "nvh264enc" => {
+            let preset = match tune {
+                EncoderTune::LowPower => "low-latency-hp",
+                _                     => "low-latency-hq",
+            };
+            enc.set_property_from_str("preset", preset);
+            enc.set_property_from_str("rc-mode", "cbr");
+            enc.set_property("zerolatency", b_frames == 0);
+            enc.set_property("bframes", b_frames);
+        }
+        _ => {
+            let speed = match tune {
+                EncoderTune::Quality    => "faster",
+                EncoderTune::LowLatency => "veryfast",
+                EncoderTune::LowPower   => "ultrafast",
+            };
+            if b_frames == 0 {
+                enc.set_property_from_str("tune", "zerolatency");
+            } else {
+                enc.set_property("bframes", b_frames);
+                enc.set_property("b-adapt", false);
+                enc.set_property("rc-lookahead", QUALITY_LOOKAHEAD);
+            }
+            enc.set_property_from_str("speed-preset", speed);
+        }
+    }
+    set_gop(enc, element, gop);
+}
+
+/// Constant quantizer used in lossless mode (≤ 18 is visually lossless for text).
+pub const LOSSLESS_QP: u32 = 18;
+
+/// `x264enc` rate-control lookahead in quality latency mode, in frames.
+const QUALITY_LOOKAHEAD: i32 = 4;
+
+/// Encoder settings that are fixed for the lifetime of a [`GstEncoder`].
+#[derive(Debug, Clone, Copy)]
+pub struct EncodeProfile {
+    pub tune:     EncoderTune,
+    /// Negotiated latency mode (see module docs).
+    pub latency:  LatencyMode,
+    /// Keyframe interval in frames.
+    pub gop:      u32,
+    /// High 4:4:4 constant-QP encode via `x264enc` (see module docs).
+    pub lossless: bool,
+    /// Output colour range / matrix, signalled in the H.264 VUI.
+    pub color:    ColorSpace,
+    /// Depths of the `appsink` and the encoded-frame channel.
+    pub queues:   SenderQueues,
+}
+
+/// Set the keyframe interval of `enc`, an instance of `element`.
+fn set_gop(enc: &gstreamer::Element, element: &str, frames: u32) {
+    match element {
+        "vaapih264lpenc" | "vaapih264enc" => enc.set_property("keyframe-period", frames),
+        "nvh264enc"                       => enc.set_property("gop-size", frames as i32),
+        _                                 => enc.set_property("key-int-max", frames),
     }
-    // x264enc should always be available if gst-plugins-ugly is installed.
-    warn!("No preferred H.264 encoder found; falling back to x264enc");
-    ("x264enc", "tune=zerolatency")
 }
 
 // ── GstEncoder ────────────────────────────────────────────────────────────────
 
-/// Encodes raw BGRx frames to H.264 using GStreamer.
+/// Encodes raw BGRx or NV12 frames to H.264 using GStreamer.
+///
+/// Two constructions are available:
 ///
-/// Push frames with [`GstEncoder::push_frame`] and pull encoded output with
-/// [`GstEncoder::next_encoded`].
+/// - [`GstEncoder::new`] (split mode) — frames are pushed with
+///   [`GstEncoder::push_frame`] from a separate `ScreenCapturer`.
+/// - [`GstEncoder::new_fused`] (fused mode) — `pipewiresrc` feeds the encoder
+///   inside the same GStreamer pipeline; no appsink → channel → appsrc hop.
+///
+/// In both modes encoded output is pulled with [`GstEncoder::next_encoded`].
 pub struct GstEncoder {
-    appsrc:     AppSrc,
+    /// `None` in fused mode — capture is linked inside the pipeline.
+    appsrc:     Option<AppSrc>,
     encoded_rx: mpsc::Receiver<EncodedFrame>,
-    _pipeline:  gstreamer::Pipeline,
+    element:    &'static str,
+    /// The encoder element itself (`name=enc`), for runtime property changes.
+    enc:        gstreamer::Element,
+    /// Caps filter in front of the encoder, for frame-rate changes of
+    /// in-pipeline sources.
+    caps:       gstreamer::Element,
+    profile:    EncodeProfile,
+    /// Pixel format the appsrc caps are currently set to.
+    input:      Cell<PixelFormat>,
+    width:      u32,
+    height:     u32,
+    fps:        Cell<u32>,
+    /// Frames pushed into the appsrc / pulled from the appsink, for
+    /// backpressure accounting ([`GstEncoder::in_flight`]).
+    pushed:     Cell<u64>,
+    encoded:    u64,
+    lossless:   bool,
+    pipeline:   gstreamer::Pipeline,
 }
 
 impl GstEncoder {
-    /// Create and start a GStreamer encode pipeline.
+    /// Create and start a split-mode encode pipeline fed through an appsrc.
+    ///
+    /// `input` is the expected frame format; if captured frames arrive in a
+    /// different format the appsrc caps are renegotiated on the fly.
     ///
     /// Must be called after `gstreamer::init()`.
     pub fn new(
@@ -71,85 +261,214 @@ impl GstEncoder {
         height: u32,
         fps: u32,
         bitrate_kbps: u32,
+        input: PixelFormat,
+        profile: EncodeProfile,
     ) -> anyhow::Result<Self> {
-        let (enc_name, enc_props) = select_encoder();
-
-        let desc = format!(
-            "appsrc name=src is-live=true format=time \
-                 caps=\"video/x-raw,format=BGRx,width={width},height={height},\
-                        framerate={fps}/1,colorimetry=bt709\" \
-             ! videoconvert \
-             ! {enc_name} {enc_props} bitrate={bitrate_kbps} \
-             ! video/x-h264,stream-format=byte-stream,alignment=au \
-             ! h264parse \
-             ! appsink name=sink max-buffers=4 drop=false sync=false emit-signals=false"
+        let appsrc = AppSrc::builder()
+            .name("src")
+            .is_live(true)
+            .format(gstreamer::Format::Time)
+            .caps(&raw_caps(input, width, height, fps))
+            .build();
+        let out_caps = encoder_input_caps(profile, None);
+        let pipeline = gstreamer::Pipeline::new();
+        let (enc_name, enc, caps) =
+            build(&pipeline, appsrc.upcast_ref(), &out_caps, width, height, bitrate_kbps, profile)?;
+        let encoded_rx = start(&pipeline, profile.queues.encoded_frames)?;
+
+        info!(
+            "GstEncoder({}) ready {}x{} @{}fps {}kbps input={:?} lossless={}",
+            enc_name, width, height, fps, bitrate_kbps, input, profile.lossless
         );
-        debug!("Encoder pipeline: {}", desc);
-
-        let pipeline = gstreamer::parse::launch(&desc)
-            .context("Parsing encoder pipeline")?
-            .downcast::<gstreamer::Pipeline>()
-            .map_err(|_| anyhow::anyhow!("Expected a Pipeline"))?;
-
-        let appsrc: AppSrc = pipeline
-            .by_name("src")
-            .context("Finding appsrc 'src'")?
-            .downcast::<AppSrc>()
-            .map_err(|_| anyhow::anyhow!("Expected AppSrc"))?;
-
-        let appsink: AppSink = pipeline
-            .by_name("sink")
-            .context("Finding appsink 'sink'")?
-            .downcast::<AppSink>()
-            .map_err(|_| anyhow::anyhow!("Expected AppSink"))?;
-
-        let (encoded_tx, encoded_rx) = mpsc::channel::<EncodedFrame>(16);
-
-        appsink.set_callbacks(
-            AppSinkCallbacks::builder()
-                .new_sample(move |sink| {
-                    let sample = sink.pull_sample().map_err(|_| gstreamer::FlowError::Eos)?;
-                    let buffer = sample.buffer().ok_or(gstreamer::FlowError::Error)?;
-
-                    let pts_us = buffer
-                        .pts()
-                        .map(|t| t.useconds())
-                        .unwrap_or(0);
-                    let is_keyframe = !buffer
-                        .flags()
-                        .contains(gstreamer::BufferFlags::DELTA_UNIT);
-
-                    let map = buffer
-                        .map_readable()
-                        .map_err(|_| gstreamer::FlowError::Error)?;
-                    let data = Bytes::copy_from_slice(map.as_slice());
-
-                    let frame = EncodedFrame {
-                        data,
-                        timestamp_us: pts_us,
-                        is_keyframe,
-                        codec: VideoCodec::H264,
-                    };
-
-                    if encoded_tx.blocking_send(frame).is_err() {
-                        return Err(gstreamer::FlowError::Flushing);
-                    }
-                    Ok(gstreamer::FlowSuccess::Ok)
-                })
-                .build(),
+        Ok(Self {
+            appsrc: Some(appsrc),
+            encoded_rx,
+            element: enc_name,
+            enc,
+            caps,
+            profile,
+            input: Cell::new(input),
+            width,
+            height,
+            fps: Cell::new(fps),
+            pushed: Cell::new(0),
+            encoded: 0,
+            lossless: profile.lossless,
+            pipeline,
+        })
+    }
+
+    /// Create and start a fused capture → encode pipeline reading straight
+    /// from a portal-granted PipeWire stream.
+    ///
+    /// Must be called after `gstreamer::init()` from within a tokio runtime
+    /// (a bus-watcher task is spawned to end the stream on EOS / error).
+    pub fn new_fused(
+        stream: &PipeWireStream,
+        width: u32,
+        height: u32,
+        fps: u32,
+        bitrate_kbps: u32,
+        profile: EncodeProfile,
+    ) -> anyhow::Result<Self> {
+        let source = make("pipewiresrc")?;
+        source.set_property("fd", stream.fd);
+        source.set_property("path", stream.node_id.to_string());
+        source.set_property("do-timestamp", true);
+        let encoder = Self::new_from_source(&source, width, height, fps, bitrate_kbps, profile)?;
+        info!(
+            "GstEncoder({}) fused pipeline ready {}x{} @{}fps {}kbps (node_id={})",
+            encoder.element, width, height, fps, bitrate_kbps, stream.node_id
         );
+        Ok(encoder)
+    }
+
+    /// Create and start an encode pipeline fed by the `videotestsrc`
+    /// `pattern` instead of screen capture.
+    ///
+    /// The default SMPTE 75 % bars have well-known values, so sampling them on
+    /// the receiver shows range / matrix mismatches directly.
+    pub fn new_test_pattern(
+        pattern: &str,
+        width: u32,
+        height: u32,
+        fps: u32,
+        bitrate_kbps: u32,
+        profile: EncodeProfile,
+    ) -> anyhow::Result<Self> {
+        let source = elements::test_source(pattern, width, height, fps)?;
+        let encoder = Self::new_from_source(&source, width, height, fps, bitrate_kbps, profile)?;
+        info!(
+            "GstEncoder({}) test pattern {} ready {}x{} @{}fps {}kbps color={}",
+            encoder.element, pattern, width, height, fps, bitrate_kbps, profile.color
+        );
+        Ok(encoder)
+    }
+
+    /// Shared construction for encoders whose source lives inside the pipeline.
+    fn new_from_source(
+        source: &gstreamer::Element,
+        width: u32,
+        height: u32,
+        fps: u32,
+        bitrate_kbps: u32,
+        profile: EncodeProfile,
+    ) -> anyhow::Result<Self> {
+        // No format in the caps unless lossless: the encoder negotiates its
+        // preferred input (NV12 for every supported element) with videoconvert.
+        let out_caps = encoder_input_caps(profile, Some((width, height, fps)));
+        let pipeline = gstreamer::Pipeline::new();
+        let (enc_name, enc, caps) = build(&pipeline, source, &out_caps, width, height, bitrate_kbps, profile)?;
+        let encoded_rx = start(&pipeline, profile.queues.encoded_frames)?;
+
+        Ok(Self {
+            appsrc: None,
+            encoded_rx,
+            element: enc_name,
+            enc,
+            caps,
+            profile,
+            input: Cell::new(PixelFormat::Nv12),
+            width,
+            height,
+            fps: Cell::new(fps),
+            pushed: Cell::new(0),
+            encoded: 0,
+            lossless: profile.lossless,
+            pipeline,
+        })
+    }
+
+    /// `true` when the source is linked inside this pipeline (no `push_frame`).
+    pub fn is_fused(&self) -> bool {
+        self.appsrc.is_none()
+    }
+
+    /// GStreamer element name of the selected encoder (e.g. `"vaapih264enc"`).
+    pub fn element_name(&self) -> &'static str {
+        self.element
+    }
+
+    /// Change the target bitrate of the running encoder.
+    ///
+    /// No effect in lossless mode (constant quantizer).
+    pub fn set_bitrate(&self, kbps: u32) {
+        self.enc.set_property("bitrate", kbps);
+        info!("GstEncoder({}) bitrate → {} kbps", self.element, kbps);
+    }
+
+    /// Change the keyframe interval of the running encoder.
+    ///
+    /// Best-effort: some elements only pick this up at the next keyframe.
+    pub fn set_gop(&self, frames: u32) {
+        set_gop(&self.enc, self.element, frames);
+        info!("GstEncoder({}) GOP → {} frames", self.element, frames);
+    }
+
+    /// Change the frame rate of the running pipeline by renegotiating caps
+    /// (see the module docs).
+    ///
+    /// Split mode only re-announces the rate to the encoder's rate control;
+    /// capture must be slowed down by the caller.
+    pub fn set_fps(&self, fps: u32) {
+        let fps = fps.max(1);
+        if self.fps.replace(fps) == fps {
+            return;
+        }
+        match &self.appsrc {
+            Some(appsrc) => appsrc.set_caps(Some(&raw_caps(self.input.get(), self.width, self.height, fps))),
+            None => self
+                .caps
+                .set_property("caps", encoder_input_caps(self.profile, Some((self.width, self.height, fps)))),
+        }
+        info!("GstEncoder({}) fps → {}", self.element, fps);
+    }
 
-        pipeline
-            .set_state(gstreamer::State::Playing)
-            .context("Starting encoder pipeline")?;
+    /// Make the next encoded frame a keyframe (with SPS/PPS), e.g. when the
+    /// receiver sends `keyframe_request` after losing frames.
+    pub fn force_keyframe(&self) {
+        let event = gstreamer_video::UpstreamForceKeyUnitEvent::builder().all_headers(true).build();
+        if !self.enc.send_event(event) {
+            warn!("GstEncoder({}) ignored force-key-unit", self.element);
+        }
+    }
 
-        Ok(Self { appsrc, encoded_rx, _pipeline: pipeline })
+    /// Publish 1 fps thumbnails of the encoder input to `slot`.
+    pub fn set_preview(&self, slot: PreviewSlot) {
+        if let Err(e) = preview::attach(&self.pipeline, slot) {
+            warn!("GstEncoder({}) preview: {:#}", self.element, e);
+        }
     }
 
-    /// Push a BGRx raw frame into the encode pipeline.
+    /// `true` when encoding H.264 High 4:4:4 at constant QP.
+    pub fn is_lossless(&self) -> bool {
+        self.lossless
+    }
+
+    /// `false` only for the `x264enc` software fallback.
+    pub fn is_hardware_accelerated(&self) -> bool {
+        self.element != "x264enc"
+    }
+
+    /// Push a raw BGRx or NV12 frame into the encode pipeline.
     ///
-    /// Non-blocking — returns `Err` only if the pipeline has terminated.
+    /// Non-blocking — returns `Err` if the pipeline has terminated or the
+    /// encoder was built in fused mode.
     pub fn push_frame(&self, frame: CapturedFrame) -> anyhow::Result<()> {
+        let appsrc = self
+            .appsrc
+            .as_ref()
+            .context("push_frame on a fused encoder (capture is internal)")?;
+
+        if frame.format != self.input.get() {
+            info!(
+                "GstEncoder({}) input format {:?} → {:?}",
+                self.element, self.input.get(), frame.format
+            );
+            appsrc.set_caps(Some(&raw_caps(frame.format, self.width, self.height, self.fps.get())));
+            self.input.set(frame.format);
+        }
+
         let mut buf = gstreamer::Buffer::with_size(frame.data.len())
             .context("Allocating GStreamer buffer")?;
         {
@@ -161,9 +480,10 @@ impl GstEncoder {
             map.copy_from_slice(&frame.data);
         }
 
-        self.appsrc
+        appsrc
             .push_buffer(buf)
             .map_err(|e| anyhow::anyhow!("appsrc push_buffer: {:?}", e))?;
+        self.pushed.set(self.pushed.get() + 1);
 
         Ok(())
     }
@@ -172,11 +492,220 @@ impl GstEncoder {
     ///
     /// Returns `None` when the pipeline ends.
     pub async fn next_encoded(&mut self) -> Option<EncodedFrame> {
-        self.encoded_rx.recv().await
+        let frame = self.encoded_rx.recv().await;
+        if frame.is_some() {
+            self.encoded += 1;
+        }
+        frame
+    }
+
+    /// Raw frames pushed but not yet returned by [`GstEncoder::next_encoded`].
+    ///
+    /// Always 0 in fused mode.
+    pub fn in_flight(&self) -> u64 {
+        self.pushed.get().saturating_sub(self.encoded)
     }
 
     /// Send EOS to the pipeline and wait for it to drain.
     pub fn send_eos(&self) {
-        let _ = self.appsrc.end_of_stream();
+        match &self.appsrc {
+            Some(appsrc) => {
+                let _ = appsrc.end_of_stream();
+            }
+            None => {
+                self.pipeline.send_event(gstreamer::event::Eos::new());
+            }
+        }
+    }
+}
+
+// ── Pipeline construction ─────────────────────────────────────────────────────
+
+/// Probe the best encoder element.
+fn select_encoder(profile: EncodeProfile) -> &'static str {
+    if profile.lossless {
+        // Only x264enc does 4:4:4; the hardware encoders are 4:2:0-only.
+        return "x264enc";
+    }
+    // x264enc should always be available if gst-plugins-ugly is installed.
+    probe_best_encoder().unwrap_or_else(|| {
+        warn!("No H.264 encoder found by probing; falling back to x264enc");
+        "x264enc"
+    })
+}
+
+/// Add `source` and everything after it to `pipeline`:
+///
+/// ```text
+/// source → tee → queue → [videorate] → videoconvert → out_caps → enc → H.264 AU caps → h264parse → appsink name=sink
+///           └→ preview branch
+/// ```
+///
+/// `videorate` (drop-only) is added when `out_caps` carries a frame rate,
+/// i.e. for in-pipeline sources. Returns the selected encoder's name and
+/// element, and the `out_caps` filter.
+fn build(
+    pipeline: &gstreamer::Pipeline,
+    source: &gstreamer::Element,
+    out_caps: &gstreamer::Caps,
+    width: u32,
+    height: u32,
+    bitrate_kbps: u32,
+    profile: EncodeProfile,
+) -> anyhow::Result<(&'static str, gstreamer::Element, gstreamer::Element)> {
+    let enc_name = select_encoder(profile);
+    let enc = make_named(enc_name, "enc")?;
+    tune_encoder(&enc, enc_name, profile.tune, profile.latency, profile.gop);
+    if profile.lossless {
+        enc.set_property_from_str("pass", "quant");
+        enc.set_property("quantizer", LOSSLESS_QP);
+    }
+    enc.set_property("bitrate", bitrate_kbps);
+
+    let au = caps_filter(
+        &gstreamer::Caps::builder("video/x-h264")
+            .field("stream-format", "byte-stream")
+            .field("alignment", "au")
+            .build(),
+    )?;
+    // Never drop encoded frames: the receiver couldn't decode until the next keyframe.
+    let appsink =
+        AppSink::builder().name("sink").max_buffers(profile.queues.encoded as u32).drop(false).sync(false).build();
+
+    pipeline.add(source).context("Adding source")?;
+    let (tee, queue) = preview::split(pipeline, width, height)?;
+    elements::link_chain(&[source, &tee])?;
+    let convert = make("videoconvert")?;
+    let caps = caps_filter(out_caps)?;
+    elements::add_chain(
+        pipeline,
+        &[&convert, &caps, &enc, &au, &make("h264parse")?, appsink.upcast_ref::<gstreamer::Element>()],
+    )?;
+    if out_caps.structure(0).is_some_and(|s| s.has_field("framerate")) {
+        let rate = make("videorate")?;
+        rate.set_property("drop-only", true);
+        elements::add_chain(pipeline, &[&rate])?;
+        elements::link_chain(&[&queue, &rate, &convert])?;
+    } else {
+        elements::link_chain(&[&queue, &convert])?;
+    }
+    debug!("Encoder pipeline: {} → {} {}kbps", source.name(), enc_name, bitrate_kbps);
+    Ok((enc_name, enc, caps))
+}
+
+/// Hook the `sink` appsink of `pipeline` up to an [`EncodedFrame`] channel
+/// of `depth` frames and set the pipeline to Playing.
+///
+/// A bus watcher closes the channel on EOS or error so that
+/// [`GstEncoder::next_encoded`] returns `None` when the stream ends.
+fn start(pipeline: &gstreamer::Pipeline, depth: usize) -> anyhow::Result<mpsc::Receiver<EncodedFrame>> {
+    let appsink: AppSink = pipeline
+        .by_name("sink")
+        .context("Finding appsink 'sink'")?
+        .downcast::<AppSink>()
+        .map_err(|_| anyhow::anyhow!("Expected AppSink"))?;
+
+    let (encoded_tx, encoded_rx) = mpsc::channel::<EncodedFrame>(depth);
+    // Shared so the bus watcher can drop the sender and end the stream.
+    let encoded_tx = Arc::new(Mutex::new(Some(encoded_tx)));
+    let sample_tx = Arc::clone(&encoded_tx);
+
+    appsink.set_callbacks(
+        AppSinkCallbacks::builder()
+            .new_sample(move |sink| {
+                let sample = sink.pull_sample().map_err(|_| gstreamer::FlowError::Eos)?;
+                let buffer = sample.buffer().ok_or(gstreamer::FlowError::Error)?;
+
+                let pts_us = buffer
+                    .pts()
+                    .map(|t| t.useconds())
+                    .unwrap_or(0);
+                let is_keyframe = !buffer
+                    .flags()
+                    .contains(gstreamer::BufferFlags::DELTA_UNIT);
+
+                let map = buffer
+                    .map_readable()
+                    .map_err(|_| gstreamer::FlowError::Error)?;
+                let data = Bytes::copy_from_slice(map.as_slice());
+                let temporal_layer = if is_keyframe { 0 } else { temporal_layer(VideoCodec::H264, &data) };
+
+                let frame = EncodedFrame {
+                    data,
+                    timestamp_us: pts_us,
+                    is_keyframe,
+                    codec: VideoCodec::H264,
+                    temporal_layer,
+                    partial: false,
+                };
+
+                let Some(tx) = sample_tx.lock().unwrap().clone() else {
+                    return Err(gstreamer::FlowError::Flushing);
+                };
+                if tx.blocking_send(frame).is_err() {
+                    return Err(gstreamer::FlowError::Flushing);
+                }
+                Ok(gstreamer::FlowSuccess::Ok)
+            })
+            .build(),
+    );
+
+    pipeline
+        .set_state(gstreamer::State::Playing)
+        .context("Starting encoder pipeline")?;
+
+    let bus = pipeline.bus().context("Encoder pipeline bus")?;
+    let pipeline_weak = pipeline.downgrade();
+    tokio::task::spawn_blocking(move || {
+        loop {
+            match bus.timed_pop(gstreamer::ClockTime::from_seconds(1)) {
+                Some(msg) => match msg.view() {
+                    gstreamer::MessageView::Eos(_) => {
+                        info!("Encoder pipeline EOS");
+                        break;
+                    }
+                    gstreamer::MessageView::Error(e) => {
+                        error!("Encoder pipeline error: {} ({:?})", e.error(), e.debug());
+                        break;
+                    }
+                    _ => {}
+                },
+                // Poll timeout — stop once the GstEncoder has been dropped.
+                None if pipeline_weak.upgrade().is_none() => break,
+                None => {}
+            }
+        }
+        encoded_tx.lock().unwrap().take();
+    });
+
+    Ok(encoded_rx)
+}
+
+/// Caps filter between `videoconvert` and the encoder.
+///
+/// Pins the colorimetry, Y444 in lossless mode (so x264enc picks High 4:4:4),
+/// and optionally size / frame rate for in-pipeline sources.
+fn encoder_input_caps(profile: EncodeProfile, geometry: Option<(u32, u32, u32)>) -> gstreamer::Caps {
+    let mut caps = gstreamer::Caps::builder("video/x-raw");
+    if profile.lossless {
+        caps = caps.field("format", "Y444");
+    }
+    if let Some((width, height, fps)) = geometry {
+        caps = caps
+            .field("width", width as i32)
+            .field("height", height as i32)
+            .field("framerate", gstreamer::Fraction::new(fps as i32, 1));
     }
+    caps.field("colorimetry", profile.color.gst_colorimetry()).build()
+}
+
+/// Raw video caps for the appsrc.
+fn raw_caps(format: PixelFormat, width: u32, height: u32, fps: u32) -> gstreamer::Caps {
+    gstreamer::Caps::builder("video/x-raw")
+        .field("format", format.gst_format())
+        .field("width", width as i32)
+        .field("height", height as i32)
+        .field("framerate", gstreamer::Fraction::new(fps as i32, 1))
+        .field("colorimetry", "bt709")
+        .build()
 }
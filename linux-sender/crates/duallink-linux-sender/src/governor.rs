@@ -0,0 +1,128 @@
+//! Content-adaptive frame governor.
+//!
+//! A static desktop (code editor, terminal) captured at 60 fps produces 60
+//! identical frames per second. [`FrameGovernor`] fingerprints each captured
+//! frame and skips encoding when nothing changed, while still letting one
+//! refresh frame through every [`REFRESH_INTERVAL`] so late joiners and lost
+//! packets recover.
+//!
+//! `pipewiresrc` does not expose PipeWire damage regions as buffer meta, so
+//! change detection uses a cheap signature instead: every
+//! [`ROW_STEP`]-th row of the frame (the Y plane for NV12) is folded into a
+//! 64-bit hash, 8 bytes at a time. Row padding past the visible width is
+//! left out, so it can't make a static frame look changed.
+//!
+//! ```text
+//! capturer ──► FrameGovernor ──(changed / refresh due)──► FrameQueue ──► encoder
+//!                   └──(unchanged)──► skipped
+//! ```
+
+use std::time::{Duration, Instant};
+
+use duallink_capture_linux::CapturedFrame;
+
+/// Longest gap between encoded frames while the content is static.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Sample every N-th row for the frame signature (1/4 of the frame at 4).
+const ROW_STEP: usize = 4;
+
+/// Decides per captured frame whether it is worth encoding.
+pub struct FrameGovernor {
+    last_signature: Option<u64>,
+    last_encoded:   Instant,
+    skipped:        u64,
+}
+
+impl FrameGovernor {
+    pub fn new() -> Self {
+        Self { last_signature: None, last_encoded: Instant::now(), skipped: 0 }
+    }
+
+    /// `true` if `frame` differs from the last encoded one or a refresh is due.
+    pub fn should_encode(&mut self, frame: &CapturedFrame) -> bool {
+        let signature = frame_signature(frame);
+        let unchanged = self.last_signature == Some(signature);
+        if unchanged && self.last_encoded.elapsed() < REFRESH_INTERVAL {
+            self.skipped += 1;
+            return false;
+        }
+        self.last_signature = Some(signature);
+        self.last_encoded = Instant::now();
+        true
+    }
+
+    /// Total frames skipped as unchanged since creation.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+}
+
+/// 64-bit signature over a row subsample of the frame's luma / pixel data.
+fn frame_signature(frame: &CapturedFrame) -> u64 {
+    // Y plane only for NV12 — chroma-only changes are rare on desktop content.
+    let row_bytes = frame.format.row_bytes(frame.width) as usize;
+    let stride = (frame.stride as usize).max(row_bytes);
+    let rows = frame.height as usize;
+    if row_bytes == 0 || rows == 0 {
+        return 0;
+    }
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    // The last row may come without its padding.
+    let visible = frame.data.chunks(stride).take(rows).filter_map(|row| row.get(..row_bytes));
+    for row in visible.step_by(ROW_STEP) {
+        for word in row.chunks_exact(8) {
+            let v = u64::from_le_bytes(word.try_into().unwrap());
+            hash = (hash ^ v).rotate_left(23).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use duallink_capture_linux::PixelFormat;
+
+    use super::*;
+
+    const STRIDE: usize = 48;
+
+    /// An 8×8 BGRx frame with 16 bytes of padding per row.
+    fn frame() -> CapturedFrame {
+        let data = vec![0; STRIDE * 8];
+        CapturedFrame { data, pts_ms: 0, format: PixelFormat::Bgrx, width: 8, height: 8, stride: STRIDE as u32 }
+    }
+
+    #[test]
+    fn unchanged_frames_skip_until_content_changes() {
+        let mut governor = FrameGovernor::new();
+        assert!(governor.should_encode(&frame()));
+        assert!(!governor.should_encode(&frame()));
+
+        // Padding isn't content.
+        let mut padded = frame();
+        padded.data[2 * STRIDE + 32] = 0xff;
+        assert!(!governor.should_encode(&padded));
+        assert_eq!(governor.skipped(), 2);
+
+        let mut changed = frame();
+        changed.data[ROW_STEP * STRIDE] = 0xff;
+        assert!(governor.should_encode(&changed));
+        // Every change goes through, at full rate.
+        assert!(governor.should_encode(&frame()));
+        assert!(governor.should_encode(&changed));
+        assert_eq!(governor.skipped(), 2);
+    }
+
+    #[test]
+    fn static_content_refreshes_once_per_interval() {
+        let mut governor = FrameGovernor::new();
+        assert!(governor.should_encode(&frame()));
+        assert!(!governor.should_encode(&frame()));
+
+        governor.last_encoded -= REFRESH_INTERVAL;
+        assert!(governor.should_encode(&frame()));
+        assert!(!governor.should_encode(&frame()));
+    }
+}
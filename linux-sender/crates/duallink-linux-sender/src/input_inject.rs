@@ -100,6 +100,10 @@ mod linux_impl {
         keyboard: VirtualDevice,
         last_x:  f64,
         last_y:  f64,
+        /// Rotation accumulated since the last arrow key, in degrees.
+        rotation: f64,
+        /// Travel of the swipe in progress (normalised).
+        swipe:   (f64, f64),
     }
 
     impl Injector {
@@ -140,7 +144,7 @@ mod linux_impl {
                 .with_keys(&key_set)?
                 .build()?;
 
-            Ok(Self { mouse, keyboard, last_x: 0.5, last_y: 0.5 })
+            Ok(Self { mouse, keyboard, last_x: 0.5, last_y: 0.5, rotation: 0.0, swipe: (0.0, 0.0) })
         }
 
         pub(super) fn inject(&mut self, event: duallink_core::InputEvent) -> anyhow::Result<()> {
@@ -228,6 +232,10 @@ mod linux_impl {
 
                 // Gestures — map pinch to Ctrl+scroll (universal zoom)
                 InputEvent::GesturePinch { magnification, .. } => {
+                    // Begin / End carry no zoom step.
+                    if magnification == 0.0 {
+                        return Ok(());
+                    }
                     let ctrl_down = [
                         evdev::InputEvent::new(EventType::KEY, Key::KEY_LEFTCTRL.code(), 1),
                         evdev::InputEvent::new(EventType::SYNCHRONIZATION, 0, 0),
@@ -270,8 +278,15 @@ mod linux_impl {
                 }
 
                 // Rotation: map to left/right arrow keys (common for presentation next/prev)
-                InputEvent::GestureRotation { rotation, .. } => {
+                InputEvent::GestureRotation { rotation, phase, .. } => {
+                    // Phased rotations arrive in small steps: one key per 15°.
+                    self.rotation = match phase {
+                        GesturePhase::Begin | GesturePhase::Cancelled => 0.0,
+                        _ => self.rotation + rotation,
+                    };
+                    let rotation = self.rotation;
                     if rotation.abs() > 15.0 {
+                        self.rotation = 0.0;
                         let key = if rotation > 0.0 { Key::KEY_RIGHT } else { Key::KEY_LEFT };
                         let events = [
                             evdev::InputEvent::new(EventType::KEY, key.code(), 1),
@@ -283,7 +298,19 @@ mod linux_impl {
                     }
                 }
 
-                InputEvent::GestureSwipe { delta_x, delta_y, .. } => {
+                InputEvent::GestureSwipe { delta_x, delta_y, phase } => {
+                    // Act once per swipe, on its total travel when it ends.
+                    self.swipe.0 += delta_x;
+                    self.swipe.1 += delta_y;
+                    match phase {
+                        GesturePhase::End => {}
+                        GesturePhase::Begin | GesturePhase::Cancelled => {
+                            self.swipe = (0.0, 0.0);
+                            return Ok(());
+                        }
+                        GesturePhase::Changed => return Ok(()),
+                    }
+                    let (delta_x, delta_y) = std::mem::take(&mut self.swipe);
                     // 3-finger swipe: map to desktop switching shortcuts
                     if delta_x.abs() > delta_y.abs() {
                         let key = if delta_x > 0.0 { Key::KEY_RIGHT } else { Key::KEY_LEFT };
@@ -10,6 +10,9 @@
 //! | **GUI** (default) | `./duallink-sender` | — |
 //! | **Headless** | `DUALLINK_NO_UI=1 ./duallink-sender` | `DUALLINK_HOST`, `DUALLINK_PIN`, etc. |
 //!
+//! Either mode streams a generated pattern instead of the screen with
+//! `--test-pattern[=<name>]` (see [`duallink_core::test_pattern`]).
+//!
 //! # Phase 5D status
 //!
 //! - [x] egui settings UI (host, PIN, resolution, fps, bitrate, display count)
@@ -19,13 +22,16 @@
 //! - [ ] Absolute mouse positioning (ABS_X/Y tablet device)
 //! - [ ] egui FPS graph overlay
 
+mod backpressure;
 mod encoder;
+mod governor;
 mod input_inject;
 mod pipeline;
+mod strings;
 mod ui;
 
 use anyhow::Result;
-use tracing::info;
+use tracing::{info, warn};
 use tracing_subscriber::EnvFilter;
 
 fn main() -> Result<()> {
@@ -62,10 +68,12 @@ fn main() -> Result<()> {
         let _rt_guard = rt.enter();
 
         let native_options = eframe::NativeOptions {
-            viewport: egui::ViewportBuilder::default()
-                .with_title("DualLink Linux Sender")
-                .with_inner_size([480.0, 320.0])
-                .with_min_inner_size([380.0, 280.0]),
+            viewport: ui::restore_window(
+                egui::ViewportBuilder::default()
+                    .with_title("DualLink Linux Sender")
+                    .with_inner_size([480.0, 320.0])
+                    .with_min_inner_size([380.0, 280.0]),
+            ),
             ..Default::default()
         };
 
@@ -82,6 +90,9 @@ fn main() -> Result<()> {
 
 async fn headless_main() -> Result<()> {
     use std::{env, time::{Duration, SystemTime, UNIX_EPOCH}};
+    use duallink_core::{
+        ColorSpace, LatencyMode, MonitorAssignments, NetworkPolicy, PortMap, QualityPreset, SenderQueues,
+    };
     use pipeline::{PipelineConfig, PipelineState, SenderPipeline};
     use tokio::sync::mpsc;
 
@@ -91,12 +102,54 @@ async fn headless_main() -> Result<()> {
         .ok().and_then(|v| v.parse().ok()).unwrap_or(1);
     let width:  u32 = env::var("DUALLINK_WIDTH").ok().and_then(|v| v.parse().ok()).unwrap_or(1920);
     let height: u32 = env::var("DUALLINK_HEIGHT").ok().and_then(|v| v.parse().ok()).unwrap_or(1080);
-    let fps:    u32 = env::var("DUALLINK_FPS").ok().and_then(|v| v.parse().ok()).unwrap_or(60);
-    let kbps:   u32 = env::var("DUALLINK_KBPS").ok().and_then(|v| v.parse().ok()).unwrap_or(8000);
+    let mut fps:  u32 = env::var("DUALLINK_FPS").ok().and_then(|v| v.parse().ok()).unwrap_or(60);
+    let mut kbps: u32 = env::var("DUALLINK_KBPS").ok().and_then(|v| v.parse().ok()).unwrap_or(8000);
+    // DUALLINK_PRESET=text_sharp|video_smooth|battery_saver overrides fps/bitrate.
+    let preset = env::var("DUALLINK_PRESET").ok().and_then(|v| QualityPreset::from_name(&v));
+    if let Some(p) = preset {
+        fps = p.params().target_fps;
+        kbps = (p.params().max_bitrate_bps / 1000) as u32;
+    }
+    let nv12        = env::var("DUALLINK_NV12").as_deref() != Ok("0");
+    // --test-pattern[=smpte75] streams a generated pattern instead of the screen.
+    let test_pattern = duallink_core::test_pattern_arg(env::args());
+    let mode = match test_pattern {
+        Some(_) => pipeline::SenderPipelineMode::TestPattern,
+        None => env::var("DUALLINK_PIPELINE_MODE")
+            .ok().and_then(|v| pipeline::SenderPipelineMode::from_name(&v)).unwrap_or_default(),
+    };
+    // DUALLINK_CAPTURE_BACKEND=pipewire|screencopy|test forces the split-mode capture backend.
+    let capture_backend = env::var("DUALLINK_CAPTURE_BACKEND")
+        .ok().and_then(|v| duallink_capture_linux::Backend::from_name(&v));
+    // DUALLINK_ENCODER=openh264 encodes in software even with GStreamer encoders installed.
+    let software_encoder = env::var("DUALLINK_ENCODER").as_deref() == Ok("openh264");
+    let queue_depth: usize = env::var("DUALLINK_QUEUE_DEPTH").ok().and_then(|v| v.parse().ok()).unwrap_or(1);
+    let drop_policy = env::var("DUALLINK_DROP_POLICY")
+        .ok().and_then(|v| backpressure::DropPolicy::from_name(&v)).unwrap_or_default();
+    let adaptive_fps = env::var("DUALLINK_ADAPTIVE_FPS").as_deref() != Ok("0");
+    let lossless     = env::var("DUALLINK_LOSSLESS").as_deref() == Ok("1");
+    // DUALLINK_MATCH_RECEIVER=1 streams at each receiver panel's resolution instead of WIDTH×HEIGHT.
+    let match_receiver_resolution = env::var("DUALLINK_MATCH_RECEIVER").as_deref() == Ok("1");
+    // DUALLINK_LATENCY_MODE=ultra_low|quality
+    let latency_mode = LatencyMode::from_env();
+    // DUALLINK_COLOR=bt709|bt601[-limited|-full]
+    let color = env::var("DUALLINK_COLOR").ok().and_then(|v| ColorSpace::from_name(&v)).unwrap_or_default();
+    // DUALLINK_MONITOR_<n>=DP-1 overrides the monitor saved for stream n in the UI.
+    let monitors = MonitorAssignments::load();
+    // DUALLINK_NETWORK_CAPS=wifi=6000@30,usb=3000@30 caps streams by the network they go over.
+    let network_caps = NetworkPolicy::from_env();
+    // DUALLINK_TOTAL_KBPS=12000 shares one bitrate budget between all displays.
+    let budget = duallink_sender_lib::BitrateAllocator::from_env();
+    // DUALLINK_QUEUES=captured=1,encoded=2,input=128 tunes the queues between stages.
+    let queues = SenderQueues::from_env();
+    // DUALLINK_BASE_PORT=9000 for a receiver whose display 0 is on UDP 9000 / TCP 9001.
+    let ports = env::var("DUALLINK_BASE_PORT").ok().and_then(|v| v.parse().ok())
+        .map(|base| PortMap::contiguous(base, display_count))
+        .unwrap_or_default();
 
     info!(
-        "Headless mode: {} display(s) → {} — {}×{} @{}fps {}kbps",
-        display_count, host, width, height, fps, kbps
+        "Headless mode: {} display(s) → {} — {}×{} @{}fps {}kbps ({:?})",
+        display_count, host, width, height, fps, kbps, mode
     );
 
     let (status_tx, mut status_rx) = mpsc::channel::<pipeline::PipelineStatus>(64);
@@ -107,10 +160,31 @@ async fn headless_main() -> Result<()> {
             host: host.clone(),
             pairing_pin: pin.clone(),
             display_index: i,
+            ports: ports.clone(),
             width,
             height,
             fps,
             bitrate_kbps: kbps,
+            prefer_nv12: nv12,
+            mode,
+            test_pattern: test_pattern.unwrap_or(duallink_core::DEFAULT_TEST_PATTERN),
+            queue_depth,
+            drop_policy,
+            adaptive_fps,
+            preset,
+            lossless,
+            latency_mode,
+            color,
+            monitor: env::var(format!("DUALLINK_MONITOR_{i}"))
+                .ok()
+                .or_else(|| monitors.get(i).map(str::to_owned)),
+            capture_backend,
+            software_encoder,
+            remote_preview: false,
+            match_receiver_resolution,
+            network_caps: network_caps.clone(),
+            budget: budget.clone(),
+            queues,
         };
         pipelines.push(SenderPipeline::spawn(cfg, status_tx.clone()));
     }
@@ -119,11 +193,19 @@ async fn headless_main() -> Result<()> {
     let mut stopped = 0usize;
     while let Some(s) = status_rx.recv().await {
         match &s.state {
+            PipelineState::AwaitingCapture => {
+                info!("Display[{}] waiting for a screen to be picked in the screen-cast dialog", s.display_index);
+            }
             PipelineState::Streaming => {
                 info!(
-                    "Display[{}] streaming — {:.1} fps {} frames",
-                    s.display_index, s.fps, s.frames_sent
+                    "Display[{}] streaming — {:.1} fps {} frames {} dropped {} skipped (encoder={}{})",
+                    s.display_index, s.fps, s.frames_sent, s.frames_dropped, s.frames_skipped,
+                    s.encoder.as_deref().unwrap_or("?"),
+                    if s.lossless { ", lossless 4:4:4" } else { "" }
                 );
+                if let Some(cap) = s.throttled_fps {
+                    warn!("Display[{}] overloaded — capture capped at {} fps", s.display_index, cap);
+                }
             }
             PipelineState::Stopped => {
                 info!("Display[{}] stopped", s.display_index);
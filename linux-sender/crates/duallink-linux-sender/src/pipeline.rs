@@ -9,24 +9,87 @@
 //!
 //! Create N pipelines for N display streams (multi-monitor sender).
 //!
-//! # Status channel
+//! Signaling, rate control, blanking, pausing, battery saver and status are
+//! the shared [`SenderSession`] of `duallink-sender-lib`; this module is the
+//! Linux [`Platform`] under it — PipeWire capture, the GStreamer encoder,
+//! uinput injection and the idle inhibitor.
 //!
-//! [`SenderPipeline::spawn`] returns a [`PipelineStatus`] receiver that the
-//! egui UI polls with [`try_recv`](tokio::sync::mpsc::Receiver::try_recv) to
-//! get live FPS, frame count, and connection state.
-
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-
-use anyhow::{Context, Result};
-use duallink_capture_linux::{CaptureConfig, ScreenCapturer};
-use duallink_core::StreamConfig;
-use duallink_transport_client::{SignalingClient, VideoSender};
-use tokio::sync::mpsc;
-use tracing::{info, warn};
-
-use crate::encoder::GstEncoder;
+//! # Modes
+//!
+//! [`SenderPipelineMode::Split`] captures through `ScreenCapturer` and pushes
+//! raw frames into the encoder's appsrc — two full-frame copies plus a channel
+//! hop, but capture and encode can be swapped independently.
+//! [`SenderPipelineMode::Fused`] links `pipewiresrc` straight into the
+//! encoder inside one GStreamer pipeline. [`SenderPipelineMode::TestPattern`]
+//! streams a `videotestsrc` pattern ([`PipelineConfig::test_pattern`], set
+//! with `--test-pattern`) instead of the screen, to verify the link, the
+//! decoder and colour range / matrix handling end to end without asking for
+//! capture.
+//!
+//! Split-mode capture runs on any capture [`Backend`]:
+//! [`PipelineConfig::capture_backend`] forces one (`DUALLINK_CAPTURE_BACKEND`),
+//! otherwise the first available is used — `Backend::Screencopy` on wlroots
+//! compositors, which needs no portal prompt. `Backend::TestPattern` scrolls
+//! bars through the whole split path — capture, queue and appsrc — with no
+//! portal prompt.
+//!
+//! # Encoder
+//!
+//! Encoding goes through the best GStreamer H.264 element installed. In
+//! split mode, when there is none or [`PipelineConfig::software_encoder`]
+//! asks for it (`DUALLINK_ENCODER=openh264`), the sender-lib
+//! `OpenH264Encoder` takes its place instead (feature `openh264`, on by
+//! default) — it needs no GStreamer plugins, but has no 4:4:4 mode and no
+//! local preview.
+//!
+//! # Backpressure
+//!
+//! In split mode captured frames wait in a [`FrameQueue`] and are handed to
+//! the encoder while it has fewer than [`MAX_IN_FLIGHT`] frames outstanding;
+//! the [`OverloadMonitor`] caps the capture rate under sustained drops. With
+//! [`PipelineConfig::adaptive_fps`] — and always in battery saver — the
+//! [`FrameGovernor`] skips unchanged frames first.
+//!
+//! # Runtime rates
+//!
+//! Rate changes reconfigure the encoder in place (see
+//! [`GstEncoder::set_fps`]); `ScreenCapturer` lowers its own capture rate.
+//!
+//! # Preview
+//!
+//! The encoder tees 1 fps RGBA thumbnails of its input into the handle's
+//! [`SenderPipeline::preview`] slot, so the UI shows what is being sent.
+//! With [`PipelineConfig::remote_preview`], thumbnails of what the receiver
+//! actually shows land in [`SenderPipeline::remote_preview`].
+
+use anyhow::Context;
+use bytes::Bytes;
+use duallink_capture_linux::{
+    open_pipewire_stream, Backend, CaptureConfig, CapturedFrame, PixelFormat, ScreenCapturer,
+};
+use duallink_core::{
+    configured_idle_after, network, ColorSpace, EncodedFrame, EncoderTune, FileTransfers, IdleInhibitor, InputEvent,
+    LatencyMode, NetworkKind, NetworkPolicy, QualityPreset, SenderQueues, StreamConfig, DEFAULT_TEST_PATTERN,
+};
+#[cfg(feature = "openh264")]
+use duallink_sender_lib::{OpenH264Encoder, RawFormat, RawFrame};
+use duallink_sender_lib::preview::{self, PreviewSlot};
+use duallink_sender_lib::{
+    configured_capture_stall, BitrateAllocator, Capture, Encoder, FeedStats, PipelineLog, Platform, SenderSession,
+    SessionConfig, CUSTOM_GOP,
+};
+use duallink_transport_client::PortMap;
+use tokio::sync::{mpsc, watch};
+use tracing::warn;
+
+pub use duallink_sender_lib::{PipelineState, PipelineStatus};
+
+use crate::backpressure::{DropPolicy, FrameQueue, OverloadMonitor};
+use crate::encoder::{probe_best_encoder, EncodeProfile, GstEncoder};
+use crate::governor::FrameGovernor;
+
+/// Raw frames allowed inside the encoder before new ones wait in the queue.
+const MAX_IN_FLIGHT: u64 = 2;
 
 // ── Configuration ─────────────────────────────────────────────────────────────
 
@@ -37,11 +100,105 @@ pub struct PipelineConfig {
     pub host:          String,
     pub pairing_pin:   String,
     pub display_index: u8,
+    /// Receiver ports from mDNS discovery (empty = default layout). The
+    /// video port is then taken from `hello_ack` when the receiver sends one.
+    pub ports:         PortMap,
     // Video
     pub width:         u32,
     pub height:        u32,
     pub fps:           u32,
     pub bitrate_kbps:  u32,
+    /// Capture NV12 directly when the compositor offers it (skips BGRx conversion).
+    pub prefer_nv12:   bool,
+    /// How capture is linked to the encoder.
+    pub mode:          SenderPipelineMode,
+    /// `videotestsrc` pattern of [`SenderPipelineMode::TestPattern`] (one of
+    /// [`duallink_core::TEST_PATTERNS`]).
+    pub test_pattern:  &'static str,
+    // Backpressure (split mode)
+    /// Raw frames that may wait for the encoder before the drop policy applies.
+    pub queue_depth:   usize,
+    pub drop_policy:   DropPolicy,
+    /// Skip encoding unchanged frames (split mode), with a periodic refresh.
+    pub adaptive_fps:  bool,
+    /// Quality preset the fps/bitrate above were taken from (`None` = custom).
+    /// Also selects encoder tune and GOP length. Follows presets applied
+    /// mid-session.
+    pub preset:        Option<QualityPreset>,
+    /// Request H.264 High 4:4:4 near-lossless encoding for text-heavy
+    /// desktops. Only used if the receiver advertises `h264_444`.
+    pub lossless:      bool,
+    /// Latency / quality trade-off to ask the receiver for. Only used if
+    /// the receiver advertises `latency_mode`.
+    pub latency_mode:  LatencyMode,
+    /// Colour range / matrix of the encoded stream, sent to the receiver.
+    pub color:         ColorSpace,
+    /// Local monitor to capture, by connector name (`None` = by display index).
+    pub monitor:       Option<String>,
+    /// Split-mode capture backend (`None` = first available, see
+    /// [`duallink_capture_linux::backend::select`]).
+    pub capture_backend: Option<Backend>,
+    /// Encode with OpenH264 rather than GStreamer (split mode, feature
+    /// `openh264`). OpenH264 is also used when GStreamer has no H.264 encoder.
+    pub software_encoder: bool,
+    /// Ask the receiver for thumbnails of what it shows.
+    pub remote_preview: bool,
+    /// Stream at the receiver panel's resolution (from `hello_ack` and
+    /// later display changes) instead of `width` × `height`.
+    pub match_receiver_resolution: bool,
+    /// Bitrate / fps caps by the kind of network the receiver is reached over.
+    pub network_caps:  NetworkPolicy,
+    /// Bitrate budget shared by all displays (`DUALLINK_TOTAL_KBPS`; `None` =
+    /// each streams at its own rate).
+    pub budget:        Option<BitrateAllocator>,
+    /// Queue depths between capture, encoder, network and input (see
+    /// [`duallink_core::queues`]).
+    pub queues:        SenderQueues,
+}
+
+impl PipelineConfig {
+    /// Encoder tune and keyframe interval (frames) for the active preset.
+    ///
+    /// Lossless and latency mode are taken from the negotiated `stream`, not
+    /// [`PipelineConfig::lossless`] / [`PipelineConfig::latency_mode`].
+    fn encode_profile(&self, stream: &StreamConfig) -> EncodeProfile {
+        let (tune, gop) = match self.preset {
+            Some(p) => (p.params().tune, p.params().keyframe_interval),
+            None => (EncoderTune::LowLatency, CUSTOM_GOP),
+        };
+        EncodeProfile {
+            tune,
+            latency: stream.latency_mode,
+            gop,
+            lossless: stream.lossless,
+            color: self.color,
+            queues: self.queues,
+        }
+    }
+}
+
+/// How the capture stage is connected to the encoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SenderPipelineMode {
+    /// `ScreenCapturer` (appsink) → channel → `GstEncoder` (appsrc).
+    #[default]
+    Split,
+    /// `pipewiresrc → videoconvert → encoder → appsink` in one pipeline.
+    Fused,
+    /// A `videotestsrc` pattern instead of capture — no portal prompt.
+    TestPattern,
+}
+
+impl SenderPipelineMode {
+    /// Parse `"split"` / `"fused"` / `"test"` (case-insensitive).
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "split" => Some(Self::Split),
+            "fused" => Some(Self::Fused),
+            "test" | "test-pattern" => Some(Self::TestPattern),
+            _ => None,
+        }
+    }
 }
 
 impl Default for PipelineConfig {
@@ -50,292 +207,457 @@ impl Default for PipelineConfig {
             host:          "192.168.1.100".to_owned(),
             pairing_pin:   "000000".to_owned(),
             display_index: 0,
+            ports:         PortMap::default(),
             width:         1920,
             height:        1080,
             fps:           60,
             bitrate_kbps:  8000,
+            prefer_nv12:   true,
+            mode:          SenderPipelineMode::Split,
+            test_pattern:  DEFAULT_TEST_PATTERN,
+            queue_depth:   1,
+            drop_policy:   DropPolicy::LatestWins,
+            adaptive_fps:  true,
+            preset:        None,
+            lossless:      false,
+            latency_mode:  LatencyMode::UltraLow,
+            color:         ColorSpace::default(),
+            monitor:       None,
+            capture_backend: None,
+            software_encoder: false,
+            remote_preview: false,
+            match_receiver_resolution: false,
+            network_caps:  NetworkPolicy::default(),
+            budget:        None,
+            queues:        SenderQueues::default(),
         }
     }
 }
 
-// ── Status ─────────────────────────────────────────────────────────────────────
-
-/// Live status update sent by the pipeline task to the UI.
-#[derive(Debug, Clone)]
-pub struct PipelineStatus {
-    pub display_index: u8,
-    pub state:         PipelineState,
-    /// Instantaneous frames per second.
-    pub fps:           f32,
-    /// Total frames sent since pipeline start.
-    pub frames_sent:   u64,
-}
-
-/// State of a sender pipeline.
-#[derive(Debug, Clone, PartialEq)]
-pub enum PipelineState {
-    Connecting,
-    Streaming,
-    /// Stopped cleanly.
-    Stopped,
-    /// Failed with an error message.
-    Failed(String),
-}
-
 // ── SenderPipeline ────────────────────────────────────────────────────────────
 
 /// Handle to a running sender pipeline task.
 pub struct SenderPipeline {
     pub display_index: u8,
-    /// Send a `()` to request graceful shutdown.
-    pub stop_tx: mpsc::Sender<()>,
-    /// Frames sent counter (shared with pipeline task).
-    pub frames_sent: Arc<AtomicU64>,
+    session: SenderSession,
+    /// Latest thumbnail of the encoder input (shared with pipeline task).
+    pub preview: PreviewSlot,
+    /// Latest thumbnail of the receiver's screen, if asked for.
+    pub remote_preview: PreviewSlot,
+    /// Files to and from this display's receiver.
+    pub files: FileTransfers,
 }
 
 impl SenderPipeline {
     /// Spawn a capture → encode → send pipeline for one display.
     ///
-    /// Returns the pipeline handle and a status-update channel that the UI
-    /// can poll. The pipeline runs until the remote session ends or
-    /// `stop_tx.send(())` is called.
+    /// Status updates go to `status_tx` for the UI to poll. The pipeline
+    /// runs until the remote session ends or [`stop`](Self::stop) is called.
     pub fn spawn(
         config: PipelineConfig,
         status_tx: mpsc::Sender<PipelineStatus>,
     ) -> Self {
-        let (stop_tx, stop_rx) = mpsc::channel::<()>(1);
-        let frames_sent = Arc::new(AtomicU64::new(0));
-        let fs = Arc::clone(&frames_sent);
-        let display_index = config.display_index;
+        let preview = PreviewSlot::default();
+        let remote_preview = PreviewSlot::default();
+        let files = FileTransfers::configured();
+        let session_config = SessionConfig {
+            host:          config.host.clone(),
+            pairing_pin:   config.pairing_pin.clone(),
+            display_index: config.display_index,
+            ports:         config.ports.clone(),
+            width:         config.width,
+            height:        config.height,
+            fps:           config.fps,
+            bitrate_kbps:  config.bitrate_kbps,
+            preset:        config.preset,
+            latency_mode:  config.latency_mode,
+            adaptive_fps:  config.adaptive_fps,
+            remote_preview: config.remote_preview,
+            network_caps:  config.network_caps.clone(),
+            budget:        config.budget.clone(),
+            capture_stall: configured_capture_stall(),
+            idle_after:    configured_idle_after(),
+            match_receiver_resolution: config.match_receiver_resolution,
+            queues:        config.queues,
+        };
+        let platform = LinuxPlatform {
+            config,
+            preview: preview.clone(),
+            remote_preview: remote_preview.clone(),
+            files: files.clone(),
+            software: false,
+        };
+        let session = SenderSession::spawn(session_config, platform, status_tx);
 
-        tokio::spawn(run_pipeline(config, stop_rx, status_tx, fs));
+        Self { display_index: session.display_index, session, preview, remote_preview, files }
+    }
 
-        Self { display_index, stop_tx, frames_sent }
+    /// Event log (shared with pipeline task).
+    pub fn log(&self) -> &PipelineLog {
+        self.session.log()
+    }
+
+    /// Switch the running pipeline to `preset` (non-blocking).
+    pub fn apply_preset(&self, preset: QualityPreset) {
+        self.session.apply_preset(preset);
+    }
+
+    /// Blank the receiver's display, or show the stream again
+    /// (non-blocking). The session and the stream carry on.
+    pub fn set_remote_blank(&self, enabled: bool) {
+        self.session.set_remote_blank(enabled);
+    }
+
+    /// Switch the running pipeline to `kbps` (non-blocking).
+    pub fn set_bitrate(&self, kbps: u32) {
+        self.session.set_bitrate(kbps);
+    }
+
+    /// Switch the running pipeline to `fps` (non-blocking).
+    pub fn set_fps(&self, fps: u32) {
+        self.session.set_fps(fps);
+    }
+
+    /// Ask the running pipeline for a keyframe (non-blocking).
+    pub fn force_keyframe(&self) {
+        self.session.force_keyframe();
+    }
+
+    /// Pause or resume this display's stream (non-blocking); the other
+    /// displays and the session carry on.
+    pub fn set_paused(&self, paused: bool) {
+        self.session.set_paused(paused);
     }
 
     /// Request graceful stop (non-blocking).
     pub fn stop(&self) {
-        let _ = self.stop_tx.try_send(());
+        self.session.stop();
     }
 
     /// Total frames sent so far.
     pub fn frames_sent(&self) -> u64 {
-        self.frames_sent.load(Ordering::Relaxed)
+        self.session.frames_sent()
     }
 }
 
-// ── Pipeline task ─────────────────────────────────────────────────────────────
-
-async fn run_pipeline(
-    config: PipelineConfig,
-    mut stop_rx: mpsc::Receiver<()>,
-    status_tx: mpsc::Sender<PipelineStatus>,
-    frames_sent: Arc<AtomicU64>,
-) {
-    let idx = config.display_index;
-
-    macro_rules! send_status {
-        ($state:expr, $fps:expr) => {
-            let _ = status_tx.try_send(PipelineStatus {
-                display_index: idx,
-                state: $state,
-                fps: $fps,
-                frames_sent: frames_sent.load(Ordering::Relaxed),
-            });
-        };
-    }
+// ── Linux platform ────────────────────────────────────────────────────────────
+
+/// PipeWire capture, GStreamer encoding and uinput injection under a
+/// [`SenderSession`].
+struct LinuxPlatform {
+    config:         PipelineConfig,
+    preview:        PreviewSlot,
+    remote_preview: PreviewSlot,
+    files:          FileTransfers,
+    /// Encode with OpenH264, decided in `prepare`.
+    software:       bool,
+}
+
+impl Platform for LinuxPlatform {
+    type Capture = LinuxCapture;
+    type Encoder = LinuxEncoder;
+
+    const NAME: &'static str = "linux-sender";
 
-    send_status!(PipelineState::Connecting, 0.0);
+    fn route_kind(host: &str) -> NetworkKind {
+        network::route_kind(host)
+    }
 
-    // ── 1. Connect signaling ──────────────────────────────────────────────
-    let mut sig = match SignalingClient::connect(&config.host, idx).await {
-        Ok(s) => s,
-        Err(e) => {
-            warn!("Display[{}] signaling connect failed: {:#}", idx, e);
-            send_status!(PipelineState::Failed(format!("Connect: {e:#}")), 0.0);
-            return;
+    fn prepare(&mut self, config: &mut StreamConfig, log: &PipelineLog) {
+        self.software = self.config.mode == SenderPipelineMode::Split
+            && (self.config.software_encoder || probe_best_encoder().is_none());
+        if self.software {
+            if !cfg!(feature = "openh264") {
+                log.warn("Software encoding asked for, but built without the openh264 feature");
+            } else if !self.config.software_encoder {
+                log.warn("No GStreamer H.264 encoder installed — encoding with OpenH264");
+            }
         }
-    };
+        // OpenH264 has no 4:4:4 mode.
+        config.lossless = self.config.lossless && !self.software;
+        config.color = self.config.color;
+    }
 
-    let session_id = format!("linux-sender-d{}-{}", idx, ts_ms());
-    let stream_config = StreamConfig {
-        width: config.width,
-        height: config.height,
-        fps: config.fps,
-        ..Default::default()
-    };
+    async fn open(
+        &mut self,
+        stream: &StreamConfig,
+        log: &PipelineLog,
+    ) -> anyhow::Result<(Option<LinuxCapture>, LinuxEncoder)> {
+        let config = &self.config;
+        let (width, height) = (stream.resolution.width, stream.resolution.height);
+        let (fps, kbps) = (config.fps, (stream.max_bitrate_bps / 1000) as u32);
+        let cap_cfg = CaptureConfig {
+            display_index: config.display_index,
+            width,
+            height,
+            fps,
+            prefer_nv12: config.prefer_nv12,
+            monitor: config.monitor.clone(),
+            backend: config.capture_backend,
+            buffers: config.queues.captured,
+        };
+        let profile = config.encode_profile(stream);
+        let (capturer, encoder) = match config.mode {
+            SenderPipelineMode::Split => {
+                let capturer = ScreenCapturer::open(cap_cfg).await.context("Capture")?;
+                log.info(format!("Capture backend {}", capturer.backend()));
+                // Start with the preferred format; the encoder follows whatever capture negotiates.
+                let input = if config.prefer_nv12 { PixelFormat::Nv12 } else { PixelFormat::Bgrx };
+                let engine = if self.software {
+                    open_software(fps, kbps, profile)
+                } else {
+                    GstEncoder::new(width, height, fps, kbps, input, profile).map(Engine::Gst)
+                };
+                (Some(LinuxCapture(capturer)), engine)
+            }
+            SenderPipelineMode::Fused => {
+                let stream = open_pipewire_stream(&cap_cfg).await.context("Capture")?;
+                (None, GstEncoder::new_fused(&stream, width, height, fps, kbps, profile).map(Engine::Gst))
+            }
+            SenderPipelineMode::TestPattern => {
+                let pattern = config.test_pattern;
+                (None, GstEncoder::new_test_pattern(pattern, width, height, fps, kbps, profile).map(Engine::Gst))
+            }
+        };
+        let engine = encoder.context("Encoder")?;
+        if let Engine::Gst(gst) = &engine {
+            gst.set_preview(self.preview.clone());
+        }
+        log.info(format!("Capture mode {:?}, encoder {}", config.mode, engine.element_name()));
+
+        let encoder = LinuxEncoder {
+            display_index: config.display_index,
+            engine,
+            queue: FrameQueue::new(config.queue_depth, config.drop_policy),
+            overload: OverloadMonitor::new(fps),
+            governor: FrameGovernor::new(),
+            adaptive_fps: config.adaptive_fps,
+            skip_unchanged: false,
+            captured: 0,
+        };
+        Ok((capturer, encoder))
+    }
 
-    let ack = match sig.send_hello(&session_id, &hostname(), stream_config, &config.pairing_pin).await {
-        Ok(a) => a,
-        Err(e) => {
-            warn!("Display[{}] send_hello failed: {:#}", idx, e);
-            send_status!(PipelineState::Failed(format!("Handshake: {e:#}")), 0.0);
-            return;
+    /// The portal prompts in fused mode, and in split mode unless capture
+    /// runs on a backend without one (screencopy on wlroots).
+    async fn capture_prompts(&self) -> bool {
+        match (self.config.mode, self.config.capture_backend) {
+            (SenderPipelineMode::Fused, _) => true,
+            (SenderPipelineMode::Split, Some(backend)) => backend.caps().interactive,
+            (SenderPipelineMode::Split, None) => Backend::Screencopy.probe().await.is_err(),
+            (SenderPipelineMode::TestPattern, _) => false,
         }
-    };
+    }
 
-    if !ack.accepted {
-        let reason = ack.reason.unwrap_or_else(|| "unknown".to_owned());
-        warn!("Display[{}] rejected: {}", idx, reason);
-        send_status!(PipelineState::Failed(format!("Rejected: {reason}")), 0.0);
-        return;
+    // Decode receiver thumbnails off the send loop; ends with the recv loop.
+    fn remote_previews(&mut self, mut previews: watch::Receiver<Option<Bytes>>) {
+        let idx = self.config.display_index;
+        let remote_preview = self.remote_preview.clone();
+        tokio::spawn(async move {
+            while previews.changed().await.is_ok() {
+                let Some(jpeg) = previews.borrow_and_update().clone() else { continue };
+                match tokio::task::spawn_blocking(move || preview::decode_jpeg(&jpeg)).await {
+                    Ok(Ok((width, height, rgba))) => remote_preview.publish(width, height, rgba),
+                    Ok(Err(e)) => warn!("Display[{}] {:#}", idx, e),
+                    Err(_) => break,
+                }
+            }
+        });
     }
-    info!("Display[{}] session accepted (id={})", idx, session_id);
 
-    let (mut sig_writer, mut input_rx) = sig.start_recv_loop();
+    fn file_transfers(&mut self) -> Option<FileTransfers> {
+        Some(self.files.clone())
+    }
 
-    // ── 2. Connect UDP video sender ───────────────────────────────────────
-    let video = match VideoSender::connect(&config.host, idx).await {
-        Ok(v) => v,
-        Err(e) => {
-            send_status!(PipelineState::Failed(format!("UDP: {e:#}")), 0.0);
-            return;
-        }
-    };
+    async fn inject(&mut self, event: InputEvent) {
+        // Forwarded to uinput injector if available — see input_inject.rs
+        #[cfg(target_os = "linux")]
+        crate::input_inject::inject_global(event).await;
+        #[cfg(not(target_os = "linux"))]
+        tracing::debug!("Display[{}] input event (stub): {:?}", self.config.display_index, event);
+    }
 
-    // ── 3. Open screen capture ────────────────────────────────────────────
-    let cap_cfg = CaptureConfig {
-        display_index: idx,
-        width:  config.width,
-        height: config.height,
-        fps:    config.fps,
-    };
-    let mut capturer = match ScreenCapturer::open(cap_cfg).await {
-        Ok(c) => c,
-        Err(e) => {
-            send_status!(PipelineState::Failed(format!("Capture: {e:#}")), 0.0);
-            return;
-        }
-    };
+    async fn keep_awake(&mut self) -> Option<Box<dyn Send>> {
+        let inhibitor = tokio::task::spawn_blocking(|| {
+            IdleInhibitor::acquire("DualLink", "Streaming this screen with DualLink")
+        })
+        .await
+        .ok()
+        .flatten()?;
+        Some(Box::new(inhibitor))
+    }
+}
 
-    // ── 4. Create GStreamer encoder ───────────────────────────────────────
-    let mut encoder = match GstEncoder::new(config.width, config.height, config.fps, config.bitrate_kbps) {
-        Ok(e) => e,
-        Err(e) => {
-            send_status!(PipelineState::Failed(format!("Encoder: {e:#}")), 0.0);
-            return;
-        }
-    };
+/// Split-mode capture through `ScreenCapturer`.
+struct LinuxCapture(ScreenCapturer);
 
-    send_status!(PipelineState::Streaming, 0.0);
-    info!("Display[{}] streaming to {} ...", idx, config.host);
+impl Capture for LinuxCapture {
+    type Frame = CapturedFrame;
 
-    // ── 5. Main loop ──────────────────────────────────────────────────────
-    let mut keepalive_ticker = tokio::time::interval(Duration::from_secs(1));
-    let mut fps_counter = FpsCounter::new();
+    async fn next_frame(&mut self) -> Option<CapturedFrame> {
+        self.0.next_frame().await
+    }
 
-    loop {
-        tokio::select! {
-            // Stop requested by UI
-            _ = stop_rx.recv() => {
-                info!("Display[{}] stop requested", idx);
-                break;
-            }
+    fn set_max_fps(&mut self, fps: u32) -> bool {
+        self.0.set_max_fps(fps);
+        true
+    }
+}
 
-            // Capture raw frame
-            maybe_raw = capturer.next_frame() => {
-                let Some(raw) = maybe_raw else {
-                    info!("Display[{}] capture EOS", idx);
-                    break;
-                };
-                if let Err(e) = encoder.push_frame(raw) {
-                    warn!("Display[{}] push_frame: {:#}", idx, e);
-                }
-            }
+#[cfg(feature = "openh264")]
+fn open_software(fps: u32, kbps: u32, profile: EncodeProfile) -> anyhow::Result<Engine> {
+    OpenH264Encoder::new(fps, kbps, profile.gop, profile.color).map(Engine::OpenH264)
+}
 
-            // Pull encoded frame and send
-            maybe_enc = encoder.next_encoded() => {
-                let Some(enc) = maybe_enc else {
-                    info!("Display[{}] encoder EOS", idx);
-                    break;
-                };
-                match video.send_frame(&enc).await {
-                    Ok(_) => {
-                        frames_sent.fetch_add(1, Ordering::Relaxed);
-                        fps_counter.tick();
-                    }
-                    Err(e) => {
-                        warn!("Display[{}] send_frame: {:#}", idx, e);
-                    }
-                }
-            }
+#[cfg(not(feature = "openh264"))]
+fn open_software(_fps: u32, _kbps: u32, _profile: EncodeProfile) -> anyhow::Result<Engine> {
+    anyhow::bail!("No GStreamer H.264 encoder, and built without the openh264 feature")
+}
 
-            // 1-Hz keepalive + FPS status update
-            _ = keepalive_ticker.tick() => {
-                let fps = fps_counter.fps();
-                send_status!(PipelineState::Streaming, fps);
-                if let Err(e) = sig_writer.send_keepalive(ts_ms()).await {
-                    warn!("Display[{}] keepalive: {:#}", idx, e);
-                    break;
-                }
-            }
+/// The encoder under [`LinuxEncoder`].
+enum Engine {
+    Gst(GstEncoder),
+    #[cfg(feature = "openh264")]
+    OpenH264(OpenH264Encoder),
+}
 
-            // Input events from receiver
-            maybe_ev = input_rx.recv() => {
-                match maybe_ev {
-                    Some(ev) => {
-                        // Forwarded to uinput injector if available — see input_inject.rs
-                        #[cfg(target_os = "linux")]
-                        crate::input_inject::inject_global(ev).await;
-                        #[cfg(not(target_os = "linux"))]
-                        tracing::debug!("Display[{}] input event (stub): {:?}", idx, ev);
-                    }
-                    None => {
-                        info!("Display[{}] signaling closed", idx);
-                        break;
-                    }
-                }
+/// Call the same-named method on whichever encoder `$engine` holds.
+macro_rules! dispatch {
+    ($engine:expr, $enc:ident => $call:expr) => {
+        match $engine {
+            Engine::Gst($enc) => $call,
+            #[cfg(feature = "openh264")]
+            Engine::OpenH264($enc) => $call,
+        }
+    };
+}
+
+impl Engine {
+    fn push_frame(&mut self, frame: CapturedFrame) -> anyhow::Result<()> {
+        match self {
+            Self::Gst(gst) => gst.push_frame(frame),
+            #[cfg(feature = "openh264")]
+            Self::OpenH264(enc) => {
+                let format = match frame.format {
+                    PixelFormat::Bgrx => RawFormat::Bgrx,
+                    PixelFormat::Nv12 => RawFormat::Nv12,
+                };
+                let CapturedFrame { data, pts_ms, width, height, .. } = frame;
+                enc.push_frame(RawFrame { data, format, width, height, pts_ms })
             }
         }
     }
 
-    // ── Cleanup ───────────────────────────────────────────────────────────
-    encoder.send_eos();
-    let _ = sig_writer.send_stop(&session_id).await;
-    send_status!(PipelineState::Stopped, 0.0);
-    info!("Display[{}] pipeline stopped", idx);
-}
+    async fn next_encoded(&mut self) -> Option<EncodedFrame> {
+        dispatch!(self, enc => enc.next_encoded().await)
+    }
 
-// ── Helpers ───────────────────────────────────────────────────────────────────
+    fn in_flight(&self) -> u64 {
+        dispatch!(self, enc => enc.in_flight())
+    }
 
-fn ts_ms() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis() as u64
+    fn element_name(&self) -> &str {
+        dispatch!(self, enc => enc.element_name())
+    }
 }
 
-fn hostname() -> String {
-    hostname::get()
-        .ok()
-        .and_then(|h| h.into_string().ok())
-        .unwrap_or_else(|| "linux-sender".to_owned())
+/// The encoder behind the split-mode frame governor and backpressure queue.
+struct LinuxEncoder {
+    display_index:  u8,
+    engine:         Engine,
+    queue:          FrameQueue,
+    overload:       OverloadMonitor,
+    governor:       FrameGovernor,
+    adaptive_fps:   bool,
+    /// Battery saver skips unchanged frames even without `adaptive_fps`.
+    skip_unchanged: bool,
+    /// Frames that passed the governor, for the overload monitor.
+    captured:       u64,
 }
 
-/// Rolling ~1 second FPS counter.
-struct FpsCounter {
-    count:      u32,
-    window_start: std::time::Instant,
-    last_fps:   f32,
+impl LinuxEncoder {
+    /// Push queued raw frames while the encoder has room.
+    fn feed(&mut self) {
+        while self.engine.in_flight() < MAX_IN_FLIGHT {
+            let Some(raw) = self.queue.pop() else { break };
+            if let Err(e) = self.engine.push_frame(raw) {
+                warn!("Display[{}] push_frame: {:#}", self.display_index, e);
+                break;
+            }
+        }
+    }
 }
 
-impl FpsCounter {
-    fn new() -> Self {
-        Self { count: 0, window_start: std::time::Instant::now(), last_fps: 0.0 }
+impl Encoder for LinuxEncoder {
+    type Frame = CapturedFrame;
+
+    fn push_frame(&mut self, frame: CapturedFrame) -> anyhow::Result<()> {
+        if (self.adaptive_fps || self.skip_unchanged) && !self.governor.should_encode(&frame) {
+            return Ok(());
+        }
+        self.captured += 1;
+        self.queue.push(frame);
+        self.feed();
+        Ok(())
+    }
+
+    async fn next_encoded(&mut self) -> Option<EncodedFrame> {
+        let frame = self.engine.next_encoded().await?;
+        // A slot freed up — hand over the freshest queued frame.
+        self.feed();
+        Some(frame)
+    }
+
+    fn set_bitrate(&mut self, kbps: u32) {
+        dispatch!(&mut self.engine, enc => enc.set_bitrate(kbps));
+    }
+
+    fn set_fps(&mut self, fps: u32) {
+        dispatch!(&mut self.engine, enc => enc.set_fps(fps));
+        self.overload.set_target(fps);
+    }
+
+    fn set_gop(&mut self, frames: u32) {
+        dispatch!(&mut self.engine, enc => enc.set_gop(frames));
+    }
+
+    fn force_keyframe(&mut self) {
+        dispatch!(&mut self.engine, enc => enc.force_keyframe());
+    }
+
+    fn send_eos(&mut self) {
+        dispatch!(&mut self.engine, enc => enc.send_eos());
+    }
+
+    fn element_name(&self) -> &str {
+        self.engine.element_name()
+    }
+
+    fn is_hardware_accelerated(&self) -> bool {
+        dispatch!(&self.engine, enc => enc.is_hardware_accelerated())
+    }
+
+    fn is_lossless(&self) -> bool {
+        dispatch!(&self.engine, enc => enc.is_lossless())
+    }
+
+    fn flush(&mut self) {
+        while self.queue.pop().is_some() {}
+    }
+
+    fn set_skip_unchanged(&mut self, enabled: bool) {
+        self.skip_unchanged = enabled;
     }
 
-    fn tick(&mut self) {
-        self.count += 1;
+    fn overload_cap(&mut self) -> Option<u32> {
+        self.overload.tick(self.captured, self.queue.dropped())
     }
 
-    /// Returns the FPS over the last ~1 second window; resets the counter.
-    fn fps(&mut self) -> f32 {
-        let elapsed = self.window_start.elapsed().as_secs_f32();
-        if elapsed >= 0.5 {
-            self.last_fps = self.count as f32 / elapsed;
-            self.count = 0;
-            self.window_start = std::time::Instant::now();
+    fn feed_stats(&self) -> FeedStats {
+        FeedStats {
+            dropped:       self.queue.dropped(),
+            skipped:       self.governor.skipped(),
+            throttled_fps: self.overload.throttled_fps(),
         }
-        self.last_fps
     }
 }
@@ -0,0 +1,267 @@
+//! Text of the sender UI in every [`Language`](duallink_core::Language)
+//! (see [`duallink_core::locale`]).
+//!
+//! Pipeline log entries stay in English: they are the same lines the
+//! terminal log shows.
+
+use std::fmt::Display;
+
+use duallink_core::locale::{self, Catalog};
+
+/// Key in the current language.
+pub fn t(key: &'static str) -> &'static str {
+    locale::tr(STRINGS, key)
+}
+
+/// Key in the current language with `{name}` placeholders filled in.
+pub fn tf(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    locale::tr_fill(STRINGS, key, args)
+}
+
+#[rustfmt::skip]
+const STRINGS: Catalog = &[
+    ("app.language", ["Language", "Idioma", "Idioma"]),
+    ("appearance.theme", ["Theme", "Tema", "Tema"]),
+    ("appearance.dark", ["Dark", "Escuro", "Oscuro"]),
+    ("appearance.light", ["Light", "Claro", "Claro"]),
+    ("appearance.system", ["System", "Sistema", "Sistema"]),
+    ("appearance.scale", [
+        "UI scale, for HiDPI screens",
+        "Escala da interface, para telas HiDPI",
+        "Escala de la interfaz, para pantallas HiDPI",
+    ]),
+
+    // ── Settings ──────────────────────────────────────────────────────────
+    ("settings.receiver_ip", ["Receiver IP:", "IP do receptor:", "IP del receptor:"]),
+    ("settings.pin", ["PIN:", "PIN:", "PIN:"]),
+    ("settings.discovered", ["Discovered:", "Encontrados:", "Encontrados:"]),
+    ("settings.scan_placeholder", ["— scan for receivers —", "— procure receptores —", "— busca receptores —"]),
+    ("settings.scan", ["⟳ Scan", "⟳ Procurar", "⟳ Buscar"]),
+    ("settings.wake_mac", ["Wake MAC:", "MAC para despertar:", "MAC para despertar:"]),
+    ("settings.wake", ["⏻ Wake", "⏻ Despertar", "⏻ Despertar"]),
+    ("settings.displays", ["Displays:", "Telas:", "Pantallas:"]),
+    ("settings.resolution", ["Resolution:", "Resolução:", "Resolución:"]),
+    ("settings.monitor", ["Monitor {n}:", "Monitor {n}:", "Monitor {n}:"]),
+    ("settings.auto", ["Auto", "Automático", "Automático"]),
+    ("settings.portal_stream", ["Portal stream {n}", "Fluxo do portal {n}", "Flujo del portal {n}"]),
+    ("settings.primary", [" (primary)", " (principal)", " (principal)"]),
+    ("settings.redetect", ["Re-detect monitors", "Detectar monitores de novo", "Volver a detectar monitores"]),
+    ("settings.preset", ["Preset:", "Predefinição:", "Preajuste:"]),
+    ("settings.custom", ["Custom", "Personalizado", "Personalizado"]),
+    ("settings.fps", ["FPS:", "FPS:", "FPS:"]),
+    ("settings.bitrate", ["Bitrate:", "Bitrate:", "Bitrate:"]),
+    ("settings.pipeline", ["Pipeline:", "Pipeline:", "Pipeline:"]),
+    ("settings.split", ["Split", "Separado", "Separado"]),
+    ("settings.split_hint", [
+        "Capture and encode as separate pipelines",
+        "Captura e codificação em pipelines separados",
+        "Captura y codificación en pipelines separados",
+    ]),
+    ("settings.fused", ["Fused", "Unificado", "Unificado"]),
+    ("settings.fused_hint", [
+        "pipewiresrc feeds the encoder directly (fewer copies)",
+        "pipewiresrc alimenta o codificador diretamente (menos cópias)",
+        "pipewiresrc alimenta el codificador directamente (menos copias)",
+    ]),
+    ("settings.test_pattern", ["Test pattern", "Padrão de teste", "Patrón de prueba"]),
+    ("settings.test_pattern_hint", [
+        "Stream colour bars with a moving ball instead of the screen, to check the link, decoder and colours",
+        "Transmite barras de cor com uma bola em movimento no lugar da tela, para conferir rede, decodificador e cores",
+        "Transmite barras de color con una bola en movimiento en lugar de la pantalla, para comprobar red, decodificador y color",
+    ]),
+    ("settings.lossless", ["Lossless:", "Sem perdas:", "Sin pérdidas:"]),
+    ("settings.lossless_check", ["H.264 4:4:4 for sharp text", "H.264 4:4:4 para texto nítido", "H.264 4:4:4 para texto nítido"]),
+    ("settings.lossless_hint", [
+        "Software x264 at QP 18, no chroma subsampling — needs a receiver with 4:4:4 decode; high bandwidth",
+        "x264 por software com QP 18, sem subamostragem de croma — requer receptor com decodificação 4:4:4; banda alta",
+        "x264 por software con QP 18, sin submuestreo de croma — requiere un receptor con decodificación 4:4:4; ancho de banda alto",
+    ]),
+    ("settings.remote_preview", ["Receiver preview:", "Prévia do receptor:", "Vista previa del receptor:"]),
+    ("settings.remote_preview_check", [
+        "Show what the receiver displays",
+        "Mostrar o que o receptor exibe",
+        "Mostrar lo que muestra el receptor",
+    ]),
+    ("settings.remote_preview_hint", [
+        "The receiver sends a small JPEG of its screen every few seconds",
+        "O receptor envia um pequeno JPEG da tela a cada poucos segundos",
+        "El receptor envía un pequeño JPEG de su pantalla cada pocos segundos",
+    ]),
+    ("settings.match_receiver", ["Receiver size:", "Tamanho do receptor:", "Tamaño del receptor:"]),
+    ("settings.match_receiver_check", [
+        "Stream at the receiver's resolution",
+        "Transmitir na resolução do receptor",
+        "Transmitir a la resolución del receptor",
+    ]),
+    ("settings.match_receiver_hint", [
+        "Capture and encode at the resolution of the receiver's panel instead of having it scaled, and follow it when the panel changes",
+        "Capturar e codificar na resolução da tela do receptor em vez de escalá-la, acompanhando-a quando a tela muda",
+        "Capturar y codificar a la resolución de la pantalla del receptor en lugar de escalarla, siguiéndola cuando la pantalla cambia",
+    ]),
+    ("settings.color", ["Color:", "Cor:", "Color:"]),
+    ("settings.limited", ["Limited", "Limitado", "Limitado"]),
+    ("settings.limited_hint", [
+        "16–235 — safest default for hardware decoders",
+        "16–235 — padrão mais seguro para decodificadores de hardware",
+        "16–235 — opción más segura para decodificadores por hardware",
+    ]),
+    ("settings.full", ["Full", "Completo", "Completo"]),
+    ("settings.full_hint", [
+        "0–255 — use if blacks look grey on the receiver",
+        "0–255 — use se o preto parecer cinza no receptor",
+        "0–255 — úsalo si el negro se ve gris en el receptor",
+    ]),
+
+    // ── Wake-on-LAN ───────────────────────────────────────────────────────
+    ("wake.waking", ["⏻ Waking receiver…", "⏻ Despertando o receptor…", "⏻ Despertando el receptor…"]),
+    ("wake.up", ["● Receiver up after {seconds}s", "● Receptor ativo após {seconds}s", "● Receptor activo tras {seconds}s"]),
+    ("wake.failed", ["✗ Wake failed: {error}", "✗ Falha ao despertar: {error}", "✗ No se pudo despertar: {error}"]),
+
+    // ── Capture pre-flight ────────────────────────────────────────────────
+    ("portal.checking", ["Checking screen capture…", "Verificando a captura de tela…", "Comprobando la captura de pantalla…"]),
+    ("portal.no_plugin", [
+        "⚠ Screen capture needs the PipeWire GStreamer plugin — install gstreamer1.0-pipewire (Debian/Ubuntu) or gst-plugin-pipewire (Fedora/Arch)",
+        "⚠ A captura de tela precisa do plugin PipeWire do GStreamer — instale gstreamer1.0-pipewire (Debian/Ubuntu) ou gst-plugin-pipewire (Fedora/Arch)",
+        "⚠ La captura de pantalla necesita el plugin PipeWire de GStreamer — instala gstreamer1.0-pipewire (Debian/Ubuntu) o gst-plugin-pipewire (Fedora/Arch)",
+    ]),
+    ("portal.install", [
+        "⚠ Screen capture needs xdg-desktop-portal and {package} — install them, then log out and back in (portal backends found: {backends})",
+        "⚠ A captura de tela precisa de xdg-desktop-portal e {package} — instale-os e entre na sessão de novo (backends de portal encontrados: {backends})",
+        "⚠ La captura de pantalla necesita xdg-desktop-portal y {package} — instálalos y vuelve a iniciar sesión (backends de portal encontrados: {backends})",
+    ]),
+    ("portal.install_any", [
+        "⚠ Screen capture needs xdg-desktop-portal and your desktop's backend (xdg-desktop-portal-wlr on wlroots compositors) — install them, then log out and back in (portal backends found: {backends})",
+        "⚠ A captura de tela precisa de xdg-desktop-portal e do backend do seu desktop (xdg-desktop-portal-wlr em compositores wlroots) — instale-os e entre na sessão de novo (backends de portal encontrados: {backends})",
+        "⚠ La captura de pantalla necesita xdg-desktop-portal y el backend de tu escritorio (xdg-desktop-portal-wlr en compositores wlroots) — instálalos y vuelve a iniciar sesión (backends de portal encontrados: {backends})",
+    ]),
+    ("portal.none", ["none", "nenhum", "ninguno"]),
+    ("portal.recheck", ["⟳ Check again", "⟳ Verificar de novo", "⟳ Comprobar de nuevo"]),
+    ("portal.test_pattern_hint", [
+        "The test-pattern pipeline streams without screen capture",
+        "O pipeline de padrão de teste transmite sem captura de tela",
+        "El pipeline de patrón de prueba transmite sin captura de pantalla",
+    ]),
+
+    // ── Actions ───────────────────────────────────────────────────────────
+    ("action.start", ["▶  Start Streaming", "▶  Iniciar transmissão", "▶  Iniciar transmisión"]),
+    ("action.start_all", ["▶  Start All Displays", "▶  Iniciar todas as telas", "▶  Iniciar todas las pantallas"]),
+    ("action.stop", ["■  Stop", "■  Parar", "■  Detener"]),
+    ("action.blank", ["Blank receiver", "Ocultar receptor", "Ocultar receptor"]),
+    ("action.blank_hint", [
+        "Black out the receiver's screens without ending the session",
+        "Escurece as telas do receptor sem encerrar a sessão",
+        "Oscurece las pantallas del receptor sin terminar la sesión",
+    ]),
+    ("action.fps_hint", [
+        "Capped at the rate the stream started with",
+        "Limitado à taxa com que a transmissão começou",
+        "Limitado a la tasa con la que empezó la transmisión",
+    ]),
+    ("action.keyframe", ["Keyframe", "Quadro-chave", "Fotograma clave"]),
+    ("action.keyframe_hint", ["Send a keyframe now", "Enviar um quadro-chave agora", "Enviar un fotograma clave ahora"]),
+
+    // ── Display status ────────────────────────────────────────────────────
+    ("status.title", ["Display Status", "Estado das telas", "Estado de las pantallas"]),
+    ("status.not_connected", [
+        "Not connected — configure and click Start Streaming.",
+        "Não conectado — configure e clique em Iniciar transmissão.",
+        "No conectado — configura y pulsa Iniciar transmisión.",
+    ]),
+    ("status.display", ["Display {n}", "Tela {n}", "Pantalla {n}"]),
+    ("status.idle", ["⊘ Idle", "⊘ Inativo", "⊘ Inactivo"]),
+    ("status.connecting", ["⟳ Connecting…", "⟳ Conectando…", "⟳ Conectando…"]),
+    ("status.awaiting_capture", [
+        "🖵 Waiting for you to pick a screen…",
+        "🖵 Aguardando você escolher uma tela…",
+        "🖵 Esperando a que elijas una pantalla…",
+    ]),
+    ("status.awaiting_capture_hint", [
+        "Pick the screen to share in the screen-cast dialog",
+        "Escolha a tela a compartilhar na janela de captura de tela",
+        "Elige la pantalla a compartir en el diálogo de captura de pantalla",
+    ]),
+    ("status.remote_preview", ["What the receiver shows", "O que o receptor exibe", "Lo que muestra el receptor"]),
+    ("status.paused", ["⏸ Paused", "⏸ Pausado", "⏸ En pausa"]),
+    ("status.paused_hint", [
+        "Capture runs but nothing is sent to the receiver",
+        "A captura continua, mas nada é enviado ao receptor",
+        "La captura sigue, pero no se envía nada al receptor",
+    ]),
+    ("status.streaming", ["● Streaming", "● Transmitindo", "● Transmitiendo"]),
+    ("status.resume", ["▶ Resume", "▶ Retomar", "▶ Reanudar"]),
+    ("status.resume_hint", ["Stream this display again", "Voltar a transmitir esta tela", "Volver a transmitir esta pantalla"]),
+    ("status.pause", ["⏸ Pause", "⏸ Pausar", "⏸ Pausar"]),
+    ("status.pause_hint", [
+        "Stop sending this display; the others and the session carry on",
+        "Para de enviar esta tela; as outras e a sessão continuam",
+        "Deja de enviar esta pantalla; las demás y la sesión siguen",
+    ]),
+    ("status.frames", ["{count} frames", "{count} quadros", "{count} fotogramas"]),
+    ("status.paused_by_receiver", ["⏸ paused by receiver", "⏸ pausado pelo receptor", "⏸ en pausa por el receptor"]),
+    ("status.paused_by_receiver_hint", [
+        "The receiver blanked this display; nothing is captured or sent",
+        "O receptor ocultou esta tela; nada é capturado nem enviado",
+        "El receptor ocultó esta pantalla; no se captura ni se envía nada",
+    ]),
+    ("status.saver", ["🔋 saver", "🔋 economia", "🔋 ahorro"]),
+    ("status.idle", ["💤 idle", "💤 ocioso", "💤 inactivo"]),
+    ("status.idle_hint", [
+        "No input or screen changes lately — streaming at 5 fps until the next one",
+        "Sem entrada nem mudanças na tela — transmitindo a 5 fps até a próxima",
+        "Sin entrada ni cambios en pantalla — transmitiendo a 5 fps hasta el próximo",
+    ]),
+    ("status.no_battery", ["no battery", "sem bateria", "sin batería"]),
+    ("status.power_hint", [
+        "This machine: {this}\nReceiver: {receiver}\nBattery saver streams at 30 fps and a lower bitrate while either end runs low",
+        "Esta máquina: {this}\nReceptor: {receiver}\nA economia de bateria transmite a 30 fps e com bitrate menor enquanto um dos lados estiver com pouca carga",
+        "Este equipo: {this}\nReceptor: {receiver}\nEl ahorro de batería transmite a 30 fps y con menor bitrate mientras cualquiera de los dos tenga poca carga",
+    ]),
+    ("status.static", ["{count} static", "{count} estáticos", "{count} estáticos"]),
+    ("status.static_hint", [
+        "Unchanged frames skipped by adaptive fps",
+        "Quadros inalterados pulados pelo fps adaptativo",
+        "Fotogramas sin cambios omitidos por los fps adaptativos",
+    ]),
+    ("status.dropped", ["{count} dropped", "{count} descartados", "{count} descartados"]),
+    ("status.capped", ["⚠ capped {fps} fps", "⚠ limitado a {fps} fps", "⚠ limitado a {fps} fps"]),
+    ("status.link_hint", [
+        "Keepalive round trip and frames the receiver could not reassemble since the last second",
+        "Ida e volta do keepalive e quadros que o receptor não conseguiu remontar no último segundo",
+        "Ida y vuelta del keepalive y fotogramas que el receptor no pudo reensamblar en el último segundo",
+    ]),
+    ("status.lossless_hint", ["Lossless mode active", "Modo sem perdas ativo", "Modo sin pérdidas activo"]),
+    ("status.receiver_panel", [
+        "Receiver panel {name} — {width}×{height} mm, scale {scale}×",
+        "Tela do receptor {name} — {width}×{height} mm, escala {scale}×",
+        "Pantalla del receptor {name} — {width}×{height} mm, escala {scale}×",
+    ]),
+    ("status.stopped", ["○ Stopped", "○ Parado", "○ Detenido"]),
+
+    // ── File transfer ─────────────────────────────────────────────────────
+    ("files.title", ["Files", "Arquivos", "Archivos"]),
+    ("files.drop_hint", [
+        "Drop files on this window to send them to the receiver",
+        "Solte arquivos nesta janela para enviá-los ao receptor",
+        "Suelta archivos en esta ventana para enviarlos al receptor",
+    ]),
+    ("files.not_streaming", [
+        "Start streaming to send files",
+        "Inicie a transmissão para enviar arquivos",
+        "Inicia la transmisión para enviar archivos",
+    ]),
+    ("files.unreadable", ["✗ {name}: {error}", "✗ {name}: {error}", "✗ {name}: {error}"]),
+    ("files.offer", [
+        "The receiver sends {name} ({size})",
+        "O receptor envia {name} ({size})",
+        "El receptor envía {name} ({size})",
+    ]),
+    ("files.accept", ["Accept", "Aceitar", "Aceptar"]),
+    ("files.decline", ["Decline", "Recusar", "Rechazar"]),
+    ("files.received", ["✓ {name} received", "✓ {name} recebido", "✓ {name} recibido"]),
+    ("files.sent", ["✓ {name} sent", "✓ {name} enviado", "✓ {name} enviado"]),
+    ("files.failed", ["✗ {name}: {reason}", "✗ {name}: {reason}", "✗ {name}: {reason}"]),
+
+    // ── Pipeline log ──────────────────────────────────────────────────────
+    ("log.title", ["Log ({count})", "Registro ({count})", "Registro ({count})"]),
+    ("log.title_problems", ["Log ({count}, {problems} ⚠)", "Registro ({count}, {problems} ⚠)", "Registro ({count}, {problems} ⚠)"]),
+];
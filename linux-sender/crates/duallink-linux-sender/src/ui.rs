@@ -4,6 +4,38 @@
 //! `_duallink._tcp.local.` and auto-fills the host field when a receiver is
 //! selected from the dropdown.
 //!
+//! Receivers that advertise a `mac` TXT key are remembered in
+//! [`WakeAddresses`] — across launches, so a receiver that is already asleep
+//! can be woken — and the "Wake" button sends a Wake-on-LAN magic packet and
+//! waits for the receiver to come up.
+//!
+//! Each stream can be pinned to a local monitor; the choice is saved with
+//! [`MonitorAssignments`] and restored on the next launch.
+//!
+//! Every display row has a collapsible log with that pipeline's recent
+//! events ([`PipelineLog`]), kept after the pipeline fails or stops.
+//!
+//! Streaming rows start with a 1 fps thumbnail of what that pipeline sends
+//! (see [`duallink_sender_lib::preview`]); hover it for full size. With
+//! "Receiver preview" checked, a thumbnail of what the receiver really
+//! shows follows it.
+//!
+//! Files dropped on the window go to the receiver of the first display;
+//! files the receiver offers wait under "Files" to be accepted or declined
+//! (see [`duallink_core::file_transfer`]).
+//!
+//! Labels and hints come from [`crate::strings`], in the language picked
+//! next to the title. The theme and UI scale pickers beside it, and the
+//! window geometry, are kept in [`AppearanceSettings`] shared with the
+//! other DualLink UIs.
+//!
+//! On launch a pre-flight check looks for a way to capture: screencopy on
+//! wlroots compositors, else the screen-cast portal
+//! ([`check_portal`]). When the picked pipeline needs the portal and it or
+//! its backend is missing, Start stays disabled and the row under it says
+//! which package to install. While the portal dialog is up, the display's
+//! row reads "Waiting for you to pick a screen…".
+//!
 //! # Layout
 //!
 //! ```
@@ -12,22 +44,42 @@
 //! ├─────────────────────────────────────────────────────┤
 //! │  Host  [192.168.1.100________]  PIN  [123456__]     │
 //! │  Discovered  [— select —___________]  [⟳ Scan]     │
+//! │  Wake MAC  [aa:bb:cc:dd:ee:ff]  [⏻ Wake]           │
 //! │  Displays  [1 ▼]  Resolution  [1920x1080 ▼]  FPS [60]│
+//! │  Monitor 0  [DP-1 — 2560×1440 ▼]  [⟳]              │
+//! │  Preset  [Text sharp ▼]                             │
 //! │  Bitrate  [8000] kbps                               │
+//! │  Pipeline  (•) Split  ( ) Fused                     │
 //! ├─────────────────────────────────────────────────────┤
 //! │  [   Start Streaming   ]  [  Stop  ]               │
 //! ├─────────────────────────────────────────────────────┤
-//! │  Display 0  ● Streaming  47.2 fps  12340 frames     │
+//! │  Display 0  [▣]  ● Streaming  47.2 fps  12340 frames│
+//! │  ▸ Log (12)                                         │
 //! └─────────────────────────────────────────────────────┘
 //! ```
 
 use std::collections::HashMap;
+use std::time::Duration;
 
+use duallink_capture_linux::{check_portal, list_monitors, Backend, PortalCheck, PortalProblem};
+use duallink_core::locale::language;
+use duallink_core::{
+    AppearanceSettings, Theme, WindowGeometry, UI_SCALES, FileOffer, FileTransferEvent,
+    set_language, ColorMatrix, ColorRange, ColorSpace, Language, LatencyMode, MonitorAssignments, MonitorInfo,
+    NetworkPolicy, QualityPreset, SenderQueues, WakeAddresses, test_pattern_arg, DEFAULT_TEST_PATTERN,
+};
+use duallink_sender_lib::pipeline_log::{LogLevel, PipelineLog};
+use duallink_sender_lib::preview::PreviewSlot;
+use duallink_sender_lib::BitrateAllocator;
+use duallink_transport_client::{ports_from_txt, signaling_port, wake_receiver, PortMap};
 use eframe::egui::{self, Color32, RichText};
 use tokio::sync::mpsc;
 use tokio::runtime::Handle;
 
-use crate::pipeline::{PipelineConfig, PipelineState, PipelineStatus, SenderPipeline};
+use crate::pipeline::{
+    PipelineConfig, PipelineState, PipelineStatus, SenderPipeline, SenderPipelineMode,
+};
+use crate::strings::{t, tf};
 
 // ── Discovered receiver ───────────────────────────────────────────────────────
 
@@ -35,8 +87,25 @@ use crate::pipeline::{PipelineConfig, PipelineState, PipelineStatus, SenderPipel
 pub struct DiscoveredReceiver {
     pub name:     String,
     pub host:     String,
-    pub port:     u16,
+    /// Per-display ports from the TXT record.
+    pub ports:    PortMap,
     pub displays: u8,
+    /// MAC address from the `mac` TXT key (Wake-on-LAN), if advertised.
+    pub mac:      Option<String>,
+}
+
+/// How long to wait for a woken receiver's signaling port to open.
+const WAKE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Finished transfers listed under "Files" before the oldest goes.
+const MAX_TRANSFER_ROWS: usize = 5;
+
+/// What the capture pre-flight found.
+#[derive(Debug, Clone)]
+struct CaptureCheck {
+    portal:     PortalCheck,
+    /// Screencopy works here, so split mode needs no portal.
+    screencopy: bool,
 }
 
 // ── SenderApp ─────────────────────────────────────────────────────────────────
@@ -51,16 +120,50 @@ pub struct SenderApp {
     height:        u32,
     fps:           u32,
     bitrate_kbps:  u32,
+    pipeline_mode: SenderPipelineMode,
+    /// Pattern streamed in test-pattern mode (`--test-pattern=<name>`).
+    test_pattern:  &'static str,
+    /// Quality preset that filled fps/bitrate (`None` = custom values).
+    preset:        Option<QualityPreset>,
+    /// Request H.264 High 4:4:4 (near-lossless text) if the receiver supports it.
+    lossless:      bool,
+    /// Colour range / matrix of the encoded stream.
+    color:         ColorSpace,
+    /// Ask receivers for thumbnails of what they show.
+    remote_preview: bool,
+    /// Stream at each receiver panel's resolution rather than the one picked.
+    match_receiver: bool,
     /// Index into RESOLUTIONS table.
     resolution_idx: usize,
 
+    // ── Monitor selection ──
+    /// Local monitors, refreshed with the ⟳ button.
+    monitors:      Vec<MonitorInfo>,
+    /// Stream → monitor mapping, saved on every change.
+    assignments:   MonitorAssignments,
+
     // ── mDNS discovery ──
     discovered:    Vec<DiscoveredReceiver>,
     discovery_rx:  Option<mpsc::Receiver<DiscoveredReceiver>>,
     selected_peer: Option<usize>,
 
+    // ── Wake-on-LAN ──
+    /// MAC address of the receiver to wake (auto-filled from discovery).
+    receiver_mac:  String,
+    /// Host → MAC, from mDNS `mac` TXT records and the MAC field; saved.
+    mac_cache:     WakeAddresses,
+    wake_rx:       Option<mpsc::Receiver<Result<Duration, String>>>,
+    wake_status:   Option<(String, Color32)>,
+
+    // ── Capture pre-flight ──
+    /// Result of the last check; `None` until one finishes.
+    capture_check: Option<CaptureCheck>,
+    capture_rx:    Option<mpsc::Receiver<CaptureCheck>>,
+
     // ── Runtime state ──
     running: bool,
+    /// Receiver displays are blanked ("Blank receiver" toggle).
+    remote_blank: bool,
     /// Pipeline handles — one per active display.
     pipelines: Vec<crate::pipeline::SenderPipeline>,
     /// Channel for receiving status updates from pipelines.
@@ -69,6 +172,25 @@ pub struct SenderApp {
     status_tx_template: mpsc::Sender<PipelineStatus>,
     /// Latest status per display index.
     status: HashMap<u8, PipelineStatus>,
+    /// Event log per display index — survives the pipeline until the next start.
+    logs:   HashMap<u8, PipelineLog>,
+    /// Uploaded preview thumbnail per display index, with its generation.
+    previews: HashMap<u8, (u64, egui::TextureHandle)>,
+    /// Likewise for the receivers' thumbnails.
+    remote_previews: HashMap<u8, (u64, egui::TextureHandle)>,
+
+    // ── File transfer ──
+    /// Files receivers offered, by display index, waiting for an answer.
+    file_offers:   Vec<(u8, FileOffer)>,
+    /// Latest event of each recent transfer, oldest first.
+    transfers:     Vec<FileTransferEvent>,
+    /// Why a dropped file could not be sent.
+    file_status:   Option<String>,
+
+    // ── Appearance ──
+    appearance:    AppearanceSettings,
+    /// Window geometry as of the last frame, saved on exit.
+    geometry:      Option<WindowGeometry>,
 
     // ── tokio handle for spawning tasks ──
     rt_handle: Handle,
@@ -78,7 +200,11 @@ impl SenderApp {
     /// Create a new sender app with a tokio runtime handle.
     pub fn new(rt_handle: Handle, cc: &eframe::CreationContext<'_>) -> Self {
         let (status_tx, status_rx) = mpsc::channel::<PipelineStatus>(64);
-        Self {
+        let appearance = AppearanceSettings::load();
+        apply_appearance(&cc.egui_ctx, &appearance);
+        // Started with --test-pattern: preselect the test-pattern mode.
+        let test_pattern = test_pattern_arg(std::env::args());
+        let mut app = Self {
             host:          "192.168.1.100".to_owned(),
             pairing_pin:   "000000".to_owned(),
             display_count: 1,
@@ -86,17 +212,46 @@ impl SenderApp {
             height:        1080,
             fps:           60,
             bitrate_kbps:  8000,
+            pipeline_mode: match test_pattern {
+                Some(_) => SenderPipelineMode::TestPattern,
+                None => SenderPipelineMode::Split,
+            },
+            test_pattern:  test_pattern.unwrap_or(DEFAULT_TEST_PATTERN),
+            preset:        None,
+            lossless:      false,
+            color:         ColorSpace::default(),
+            remote_preview: false,
+            match_receiver: false,
             resolution_idx: 2, // 1920×1080
+            monitors:      list_monitors(),
+            assignments:   MonitorAssignments::load(),
             discovered:    Vec::new(),
             discovery_rx:  None,
             selected_peer: None,
+            receiver_mac:  String::new(),
+            mac_cache:     WakeAddresses::load(),
+            wake_rx:       None,
+            wake_status:   None,
+            capture_check: None,
+            capture_rx:    None,
             running: false,
+            remote_blank: false,
             pipelines: Vec::new(),
             status_rx,
             status_tx_template: status_tx,
             status: HashMap::new(),
+            logs:   HashMap::new(),
+            previews: HashMap::new(),
+            remote_previews: HashMap::new(),
+            file_offers:   Vec::new(),
+            transfers:     Vec::new(),
+            file_status:   None,
+            appearance,
+            geometry:      None,
             rt_handle,
-        }
+        };
+        app.start_capture_check();
+        app
     }
 
     // ── mDNS discovery ────────────────────────────────────────────────────
@@ -113,6 +268,9 @@ impl SenderApp {
     fn poll_discovery(&mut self) {
         if let Some(rx) = &mut self.discovery_rx {
             while let Ok(peer) = rx.try_recv() {
+                if let Some(mac) = &peer.mac {
+                    self.remember_mac(&peer.host, mac);
+                }
                 if !self.discovered.iter().any(|p| p.host == peer.host) {
                     self.discovered.push(peer);
                 }
@@ -120,38 +278,198 @@ impl SenderApp {
         }
     }
 
+    // ── Wake-on-LAN ───────────────────────────────────────────────────────
+
+    /// Remember `mac` for `host` across launches — the receiver may be
+    /// asleep, and not advertising it, the next time the sender starts.
+    fn remember_mac(&mut self, host: &str, mac: &str) {
+        if self.mac_cache.insert(host, mac) {
+            if let Err(e) = self.mac_cache.save() {
+                tracing::warn!("Saving receiver MAC addresses: {}", e);
+            }
+        }
+    }
+
+    fn start_wake(&mut self) {
+        let (tx, rx) = mpsc::channel::<Result<Duration, String>>(1);
+        self.wake_rx = Some(rx);
+        self.wake_status = Some((t("wake.waking").to_owned(), Color32::YELLOW));
+
+        let mac  = self.receiver_mac.clone();
+        let host = self.host.clone();
+        // A MAC typed in by hand is remembered like a discovered one.
+        self.remember_mac(&host, &mac);
+        let port = signaling_port(&self.receiver_ports(), 0);
+        let _guard = self.rt_handle.enter();
+        tokio::spawn(async move {
+            let result = wake_receiver(&mac, &host, port, WAKE_TIMEOUT)
+                .await
+                .map_err(|e| format!("{e:#}"));
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Ports advertised by the discovered receiver at `host`, else the
+    /// default layout.
+    fn receiver_ports(&self) -> PortMap {
+        self.discovered
+            .iter()
+            .find(|p| p.host == self.host)
+            .map(|p| p.ports.clone())
+            .unwrap_or_default()
+    }
+
+    fn poll_wake(&mut self) {
+        let Some(rx) = &mut self.wake_rx else { return };
+        if let Ok(result) = rx.try_recv() {
+            self.wake_status = Some(match result {
+                Ok(took) => (tf("wake.up", &[("seconds", &format!("{:.0}", took.as_secs_f32()))]), Color32::GREEN),
+                Err(e)   => (tf("wake.failed", &[("error", &e)]), Color32::RED),
+            });
+            self.wake_rx = None;
+        }
+    }
+
+    // ── Capture pre-flight ────────────────────────────────────────────────
+
+    fn start_capture_check(&mut self) {
+        let (tx, rx) = mpsc::channel::<CaptureCheck>(1);
+        self.capture_rx = Some(rx);
+        let _guard = self.rt_handle.enter();
+        tokio::spawn(async move {
+            let screencopy = Backend::Screencopy.probe().await.is_ok();
+            let _ = tx.send(CaptureCheck { portal: check_portal().await, screencopy }).await;
+        });
+    }
+
+    fn poll_capture_check(&mut self) {
+        let Some(rx) = &mut self.capture_rx else { return };
+        if let Ok(check) = rx.try_recv() {
+            if let Some(problem) = &check.portal.problem {
+                tracing::warn!("Screen-cast portal unavailable: {} (backends: {:?})", problem, check.portal.backends);
+            }
+            self.capture_check = Some(check);
+            self.capture_rx = None;
+        }
+    }
+
+    /// The portal check, if the picked pipeline mode needs the portal and
+    /// it doesn't work.
+    fn capture_blocker(&self) -> Option<&PortalCheck> {
+        let check = self.capture_check.as_ref()?;
+        let needs_portal = match self.pipeline_mode {
+            SenderPipelineMode::Fused => true,
+            SenderPipelineMode::Split => !check.screencopy,
+            SenderPipelineMode::TestPattern => false,
+        };
+        (needs_portal && !check.portal.is_ready()).then_some(&check.portal)
+    }
+
     fn start(&mut self) {
         if self.running {
             return;
         }
         self.running = true;
         self.status.clear();
+        self.logs.clear();
+        self.previews.clear();
+        self.remote_previews.clear();
 
         // Spawn N pipelines
+        let ports = self.receiver_ports();
+        // One budget for this run's displays.
+        let budget = BitrateAllocator::from_env();
         for i in 0..self.display_count as u8 {
             let cfg = PipelineConfig {
                 host:          self.host.clone(),
                 pairing_pin:   self.pairing_pin.clone(),
                 display_index: i,
+                ports:         ports.clone(),
                 width:         self.width,
                 height:        self.height,
                 fps:           self.fps,
                 bitrate_kbps:  self.bitrate_kbps,
+                prefer_nv12:   true,
+                mode:          self.pipeline_mode,
+                test_pattern:  self.test_pattern,
+                preset:        self.preset,
+                lossless:      self.lossless,
+                color:         self.color,
+                monitor:       self.assignments.get(i).map(str::to_owned),
+                remote_preview: self.remote_preview,
+                match_receiver_resolution: self.match_receiver,
+                network_caps:  NetworkPolicy::from_env(),
+                budget:        budget.clone(),
+                latency_mode:  LatencyMode::from_env(),
+                queues:        SenderQueues::from_env(),
+                ..PipelineConfig::default()
             };
             let status_tx = self.status_tx_template.clone();
             // Enter the tokio runtime context so tokio::spawn works from eframe's main thread.
             let _guard = self.rt_handle.enter();
             let pl = SenderPipeline::spawn(cfg, status_tx);
+            self.logs.insert(i, pl.log().clone());
             self.pipelines.push(pl);
         }
     }
 
+    /// Fill fps/bitrate from `preset` and switch any running pipelines to it
+    /// without restarting them.
+    fn apply_preset(&mut self, preset: QualityPreset) {
+        let params = preset.params();
+        self.fps = params.target_fps;
+        self.bitrate_kbps = (params.max_bitrate_bps / 1000) as u32;
+        for pl in &self.pipelines {
+            pl.apply_preset(preset);
+        }
+    }
+
+    /// Bitrate / fps / keyframe controls for running pipelines. Changed
+    /// rates apply in place and switch the preset to custom.
+    fn rate_controls(&mut self, ui: &mut egui::Ui) {
+        let bitrate = ui.add(
+            egui::DragValue::new(&mut self.bitrate_kbps)
+                .range(500..=50000)
+                .speed(100.0)
+                .suffix(" kbps"),
+        );
+        // Send once a drag ends, not on every step of it.
+        if bitrate.drag_stopped() || (bitrate.changed() && !bitrate.dragged()) {
+            self.preset = None;
+            for pl in &self.pipelines {
+                pl.set_bitrate(self.bitrate_kbps);
+            }
+        }
+        let fps = ui
+            .add(egui::DragValue::new(&mut self.fps).range(1..=60).suffix(" fps"))
+            .on_hover_text(t("action.fps_hint"));
+        if fps.drag_stopped() || (fps.changed() && !fps.dragged()) {
+            self.preset = None;
+            for pl in &self.pipelines {
+                pl.set_fps(self.fps);
+            }
+        }
+        if ui.button(t("action.keyframe")).on_hover_text(t("action.keyframe_hint")).clicked() {
+            for pl in &self.pipelines {
+                pl.force_keyframe();
+            }
+        }
+    }
+
+    fn assign_monitor(&mut self, display_index: u8, monitor: Option<String>) {
+        self.assignments.set(display_index, monitor);
+        if let Err(e) = self.assignments.save() {
+            tracing::warn!("Saving monitor assignments: {}", e);
+        }
+    }
+
     fn stop(&mut self) {
         for pl in &self.pipelines {
             pl.stop();
         }
         self.pipelines.clear();
         self.running = false;
+        self.remote_blank = false;
     }
 
     fn poll_status(&mut self) {
@@ -172,11 +490,117 @@ impl SenderApp {
     }
 }
 
+impl SenderApp {
+    /// Upload thumbnails published since the last frame.
+    fn poll_previews(&mut self, ctx: &egui::Context) {
+        for pl in &self.pipelines {
+            upload_thumbnail(ctx, &mut self.previews, pl.display_index, &pl.preview, "preview");
+            upload_thumbnail(ctx, &mut self.remote_previews, pl.display_index, &pl.remote_preview, "remote_preview");
+        }
+    }
+}
+
+impl SenderApp {
+    /// Send files dropped on the window and collect transfer events.
+    fn poll_files(&mut self, ctx: &egui::Context) {
+        let dropped: Vec<_> = ctx.input(|i| i.raw.dropped_files.iter().filter_map(|f| f.path.clone()).collect());
+        for path in dropped {
+            self.file_status = match self.pipelines.first() {
+                None => Some(t("files.not_streaming").to_owned()),
+                Some(pl) => pl.files.send(&path).err().map(|e| {
+                    tf("files.unreadable", &[("name", &path.display()), ("error", &e)])
+                }),
+            };
+        }
+        for pl in &self.pipelines {
+            for event in pl.files.take_events() {
+                match event {
+                    FileTransferEvent::Offered(offer) => self.file_offers.push((pl.display_index, offer)),
+                    event => {
+                        self.file_offers.retain(|(_, o)| o.transfer_id != event.transfer_id());
+                        self.transfers.retain(|e| e.transfer_id() != event.transfer_id());
+                        self.transfers.push(event);
+                    }
+                }
+            }
+        }
+        while self.transfers.len() > MAX_TRANSFER_ROWS {
+            let finished = self.transfers.iter().position(|e| !matches!(e, FileTransferEvent::Progress { .. }));
+            let Some(i) = finished else { break };
+            self.transfers.remove(i);
+        }
+    }
+
+    /// Offers to answer and the recent transfers, if any.
+    fn file_rows(&mut self, ui: &mut egui::Ui) {
+        if !self.running && self.file_offers.is_empty() && self.transfers.is_empty() {
+            return;
+        }
+        ui.separator();
+        ui.label(RichText::new(t("files.title")).strong()).on_hover_text(t("files.drop_hint"));
+        if let Some(msg) = &self.file_status {
+            ui.label(RichText::new(msg).color(Color32::YELLOW));
+        }
+        let mut answered = None;
+        for (display, offer) in &self.file_offers {
+            ui.horizontal(|ui| {
+                ui.label(tf("files.offer", &[("name", &offer.name), ("size", &megabytes(offer.size))]));
+                if ui.small_button(t("files.accept")).clicked() {
+                    answered = Some((*display, offer.transfer_id.clone(), true));
+                }
+                if ui.small_button(t("files.decline")).clicked() {
+                    answered = Some((*display, offer.transfer_id.clone(), false));
+                }
+            });
+        }
+        if let Some((display, transfer_id, accept)) = answered {
+            if let Some(pl) = self.pipelines.iter().find(|p| p.display_index == display) {
+                pl.files.answer(&transfer_id, accept);
+            }
+            self.file_offers.retain(|(_, o)| o.transfer_id != transfer_id);
+        }
+        for event in &self.transfers {
+            match event {
+                FileTransferEvent::Progress { name, done, total, .. } => {
+                    ui.horizontal(|ui| {
+                        ui.label(name);
+                        ui.add(
+                            egui::ProgressBar::new(*done as f32 / (*total).max(1) as f32)
+                                .desired_width(160.0)
+                                .text(format!("{} / {}", megabytes(*done), megabytes(*total))),
+                        );
+                    });
+                }
+                FileTransferEvent::Received { name, path, .. } => {
+                    ui.label(RichText::new(tf("files.received", &[("name", name)])).color(Color32::GREEN))
+                        .on_hover_text(path.display().to_string());
+                }
+                FileTransferEvent::Sent { name, .. } => {
+                    ui.label(RichText::new(tf("files.sent", &[("name", name)])).color(Color32::GREEN));
+                }
+                FileTransferEvent::Failed { name, reason, .. } => {
+                    ui.label(
+                        RichText::new(tf("files.failed", &[("name", name), ("reason", reason)])).color(Color32::RED),
+                    );
+                }
+                FileTransferEvent::Offered(_) => {}
+            }
+        }
+    }
+}
+
 impl eframe::App for SenderApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Poll status updates every frame
         self.poll_status();
         self.poll_discovery();
+        self.poll_wake();
+        self.poll_capture_check();
+        self.poll_previews(ctx);
+        self.poll_files(ctx);
+        if let Some(geometry) = current_geometry(ctx) {
+            self.geometry = Some(geometry);
+        }
         // Request a repaint so the UI stays fresh even without user interaction
         ctx.request_repaint_after(std::time::Duration::from_millis(500));
 
@@ -184,7 +608,13 @@ impl eframe::App for SenderApp {
             ui.spacing_mut().item_spacing = egui::vec2(8.0, 6.0);
 
             // ── Title ─────────────────────────────────────────────────────
-            ui.heading("DualLink Linux Sender");
+            ui.horizontal(|ui| {
+                ui.heading("DualLink Linux Sender");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    language_picker(ui);
+                    appearance_pickers(ui, &mut self.appearance);
+                });
+            });
             ui.separator();
 
             // ── Connection settings ───────────────────────────────────────
@@ -195,13 +625,13 @@ impl eframe::App for SenderApp {
                     .spacing([8.0, 4.0])
                     .show(ui, |ui| {
                         // Row 1: Host + PIN
-                        ui.label("Receiver IP:");
+                        ui.label(t("settings.receiver_ip"));
                         ui.add(
                             egui::TextEdit::singleline(&mut self.host)
                                 .hint_text("192.168.1.100")
                                 .desired_width(160.0),
                         );
-                        ui.label("PIN:");
+                        ui.label(t("settings.pin"));
                         ui.add(
                             egui::TextEdit::singleline(&mut self.pairing_pin)
                                 .hint_text("000000")
@@ -210,11 +640,11 @@ impl eframe::App for SenderApp {
                         ui.end_row();
 
                         // Row 2: mDNS discovered receivers
-                        ui.label("Discovered:");
+                        ui.label(t("settings.discovered"));
                         let sel_label = self.selected_peer
                             .and_then(|i| self.discovered.get(i))
                             .map(|p| p.name.clone())
-                            .unwrap_or_else(|| "— scan for receivers —".to_owned());
+                            .unwrap_or_else(|| t("settings.scan_placeholder").to_owned());
                         egui::ComboBox::from_id_source("discovered")
                             .selected_text(sel_label)
                             .width(190.0)
@@ -224,16 +654,37 @@ impl eframe::App for SenderApp {
                                     if ui.selectable_label(self.selected_peer == Some(i), &label).clicked() {
                                         self.selected_peer = Some(i);
                                         self.host = peer.host.clone();
+                                        if let Some(mac) = &peer.mac {
+                                            self.receiver_mac = mac.clone();
+                                        }
                                     }
                                 }
                             });
-                        if ui.small_button("⟳ Scan").clicked() {
+                        if ui.small_button(t("settings.scan")).clicked() {
                             self.start_discovery();
                         }
                         ui.end_row();
 
+                        // Row 2b: Wake-on-LAN
+                        if self.receiver_mac.is_empty() {
+                            if let Some(mac) = self.mac_cache.get(&self.host) {
+                                self.receiver_mac = mac.to_owned();
+                            }
+                        }
+                        ui.label(t("settings.wake_mac"));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.receiver_mac)
+                                .hint_text("aa:bb:cc:dd:ee:ff")
+                                .desired_width(160.0),
+                        );
+                        let can_wake = self.wake_rx.is_none() && !self.receiver_mac.trim().is_empty();
+                        if ui.add_enabled(can_wake, egui::Button::new(t("settings.wake")).small()).clicked() {
+                            self.start_wake();
+                        }
+                        ui.end_row();
+
                         // Row 3: Display count + Resolution
-                        ui.label("Displays:");
+                        ui.label(t("settings.displays"));
                         egui::ComboBox::from_id_source("display_count")
                             .selected_text(format!("{}", self.display_count))
                             .width(60.0)
@@ -247,7 +698,7 @@ impl eframe::App for SenderApp {
                                 }
                             });
 
-                        ui.label("Resolution:");
+                        ui.label(t("settings.resolution"));
                         egui::ComboBox::from_id_source("resolution")
                             .selected_text(format!("{}×{}", self.width, self.height))
                             .width(120.0)
@@ -268,8 +719,55 @@ impl eframe::App for SenderApp {
                             });
                         ui.end_row();
 
-                        // Row 4: FPS + Bitrate
-                        ui.label("FPS:");
+                        // Row 3b: per-stream monitor picker
+                        for i in 0..self.display_count as u8 {
+                            ui.label(tf("settings.monitor", &[("n", &i)]));
+                            let current = self.assignments.get(i).map(str::to_owned);
+                            let mut selected = current.clone();
+                            egui::ComboBox::from_id_source(("monitor", i))
+                                .selected_text(current.as_deref().unwrap_or(t("settings.auto")))
+                                .width(190.0)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut selected, None, t("settings.auto"))
+                                        .on_hover_text(tf("settings.portal_stream", &[("n", &i)]));
+                                    for m in &self.monitors {
+                                        let label = format!(
+                                            "{} — {} at {},{}{}",
+                                            m.name, m.resolution, m.x, m.y,
+                                            if m.primary { t("settings.primary") } else { "" }
+                                        );
+                                        ui.selectable_value(&mut selected, Some(m.name.clone()), label);
+                                    }
+                                });
+                            if selected != current {
+                                self.assign_monitor(i, selected);
+                            }
+                            if i == 0 && ui.small_button("⟳").on_hover_text(t("settings.redetect")).clicked() {
+                                self.monitors = list_monitors();
+                            }
+                            ui.end_row();
+                        }
+
+                        // Row 4: Quality preset
+                        ui.label(t("settings.preset"));
+                        let prev_preset = self.preset;
+                        egui::ComboBox::from_id_source("preset")
+                            .selected_text(self.preset.map_or(t("settings.custom"), |p| p.label()))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.preset, None, t("settings.custom"));
+                                for p in QualityPreset::ALL {
+                                    ui.selectable_value(&mut self.preset, Some(p), p.label());
+                                }
+                            });
+                        if self.preset != prev_preset {
+                            if let Some(p) = self.preset {
+                                self.apply_preset(p);
+                            }
+                        }
+                        ui.end_row();
+
+                        // Row 5: FPS + Bitrate
+                        ui.label(t("settings.fps"));
                         egui::ComboBox::from_id_source("fps")
                             .selected_text(format!("{}", self.fps))
                             .width(60.0)
@@ -279,7 +777,7 @@ impl eframe::App for SenderApp {
                                 }
                             });
 
-                        ui.label("Bitrate:");
+                        ui.label(t("settings.bitrate"));
                         ui.horizontal(|ui| {
                             ui.add(
                                 egui::DragValue::new(&mut self.bitrate_kbps)
@@ -289,47 +787,130 @@ impl eframe::App for SenderApp {
                             ui.label("kbps");
                         });
                         ui.end_row();
+
+                        // Row 6: capture → encode linking
+                        ui.label(t("settings.pipeline"));
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut self.pipeline_mode, SenderPipelineMode::Split, t("settings.split"))
+                                .on_hover_text(t("settings.split_hint"));
+                            ui.radio_value(&mut self.pipeline_mode, SenderPipelineMode::Fused, t("settings.fused"))
+                                .on_hover_text(t("settings.fused_hint"));
+                            ui.radio_value(&mut self.pipeline_mode, SenderPipelineMode::TestPattern, t("settings.test_pattern"))
+                                .on_hover_text(t("settings.test_pattern_hint"));
+                        });
+                        ui.end_row();
+
+                        // Row 7: near-lossless text mode
+                        ui.label(t("settings.lossless"));
+                        ui.checkbox(&mut self.lossless, t("settings.lossless_check"))
+                            .on_hover_text(t("settings.lossless_hint"));
+                        ui.end_row();
+
+                        // Row 8: thumbnails back from the receiver
+                        ui.label(t("settings.remote_preview"));
+                        ui.checkbox(&mut self.remote_preview, t("settings.remote_preview_check"))
+                            .on_hover_text(t("settings.remote_preview_hint"));
+                        ui.end_row();
+
+                        // Row 8b: the receiver panel's resolution instead of the one above
+                        ui.label(t("settings.match_receiver"));
+                        ui.checkbox(&mut self.match_receiver, t("settings.match_receiver_check"))
+                            .on_hover_text(t("settings.match_receiver_hint"));
+                        ui.end_row();
+
+                        // Row 9: colour range / matrix
+                        ui.label(t("settings.color"));
+                        ui.horizontal(|ui| {
+                            egui::ComboBox::from_id_source("color_matrix")
+                                .selected_text(match self.color.matrix {
+                                    ColorMatrix::Bt709 => "BT.709",
+                                    ColorMatrix::Bt601 => "BT.601",
+                                })
+                                .width(70.0)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut self.color.matrix, ColorMatrix::Bt709, "BT.709");
+                                    ui.selectable_value(&mut self.color.matrix, ColorMatrix::Bt601, "BT.601");
+                                });
+                            ui.radio_value(&mut self.color.range, ColorRange::Limited, t("settings.limited"))
+                                .on_hover_text(t("settings.limited_hint"));
+                            ui.radio_value(&mut self.color.range, ColorRange::Full, t("settings.full"))
+                                .on_hover_text(t("settings.full_hint"));
+                        });
+                        ui.end_row();
                     });
             });
 
+            if let Some((msg, color)) = &self.wake_status {
+                ui.label(RichText::new(msg).color(*color));
+            }
+
             ui.separator();
 
             // ── Action buttons ────────────────────────────────────────────
+            let blocker = self.capture_blocker().map(|p| (portal_guidance(p), p.problem.clone()));
             ui.horizontal(|ui| {
                 if !self.running {
-                    if ui
-                        .add_sized(
+                    let start = ui.add_enabled_ui(blocker.is_none(), |ui| {
+                        ui.add_sized(
                             [150.0, 32.0],
                             egui::Button::new(
                                 if self.display_count == 1 {
-                                    "▶  Start Streaming"
+                                    t("action.start")
                                 } else {
-                                    "▶  Start All Displays"
+                                    t("action.start_all")
                                 },
                             ),
                         )
-                        .clicked()
-                    {
+                    });
+                    if start.inner.clicked() {
                         self.start();
                     }
                 } else {
                     if ui
-                        .add_sized([120.0, 32.0], egui::Button::new("■  Stop"))
+                        .add_sized([120.0, 32.0], egui::Button::new(t("action.stop")))
                         .clicked()
                     {
                         self.stop();
                     }
+                    if ui
+                        .checkbox(&mut self.remote_blank, t("action.blank"))
+                        .on_hover_text(t("action.blank_hint"))
+                        .changed()
+                    {
+                        for pl in &self.pipelines {
+                            pl.set_remote_blank(self.remote_blank);
+                        }
+                    }
+                    ui.separator();
+                    self.rate_controls(ui);
                 }
             });
 
+            // What keeps Start disabled, and how to fix it.
+            if !self.running {
+                if let Some((guidance, problem)) = &blocker {
+                    ui.horizontal_wrapped(|ui| {
+                        let details = problem.as_ref().map(ToString::to_string).unwrap_or_default();
+                        ui.label(RichText::new(guidance).color(Color32::YELLOW))
+                            .on_hover_text(format!("{details}\n{}", t("portal.test_pattern_hint")));
+                        let recheck = egui::Button::new(t("portal.recheck")).small();
+                        if ui.add_enabled(self.capture_rx.is_none(), recheck).clicked() {
+                            self.start_capture_check();
+                        }
+                    });
+                } else if self.capture_rx.is_some() && self.pipeline_mode != SenderPipelineMode::TestPattern {
+                    ui.label(RichText::new(t("portal.checking")).color(Color32::GRAY));
+                }
+            }
+
             ui.separator();
 
             // ── Per-display status ────────────────────────────────────────
-            ui.label(RichText::new("Display Status").strong());
+            ui.label(RichText::new(t("status.title")).strong());
 
             if !self.running && self.status.is_empty() {
                 ui.label(
-                    RichText::new("Not connected — configure and click Start Streaming.")
+                    RichText::new(t("status.not_connected"))
                         .color(Color32::GRAY),
                 );
             }
@@ -339,32 +920,143 @@ impl eframe::App for SenderApp {
                 ui.horizontal(|ui| {
                     match status {
                         None => {
-                            ui.label(format!("Display {i}"));
-                            ui.label(RichText::new("⊘ Idle").color(Color32::GRAY));
+                            ui.label(tf("status.display", &[("n", &i)]));
+                            ui.label(RichText::new(t("status.idle")).color(Color32::GRAY));
                         }
                         Some(s) => {
-                            ui.label(format!("Display {i}"));
+                            ui.label(tf("status.display", &[("n", &i)]));
                             match &s.state {
                                 PipelineState::Connecting => {
                                     ui.label(
-                                        RichText::new("⟳ Connecting…")
+                                        RichText::new(t("status.connecting"))
                                             .color(Color32::YELLOW),
                                     );
                                 }
-                                PipelineState::Streaming => {
+                                PipelineState::AwaitingCapture => {
                                     ui.label(
-                                        RichText::new("● Streaming")
-                                            .color(Color32::GREEN),
-                                    );
+                                        RichText::new(t("status.awaiting_capture"))
+                                            .color(Color32::YELLOW),
+                                    )
+                                    .on_hover_text(t("status.awaiting_capture_hint"));
+                                }
+                                PipelineState::Streaming => {
+                                    if let Some((_, texture)) = self.previews.get(&i) {
+                                        ui.add(egui::Image::new(texture).max_width(96.0))
+                                            .on_hover_ui(|ui| {
+                                                ui.image(texture);
+                                            });
+                                    }
+                                    if let Some((_, texture)) = self.remote_previews.get(&i) {
+                                        ui.label("→");
+                                        ui.add(egui::Image::new(texture).max_width(96.0))
+                                            .on_hover_ui(|ui| {
+                                                ui.label(t("status.remote_preview"));
+                                                ui.image(texture);
+                                            });
+                                    }
+                                    if s.display_paused {
+                                        ui.label(RichText::new(t("status.paused")).color(Color32::YELLOW))
+                                            .on_hover_text(t("status.paused_hint"));
+                                    } else {
+                                        ui.label(
+                                            RichText::new(t("status.streaming"))
+                                                .color(Color32::GREEN),
+                                        );
+                                    }
+                                    let (label, hover) = if s.display_paused {
+                                        (t("status.resume"), t("status.resume_hint"))
+                                    } else {
+                                        (t("status.pause"), t("status.pause_hint"))
+                                    };
+                                    if ui.small_button(label).on_hover_text(hover).clicked() {
+                                        if let Some(pl) = self.pipelines.iter().find(|p| p.display_index == i) {
+                                            pl.set_paused(!s.display_paused);
+                                        }
+                                    }
                                     ui.label(format!("{:.1} fps", s.fps));
                                     ui.label(
-                                        RichText::new(format!("{} frames", s.frames_sent))
+                                        RichText::new(tf("status.frames", &[("count", &s.frames_sent)]))
                                             .color(Color32::GRAY),
                                     );
+                                    if s.capture_paused {
+                                        ui.label(
+                                            RichText::new(t("status.paused_by_receiver"))
+                                                .color(Color32::YELLOW),
+                                        )
+                                        .on_hover_text(t("status.paused_by_receiver_hint"));
+                                    }
+                                    let on_battery = [s.power, s.receiver_power].iter().flatten().any(|p| p.on_battery);
+                                    if s.battery_saver || on_battery {
+                                        let color = if s.battery_saver { Color32::YELLOW } else { Color32::GRAY };
+                                        let label = if s.battery_saver { t("status.saver") } else { "🔋" };
+                                        let this = s.power.map_or_else(|| t("status.no_battery").to_owned(), |p| p.to_string());
+                                        let receiver = s
+                                            .receiver_power
+                                            .map_or_else(|| t("status.no_battery").to_owned(), |p| p.to_string());
+                                        ui.label(RichText::new(label).color(color)).on_hover_text(tf(
+                                            "status.power_hint",
+                                            &[("this", &this), ("receiver", &receiver)],
+                                        ));
+                                    }
+                                    if s.idle {
+                                        ui.label(RichText::new(t("status.idle")).color(Color32::GRAY))
+                                            .on_hover_text(t("status.idle_hint"));
+                                    }
+                                    if s.frames_skipped > 0 {
+                                        ui.label(
+                                            RichText::new(tf("status.static", &[("count", &s.frames_skipped)]))
+                                                .color(Color32::GRAY),
+                                        )
+                                        .on_hover_text(t("status.static_hint"));
+                                    }
+                                    if s.frames_dropped > 0 {
+                                        ui.label(
+                                            RichText::new(tf("status.dropped", &[("count", &s.frames_dropped)]))
+                                                .color(Color32::YELLOW),
+                                        );
+                                    }
+                                    if let Some(cap) = s.throttled_fps {
+                                        ui.label(
+                                            RichText::new(tf("status.capped", &[("fps", &cap)]))
+                                                .color(Color32::YELLOW),
+                                        );
+                                    }
+                                    if let Some(link) = &s.link {
+                                        let color = if link.is_degraded() {
+                                            Color32::YELLOW
+                                        } else {
+                                            Color32::GRAY
+                                        };
+                                        ui.label(RichText::new(link.to_string()).color(color))
+                                            .on_hover_text(t("status.link_hint"));
+                                    }
+                                    if let Some(enc) = &s.encoder {
+                                        ui.label(RichText::new(enc).color(Color32::GRAY).small());
+                                    }
+                                    if s.lossless {
+                                        ui.label(RichText::new("4:4:4").color(Color32::GRAY).small())
+                                            .on_hover_text(t("status.lossless_hint"));
+                                    }
+                                    if let Some(panel) = &s.receiver_display {
+                                        ui.label(
+                                            RichText::new(format!(
+                                                "→ {} @ {:.0} Hz",
+                                                panel.resolution, panel.refresh_hz
+                                            ))
+                                            .color(Color32::GRAY)
+                                            .small(),
+                                        )
+                                        .on_hover_text(tf("status.receiver_panel", &[
+                                            ("name", &panel.name),
+                                            ("width", &panel.width_mm),
+                                            ("height", &panel.height_mm),
+                                            ("scale", &panel.scale),
+                                        ]));
+                                    }
                                 }
                                 PipelineState::Stopped => {
                                     ui.label(
-                                        RichText::new("○ Stopped").color(Color32::GRAY),
+                                        RichText::new(t("status.stopped")).color(Color32::GRAY),
                                     );
                                 }
                                 PipelineState::Failed(msg) => {
@@ -377,14 +1069,210 @@ impl eframe::App for SenderApp {
                         }
                     }
                 });
+                if let Some(log) = self.logs.get(&i) {
+                    render_pipeline_log(ui, i, log);
+                }
             }
 
+            self.file_rows(ui);
+
             // ── Footer ────────────────────────────────────────────────────
             ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
                 ui.small(concat!("DualLink v", env!("CARGO_PKG_VERSION")));
             });
         });
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Some(geometry) = self.geometry {
+            if let Err(e) = AppearanceSettings::save_window(WINDOW_KEY, geometry) {
+                tracing::warn!("Saving window geometry: {}", e);
+            }
+        }
+    }
+}
+
+// ── Capture pre-flight ────────────────────────────────────────────────────────
+
+/// What to install so that `portal` works, in the current language.
+fn portal_guidance(portal: &PortalCheck) -> String {
+    if portal.problem == Some(PortalProblem::NoPipeWirePlugin) {
+        return t("portal.no_plugin").to_owned();
+    }
+    let backends = if portal.backends.is_empty() { t("portal.none").to_owned() } else { portal.backends.join(", ") };
+    match portal.package() {
+        Some(package) => tf("portal.install", &[("package", &package), ("backends", &backends)]),
+        None => tf("portal.install_any", &[("backends", &backends)]),
+    }
+}
+
+// ── Language ──────────────────────────────────────────────────────────────────
+
+/// UI language picker; the choice applies at once and is saved for the
+/// next launch.
+fn language_picker(ui: &mut egui::Ui) {
+    let current = language();
+    let mut choice = current;
+    egui::ComboBox::from_id_source("language")
+        .selected_text(current.native_name())
+        .show_ui(ui, |ui| {
+            for lang in Language::ALL {
+                ui.selectable_value(&mut choice, lang, lang.native_name());
+            }
+        })
+        .response
+        .on_hover_text(t("app.language"));
+    if choice != current {
+        set_language(choice);
+        if let Err(e) = choice.save() {
+            tracing::warn!("Saving language: {}", e);
+        }
+    }
+}
+
+// ── Appearance ────────────────────────────────────────────────────────────────
+
+/// Key of this UI's window in [`AppearanceSettings::windows`].
+const WINDOW_KEY: &str = "linux-sender";
+
+/// `viewport` at the size and place the window last closed at.
+pub fn restore_window(viewport: egui::ViewportBuilder) -> egui::ViewportBuilder {
+    let Some(geometry) = AppearanceSettings::load().window(WINDOW_KEY) else { return viewport };
+    let viewport = viewport.with_inner_size(geometry.size);
+    match geometry.position {
+        Some(position) => viewport.with_position(position),
+        None => viewport,
+    }
+}
+
+/// Apply the saved theme and scale to `ctx`.
+fn apply_appearance(ctx: &egui::Context, settings: &AppearanceSettings) {
+    ctx.set_theme(match settings.theme {
+        Theme::Dark => egui::ThemePreference::Dark,
+        Theme::Light => egui::ThemePreference::Light,
+        Theme::System => egui::ThemePreference::System,
+    });
+    ctx.set_zoom_factor(settings.scale());
+}
+
+/// The window's current geometry in logical pixels, for saving on exit.
+fn current_geometry(ctx: &egui::Context) -> Option<WindowGeometry> {
+    // Viewport rects are in points, which the zoom factor scales.
+    let zoom = ctx.zoom_factor();
+    ctx.input(|i| {
+        let viewport = i.viewport();
+        let inner = viewport.inner_rect?;
+        Some(WindowGeometry {
+            position: viewport.outer_rect.map(|r| [r.min.x * zoom, r.min.y * zoom]),
+            size:     [inner.width() * zoom, inner.height() * zoom],
+        })
+    })
+}
+
+/// Theme and UI scale pickers; changes apply at once and are saved for
+/// every DualLink UI.
+fn appearance_pickers(ui: &mut egui::Ui, settings: &mut AppearanceSettings) {
+    let before = (settings.theme, settings.ui_scale);
+    egui::ComboBox::from_id_source("theme")
+        .selected_text(theme_label(settings.theme))
+        .show_ui(ui, |ui| {
+            for theme in Theme::ALL {
+                ui.selectable_value(&mut settings.theme, theme, theme_label(theme));
+            }
+        })
+        .response
+        .on_hover_text(t("appearance.theme"));
+    egui::ComboBox::from_id_source("ui_scale")
+        .selected_text(format!("{:.0}%", settings.scale() * 100.0))
+        .show_ui(ui, |ui| {
+            for scale in UI_SCALES {
+                ui.selectable_value(&mut settings.ui_scale, scale, format!("{:.0}%", scale * 100.0));
+            }
+        })
+        .response
+        .on_hover_text(t("appearance.scale"));
+    if (settings.theme, settings.ui_scale) == before {
+        return;
+    }
+    apply_appearance(ui.ctx(), settings);
+    // Geometry is written by each UI on exit; keep the others'.
+    let mut saved = AppearanceSettings::load();
+    saved.theme = settings.theme;
+    saved.ui_scale = settings.ui_scale;
+    if let Err(e) = saved.save() {
+        tracing::warn!("Saving appearance: {}", e);
+    }
+}
+
+fn theme_label(theme: Theme) -> &'static str {
+    t(match theme {
+        Theme::Dark => "appearance.dark",
+        Theme::Light => "appearance.light",
+        Theme::System => "appearance.system",
+    })
+}
+
+// ── Per-display log panel ─────────────────────────────────────────────────────
+
+/// Collapsible log of one pipeline's events, newest at the bottom.
+/// `bytes` as megabytes for file rows.
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / 1e6)
+}
+
+fn render_pipeline_log(ui: &mut egui::Ui, display_index: u8, log: &PipelineLog) {
+    let entries = log.entries();
+    let problems = entries.iter().filter(|e| e.level != LogLevel::Info).count();
+    let title = if problems > 0 {
+        tf("log.title_problems", &[("count", &entries.len()), ("problems", &problems)])
+    } else {
+        tf("log.title", &[("count", &entries.len())])
+    };
+    egui::CollapsingHeader::new(RichText::new(title).small())
+        .id_salt(("pipeline_log", display_index))
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .id_salt(("pipeline_log_scroll", display_index))
+                .max_height(140.0)
+                .auto_shrink([false, true])
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for entry in &entries {
+                        let color = match entry.level {
+                            LogLevel::Error => Color32::from_rgb(220, 80, 70),
+                            LogLevel::Warn  => Color32::from_rgb(220, 165, 50),
+                            LogLevel::Info  => Color32::from_rgb(160, 170, 185),
+                        };
+                        ui.label(RichText::new(entry.to_string()).monospace().small().color(color));
+                    }
+                });
+        });
+}
+
+// ── Thumbnails ────────────────────────────────────────────────────────────────
+
+/// Upload `slot`'s thumbnail into `textures[display_index]` if it is newer
+/// than the one there.
+fn upload_thumbnail(
+    ctx: &egui::Context,
+    textures: &mut HashMap<u8, (u64, egui::TextureHandle)>,
+    display_index: u8,
+    slot: &PreviewSlot,
+    name: &str,
+) {
+    let generation = textures.get(&display_index).map_or(0, |(g, _)| *g);
+    let Some(thumb) = slot.newer_than(generation) else { return };
+    let image = egui::ColorImage::from_rgba_unmultiplied([thumb.width as usize, thumb.height as usize], &thumb.rgba);
+    match textures.get_mut(&display_index) {
+        Some((g, texture)) => {
+            texture.set(image, egui::TextureOptions::LINEAR);
+            *g = thumb.generation;
+        }
+        None => {
+            let texture = ctx.load_texture(format!("{name}_{display_index}"), image, egui::TextureOptions::LINEAR);
+            textures.insert(display_index, (thumb.generation, texture));
+        }
+    }
 }
 
 // ── mDNS browser task ─────────────────────────────────────────────────────────
@@ -422,20 +1310,27 @@ async fn browse_receivers(tx: mpsc::Sender<DiscoveredReceiver>) {
 
                 let port = info.get_properties()
                     .get("port")
-                    .and_then(|v| v.val_str().parse().ok())
-                    .unwrap_or(7879u16);
+                    .and_then(|v| v.val_str().parse().ok());
                 let displays = info.get_properties()
                     .get("displays")
                     .and_then(|v| v.val_str().parse().ok())
                     .unwrap_or(1u8);
+                let ports = ports_from_txt(
+                    info.get_properties().get("ports").map(|v| v.val_str()),
+                    port,
+                    displays,
+                );
+                let mac = info.get_properties()
+                    .get("mac")
+                    .map(|v| v.val_str().to_owned());
                 let name = info.get_fullname()
                     .split('.')
                     .next()
                     .unwrap_or("DualLink Receiver")
                     .to_owned();
 
-                tracing::info!("[mDNS] Found receiver: {} @ {}:{}", name, host, port);
-                let _ = tx.send(DiscoveredReceiver { name, host, port, displays }).await;
+                tracing::info!("[mDNS] Found receiver: {} @ {} (ports {})", name, host, ports.to_txt());
+                let _ = tx.send(DiscoveredReceiver { name, host, ports, displays, mac }).await;
             }
             Ok(Ok(_)) | Ok(Err(_)) => {}
             Err(_) => break,
@@ -0,0 +1,152 @@
+//! What a platform plugs into a [`SenderSession`](crate::SenderSession):
+//! a [`Capture`] source, an [`Encoder`] and the [`Platform`] glue that opens
+//! them and touches the rest of the machine (input, power, routes).
+
+use std::future::Future;
+
+use bytes::Bytes;
+use duallink_core::{EncodedFrame, FileTransfers, InputEvent, NetworkKind, StreamConfig};
+use tokio::sync::watch;
+
+use crate::pipeline_log::PipelineLog;
+
+// ── Capture ───────────────────────────────────────────────────────────────────
+
+/// A screen capture source feeding an [`Encoder`].
+pub trait Capture: Send + 'static {
+    /// A raw captured frame.
+    type Frame: Send + 'static;
+
+    /// The next captured frame; `None` once capture has ended.
+    fn next_frame(&mut self) -> impl Future<Output = Option<Self::Frame>> + Send;
+
+    /// Capture at most `fps` frames per second. Returns `false` for sources
+    /// that capture at a fixed rate — the session then drops the frames
+    /// beyond `fps` itself.
+    fn set_max_fps(&mut self, fps: u32) -> bool;
+}
+
+// ── Encoder ───────────────────────────────────────────────────────────────────
+
+/// Counters of frames an [`Encoder`] did not encode, for the status row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeedStats {
+    /// Raw frames discarded because the encoder fell behind.
+    pub dropped:       u64,
+    /// Unchanged frames skipped.
+    pub skipped:       u64,
+    /// Capture frame-rate cap while overloaded (`None` = running at target fps).
+    pub throttled_fps: Option<u32>,
+}
+
+/// Turns raw frames into encoded access units, reconfigurable mid-session.
+pub trait Encoder: Send + 'static {
+    /// The raw frame type it takes, the [`Capture::Frame`] of its source.
+    type Frame: Send + 'static;
+
+    /// Hand a raw frame to the encoder (or to whatever queue is in front of
+    /// it). Errors are logged and the frame is lost.
+    fn push_frame(&mut self, frame: Self::Frame) -> anyhow::Result<()>;
+
+    /// The next encoded access unit; `None` once the encoder has ended.
+    fn next_encoded(&mut self) -> impl Future<Output = Option<EncodedFrame>> + Send;
+
+    fn set_bitrate(&mut self, kbps: u32);
+
+    /// Change the frame rate the encoder is configured for.
+    fn set_fps(&mut self, fps: u32);
+
+    /// Change the keyframe interval, in frames.
+    fn set_gop(&mut self, frames: u32);
+
+    /// Make the next encoded frame a keyframe.
+    fn force_keyframe(&mut self);
+
+    /// Flush and stop; [`next_encoded`](Self::next_encoded) then ends.
+    fn send_eos(&mut self);
+
+    /// Name of the encoder in use (e.g. a GStreamer element), for the UI.
+    fn element_name(&self) -> &str;
+
+    fn is_hardware_accelerated(&self) -> bool;
+
+    /// `true` if the encoder runs in lossless (High 4:4:4) mode.
+    fn is_lossless(&self) -> bool {
+        false
+    }
+
+    /// Drop raw frames still waiting to be encoded (the stream was paused).
+    fn flush(&mut self) {}
+
+    /// Skip unchanged frames while `enabled` (battery saver), on top of any
+    /// skipping the encoder does by itself.
+    fn set_skip_unchanged(&mut self, _enabled: bool) {}
+
+    /// Called once a second; a lower capture rate when encoding cannot keep
+    /// up, `None` to leave it.
+    fn overload_cap(&mut self) -> Option<u32> {
+        None
+    }
+
+    fn feed_stats(&self) -> FeedStats {
+        FeedStats::default()
+    }
+
+    /// Why the encoder ended by itself, if it crashed (a panicking encode
+    /// thread). Once [`next_encoded`](Self::next_encoded) ends, the session
+    /// reopens capture and encoder for a failure instead of stopping.
+    fn failure(&self) -> Option<String> {
+        None
+    }
+}
+
+// ── Platform ──────────────────────────────────────────────────────────────────
+
+/// The platform side of a sender: opens capture and encoder, and reaches the
+/// parts of the machine the session itself cannot.
+pub trait Platform: Send + 'static {
+    type Capture: Capture;
+    type Encoder: Encoder<Frame = <Self::Capture as Capture>::Frame>;
+
+    /// Prefix of session ids and fallback device name, e.g. `linux-sender`.
+    const NAME: &'static str;
+
+    /// Kind of network the route to `host` leaves over. Blocking.
+    fn route_kind(host: &str) -> NetworkKind;
+
+    /// Fill in what the platform asks of the stream before `hello` — colour
+    /// range, lossless, HDR.
+    fn prepare(&mut self, _config: &mut StreamConfig, _log: &PipelineLog) {}
+
+    /// Open capture and the encoder for the negotiated `config`. Capture is
+    /// `None` when the encoder captures by itself (an in-pipeline source).
+    fn open(
+        &mut self,
+        config: &StreamConfig,
+        log: &PipelineLog,
+    ) -> impl Future<Output = anyhow::Result<(Option<Self::Capture>, Self::Encoder)>> + Send;
+
+    /// Whether [`open`](Self::open) will show a permission prompt (a
+    /// screen-cast portal dialog), so the session reports
+    /// [`PipelineState::AwaitingCapture`](crate::PipelineState::AwaitingCapture) meanwhile.
+    fn capture_prompts(&self) -> impl Future<Output = bool> + Send {
+        async { false }
+    }
+
+    /// JPEG thumbnails of what the receiver shows, when the session asked
+    /// for them and the receiver sends them. Called once per session.
+    fn remote_previews(&mut self, _previews: watch::Receiver<Option<Bytes>>) {}
+
+    /// Files to exchange with the receiver, if the platform offers any.
+    /// Asked once per connection; used when the receiver takes files too.
+    fn file_transfers(&mut self) -> Option<FileTransfers> {
+        None
+    }
+
+    /// Inject an input event the receiver forwarded.
+    fn inject(&mut self, event: InputEvent) -> impl Future<Output = ()> + Send;
+
+    /// Keep this machine awake while streaming; released when the returned
+    /// guard drops.
+    fn keep_awake(&mut self) -> impl Future<Output = Option<Box<dyn Send>>> + Send;
+}
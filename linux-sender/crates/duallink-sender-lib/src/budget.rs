@@ -0,0 +1,199 @@
+//! [`BitrateAllocator`] — one bitrate budget shared by every display a
+//! sender streams.
+//!
+//! Three displays at 8000 kbps each saturate many uplinks. With a budget
+//! (`DUALLINK_TOTAL_KBPS`, see [`BitrateAllocator::from_env`]) each
+//! session [joins](BitrateAllocator::join) it and streams at most the share
+//! it is granted:
+//!
+//! - shares are split by resolution — a 4K display weighs four times a
+//!   1080p one;
+//! - each display's weight is scaled by how much of its stream changes
+//!   ([`BudgetShare::set_activity`]), so a static desktop gives bandwidth
+//!   to the display playing video, down to [`IDLE_WEIGHT`] of its own;
+//! - a display that wants less than its share (a low preset, a network
+//!   cap, an idle stream) keeps only what it asks for, and the rest goes to
+//!   the others.
+//!
+//! Sessions re-read their grant once a second and reconfigure the encoder
+//! when it moved by a tenth or more.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Weight a display keeps with nothing changing, relative to a fully
+/// active one of the same size.
+pub const IDLE_WEIGHT: f64 = 0.25;
+
+/// The smallest grant; a budget too small for every display still leaves
+/// each a picture.
+pub const MIN_SHARE_KBPS: u32 = 300;
+
+#[derive(Debug, Clone, Copy)]
+struct Member {
+    pixels:      u64,
+    /// What the session would stream without the budget.
+    demand_kbps: u32,
+    /// Fraction of recent frames that changed the picture, 0–1.
+    activity:    f64,
+}
+
+impl Member {
+    fn weight(&self) -> f64 {
+        self.pixels.max(1) as f64 * (IDLE_WEIGHT + (1.0 - IDLE_WEIGHT) * self.activity)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Budget {
+    total_kbps: u32,
+    next_id:    u64,
+    members:    BTreeMap<u64, Member>,
+    /// Grants as of the last change, by member.
+    grants:     BTreeMap<u64, u32>,
+}
+
+impl Budget {
+    fn reallocate(&mut self) {
+        let members: Vec<Member> = self.members.values().copied().collect();
+        let grants = allocate(self.total_kbps, &members);
+        self.grants = self.members.keys().copied().zip(grants).collect();
+    }
+}
+
+/// Split `total_kbps` between `members` in proportion to their weights,
+/// giving members that want less than their share only what they want
+/// and sharing the rest out again.
+fn allocate(total_kbps: u32, members: &[Member]) -> Vec<u32> {
+    let mut grants = vec![0u32; members.len()];
+    let mut open: Vec<usize> = (0..members.len()).collect();
+    let mut remaining = total_kbps as f64;
+    while !open.is_empty() {
+        let weights: f64 = open.iter().map(|&i| members[i].weight()).sum();
+        let share = |i: usize| remaining * members[i].weight() / weights;
+        let (content, rest): (Vec<usize>, Vec<usize>) =
+            open.iter().partition(|&&i| (members[i].demand_kbps as f64) <= share(i));
+        if content.is_empty() {
+            for &i in &open {
+                grants[i] = share(i) as u32;
+            }
+            break;
+        }
+        for &i in &content {
+            grants[i] = members[i].demand_kbps;
+            remaining -= members[i].demand_kbps as f64;
+        }
+        open = rest;
+    }
+    grants.iter().zip(members).map(|(&g, m)| g.max(MIN_SHARE_KBPS.min(m.demand_kbps))).collect()
+}
+
+/// A bitrate budget shared by the sessions of one sender; clones share it.
+#[derive(Debug, Clone)]
+pub struct BitrateAllocator {
+    budget: Arc<Mutex<Budget>>,
+}
+
+impl BitrateAllocator {
+    pub fn new(total_kbps: u32) -> Self {
+        Self { budget: Arc::new(Mutex::new(Budget { total_kbps, ..Budget::default() })) }
+    }
+
+    /// The budget from `DUALLINK_TOTAL_KBPS`, if set to a positive number.
+    pub fn from_env() -> Option<Self> {
+        let v = std::env::var("DUALLINK_TOTAL_KBPS").ok()?;
+        match v.trim().parse::<u32>() {
+            Ok(kbps) if kbps > 0 => Some(Self::new(kbps)),
+            _ => {
+                tracing::warn!("Ignoring DUALLINK_TOTAL_KBPS='{v}' — expected kbps");
+                None
+            }
+        }
+    }
+
+    pub fn total_kbps(&self) -> u32 {
+        self.budget.lock().unwrap().total_kbps
+    }
+
+    /// Add a display of `pixels` wanting `demand_kbps`, active until told
+    /// otherwise. It leaves the budget when the share is dropped.
+    pub fn join(&self, pixels: u64, demand_kbps: u32) -> BudgetShare {
+        let mut budget = self.budget.lock().unwrap();
+        let id = budget.next_id;
+        budget.next_id += 1;
+        budget.members.insert(id, Member { pixels, demand_kbps, activity: 1.0 });
+        budget.reallocate();
+        BudgetShare { id, allocator: self.clone() }
+    }
+
+    fn update(&self, id: u64, change: impl FnOnce(&mut Member)) -> u32 {
+        let mut budget = self.budget.lock().unwrap();
+        if let Some(member) = budget.members.get_mut(&id) {
+            change(member);
+            budget.reallocate();
+        }
+        budget.grants.get(&id).copied().unwrap_or(0)
+    }
+}
+
+/// One display's place in a [`BitrateAllocator`].
+#[derive(Debug)]
+pub struct BudgetShare {
+    id:        u64,
+    allocator: BitrateAllocator,
+}
+
+impl BudgetShare {
+    /// Record that the display wants `demand_kbps`; returns what it may
+    /// stream now.
+    pub fn request(&self, demand_kbps: u32) -> u32 {
+        self.allocator.update(self.id, |m| m.demand_kbps = demand_kbps)
+    }
+
+    /// Record the fraction of recent frames that changed the picture (0–1).
+    pub fn set_activity(&self, activity: f64) {
+        self.allocator.update(self.id, |m| m.activity = activity.clamp(0.0, 1.0));
+    }
+
+    /// The display's grant as of the other displays' latest reports.
+    pub fn granted(&self) -> u32 {
+        self.allocator.budget.lock().unwrap().grants.get(&self.id).copied().unwrap_or(0)
+    }
+}
+
+impl Drop for BudgetShare {
+    fn drop(&mut self) {
+        let mut budget = self.allocator.budget.lock().unwrap();
+        budget.members.remove(&self.id);
+        budget.reallocate();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HD: u64 = 1920 * 1080;
+
+    #[test]
+    fn shares_follow_size_activity_and_demand() {
+        let budget = BitrateAllocator::new(12_000);
+        let uhd = budget.join(4 * HD, 20_000);
+        let hd = budget.join(HD, 8_000);
+        assert_eq!((uhd.granted(), hd.granted()), (9_600, 2_400));
+
+        // The 4K desktop goes static and weighs no more than the 1080p video.
+        uhd.set_activity(0.0);
+        assert_eq!((uhd.granted(), hd.granted()), (6_000, 6_000));
+
+        // An idle stream asks for little; the rest goes to the other.
+        assert_eq!(hd.request(500), 500);
+        assert_eq!(uhd.granted(), 11_500);
+
+        drop(hd);
+        assert_eq!(uhd.granted(), 12_000);
+        let tight = BitrateAllocator::new(400);
+        let shares: Vec<_> = (0..3).map(|_| tight.join(HD, 8_000)).collect();
+        assert!(shares.iter().all(|s| s.granted() == MIN_SHARE_KBPS));
+    }
+}
@@ -0,0 +1,147 @@
+//! Pipeline construction from elements.
+//!
+//! Encode pipelines are put together from `ElementFactory::make` builders
+//! with typed properties instead of `parse::launch` strings: a missing
+//! plugin or a misspelt property fails where the element is made, naming
+//! it, and sizes or element names are never spliced into a description.
+//!
+//! [`add_chain`] links each element to the next. An element whose source
+//! pad only appears once data flows (a *sometimes* pad) is linked from its
+//! `pad-added` signal instead.
+//!
+//! [`test_source`] builds the `--test-pattern` source (see
+//! [`duallink_core::test_pattern`]) as one bin that stands in for capture:
+//!
+//! ```text
+//! videotestsrc (pattern) ─┐
+//!                         ├→ compositor → timeoverlay → videoconvert → videorate → testcaps
+//! videotestsrc (ball) ────┘
+//! ```
+
+use anyhow::Context;
+use gstreamer::prelude::*;
+use tracing::{debug, warn};
+
+/// An element from `factory`.
+pub fn make(factory: &str) -> anyhow::Result<gstreamer::Element> {
+    gstreamer::ElementFactory::make(factory)
+        .build()
+        .with_context(|| format!("GStreamer element '{factory}' is not available"))
+}
+
+/// An element from `factory` named `name`, to be found again with `by_name`.
+pub fn make_named(factory: &str, name: &str) -> anyhow::Result<gstreamer::Element> {
+    gstreamer::ElementFactory::make(factory)
+        .name(name)
+        .build()
+        .with_context(|| format!("GStreamer element '{factory}' is not available"))
+}
+
+/// A `capsfilter` for `caps`.
+pub fn caps_filter(caps: &gstreamer::Caps) -> anyhow::Result<gstreamer::Element> {
+    let filter = make("capsfilter")?;
+    filter.set_property("caps", caps);
+    Ok(filter)
+}
+
+/// A `queue` holding at most `buffers` buffers (no byte / time limit),
+/// dropping the oldest when full if `leaky`.
+pub fn queue(buffers: u32, leaky: bool) -> anyhow::Result<gstreamer::Element> {
+    let queue = make("queue")?;
+    queue.set_property("max-size-buffers", buffers);
+    queue.set_property("max-size-bytes", 0u32);
+    queue.set_property("max-size-time", 0u64);
+    if leaky {
+        queue.set_property_from_str("leaky", "downstream");
+    }
+    Ok(queue)
+}
+
+/// Add `elements` to `pipeline` and link them in order.
+pub fn add_chain(pipeline: &gstreamer::Pipeline, elements: &[&gstreamer::Element]) -> anyhow::Result<()> {
+    for element in elements {
+        pipeline.add(*element).with_context(|| format!("Adding {}", element.name()))?;
+    }
+    link_chain(elements)
+}
+
+/// Link each of `elements` to the next.
+pub fn link_chain(elements: &[&gstreamer::Element]) -> anyhow::Result<()> {
+    for pair in elements.windows(2) {
+        link_or_defer(pair[0], pair[1])?;
+    }
+    Ok(())
+}
+
+/// Link `src` to `sink` now, or — if `src` only has sometimes source pads —
+/// as soon as it adds one.
+fn link_or_defer(src: &gstreamer::Element, sink: &gstreamer::Element) -> anyhow::Result<()> {
+    if src.link(sink).is_ok() {
+        return Ok(());
+    }
+    let sometimes = src.pad_template_list().iter().any(|t| {
+        t.direction() == gstreamer::PadDirection::Src && t.presence() == gstreamer::PadPresence::Sometimes
+    });
+    anyhow::ensure!(sometimes, "Cannot link {} to {}", src.name(), sink.name());
+    debug!("{} has no source pad yet — linking to {} on pad-added", src.name(), sink.name());
+    let sink = sink.downgrade();
+    src.connect_pad_added(move |src, pad| {
+        let Some(sink_pad) = sink.upgrade().and_then(|s| s.static_pad("sink")) else { return };
+        if sink_pad.is_linked() {
+            return;
+        }
+        if let Err(e) = pad.link(&sink_pad) {
+            warn!("Linking {}:{} on pad-added: {:?}", src.name(), pad.name(), e);
+        }
+    });
+    Ok(())
+}
+
+/// A live bin of `pattern` at `width`×`height` and `fps`, BGRx, with a
+/// moving ball over it and the running time burned in. Its output caps
+/// filter is named `testcaps`; a lower frame rate set there drops frames.
+pub fn test_source(pattern: &str, width: u32, height: u32, fps: u32) -> anyhow::Result<gstreamer::Element> {
+    let caps = |format: &str| {
+        gstreamer::Caps::builder("video/x-raw")
+            .field("format", format)
+            .field("width", width as i32)
+            .field("height", height as i32)
+            .field("framerate", gstreamer::Fraction::new(fps as i32, 1))
+            .build()
+    };
+    let layer = |pattern: &str| -> anyhow::Result<gstreamer::Element> {
+        let source = make("videotestsrc")?;
+        source.set_property("is-live", true);
+        source.set_property_from_str("pattern", pattern);
+        Ok(source)
+    };
+    let bars = layer(pattern)?;
+    let ball = layer("ball")?;
+    // Transparent around the ball, so the bars show through.
+    ball.set_property("background-color", 0u32);
+    let mixer = make("compositor")?;
+    let clock = make("timeoverlay")?;
+    clock.set_property_from_str("time-mode", "running-time");
+    clock.set_property("font-desc", "Monospace 32");
+    let rate = make("videorate")?;
+    rate.set_property("drop-only", true);
+    let out = make_named("capsfilter", "testcaps")?;
+    out.set_property("caps", caps("BGRx"));
+    let (bars_caps, ball_caps) = (caps_filter(&caps("AYUV"))?, caps_filter(&caps("AYUV"))?);
+
+    let convert = make("videoconvert")?;
+
+    let bin = gstreamer::Bin::new();
+    let chain = [&mixer, &clock, &convert, &rate, &out];
+    for element in [&bars, &bars_caps, &ball, &ball_caps].into_iter().chain(chain) {
+        bin.add(element).with_context(|| format!("Adding {}", element.name()))?;
+    }
+    // The compositor hands out a sink pad per link; the first is drawn first.
+    link_chain(&[&bars, &bars_caps, &mixer])?;
+    link_chain(&[&ball, &ball_caps, &mixer])?;
+    link_chain(&chain)?;
+    let src = out.static_pad("src").context("No testcaps src pad")?;
+    let ghost = gstreamer::GhostPad::builder_with_target(&src)?.name("src").build();
+    bin.add_pad(&ghost).context("Adding test source pad")?;
+    Ok(bin.upcast())
+}
@@ -0,0 +1,106 @@
+//! One display's capture → encode → send session, shared by the Linux and
+//! Windows senders and usable by third-party senders.
+//!
+//! ```text
+//! Capture ──▶ Encoder ──▶ VideoSender (UDP:7878+2n)
+//!                          SignalingClient (TLS:7879+2n)
+//! ```
+//!
+//! A [`SenderSession`] owns everything that is the same on every platform:
+//! the signaling handshake and feature negotiation, the UDP video sender,
+//! keepalives, rate control and the status reported to the UI. A platform
+//! plugs in through [`Platform`], which opens a [`Capture`] source and an
+//! [`Encoder`] for the negotiated stream and injects the receiver's input.
+//!
+//! # Blanking and pausing
+//!
+//! While the receiver asks for a capture pause (`blank`), captured frames
+//! are dropped before the encoder and nothing is sent; the stream resumes
+//! with a forced keyframe. [`SenderSession::set_remote_blank`] blanks the
+//! receiver's display the other way round. [`SenderSession::set_paused`]
+//! pauses this display alone the same way, and tells receivers with
+//! `CAP_DISPLAY_STATE` so they can show it — or pause it themselves, which
+//! [`PipelineStatus::display_paused`] reports back.
+//!
+//! # Rates
+//!
+//! Every rate change — presets, [`SenderSession::set_bitrate`] and
+//! [`SenderSession::set_fps`], the per-network caps of
+//! [`SessionConfig::network_caps`], the receiver's fps requests and battery
+//! saver — reconfigures the encoder and capture in place and sends the
+//! receiver a `config_update`. Capture that cannot lower its own rate
+//! (see [`Capture::set_max_fps`]) has the extra frames dropped by the
+//! session.
+//!
+//! Sessions of one sender can share a [`BitrateAllocator`]
+//! ([`SessionConfig::budget`]): the bitrate each streams is then capped by
+//! its share of one total, split by resolution and moved towards the
+//! displays whose screens change.
+//!
+//! # Battery saver
+//!
+//! The session re-reads this machine's battery and tells receivers with
+//! `CAP_POWER` about it; they report theirs back. While either end wants
+//! saver the stream runs on `QualityPreset::BatterySaver` with unchanged
+//! frames skipped, and goes back to the configured preset afterwards.
+//!
+//! # Capture watchdog
+//!
+//! Capture can stop delivering frames without ending — a compositor that
+//! lost the screen-cast stream, a GPU reset. When nothing was captured for
+//! [`SessionConfig::capture_stall`] (`DUALLINK_CAPTURE_STALL_SECS`, see
+//! [`configured_capture_stall`]) while the stream is neither paused nor
+//! blanked, the session reopens capture and encoder through the
+//! [`Platform`] and carries on with a keyframe. An encoder that ends by
+//! crashing ([`Encoder::failure`], e.g. a panicking encode thread) is
+//! reopened the same way, a few times per session.
+//!
+//! # Idle streams
+//!
+//! Input forwarded by the receiver and encoded frames go through a
+//! [`duallink_core::IdleDetector`]. After [`SessionConfig::idle_after`]
+//! (`DUALLINK_IDLE_SECS`) with neither input nor a changed screen, the
+//! stream drops to 5 fps and a low bitrate on top of any other cap; the
+//! next input event or screen change restores the full rate at once.
+//!
+//! # Status
+//!
+//! [`SenderSession::spawn`] takes a [`PipelineStatus`] channel the UI polls
+//! for live FPS, frame counts and connection state. Connection attempts,
+//! errors and other events go to the session's [`PipelineLog`], which
+//! outlives the task so the UI can show why a session failed.
+//!
+//! # Encoders
+//!
+//! [`Encoder`] is the extension point for codecs: a platform's GStreamer
+//! pipeline implements it, and with the `openh264` feature
+//! [`OpenH264Encoder`] encodes [`RawFrame`]s in software, so a sender can
+//! run without any GStreamer encoder plugin installed.
+//!
+//! With the `gstreamer` feature, [`elements`] builds GStreamer pipelines
+//! element by element and [`preview`] tees a pipeline's input into the
+//! UI's thumbnails — the parts of the senders' GStreamer encoders that
+//! don't depend on the platform.
+
+mod backend;
+mod budget;
+#[cfg(feature = "gstreamer")]
+pub mod elements;
+pub mod pipeline_log;
+#[cfg(feature = "gstreamer")]
+pub mod preview;
+mod raw;
+mod session;
+#[cfg(feature = "openh264")]
+mod software;
+
+pub use backend::{Capture, Encoder, FeedStats, Platform};
+pub use budget::{BitrateAllocator, BudgetShare, IDLE_WEIGHT, MIN_SHARE_KBPS};
+pub use pipeline_log::PipelineLog;
+pub use raw::{RawFormat, RawFrame};
+pub use session::{
+    configured_capture_stall, PipelineControl, PipelineState, PipelineStatus, SenderSession, SessionConfig,
+    CAPTURE_STALL_TIMEOUT, CUSTOM_GOP,
+};
+#[cfg(feature = "openh264")]
+pub use software::OpenH264Encoder;
@@ -0,0 +1,105 @@
+//! Per-pipeline event log for the sender UI.
+//!
+//! Each [`SenderSession`](crate::SenderSession) records connect
+//! attempts, the encoder it picked, send errors and keepalive results in a
+//! bounded [`PipelineLog`]. The UI keeps a clone of the handle, so a failed
+//! pipeline's history stays readable after its task has exited.
+//!
+//! Every entry is also emitted through `tracing` with the usual
+//! `Display[n]` prefix, so the terminal log is unchanged.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Entries kept per pipeline; the oldest are dropped first.
+pub const LOG_CAPACITY: usize = 200;
+
+/// Severity of a [`LogEntry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+/// One line in a pipeline log.
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    /// Time since the pipeline was spawned (of the latest repeat).
+    pub at:      Duration,
+    pub level:   LogLevel,
+    pub message: String,
+    /// How many times this message was logged back to back (≥ 1).
+    pub repeats: u32,
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{:>7.1}s] {}", self.at.as_secs_f32(), self.message)?;
+        if self.repeats > 1 {
+            write!(f, " (×{})", self.repeats)?;
+        }
+        Ok(())
+    }
+}
+
+/// Shared ring buffer of [`LogEntry`]s for one display pipeline.
+#[derive(Debug, Clone)]
+pub struct PipelineLog {
+    display_index: u8,
+    started:       Instant,
+    entries:       Arc<Mutex<VecDeque<LogEntry>>>,
+}
+
+impl PipelineLog {
+    pub fn new(display_index: u8) -> Self {
+        Self {
+            display_index,
+            started: Instant::now(),
+            entries: Arc::new(Mutex::new(VecDeque::with_capacity(LOG_CAPACITY))),
+        }
+    }
+
+    pub fn info(&self, message: impl Into<String>) {
+        let message = message.into();
+        tracing::info!("Display[{}] {}", self.display_index, message);
+        self.push(LogLevel::Info, message);
+    }
+
+    pub fn warn(&self, message: impl Into<String>) {
+        let message = message.into();
+        tracing::warn!("Display[{}] {}", self.display_index, message);
+        self.push(LogLevel::Warn, message);
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        let message = message.into();
+        tracing::error!("Display[{}] {}", self.display_index, message);
+        self.push(LogLevel::Error, message);
+    }
+
+    /// Snapshot of the current entries, oldest first.
+    pub fn entries(&self) -> Vec<LogEntry> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Consecutive identical messages (e.g. a send error every frame) are
+    /// folded into one entry so they cannot flush the history.
+    fn push(&self, level: LogLevel, message: String) {
+        let at = self.started.elapsed();
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(last) = entries.back_mut() {
+            if last.level == level && last.message == message {
+                last.repeats += 1;
+                last.at = at;
+                return;
+            }
+        }
+        if entries.len() >= LOG_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(LogEntry { at, level, message, repeats: 1 });
+    }
+}
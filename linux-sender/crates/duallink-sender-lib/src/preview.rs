@@ -0,0 +1,154 @@
+//! Low-rate thumbnails of what a pipeline sends, for the sender UI.
+//!
+//! Every encode pipeline tees its raw input into a preview branch that
+//! ends in a second appsink:
+//!
+//! ```text
+//! <source> → tee ─→ queue → videoconvert → <encoder> → … → appsink name=sink
+//!                └→ queue (leaky) → videorate max-rate=1 → videoscale
+//!                     → videoconvert → RGBA PREVIEW_WIDTH wide → appsink name=preview
+//! ```
+//!
+//! Frames are thinned to 1 fps before they are scaled, and the leaky queue
+//! drops rather than ever holding back the encoder. Each thumbnail lands in
+//! a [`PreviewSlot`] shared with the UI, which re-uploads its texture when
+//! the [`Thumbnail::generation`] changes.
+//!
+//! JPEG thumbnails the receiver sends of what it shows (`preview`) are
+//! decoded with [`decode_jpeg`] into a second slot per pipeline.
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use bytes::Bytes;
+use gstreamer::prelude::*;
+use gstreamer_app::{AppSink, AppSinkCallbacks};
+
+use crate::elements::{self, caps_filter, make};
+
+/// Thumbnail width in pixels; the height follows the stream's aspect ratio.
+pub const PREVIEW_WIDTH: u32 = 192;
+
+/// Add to `pipeline` the `tee` that goes right after the source, with the
+/// preview branch for a `width`×`height` stream on one side. Returns the
+/// tee (to link the source to) and the queue on its other side (for the
+/// encoder chain to continue from).
+pub fn split(
+    pipeline: &gstreamer::Pipeline,
+    width: u32,
+    height: u32,
+) -> anyhow::Result<(gstreamer::Element, gstreamer::Element)> {
+    let thumb_height = (PREVIEW_WIDTH * height / width.max(1)).max(2) & !1;
+    let tee = make("tee")?;
+    let main = elements::queue(2, false)?;
+    let thumb = elements::queue(1, true)?;
+    let rate = make("videorate")?;
+    rate.set_property("max-rate", 1i32);
+    rate.set_property("drop-only", true);
+    let rgba = caps_filter(
+        &gstreamer::Caps::builder("video/x-raw")
+            .field("format", "RGBA")
+            .field("width", PREVIEW_WIDTH as i32)
+            .field("height", thumb_height as i32)
+            .field("pixel-aspect-ratio", gstreamer::Fraction::new(1, 1))
+            .build(),
+    )?;
+    let sink = AppSink::builder().name("preview").max_buffers(1).drop(true).sync(false).build();
+
+    elements::add_chain(pipeline, &[&tee, &main])?;
+    elements::add_chain(
+        pipeline,
+        &[&thumb, &rate, &make("videoscale")?, &make("videoconvert")?, &rgba, sink.upcast_ref::<gstreamer::Element>()],
+    )?;
+    elements::link_chain(&[&tee, &thumb])?;
+    Ok((tee, main))
+}
+
+// ── Thumbnail ─────────────────────────────────────────────────────────────────
+
+/// One preview frame, tightly packed RGBA.
+#[derive(Debug, Clone)]
+pub struct Thumbnail {
+    /// Increases with every frame published to the same slot.
+    pub generation: u64,
+    pub width:      u32,
+    pub height:     u32,
+    pub rgba:       Bytes,
+}
+
+/// Latest [`Thumbnail`] of one pipeline, shared between its encoder and
+/// the UI. Cloning shares the slot.
+#[derive(Debug, Clone, Default)]
+pub struct PreviewSlot(Arc<Mutex<Option<Thumbnail>>>);
+
+impl PreviewSlot {
+    /// Replace the latest thumbnail.
+    pub fn publish(&self, width: u32, height: u32, rgba: Bytes) {
+        let mut latest = self.0.lock().unwrap();
+        let generation = latest.as_ref().map_or(1, |t| t.generation + 1);
+        *latest = Some(Thumbnail { generation, width, height, rgba });
+    }
+
+    /// The latest thumbnail, if it is newer than `generation`.
+    pub fn newer_than(&self, generation: u64) -> Option<Thumbnail> {
+        self.0.lock().unwrap().as_ref().filter(|t| t.generation > generation).cloned()
+    }
+}
+
+/// Feed the `preview` appsink of `pipeline` into `slot`.
+pub fn attach(pipeline: &gstreamer::Pipeline, slot: PreviewSlot) -> anyhow::Result<()> {
+    let appsink: AppSink = pipeline
+        .by_name("preview")
+        .context("Finding appsink 'preview'")?
+        .downcast::<AppSink>()
+        .map_err(|_| anyhow::anyhow!("Expected AppSink"))?;
+
+    appsink.set_callbacks(
+        AppSinkCallbacks::builder()
+            .new_sample(move |sink| {
+                let sample = sink.pull_sample().map_err(|_| gstreamer::FlowError::Eos)?;
+                let (width, height, rgba) = packed_rgba(&sample)?;
+                slot.publish(width, height, rgba);
+                Ok(gstreamer::FlowSuccess::Ok)
+            })
+            .build(),
+    );
+    Ok(())
+}
+
+/// Decode a JPEG (a receiver `preview`) to packed RGBA. Blocking.
+pub fn decode_jpeg(jpeg: &[u8]) -> anyhow::Result<(u32, u32, Bytes)> {
+    let caps = gstreamer::Caps::builder("image/jpeg").build();
+    let sample = gstreamer::Sample::builder()
+        .buffer(&gstreamer::Buffer::from_slice(jpeg.to_vec()))
+        .caps(&caps)
+        .build();
+    let rgba_caps = gstreamer::Caps::builder("video/x-raw").field("format", "RGBA").build();
+    let decoded = gstreamer_video::convert_sample(&sample, &rgba_caps, gstreamer::ClockTime::from_seconds(1))
+        .context("Decoding receiver preview")?;
+    packed_rgba(&decoded).map_err(|e| anyhow::anyhow!("Unusable receiver preview: {e:?}"))
+}
+
+/// Size and tightly packed pixels of an RGBA sample; rows may be padded,
+/// the UI wants them packed.
+fn packed_rgba(sample: &gstreamer::Sample) -> Result<(u32, u32, Bytes), gstreamer::FlowError> {
+    let info = sample
+        .caps()
+        .and_then(|caps| gstreamer_video::VideoInfo::from_caps(caps).ok())
+        .ok_or(gstreamer::FlowError::NotNegotiated)?;
+    let buffer = sample.buffer().ok_or(gstreamer::FlowError::Error)?;
+    let map = buffer.map_readable().map_err(|_| gstreamer::FlowError::Error)?;
+
+    let (width, height) = (info.width(), info.height());
+    let row = width as usize * 4;
+    let stride = info.stride()[0] as usize;
+    let rgba = if stride == row {
+        Bytes::copy_from_slice(map.get(..row * height as usize).ok_or(gstreamer::FlowError::Error)?)
+    } else {
+        map.chunks(stride).take(height as usize).flat_map(|r| &r[..row.min(r.len())]).copied().collect()
+    };
+    if rgba.len() != row * height as usize {
+        return Err(gstreamer::FlowError::Error);
+    }
+    Ok((width, height, rgba))
+}
@@ -0,0 +1,182 @@
+//! Owned raw frames for encoders outside GStreamer, and their conversion to
+//! the planar I420 that software encoders take.
+
+use duallink_core::{ColorMatrix, ColorRange, ColorSpace};
+
+/// Pixel layout of a [`RawFrame`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawFormat {
+    /// 4 bytes per pixel: Blue, Green, Red, unused.
+    Bgrx,
+    /// Y plane followed by an interleaved UV plane at half resolution.
+    Nv12,
+}
+
+/// A captured frame, tightly packed (no row padding).
+#[derive(Debug, Clone)]
+pub struct RawFrame {
+    pub data:   Vec<u8>,
+    pub format: RawFormat,
+    pub width:  u32,
+    pub height: u32,
+    /// Presentation timestamp in milliseconds.
+    pub pts_ms: u64,
+}
+
+impl RawFrame {
+    /// The frame as I420 — Y, then U, then V, chroma at half resolution
+    /// (rounded up). BGRx is converted with `color`'s matrix and range;
+    /// NV12 is assumed to be in `color` already and only de-interleaved.
+    ///
+    /// `None` if `data` is shorter than the format and size call for.
+    pub fn to_i420(&self, color: ColorSpace) -> Option<Vec<u8>> {
+        let (w, h) = (self.width as usize, self.height as usize);
+        let (cw, ch) = (w.div_ceil(2), h.div_ceil(2));
+        let mut out = vec![0u8; w * h + 2 * cw * ch];
+        let (y_plane, chroma) = out.split_at_mut(w * h);
+        let (u_plane, v_plane) = chroma.split_at_mut(cw * ch);
+        match self.format {
+            RawFormat::Bgrx => {
+                let src = self.data.get(..w * h * 4)?;
+                let k = Coeffs::new(color);
+                for (dst, row) in y_plane.chunks_exact_mut(w).zip(src.chunks_exact(w * 4)) {
+                    for (y, px) in dst.iter_mut().zip(row.chunks_exact(4)) {
+                        *y = k.luma(px[2].into(), px[1].into(), px[0].into());
+                    }
+                }
+                for cy in 0..ch {
+                    for cx in 0..cw {
+                        // Average the 2×2 block, clamped at odd right / bottom edges.
+                        let (mut r, mut g, mut b) = (0, 0, 0);
+                        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                            let x = (cx * 2 + dx).min(w - 1);
+                            let y = (cy * 2 + dy).min(h - 1);
+                            let px = &src[(y * w + x) * 4..];
+                            (r, g, b) = (r + i32::from(px[2]), g + i32::from(px[1]), b + i32::from(px[0]));
+                        }
+                        let (u, v) = k.chroma((r + 2) / 4, (g + 2) / 4, (b + 2) / 4);
+                        u_plane[cy * cw + cx] = u;
+                        v_plane[cy * cw + cx] = v;
+                    }
+                }
+            }
+            RawFormat::Nv12 => {
+                y_plane.copy_from_slice(self.data.get(..w * h)?);
+                let uv = self.data.get(w * h..w * h + 2 * cw * ch)?;
+                for ((u, v), pair) in u_plane.iter_mut().zip(v_plane.iter_mut()).zip(uv.chunks_exact(2)) {
+                    (*u, *v) = (pair[0], pair[1]);
+                }
+            }
+        }
+        Some(out)
+    }
+}
+
+/// RGB → Y'CbCr in 16.16 fixed point, with the range offsets folded in.
+struct Coeffs {
+    y:     [i32; 3],
+    u:     [i32; 3],
+    v:     [i32; 3],
+    y_off: i32,
+}
+
+impl Coeffs {
+    fn new(color: ColorSpace) -> Self {
+        let (kr, kb) = match color.matrix {
+            ColorMatrix::Bt709 => (0.2126, 0.0722),
+            ColorMatrix::Bt601 => (0.299, 0.114),
+        };
+        let kg = 1.0 - kr - kb;
+        let (y_scale, c_scale, y_off) = match color.range {
+            ColorRange::Limited => (219.0 / 255.0, 224.0 / 255.0, 16),
+            ColorRange::Full => (1.0, 1.0, 0),
+        };
+        let fixed = |c: f64| (c * 65536.0).round() as i32;
+        let row = |r: f64, g: f64, b: f64, scale: f64| [fixed(r * scale), fixed(g * scale), fixed(b * scale)];
+        let cb = 2.0 * (1.0 - kb);
+        let cr = 2.0 * (1.0 - kr);
+        Self {
+            y: row(kr, kg, kb, y_scale),
+            u: row(-kr / cb, -kg / cb, 0.5, c_scale),
+            v: row(0.5, -kg / cr, -kb / cr, c_scale),
+            y_off,
+        }
+    }
+
+    fn luma(&self, r: i32, g: i32, b: i32) -> u8 {
+        Self::apply(self.y, r, g, b, self.y_off)
+    }
+
+    fn chroma(&self, r: i32, g: i32, b: i32) -> (u8, u8) {
+        (Self::apply(self.u, r, g, b, 128), Self::apply(self.v, r, g, b, 128))
+    }
+
+    fn apply(k: [i32; 3], r: i32, g: i32, b: i32, offset: i32) -> u8 {
+        let sum = k[0] * r + k[1] * g + k[2] * b + (1 << 15);
+        ((sum >> 16) + offset).clamp(0, 255) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bgrx(width: u32, height: u32, px: [u8; 4]) -> RawFrame {
+        RawFrame {
+            data:   px.repeat((width * height) as usize),
+            format: RawFormat::Bgrx,
+            width,
+            height,
+            pts_ms: 0,
+        }
+    }
+
+    #[test]
+    fn limited_range_black_and_white() {
+        let color = ColorSpace { range: ColorRange::Limited, matrix: ColorMatrix::Bt709 };
+        let black = bgrx(4, 2, [0, 0, 0, 0xff]).to_i420(color).unwrap();
+        assert_eq!(black.len(), 4 * 2 + 2 * 2);
+        assert_eq!(black[..8], [16; 8]);
+        assert_eq!(black[8..], [128; 4]);
+        let white = bgrx(4, 2, [0xff; 4]).to_i420(color).unwrap();
+        assert_eq!(white[..8], [235; 8]);
+        assert_eq!(white[8..], [128; 4]);
+    }
+
+    #[test]
+    fn full_range_primaries() {
+        let color = ColorSpace { range: ColorRange::Full, matrix: ColorMatrix::Bt601 };
+        // Pure blue: Y = 0.114 × 255, Cb at the top of its range.
+        let blue = bgrx(2, 2, [0xff, 0, 0, 0xff]).to_i420(color).unwrap();
+        assert_eq!(blue, [29, 29, 29, 29, 255, 107]);
+        // Pure red: Cr at the top of its range.
+        let red = bgrx(2, 2, [0, 0, 0xff, 0xff]).to_i420(color).unwrap();
+        assert_eq!(red, [76, 76, 76, 76, 85, 255]);
+    }
+
+    #[test]
+    fn odd_sizes_round_chroma_up() {
+        let frame = bgrx(3, 3, [0, 0, 0, 0xff]).to_i420(ColorSpace::default()).unwrap();
+        assert_eq!(frame.len(), 9 + 2 * 4);
+    }
+
+    #[test]
+    fn nv12_is_deinterleaved() {
+        let frame = RawFrame {
+            data:   vec![1, 2, 3, 4, 5, 6, 7, 8, 10, 20, 30, 40],
+            format: RawFormat::Nv12,
+            width:  4,
+            height: 2,
+            pts_ms: 0,
+        };
+        let i420 = frame.to_i420(ColorSpace::default()).unwrap();
+        assert_eq!(i420, [1, 2, 3, 4, 5, 6, 7, 8, 10, 30, 20, 40]);
+    }
+
+    #[test]
+    fn short_data_is_rejected() {
+        let mut frame = bgrx(4, 4, [0; 4]);
+        frame.data.pop();
+        assert!(frame.to_i420(ColorSpace::default()).is_none());
+    }
+}
@@ -0,0 +1,1079 @@
+//! [`SenderSession`] — one display's signaling, rate control and status
+//! around a [`Platform`]'s capture and encoder.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use duallink_core::{
+    configured_slice_decode, read_power, FrameSample, IdleDetector, IntervalMeter, LatencyMode, LinkQuality,
+    MonitorInfo, NetworkKind, NetworkPolicy, PowerState, QualityPreset, Resolution, SenderQueues, SessionEvent,
+    StatsSink, StatsSinks, StreamConfig, StreamLimits, CAP_BLANK, CAP_DISPLAY_STATE, CAP_DLNK_V2, CAP_POWER,
+    CAP_PREVIEW, CHANGED_FRAME_BYTES, DEFAULT_IDLE_AFTER, FILE_CHUNK_INTERVAL, IDLE_FPS, POWER_POLL_INTERVAL,
+    ROUTE_POLL_INTERVAL,
+};
+use duallink_transport_client::{signaling_port, PortMap, SignalingClient, VideoSender};
+use tokio::sync::{mpsc, watch};
+
+use crate::backend::{Capture, Encoder, FeedStats, Platform};
+use crate::budget::BitrateAllocator;
+use crate::pipeline_log::PipelineLog;
+
+/// Keyframe interval (frames) of custom rates, i.e. without a preset.
+pub const CUSTOM_GOP: u32 = 60;
+
+/// Longest capture may deliver nothing before the session reopens it,
+/// unless configured otherwise (see [`configured_capture_stall`]).
+pub const CAPTURE_STALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// [`SessionConfig::capture_stall`] from `DUALLINK_CAPTURE_STALL_SECS`, else
+/// [`CAPTURE_STALL_TIMEOUT`]. Zero turns the watchdog off.
+pub fn configured_capture_stall() -> Duration {
+    std::env::var("DUALLINK_CAPTURE_STALL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map_or(CAPTURE_STALL_TIMEOUT, Duration::from_secs)
+}
+
+/// Crashed encoders a session reopens before it fails.
+const MAX_ENCODER_RESTARTS: u32 = 3;
+
+// ── Configuration ─────────────────────────────────────────────────────────────
+
+/// Configuration of one display's session; platform settings (capture
+/// mode, colour, HDR, …) stay with the [`Platform`].
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    // Network
+    pub host:          String,
+    pub pairing_pin:   String,
+    pub display_index: u8,
+    /// Receiver ports from mDNS discovery (empty = default layout). The
+    /// video port is then taken from `hello_ack` when the receiver sends one.
+    pub ports:         PortMap,
+    // Video
+    pub width:         u32,
+    pub height:        u32,
+    pub fps:           u32,
+    pub bitrate_kbps:  u32,
+    /// Quality preset the fps/bitrate above were taken from (`None` = custom).
+    /// Follows presets applied mid-session.
+    pub preset:        Option<QualityPreset>,
+    /// Latency / quality trade-off to ask the receiver for. `bitrate_kbps`
+    /// is scaled for it once the receiver accepts.
+    pub latency_mode:  LatencyMode,
+    /// The encoder skips unchanged frames; tell the receiver when the
+    /// effective rate moves.
+    pub adaptive_fps:  bool,
+    /// Ask the receiver for thumbnails of what it shows.
+    pub remote_preview: bool,
+    /// Bitrate / fps caps by the kind of network the receiver is reached over.
+    pub network_caps:  NetworkPolicy,
+    /// Capture and encoder are reopened after this long without a frame
+    /// while streaming (`ZERO` = never).
+    pub capture_stall: Duration,
+    /// The stream drops to the idle rate after this long without input or
+    /// screen changes (`ZERO` = never; see [`duallink_core::idle`]).
+    pub idle_after:    Duration,
+    /// Stream at the receiver panel's resolution instead of `width` ×
+    /// `height`, reopening capture and encoder when it reports another panel.
+    pub match_receiver_resolution: bool,
+    /// Queue depths between capture, encoder, network and input (see
+    /// [`duallink_core::queues`]); the platform sizes its own stages.
+    pub queues:        SenderQueues,
+    /// Bitrate budget shared with the sender's other displays (`None` =
+    /// each streams at its own rate).
+    pub budget:        Option<BitrateAllocator>,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            host:          "192.168.1.100".to_owned(),
+            pairing_pin:   "000000".to_owned(),
+            display_index: 0,
+            ports:         PortMap::default(),
+            width:         1920,
+            height:        1080,
+            fps:           60,
+            bitrate_kbps:  8000,
+            preset:        None,
+            latency_mode:  LatencyMode::UltraLow,
+            adaptive_fps:  false,
+            remote_preview: false,
+            network_caps:  NetworkPolicy::default(),
+            capture_stall: CAPTURE_STALL_TIMEOUT,
+            idle_after:    DEFAULT_IDLE_AFTER,
+            match_receiver_resolution: false,
+            queues:        SenderQueues::default(),
+            budget:        None,
+        }
+    }
+}
+
+/// Mid-session commands sent from the UI to a running session.
+#[derive(Debug, Clone)]
+pub enum PipelineControl {
+    /// Switch quality preset without restarting the session.
+    ApplyPreset(QualityPreset),
+    /// Blank (`true`) or show again the receiver's display.
+    Blank(bool),
+    /// Switch to a custom bitrate (kbps) without restarting.
+    SetBitrate(u32),
+    /// Switch to a custom frame rate without restarting; capped at the rate
+    /// capture was opened with.
+    SetFps(u32),
+    /// Send a keyframe now.
+    ForceKeyframe,
+    /// Pause (`true`) or resume this display's stream.
+    Pause(bool),
+}
+
+// ── Status ────────────────────────────────────────────────────────────────────
+
+/// Live status update sent by the session task to the UI.
+#[derive(Debug, Clone)]
+pub struct PipelineStatus {
+    pub display_index: u8,
+    pub state:         PipelineState,
+    /// Instantaneous frames per second actually encoded and sent.
+    pub fps:           f32,
+    /// Total frames sent since the session started.
+    pub frames_sent:   u64,
+    /// Encoder in use (`None` until created).
+    pub encoder:       Option<String>,
+    /// Raw frames discarded because the encoder fell behind.
+    pub frames_dropped: u64,
+    /// Capture frame-rate cap while overloaded (`None` = running at target fps).
+    pub throttled_fps: Option<u32>,
+    /// Unchanged frames skipped since the session started.
+    pub frames_skipped: u64,
+    /// `true` once the receiver accepted lossless (High 4:4:4) mode.
+    pub lossless:      bool,
+    /// Receiver panel this stream is shown on, as reported in `hello_ack`.
+    pub receiver_display: Option<MonitorInfo>,
+    /// Keepalive RTT and receiver-side frame loss (`None` until the first
+    /// `keepalive_ack`, or for receivers that do not send one).
+    pub link:          Option<LinkQuality>,
+    /// The receiver has paused capture (privacy blank); nothing is sent.
+    pub capture_paused: bool,
+    /// This machine's battery (`None` = none).
+    pub power:         Option<PowerState>,
+    /// The receiver's battery, if it reports one.
+    pub receiver_power: Option<PowerState>,
+    /// Streaming on the battery-saver preset because either end is low.
+    pub battery_saver: bool,
+    /// This display is paused, by either end; capture runs but nothing is
+    /// sent.
+    pub display_paused: bool,
+    /// Streaming at the idle rate — no input or screen changes lately.
+    pub idle:          bool,
+}
+
+/// State of a sender session.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineState {
+    Connecting,
+    /// Capture is opening behind a permission prompt (screen-cast portal)
+    /// the user has to answer.
+    AwaitingCapture,
+    Streaming,
+    /// Stopped cleanly.
+    Stopped,
+    /// Failed with an error message.
+    Failed(String),
+}
+
+// ── SenderSession ─────────────────────────────────────────────────────────────
+
+/// Handle to a running sender session task.
+pub struct SenderSession {
+    pub display_index: u8,
+    stop_tx:     mpsc::Sender<()>,
+    control_tx:  mpsc::Sender<PipelineControl>,
+    frames_sent: Arc<AtomicU64>,
+    log:         PipelineLog,
+}
+
+impl SenderSession {
+    /// Spawn a capture → encode → send session for one display on
+    /// `platform`'s backends.
+    ///
+    /// Status updates go to `status_tx` for the UI to poll. The session runs
+    /// until the receiver ends it or [`stop`](Self::stop) is called.
+    pub fn spawn<P: Platform>(config: SessionConfig, platform: P, status_tx: mpsc::Sender<PipelineStatus>) -> Self {
+        Self::spawn_with_stats(config, platform, status_tx, StatsSinks::configured())
+    }
+
+    /// Like [`spawn`](Self::spawn), reporting sent frames, the session's
+    /// start and end and a summary every second to `stats`.
+    pub fn spawn_with_stats<P: Platform>(
+        config: SessionConfig,
+        platform: P,
+        status_tx: mpsc::Sender<PipelineStatus>,
+        stats: StatsSinks,
+    ) -> Self {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>(1);
+        let (control_tx, control_rx) = mpsc::channel::<PipelineControl>(8);
+        let frames_sent = Arc::new(AtomicU64::new(0));
+        let display_index = config.display_index;
+        let log = PipelineLog::new(display_index);
+
+        let ctx = SessionContext {
+            stop_rx, control_rx, status_tx, frames_sent: Arc::clone(&frames_sent), log: log.clone(), stats,
+        };
+        tokio::spawn(run_session(config, platform, ctx));
+
+        Self { display_index, stop_tx, control_tx, frames_sent, log }
+    }
+
+    /// Event log of this session (shared with the task).
+    pub fn log(&self) -> &PipelineLog {
+        &self.log
+    }
+
+    /// Switch the running session to `preset` (non-blocking).
+    pub fn apply_preset(&self, preset: QualityPreset) {
+        let _ = self.control_tx.try_send(PipelineControl::ApplyPreset(preset));
+    }
+
+    /// Blank the receiver's display, or show the stream again
+    /// (non-blocking). The session and the stream carry on.
+    pub fn set_remote_blank(&self, enabled: bool) {
+        let _ = self.control_tx.try_send(PipelineControl::Blank(enabled));
+    }
+
+    /// Switch the running session to `kbps` (non-blocking).
+    pub fn set_bitrate(&self, kbps: u32) {
+        let _ = self.control_tx.try_send(PipelineControl::SetBitrate(kbps));
+    }
+
+    /// Switch the running session to `fps` (non-blocking).
+    pub fn set_fps(&self, fps: u32) {
+        let _ = self.control_tx.try_send(PipelineControl::SetFps(fps));
+    }
+
+    /// Ask the running session for a keyframe (non-blocking).
+    pub fn force_keyframe(&self) {
+        let _ = self.control_tx.try_send(PipelineControl::ForceKeyframe);
+    }
+
+    /// Pause or resume this display's stream (non-blocking); the other
+    /// displays and the session carry on.
+    pub fn set_paused(&self, paused: bool) {
+        let _ = self.control_tx.try_send(PipelineControl::Pause(paused));
+    }
+
+    /// Request graceful stop (non-blocking).
+    pub fn stop(&self) {
+        let _ = self.stop_tx.try_send(());
+    }
+
+    /// Total frames sent so far.
+    pub fn frames_sent(&self) -> u64 {
+        self.frames_sent.load(Ordering::Relaxed)
+    }
+}
+
+// ── Session task ──────────────────────────────────────────────────────────────
+
+/// What a session task shares with its [`SenderSession`] and the UI.
+struct SessionContext {
+    stop_rx:     mpsc::Receiver<()>,
+    control_rx:  mpsc::Receiver<PipelineControl>,
+    status_tx:   mpsc::Sender<PipelineStatus>,
+    frames_sent: Arc<AtomicU64>,
+    log:         PipelineLog,
+    stats:       StatsSinks,
+}
+
+async fn run_session<P: Platform>(mut config: SessionConfig, mut platform: P, ctx: SessionContext) {
+    let SessionContext { mut stop_rx, mut control_rx, status_tx, frames_sent, log, mut stats } = ctx;
+    let idx = config.display_index;
+    let mut encoder_name: Option<String> = None;
+    let mut feed = FeedStats::default();
+    // Current target rate; changes with presets, caps and receiver requests.
+    let mut target_fps = config.fps;
+    let mut lossless = false;
+    let mut receiver_display: Option<MonitorInfo> = None;
+    let mut link: Option<LinkQuality> = None;
+    let mut capture_paused = false;
+    let mut display_paused = false;
+    let mut power: Option<PowerState> = None;
+    let mut receiver_power: Option<PowerState> = None;
+    let mut battery_saver = false;
+    let mut idle = IdleDetector::new(config.idle_after, Instant::now());
+
+    macro_rules! send_status {
+        ($state:expr, $fps:expr) => {
+            let FeedStats { dropped, skipped, throttled_fps } = feed;
+            let _ = status_tx.try_send(PipelineStatus {
+                display_index: idx,
+                state: $state,
+                fps: $fps,
+                frames_sent: frames_sent.load(Ordering::Relaxed),
+                encoder: encoder_name.clone(),
+                frames_dropped: dropped,
+                throttled_fps,
+                frames_skipped: skipped,
+                lossless,
+                receiver_display: receiver_display.clone(),
+                link,
+                capture_paused,
+                power,
+                receiver_power,
+                battery_saver,
+                display_paused,
+                idle: idle.is_idle(),
+            });
+        };
+    }
+
+    // The receiver accepted the session; its end goes to the stats sinks.
+    let mut accepted = false;
+
+    // Record the failure in the session log, report it and end the task.
+    macro_rules! fail {
+        ($msg:expr) => {{
+            let msg: String = $msg;
+            log.error(msg.clone());
+            if accepted {
+                stats.on_session_event(&SessionEvent::Ended { display: idx, reason: msg.clone() });
+            }
+            send_status!(PipelineState::Failed(msg), 0.0);
+            return;
+        }};
+    }
+
+    send_status!(PipelineState::Connecting, 0.0);
+    log.info(format!("Connecting to {}:{}…", config.host, signaling_port(&config.ports, idx)));
+
+    // ── 1. Connect signaling ──────────────────────────────────────────────
+    let mut sig = match SignalingClient::connect(&config.host, &config.ports, idx).await {
+        Ok(s) => s
+            .with_preview(config.remote_preview)
+            .with_files(platform.file_transfers())
+            .with_input_queue(config.queues.input)
+            .with_resume_token(resume_tokens().lock().unwrap().remove(&(config.host.clone(), idx))),
+        Err(e) => {
+            fail!(format!("Connect: {e:#}"));
+        }
+    };
+
+    let usage = sig.usage();
+    let mut network = NetworkKind::Unknown;
+    if !config.network_caps.is_empty() {
+        let host = config.host.clone();
+        network = tokio::task::spawn_blocking(move || P::route_kind(&host)).await.unwrap_or(NetworkKind::Unknown);
+        log.info(format!("Receiver reached over {network} — {}", config.network_caps.cap(network)));
+    }
+    let session_id = format!("{}-d{}-{}", P::NAME, idx, ts_ms());
+    let mut stream_config = StreamConfig {
+        resolution: Resolution::new(config.width, config.height),
+        target_fps: config.fps,
+        max_bitrate_bps: config.latency_mode.scale_bitrate(config.bitrate_kbps as u64 * 1000),
+        low_latency_mode: config.latency_mode == LatencyMode::UltraLow,
+        latency_mode: config.latency_mode,
+        display_index: idx,
+        quality_preset: config.preset,
+        nal_slices: configured_slice_decode(),
+        ..Default::default()
+    };
+    platform.prepare(&mut stream_config, &log);
+    let requested = stream_config.clone();
+
+    let ack = match sig.send_hello(&session_id, &hostname::<P>(), stream_config.clone(), &config.pairing_pin).await {
+        Ok(a) => a,
+        Err(e) => {
+            fail!(format!("Handshake: {e:#}"));
+        }
+    };
+
+    if !ack.accepted {
+        let reason = ack.reason.unwrap_or_else(|| "unknown".to_owned());
+        fail!(format!("Rejected: {reason}"));
+    }
+    log.info(format!("Session accepted (id={session_id})"));
+    accepted = true;
+    stats.on_session_event(&SessionEvent::Started {
+        display:    idx,
+        session_id: session_id.clone(),
+        peer:       config.host.clone(),
+    });
+    if let Some(token) = ack.resume_token.clone() {
+        resume_tokens().lock().unwrap().insert((config.host.clone(), idx), token);
+    }
+
+    // Drop features the receiver cannot decode (older receivers echo no config).
+    stream_config = stream_config.negotiate(&ack.capabilities);
+    if let Some(negotiated) = &ack.config {
+        stream_config.lossless &= negotiated.lossless;
+        stream_config.nal_slices &= negotiated.nal_slices;
+    }
+    if requested.lossless && !stream_config.lossless {
+        log.warn("Receiver lacks H.264 4:4:4 support — lossless mode disabled");
+    }
+    if requested.nal_slices && !stream_config.nal_slices {
+        log.info("Receiver decodes whole frames only — slice-level decoding off");
+    }
+    if requested.hdr.is_some() && stream_config.hdr.is_none() {
+        log.warn("Receiver cannot decode HEVC Main10 — sending SDR");
+    }
+    if requested.latency_mode != stream_config.latency_mode {
+        log.warn(format!("Receiver has no latency modes — {} instead", stream_config.latency_mode.label()));
+        stream_config.max_bitrate_bps = config.bitrate_kbps as u64 * 1000;
+    } else if stream_config.latency_mode != LatencyMode::UltraLow {
+        log.info(format!("Latency mode: {}", stream_config.latency_mode.label()));
+    }
+    config.bitrate_kbps = (stream_config.max_bitrate_bps / 1000) as u32;
+    lossless = stream_config.lossless;
+
+    // Stay under the receiver's ceilings; capture and encode at the clamped size.
+    let limits = ack.limits;
+    if let Some(why) = limits.violation(&stream_config) {
+        log.warn(format!("Receiver limits: {why} — reducing"));
+        stream_config = stream_config.clamp_to(&limits);
+        config.width = stream_config.resolution.width;
+        config.height = stream_config.resolution.height;
+        config.bitrate_kbps = (stream_config.max_bitrate_bps / 1000) as u32;
+        config.fps = stream_config.target_fps;
+    }
+    // Rate the preset / settings ask for, before the network cap.
+    let mut wanted_kbps = config.bitrate_kbps;
+    let mut wanted_fps = config.fps;
+    // Frame rate of custom rates (no preset), set from the UI mid-session.
+    let mut custom_fps = config.fps;
+
+    if !ack.allow_input {
+        log.info("View-only session — the receiver sends no input");
+    }
+    receiver_display = ack.display_info;
+    // Capture and encoder open at the panel's size; the receiver learns it
+    // from the config update sent once they are.
+    let matched = panel_resolution(&config, receiver_display.as_ref(), &stream_config, &limits);
+    if let Some(size) = matched {
+        log.info(format!("Matching the receiver panel — streaming {} instead of {}", size, stream_config.resolution));
+        stream_config.resolution = size;
+        (config.width, config.height) = (size.width, size.height);
+    }
+    if let Some(panel) = &receiver_display {
+        let native = Resolution::new(config.width, config.height);
+        if native != panel.resolution && native != panel.logical_resolution() {
+            log.info(format!(
+                "Receiver panel {} is {} (scale {}) — stream {} will be scaled",
+                panel.name, panel.resolution, panel.scale, native
+            ));
+        }
+    }
+
+    let (mut sig_writer, mut input_rx) = sig.start_recv_loop();
+    let mut receiver_display_rx = sig_writer.receiver_display();
+    let mut receiver_displays_rx = sig_writer.receiver_displays();
+    let link_rx = sig_writer.link_quality();
+    let mut keyframe_rx = sig_writer.keyframe_requests();
+    let mut blank_rx = sig_writer.blank_requests();
+    let mut pause_rx = sig_writer.display_states();
+    let mut fps_rx = sig_writer.fps_requests();
+    // Ceiling the receiver asked for (hidden window), `None` = none.
+    let mut receiver_fps: Option<u32> = None;
+    let mut receiver_power_rx = sig_writer.receiver_power();
+    let can_blank = ack.capabilities.iter().any(|c| c == CAP_BLANK);
+    let can_power = ack.capabilities.iter().any(|c| c == CAP_POWER);
+    let can_pause = ack.capabilities.iter().any(|c| c == CAP_DISPLAY_STATE);
+
+    if config.remote_preview {
+        if ack.capabilities.iter().any(|c| c == CAP_PREVIEW) {
+            platform.remote_previews(sig_writer.receiver_previews());
+        } else {
+            log.info("Receiver sends no previews");
+        }
+    }
+
+    // ── 2. Connect UDP video sender ───────────────────────────────────────
+    let header_v2 = ack.capabilities.iter().any(|c| c == CAP_DLNK_V2);
+    // NAL ends are only flagged in v2 headers; the frame checksum would hold
+    // every slice back until the whole frame is in.
+    let nal_slices = stream_config.nal_slices && header_v2;
+    // Older receivers send no port map; keep the one we connected with.
+    let ports = if ack.ports.is_empty() { &config.ports } else { &ack.ports };
+    let video = match VideoSender::connect(&config.host, ports, idx).await {
+        Ok(v) => v
+            .with_header_v2(header_v2)
+            .with_checksum(!nal_slices)
+            .with_nal_fragments(nal_slices)
+            .with_usage(usage.clone()),
+        Err(e) => {
+            fail!(format!("UDP: {e:#}"));
+        }
+    };
+
+    // ── 3. Open capture and encoder ───────────────────────────────────────
+    if platform.capture_prompts().await {
+        send_status!(PipelineState::AwaitingCapture, 0.0);
+        log.info("Waiting for a screen to be picked in the screen-cast dialog…");
+    }
+    let (mut capture, mut encoder) = match platform.open(&stream_config, &log).await {
+        Ok(opened) => opened,
+        Err(e) => {
+            fail!(format!("{e:#}"));
+        }
+    };
+    encoder_name = Some(encoder.element_name().to_owned());
+
+    send_status!(PipelineState::Streaming, 0.0);
+    log.info(format!(
+        "Streaming to {} (encoder={} hw={} lossless={})",
+        config.host, encoder.element_name(), encoder.is_hardware_accelerated(), encoder.is_lossless()
+    ));
+
+    // Keep this machine from locking mid-capture; released when the session ends.
+    let _awake = platform.keep_awake().await;
+
+    // Capture that cannot lower its own rate gets frames beyond the target
+    // dropped here; `last_pushed` is when the last one went to the encoder.
+    let mut drop_above: Option<u32> = None;
+    let mut last_pushed = Instant::now();
+    // When capture last delivered a frame, raw or (in-encoder capture) encoded.
+    let mut last_captured = Instant::now();
+
+    // This display's share of the sender's bitrate budget; leaves it when
+    // the session ends.
+    let pixels = stream_config.resolution.width as u64 * stream_config.resolution.height as u64;
+    let share = config.budget.as_ref().map(|budget| budget.join(pixels, wanted_kbps));
+
+    // Apply the wanted rate under the current network's cap and the budget
+    // to the encoder, capture and receiver.
+    macro_rules! apply_rates {
+        () => {{
+            let (kbps, fps) = config.network_caps.cap(network).apply(wanted_kbps, wanted_fps);
+            let (kbps, fps) = idle.cap(kbps, fps);
+            let kbps = share.as_ref().map_or(kbps, |s| s.request(kbps));
+            encoder.set_bitrate(kbps);
+            // fps can only be lowered below the negotiated capture rate.
+            target_fps = fps.min(config.fps).min(receiver_fps.unwrap_or(u32::MAX));
+            encoder.set_fps(target_fps);
+            if let Some(c) = &mut capture {
+                drop_above = (!c.set_max_fps(target_fps) && target_fps < config.fps).then_some(target_fps);
+            }
+            stream_config.max_bitrate_bps = kbps as u64 * 1000;
+            stream_config.target_fps = target_fps;
+            if let Err(e) = sig_writer.send_config_update(&session_id, stream_config.clone()).await {
+                log.warn(format!("Config update: {e:#}"));
+            }
+        }};
+    }
+
+    // Switch the stream to `preset` under the receiver's limits.
+    macro_rules! apply_preset {
+        ($preset:expr) => {{
+            let preset: QualityPreset = $preset;
+            let params = preset.params();
+            log.info(format!("Applying preset {preset:?}"));
+            stream_config = stream_config.clone().with_preset(preset).clamp_to(&limits);
+            encoder.set_gop(params.keyframe_interval);
+            wanted_kbps = (stream_config.max_bitrate_bps / 1000) as u32;
+            wanted_fps = params.target_fps;
+            apply_rates!();
+        }};
+    }
+
+    // Enter battery saver while either end wants it; leave it for the
+    // configured preset (or custom rates) once neither does.
+    macro_rules! update_saver {
+        () => {{
+            let wants = power.is_some_and(|p| p.saver) || receiver_power.is_some_and(|p| p.saver);
+            if wants != battery_saver {
+                battery_saver = wants;
+                encoder.set_skip_unchanged(wants);
+                if wants {
+                    log.info("Battery low — switching to battery saver");
+                    apply_preset!(QualityPreset::BatterySaver);
+                } else if let Some(preset) = config.preset {
+                    log.info("Battery saver off");
+                    apply_preset!(preset);
+                } else {
+                    log.info("Battery saver off — back to custom rates");
+                    stream_config.quality_preset = None;
+                    encoder.set_gop(CUSTOM_GOP);
+                    wanted_kbps = config.bitrate_kbps;
+                    wanted_fps = custom_fps;
+                    apply_rates!();
+                }
+            }
+        }};
+    }
+
+    // Stop sending, or start again with a keyframe.
+    macro_rules! set_display_paused {
+        ($paused:expr, $by:expr) => {{
+            display_paused = $paused;
+            if display_paused {
+                log.info(format!("Display paused{}", $by));
+                encoder.flush();
+            } else {
+                log.info(format!("Display resumed{}", $by));
+                encoder.force_keyframe();
+            }
+        }};
+    }
+
+    // Reopen capture and encoder (stalled capture, crashed encoder) and
+    // carry on with a keyframe.
+    macro_rules! reopen {
+        ($done:expr) => {{
+            encoder.send_eos();
+            drop(capture.take());
+            if platform.capture_prompts().await {
+                send_status!(PipelineState::AwaitingCapture, 0.0);
+            }
+            match platform.open(&stream_config, &log).await {
+                Ok((c, e)) => {
+                    (capture, encoder) = (c, e);
+                    encoder_name = Some(encoder.element_name().to_owned());
+                    encoder.set_skip_unchanged(battery_saver);
+                    apply_rates!();
+                    encoder.force_keyframe();
+                    log.info($done);
+                }
+                Err(e) => {
+                    let _ = sig_writer.send_stop(&session_id).await;
+                    fail!(format!("Restarting capture: {e:#}"));
+                }
+            }
+            last_captured = Instant::now();
+        }};
+    }
+
+    // Capture and encoder were opened at the rate asked for, above the receiver's cap.
+    if stream_config.target_fps < requested.target_fps || matched.is_some() {
+        apply_rates!();
+    }
+
+    let (network_tx, mut network_rx) = watch::channel(network);
+    if !config.network_caps.is_empty() {
+        watch_route::<P>(config.host.clone(), network_tx);
+        if config.network_caps.cap(network).apply(wanted_kbps, wanted_fps) != (wanted_kbps, wanted_fps) {
+            apply_rates!();
+        }
+    }
+    if share.as_ref().is_some_and(|s| s.granted() < (stream_config.max_bitrate_bps / 1000) as u32) {
+        apply_rates!();
+    }
+
+    // ── 4. Main loop ──────────────────────────────────────────────────────
+    let mut keepalive_ticker = tokio::time::interval(Duration::from_secs(1));
+    let mut file_ticker = tokio::time::interval(FILE_CHUNK_INTERVAL);
+    let sends_files = sig_writer.sends_files();
+    let mut meter = IntervalMeter::default();
+    // Encoders reopened after crashing; the session fails past the limit.
+    let mut encoder_restarts = 0;
+    // Sent frame rate over the last second, for the status row.
+    let mut fps = 0.0;
+    // Frames sent and frames that changed the picture since the last tick,
+    // for the budget.
+    let (mut frames_seen, mut frames_changed) = (0u32, 0u32);
+
+    let (power_tx, mut power_rx) = watch::channel(None);
+    watch_power(power_tx);
+
+    loop {
+        tokio::select! {
+            // Stop requested by UI
+            _ = stop_rx.recv() => {
+                log.info("Stop requested");
+                break;
+            }
+
+            // Raw frame from a separate capture source
+            maybe_raw = next_raw_frame(&mut capture) => {
+                let Some(raw) = maybe_raw else {
+                    log.warn("Capture ended (EOS)");
+                    break;
+                };
+                last_captured = Instant::now();
+                if capture_paused || display_paused {
+                    continue;
+                }
+                if let Some(fps) = drop_above {
+                    if last_pushed.elapsed() < Duration::from_secs(1) / fps.max(1) {
+                        continue;
+                    }
+                }
+                last_pushed = Instant::now();
+                if let Err(e) = encoder.push_frame(raw) {
+                    log.warn(format!("push_frame: {e:#}"));
+                }
+            }
+
+            // Pull encoded frame and send
+            maybe_enc = encoder.next_encoded() => {
+                let Some(enc) = maybe_enc else {
+                    let Some(why) = encoder.failure() else {
+                        log.warn("Encoder ended (EOS)");
+                        break;
+                    };
+                    encoder_restarts += 1;
+                    if encoder_restarts > MAX_ENCODER_RESTARTS {
+                        let _ = sig_writer.send_stop(&session_id).await;
+                        fail!(format!("{why} — gave up after {MAX_ENCODER_RESTARTS} restarts"));
+                    }
+                    log.error(format!("{why} — restarting capture and encoder"));
+                    reopen!("Capture and encoder restarted");
+                    continue;
+                };
+                last_captured = Instant::now();
+                // In-encoder capture keeps running while paused.
+                if capture_paused || display_paused {
+                    continue;
+                }
+                match video.send_frame(&enc).await {
+                    Ok(_) => {
+                        frames_sent.fetch_add(1, Ordering::Relaxed);
+                        let (bytes, keyframe) = (enc.data.len(), enc.is_keyframe);
+                        if idle.frame(bytes, keyframe, Instant::now()) {
+                            log.info("Screen changed — back to full rate");
+                            apply_rates!();
+                        }
+                        frames_seen += 1;
+                        frames_changed += u32::from(!keyframe && bytes >= CHANGED_FRAME_BYTES);
+                        let sample = FrameSample { display: idx, bytes, keyframe, errors: 0 };
+                        meter.record(&sample);
+                        stats.on_frame(&sample);
+                    }
+                    Err(e) => {
+                        log.warn(format!("send_frame: {e:#}"));
+                    }
+                }
+            }
+
+            // 1-Hz keepalive + FPS status update
+            _ = keepalive_ticker.tick() => {
+                // Paused capture may go quiet; the watchdog counts from the resume.
+                if capture_paused || display_paused || config.capture_stall.is_zero() {
+                    last_captured = Instant::now();
+                } else if last_captured.elapsed() >= config.capture_stall {
+                    log.warn(format!(
+                        "No frames captured for {:.0} s — restarting capture",
+                        last_captured.elapsed().as_secs_f64()
+                    ));
+                    reopen!("Capture restarted");
+                }
+                feed = encoder.feed_stats();
+                if let (Some(cap), Some(c)) = (encoder.overload_cap(), &mut capture) {
+                    log.warn(format!("Backpressure: capture capped at {} fps ({} dropped)", cap, feed.dropped));
+                    c.set_max_fps(cap);
+                }
+                if receiver_display_rx.has_changed().unwrap_or(false) {
+                    receiver_display = receiver_display_rx.borrow_and_update().clone();
+                    if let Some(size) =
+                        panel_resolution(&config, receiver_display.as_ref(), &stream_config, &limits)
+                    {
+                        log.info(format!("Receiver panel changed — streaming {size}"));
+                        stream_config.resolution = size;
+                        (config.width, config.height) = (size.width, size.height);
+                        reopen!(format!("Capture and encoder reopened at {size}"));
+                    }
+                }
+                if receiver_displays_rx.has_changed().unwrap_or(false) {
+                    let displays = receiver_displays_rx.borrow_and_update().clone().unwrap_or_default();
+                    if !displays.contains(&idx) {
+                        log.warn("Removed by receiver — stopping");
+                        break;
+                    }
+                    log.info(format!("Receiver now serves displays {displays:?}"));
+                }
+                let latest = *link_rx.borrow();
+                if let Some(q) = latest {
+                    // Log transitions only; the status row shows the live value.
+                    match (link.is_some_and(|l| l.is_degraded()), q.is_degraded()) {
+                        (false, true) => log.warn(format!("Link degraded: {q}")),
+                        (true, false) => log.info(format!("Link recovered: {q}")),
+                        _ => {}
+                    }
+                }
+                link = latest;
+                if !display_paused && !capture_paused && idle.poll(Instant::now()) {
+                    log.info(format!("Idle for {} s — dropping to {IDLE_FPS} fps", config.idle_after.as_secs()));
+                    apply_rates!();
+                }
+                if let Some(budget) = &share {
+                    budget.set_activity(frames_changed as f64 / frames_seen.max(1) as f64);
+                    (frames_seen, frames_changed) = (0, 0);
+                    let (granted, streaming) = (budget.granted(), (stream_config.max_bitrate_bps / 1000) as u32);
+                    if granted.abs_diff(streaming) * 10 >= streaming.max(1) {
+                        log.info(format!("Bitrate budget moved — {streaming} → {granted} kbps"));
+                        apply_rates!();
+                    }
+                }
+                let summary = meter.take(idx, feed.dropped, Instant::now());
+                stats.on_interval_summary(&summary);
+                fps = summary.fps as f32;
+                send_status!(PipelineState::Streaming, fps);
+
+                // Tell the receiver when the effective rate moves noticeably
+                // (static content → ~1 fps refresh, motion → back to target).
+                let effective = (fps.round() as u32).clamp(1, target_fps);
+                if config.adaptive_fps && !display_paused && effective.abs_diff(stream_config.target_fps) >= 5 {
+                    stream_config.target_fps = effective;
+                    if let Err(e) = sig_writer.send_config_update(&session_id, stream_config.clone()).await {
+                        log.warn(format!("Config update: {e:#}"));
+                    }
+                }
+                if let Err(e) = sig_writer.send_keepalive(ts_ms()).await {
+                    log.error(format!("Keepalive: {e:#}"));
+                    break;
+                }
+            }
+
+            // File offers, answers and chunks, paced under the rate limit
+            _ = file_ticker.tick(), if sends_files => {
+                if let Err(e) = sig_writer.send_file_messages().await {
+                    log.warn(format!("File transfer: {e:#}"));
+                }
+            }
+
+            // Receiver lost frames and is waiting for a keyframe
+            Ok(()) = keyframe_rx.changed() => {
+                log.info("Keyframe requested by receiver");
+                encoder.force_keyframe();
+            }
+
+            // Receiver paused or resumed capture (privacy blank)
+            Ok(()) = blank_rx.changed() => {
+                capture_paused = *blank_rx.borrow_and_update();
+                if capture_paused {
+                    log.info("Capture paused by receiver");
+                    encoder.flush();
+                } else {
+                    log.info("Capture resumed by receiver");
+                    encoder.force_keyframe();
+                }
+                send_status!(PipelineState::Streaming, fps);
+            }
+
+            // Receiver paused or resumed this display
+            Ok(()) = pause_rx.changed() => {
+                let paused = *pause_rx.borrow_and_update();
+                if paused != display_paused {
+                    set_display_paused!(paused, " by receiver");
+                    send_status!(PipelineState::Streaming, fps);
+                }
+            }
+
+            // Receiver window hidden or shown again
+            Ok(()) = fps_rx.changed() => {
+                receiver_fps = *fps_rx.borrow_and_update();
+                log.info(format!("Receiver asks for at most {} fps", receiver_fps.unwrap_or(config.fps)));
+                apply_rates!();
+            }
+
+            // This machine's battery changed
+            Ok(()) = power_rx.changed() => {
+                power = *power_rx.borrow_and_update();
+                if let Some(p) = power {
+                    log.info(format!("This machine is {p}"));
+                    if can_power {
+                        if let Err(e) = sig_writer.send_power(p).await {
+                            log.warn(format!("Power: {e:#}"));
+                        }
+                    }
+                }
+                update_saver!();
+            }
+
+            // The receiver's battery changed
+            Ok(()) = receiver_power_rx.changed() => {
+                receiver_power = *receiver_power_rx.borrow_and_update();
+                update_saver!();
+            }
+
+            // The route to the receiver moved to another kind of network
+            Ok(()) = network_rx.changed() => {
+                network = *network_rx.borrow_and_update();
+                log.info(format!("Receiver now reached over {network} — {}", config.network_caps.cap(network)));
+                apply_rates!();
+            }
+
+            // Mid-session control from the UI
+            Some(ctrl) = control_rx.recv() => {
+                match ctrl {
+                    PipelineControl::ApplyPreset(preset) => {
+                        config.preset = Some(preset);
+                        if battery_saver {
+                            log.info(format!("Preset {preset:?} applies once battery saver ends"));
+                        } else {
+                            apply_preset!(preset);
+                        }
+                    }
+                    PipelineControl::Blank(enabled) => {
+                        if !can_blank {
+                            log.warn("Receiver cannot blank its display");
+                        } else if let Err(e) = sig_writer.send_blank(enabled).await {
+                            log.warn(format!("Blank: {e:#}"));
+                        } else {
+                            log.info(if enabled { "Receiver display blanked" } else { "Receiver display unblanked" });
+                        }
+                    }
+                    PipelineControl::SetBitrate(kbps) => {
+                        config.preset = None;
+                        config.bitrate_kbps = kbps.min(limits.max_bitrate_kbps.unwrap_or(u32::MAX));
+                        if battery_saver {
+                            log.info(format!("{kbps} kbps applies once battery saver ends"));
+                        } else {
+                            log.info(format!("Bitrate set to {} kbps", config.bitrate_kbps));
+                            stream_config.quality_preset = None;
+                            wanted_kbps = config.bitrate_kbps;
+                            apply_rates!();
+                        }
+                    }
+                    PipelineControl::SetFps(fps) => {
+                        config.preset = None;
+                        custom_fps = fps.clamp(1, config.fps);
+                        if battery_saver {
+                            log.info(format!("{fps} fps applies once battery saver ends"));
+                        } else {
+                            log.info(format!("Frame rate set to {custom_fps} fps"));
+                            stream_config.quality_preset = None;
+                            wanted_fps = custom_fps;
+                            apply_rates!();
+                        }
+                    }
+                    PipelineControl::ForceKeyframe => {
+                        log.info("Keyframe requested");
+                        encoder.force_keyframe();
+                    }
+                    PipelineControl::Pause(paused) => {
+                        if paused != display_paused {
+                            set_display_paused!(paused, "");
+                            if !can_pause {
+                                log.info("Receiver cannot show pauses — it keeps the last frame");
+                            } else if let Err(e) = sig_writer.send_display_state(paused).await {
+                                log.warn(format!("Display state: {e:#}"));
+                            }
+                            send_status!(PipelineState::Streaming, fps);
+                        }
+                    }
+                }
+            }
+
+            // Input events from receiver
+            maybe_ev = input_rx.recv() => {
+                match maybe_ev {
+                    Some(ev) => {
+                        if idle.input(Instant::now()) {
+                            log.info("Input — back to full rate");
+                            apply_rates!();
+                            encoder.force_keyframe();
+                        }
+                        platform.inject(ev).await;
+                    }
+                    None => {
+                        log.warn("Signaling connection closed");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    // ── Cleanup ───────────────────────────────────────────────────────────
+    encoder.send_eos();
+    let _ = sig_writer.send_stop(&session_id).await;
+    let summary = sig_writer.usage().summary(&session_id, &config.host);
+    log.info(format!(
+        "Session used {:.1} MB (avg {} kbps, peak {} kbps)",
+        summary.total_bytes() as f64 / 1e6,
+        summary.average_kbps,
+        summary.peak_kbps
+    ));
+    if let Some(path) = duallink_core::usage::history_file() {
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = summary.append_to(&path) {
+                tracing::warn!("Display[{idx}] writing usage history {}: {e}", path.display());
+            }
+        });
+    }
+    stats.on_session_event(&SessionEvent::Ended { display: idx, reason: "stopped".to_owned() });
+    feed = encoder.feed_stats();
+    send_status!(PipelineState::Stopped, 0.0);
+    log.info("Pipeline stopped");
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// The resolution that matches `panel` within the receiver's `limits`, when
+/// the session is to match it and `stream` is not at it yet.
+fn panel_resolution(
+    config: &SessionConfig,
+    panel: Option<&MonitorInfo>,
+    stream: &StreamConfig,
+    limits: &StreamLimits,
+) -> Option<Resolution> {
+    let panel = panel.filter(|_| config.match_receiver_resolution)?;
+    let size = StreamConfig { resolution: panel.resolution, ..stream.clone() }.clamp_to(limits).resolution;
+    (size != stream.resolution).then_some(size)
+}
+
+/// Re-check the kind of network `host` is reached over every
+/// [`ROUTE_POLL_INTERVAL`], publishing changes, until the session drops
+/// the receiver.
+fn watch_route<P: Platform>(host: String, tx: watch::Sender<NetworkKind>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(ROUTE_POLL_INTERVAL);
+        ticker.tick().await;
+        while !tx.is_closed() {
+            ticker.tick().await;
+            let host = host.clone();
+            let Ok(kind) = tokio::task::spawn_blocking(move || P::route_kind(&host)).await else { break };
+            // A route that cannot be classified keeps the last known cap.
+            if kind != NetworkKind::Unknown {
+                tx.send_if_modified(|current| std::mem::replace(current, kind) != kind);
+            }
+        }
+    });
+}
+
+/// Re-read this machine's battery every [`POWER_POLL_INTERVAL`],
+/// publishing changes, until the session ends.
+fn watch_power(tx: watch::Sender<Option<PowerState>>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POWER_POLL_INTERVAL);
+        while !tx.is_closed() {
+            ticker.tick().await;
+            let Ok(power) = tokio::task::spawn_blocking(read_power).await else { break };
+            tx.send_if_modified(|current| std::mem::replace(current, power) != power);
+        }
+    });
+}
+
+/// Next frame from a separate capture source; pends forever when the
+/// encoder captures by itself.
+async fn next_raw_frame<C: Capture>(capture: &mut Option<C>) -> Option<C::Frame> {
+    match capture {
+        Some(c) => c.next_frame().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Resume token of the last session with each receiver display, by host and
+/// display index, so a session restarted after a drop — also against a
+/// restarted receiver — skips the PIN.
+fn resume_tokens() -> &'static Mutex<HashMap<(String, u8), String>> {
+    static TOKENS: OnceLock<Mutex<HashMap<(String, u8), String>>> = OnceLock::new();
+    TOKENS.get_or_init(Mutex::default)
+}
+
+fn ts_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn hostname<P: Platform>() -> String {
+    hostname::get()
+        .ok()
+        .and_then(|h| h.into_string().ok())
+        .unwrap_or_else(|| P::NAME.to_owned())
+}
@@ -0,0 +1,233 @@
+//! [`OpenH264Encoder`] — software H.264 through Cisco's OpenH264, for
+//! machines without GStreamer's encoder plugins.
+//!
+//! Encoding runs on a dedicated thread fed through a short bounded queue;
+//! frames that arrive while the queue is full are dropped and counted in
+//! [`FeedStats::dropped`]. OpenH264 cannot change its rate control in place,
+//! so a rate or GOP change — or a new frame size — reopens the encoder at
+//! the next frame, which then starts with a keyframe.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self as std_mpsc, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use anyhow::Context;
+use bytes::Bytes;
+use duallink_core::{panic_message, temporal_layer, ColorSpace, EncodedFrame, VideoCodec};
+use openh264::encoder::{BitRate, EncoderConfig, FrameRate, FrameType, IntraFramePeriod, UsageType};
+use openh264::formats::YUVBuffer;
+use openh264::OpenH264API;
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::backend::{Encoder, FeedStats};
+use crate::raw::RawFrame;
+
+/// Raw frames waiting for the encode thread before new ones are dropped.
+const QUEUE_DEPTH: usize = 2;
+
+/// Rate control the encode thread opens OpenH264 with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rates {
+    kbps: u32,
+    fps:  u32,
+    gop:  u32,
+}
+
+/// State the encode thread reads before every frame.
+struct Shared {
+    rates:    Mutex<Rates>,
+    keyframe: AtomicBool,
+    /// Frames the thread is done with — encoded, or skipped by rate control.
+    done:     AtomicU64,
+    /// Set before the encoded stream ends if the thread panicked.
+    panic:    Mutex<Option<String>>,
+}
+
+/// H.264 encoder on OpenH264, taking [`RawFrame`]s.
+pub struct OpenH264Encoder {
+    raw_tx:     Option<SyncSender<RawFrame>>,
+    encoded_rx: mpsc::Receiver<EncodedFrame>,
+    shared:     Arc<Shared>,
+    pushed:     u64,
+    dropped:    u64,
+}
+
+impl OpenH264Encoder {
+    /// Start the encode thread. BGRx frames are converted into `color`;
+    /// NV12 frames are taken to be in it already.
+    pub fn new(fps: u32, bitrate_kbps: u32, gop: u32, color: ColorSpace) -> anyhow::Result<Self> {
+        let (raw_tx, raw_rx) = std_mpsc::sync_channel(QUEUE_DEPTH);
+        let (encoded_tx, encoded_rx) = mpsc::channel(8);
+        let shared = Arc::new(Shared {
+            rates:    Mutex::new(Rates { kbps: bitrate_kbps, fps: fps.max(1), gop: gop.max(1) }),
+            keyframe: AtomicBool::new(false),
+            done:     AtomicU64::new(0),
+            panic:    Mutex::new(None),
+        });
+        let thread_shared = Arc::clone(&shared);
+        thread::Builder::new()
+            .name("openh264".to_owned())
+            .spawn(move || {
+                let run = panic::catch_unwind(AssertUnwindSafe(|| {
+                    encode_thread(raw_rx, &encoded_tx, &thread_shared, color)
+                }));
+                // Recorded while `encoded_tx` is still open, so the session
+                // sees the failure when the stream ends.
+                if let Err(payload) = run {
+                    let message = panic_message(payload.as_ref());
+                    error!("OpenH264 thread panicked: {}", message);
+                    *thread_shared.panic.lock().unwrap() = Some(format!("OpenH264 thread panicked: {message}"));
+                }
+            })
+            .context("Spawning the OpenH264 thread")?;
+        info!("OpenH264Encoder ready @{}fps {}kbps gop={} color={}", fps, bitrate_kbps, gop, color);
+        Ok(Self { raw_tx: Some(raw_tx), encoded_rx, shared, pushed: 0, dropped: 0 })
+    }
+
+    /// Raw frames pushed that the encode thread has not finished with.
+    pub fn in_flight(&self) -> u64 {
+        self.pushed.saturating_sub(self.shared.done.load(Ordering::Acquire))
+    }
+
+    fn update_rates(&self, update: impl FnOnce(&mut Rates)) {
+        update(&mut self.shared.rates.lock().unwrap());
+    }
+}
+
+impl Encoder for OpenH264Encoder {
+    type Frame = RawFrame;
+
+    fn push_frame(&mut self, frame: RawFrame) -> anyhow::Result<()> {
+        let raw_tx = self.raw_tx.as_ref().context("push_frame after EOS")?;
+        match raw_tx.try_send(frame) {
+            Ok(()) => self.pushed += 1,
+            Err(TrySendError::Full(_)) => self.dropped += 1,
+            Err(TrySendError::Disconnected(_)) => anyhow::bail!("OpenH264 thread has stopped"),
+        }
+        Ok(())
+    }
+
+    async fn next_encoded(&mut self) -> Option<EncodedFrame> {
+        self.encoded_rx.recv().await
+    }
+
+    fn set_bitrate(&mut self, kbps: u32) {
+        self.update_rates(|r| r.kbps = kbps);
+    }
+
+    fn set_fps(&mut self, fps: u32) {
+        self.update_rates(|r| r.fps = fps.max(1));
+    }
+
+    fn set_gop(&mut self, frames: u32) {
+        self.update_rates(|r| r.gop = frames.max(1));
+    }
+
+    fn force_keyframe(&mut self) {
+        self.shared.keyframe.store(true, Ordering::Relaxed);
+    }
+
+    fn send_eos(&mut self) {
+        // The thread drains what is queued, then ends the encoded stream.
+        self.raw_tx = None;
+    }
+
+    fn element_name(&self) -> &str {
+        "openh264"
+    }
+
+    fn is_hardware_accelerated(&self) -> bool {
+        false
+    }
+
+    fn feed_stats(&self) -> FeedStats {
+        FeedStats { dropped: self.dropped, ..FeedStats::default() }
+    }
+
+    fn failure(&self) -> Option<String> {
+        self.shared.panic.lock().unwrap().clone()
+    }
+}
+
+// ── Encode thread ─────────────────────────────────────────────────────────────
+
+fn encode_thread(
+    raw_rx: Receiver<RawFrame>,
+    encoded_tx: &mpsc::Sender<EncodedFrame>,
+    shared: &Shared,
+    color: ColorSpace,
+) {
+    // The open encoder with the rates and frame size it was opened for.
+    let mut open: Option<(Rates, u32, u32, openh264::encoder::Encoder)> = None;
+    while let Ok(frame) = raw_rx.recv() {
+        let rates = *shared.rates.lock().unwrap();
+        if !matches!(&open, Some((r, w, h, _)) if (*r, *w, *h) == (rates, frame.width, frame.height)) {
+            match open_encoder(rates) {
+                Ok(encoder) => open = Some((rates, frame.width, frame.height, encoder)),
+                Err(e) => {
+                    warn!("OpenH264: {:#}", e);
+                    break;
+                }
+            }
+        }
+        let Some((_, _, _, encoder)) = open.as_mut() else { break };
+
+        let encoded = encode(encoder, &frame, color, shared.keyframe.swap(false, Ordering::Relaxed));
+        shared.done.fetch_add(1, Ordering::Release);
+        match encoded {
+            Ok(Some(frame)) => {
+                if encoded_tx.blocking_send(frame).is_err() {
+                    break;
+                }
+            }
+            // Skipped by rate control.
+            Ok(None) => {}
+            Err(e) => {
+                warn!("OpenH264: {:#}", e);
+                break;
+            }
+        }
+    }
+}
+
+fn open_encoder(rates: Rates) -> anyhow::Result<openh264::encoder::Encoder> {
+    let config = EncoderConfig::new()
+        .bitrate(BitRate::from_bps(rates.kbps.saturating_mul(1000)))
+        .max_frame_rate(FrameRate::from_hz(rates.fps as f32))
+        .intra_frame_period(IntraFramePeriod::from_num_frames(rates.gop))
+        .usage_type(UsageType::ScreenContentRealTime);
+    openh264::encoder::Encoder::with_api_config(OpenH264API::from_source(), config)
+        .context("Opening the OpenH264 encoder")
+}
+
+/// Encode one frame; `None` if rate control skipped it.
+fn encode(
+    encoder: &mut openh264::encoder::Encoder,
+    frame: &RawFrame,
+    color: ColorSpace,
+    keyframe: bool,
+) -> anyhow::Result<Option<EncodedFrame>> {
+    let i420 = frame.to_i420(color).context("Raw frame shorter than its size")?;
+    let yuv = YUVBuffer::from_vec(i420, frame.width as usize, frame.height as usize);
+    if keyframe {
+        encoder.force_intra_frame();
+    }
+    let bitstream = encoder.encode(&yuv).context("Encoding a frame")?;
+    let is_keyframe = match bitstream.frame_type() {
+        FrameType::Skip | FrameType::Invalid => return Ok(None),
+        frame_type => frame_type == FrameType::IDR,
+    };
+    let data = Bytes::from(bitstream.to_vec());
+    let temporal_layer = if is_keyframe { 0 } else { temporal_layer(VideoCodec::H264, &data) };
+    Ok(Some(EncodedFrame {
+        data,
+        timestamp_us: frame.pts_ms * 1000,
+        is_keyframe,
+        codec: VideoCodec::H264,
+        temporal_layer,
+        partial: false,
+    }))
+}
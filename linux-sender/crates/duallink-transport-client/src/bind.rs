@@ -0,0 +1,174 @@
+//! Source address and interface binding for multi-homed senders.
+//!
+//! A laptop on Wi-Fi and cabled to the receiver over USB-Ethernet has two
+//! ways out; when the routing table picks the wrong one, video crosses the
+//! slow link. A [`LocalBinding`] pins the sockets of
+//! [`VideoSender`](crate::VideoSender) and
+//! [`SignalingClient`](crate::SignalingClient) to one source address and,
+//! on Linux, one interface (`SO_BINDTODEVICE`). [`LocalBinding::configured`]
+//! picks it:
+//!
+//! - `DUALLINK_BIND_INTERFACE=<name>` — leave through that interface
+//! - `DUALLINK_BIND_ADDR=<ip>` — send from that local address
+//! - neither — a receiver on the USB gadget subnet
+//!   ([`USB_GADGET_SUBNET`](duallink_core::usb::USB_GADGET_SUBNET)) is
+//!   reached through the USB Ethernet interface
+//!   [`detect_usb_ethernet`] finds; other receivers follow the routing table.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use anyhow::Context;
+use duallink_core::usb::USB_GADGET_SUBNET;
+use duallink_core::{detect_usb_ethernet, UsbEthernetInfo};
+use tokio::net::{TcpSocket, TcpStream, UdpSocket};
+use tracing::{debug, warn};
+
+/// Where a sender's sockets are bound locally. The default binds nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocalBinding {
+    /// Interface name for `SO_BINDTODEVICE` (Linux; ignored elsewhere).
+    pub interface: Option<String>,
+    /// Source address.
+    pub address:   Option<IpAddr>,
+}
+
+impl fmt::Display for LocalBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.interface, self.address) {
+            (Some(iface), Some(ip)) => write!(f, "{iface} ({ip})"),
+            (Some(iface), None) => write!(f, "{iface}"),
+            (None, Some(ip)) => write!(f, "{ip}"),
+            (None, None) => write!(f, "any interface"),
+        }
+    }
+}
+
+impl LocalBinding {
+    /// The binding for connections to `host`: `DUALLINK_BIND_INTERFACE` /
+    /// `DUALLINK_BIND_ADDR` if either is set, else [`auto`](Self::auto).
+    pub fn configured(host: &str) -> Self {
+        let interface = std::env::var("DUALLINK_BIND_INTERFACE").ok().filter(|v| !v.trim().is_empty());
+        let address = std::env::var("DUALLINK_BIND_ADDR").ok().and_then(|v| match v.trim().parse() {
+            Ok(ip) => Some(ip),
+            Err(_) => {
+                warn!("Ignoring DUALLINK_BIND_ADDR='{v}' — expected an IP address");
+                None
+            }
+        });
+        let explicit = Self { interface: interface.map(|i| i.trim().to_owned()), address };
+        if explicit.is_empty() {
+            Self::auto(host)
+        } else {
+            explicit
+        }
+    }
+
+    /// The USB Ethernet interface if `host` is on the USB gadget subnet and
+    /// one is up, else no binding.
+    pub fn auto(host: &str) -> Self {
+        Self::for_usb(host, detect_usb_ethernet)
+    }
+
+    fn for_usb(host: &str, detect: impl FnOnce() -> Option<UsbEthernetInfo>) -> Self {
+        let on_usb_subnet = host
+            .parse::<Ipv4Addr>()
+            .is_ok_and(|ip| ip.to_string().rsplit_once('.').is_some_and(|(net, _)| net == USB_GADGET_SUBNET));
+        match on_usb_subnet.then(detect).flatten() {
+            Some(usb) => Self { interface: Some(usb.interface_name), address: Some(IpAddr::V4(usb.local_ip)) },
+            None => Self::default(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.interface.is_none() && self.address.is_none()
+    }
+
+    /// Local address to bind for `remote`: the source address, or any
+    /// address of `remote`'s family.
+    fn local_addr(&self, remote: &SocketAddr) -> SocketAddr {
+        let ip = self.address.unwrap_or(match remote {
+            SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        });
+        SocketAddr::new(ip, 0)
+    }
+
+    /// A UDP socket bound as configured, for datagrams to `remote`.
+    pub(crate) async fn udp_socket(&self, remote: SocketAddr) -> anyhow::Result<UdpSocket> {
+        let local = self.local_addr(&remote);
+        let socket = UdpSocket::bind(local).await.with_context(|| format!("Binding UDP socket to {local}"))?;
+        #[cfg(target_os = "linux")]
+        if let Some(iface) = &self.interface {
+            if let Err(e) = socket.bind_device(Some(iface.as_bytes())) {
+                warn!("UDP socket not bound to {}: {} — sending from {} only", iface, e, local);
+            }
+        }
+        self.warn_unsupported();
+        debug!("UDP to {} bound to {}", remote, self);
+        Ok(socket)
+    }
+
+    /// A TCP connection to `host:port` bound as configured. With a source
+    /// address, `host` resolves to an address of the same family.
+    pub(crate) async fn tcp_connect(&self, host: &str, port: u16) -> anyhow::Result<TcpStream> {
+        if self.is_empty() {
+            return Ok(TcpStream::connect((host, port)).await?);
+        }
+        let remote = tokio::net::lookup_host((host, port))
+            .await?
+            .find(|a| self.address.is_none_or(|ip| ip.is_ipv4() == a.is_ipv4()))
+            .with_context(|| format!("No address of {host} reachable from {self}"))?;
+        let local = self.local_addr(&remote);
+        let socket = if remote.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+        #[cfg(target_os = "linux")]
+        if let Some(iface) = &self.interface {
+            if let Err(e) = socket.bind_device(Some(iface.as_bytes())) {
+                warn!("TCP socket not bound to {}: {} — connecting from {} only", iface, e, local);
+            }
+        }
+        self.warn_unsupported();
+        socket.bind(local).with_context(|| format!("Binding TCP socket to {local}"))?;
+        debug!("TCP to {} bound to {}", remote, self);
+        Ok(socket.connect(remote).await?)
+    }
+
+    fn warn_unsupported(&self) {
+        #[cfg(not(target_os = "linux"))]
+        if let Some(iface) = &self.interface {
+            warn!("Binding to interface {} is only supported on Linux — set DUALLINK_BIND_ADDR instead", iface);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn usb_subnet_hosts_bind_to_usb_ethernet() {
+        let usb = || {
+            Some(UsbEthernetInfo {
+                interface_name: "usb0".into(),
+                local_ip:       "10.0.1.2".parse().unwrap(),
+                peer_ip:        "10.0.1.1".parse().unwrap(),
+            })
+        };
+        let bound = LocalBinding::for_usb("10.0.1.1", usb);
+        assert_eq!(bound.interface.as_deref(), Some("usb0"));
+        assert_eq!(bound.address, Some("10.0.1.2".parse().unwrap()));
+        assert_eq!(bound.to_string(), "usb0 (10.0.1.2)");
+
+        assert!(LocalBinding::for_usb("10.0.10.1", usb).is_empty());
+        assert!(LocalBinding::for_usb("192.168.1.20", usb).is_empty());
+        assert!(LocalBinding::for_usb("10.0.1.1", || None).is_empty());
+    }
+
+    #[test]
+    fn local_addr_follows_the_remote_family() {
+        let v6: SocketAddr = "[fe80::1]:7879".parse().unwrap();
+        assert_eq!(LocalBinding::default().local_addr(&v6), "[::]:0".parse().unwrap());
+        let from = LocalBinding { interface: None, address: Some("10.0.1.2".parse().unwrap()) };
+        assert_eq!(from.local_addr(&"10.0.1.1:7878".parse().unwrap()), "10.0.1.2:0".parse().unwrap());
+    }
+}
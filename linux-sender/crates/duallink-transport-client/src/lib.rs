@@ -14,42 +14,75 @@
 //! SignalingClient ─ TLS:7879+2n ─────►  SignalingServer (TLS)
 //! ```
 //!
+//! The ports above are the defaults. Receivers advertise the ports they
+//! actually bound as a [`PortMap`] — in the mDNS `ports` TXT key (see
+//! [`ports_from_txt`]) and in `hello_ack` ([`HelloAck::ports`]) — and
+//! [`video_port`] / [`signaling_port`] look them up there.
+//!
+//! # Multi-homed senders
+//!
+//! Both connections bind to a [`LocalBinding`]: an interface and/or source
+//! address from `DUALLINK_BIND_INTERFACE` / `DUALLINK_BIND_ADDR`, else the
+//! USB Ethernet interface when the receiver is on the USB gadget subnet (see
+//! [`bind`]). [`VideoSender::connect_bound`] and
+//! [`SignalingClient::connect_verified`] take one explicitly.
+//!
 //! # Quick Start
 //!
 //! ```rust,no_run
-//! use duallink_transport_client::{SignalingClient, VideoSender};
+//! use duallink_transport_client::{PortMap, SignalingClient, VideoSender};
 //! use duallink_core::StreamConfig;
 //!
 //! # tokio_test::block_on(async {
-//! let mut sig = SignalingClient::connect("192.168.1.100", 0).await.unwrap();
+//! // Default ports; use the map from mDNS discovery when there is one.
+//! let mut sig = SignalingClient::connect("192.168.1.100", &PortMap::default(), 0).await.unwrap();
 //! let config  = StreamConfig::default();
 //! let ack = sig.send_hello("session-1", "My Linux Box", config.clone(), "123456").await.unwrap();
 //! assert!(ack.accepted);
 //!
-//! let video = VideoSender::connect("192.168.1.100", 0).await.unwrap();
+//! let video = VideoSender::connect("192.168.1.100", &ack.ports, 0).await.unwrap();
 //! // … encode frames and call video.send_frame(&frame).await?
 //! # })
 //! ```
 
+pub mod bind;
 pub mod signaling;
 pub mod video_sender;
+pub mod wol;
 
-pub use signaling::{HelloAck, SignalingClient, SignalingWriter};
+pub use bind::LocalBinding;
+pub use signaling::{ClientCertificate, HelloAck, ServerVerification, SignalingClient, SignalingWriter};
 pub use video_sender::VideoSender;
+pub use wol::wake_receiver;
+pub use duallink_core::{DisplayPorts, PortMap};
 
 // ── Port helpers (mirrors duallink-transport receiver) ───────────────────────
 
-pub const VIDEO_PORT: u16 = 7878;
-pub const SIGNALING_PORT: u16 = 7879;
+pub const VIDEO_PORT: u16 = duallink_core::DEFAULT_VIDEO_PORT;
+pub const SIGNALING_PORT: u16 = duallink_core::DEFAULT_SIGNALING_PORT;
 
-/// UDP video port for a given display index: 7878, 7880, 7882, …
+/// UDP video port for a given display index as advertised in `ports`;
+/// displays it does not list follow its layout (an empty map gives
+/// 7878, 7880, 7882, …).
 #[inline]
-pub fn video_port(display_index: u8) -> u16 {
-    VIDEO_PORT + (display_index as u16) * 2
+pub fn video_port(ports: &PortMap, display_index: u8) -> u16 {
+    ports.video_port(display_index)
 }
 
-/// TCP signaling port for a given display index: 7879, 7881, 7883, …
+/// TCP signaling port for a given display index as advertised in `ports`;
+/// displays it does not list follow its layout (an empty map gives
+/// 7879, 7881, 7883, …).
 #[inline]
-pub fn signaling_port(display_index: u8) -> u16 {
-    SIGNALING_PORT + (display_index as u16) * 2
+pub fn signaling_port(ports: &PortMap, display_index: u8) -> u16 {
+    ports.signaling_port(display_index)
+}
+
+/// Port map from a receiver's mDNS TXT values: the `ports` key when
+/// present, else `displays` displays laid out from `port`, display 0's
+/// signaling port (receivers that predate the `ports` key).
+pub fn ports_from_txt(ports: Option<&str>, port: Option<u16>, displays: u8) -> PortMap {
+    ports.and_then(PortMap::from_txt).filter(|m| !m.is_empty()).unwrap_or_else(|| {
+        let base = port.map_or(VIDEO_PORT, |p| p.saturating_sub(1));
+        PortMap::contiguous(base, displays.max(1))
+    })
 }
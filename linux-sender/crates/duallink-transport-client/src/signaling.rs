@@ -5,27 +5,77 @@
 //! # Lifecycle
 //!
 //! ```text
-//! 1. SignalingClient::connect(host, display_index)
+//! 1. SignalingClient::connect(host, ports, display_index)
 //! 2. client.send_hello(session_id, device_name, config, pairing_pin)
 //!       └─ returns HelloAck { accepted, reason }
 //! 3. let (writer, input_rx) = client.start_recv_loop()
 //!       ├─ writer: SignalingWriter for keepalive / stop / config_update
+//!       │          (+ writer.receiver_display() for panel hot-plug updates,
+//!       │             writer.receiver_displays() for runtime display add/remove,
+//!       │             writer.link_quality() for RTT / loss from keepalive_ack,
+//!       │             writer.keyframe_requests() for receiver PLIs,
+//!       │             writer.blank_requests() for receiver capture pauses,
+//!       │             writer.display_states() for receiver display pauses,
+//!       │             writer.fps_requests() for receiver frame-rate caps,
+//!       │             writer.receiver_power() for the receiver's battery,
+//!       │             writer.receiver_previews() for thumbnails of the
+//!       │             receiver's screen, if asked for with with_preview)
 //!       └─ input_rx: channel for InputEvents from the receiver
 //! 4. writer.send_keepalive(timestamp_ms)  ← every 1 Hz
+//!    writer.send_power(state)             ← when the local battery changes
+//!    writer.send_display_state(paused)    ← when the user pauses this display
 //! 5. writer.send_stop(session_id)
+//! 6. writer.usage().summary(session_id, host)  ← bytes used, see below
 //! ```
+//!
+//! # Client certificates
+//!
+//! Receivers configured for mutual TLS verify senders by certificate
+//! instead of the pairing PIN. [`SignalingClient::connect`] presents the
+//! certificate named by `DUALLINK_CLIENT_CERT` and `DUALLINK_CLIENT_KEY`
+//! (PEM files, see [`ClientCertificate::from_env`]) when both are set.
+//!
+//! # Verifying the receiver
+//!
+//! By default any receiver certificate is accepted (trust on first use —
+//! the user compares the fingerprint the receiver shows). Receivers given a
+//! certificate from the site's own CA can be verified properly instead:
+//! with `DUALLINK_SERVER_CA` naming a PEM bundle of those CAs, the
+//! certificate must chain to one of them and be issued for the host name
+//! connected to (see [`ServerVerification`]).
+//!
+//! # Resuming sessions
+//!
+//! Receivers hand out a one-time [`HelloAck::resume_token`]. Passed to the
+//! next connection's [`SignalingClient::with_resume_token`], it gets the
+//! session accepted without the PIN, also by a receiver restarted since.
+//!
+//! # Usage accounting
+//!
+//! Every message in either direction is counted in the connection's
+//! [`UsageMeter`] ([`SignalingClient::usage`]); share it with the
+//! [`VideoSender`](crate::VideoSender) through
+//! [`with_usage`](crate::VideoSender::with_usage) and the session's
+//! [`SessionSummary`](duallink_core::SessionSummary) covers the video too.
 
+use std::path::Path;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::Context;
-use duallink_core::{InputEvent, StreamConfig};
+use duallink_core::{
+    FrameCounters, InputEvent, LinkQuality, MonitorInfo, PowerState, Resolution, StreamConfig, StreamLimits, UsageMeter,
+    CAP_BLANK, CAP_DISPLAYS_CHANGED, CAP_DISPLAY_INFO, CAP_DISPLAY_STATE, CAP_FPS_REQUEST, CAP_KEEPALIVE_ACK, CAP_KEYFRAME_REQUEST,
+    CAP_POWER, CAP_PREVIEW, CAP_FILE_TRANSFER, FileMessage, FileOffer, FileTransfers, SenderQueues,
+};
+use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncReadExt, AsyncWriteExt, WriteHalf};
 use tokio::net::TcpStream;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 use tracing::{debug, info, warn};
 
-use crate::signaling_port;
+use crate::{signaling_port, LocalBinding, PortMap};
 
 // ── Internal alias ────────────────────────────────────────────────────────────
 
@@ -40,8 +90,20 @@ pub(crate) enum MessageType {
     HelloAck,
     ConfigUpdate,
     Keepalive,
+    KeepaliveAck,
+    KeyframeRequest,
     Stop,
     InputEvent,
+    DisplayInfo,
+    DisplaysChanged,
+    Blank,
+    Preview,
+    Power,
+    Error,
+    DisplayState,
+    FileOffer,
+    FileAccept,
+    FileChunk,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -60,12 +122,60 @@ pub(crate) struct SignalingMessage {
     pub reason: Option<String>,
     #[serde(rename = "timestampMs", skip_serializing_if = "Option::is_none")]
     pub timestamp_ms: Option<u64>,
-    #[serde(rename = "inputEvent", skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "inputEvent",
+        default,
+        deserialize_with = "duallink_core::input_schema::deserialize_input_event",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub input_event: Option<InputEvent>,
     #[serde(rename = "pairingPin", skip_serializing_if = "Option::is_none")]
     pub pairing_pin: Option<String>,
     #[serde(rename = "displayIndex", skip_serializing_if = "Option::is_none")]
     pub display_index: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<Vec<String>>,
+    #[serde(rename = "displayInfo", skip_serializing_if = "Option::is_none")]
+    pub display_info: Option<MonitorInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub displays: Option<Vec<u8>>,
+    #[serde(rename = "frameCounters", skip_serializing_if = "Option::is_none")]
+    pub frame_counters: Option<FrameCounters>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ports: Option<PortMap>,
+    #[serde(rename = "maxBitrateKbps", skip_serializing_if = "Option::is_none")]
+    pub max_bitrate_kbps: Option<u32>,
+    #[serde(rename = "maxResolution", skip_serializing_if = "Option::is_none")]
+    pub max_resolution: Option<Resolution>,
+    #[serde(rename = "maxFps", skip_serializing_if = "Option::is_none")]
+    pub max_fps: Option<u32>,
+    #[serde(rename = "allowInput", skip_serializing_if = "Option::is_none")]
+    pub allow_input: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<PowerState>,
+    /// Why the receiver refused one of our messages, in `error`.
+    #[serde(rename = "errorCode", skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paused: Option<bool>,
+    /// Issued in `hello_ack`; sent back in `hello` to skip the PIN.
+    #[serde(rename = "resumeToken", skip_serializing_if = "Option::is_none")]
+    pub resume_token: Option<String>,
+    #[serde(rename = "transferId", skip_serializing_if = "Option::is_none")]
+    pub transfer_id: Option<String>,
+    #[serde(rename = "fileName", skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    #[serde(rename = "fileSize", skip_serializing_if = "Option::is_none")]
+    pub file_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+    /// Base64 file bytes, in `file_chunk`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
 }
 
 impl SignalingMessage {
@@ -75,7 +185,22 @@ impl SignalingMessage {
         config: StreamConfig,
         pairing_pin: &str,
         display_index: u8,
+        allow_input: bool,
+        preview: bool,
     ) -> Self {
+        let mut capabilities = vec![
+            CAP_DISPLAY_INFO.to_owned(),
+            CAP_DISPLAYS_CHANGED.to_owned(),
+            CAP_KEEPALIVE_ACK.to_owned(),
+            CAP_KEYFRAME_REQUEST.to_owned(),
+            CAP_BLANK.to_owned(),
+            CAP_FPS_REQUEST.to_owned(),
+            CAP_POWER.to_owned(),
+            CAP_DISPLAY_STATE.to_owned(),
+        ];
+        if preview {
+            capabilities.push(CAP_PREVIEW.to_owned());
+        }
         Self {
             msg_type: MessageType::Hello,
             session_id: Some(session_id.to_owned()),
@@ -87,6 +212,26 @@ impl SignalingMessage {
             input_event: None,
             pairing_pin: Some(pairing_pin.to_owned()),
             display_index: Some(display_index),
+            capabilities: Some(capabilities),
+            display_info: None,
+            displays: None,
+            frame_counters: None,
+            ports: None,
+            max_bitrate_kbps: None,
+            max_resolution: None,
+            max_fps: None,
+            allow_input: Some(allow_input),
+            enabled: None,
+            image: None,
+            power: None,
+            error_code: None,
+            paused: None,
+            resume_token: None,
+            transfer_id: None,
+            file_name: None,
+            file_size: None,
+            offset: None,
+            data: None,
         }
     }
 
@@ -102,6 +247,26 @@ impl SignalingMessage {
             input_event: None,
             pairing_pin: None,
             display_index: None,
+            capabilities: None,
+            display_info: None,
+            displays: None,
+            frame_counters: None,
+            ports: None,
+            max_bitrate_kbps: None,
+            max_resolution: None,
+            max_fps: None,
+            allow_input: None,
+            enabled: None,
+            image: None,
+            power: None,
+            error_code: None,
+            paused: None,
+            resume_token: None,
+            transfer_id: None,
+            file_name: None,
+            file_size: None,
+            offset: None,
+            data: None,
         }
     }
 
@@ -117,9 +282,105 @@ impl SignalingMessage {
             input_event: None,
             pairing_pin: None,
             display_index: None,
+            capabilities: None,
+            display_info: None,
+            displays: None,
+            frame_counters: None,
+            ports: None,
+            max_bitrate_kbps: None,
+            max_resolution: None,
+            max_fps: None,
+            allow_input: None,
+            enabled: None,
+            image: None,
+            power: None,
+            error_code: None,
+            paused: None,
+            resume_token: None,
+            transfer_id: None,
+            file_name: None,
+            file_size: None,
+            offset: None,
+            data: None,
         }
     }
 
+    pub(crate) fn blank(enabled: bool) -> Self {
+        Self {
+            msg_type: MessageType::Blank,
+            timestamp_ms: None,
+            enabled: Some(enabled),
+            ..Self::keepalive(0)
+        }
+    }
+
+    pub(crate) fn power(power: PowerState) -> Self {
+        Self {
+            msg_type: MessageType::Power,
+            timestamp_ms: None,
+            power: Some(power),
+            ..Self::keepalive(0)
+        }
+    }
+
+    pub(crate) fn display_state(display_index: u8, paused: bool) -> Self {
+        Self {
+            msg_type: MessageType::DisplayState,
+            timestamp_ms: None,
+            display_index: Some(display_index),
+            paused: Some(paused),
+            ..Self::keepalive(0)
+        }
+    }
+
+    pub(crate) fn file(message: FileMessage) -> Self {
+        use base64::Engine as _;
+        let base = Self { timestamp_ms: None, ..Self::keepalive(0) };
+        match message {
+            FileMessage::Offer(offer) => Self {
+                msg_type: MessageType::FileOffer,
+                transfer_id: Some(offer.transfer_id),
+                file_name: Some(offer.name),
+                file_size: Some(offer.size),
+                ..base
+            },
+            FileMessage::Answer { transfer_id, accepted, reason } => Self {
+                msg_type: MessageType::FileAccept,
+                transfer_id: Some(transfer_id),
+                accepted: Some(accepted),
+                reason,
+                ..base
+            },
+            FileMessage::Chunk { transfer_id, offset, data } => Self {
+                msg_type: MessageType::FileChunk,
+                transfer_id: Some(transfer_id),
+                offset: Some(offset),
+                data: Some(base64::engine::general_purpose::STANDARD.encode(data)),
+                ..base
+            },
+        }
+    }
+
+    /// The file-transfer message this is, if it is a complete one.
+    pub(crate) fn into_file(self) -> Option<FileMessage> {
+        use base64::Engine as _;
+        let transfer_id = self.transfer_id?;
+        Some(match self.msg_type {
+            MessageType::FileOffer => {
+                FileMessage::Offer(FileOffer { transfer_id, name: self.file_name?, size: self.file_size? })
+            }
+            MessageType::FileAccept => {
+                FileMessage::Answer { transfer_id, accepted: self.accepted?, reason: self.reason }
+            }
+            MessageType::FileChunk => FileMessage::Chunk {
+                transfer_id,
+                offset: self.offset?,
+                data: base64::engine::general_purpose::STANDARD.decode(self.data?).ok()?,
+            },
+            _ => return None,
+        })
+    }
+
     pub(crate) fn stop(session_id: &str) -> Self {
         Self {
             msg_type: MessageType::Stop,
@@ -132,6 +393,26 @@ impl SignalingMessage {
             input_event: None,
             pairing_pin: None,
             display_index: None,
+            capabilities: None,
+            display_info: None,
+            displays: None,
+            frame_counters: None,
+            ports: None,
+            max_bitrate_kbps: None,
+            max_resolution: None,
+            max_fps: None,
+            allow_input: None,
+            enabled: None,
+            image: None,
+            power: None,
+            error_code: None,
+            paused: None,
+            resume_token: None,
+            transfer_id: None,
+            file_name: None,
+            file_size: None,
+            offset: None,
+            data: None,
         }
     }
 }
@@ -141,18 +422,21 @@ impl SignalingMessage {
 async fn write_msg(
     stream: &mut (impl AsyncWriteExt + Unpin),
     msg: &SignalingMessage,
+    usage: &UsageMeter,
 ) -> anyhow::Result<()> {
     let json = serde_json::to_vec(msg)?;
     let len = json.len() as u32;
     stream.write_all(&len.to_be_bytes()).await?;
     stream.write_all(&json).await?;
     stream.flush().await?;
+    usage.add_sent(4 + json.len());
     debug!("Sent {:?} ({} bytes)", msg.msg_type, json.len());
     Ok(())
 }
 
 async fn read_msg(
     stream: &mut (impl AsyncReadExt + Unpin),
+    usage: &UsageMeter,
 ) -> anyhow::Result<SignalingMessage> {
     let mut len_buf = [0u8; 4];
     stream.read_exact(&mut len_buf).await.context("reading message length")?;
@@ -162,6 +446,7 @@ async fn read_msg(
     }
     let mut body = vec![0u8; len];
     stream.read_exact(&mut body).await.context("reading message body")?;
+    usage.add_received(4 + len);
     let msg: SignalingMessage = serde_json::from_slice(&body).context("parsing signaling message")?;
     debug!("Received {:?} ({} bytes)", msg.msg_type, len);
     Ok(msg)
@@ -221,6 +506,85 @@ impl rustls::client::danger::ServerCertVerifier for TofuCertVerifier {
     }
 }
 
+// ── Receiver certificate verification ────────────────────────────────────────
+
+/// How the receiver's certificate is checked.
+#[derive(Debug, Clone, Default)]
+pub enum ServerVerification {
+    /// Accept any certificate; the user compares its fingerprint.
+    #[default]
+    TrustOnFirstUse,
+    /// The certificate must chain to one of these CAs and be issued for the
+    /// host name (or IP address) connected to.
+    Hostname(Arc<rustls::RootCertStore>),
+}
+
+impl ServerVerification {
+    /// Verify receivers against the PEM CA bundle at `path`.
+    pub fn ca_bundle(path: &Path) -> anyhow::Result<Self> {
+        let pem = std::fs::read(path).with_context(|| format!("Reading server CA {}", path.display()))?;
+        let mut roots = rustls::RootCertStore::empty();
+        for cert in rustls_pemfile::certs(&mut pem.as_slice()) {
+            roots.add(cert.with_context(|| format!("Parsing server CA {}", path.display()))?)?;
+        }
+        anyhow::ensure!(!roots.is_empty(), "No certificates in server CA {}", path.display());
+        Ok(Self::Hostname(Arc::new(roots)))
+    }
+
+    /// [`Hostname`](Self::Hostname) verification against the bundle named
+    /// by `DUALLINK_SERVER_CA`, else trust on first use.
+    pub fn from_env() -> anyhow::Result<Self> {
+        match std::env::var_os("DUALLINK_SERVER_CA") {
+            Some(path) => Self::ca_bundle(Path::new(&path)),
+            None => Ok(Self::TrustOnFirstUse),
+        }
+    }
+
+    fn verifier(&self) -> anyhow::Result<Arc<dyn rustls::client::danger::ServerCertVerifier>> {
+        Ok(match self {
+            Self::TrustOnFirstUse => Arc::new(TofuCertVerifier),
+            Self::Hostname(roots) => rustls::client::WebPkiServerVerifier::builder(Arc::clone(roots))
+                .build()
+                .context("Building server certificate verifier")?,
+        })
+    }
+}
+
+// ── Client certificate (mutual TLS) ──────────────────────────────────────────
+
+/// Certificate chain and private key presented to receivers that verify
+/// senders.
+pub struct ClientCertificate {
+    chain: Vec<rustls::pki_types::CertificateDer<'static>>,
+    key:   rustls::pki_types::PrivateKeyDer<'static>,
+}
+
+impl ClientCertificate {
+    /// Load a PEM certificate chain (leaf first) and its PEM private key.
+    pub fn from_pem_files(cert: &Path, key: &Path) -> anyhow::Result<Self> {
+        let pem = std::fs::read(cert).with_context(|| format!("Reading client certificate {}", cert.display()))?;
+        let chain = rustls_pemfile::certs(&mut pem.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("Parsing client certificate {}", cert.display()))?;
+        anyhow::ensure!(!chain.is_empty(), "No certificate in {}", cert.display());
+        let pem = std::fs::read(key).with_context(|| format!("Reading client key {}", key.display()))?;
+        let key = rustls_pemfile::private_key(&mut pem.as_slice())
+            .with_context(|| format!("Parsing client key {}", key.display()))?
+            .with_context(|| format!("No private key in {}", key.display()))?;
+        Ok(Self { chain, key })
+    }
+
+    /// The certificate named by `DUALLINK_CLIENT_CERT` and
+    /// `DUALLINK_CLIENT_KEY`; `None` unless both are set.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        match (std::env::var_os("DUALLINK_CLIENT_CERT"), std::env::var_os("DUALLINK_CLIENT_KEY")) {
+            (Some(cert), Some(key)) => Self::from_pem_files(Path::new(&cert), Path::new(&key)).map(Some),
+            (None, None) => Ok(None),
+            _ => anyhow::bail!("DUALLINK_CLIENT_CERT and DUALLINK_CLIENT_KEY must be set together"),
+        }
+    }
+}
+
 // ── Public result types ───────────────────────────────────────────────────────
 
 /// Result of the `hello` / `hello_ack` handshake.
@@ -229,6 +593,29 @@ pub struct HelloAck {
     pub accepted: bool,
     pub reason: Option<String>,
     pub session_id: Option<String>,
+    /// Optional stream capabilities advertised by the receiver
+    /// (e.g. [`duallink_core::CAP_H264_444`]); empty for older receivers.
+    pub capabilities: Vec<String>,
+    /// Config after receiver-side negotiation, if the receiver echoes it.
+    /// Features the receiver cannot handle (e.g. `lossless`) are cleared.
+    pub config: Option<StreamConfig>,
+    /// Geometry of the receiver panel this display stream is shown on
+    /// (preferred mode, physical size, HiDPI scale), if the receiver reports it.
+    pub display_info: Option<MonitorInfo>,
+    /// Ports of every display the receiver serves; empty for older
+    /// receivers, which use the default layout.
+    pub ports: PortMap,
+    /// Bitrate / resolution ceilings for this display. The negotiated
+    /// [`config`](Self::config) is already within them; stay there for
+    /// later `config_update`s too — the receiver drops frames of senders
+    /// far above the bitrate ceiling.
+    pub limits: StreamLimits,
+    /// `false` for a view-only session: the receiver sends no input events.
+    pub allow_input: bool,
+    /// Token to resume this session with after a reconnect, also to a
+    /// restarted receiver (see [`SignalingClient::with_resume_token`]);
+    /// `None` from older receivers or with resumption off.
+    pub resume_token: Option<String>,
 }
 
 // ── SignalingClient ───────────────────────────────────────────────────────────
@@ -242,41 +629,78 @@ pub struct HelloAck {
 pub struct SignalingClient {
     stream: TlsClientStream,
     display_index: u8,
+    /// Receiver panel from `hello_ack`; seeds the writer's display watch.
+    display_info: Option<MonitorInfo>,
+    /// Asked for in `hello`; see [`with_input`](Self::with_input).
+    allow_input: bool,
+    /// Asked for in `hello`; see [`with_preview`](Self::with_preview).
+    preview: bool,
+    /// Sent in `hello`; see [`with_resume_token`](Self::with_resume_token).
+    resume_token: Option<String>,
+    /// Offered in `hello`; see [`with_files`](Self::with_files).
+    files: Option<FileTransfers>,
+    /// Depth of the input event channel; see
+    /// [`with_input_queue`](Self::with_input_queue).
+    input_queue: usize,
+    usage: UsageMeter,
 }
 
 impl SignalingClient {
     // ── Construction ─────────────────────────────────────────────────────────
 
-    /// Connect to a DualLink receiver at `host`, auto-resolving the signaling
-    /// port from `display_index` (port = 7879 + 2 × display_index).
-    pub async fn connect(host: &str, display_index: u8) -> anyhow::Result<Self> {
-        let port = signaling_port(display_index);
+    /// Connect to a DualLink receiver at `host`, looking the signaling port
+    /// of `display_index` up in the receiver's advertised `ports`.
+    pub async fn connect(host: &str, ports: &PortMap, display_index: u8) -> anyhow::Result<Self> {
+        let port = signaling_port(ports, display_index);
         Self::connect_with_port(host, port, display_index).await
     }
 
-    /// Connect with an explicit port number.
+    /// Connect with an explicit port number, verifying the receiver as
+    /// `DUALLINK_SERVER_CA` says (see [`ServerVerification::from_env`]) and
+    /// binding as [`LocalBinding::configured`] says.
     pub async fn connect_with_port(
         host: &str,
         port: u16,
         display_index: u8,
+    ) -> anyhow::Result<Self> {
+        let verification = ServerVerification::from_env()?;
+        Self::connect_verified(host, port, display_index, &verification, &LocalBinding::configured(host)).await
+    }
+
+    /// Connect with an explicit port number, checking the receiver's
+    /// certificate as `verification` says and connecting from the interface
+    /// or source address in `binding`.
+    pub async fn connect_verified(
+        host: &str,
+        port: u16,
+        display_index: u8,
+        verification: &ServerVerification,
+        binding: &LocalBinding,
     ) -> anyhow::Result<Self> {
         // Install ring crypto provider (ignored if already installed)
         let _ = rustls::crypto::ring::default_provider().install_default();
 
-        let client_config = rustls::ClientConfig::builder()
+        let builder = rustls::ClientConfig::builder()
             .dangerous()
-            .with_custom_certificate_verifier(Arc::new(TofuCertVerifier))
-            .with_no_client_auth();
+            .with_custom_certificate_verifier(verification.verifier()?);
+        let client_config = match ClientCertificate::from_env()? {
+            Some(cert) => {
+                info!("Presenting client certificate to {}:{}", host, port);
+                builder.with_client_auth_cert(cert.chain, cert.key).context("Using client certificate")?
+            }
+            None => builder.with_no_client_auth(),
+        };
 
         let connector = tokio_rustls::TlsConnector::from(Arc::new(client_config));
 
-        let tcp = TcpStream::connect((host, port))
+        let tcp = binding
+            .tcp_connect(host, port)
             .await
             .with_context(|| format!("TCP connect to {}:{}", host, port))?;
         tcp.set_nodelay(true)?;
 
         // Build a ServerName for SNI/handshake.  IP addresses and DNS names
-        // are both handled; the cert is accepted regardless (TOFU).
+        // are both handled; with TOFU the cert is accepted regardless.
         let server_name: rustls::pki_types::ServerName =
             if let Ok(ip) = host.parse::<std::net::IpAddr>() {
                 rustls::pki_types::ServerName::IpAddress(ip.into())
@@ -291,7 +715,63 @@ impl SignalingClient {
             .with_context(|| format!("TLS handshake with {}:{}", host, port))?;
 
         info!("Signaling connected to {}:{} (display_index={})", host, port, display_index);
-        Ok(Self { stream: tls, display_index })
+        Ok(Self {
+            stream: tls,
+            display_index,
+            display_info: None,
+            allow_input: true,
+            preview: false,
+            resume_token: None,
+            files: None,
+            input_queue: SenderQueues::default().input,
+            usage: UsageMeter::new(),
+        })
+    }
+
+    /// Ask the receiver to send input back (the default), or to run the
+    /// session view-only. The receiver may still refuse input — check
+    /// [`HelloAck::allow_input`].
+    pub fn with_input(mut self, allow: bool) -> Self {
+        self.allow_input = allow;
+        self
+    }
+
+    /// Ask the receiver for periodic thumbnails of what it shows (see
+    /// [`SignalingWriter::receiver_previews`]). Receivers without
+    /// [`CAP_PREVIEW`] in [`HelloAck::capabilities`] never send any.
+    pub fn with_preview(mut self, preview: bool) -> Self {
+        self.preview = preview;
+        self
+    }
+
+    /// Resume an earlier session with the [`HelloAck::resume_token`] it was
+    /// given: a receiver that still knows the token accepts `hello` without
+    /// checking the PIN. Unknown or expired tokens fall back to the PIN.
+    pub fn with_resume_token(mut self, token: Option<String>) -> Self {
+        self.resume_token = token;
+        self
+    }
+
+    /// Exchange files with the receiver through `files`. Only receivers
+    /// with [`CAP_FILE_TRANSFER`] in [`HelloAck::capabilities`] do; with
+    /// others `files` is left alone.
+    pub fn with_files(mut self, files: Option<FileTransfers>) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Input events [`start_recv_loop`](Self::start_recv_loop) buffers
+    /// before the receive task waits for them to be taken (see
+    /// [`SenderQueues::input`]).
+    pub fn with_input_queue(mut self, depth: usize) -> Self {
+        self.input_queue = depth.max(1);
+        self
+    }
+
+    /// Bytes this connection has moved since it was opened; cloning shares
+    /// the counters (see [`VideoSender::with_usage`](crate::VideoSender::with_usage)).
+    pub fn usage(&self) -> UsageMeter {
+        self.usage.clone()
     }
 
     // ── Handshake ─────────────────────────────────────────────────────────────
@@ -307,30 +787,65 @@ impl SignalingClient {
         config: StreamConfig,
         pairing_pin: &str,
     ) -> anyhow::Result<HelloAck> {
-        let msg = SignalingMessage::hello(
-            session_id,
-            device_name,
-            config,
-            pairing_pin,
-            self.display_index,
-        );
-        write_msg(&mut self.stream, &msg).await?;
+        let mut msg = SignalingMessage {
+            resume_token: self.resume_token.clone(),
+            ..SignalingMessage::hello(
+                session_id,
+                device_name,
+                config,
+                pairing_pin,
+                self.display_index,
+                self.allow_input,
+                self.preview,
+            )
+        };
+        if self.files.is_some() {
+            msg.capabilities.get_or_insert_with(Vec::new).push(CAP_FILE_TRANSFER.to_owned());
+        }
+        write_msg(&mut self.stream, &msg, &self.usage).await?;
         info!("Sent hello (session={}, display={})", session_id, self.display_index);
 
         // Wait for hello_ack — ignore any non-ack messages (defensive)
         loop {
-            let reply = read_msg(&mut self.stream).await?;
+            let reply = read_msg(&mut self.stream, &self.usage).await?;
             match reply.msg_type {
                 MessageType::HelloAck => {
                     let accepted = reply.accepted.unwrap_or(false);
                     let reason = reply.reason.clone();
                     let sid = reply.session_id.clone();
+                    let capabilities = reply.capabilities.unwrap_or_default();
+                    self.display_info = reply.display_info.clone();
+                    if !capabilities.iter().any(|c| c == CAP_FILE_TRANSFER) {
+                        self.files = None;
+                    }
                     if accepted {
-                        info!("hello_ack: session accepted (id={:?})", sid);
+                        info!("hello_ack: session accepted (id={:?}, capabilities={:?})", sid, capabilities);
+                        if let Some(m) = &reply.display_info {
+                            info!(
+                                "hello_ack: receiver panel {} {} @ {:.2} Hz, scale {}",
+                                m.name, m.resolution, m.refresh_hz, m.scale
+                            );
+                        }
                     } else {
                         warn!("hello_ack: session rejected: {:?}", reason);
                     }
-                    return Ok(HelloAck { accepted, reason, session_id: sid });
+                    return Ok(HelloAck {
+                        accepted,
+                        reason,
+                        session_id: sid,
+                        capabilities,
+                        config: reply.config,
+                        display_info: reply.display_info.clone(),
+                        ports: reply.ports.unwrap_or_default(),
+                        limits: StreamLimits {
+                            max_bitrate_kbps: reply.max_bitrate_kbps,
+                            max_resolution: reply.max_resolution,
+                            max_fps: reply.max_fps,
+                        },
+                        // Older receivers always forward input.
+                        allow_input: reply.allow_input.unwrap_or(true),
+                        resume_token: reply.resume_token,
+                    });
                 }
                 other => {
                     debug!("Ignoring {:?} while waiting for hello_ack", other);
@@ -347,25 +862,77 @@ impl SignalingClient {
     /// - [`SignalingWriter`] — for sending keepalive / stop / config_update
     /// - `Receiver<InputEvent>` — input events forwarded from the receiver
     pub fn start_recv_loop(self) -> (SignalingWriter, mpsc::Receiver<InputEvent>) {
-        let (input_tx, input_rx) = mpsc::channel::<InputEvent>(256);
+        let (input_tx, input_rx) = mpsc::channel::<InputEvent>(self.input_queue);
         let (read_half, write_half) = tokio::io::split(self.stream);
         let display_index = self.display_index;
+        let (display_tx, display_rx) = watch::channel(self.display_info);
+        let (displays_tx, displays_rx) = watch::channel(None);
+        let (link_tx, link_rx) = watch::channel(None);
+        let (keyframe_tx, keyframe_rx) = watch::channel(0);
+        let (blank_tx, blank_rx) = watch::channel(false);
+        let (fps_tx, fps_rx) = watch::channel(None);
+        let (power_tx, power_rx) = watch::channel(None);
+        let (preview_tx, preview_rx) = watch::channel(None);
+        let (pause_tx, pause_rx) = watch::channel(false);
 
-        tokio::spawn(recv_loop(read_half, input_tx, display_index));
+        let files = self.files.clone();
+        let usage = self.usage.clone();
+        let ctx = RecvContext {
+            display_index, usage, input_tx, display_tx, displays_tx, link_tx, keyframe_tx, blank_tx, fps_tx,
+            power_tx, preview_tx, pause_tx, files: files.clone(),
+        };
+        tokio::spawn(async move {
+            recv_loop(read_half, ctx).await;
+            // Nothing more arrives; transfers still running can't finish.
+            if let Some(files) = files {
+                files.abort_all("The receiver disconnected");
+            }
+        });
 
-        (SignalingWriter { writer: write_half }, input_rx)
+        let writer = SignalingWriter {
+            writer: write_half, display_rx, displays_rx, link_rx, keyframe_rx, blank_rx, fps_rx, power_rx, preview_rx,
+            pause_rx, files: self.files, display_index, usage: self.usage,
+        };
+        (writer, input_rx)
     }
 }
 
 // ── Background receive loop ───────────────────────────────────────────────────
 
+/// Where the receive loop hands what the receiver sends; the other ends
+/// live in [`SignalingWriter`].
+struct RecvContext {
+    display_index: u8,
+    usage:         UsageMeter,
+    input_tx:      mpsc::Sender<InputEvent>,
+    display_tx:    watch::Sender<Option<MonitorInfo>>,
+    displays_tx:   watch::Sender<Option<Vec<u8>>>,
+    link_tx:       watch::Sender<Option<LinkQuality>>,
+    keyframe_tx:   watch::Sender<u64>,
+    /// Receiver's capture-pause request.
+    blank_tx:      watch::Sender<bool>,
+    fps_tx:        watch::Sender<Option<u32>>,
+    power_tx:      watch::Sender<Option<PowerState>>,
+    /// Latest receiver thumbnail, JPEG.
+    preview_tx:    watch::Sender<Option<Bytes>>,
+    /// Whether the receiver paused this display.
+    pause_tx:      watch::Sender<bool>,
+    /// `None` when the session doesn't transfer files.
+    files:         Option<FileTransfers>,
+}
+
 async fn recv_loop(
     mut reader: tokio::io::ReadHalf<TlsClientStream>,
-    input_tx: mpsc::Sender<InputEvent>,
-    display_index: u8,
+    ctx: RecvContext,
 ) {
+    let RecvContext {
+        display_index, usage, input_tx, display_tx, displays_tx, link_tx, keyframe_tx, blank_tx, fps_tx,
+        power_tx, preview_tx, pause_tx, files,
+    } = ctx;
+    // Counters from the previous ack, for the per-interval loss estimate.
+    let mut last_counters: Option<FrameCounters> = None;
     loop {
-        match read_msg(&mut reader).await {
+        match read_msg(&mut reader, &usage).await {
             Ok(msg) => match msg.msg_type {
                 MessageType::InputEvent => {
                     if let Some(event) = msg.input_event {
@@ -375,10 +942,89 @@ async fn recv_loop(
                         }
                     }
                 }
+                MessageType::DisplayInfo => {
+                    match &msg.display_info {
+                        Some(m) => info!(
+                            "Receiver panel changed (display={}): {} {} @ {:.2} Hz, scale {}",
+                            display_index, m.name, m.resolution, m.refresh_hz, m.scale
+                        ),
+                        None => info!("Receiver panel disconnected (display={})", display_index),
+                    }
+                    display_tx.send_replace(msg.display_info);
+                }
+                MessageType::DisplaysChanged => {
+                    let displays = msg.displays.unwrap_or_default();
+                    info!("Receiver displays changed (display={}): {:?}", display_index, displays);
+                    displays_tx.send_replace(Some(displays));
+                }
+                MessageType::KeepaliveAck => {
+                    let Some(sent_ms) = msg.timestamp_ms else { continue };
+                    let rtt_ms = now_ms().saturating_sub(sent_ms).min(u32::MAX as u64) as u32;
+                    let loss_pct = match (msg.frame_counters, last_counters) {
+                        (Some(now), Some(earlier)) => now.loss_since(&earlier),
+                        _ => None,
+                    };
+                    last_counters = msg.frame_counters;
+                    debug!("keepalive_ack (display={}): rtt={} ms loss={:?}", display_index, rtt_ms, loss_pct);
+                    link_tx.send_replace(Some(LinkQuality { rtt_ms, loss_pct }));
+                }
+                MessageType::KeyframeRequest => {
+                    debug!("keyframe_request (display={})", display_index);
+                    keyframe_tx.send_modify(|n| *n += 1);
+                }
+                MessageType::Blank => {
+                    let enabled = msg.enabled.unwrap_or(true);
+                    info!("Receiver {} capture (display={})", if enabled { "paused" } else { "resumed" }, display_index);
+                    blank_tx.send_replace(enabled);
+                }
+                MessageType::DisplayState => {
+                    let paused = msg.paused.unwrap_or(true);
+                    info!("Receiver {} the display (display={})", if paused { "paused" } else { "resumed" }, display_index);
+                    pause_tx.send_replace(paused);
+                }
+                MessageType::ConfigUpdate => {
+                    let Some(config) = msg.config else { continue };
+                    info!("Receiver asks for {} fps (display={})", config.target_fps, display_index);
+                    fps_tx.send_replace(Some(config.target_fps));
+                }
+                MessageType::Power => {
+                    let Some(power) = msg.power else { continue };
+                    info!("Receiver is {} (display={})", power, display_index);
+                    power_tx.send_replace(Some(power));
+                }
+                MessageType::Preview => {
+                    use base64::Engine as _;
+                    let Some(image) = msg.image else { continue };
+                    match base64::engine::general_purpose::STANDARD.decode(image) {
+                        Ok(jpeg) => {
+                            preview_tx.send_replace(Some(Bytes::from(jpeg)));
+                        }
+                        Err(e) => debug!("Bad preview from receiver (display={}): {}", display_index, e),
+                    }
+                }
+                MessageType::FileOffer | MessageType::FileAccept | MessageType::FileChunk => {
+                    let Some(files) = &files else { continue };
+                    match msg.into_file() {
+                        Some(message) => {
+                            // Chunks are written to disk: off the runtime, and in order.
+                            let f = files.clone();
+                            let _ = tokio::task::spawn_blocking(move || f.receive(message)).await;
+                        }
+                        None => debug!("Incomplete file-transfer message (display={})", display_index),
+                    }
+                }
                 MessageType::Stop => {
                     info!("Receiver sent stop (display={})", display_index);
                     return;
                 }
+                MessageType::Error => {
+                    warn!(
+                        "Receiver refused a message (display={}): {} — {}",
+                        display_index,
+                        msg.error_code.as_deref().unwrap_or("unknown"),
+                        msg.reason.as_deref().unwrap_or("no reason given")
+                    );
+                }
                 other => {
                     debug!("Recv loop: ignoring {:?} (display={})", other, display_index);
                 }
@@ -391,6 +1037,13 @@ async fn recv_loop(
     }
 }
 
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
 // ── SignalingWriter ───────────────────────────────────────────────────────────
 
 /// Write-only handle to the signaling connection, returned by
@@ -399,12 +1052,89 @@ async fn recv_loop(
 /// Not `Clone` — only one writer at a time.
 pub struct SignalingWriter {
     writer: WriteHalf<TlsClientStream>,
+    display_rx: watch::Receiver<Option<MonitorInfo>>,
+    displays_rx: watch::Receiver<Option<Vec<u8>>>,
+    link_rx: watch::Receiver<Option<LinkQuality>>,
+    keyframe_rx: watch::Receiver<u64>,
+    blank_rx: watch::Receiver<bool>,
+    fps_rx: watch::Receiver<Option<u32>>,
+    power_rx: watch::Receiver<Option<PowerState>>,
+    preview_rx: watch::Receiver<Option<Bytes>>,
+    pause_rx: watch::Receiver<bool>,
+    files: Option<FileTransfers>,
+    display_index: u8,
+    usage: UsageMeter,
 }
 
 impl SignalingWriter {
+    /// Receiver panel for this stream — the `hello_ack` value, updated when
+    /// the receiver reports a monitor hot-plug.
+    pub fn receiver_display(&self) -> watch::Receiver<Option<MonitorInfo>> {
+        self.display_rx.clone()
+    }
+
+    /// Display indices the receiver serves — `None` until it first adds or
+    /// removes a display at runtime.
+    pub fn receiver_displays(&self) -> watch::Receiver<Option<Vec<u8>>> {
+        self.displays_rx.clone()
+    }
+
+    /// Latest RTT / loss sample from `keepalive_ack` — `None` until the
+    /// first ack, and for receivers that never send one.
+    pub fn link_quality(&self) -> watch::Receiver<Option<LinkQuality>> {
+        self.link_rx.clone()
+    }
+
+    /// Number of `keyframe_request`s received; changes each time the
+    /// receiver is waiting for a keyframe and the encoder should force one.
+    pub fn keyframe_requests(&self) -> watch::Receiver<u64> {
+        self.keyframe_rx.clone()
+    }
+
+    /// `true` while the receiver asks for capture to be paused (`blank`);
+    /// nothing should be sent until it turns `false` again.
+    pub fn blank_requests(&self) -> watch::Receiver<bool> {
+        self.blank_rx.clone()
+    }
+
+    /// `true` while the receiver has this display paused (`display_state`);
+    /// nothing should be sent until it turns `false` again. Only receivers
+    /// advertising [`CAP_DISPLAY_STATE`] pause displays.
+    pub fn display_states(&self) -> watch::Receiver<bool> {
+        self.pause_rx.clone()
+    }
+
+    /// Frame-rate ceiling the receiver asked for in a `config_update` (e.g.
+    /// while its window is hidden) — `None` until it asks. Raised again to
+    /// the negotiated rate when the receiver withdraws the request.
+    pub fn fps_requests(&self) -> watch::Receiver<Option<u32>> {
+        self.fps_rx.clone()
+    }
+
+    /// The receiver's power source (`power`) — `None` until it reports
+    /// one; receivers without a battery or without [`CAP_POWER`] never do.
+    pub fn receiver_power(&self) -> watch::Receiver<Option<PowerState>> {
+        self.power_rx.clone()
+    }
+
+    /// Latest JPEG thumbnail of what the receiver shows (`preview`) —
+    /// `None` until the first one, and unless asked for with
+    /// [`SignalingClient::with_preview`].
+    pub fn receiver_previews(&self) -> watch::Receiver<Option<Bytes>> {
+        self.preview_rx.clone()
+    }
+
+    /// Bytes the connection has moved — see [`SignalingClient::usage`].
+    pub fn usage(&self) -> UsageMeter {
+        self.usage.clone()
+    }
+
     /// Send a 1-Hz keepalive heartbeat.
+    ///
+    /// `timestamp_ms` must be Unix-epoch milliseconds: the receiver echoes
+    /// it in `keepalive_ack` and the round trip is measured against it.
     pub async fn send_keepalive(&mut self, timestamp_ms: u64) -> anyhow::Result<()> {
-        write_msg(&mut self.writer, &SignalingMessage::keepalive(timestamp_ms)).await
+        write_msg(&mut self.writer, &SignalingMessage::keepalive(timestamp_ms), &self.usage).await
     }
 
     /// Notify the receiver of a mid-session configuration change.
@@ -413,11 +1143,50 @@ impl SignalingWriter {
         session_id: &str,
         config: StreamConfig,
     ) -> anyhow::Result<()> {
-        write_msg(&mut self.writer, &SignalingMessage::config_update(session_id, config)).await
+        write_msg(&mut self.writer, &SignalingMessage::config_update(session_id, config), &self.usage).await
+    }
+
+    /// Blank (or show again) the receiver's display without ending the
+    /// session. Only receivers advertising [`CAP_BLANK`] understand it.
+    pub async fn send_blank(&mut self, enabled: bool) -> anyhow::Result<()> {
+        write_msg(&mut self.writer, &SignalingMessage::blank(enabled), &self.usage).await
+    }
+
+    /// Report this machine's power source. Only receivers advertising
+    /// [`CAP_POWER`] understand it.
+    pub async fn send_power(&mut self, power: PowerState) -> anyhow::Result<()> {
+        write_msg(&mut self.writer, &SignalingMessage::power(power), &self.usage).await
+    }
+
+    /// Tell the receiver this display was paused or resumed here. Only
+    /// receivers advertising [`CAP_DISPLAY_STATE`] understand it.
+    pub async fn send_display_state(&mut self, paused: bool) -> anyhow::Result<()> {
+        let msg = SignalingMessage::display_state(self.display_index, paused);
+        write_msg(&mut self.writer, &msg, &self.usage).await
+    }
+
+    /// `true` when files go through this connection — see
+    /// [`SignalingClient::with_files`].
+    pub fn sends_files(&self) -> bool {
+        self.files.is_some()
+    }
+
+    /// Send what file transfers have to send by now: answers and offers, and
+    /// the next chunk. Call every [`FILE_CHUNK_INTERVAL`](duallink_core::FILE_CHUNK_INTERVAL)
+    /// while [`sends_files`](Self::sends_files).
+    pub async fn send_file_messages(&mut self) -> anyhow::Result<()> {
+        let Some(files) = self.files.clone() else { return Ok(()) };
+        let now = std::time::Instant::now();
+        // Reads the next chunk from disk.
+        let messages = tokio::task::spawn_blocking(move || files.next_messages(now)).await?;
+        for message in messages {
+            write_msg(&mut self.writer, &SignalingMessage::file(message), &self.usage).await?;
+        }
+        Ok(())
     }
 
     /// Gracefully end the session.
     pub async fn send_stop(&mut self, session_id: &str) -> anyhow::Result<()> {
-        write_msg(&mut self.writer, &SignalingMessage::stop(session_id)).await
+        write_msg(&mut self.writer, &SignalingMessage::stop(session_id), &self.usage).await
     }
 }
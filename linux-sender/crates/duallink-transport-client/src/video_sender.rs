@@ -10,22 +10,51 @@
 //! [12..16] pts_ms        u32 BE  presentation timestamp (milliseconds)
 //! [16]     flags         u8      bit0 = key-frame
 //! [17]     display_index u8      zero-based display stream index
-//! [18..20] reserved      [u8;2]  0x00 0x00
+//! [18]     temporal_layer u8     0 = referenced, ≥ 1 = droppable
+//! [19]     reserved      u8      0x00
 //! [20..]   payload       [u8]    H.264 NAL unit slice
 //! ```
 //!
 //! Packet size = 20 (header) + up to `MAX_PAYLOAD_BYTES` payload ≤ ~1404 bytes.
+//!
+//! # v2 header (receivers advertising `dlnk_v2`)
+//!
+//! ```text
+//! [0..4]   magic         u32 BE  0x444C4E32 ("DLN2")
+//! [4..12]  frame_seq, frag_index, frag_count — as v1
+//! [12..16] clock_epoch   u32 BE  id of this sender's PTS clock origin
+//! [16]     flags         u8      bit0 = key-frame, bit1 = checksum present,
+//!                                bits2–3 = temporal layer, bit4 = NAL end
+//! [17]     display_index u8      as v1
+//! [18..20] checksum      u16 BE  low 16 bits of the frame payload's CRC-32
+//! [20..28] pts_us        u64 BE  presentation timestamp (microseconds)
+//! [28..]   payload       [u8]    H.264 NAL unit slice
+//! ```
+//!
+//! The payload shrinks by 8 bytes so datagrams stay ≤ ~1404 bytes.
+//!
+//! With [`VideoSender::with_checksum`] every fragment carries the checksum
+//! of the whole frame, which the receiver verifies after reassembly and
+//! drops the frame on mismatch instead of feeding it to its decoder.
+//!
+//! [`EncodedFrame::temporal_layer`] goes in every fragment; a receiver whose
+//! decoder falls behind drops frames above layer 0.
+//!
+//! With [`VideoSender::with_nal_fragments`] (v2 only) every NAL unit starts
+//! a new fragment and its last fragment sets the NAL-end flag, so the
+//! receiver can decode a frame's first slices before the rest arrives (see
+//! [`duallink_core::slices`]).
 
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
 use anyhow::Context;
-use duallink_core::EncodedFrame;
+use duallink_core::{frame_checksum, nal_starts, EncodedFrame, UsageMeter};
 use tokio::net::UdpSocket;
 use tracing::debug;
 
-use crate::video_port;
+use crate::{video_port, LocalBinding, PortMap};
 
 // ── Constants ─────────────────────────────────────────────────────────────────
 
@@ -34,6 +63,14 @@ use crate::video_port;
 const MAX_PAYLOAD_BYTES: usize = 1_384;
 const HEADER_SIZE: usize = 20;
 const MAGIC: u32 = 0x444C_4E4B;
+const HEADER_SIZE_V2: usize = 28;
+const MAGIC_V2: u32 = 0x444C_4E32;
+const FLAG_KEYFRAME: u8 = 0x01;
+const FLAG_CHECKSUM: u8 = 0x02;
+const FLAG_LAYER_SHIFT: u8 = 2;
+const FLAG_NAL_END: u8 = 0x10;
+/// Highest temporal layer a v2 header's flag bits can carry.
+const MAX_LAYER_V2: u8 = 3;
 
 // ── VideoSender ───────────────────────────────────────────────────────────────
 
@@ -47,32 +84,51 @@ pub struct VideoSender {
     remote_addr: SocketAddr,
     display_index: u8,
     frame_seq: Arc<AtomicU32>,
+    /// `Some` = send v2 headers with this clock epoch.
+    clock_epoch: Option<u32>,
+    /// Stamp v2 headers with the frame checksum.
+    checksum: bool,
+    /// Fragment v2 frames at NAL unit boundaries and flag NAL ends.
+    nal_fragments: bool,
+    /// Counts every datagram sent; see [`with_usage`](Self::with_usage).
+    usage: Option<UsageMeter>,
 }
 
 impl VideoSender {
     // ── Construction ─────────────────────────────────────────────────────────
 
-    /// Create a sender targeting `host`, auto-resolving the UDP video port
-    /// from `display_index` (port = 7878 + 2 × display_index).
-    pub async fn connect(host: &str, display_index: u8) -> anyhow::Result<Self> {
-        let port = video_port(display_index);
+    /// Create a sender targeting `host`, looking the UDP video port of
+    /// `display_index` up in the receiver's advertised `ports` (e.g.
+    /// [`HelloAck::ports`](crate::HelloAck::ports)).
+    pub async fn connect(host: &str, ports: &PortMap, display_index: u8) -> anyhow::Result<Self> {
+        let port = video_port(ports, display_index);
         Self::connect_with_port(host, port, display_index).await
     }
 
-    /// Create a sender targeting `host:port` with the given display index.
+    /// Create a sender targeting `host:port` with the given display index,
+    /// bound as [`LocalBinding::configured`] says.
     pub async fn connect_with_port(
         host: &str,
         port: u16,
         display_index: u8,
+    ) -> anyhow::Result<Self> {
+        Self::connect_bound(host, port, display_index, &LocalBinding::configured(host)).await
+    }
+
+    /// Create a sender targeting `host:port`, sending from the interface or
+    /// source address in `binding`.
+    pub async fn connect_bound(
+        host: &str,
+        port: u16,
+        display_index: u8,
+        binding: &LocalBinding,
     ) -> anyhow::Result<Self> {
         let remote: SocketAddr = format!("{}:{}", host, port)
             .parse()
             .with_context(|| format!("Parsing remote address {}:{}", host, port))?;
 
-        // Bind to an OS-assigned local port on all interfaces.
-        let socket = UdpSocket::bind("0.0.0.0:0")
-            .await
-            .context("Binding UDP socket")?;
+        // An OS-assigned local port on the bound (or every) interface.
+        let socket = binding.udp_socket(remote).await?;
 
         // "Connect" sets the default destination so we use `send()` below.
         socket.connect(remote).await.context("UDP connect")?;
@@ -82,9 +138,53 @@ impl VideoSender {
             remote_addr: remote,
             display_index,
             frame_seq: Arc::new(AtomicU32::new(0)),
+            clock_epoch: None,
+            checksum: false,
+            nal_fragments: false,
+            usage: None,
         })
     }
 
+    /// Send v2 headers (64-bit µs PTS + clock epoch) — only to receivers
+    /// that advertise [`duallink_core::CAP_DLNK_V2`] in `hello_ack`.
+    ///
+    /// Each `VideoSender` gets a fresh clock epoch, as each session's
+    /// encoder starts its own PTS clock.
+    pub fn with_header_v2(mut self, enabled: bool) -> Self {
+        self.clock_epoch = enabled.then(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u32)
+                .unwrap_or(1)
+        });
+        self
+    }
+
+    /// Put each frame's checksum in the v2 header so the receiver can drop
+    /// frames corrupted in transit. No effect on v1 headers, which have no
+    /// room for it; receivers that predate it ignore the field.
+    pub fn with_checksum(mut self, enabled: bool) -> Self {
+        self.checksum = enabled;
+        self
+    }
+
+    /// Start a fragment at every NAL unit and flag the last one of each, for
+    /// receivers that negotiated
+    /// [`StreamConfig::nal_slices`](duallink_core::StreamConfig::nal_slices).
+    /// No effect on v1 headers. The receiver hands no slice on early from a
+    /// frame with a checksum, so pair it with `with_checksum(false)`.
+    pub fn with_nal_fragments(mut self, enabled: bool) -> Self {
+        self.nal_fragments = enabled;
+        self
+    }
+
+    /// Count every datagram sent in `usage` — usually the session's
+    /// [`SignalingClient::usage`](crate::SignalingClient::usage).
+    pub fn with_usage(mut self, usage: UsageMeter) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
     // ── Sending ───────────────────────────────────────────────────────────────
 
     /// Packetize and send one encoded frame to the receiver.
@@ -97,36 +197,57 @@ impl VideoSender {
         }
 
         let frame_seq = self.frame_seq.fetch_add(1, Ordering::Relaxed);
-        let pts_ms = (frame.timestamp_us / 1_000) as u32;
-        let flags: u8 = if frame.is_keyframe { 0x01 } else { 0x00 };
+        // v1: pts_ms at [12..16]; v2: clock_epoch there and pts_us after the header.
+        let (magic, header_size, pts_word) = match self.clock_epoch {
+            Some(epoch) => (MAGIC_V2, HEADER_SIZE_V2, epoch),
+            None => (MAGIC, HEADER_SIZE, (frame.timestamp_us / 1_000) as u32),
+        };
+        let max_payload = MAX_PAYLOAD_BYTES + HEADER_SIZE - header_size;
+        let checksum = (self.checksum && self.clock_epoch.is_some()).then(|| frame_checksum(data));
+        let mut flags: u8 = if frame.is_keyframe { FLAG_KEYFRAME } else { 0x00 };
+        if checksum.is_some() {
+            flags |= FLAG_CHECKSUM;
+        }
+        // v1: temporal layer at [18]; v2: in the flags, as [18..20] hold the checksum.
+        let reserved = match self.clock_epoch {
+            Some(_) => {
+                flags |= frame.temporal_layer.min(MAX_LAYER_V2) << FLAG_LAYER_SHIFT;
+                checksum.unwrap_or(0).to_be_bytes()
+            }
+            None => [frame.temporal_layer, 0],
+        };
 
         let total_bytes = data.len();
-        let num_fragments = ((total_bytes + MAX_PAYLOAD_BYTES - 1) / MAX_PAYLOAD_BYTES).max(1);
+        let fragments = self.fragments(data, max_payload);
+        let num_fragments = fragments.len();
         let frag_count = num_fragments as u16;
+        let mut bytes_sent = 0;
 
-        for i in 0..num_fragments {
-            let offset = i * MAX_PAYLOAD_BYTES;
-            let length = (MAX_PAYLOAD_BYTES).min(total_bytes - offset);
+        for (i, &(offset, length, nal_end)) in fragments.iter().enumerate() {
             let payload = &data[offset..offset + length];
 
-            let mut datagram = Vec::with_capacity(HEADER_SIZE + length);
+            let mut datagram = Vec::with_capacity(header_size + length);
 
             // magic
-            datagram.extend_from_slice(&MAGIC.to_be_bytes());
+            datagram.extend_from_slice(&magic.to_be_bytes());
             // frame_seq
             datagram.extend_from_slice(&frame_seq.to_be_bytes());
             // frag_index
             datagram.extend_from_slice(&(i as u16).to_be_bytes());
             // frag_count
             datagram.extend_from_slice(&frag_count.to_be_bytes());
-            // pts_ms
-            datagram.extend_from_slice(&pts_ms.to_be_bytes());
+            // pts_ms (v1) / clock_epoch (v2)
+            datagram.extend_from_slice(&pts_word.to_be_bytes());
             // flags
-            datagram.push(flags);
+            datagram.push(if nal_end { flags | FLAG_NAL_END } else { flags });
             // display_index (byte [17])
             datagram.push(self.display_index);
-            // reserved [18..20]
-            datagram.extend_from_slice(&[0x00, 0x00]);
+            // temporal layer (v1) / checksum (v2) [18..20]
+            datagram.extend_from_slice(&reserved);
+            // pts_us [20..28] (v2)
+            if self.clock_epoch.is_some() {
+                datagram.extend_from_slice(&frame.timestamp_us.to_be_bytes());
+            }
             // payload
             datagram.extend_from_slice(payload);
 
@@ -142,6 +263,10 @@ impl VideoSender {
                         frame_seq
                     )
                 })?;
+            bytes_sent += datagram.len();
+        }
+        if let Some(usage) = &self.usage {
+            usage.add_sent(bytes_sent);
         }
 
         debug!(
@@ -156,6 +281,29 @@ impl VideoSender {
         Ok(num_fragments as u32)
     }
 
+    /// `(offset, length, nal_end)` of each fragment of `data`: runs of
+    /// `max_payload` bytes, each NAL unit on its own with
+    /// [`with_nal_fragments`](Self::with_nal_fragments).
+    fn fragments(&self, data: &[u8], max_payload: usize) -> Vec<(usize, usize, bool)> {
+        let by_nal = self.nal_fragments && self.clock_epoch.is_some();
+        let mut units = if by_nal { nal_starts(data) } else { vec![0] };
+        units.push(data.len());
+        let mut fragments = Vec::new();
+        for unit in units.windows(2) {
+            let (start, end) = (unit[0], unit[1]);
+            let mut offset = start;
+            loop {
+                let length = max_payload.min(end - offset);
+                offset += length;
+                fragments.push((offset - length, length, by_nal && offset == end));
+                if offset == end {
+                    break;
+                }
+            }
+        }
+        fragments
+    }
+
     // ── Diagnostics ───────────────────────────────────────────────────────────
 
     /// Remote address this sender is targeting.
@@ -0,0 +1,136 @@
+//! Wake-on-LAN helpers (sender role).
+//!
+//! Receivers advertise the MAC address of their LAN interface in the mDNS
+//! `mac` TXT key.  Sender UIs cache it so a sleeping receiver can be woken
+//! with a magic packet, then poll the signaling port until the receiver
+//! service is accepting connections again.
+//!
+//! # Magic packet layout
+//!
+//! ```text
+//! [0..6]    0xFF × 6
+//! [6..102]  target MAC address × 16
+//! ```
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use tokio::net::{TcpStream, UdpSocket};
+use tracing::{debug, info};
+
+/// Standard "discard" port used for WoL magic packets.
+pub const WOL_PORT: u16 = 9;
+
+/// Size of a WoL magic packet in bytes (6 sync bytes + 16 × MAC).
+pub const MAGIC_PACKET_SIZE: usize = 102;
+
+/// Parse a MAC address written as `aa:bb:cc:dd:ee:ff` or `aa-bb-cc-dd-ee-ff`.
+pub fn parse_mac(s: &str) -> Option<[u8; 6]> {
+    let mut mac = [0u8; 6];
+    let mut parts = s.trim().split([':', '-']);
+    for byte in mac.iter_mut() {
+        *byte = u8::from_str_radix(parts.next()?, 16).ok()?;
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(mac)
+}
+
+/// Build the 102-byte magic packet for `mac`.
+pub fn magic_packet(mac: [u8; 6]) -> [u8; MAGIC_PACKET_SIZE] {
+    let mut packet = [0xFFu8; MAGIC_PACKET_SIZE];
+    for chunk in packet[6..].chunks_exact_mut(6) {
+        chunk.copy_from_slice(&mac);
+    }
+    packet
+}
+
+/// Broadcast a magic packet for `mac` on the local subnet (UDP port 9).
+pub async fn send_magic_packet(mac: [u8; 6]) -> anyhow::Result<()> {
+    let socket = UdpSocket::bind("0.0.0.0:0")
+        .await
+        .context("Binding WoL socket")?;
+    socket.set_broadcast(true).context("Enabling SO_BROADCAST")?;
+
+    let target = SocketAddr::from((Ipv4Addr::BROADCAST, WOL_PORT));
+    socket
+        .send_to(&magic_packet(mac), target)
+        .await
+        .with_context(|| format!("Sending WoL magic packet to {}", target))?;
+
+    info!(
+        "WoL magic packet sent for {:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+        mac[0], mac[1], mac[2], mac[3], mac[4], mac[5]
+    );
+    Ok(())
+}
+
+/// Poll `host:port` with TCP connects until one succeeds or `timeout` elapses.
+///
+/// Returns `true` once the port accepts a connection.
+pub async fn wait_until_reachable(host: &str, port: u16, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        let attempt = tokio::time::timeout(
+            Duration::from_secs(1),
+            TcpStream::connect((host, port)),
+        )
+        .await;
+        if let Ok(Ok(_)) = attempt {
+            info!("Receiver {}:{} is reachable", host, port);
+            return true;
+        }
+        debug!("Receiver {}:{} not reachable yet", host, port);
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+    false
+}
+
+/// Send a magic packet for `mac`, then wait for the receiver's signaling port
+/// (`port`) on `host` to come up.
+///
+/// Returns the time it took for the receiver to become reachable, or an error
+/// if the MAC is invalid, the packet could not be sent, or `timeout` elapsed.
+pub async fn wake_receiver(
+    mac: &str,
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> anyhow::Result<Duration> {
+    let mac = parse_mac(mac).with_context(|| format!("Invalid MAC address: {mac:?}"))?;
+    let started = Instant::now();
+    send_magic_packet(mac).await?;
+
+    if wait_until_reachable(host, port, timeout).await {
+        Ok(started.elapsed())
+    } else {
+        anyhow::bail!("Receiver {}:{} did not come up within {:?}", host, port, timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_colon_and_dash_separated_macs() {
+        let expected = [0xaa, 0xbb, 0xcc, 0x01, 0x02, 0x03];
+        assert_eq!(parse_mac("aa:bb:cc:01:02:03"), Some(expected));
+        assert_eq!(parse_mac("AA-BB-CC-01-02-03"), Some(expected));
+        assert_eq!(parse_mac("aa:bb:cc:01:02"), None);
+        assert_eq!(parse_mac("aa:bb:cc:01:02:03:04"), None);
+        assert_eq!(parse_mac("zz:bb:cc:01:02:03"), None);
+    }
+
+    #[test]
+    fn magic_packet_layout() {
+        let mac = [1, 2, 3, 4, 5, 6];
+        let packet = magic_packet(mac);
+        assert_eq!(&packet[..6], &[0xFF; 6]);
+        for chunk in packet[6..].chunks_exact(6) {
+            assert_eq!(chunk, &mac);
+        }
+    }
+}
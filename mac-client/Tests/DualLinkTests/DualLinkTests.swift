@@ -64,3 +64,40 @@ final class ConnectionStateTests: XCTestCase {
         XCTAssertFalse(ConnectionState.connecting(peer: peer, attempt: 1).isActive)
     }
 }
+
+// MARK: - InputEvent Wire Schema Tests
+
+/// Decodes the golden fixtures shared with the Rust receivers
+/// (`linux-receiver/crates/duallink-core/fixtures`) and checks that every
+/// event encodes back to the exact wire form.
+final class InputEventSchemaTests: XCTestCase {
+
+    private func fixtures(version: Int) throws -> [[String: Any]] {
+        let url = URL(fileURLWithPath: #filePath)
+            .deletingLastPathComponent()   // DualLinkTests
+            .deletingLastPathComponent()   // Tests
+            .deletingLastPathComponent()   // mac-client
+            .deletingLastPathComponent()   // repository root
+            .appendingPathComponent("linux-receiver/crates/duallink-core/fixtures/input-events-v\(version).json")
+        let data = try Data(contentsOf: url)
+        return try XCTUnwrap(JSONSerialization.jsonObject(with: data) as? [[String: Any]])
+    }
+
+    func test_goldenFixtures_roundTrip() throws {
+        for fixture in try fixtures(version: 1) {
+            let wire = try JSONSerialization.data(withJSONObject: fixture)
+            let event = try JSONDecoder().decode(InputEvent.self, from: wire)
+            let encoded = try JSONSerialization.jsonObject(with: JSONEncoder().encode(event)) as? [String: Any]
+            XCTAssertEqual(encoded.map { NSDictionary(dictionary: $0) }, NSDictionary(dictionary: fixture), "\(fixture)")
+        }
+    }
+
+    func test_additiveFields_areIgnored() throws {
+        let wire = Data(#"{"kind":"mouse_move","x":0.5,"y":0.25,"pressure":0.8}"#.utf8)
+        guard case .mouseMove(let x, let y) = try JSONDecoder().decode(InputEvent.self, from: wire) else {
+            return XCTFail("expected mouse_move")
+        }
+        XCTAssertEqual(x, 0.5)
+        XCTAssertEqual(y, 0.25)
+    }
+}
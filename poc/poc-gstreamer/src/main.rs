@@ -3,9 +3,16 @@
 // Measures per-frame decode latency for each available hardware accelerator.
 // Pipeline: videotestsrc → x264enc → tee → {decoder → appsink} (one at a time)
 //
-// Run: cargo run --release
-// Output: per-decoder avg/p50/p99 latency + fps + verdict
+// Encoder mode (`--encoders`) measures the sender side instead:
+// Pipeline: videotestsrc → NV12 → ENCODER → h264parse → appsink
+// Per-frame encode latency is the wallclock delta between a buffer entering the
+// encoder sink pad and the encoded buffer with the same PTS reaching appsink.
+//
+// Run: cargo run --release                 (decoders)
+//      cargo run --release -- --encoders   (encoders)
+// Output: per-element avg/p50/p99 latency + fps (+ CPU% for encoders) + verdict
 
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -20,6 +27,8 @@ const WIDTH: u32 = 1920;
 const HEIGHT: u32 = 1080;
 const FPS: u32 = 30;
 const LATENCY_TARGET_MS: f64 = 20.0; // DualLink Wi-Fi budget leaves ~20ms for decode
+const ENCODE_TARGET_MS: f64 = 10.0; // sender budget: capture + encode must fit one 60fps frame
+const ENCODE_BITRATE_KBPS: u32 = 8000;
 
 // ── Decoder descriptors ───────────────────────────────────────────────────────
 
@@ -57,6 +66,49 @@ static DECODERS: &[DecoderDesc] = &[
     },
 ];
 
+// ── Encoder descriptors ───────────────────────────────────────────────────────
+
+struct EncoderDesc {
+    name: &'static str,
+    element: &'static str,
+    tier: &'static str,
+    /// Low-latency tuning properties (bitrate is appended separately, kbit/s).
+    props: &'static str,
+}
+
+static ENCODERS: &[EncoderDesc] = &[
+    EncoderDesc {
+        name: "VA-API vaapih264lpenc (HW, low-power)",
+        element: "vaapih264lpenc",
+        tier: "primary",
+        props: "rate-control=cbr tune=low-power keyframe-period=60",
+    },
+    EncoderDesc {
+        name: "VA-API vaapih264enc (HW)",
+        element: "vaapih264enc",
+        tier: "primary",
+        props: "rate-control=cbr quality-level=6 keyframe-period=60",
+    },
+    EncoderDesc {
+        name: "NVIDIA nvh264enc (HW)",
+        element: "nvh264enc",
+        tier: "primary",
+        props: "preset=low-latency-hq rc-mode=cbr zerolatency=true gop-size=60",
+    },
+    EncoderDesc {
+        name: "Media Foundation mfh264enc (HW, Windows)",
+        element: "mfh264enc",
+        tier: "fallback",
+        props: "rc-mode=cbr low-latency=true gop-size=60",
+    },
+    EncoderDesc {
+        name: "Software x264enc (CPU)",
+        element: "x264enc",
+        tier: "last_resort",
+        props: "tune=zerolatency speed-preset=veryfast key-int-max=30",
+    },
+];
+
 // ── Result types ─────────────────────────────────────────────────────────────
 
 #[derive(Debug)]
@@ -74,6 +126,21 @@ struct BenchResult {
     meets_target: bool,
 }
 
+#[derive(Debug)]
+struct EncodeResult {
+    name: String,
+    element: String,
+    tier: String,
+    frames_encoded: u32,
+    avg_fps: f64,
+    avg_encode_ms: f64,
+    p50_ms: f64,
+    p99_ms: f64,
+    /// Process CPU usage over the run (100% = one core). `None` if unsupported.
+    cpu_pct: Option<f64>,
+    meets_target: bool,
+}
+
 // ── Check element availability ────────────────────────────────────────────────
 
 fn has_element(name: &str) -> bool {
@@ -223,6 +290,150 @@ fn run_benchmark(desc: &DecoderDesc) -> Result<BenchResult> {
     })
 }
 
+// ── Encoder benchmark ────────────────────────────────────────────────────────
+//
+// Pipeline: videotestsrc → videoconvert → NV12 → ENCODER(name=enc) → h264parse → appsink
+//
+// A pad probe on the encoder sink pad records the wallclock time each raw
+// buffer enters the encoder, keyed by PTS. appsink looks up the same PTS on the
+// encoded output, giving true per-frame encode latency rather than inter-frame time.
+
+fn run_encode_benchmark(desc: &EncoderDesc) -> Result<EncodeResult> {
+    let pipeline_str = format!(
+        "videotestsrc num-buffers={frames} is-live=false pattern=smpte \
+         ! video/x-raw,width={w},height={h},framerate={fps}/1 \
+         ! videoconvert ! video/x-raw,format=NV12 \
+         ! {encoder} name=enc {props} bitrate={bitrate} \
+         ! h264parse \
+         ! appsink name=mysink max-buffers=10 drop=false sync=false",
+        frames = FRAMES,
+        w = WIDTH,
+        h = HEIGHT,
+        fps = FPS,
+        encoder = desc.element,
+        props = desc.props,
+        bitrate = ENCODE_BITRATE_KBPS,
+    );
+
+    let pipeline = gst::parse::launch(&pipeline_str)
+        .context(format!("Failed to build pipeline for {}", desc.element))?
+        .dynamic_cast::<gst::Pipeline>()
+        .map_err(|_| anyhow::anyhow!("Not a pipeline"))?;
+
+    let appsink = pipeline
+        .by_name("mysink")
+        .context("No appsink named 'mysink'")?
+        .dynamic_cast::<AppSink>()
+        .map_err(|_| anyhow::anyhow!("Not an appsink"))?;
+
+    let enc_sink_pad = pipeline
+        .by_name("enc")
+        .context("No encoder named 'enc'")?
+        .static_pad("sink")
+        .context("Encoder has no sink pad")?;
+
+    // PTS (ns) → time the raw buffer entered the encoder
+    let entered: Arc<Mutex<HashMap<u64, Instant>>> = Arc::new(Mutex::new(HashMap::new()));
+    let entered_probe = Arc::clone(&entered);
+    let latencies: Arc<Mutex<Vec<f64>>> = Arc::new(Mutex::new(Vec::with_capacity(FRAMES as usize)));
+    let latencies_clone = Arc::clone(&latencies);
+
+    enc_sink_pad.add_probe(gst::PadProbeType::BUFFER, move |_pad, info| {
+        if let Some(pts) = info.buffer().and_then(|b| b.pts()) {
+            entered_probe.lock().unwrap().insert(pts.nseconds(), Instant::now());
+        }
+        gst::PadProbeReturn::Ok
+    });
+
+    appsink.set_callbacks(
+        gstreamer_app::AppSinkCallbacks::builder()
+            .new_sample(move |sink| {
+                let sample = sink.pull_sample().map_err(|_| gst::FlowError::Error)?;
+                let pts = sample.buffer().and_then(|b| b.pts());
+                if let Some(pts) = pts {
+                    if let Some(t0) = entered.lock().unwrap().remove(&pts.nseconds()) {
+                        latencies_clone
+                            .lock()
+                            .unwrap()
+                            .push(t0.elapsed().as_micros() as f64 / 1000.0);
+                    }
+                }
+                Ok(gst::FlowSuccess::Ok)
+            })
+            .build(),
+    );
+
+    let cpu_start = process_cpu_time();
+    let wall_start = Instant::now();
+    pipeline.set_state(gst::State::Playing)?;
+
+    let bus = pipeline.bus().context("No bus")?;
+    loop {
+        if let Some(msg) = bus.timed_pop(gst::ClockTime::from_seconds(30)) {
+            match msg.view() {
+                gst::MessageView::Eos(_) => break,
+                gst::MessageView::Error(err) => {
+                    pipeline.set_state(gst::State::Null)?;
+                    return Err(anyhow::anyhow!(
+                        "Pipeline error: {} — {:?}",
+                        err.error(),
+                        err.debug()
+                    ));
+                }
+                _ => {}
+            }
+        } else {
+            pipeline.set_state(gst::State::Null)?;
+            return Err(anyhow::anyhow!("Pipeline timed out after 30s"));
+        }
+    }
+
+    let wall = wall_start.elapsed();
+    let cpu_end = process_cpu_time();
+    pipeline.set_state(gst::State::Null)?;
+
+    let mut samples = latencies.lock().unwrap().clone();
+    let frames_encoded = samples.len() as u32;
+    if frames_encoded == 0 {
+        return Err(anyhow::anyhow!("No frames were encoded"));
+    }
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let avg_encode_ms = samples.iter().sum::<f64>() / samples.len() as f64;
+    let cpu_pct = match (cpu_start, cpu_end) {
+        (Some(a), Some(b)) => Some((b - a).as_secs_f64() / wall.as_secs_f64() * 100.0),
+        _ => None,
+    };
+
+    Ok(EncodeResult {
+        name: desc.name.to_string(),
+        element: desc.element.to_string(),
+        tier: desc.tier.to_string(),
+        frames_encoded,
+        avg_fps: frames_encoded as f64 / wall.as_secs_f64(),
+        avg_encode_ms,
+        p50_ms: percentile(&samples, 50.0),
+        p99_ms: percentile(&samples, 99.0),
+        cpu_pct,
+        meets_target: avg_encode_ms <= ENCODE_TARGET_MS,
+    })
+}
+
+/// Total user + system CPU time consumed by this process so far.
+///
+/// Reads `/proc/self/stat` (fields 14/15, in clock ticks; USER_HZ is 100 on
+/// every mainstream Linux distro). Returns `None` on other platforms.
+fn process_cpu_time() -> Option<Duration> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // Skip past "pid (comm)" — comm may contain spaces.
+    let rest = &stat[stat.rfind(')')? + 2..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    // `rest` starts at field 3 (state), so utime/stime are at indices 11/12.
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(Duration::from_millis((utime + stime) * 10))
+}
+
 fn percentile(sorted: &[f64], pct: f64) -> f64 {
     if sorted.is_empty() {
         return 0.0;
@@ -236,6 +447,14 @@ fn percentile(sorted: &[f64], pct: f64) -> f64 {
 fn main() -> Result<()> {
     gst::init().context("Failed to initialize GStreamer")?;
 
+    if std::env::args().any(|a| a == "--encoders") {
+        encode_main()
+    } else {
+        decode_main()
+    }
+}
+
+fn decode_main() -> Result<()> {
     println!("=== DualLink Sprint 0.3 — GStreamer H.264 Decode Benchmark ===");
     println!();
     println!(
@@ -373,3 +592,142 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+fn encode_main() -> Result<()> {
+    println!("=== DualLink — GStreamer H.264 Encode Benchmark ===");
+    println!();
+    println!(
+        "Config: {}x{} @ {}fps, {}kbps, {} frames per encoder",
+        WIDTH, HEIGHT, FPS, ENCODE_BITRATE_KBPS, FRAMES
+    );
+    println!("Target: avg encode latency < {}ms (sender budget)", ENCODE_TARGET_MS);
+    println!();
+
+    println!("[1/3] Probing encoder availability...");
+    println!();
+    let available: Vec<&EncoderDesc> = ENCODERS
+        .iter()
+        .filter(|d| {
+            let found = has_element(d.element);
+            let icon = if found { "✅" } else { "❌" };
+            println!("  {} {:<40} ({})", icon, d.name, d.element);
+            found
+        })
+        .collect();
+
+    println!();
+    if available.is_empty() {
+        eprintln!("No encoders available! Run ./setup.sh first.");
+        return Ok(());
+    }
+
+    println!("[2/3] Running benchmarks ({} frames each)...", FRAMES);
+    println!();
+
+    let mut results: Vec<EncodeResult> = Vec::new();
+
+    for desc in &available {
+        print!("  Benchmarking {} ... ", desc.name);
+        std::io::Write::flush(&mut std::io::stdout()).ok();
+
+        match run_encode_benchmark(desc) {
+            Ok(result) => {
+                let verdict = if result.meets_target { "✅" } else { "⚠️" };
+                println!(
+                    "{} avg={:.1}ms  p50={:.1}ms  p99={:.1}ms  fps={:.1}  cpu={}",
+                    verdict,
+                    result.avg_encode_ms,
+                    result.p50_ms,
+                    result.p99_ms,
+                    result.avg_fps,
+                    fmt_cpu(result.cpu_pct)
+                );
+                results.push(result);
+            }
+            Err(e) => {
+                println!("❌ FAILED: {}", e);
+                results.push(EncodeResult {
+                    name: desc.name.to_string(),
+                    element: desc.element.to_string(),
+                    tier: desc.tier.to_string(),
+                    frames_encoded: 0,
+                    avg_fps: 0.0,
+                    avg_encode_ms: f64::MAX,
+                    p50_ms: 0.0,
+                    p99_ms: 0.0,
+                    cpu_pct: None,
+                    meets_target: false,
+                });
+            }
+        }
+    }
+
+    println!();
+
+    println!("[3/3] Results Summary");
+    println!();
+    println!(
+        "  {:<40} {:>8}  {:>8}  {:>8}  {:>8}  {:>7}  {:>6}",
+        "Encoder", "avg(ms)", "p50(ms)", "p99(ms)", "fps", "cpu%", "target"
+    );
+    println!("  {}", "-".repeat(91));
+
+    for r in &results {
+        if r.frames_encoded == 0 {
+            println!(
+                "  {:<40} {:>8}  {:>8}  {:>8}  {:>8}  {:>7}  {:>6}",
+                r.name, "FAIL", "—", "—", "—", "—", "❌"
+            );
+        } else {
+            let target = if r.meets_target { "✅" } else { "⚠️ SLOW" };
+            println!(
+                "  {:<40} {:>8.1}  {:>8.1}  {:>8.1}  {:>8.1}  {:>7}  {:>6}",
+                r.name, r.avg_encode_ms, r.p50_ms, r.p99_ms, r.avg_fps, fmt_cpu(r.cpu_pct), target
+            );
+        }
+    }
+
+    println!();
+
+    // Recommend encoder priority: working elements, hardware tiers first,
+    // then by measured latency within each tier.
+    println!("Recommended encoder priority for duallink-linux-sender / duallink-windows-sender:");
+    println!();
+
+    let tier_rank = |tier: &str| match tier {
+        "primary" => 0,
+        "fallback" => 1,
+        _ => 2,
+    };
+    let mut ranked: Vec<&EncodeResult> = results.iter().filter(|r| r.frames_encoded > 0).collect();
+    ranked.sort_by(|a, b| {
+        tier_rank(&a.tier)
+            .cmp(&tier_rank(&b.tier))
+            .then(a.avg_encode_ms.partial_cmp(&b.avg_encode_ms).unwrap())
+    });
+
+    for (i, r) in ranked.iter().enumerate() {
+        let label = match r.tier.as_str() {
+            "primary" => "PRIMARY",
+            "fallback" => "FALLBACK",
+            _ => "LAST RESORT",
+        };
+        println!(
+            "  {}. {}: {}  (avg {:.1}ms, cpu {})",
+            i + 1,
+            label,
+            r.element,
+            r.avg_encode_ms,
+            fmt_cpu(r.cpu_pct)
+        );
+    }
+
+    println!();
+    println!("→ Update ENCODER_PRIORITY in duallink-linux-sender/src/encoder.rs to match");
+
+    Ok(())
+}
+
+fn fmt_cpu(cpu_pct: Option<f64>) -> String {
+    cpu_pct.map_or_else(|| "n/a".to_string(), |c| format!("{:.0}%", c))
+}
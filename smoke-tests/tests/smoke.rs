@@ -0,0 +1,330 @@
+//! Sender ↔ receiver smoke tests over loopback.
+
+use std::time::Duration;
+
+use duallink_core::{InputEvent, PowerState, Resolution, StreamConfig};
+use duallink_smoke_tests::{expect_event, expect_frame, test_frames, Harness};
+use duallink_transport::{DisplayPhase, SignalingEvent};
+
+fn config() -> StreamConfig {
+    StreamConfig { resolution: Resolution { width: 320, height: 240 }, ..StreamConfig::default() }
+}
+
+#[tokio::test]
+async fn frames_are_delivered_intact() {
+    let mut h = Harness::start(1).await.unwrap();
+    let pin = h.startup.pairing_pin.clone();
+    let sender = h.connect(0, &pin, config()).await.unwrap();
+    assert!(sender.ack.accepted, "rejected: {:?}", sender.ack.reason);
+    expect_event(h.display(0), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+
+    let frames = test_frames(60, 320, 240);
+    assert!(frames[0].is_keyframe);
+    for frame in &frames {
+        sender.video.send_frame(frame).await.unwrap();
+        // Loopback never drops, but the socket buffer can overflow.
+        tokio::time::sleep(Duration::from_millis(2)).await;
+    }
+    for sent in &frames {
+        let got = expect_frame(h.display(0)).await.unwrap();
+        assert_eq!(got.data, sent.data);
+        assert_eq!(got.is_keyframe, sent.is_keyframe);
+    }
+    assert_eq!(h.receiver.frame_stats(0).unwrap().lost, 0);
+}
+
+#[tokio::test]
+async fn wrong_pin_is_rejected() {
+    let mut h = Harness::start(1).await.unwrap();
+    let wrong = if h.startup.pairing_pin == "000000" { "111111" } else { "000000" };
+    let sender = h.connect(0, wrong, config()).await.unwrap();
+    assert!(!sender.ack.accepted);
+    assert!(sender.writer.is_none());
+
+    // No session was started.
+    let event = tokio::time::timeout(Duration::from_millis(500), h.display(0).event_rx.recv()).await;
+    assert!(!matches!(event, Ok(Some(SignalingEvent::SessionStarted { .. }))));
+}
+
+#[tokio::test]
+async fn config_update_reaches_the_receiver() {
+    let mut h = Harness::start(1).await.unwrap();
+    let pin = h.startup.pairing_pin.clone();
+    let mut sender = h.connect(0, &pin, config()).await.unwrap();
+    expect_event(h.display(0), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+
+    let updated = StreamConfig { resolution: Resolution { width: 640, height: 480 }, ..config() };
+    let id = sender.session_id.clone();
+    sender.writer().send_config_update(&id, updated).await.unwrap();
+    let event = expect_event(h.display(0), |e| matches!(e, SignalingEvent::ConfigUpdated { .. })).await.unwrap();
+    let SignalingEvent::ConfigUpdated { config } = event else { unreachable!() };
+    assert_eq!(config.resolution, Resolution { width: 640, height: 480 });
+
+    // Frames at the new size keep flowing in the same session.
+    let frames = test_frames(2, 640, 480);
+    sender.video.send_frame(&frames[0]).await.unwrap();
+    assert_eq!(expect_frame(h.display(0)).await.unwrap().data, frames[0].data);
+}
+
+#[tokio::test]
+async fn sender_can_reconnect_after_stop() {
+    let mut h = Harness::start(1).await.unwrap();
+    let pin = h.startup.pairing_pin.clone();
+
+    for round in 0..2 {
+        let mut sender = h.connect(0, &pin, config()).await.unwrap();
+        assert!(sender.ack.accepted, "round {round} rejected: {:?}", sender.ack.reason);
+        expect_event(h.display(0), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+
+        let frame = &test_frames(1, 320, 240)[0];
+        sender.video.send_frame(frame).await.unwrap();
+        assert_eq!(expect_frame(h.display(0)).await.unwrap().data, frame.data);
+
+        sender.stop().await.unwrap();
+        let event = expect_event(h.display(0), |e| matches!(e, SignalingEvent::SessionStopped { .. })).await.unwrap();
+
+        // Each session's usage starts from zero and covers the video.
+        let SignalingEvent::SessionStopped { summary, .. } = event else { unreachable!() };
+        let sent = frame.data.len() as u64;
+        assert!(summary.bytes_received >= sent && summary.bytes_received < 2 * sent + 4096, "{summary:?}");
+        assert!(summary.bytes_sent > 0, "hello_ack not counted: {summary:?}");
+    }
+}
+
+#[tokio::test]
+async fn handle_tracks_sessions_and_regenerates_the_pin() {
+    let mut h = Harness::start(1).await.unwrap();
+    let handle = h.receiver.handle();
+    let mut changes = handle.subscribe();
+    let old_pin = handle.state().pairing_pin;
+    assert_eq!(old_pin, h.startup.pairing_pin);
+    assert_eq!(handle.state().display(0).unwrap().phase, DisplayPhase::Listening);
+
+    let _sender = h.connect(0, &old_pin, config()).await.unwrap();
+    expect_event(h.display(0), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+    changes.changed().await.unwrap();
+    let state = changes.borrow_and_update().clone();
+    let peer = state.display(0).unwrap().phase.peer().unwrap();
+    assert_eq!(peer.device_name, "smoke-test");
+    assert_eq!(peer.config.resolution, config().resolution);
+
+    // A new PIN locks out new senders; the running session goes on.
+    let new_pin = handle.regenerate_pin();
+    assert_ne!(new_pin, old_pin);
+    let late = h.connect(0, &old_pin, config()).await.unwrap();
+    assert!(!late.ack.accepted);
+
+    assert!(handle.disconnect_client(0));
+    expect_event(h.display(0), |e| matches!(e, SignalingEvent::ClientDisconnected)).await.unwrap();
+    let listening = changes.wait_for(|s| s.display(0).unwrap().phase == DisplayPhase::Listening);
+    tokio::time::timeout(Duration::from_secs(5), listening).await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn displays_are_independent() {
+    let mut h = Harness::start(2).await.unwrap();
+    let pin = h.startup.pairing_pin.clone();
+    let senders = [h.connect(0, &pin, config()).await.unwrap(), h.connect(1, &pin, config()).await.unwrap()];
+    for n in 0..2 {
+        expect_event(h.display(n), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+    }
+
+    // hello_ack advertises the ephemeral ports actually bound.
+    for (sender, ch) in senders.iter().zip(&h.channels) {
+        let ports = sender.ack.ports.get(ch.display_index).unwrap();
+        assert_eq!((ports.video, ports.signaling), (ch.config.video_port, ch.config.signaling_port));
+        assert_eq!(sender.ack.ports, h.receiver.port_map());
+    }
+
+    // Different keyframes (sizes differ) to each display.
+    let frames = [test_frames(1, 320, 240).remove(0), test_frames(1, 640, 480).remove(0)];
+    for (sender, frame) in senders.iter().zip(&frames) {
+        sender.video.send_frame(frame).await.unwrap();
+    }
+    for (n, frame) in frames.iter().enumerate() {
+        assert_eq!(expect_frame(h.display(n as u8)).await.unwrap().data, frame.data);
+    }
+}
+
+#[tokio::test]
+async fn displays_belong_to_their_sender() {
+    let mut h = Harness::start(2).await.unwrap();
+    let pin = h.startup.pairing_pin.clone();
+    let mut mac = h.connect_as(0, "Mac", &pin, config()).await.unwrap();
+    let mut pc = h.connect_as(1, "PC", &pin, config()).await.unwrap();
+    for n in 0..2 {
+        expect_event(h.display(n), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+    }
+
+    // Display 0 is the Mac's until it leaves.
+    let intruder = h.connect_as(0, "PC", &pin, config()).await.unwrap();
+    assert!(!intruder.ack.accepted);
+    assert_eq!(intruder.ack.reason.as_deref(), Some("Display 0 is in use by 'Mac'"));
+
+    // Each display's input reaches only its own sender.
+    let to_mac = InputEvent::KeyUp { keycode: 1 };
+    let to_pc = InputEvent::KeyUp { keycode: 2 };
+    h.input.for_display(1).try_send(to_pc.clone()).unwrap();
+    h.input.for_display(0).try_send(to_mac.clone()).unwrap();
+    assert_eq!(pc.expect_input().await.unwrap(), to_pc);
+    assert_eq!(mac.expect_input().await.unwrap(), to_mac);
+    assert!(h.input.for_display(5).try_send(to_pc).is_err());
+}
+
+#[tokio::test]
+async fn view_only_sessions_are_negotiated() {
+    let mut h = Harness::start(1).await.unwrap();
+    let pin = h.startup.pairing_pin.clone();
+    h.receiver.set_allow_input(false);
+
+    let sender = h.connect(0, &pin, config()).await.unwrap();
+    assert!(sender.ack.accepted, "rejected: {:?}", sender.ack.reason);
+    assert!(!sender.ack.allow_input);
+    let event = expect_event(h.display(0), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+    assert!(matches!(event, SignalingEvent::SessionStarted { allow_input: false, .. }));
+}
+
+#[tokio::test]
+async fn blank_works_both_ways() {
+    let mut h = Harness::start(1).await.unwrap();
+    let pin = h.startup.pairing_pin.clone();
+    let mut sender = h.connect(0, &pin, config()).await.unwrap();
+    assert!(sender.ack.capabilities.iter().any(|c| c == duallink_core::CAP_BLANK));
+    expect_event(h.display(0), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+
+    // Sender → receiver: blank the display.
+    sender.writer().send_blank(true).await.unwrap();
+    let event = expect_event(h.display(0), |e| matches!(e, SignalingEvent::Blank { .. })).await.unwrap();
+    assert!(matches!(event, SignalingEvent::Blank { enabled: true }));
+
+    // Receiver → sender: pause capture, then resume.
+    let mut requests = sender.writer().blank_requests();
+    h.display(0).blank.request(true);
+    tokio::time::timeout(Duration::from_secs(5), requests.changed()).await.unwrap().unwrap();
+    assert!(*requests.borrow_and_update());
+    h.display(0).blank.request(false);
+    tokio::time::timeout(Duration::from_secs(5), requests.changed()).await.unwrap().unwrap();
+    assert!(!*requests.borrow());
+}
+
+#[tokio::test]
+async fn displays_pause_from_either_end() {
+    let mut h = Harness::start(2).await.unwrap();
+    let pin = h.startup.pairing_pin.clone();
+    let mut sender = h.connect(1, &pin, config()).await.unwrap();
+    assert!(sender.ack.capabilities.iter().any(|c| c == duallink_core::CAP_DISPLAY_STATE));
+    expect_event(h.display(1), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+
+    // Sender → receiver: the pause is recorded and not echoed back.
+    let mut states = sender.writer().display_states();
+    sender.writer().send_display_state(true).await.unwrap();
+    let event = expect_event(h.display(1), |e| matches!(e, SignalingEvent::DisplayState { .. })).await.unwrap();
+    assert!(matches!(event, SignalingEvent::DisplayState { paused: true }));
+    assert!(h.display(1).pause.is_paused());
+    assert!(!h.display(0).pause.is_paused());
+
+    // Receiver → sender: resume, then pause again.
+    h.display(1).pause.set_paused(false);
+    tokio::time::timeout(Duration::from_secs(5), states.changed()).await.unwrap().unwrap();
+    assert!(!*states.borrow_and_update());
+    h.display(1).pause.set_paused(true);
+    tokio::time::timeout(Duration::from_secs(5), states.changed()).await.unwrap().unwrap();
+    assert!(*states.borrow());
+}
+
+#[tokio::test]
+async fn recorded_input_replays_to_the_sender() {
+    let mut h = Harness::start(1).await.unwrap();
+    let pin = h.startup.pairing_pin.clone();
+    let mut sender = h.connect(0, &pin, config()).await.unwrap();
+    expect_event(h.display(0), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+
+    let events = [InputEvent::MouseMove { x: 0.25, y: 0.5 }, InputEvent::KeyUp { keycode: 65 }];
+    h.input.start_recording();
+    for event in &events {
+        h.input.try_send(event.clone()).unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let recording = h.input.stop_recording().unwrap();
+    assert_eq!(recording.events.len(), 2);
+    assert!(recording.events[1].at_ms >= 20);
+
+    for event in &events {
+        assert_eq!(&sender.expect_input().await.unwrap(), event);
+    }
+    let started = std::time::Instant::now();
+    let replay = h.input.replay(recording, false);
+    for event in &events {
+        assert_eq!(&sender.expect_input().await.unwrap(), event);
+    }
+    assert!(started.elapsed() >= Duration::from_millis(20));
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !replay.is_finished() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert!(h.input.stop_recording().is_none());
+}
+
+#[tokio::test]
+async fn previews_reach_the_sender() {
+    let mut h = Harness::start(1).await.unwrap();
+    let pin = h.startup.pairing_pin.clone();
+    assert!(!h.display(0).preview.is_wanted());
+    let mut sender = h.connect(0, &pin, config()).await.unwrap();
+    assert!(sender.ack.capabilities.iter().any(|c| c == duallink_core::CAP_PREVIEW));
+    expect_event(h.display(0), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+
+    // The forwarder subscribes just after the session starts.
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !h.display(0).preview.is_wanted() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    let mut previews = sender.writer().receiver_previews();
+    let jpeg = vec![0xff, 0xd8, 0x00, 0x01, 0xff, 0xd9];
+    h.display(0).preview.publish(jpeg.clone());
+    tokio::time::timeout(Duration::from_secs(5), previews.changed()).await.unwrap().unwrap();
+    assert_eq!(previews.borrow().as_deref(), Some(&jpeg[..]));
+}
+
+#[tokio::test]
+async fn fps_requests_reach_the_sender() {
+    let mut h = Harness::start(1).await.unwrap();
+    let pin = h.startup.pairing_pin.clone();
+    let mut sender = h.connect(0, &pin, config()).await.unwrap();
+    expect_event(h.display(0), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+
+    let mut requests = sender.writer().fps_requests();
+    h.display(0).pace.request(Some(duallink_core::HIDDEN_FPS));
+    tokio::time::timeout(Duration::from_secs(5), requests.changed()).await.unwrap().unwrap();
+    assert_eq!(*requests.borrow_and_update(), Some(duallink_core::HIDDEN_FPS));
+    h.display(0).pace.request(None);
+    tokio::time::timeout(Duration::from_secs(5), requests.changed()).await.unwrap().unwrap();
+    assert_eq!(*requests.borrow(), Some(config().target_fps));
+}
+
+#[tokio::test]
+async fn power_states_are_exchanged() {
+    let mut h = Harness::start(1).await.unwrap();
+    let pin = h.startup.pairing_pin.clone();
+    // Published before the sender connects: sent on connect.
+    let receiver_power = PowerState::new(true, Some(12), Some(30));
+    h.display(0).power.publish(Some(receiver_power));
+    let mut sender = h.connect(0, &pin, config()).await.unwrap();
+    assert!(sender.ack.capabilities.iter().any(|c| c == duallink_core::CAP_POWER));
+    expect_event(h.display(0), |e| matches!(e, SignalingEvent::SessionStarted { .. })).await.unwrap();
+
+    let mut power = sender.writer().receiver_power();
+    tokio::time::timeout(Duration::from_secs(5), power.wait_for(Option::is_some)).await.unwrap().unwrap();
+    assert_eq!(*power.borrow(), Some(receiver_power));
+
+    let sender_power = PowerState::new(false, Some(80), Some(30));
+    sender.writer().send_power(sender_power).await.unwrap();
+    let event = expect_event(h.display(0), |e| matches!(e, SignalingEvent::SenderPower { .. })).await.unwrap();
+    assert!(matches!(event, SignalingEvent::SenderPower { power } if power == sender_power));
+}
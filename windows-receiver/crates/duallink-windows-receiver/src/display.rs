@@ -0,0 +1,234 @@
+//! Per-display session loop: wait for a sender, decode and show its stream
+//! in a `d3d11videosink` window, forward the window's input back.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use duallink_core::{
+    coded_size, errors::DecoderError, read_power, FileTransferEvent, HiddenMode, PowerState, StreamConfig,
+    HIDDEN_FPS, POWER_POLL_INTERVAL,
+};
+use duallink_decoder::{AsyncDecoder, DecoderFactory};
+use duallink_transport::{DisplayChannels, InputSender, SignalingEvent, PREVIEW_INTERVAL, PREVIEW_WIDTH};
+use tracing::{debug, info, warn};
+
+use crate::DECODER_PREFERENCE;
+
+/// Serve display `ch` until the transport shuts down, one iteration per
+/// sender session.
+pub async fn run_display(ch: DisplayChannels, input_sender: InputSender) -> Result<()> {
+    let DisplayChannels {
+        display_index: n, mut frame_rx, mut event_rx, config: display_cfg, keyframes, kick, blank, preview, pace, power,
+        pause, files,
+    } = ch;
+
+    // Per-display and user preference first, then the Windows order.
+    let preference: Vec<String> = display_cfg
+        .decoder
+        .iter()
+        .cloned()
+        .chain(DecoderFactory::from_settings().preference().iter().cloned())
+        .chain(DECODER_PREFERENCE.iter().map(|e| e.to_string()))
+        .collect();
+    let mut failed_decoders: Vec<String> = Vec::new();
+    let mut pending_config: Option<StreamConfig> = None;
+    let mut allow_input = true;
+    // This machine's power source, re-read during sessions.
+    let mut local_power: Option<PowerState> = None;
+
+    'reconnect: loop {
+        // ── Wait for hello (or reuse a config from a hot-reload) ───────────
+        let config = match pending_config.take() {
+            Some(cfg) => cfg,
+            None => {
+                info!("Display[{n}] Waiting for sender to connect...");
+                loop {
+                    match event_rx.recv().await {
+                        Some(SignalingEvent::SessionStarted { session_id, device_name, config, client_addr, allow_input: input }) => {
+                            let mode = if input { "" } else { " (view-only)" };
+                            info!("Display[{n}] Session {session_id} from '{device_name}' ({client_addr}){mode}: {config:?}");
+                            allow_input = input;
+                            break config;
+                        }
+                        Some(other) => debug!("Display[{n}] Pre-session event: {:?}", other),
+                        None => break 'reconnect,
+                    }
+                }
+            }
+        };
+
+        // ── Open the decoder + window on its own thread ────────────────────
+        let dec_config = config.clone();
+        let preferred = preference.clone();
+        let excluded = failed_decoders.clone();
+        let open = move || {
+            let preferred: Vec<&str> = preferred.iter().map(String::as_str).collect();
+            DecoderFactory::with_preference(&preferred)
+                .excluding(&excluded)
+                .decoder_for(&dec_config)
+        };
+        keyframes.arm();
+        let (decoder, mut input_events) = match AsyncDecoder::spawn(n, open, |_| {}).await {
+            Ok(d) => d,
+            Err(e) => {
+                warn!("Display[{n}] Decoder init failed: {e} — skipping session");
+                continue 'reconnect;
+            }
+        };
+        info!(
+            "Display[{n}] Decoder ready: {} hw={}",
+            decoder.element_name(), decoder.is_hardware_accelerated()
+        );
+        decoder.set_input_enabled(allow_input).await;
+        if blank.is_requested() || pause.is_paused() {
+            decoder.set_blanked(true).await;
+        }
+
+        // Window input → this display's sender; ends with the decode thread.
+        let is = input_sender.for_display(n);
+        tokio::spawn(async move {
+            while let Some(event) = input_events.next().await {
+                let _ = is.try_send(event);
+            }
+        });
+
+        // ── Receive → decode loop ──────────────────────────────────────────
+        let mut failed_element = None;
+        let mut preview_tick = tokio::time::interval(PREVIEW_INTERVAL);
+        let mut power_tick = tokio::time::interval(POWER_POLL_INTERVAL);
+        // Whether a keyframe's SPS was checked against the config yet.
+        let mut size_checked = false;
+        let reason = loop {
+            tokio::select! {
+                Some(frame) = frame_rx.recv() => {
+                    if frame.is_keyframe && !size_checked {
+                        size_checked = true;
+                        if let Some(coded) = coded_size(config.codec, &frame.data).filter(|s| *s != config.resolution) {
+                            warn!(
+                                "Display[{n}] Stream is {coded} but the session negotiated {} — reopening the decoder",
+                                config.resolution
+                            );
+                            pending_config = Some(StreamConfig { resolution: coded, ..config.clone() });
+                            break "coded_size_mismatch";
+                        }
+                    }
+                    match decoder.push(frame).await {
+                        Ok(()) => {}
+                        Err(DecoderError::Pipeline { source_element, message, .. }) => {
+                            warn!("Display[{n}] Decoder pipeline failed in {source_element}: {message}");
+                            failed_element = Some(decoder.element_name().to_string());
+                            break "decoder_failed";
+                        }
+                        Err(e @ DecoderError::Panicked(_)) => {
+                            warn!("Display[{n}] {e} — restarting with another decoder");
+                            failed_element = Some(decoder.element_name().to_string());
+                            break "decoder_failed";
+                        }
+                        Err(e) => {
+                            warn!("Display[{n}] Decode thread gone ({e}) — stopping session");
+                            break "decode_thread_gone";
+                        }
+                    }
+                }
+                Some(event) = event_rx.recv() => match event {
+                    SignalingEvent::SessionStopped { .. } => break "session_stopped",
+                    SignalingEvent::ClientDisconnected => break "client_disconnected",
+                    SignalingEvent::ConfigUpdated { config: new_cfg } => {
+                        if new_cfg.resolution != config.resolution || new_cfg.lossless != config.lossless {
+                            pending_config = Some(new_cfg);
+                            break "config_updated";
+                        }
+                    }
+                    SignalingEvent::FrameGap { missing, stats } => {
+                        warn!("Display[{n}] Lost {missing} frame(s) — waiting for keyframe ({stats})");
+                    }
+                    SignalingEvent::StreamStalled { silent } => {
+                        warn!("Display[{n}] No frames for {:.0} s — keyframe requested", silent.as_secs_f64());
+                    }
+                    SignalingEvent::BitrateExceeded { limit_kbps, measured_kbps, dropped } => {
+                        warn!("Display[{n}] Sender at {measured_kbps} kbps exceeds the {limit_kbps} kbps limit — dropped {dropped} frame(s)");
+                    }
+                    SignalingEvent::ReceiverDisplayChanged { monitor: Some(m), .. } => {
+                        decoder.move_to_monitor(m).await;
+                    }
+                    SignalingEvent::Blank { enabled } => {
+                        info!("Display[{n}] Sender {} the display", if enabled { "blanked" } else { "unblanked" });
+                        decoder.set_blanked(enabled).await;
+                    }
+                    SignalingEvent::SenderPower { power } => info!("Display[{n}] Sender is {power}"),
+                    SignalingEvent::DisplayState { paused } => {
+                        info!("Display[{n}] Sender {} the display", if paused { "paused" } else { "resumed" });
+                        decoder.set_blanked(paused || blank.is_requested()).await;
+                    }
+                    // No window to ask in; the sender hears at once.
+                    SignalingEvent::File(FileTransferEvent::Offered(offer)) => {
+                        info!("Display[{n}] Declining {} — file transfer needs the Linux GUI", offer.name);
+                        files.answer(&offer.transfer_id, false);
+                    }
+                    _ => {}
+                },
+                // End-session hotkey; ClientDisconnected follows.
+                _ = decoder.end_requested() => {
+                    info!("Display[{n}] Session ended from the video window");
+                    kick.disconnect();
+                }
+                // Blank hotkey: black out here and pause the sender's capture.
+                _ = decoder.blank_toggled() => {
+                    let enabled = !decoder.stats().blanked;
+                    decoder.set_blanked(enabled).await;
+                    blank.request(enabled);
+                }
+                // Thumbnail for a sender that asked for previews; skipped on
+                // battery saver.
+                _ = preview_tick.tick() => {
+                    if preview.is_wanted() && !local_power.is_some_and(|p| p.saver) {
+                        if let Some(jpeg) = decoder.snapshot_jpeg(PREVIEW_WIDTH).await {
+                            preview.publish(jpeg);
+                        }
+                    }
+                }
+                // Re-read the power source; the sender hears of changes.
+                _ = power_tick.tick() => {
+                    let state = tokio::task::spawn_blocking(read_power).await.ok().flatten();
+                    if state != local_power {
+                        if let Some(p) = state {
+                            info!("Display[{n}] Receiver is {p}");
+                        }
+                        local_power = state;
+                    }
+                    power.publish(state);
+                }
+                // Window minimized or shown again: fewer frames meanwhile.
+                _ = decoder.visibility_changed() => {
+                    let hidden = decoder.stats().hidden;
+                    pace.request(hidden.then_some(HIDDEN_FPS));
+                    if !hidden && decoder.hidden_mode() == Some(HiddenMode::KeyframesOnly) {
+                        keyframes.arm();
+                    }
+                }
+                else => break "channels_closed",
+            }
+        };
+
+        let stats = decoder.shutdown().await;
+        pace.request(None);
+        info!(
+            "Display[{n}] Session ended ({reason}), push errors={}, duplicate frames dropped={}",
+            stats.push_errors, stats.duplicates
+        );
+        if reason == "channels_closed" {
+            break;
+        }
+        if let Some(element) = failed_element {
+            warn!("Display[{n}] Excluding decoder {element} and restarting");
+            failed_decoders.push(element);
+            pending_config.get_or_insert(config);
+        } else if pending_config.is_none() {
+            // Give the OS a moment to clean up the previous TCP connection.
+            tokio::time::sleep(Duration::from_millis(300)).await;
+        }
+    }
+
+    info!("Display[{n}] Receiver loop exited.");
+    Ok(())
+}
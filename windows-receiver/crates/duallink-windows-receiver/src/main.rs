@@ -0,0 +1,100 @@
+//! DualLink Windows Receiver.
+//!
+//! Turns a Windows machine into an extra DualLink **monitor** for any
+//! DualLink sender (macOS, Linux or Windows). The network side is the Linux
+//! receiver's `duallink-transport` unchanged; only the display end differs:
+//!
+//! ```text
+//! UDP/TLS ─► duallink-transport ─► appsrc → h264parse → d3d11h264dec → d3d11videosink
+//!                      ▲                                                   │
+//!                      └──────────── input_event (TLS) ◄── navigation ─────┘
+//! ```
+//!
+//! # Decoding
+//!
+//! Decoders are tried in [`DECODER_PREFERENCE`] order (`d3d11h264dec`, then
+//! `mfh264dec`, then `avdec_h264`), after any `DUALLINK_DECODER` list.
+//! Frames are shown with `d3d11videosink` ([`duallink_decoder::VIDEO_SINK`]).
+//!
+//! # Input
+//!
+//! Mouse and keyboard events in the video window arrive as GStreamer
+//! navigation messages, are normalised to the stream and sent back over the
+//! signaling connection; the sender injects them (`SendInput` on a Windows
+//! sender, CGEvent on macOS).
+//!
+//! # Environment
+//!
+//! - `DUALLINK_DISPLAY_COUNT` — displays to expose (default 1, max 8);
+//!   display `n` uses UDP `7878 + 2n` / TCP `7879 + 2n`
+//! - `DUALLINK_BASE_PORT=9000` — moves display 0 to UDP 9000 / TCP 9001
+//!   (advertised to senders over mDNS and in `hello_ack`)
+//! - `DUALLINK_DECODER=mfh264dec,…` — decoders to try first
+
+mod display;
+
+use anyhow::Result;
+use duallink_decoder::receiver_capabilities;
+use duallink_discovery::{detect_local_ip, DualLinkAdvertiser};
+use duallink_transport::{hooks::Hooks, DualLinkReceiver};
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
+
+/// Windows decode priority: Direct3D 11, Media Foundation, then software.
+const DECODER_PREFERENCE: &[&str] = &["d3d11h264dec", "mfh264dec", "avdec_h264"];
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .with_target(true)
+        .init();
+
+    info!("DualLink Windows Receiver v{}", env!("CARGO_PKG_VERSION"));
+
+    let display_count: u8 = std::env::var("DUALLINK_DISPLAY_COUNT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(1)
+        .clamp(1, 8);
+
+    let (recv, channels, input_sender, startup) =
+        DualLinkReceiver::start_all_with_capabilities(display_count, receiver_capabilities()).await?;
+
+    // ── Advertise via mDNS so senders can auto-discover this receiver ──────
+    let local_ip = detect_local_ip();
+    let _advertiser = DualLinkAdvertiser::register(
+        "DualLink Windows Receiver",
+        &recv.port_map(),
+        local_ip,
+        &startup.tls_fingerprint,
+    )
+    .map_err(|e| warn!("mDNS advertising unavailable: {e}"))
+    .ok();
+
+    info!("Pairing PIN: {}  |  Enter {} in the DualLink sender app.", startup.pairing_pin, local_ip);
+
+    // ── One task per display ───────────────────────────────────────────────
+    let hooks = Hooks::load();
+    let mut handles = Vec::with_capacity(channels.len());
+    for mut ch in channels {
+        ch.event_rx = hooks.tap(ch.display_index, ch.event_rx);
+        let is = input_sender.clone();
+        handles.push(tokio::spawn(async move {
+            let idx = ch.display_index;
+            if let Err(e) = display::run_display(ch, is).await {
+                warn!("Display[{idx}] exited with error: {:#}", e);
+            }
+        }));
+    }
+
+    for h in handles {
+        let _ = h.await;
+    }
+
+    info!("All display streams exited.");
+    Ok(())
+}
@@ -6,7 +6,7 @@
 //! # Windows pipeline
 //!
 //! ```text
-//! EnumDisplayMonitors → HMONITOR[display_index]
+//! EnumDisplayMonitors → HMONITOR[display_index]  (or by name, see below)
 //!   │  IGraphicsCaptureItemInterop::CreateForMonitor
 //!   ▼
 //! GraphicsCaptureItem
@@ -20,6 +20,20 @@
 //!   ▼
 //! Vec<u8> BGRA8 → tokio mpsc channel → ScreenCapturer::next_frame()
 //! ```
+//!
+//! # HDR
+//!
+//! [`display_hdr_metadata`] reports a monitor's HDR10 static metadata (from
+//! `IDXGIOutput6::GetDesc1`) when Windows HD Color is on. Frames are still
+//! captured as 8-bit BGRA: WGC only offers BGRA8 or scRGB FP16, and GStreamer
+//! has no FP16 raw format, so the sender maps SDR-range frames into the PQ
+//! container rather than capturing P010 directly.
+//!
+//! # Monitor selection
+//!
+//! [`list_monitors`] reports each monitor's GDI device name (`\\.\DISPLAY1`)
+//! and desktop geometry. Setting [`CaptureConfig::monitor`] to one of those
+//! names captures that monitor instead of the `display_index`-th one.
 
 /// Configuration for a single display capture stream.
 #[derive(Debug, Clone)]
@@ -28,6 +42,9 @@ pub struct CaptureConfig {
     pub width:  u32,
     pub height: u32,
     pub fps:    u32,
+    /// GDI device name of the monitor to capture (from [`list_monitors`]);
+    /// `None` picks the `display_index`-th monitor.
+    pub monitor: Option<String>,
 }
 
 /// A raw captured video frame (BGRA8, CPU-side).
@@ -44,9 +61,9 @@ pub struct CapturedFrame {
 #[cfg(target_os = "windows")]
 mod wgc;
 #[cfg(target_os = "windows")]
-pub use wgc::ScreenCapturer;
+pub use wgc::{display_hdr_metadata, list_monitors, ScreenCapturer};
 
 #[cfg(not(target_os = "windows"))]
 mod stub;
 #[cfg(not(target_os = "windows"))]
-pub use stub::ScreenCapturer;
+pub use stub::{display_hdr_metadata, list_monitors, ScreenCapturer};
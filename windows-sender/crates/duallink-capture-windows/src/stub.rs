@@ -1,8 +1,19 @@
 //! Non-Windows stub for ScreenCapturer (CI + cross-compilation).
 
 use anyhow::Result;
+use duallink_core::{HdrMetadata, MonitorInfo};
 use super::{CaptureConfig, CapturedFrame};
 
+/// No HDR displays off Windows.
+pub fn display_hdr_metadata(_config: &CaptureConfig) -> Option<HdrMetadata> {
+    None
+}
+
+/// No monitors to enumerate off Windows.
+pub fn list_monitors() -> Vec<MonitorInfo> {
+    Vec::new()
+}
+
 #[allow(dead_code)]
 pub struct ScreenCapturer {
     config: CaptureConfig,
@@ -11,6 +11,7 @@ use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
+use duallink_core::{HdrMetadata, MasteringDisplay, MonitorInfo, Resolution};
 use tokio::sync::mpsc;
 use windows::{
     core::*,
@@ -31,8 +32,14 @@ use windows::{
                 D3D11_BIND_FLAG, D3D11_CPU_ACCESS_READ, D3D11_CREATE_DEVICE_BGRA_SUPPORT,
                 D3D11_SDK_VERSION, D3D11_TEXTURE2D_DESC, D3D11_USAGE_STAGING,
             },
-            Dxgi::IDXGIDevice,
-            Gdi::{EnumDisplayMonitors, HMONITOR, HDC},
+            Dxgi::{
+                Common::DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020, CreateDXGIFactory1,
+                IDXGIDevice, IDXGIFactory1, IDXGIOutput6,
+            },
+            Gdi::{
+                EnumDisplayMonitors, EnumDisplaySettingsW, GetMonitorInfoW, DEVMODEW,
+                ENUM_CURRENT_SETTINGS, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW,
+            },
         },
         System::WinRT::{
             Direct3D11::CreateDirect3D11DeviceFromDXGIDevice,
@@ -63,15 +70,14 @@ impl ScreenCapturer {
         let display_index = config.display_index as usize;
 
         // ── 1. Enumerate monitors ─────────────────────────────────────────
-        let monitors = enumerate_monitors();
-        if display_index >= monitors.len() {
+        let Some(hmonitor) = resolve_monitor(&config) else {
             anyhow::bail!(
-                "Display[{}] not found ({} monitors detected)",
+                "Display[{}] monitor {} not found ({} monitors detected)",
                 display_index,
-                monitors.len()
+                config.monitor.as_deref().unwrap_or("by index"),
+                enumerate_monitors().len()
             );
-        }
-        let hmonitor = monitors[display_index];
+        };
         tracing::info!(
             "Display[{}] WGC capturing HMONITOR {:?}",
             display_index, hmonitor
@@ -228,6 +234,104 @@ fn create_staging_texture(device: &ID3D11Device, w: u32, h: u32) -> Result<ID3D1
     tex.context("CreateTexture2D staging")
 }
 
+// ── HDR ───────────────────────────────────────────────────────────────────────
+
+/// HDR10 static metadata of the monitor `config` captures, or `None` if the
+/// monitor is not in HDR (ST 2084 / BT.2020) mode.
+pub fn display_hdr_metadata(config: &CaptureConfig) -> Option<HdrMetadata> {
+    let display_index = config.display_index;
+    let monitor = resolve_monitor(config)?;
+    unsafe {
+        let factory: IDXGIFactory1 = CreateDXGIFactory1().ok()?;
+        let mut a = 0;
+        while let Ok(adapter) = factory.EnumAdapters1(a) {
+            a += 1;
+            let mut o = 0;
+            while let Ok(output) = adapter.EnumOutputs(o) {
+                o += 1;
+                let Ok(desc) = output.cast::<IDXGIOutput6>().and_then(|out| out.GetDesc1()) else {
+                    continue;
+                };
+                if desc.Monitor != monitor {
+                    continue;
+                }
+                if desc.ColorSpace != DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020 {
+                    tracing::info!("Display[{}] is not in HDR mode", display_index);
+                    return None;
+                }
+                return Some(HdrMetadata {
+                    mastering_display: MasteringDisplay::from_cie(
+                        [desc.RedPrimary, desc.GreenPrimary, desc.BluePrimary],
+                        desc.WhitePoint,
+                        desc.MaxLuminance,
+                        desc.MinLuminance,
+                    ),
+                    max_cll: desc.MaxLuminance as u16,
+                    max_fall: desc.MaxFullFrameLuminance as u16,
+                });
+            }
+        }
+    }
+    None
+}
+
+// ── Monitor enumeration ───────────────────────────────────────────────────────
+
+/// Connected monitors with their GDI device names and desktop geometry, in
+/// the order Windows reports them.
+pub fn list_monitors() -> Vec<MonitorInfo> {
+    enumerate_monitors()
+        .into_iter()
+        .filter_map(monitor_info)
+        .collect()
+}
+
+/// The monitor `config` selects: by name when set, else by index.
+fn resolve_monitor(config: &CaptureConfig) -> Option<HMONITOR> {
+    let monitors = enumerate_monitors();
+    match config.monitor.as_deref() {
+        Some(name) => monitors
+            .into_iter()
+            .find(|&hmon| monitor_info(hmon).is_some_and(|info| info.name == name)),
+        None => monitors.get(config.display_index as usize).copied(),
+    }
+}
+
+/// Name, geometry and refresh rate of one monitor.
+fn monitor_info(hmon: HMONITOR) -> Option<MonitorInfo> {
+    let mut info = MONITORINFOEXW::default();
+    info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+    unsafe {
+        GetMonitorInfoW(hmon, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO).ok().ok()?;
+    }
+    let device = &info.szDevice;
+    let len = device.iter().position(|&c| c == 0).unwrap_or(device.len());
+    let name = String::from_utf16_lossy(&device[..len]);
+
+    let mut mode = DEVMODEW { dmSize: std::mem::size_of::<DEVMODEW>() as u16, ..Default::default() };
+    let refresh_hz = unsafe {
+        if EnumDisplaySettingsW(PCWSTR(device.as_ptr()), ENUM_CURRENT_SETTINGS, &mut mode).as_bool() {
+            mode.dmDisplayFrequency as f32
+        } else {
+            0.0
+        }
+    };
+
+    let rect = info.monitorInfo.rcMonitor;
+    Some(MonitorInfo {
+        name,
+        resolution: Resolution::new((rect.right - rect.left) as u32, (rect.bottom - rect.top) as u32),
+        refresh_hz,
+        width_mm: 0,
+        height_mm: 0,
+        scale: 1,
+        x: rect.left,
+        y: rect.top,
+        // MONITORINFOF_PRIMARY
+        primary: info.monitorInfo.dwFlags & 1 != 0,
+    })
+}
+
 /// Enumerate connected monitors, in the order Windows reports them.
 fn enumerate_monitors() -> Vec<HMONITOR> {
     let mut list: Vec<HMONITOR> = Vec::new();
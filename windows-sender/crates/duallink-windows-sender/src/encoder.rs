@@ -8,18 +8,57 @@
 //! Pipeline:
 //! ```text
 //! appsrc (BGRx)
+//!   → tee ─→ preview branch  (1 fps RGBA thumbnails, see `preview`)
 //!   → videoconvert
 //!   → video/x-raw,format=BGRx  (or NV12 for mfh264enc)
 //!   → <encoder>
 //!   → h264parse
 //!   → appsink
 //! ```
+//!
+//! The encoder element is named `enc` so bitrate and GOP can be changed
+//! mid-session when the user switches quality preset or sets custom rates.
+//! A new frame rate ([`GstEncoder::set_fps`]) is renegotiated through the
+//! appsrc and encoder input caps. Pipelines are built element by element
+//! with typed properties (see [`duallink_sender_lib::elements`]), never
+//! from `parse::launch` strings.
+//!
+//! Encoded access units leave the appsink through a callback into a channel,
+//! so [`GstEncoder::next_encoded`] can be awaited alongside capture; a bus
+//! watcher closes the channel when the pipeline ends.
+//!
+//! # Latency mode
+//!
+//! H.264 encodes follow the negotiated [`LatencyMode`]: zero-latency tuning
+//! without B-frames in `UltraLow`, [`LatencyMode::b_frames`] B-frames in
+//! `Quality`. HDR encodes always run in ultra-low latency mode.
+//!
+//! # HDR
+//!
+//! With HDR metadata the pipeline encodes HEVC Main10 instead
+//! (`nvh265enc` / `mfh265enc` / `x265enc`): `videoconvert` remaps the BGRx
+//! frames into BT.2020 / PQ 10-bit, and the caps carry the display's
+//! mastering-display and content-light-level info into the bitstream SEI.
+//!
+//! ```text
+//! appsrc (BGRx) → videoconvert (gamma/primaries remap)
+//!   → video/x-raw,format=P010_10LE,colorimetry=<PQ>,mastering-display-info=…
+//!   → <hevc-encoder> → video/x-h265,profile=main-10 → h265parse → appsink
+//! ```
+
+use std::sync::{Arc, Mutex};
 
 use anyhow::{Context, Result};
+use bytes::Bytes;
 use duallink_capture_windows::CapturedFrame;
-use duallink_core::EncodedFrame;
+use duallink_core::{
+    temporal_layer, EncodedFrame, EncoderTune, HdrMetadata, LatencyMode, SenderQueues, VideoCodec, HDR_COLORIMETRY,
+};
+use duallink_sender_lib::elements::{self, caps_filter, make, make_named};
+use duallink_sender_lib::preview::{self, PreviewSlot};
 use gstreamer::{self as gst, prelude::*};
-use gstreamer_app::{AppSink, AppSrc};
+use gstreamer_app::{AppSink, AppSinkCallbacks, AppSrc};
+use tokio::sync::mpsc;
 
 // ── Encoder selection ─────────────────────────────────────────────────────────
 
@@ -36,86 +75,318 @@ fn pick_encoder() -> &'static str {
     "x264enc"
 }
 
+/// HEVC Main10 encoders for HDR, in priority order.
+const HEVC_ENCODER_CANDIDATES: &[&str] = &["nvh265enc", "mfh265enc", "x265enc"];
+
+fn pick_hevc_encoder() -> &'static str {
+    for name in HEVC_ENCODER_CANDIDATES {
+        if gst::ElementFactory::find(name).is_some() {
+            tracing::info!("[GstEncoderWin] Using HDR encoder: {}", name);
+            return name;
+        }
+    }
+    tracing::warn!("[GstEncoderWin] No HEVC encoder found; defaulting to x265enc");
+    "x265enc"
+}
+
+/// Encoder → parser part of a HEVC Main10 HDR10 encode, after
+/// `videoconvert`; sets the tuning properties of `enc`.
+fn hdr_chain(
+    enc: &gst::Element,
+    enc_name: &str,
+    width: u32,
+    height: u32,
+    bitrate_kbps: u32,
+    gop: u32,
+    hdr: &HdrMetadata,
+) -> Result<Vec<gst::Element>> {
+    enc.set_property("bitrate", bitrate_kbps);
+    let format = match enc_name {
+        "nvh265enc" => {
+            enc.set_property_from_str("preset", "low-latency-hq");
+            "P010_10LE"
+        }
+        "mfh265enc" => {
+            enc.set_property("low-latency", true);
+            "P010_10LE"
+        }
+        _ => {
+            enc.set_property_from_str("speed-preset", "ultrafast");
+            enc.set_property_from_str("tune", "zerolatency");
+            "I420_10LE"
+        }
+    };
+    set_gop(enc, enc_name, gop);
+    let input = gst::Caps::builder("video/x-raw")
+        .field("format", format)
+        .field("width", width as i32)
+        .field("height", height as i32)
+        .field("colorimetry", HDR_COLORIMETRY)
+        .field("mastering-display-info", hdr.gst_mastering_display_info())
+        .field("content-light-level", hdr.gst_content_light_level())
+        .build();
+    let output = gst::Caps::builder("video/x-h265").field("profile", "main-10").build();
+    Ok(vec![caps_filter(&input)?, enc.clone(), caps_filter(&output)?, make("h265parse")?])
+}
+
+/// Encoder → parser part of an H.264 encode, after `videoconvert`; sets the
+/// tuning properties of `enc`.
+#[allow(clippy::too_many_arguments)]
+fn h264_chain(
+    enc: &gst::Element,
+    enc_name: &str,
+    width: u32,
+    height: u32,
+    fps: u32,
+    bitrate_kbps: u32,
+    tune: EncoderTune,
+    latency: LatencyMode,
+    gop: u32,
+) -> Result<Vec<gst::Element>> {
+    let b_frames = latency.b_frames();
+    let input = gst::Caps::builder("video/x-raw").field("width", width as i32).field("height", height as i32);
+    let input = match enc_name {
+        // mfh264enc accepts NV12 natively; convert from BGRx first
+        "mfh264enc" => {
+            let qvs: u32 = if tune == EncoderTune::Quality { 50 } else { 100 };
+            enc.set_property("bitrate", bitrate_kbps);
+            enc.set_property("quality-vs-speed", qvs);
+            enc.set_property("low-latency", b_frames == 0);
+            enc.set_property("bframes", b_frames);
+            input.field("format", "NV12").field("framerate", gst::Fraction::new(fps as i32, 1))
+        }
+        "nvh264enc" => {
+            let preset = if tune == EncoderTune::LowPower { "low-latency-hp" } else { "low-latency-hq" };
+            enc.set_property("bitrate", bitrate_kbps * 1000);
+            enc.set_property_from_str("preset", preset);
+            enc.set_property("zerolatency", b_frames == 0);
+            enc.set_property("bframes", b_frames);
+            input.field("format", "NV12")
+        }
+        // x264enc: software
+        _ => {
+            let speed = if tune == EncoderTune::Quality { "superfast" } else { "ultrafast" };
+            enc.set_property("bitrate", bitrate_kbps);
+            enc.set_property_from_str("speed-preset", speed);
+            if b_frames == 0 {
+                enc.set_property_from_str("tune", "zerolatency");
+            } else {
+                enc.set_property("bframes", b_frames);
+                enc.set_property("b-adapt", false);
+            }
+            input.field("format", "I420")
+        }
+    };
+    set_gop(enc, enc_name, gop);
+    Ok(vec![caps_filter(&input.build())?, enc.clone(), make("h264parse")?])
+}
+
 // ── GstEncoder ────────────────────────────────────────────────────────────────
 
+/// Set the keyframe interval of `enc`, an instance of `element`.
+fn set_gop(enc: &gst::Element, element: &str, frames: u32) {
+    match element {
+        "nvh264enc" | "nvh265enc" => enc.set_property("gop-size", frames as i32),
+        "mfh264enc" | "mfh265enc" => enc.set_property("gop-size", frames),
+        "x265enc"                 => enc.set_property("key-int-max", frames as i32),
+        _                         => enc.set_property("key-int-max", frames),
+    }
+}
+
 /// GStreamer H.264 encode pipeline for the Windows sender.
 pub struct GstEncoder {
-    pipeline: gst::Pipeline,
-    appsrc:   AppSrc,
-    appsink:  AppSink,
-    width:    u32,
-    height:   u32,
-    fps:      u32,
+    pipeline:   gst::Pipeline,
+    element:    &'static str,
+    enc:        gst::Element,
+    /// Caps filter in front of `enc`.
+    input:      gst::Element,
+    /// Where captured frames go; `None` for a test pattern.
+    appsrc:     Option<AppSrc>,
+    /// Element whose `caps` set the source frame rate: the appsrc or the
+    /// test pattern's `testcaps`.
+    rate_caps:  gst::Element,
+    encoded_rx: mpsc::Receiver<EncodedFrame>,
+    width:      u32,
+    height:     u32,
+    fps:        u32,
 }
 
 impl GstEncoder {
     /// Create and start a GStreamer encode pipeline.
-    pub fn new(width: u32, height: u32, fps: u32, bitrate_kbps: u32) -> Result<Self> {
-        let enc_name = pick_encoder();
-        let bitrate_bps = bitrate_kbps * 1000;
-
-        let pipeline_desc = if enc_name == "mfh264enc" {
-            // mfh264enc accepts NV12 natively; convert from BGRx first
-            format!(
-                "appsrc name=src is-live=true format=time \
-                 caps=video/x-raw,format=BGRx,width={width},height={height},framerate={fps}/1 \
-                 ! videoconvert \
-                 ! video/x-raw,format=NV12,width={width},height={height},framerate={fps}/1 \
-                 ! mfh264enc bitrate={bitrate_kbps} quality-vs-speed=100 low-latency=true \
-                 ! h264parse \
-                 ! appsink name=sink sync=false emit-signals=true"
-            )
-        } else if enc_name == "nvh264enc" {
-            format!(
-                "appsrc name=src is-live=true format=time \
-                 caps=video/x-raw,format=BGRx,width={width},height={height},framerate={fps}/1 \
-                 ! videoconvert \
-                 ! video/x-raw,format=NV12,width={width},height={height} \
-                 ! nvh264enc bitrate={bitrate_bps} preset=low-latency-hq \
-                 ! h264parse \
-                 ! appsink name=sink sync=false emit-signals=true"
+    ///
+    /// `tune` biases the encoder's speed/quality knob, `latency` picks
+    /// zero-latency or B-frame tuning; `gop` is the keyframe interval in
+    /// frames. With `hdr` set, encodes HEVC Main10 HDR10 (see module docs) —
+    /// only pass it once the receiver accepted HDR. `queues` sizes the
+    /// `appsink` and the encoded-frame channel.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        width: u32,
+        height: u32,
+        fps: u32,
+        bitrate_kbps: u32,
+        tune: EncoderTune,
+        latency: LatencyMode,
+        gop: u32,
+        hdr: Option<&HdrMetadata>,
+        queues: SenderQueues,
+    ) -> Result<Self> {
+        let appsrc = AppSrc::builder()
+            .name("src")
+            .is_live(true)
+            .format(gst::Format::Time)
+            .caps(
+                &gst::Caps::builder("video/x-raw")
+                    .field("format", "BGRx")
+                    .field("width", width as i32)
+                    .field("height", height as i32)
+                    .field("framerate", gst::Fraction::new(fps as i32, 1))
+                    .build(),
             )
-        } else {
-            // x264enc: software
-            let x264_kbps = bitrate_kbps;
-            format!(
-                "appsrc name=src is-live=true format=time \
-                 caps=video/x-raw,format=BGRx,width={width},height={height},framerate={fps}/1 \
-                 ! videoconvert \
-                 ! video/x-raw,format=I420,width={width},height={height} \
-                 ! x264enc bitrate={x264_kbps} speed-preset=ultrafast \
-                   tune=zerolatency key-int-max=60 \
-                 ! h264parse \
-                 ! appsink name=sink sync=false emit-signals=true"
-            )
-        };
-
-        tracing::debug!("[GstEncoderWin] Pipeline: {}", pipeline_desc);
+            .build();
+        Self::with_source(appsrc.upcast(), width, height, fps, bitrate_kbps, tune, latency, gop, hdr, queues)
+    }
 
-        let pipeline = gst::parse::launch(&pipeline_desc)
-            .context("GStreamer pipeline parse")?
-            .downcast::<gst::Pipeline>()
-            .map_err(|_| anyhow::anyhow!("Pipeline downcast failed"))?;
+    /// Create and start an encode pipeline fed by the `videotestsrc`
+    /// `pattern` ([`elements::test_source`]) instead of capture. Always SDR.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_test_pattern(
+        pattern: &str,
+        width: u32,
+        height: u32,
+        fps: u32,
+        bitrate_kbps: u32,
+        tune: EncoderTune,
+        latency: LatencyMode,
+        gop: u32,
+        queues: SenderQueues,
+    ) -> Result<Self> {
+        let source = elements::test_source(pattern, width, height, fps)?;
+        let encoder = Self::with_source(source, width, height, fps, bitrate_kbps, tune, latency, gop, None, queues)?;
+        tracing::info!("[GstEncoderWin] Test pattern {} ({})", pattern, encoder.element);
+        Ok(encoder)
+    }
 
-        let appsrc = pipeline
-            .by_name("src")
-            .context("src element")?
-            .downcast::<AppSrc>()
-            .map_err(|_| anyhow::anyhow!("AppSrc downcast"))?;
+    /// Shared construction behind `source`: an appsrc, or a bin with a
+    /// `testcaps` caps filter.
+    #[allow(clippy::too_many_arguments)]
+    fn with_source(
+        source: gst::Element,
+        width: u32,
+        height: u32,
+        fps: u32,
+        bitrate_kbps: u32,
+        tune: EncoderTune,
+        latency: LatencyMode,
+        gop: u32,
+        hdr: Option<&HdrMetadata>,
+        queues: SenderQueues,
+    ) -> Result<Self> {
+        let appsrc = source.clone().downcast::<AppSrc>().ok();
+        let rate_caps = match &appsrc {
+            Some(appsrc) => appsrc.clone().upcast(),
+            None => source
+                .downcast_ref::<gst::Bin>()
+                .and_then(|bin| bin.by_name("testcaps"))
+                .context("Source has no testcaps filter")?,
+        };
+        let (enc_name, codec) = match hdr {
+            Some(_) => (pick_hevc_encoder(), VideoCodec::H265),
+            None => (pick_encoder(), VideoCodec::H264),
+        };
+        let convert = make("videoconvert")?;
+        let enc = make_named(enc_name, "enc")?;
+        let encode = match hdr {
+            Some(hdr) => {
+                convert.set_property_from_str("gamma-mode", "remap");
+                convert.set_property_from_str("primaries-mode", "full");
+                hdr_chain(&enc, enc_name, width, height, bitrate_kbps, gop, hdr)?
+            }
+            None => h264_chain(&enc, enc_name, width, height, fps, bitrate_kbps, tune, latency, gop)?,
+        };
+        let input = encode[0].clone();
+        // Never drop encoded frames: the receiver couldn't decode until the next keyframe.
+        let appsink =
+            AppSink::builder().name("sink").sync(false).max_buffers(queues.encoded as u32).drop(false).build();
 
-        let appsink = pipeline
-            .by_name("sink")
-            .context("sink element")?
-            .downcast::<AppSink>()
-            .map_err(|_| anyhow::anyhow!("AppSink downcast"))?;
+        let pipeline = gst::Pipeline::new();
+        pipeline.add(&source).context("Adding source")?;
+        let (tee, queue) = preview::split(&pipeline, width, height)?;
+        elements::link_chain(&[&source, &tee])?;
+        let mut chain = vec![convert];
+        chain.extend(encode);
+        chain.push(appsink.clone().upcast());
+        elements::add_chain(&pipeline, &chain.iter().collect::<Vec<_>>())?;
+        elements::link_chain(&[&queue, &chain[0]])?;
+        tracing::debug!("[GstEncoderWin] Pipeline: {}", chain.iter().map(|e| e.name().to_string()).collect::<Vec<_>>().join(" → "));
 
-        pipeline.set_state(gst::State::Playing).context("Pipeline → Playing")?;
+        let encoded_rx = start(&pipeline, &appsink, codec, queues.encoded_frames)?;
         tracing::info!(
             "[GstEncoderWin] Pipeline running: {}×{} @{}fps {}kbps ({})",
             width, height, fps, bitrate_kbps, enc_name
         );
 
-        Ok(Self { pipeline, appsrc, appsink, width, height, fps })
+        Ok(Self { pipeline, element: enc_name, enc, input, appsrc, rate_caps, encoded_rx, width, height, fps })
+    }
+
+    /// GStreamer encoder element in use (e.g. `"mfh264enc"`).
+    pub fn element_name(&self) -> &str {
+        self.element
+    }
+
+    /// Change the target bitrate of the running encoder.
+    pub fn set_bitrate(&self, kbps: u32) {
+        // nvh264enc is configured in bit/s here (see `new`); the others in kbit/s.
+        let value = if self.element == "nvh264enc" { kbps * 1000 } else { kbps };
+        self.enc.set_property("bitrate", value);
+        tracing::info!("[GstEncoderWin] bitrate → {} kbps ({})", kbps, self.element);
+    }
+
+    /// Change the keyframe interval (frames) of the running encoder.
+    pub fn set_gop(&self, frames: u32) {
+        set_gop(&self.enc, self.element, frames);
+        tracing::info!("[GstEncoderWin] GOP → {} frames ({})", frames, self.element);
+    }
+
+    /// Change the frame rate announced to the encoder by renegotiating the
+    /// source caps (and the encoder input caps where they pin a rate). A
+    /// test pattern only goes below the rate it was opened with.
+    ///
+    /// WGC keeps capturing at the rate it was opened with; the caller drops
+    /// frames down to `fps`.
+    pub fn set_fps(&mut self, fps: u32) {
+        let fps = fps.max(1);
+        if fps == self.fps {
+            return;
+        }
+        self.fps = fps;
+        let rate = gst::Fraction::new(fps as i32, 1);
+        if let Some(mut caps) = self.rate_caps.property::<Option<gst::Caps>>("caps") {
+            caps.make_mut().set("framerate", rate);
+            self.rate_caps.set_property("caps", &caps);
+        }
+        let mut caps = self.input.property::<gst::Caps>("caps");
+        if caps.structure(0).is_some_and(|s| s.has_field("framerate")) {
+            caps.make_mut().set("framerate", rate);
+            self.input.set_property("caps", &caps);
+        }
+        tracing::info!("[GstEncoderWin] fps → {} ({})", fps, self.element);
+    }
+
+    /// Make the next encoded frame a keyframe (with SPS/PPS), e.g. when the
+    /// receiver sends `keyframe_request` after losing frames.
+    pub fn force_keyframe(&self) {
+        let event = gstreamer_video::UpstreamForceKeyUnitEvent::builder().all_headers(true).build();
+        if !self.enc.send_event(event) {
+            tracing::warn!("[GstEncoderWin] force-key-unit ignored ({})", self.element);
+        }
+    }
+
+    /// Publish 1 fps thumbnails of the captured frames to `slot`.
+    pub fn set_preview(&self, slot: PreviewSlot) {
+        if let Err(e) = preview::attach(&self.pipeline, slot) {
+            tracing::warn!("[GstEncoderWin] preview: {:#} ({})", e, self.element);
+        }
     }
 
     /// Push a raw captured frame into the GStreamer appsrc.
@@ -132,34 +403,92 @@ impl GstEncoder {
             map.as_mut_slice().copy_from_slice(&frame.data);
         }
         self.appsrc
+            .as_ref()
+            .context("Test pattern takes no frames")?
             .push_buffer(buf)
             .map_err(|e| anyhow::anyhow!("push_buffer: {e}"))?;
         Ok(())
     }
 
-    /// Pull the next encoded frame from the GStreamer appsink (blocks briefly).
-    pub fn next_encoded(&mut self) -> Option<EncodedFrame> {
-        use gstreamer::BufferFlags;
-
-        let sample = self.appsink.try_pull_sample(gst::ClockTime::from_mseconds(50))?;
-        let buf = sample.buffer()?;
-        let map = buf.map_readable().ok()?;
-        let is_keyframe = !buf.flags().contains(BufferFlags::DELTA_UNIT);
-        let pts_ms = buf.pts().map(|t| t.mseconds()).unwrap_or(0);
-        Some(EncodedFrame {
-            data: map.as_slice().to_vec(),
-            is_keyframe,
-            pts_ms,
-            display_index: 0,
-        })
+    /// Await the next encoded access unit; `None` once the pipeline ends.
+    pub async fn next_encoded(&mut self) -> Option<EncodedFrame> {
+        self.encoded_rx.recv().await
     }
 
     /// Send EOS to flush remaining encoded frames.
     pub fn send_eos(&mut self) {
-        let _ = self.appsrc.end_of_stream();
+        let _ = match &self.appsrc {
+            Some(appsrc) => appsrc.end_of_stream().is_ok(),
+            None => self.pipeline.send_event(gst::event::Eos::new()),
+        };
     }
 }
 
+/// Send `appsink`'s samples of `pipeline` into an [`EncodedFrame`] channel of
+/// `depth` frames and set the pipeline to Playing.
+///
+/// A bus watcher closes the channel on EOS or error so that
+/// [`GstEncoder::next_encoded`] returns `None` when the stream ends.
+fn start(
+    pipeline: &gst::Pipeline,
+    appsink: &AppSink,
+    codec: VideoCodec,
+    depth: usize,
+) -> Result<mpsc::Receiver<EncodedFrame>> {
+    let (encoded_tx, encoded_rx) = mpsc::channel::<EncodedFrame>(depth);
+    // Shared so the bus watcher can drop the sender and end the stream.
+    let encoded_tx = Arc::new(Mutex::new(Some(encoded_tx)));
+    let sample_tx = Arc::clone(&encoded_tx);
+
+    appsink.set_callbacks(
+        AppSinkCallbacks::builder()
+            .new_sample(move |sink| {
+                let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                let buf = sample.buffer().ok_or(gst::FlowError::Error)?;
+                let is_keyframe = !buf.flags().contains(gst::BufferFlags::DELTA_UNIT);
+                let timestamp_us = buf.pts().map(|t| t.useconds()).unwrap_or(0);
+                let map = buf.map_readable().map_err(|_| gst::FlowError::Error)?;
+                let data = Bytes::copy_from_slice(map.as_slice());
+                let temporal_layer = if is_keyframe { 0 } else { temporal_layer(codec, &data) };
+                let frame = EncodedFrame { data, timestamp_us, is_keyframe, codec, temporal_layer, partial: false };
+
+                let Some(tx) = sample_tx.lock().unwrap().clone() else {
+                    return Err(gst::FlowError::Flushing);
+                };
+                if tx.blocking_send(frame).is_err() {
+                    return Err(gst::FlowError::Flushing);
+                }
+                Ok(gst::FlowSuccess::Ok)
+            })
+            .build(),
+    );
+
+    pipeline.set_state(gst::State::Playing).context("Pipeline → Playing")?;
+
+    let bus = pipeline.bus().context("Encoder pipeline bus")?;
+    let pipeline_weak = pipeline.downgrade();
+    tokio::task::spawn_blocking(move || {
+        loop {
+            match bus.timed_pop(gst::ClockTime::from_seconds(1)) {
+                Some(msg) => match msg.view() {
+                    gst::MessageView::Eos(_) => break,
+                    gst::MessageView::Error(e) => {
+                        tracing::error!("[GstEncoderWin] Pipeline error: {} ({:?})", e.error(), e.debug());
+                        break;
+                    }
+                    _ => {}
+                },
+                // Poll timeout — stop once the GstEncoder has been dropped.
+                None if pipeline_weak.upgrade().is_none() => break,
+                None => {}
+            }
+        }
+        encoded_tx.lock().unwrap().take();
+    });
+
+    Ok(encoded_rx)
+}
+
 impl Drop for GstEncoder {
     fn drop(&mut self) {
         let _ = self.pipeline.set_state(gst::State::Null);
@@ -10,6 +10,9 @@
 //! | **GUI** (default) | `.\duallink-sender.exe` | — |
 //! | **Headless** | `DUALLINK_NO_UI=1 .\duallink-sender.exe` | `DUALLINK_HOST`, `DUALLINK_PIN`, etc. |
 //!
+//! Either mode streams a generated pattern instead of the screen with
+//! `--test-pattern[=<name>]` (see [`duallink_core::test_pattern`]).
+//!
 //! # Phase 5E status
 //!
 //! - [x] WGC capture (Windows.Graphics.Capture via `windows` crate)
@@ -21,7 +24,10 @@
 
 mod encoder;
 mod input_inject;
+mod network;
 mod pipeline;
+mod power;
+mod strings;
 mod ui;
 
 use anyhow::Result;
@@ -56,10 +62,12 @@ fn main() -> Result<()> {
         let _rt_guard = rt.enter();
 
         let native_options = eframe::NativeOptions {
-            viewport: egui::ViewportBuilder::default()
-                .with_title("DualLink Windows Sender")
-                .with_inner_size([520.0, 360.0])
-                .with_min_inner_size([400.0, 300.0]),
+            viewport: ui::restore_window(
+                egui::ViewportBuilder::default()
+                    .with_title("DualLink Windows Sender")
+                    .with_inner_size([520.0, 360.0])
+                    .with_min_inner_size([400.0, 300.0]),
+            ),
             ..Default::default()
         };
 
@@ -84,17 +92,47 @@ async fn headless_main() -> Result<()> {
     let n: u8 = env::var("DUALLINK_DISPLAY_COUNT").ok().and_then(|v| v.parse().ok()).unwrap_or(1);
     let w: u32 = env::var("DUALLINK_WIDTH").ok().and_then(|v| v.parse().ok()).unwrap_or(1920);
     let h: u32 = env::var("DUALLINK_HEIGHT").ok().and_then(|v| v.parse().ok()).unwrap_or(1080);
-    let fps: u32 = env::var("DUALLINK_FPS").ok().and_then(|v| v.parse().ok()).unwrap_or(60);
-    let kbps: u32 = env::var("DUALLINK_KBPS").ok().and_then(|v| v.parse().ok()).unwrap_or(8000);
+    let mut fps: u32 = env::var("DUALLINK_FPS").ok().and_then(|v| v.parse().ok()).unwrap_or(60);
+    let mut kbps: u32 = env::var("DUALLINK_KBPS").ok().and_then(|v| v.parse().ok()).unwrap_or(8000);
+    let preset = env::var("DUALLINK_PRESET").ok()
+        .and_then(|v| duallink_core::QualityPreset::from_name(&v));
+    if let Some(p) = preset {
+        fps = p.params().target_fps;
+        kbps = (p.params().max_bitrate_bps / 1000) as u32;
+    }
+
+    let hdr = env::var("DUALLINK_HDR").as_deref() == Ok("1");
+    // DUALLINK_LATENCY_MODE=ultra_low|quality
+    let latency_mode = duallink_core::LatencyMode::from_env();
+    // DUALLINK_MONITOR_<n>=\\.\DISPLAY2 overrides the monitor saved for stream n in the UI.
+    let monitors = duallink_core::MonitorAssignments::load();
+    // DUALLINK_NETWORK_CAPS=wifi=6000@30,usb=3000@30 caps streams by the network they go over.
+    let network_caps = duallink_core::NetworkPolicy::from_env();
+    // DUALLINK_TOTAL_KBPS=12000 shares one bitrate budget between all displays.
+    let budget = duallink_sender_lib::BitrateAllocator::from_env();
+    // DUALLINK_QUEUES=encoded=2,input=128 tunes the queues between stages.
+    let queues = duallink_core::SenderQueues::from_env();
+    // --test-pattern[=smpte75] streams a generated pattern instead of the screen.
+    let test_pattern = duallink_core::test_pattern_arg(env::args());
 
     info!("Headless: {} display(s) → {} — {}×{} @{}fps {}kbps", n, host, w, h, fps, kbps);
 
     let (status_tx, mut status_rx) = mpsc::channel::<pipeline::PipelineStatus>(64);
     let mut pipelines = Vec::new();
 
+    // DUALLINK_BASE_PORT=9000 for a receiver whose display 0 is on UDP 9000 / TCP 9001.
+    let ports = env::var("DUALLINK_BASE_PORT").ok().and_then(|v| v.parse().ok())
+        .map(|base| duallink_transport_client::PortMap::contiguous(base, n))
+        .unwrap_or_default();
+
     for i in 0..n {
         let cfg = PipelineConfig { host: host.clone(), pairing_pin: pin.clone(),
-            display_index: i, width: w, height: h, fps, bitrate_kbps: kbps };
+            display_index: i, ports: ports.clone(), width: w, height: h, fps, bitrate_kbps: kbps, preset,
+            latency_mode, hdr,
+            monitor: env::var(format!("DUALLINK_MONITOR_{i}")).ok()
+                .or_else(|| monitors.get(i).map(str::to_owned)),
+            test_pattern, remote_preview: false, network_caps: network_caps.clone(), budget: budget.clone(),
+            queues };
         pipelines.push(WinSenderPipeline::spawn(cfg, status_tx.clone()));
     }
 
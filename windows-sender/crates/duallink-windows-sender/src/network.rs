@@ -0,0 +1,95 @@
+//! Which kind of network the route to the receiver leaves through, via
+//! `GetBestInterfaceEx` + `GetAdaptersAddresses`.
+//!
+//! Feeds the per-network caps of [`duallink_core::NetworkPolicy`]; the
+//! pipeline re-checks it every [`duallink_core::ROUTE_POLL_INTERVAL`].
+
+use duallink_core::NetworkKind;
+
+/// `IF_TYPE_ETHERNET_CSMACD` (ipifcons.h).
+#[cfg(target_os = "windows")]
+const IF_TYPE_ETHERNET: u32 = 6;
+/// `IF_TYPE_IEEE80211` (ipifcons.h).
+#[cfg(target_os = "windows")]
+const IF_TYPE_WIFI: u32 = 71;
+
+/// Kind of the adapter Windows would use to reach `host` (IPv4).
+///
+/// USB tethers (RNDIS, CDC NCM, Apple Mobile Device Ethernet) report
+/// themselves as Ethernet, so they are told apart by adapter description.
+#[cfg(target_os = "windows")]
+pub fn route_kind(host: &str) -> NetworkKind {
+    use std::net::{IpAddr, ToSocketAddrs};
+    use windows::Win32::NetworkManagement::IpHelper::{
+        GetAdaptersAddresses, GetBestInterfaceEx, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER,
+        GAA_FLAG_SKIP_MULTICAST, IP_ADAPTER_ADDRESSES_LH,
+    };
+    use windows::Win32::Networking::WinSock::{AF_INET, AF_UNSPEC, IN_ADDR, IN_ADDR_0, SOCKADDR, SOCKADDR_IN};
+
+    let Some(ip) = (host, 0)
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.find_map(|a| match a.ip() { IpAddr::V4(v4) => Some(v4), _ => None }))
+    else {
+        return NetworkKind::Unknown;
+    };
+
+    let dest = SOCKADDR_IN {
+        sin_family: AF_INET,
+        sin_port:   0,
+        sin_addr:   IN_ADDR { S_un: IN_ADDR_0 { S_addr: u32::from_ne_bytes(ip.octets()) } },
+        sin_zero:   [0; 8],
+    };
+    let mut if_index = 0u32;
+    if unsafe { GetBestInterfaceEx(&dest as *const SOCKADDR_IN as *const SOCKADDR, &mut if_index) } != 0 {
+        return NetworkKind::Unknown;
+    }
+
+    let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+    let mut size = 16 * 1024u32;
+    let mut buf: Vec<u64> = Vec::new();
+    // Retried once if the adapter list grew between the two calls.
+    for _ in 0..2 {
+        buf = vec![0u64; (size as usize).div_ceil(8)];
+        let ret = unsafe {
+            GetAdaptersAddresses(AF_UNSPEC.0 as u32, flags, None, Some(buf.as_mut_ptr().cast()), &mut size)
+        };
+        match ret {
+            0 => break,
+            // ERROR_BUFFER_OVERFLOW: `size` now holds what is needed.
+            111 => continue,
+            _ => return NetworkKind::Unknown,
+        }
+    }
+
+    let mut adapter = buf.as_ptr() as *const IP_ADAPTER_ADDRESSES_LH;
+    while let Some(a) = unsafe { adapter.as_ref() } {
+        if unsafe { a.Anonymous1.Anonymous.IfIndex } == if_index {
+            let description = unsafe { a.Description.to_string() }.unwrap_or_default();
+            return classify(a.IfType, &description);
+        }
+        adapter = a.Next;
+    }
+    NetworkKind::Unknown
+}
+
+#[cfg(target_os = "windows")]
+fn classify(if_type: u32, description: &str) -> NetworkKind {
+    let description = description.to_ascii_lowercase();
+    match if_type {
+        IF_TYPE_WIFI => NetworkKind::Wifi,
+        IF_TYPE_ETHERNET
+            if ["remote ndis", "usb", "apple mobile device"].iter().any(|s| description.contains(s)) =>
+        {
+            NetworkKind::UsbTether
+        }
+        IF_TYPE_ETHERNET => NetworkKind::Ethernet,
+        _ => NetworkKind::Unknown,
+    }
+}
+
+/// Non-Windows fallback (development builds).
+#[cfg(not(target_os = "windows"))]
+pub fn route_kind(host: &str) -> NetworkKind {
+    duallink_core::network::route_kind(host)
+}
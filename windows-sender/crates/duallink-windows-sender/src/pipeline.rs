@@ -1,19 +1,44 @@
 //! `WinSenderPipeline` — one Windows display's full capture → encode → send loop.
 //!
-//! Mirrors `linux-sender/src/pipeline.rs` but uses:
-//! - `duallink_capture_windows::ScreenCapturer` (WGC on Windows, stub otherwise)
+//! Runs the shared [`SenderSession`] of `duallink-sender-lib` — signaling,
+//! rate control, blanking, pausing, battery saver and status, as on Linux —
+//! over the Windows [`Platform`]:
+//! - `duallink_capture_windows::ScreenCapturer` (WGC on Windows, stub otherwise),
+//!   or a generated pattern with [`PipelineConfig::test_pattern`]
 //! - `encoder::GstEncoder` with `mfh264enc` / `nvh264enc` / `x264enc` priority
-
-use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use std::collections::VecDeque;
-
-use duallink_capture_windows::{CaptureConfig, ScreenCapturer};
-use duallink_transport_client::{SignalingClient, VideoSender};
-use duallink_core::StreamConfig;
-use tokio::sync::{mpsc, Notify};
-use tracing::{info, warn};
+//!   (HEVC Main10 for HDR10 displays)
+//! - [`crate::network::route_kind`] for the per-network caps of
+//!   [`PipelineConfig::network_caps`]
+//! - `SendInput` injection and an [`AwakeGuard`] while streaming
+//!
+//! WGC captures at a fixed rate, so the session drops captured frames beyond
+//! the target rate (fps caps, custom rates, battery saver, the receiver's
+//! requests) before the encoder.
+//!
+//! Events go to the pipeline's [`PipelineLog`], which the UI keeps after the
+//! task exits so failures can be inspected. The encoder publishes 1 fps
+//! thumbnails of what is sent to [`WinSenderPipeline::preview`]; with
+//! [`PipelineConfig::remote_preview`], the receiver's thumbnails of what it
+//! shows go to [`WinSenderPipeline::remote_preview`].
+
+use anyhow::Context;
+use bytes::Bytes;
+use duallink_capture_windows::{display_hdr_metadata, CaptureConfig, CapturedFrame, ScreenCapturer};
+use duallink_core::{
+    configured_idle_after, EncodedFrame, EncoderTune, InputEvent, LatencyMode, NetworkKind, NetworkPolicy,
+    QualityPreset, SenderQueues, StreamConfig, VideoCodec,
+};
+use duallink_sender_lib::preview::{self, PreviewSlot};
+use duallink_sender_lib::{
+    BitrateAllocator, Capture, Encoder, PipelineLog, Platform, SenderSession, SessionConfig, CUSTOM_GOP,
+};
+use duallink_transport_client::PortMap;
+use tokio::sync::{mpsc, watch};
+
+pub use duallink_sender_lib::{PipelineState, PipelineStatus};
+
+use crate::encoder::GstEncoder;
+use crate::power::AwakeGuard;
 
 // ── Public types ──────────────────────────────────────────────────────────────
 
@@ -23,10 +48,37 @@ pub struct PipelineConfig {
     pub host:          String,
     pub pairing_pin:   String,
     pub display_index: u8,
+    /// Receiver ports from mDNS discovery (empty = default layout). The
+    /// video port is then taken from `hello_ack` when the receiver sends one.
+    pub ports:         PortMap,
     pub width:         u32,
     pub height:        u32,
     pub fps:           u32,
     pub bitrate_kbps:  u32,
+    /// Quality preset the fps/bitrate above were taken from (`None` = custom).
+    /// Follows presets applied mid-session.
+    pub preset:        Option<QualityPreset>,
+    /// Latency / quality trade-off to ask the receiver for. Only used if
+    /// the receiver advertises `latency_mode`.
+    pub latency_mode:  LatencyMode,
+    /// Stream HEVC Main10 HDR10 when the display is in HDR mode and the
+    /// receiver advertises `hevc_main10`; otherwise H.264 SDR.
+    pub hdr:           bool,
+    /// Monitor to capture, by GDI device name (`None` = by display index).
+    pub monitor:       Option<String>,
+    /// Stream this `videotestsrc` pattern instead of capturing
+    /// (`--test-pattern`, see [`duallink_core::test_pattern`]).
+    pub test_pattern:  Option<&'static str>,
+    /// Ask the receiver for thumbnails of what it shows.
+    pub remote_preview: bool,
+    /// Bitrate / fps caps by the kind of network the receiver is reached over.
+    pub network_caps:  NetworkPolicy,
+    /// Bitrate budget shared by all displays (`DUALLINK_TOTAL_KBPS`; `None` =
+    /// each streams at its own rate).
+    pub budget:        Option<BitrateAllocator>,
+    /// Queue depths between capture, encoder, network and input (see
+    /// [`duallink_core::queues`]).
+    pub queues:        SenderQueues,
 }
 
 impl Default for PipelineConfig {
@@ -35,254 +87,275 @@ impl Default for PipelineConfig {
             host:          "192.168.1.100".to_owned(),
             pairing_pin:   "000000".to_owned(),
             display_index: 0,
+            ports:         PortMap::default(),
             width:         1920,
             height:        1080,
             fps:           60,
             bitrate_kbps:  8000,
+            preset:        None,
+            latency_mode:  LatencyMode::UltraLow,
+            hdr:           false,
+            monitor:       None,
+            test_pattern:  None,
+            remote_preview: false,
+            network_caps:  NetworkPolicy::default(),
+            budget:        None,
+            queues:        SenderQueues::default(),
         }
     }
 }
 
-/// Lifecycle state of a pipeline.
-#[derive(Debug, Clone, PartialEq)]
-pub enum PipelineState {
-    Connecting,
-    Streaming,
-    Stopped,
-    Failed(String),
-}
-
-/// Periodic status update pushed to the UI via mpsc channel.
-#[derive(Debug, Clone)]
-pub struct PipelineStatus {
-    pub display_index: u8,
-    pub state:         PipelineState,
-    pub fps:           f32,
-    pub frames_sent:   u64,
-}
-
 // ── WinSenderPipeline ─────────────────────────────────────────────────────────
 
 /// Handle to a running capture → encode → send pipeline task.
 pub struct WinSenderPipeline {
-    stop_notify:  Arc<Notify>,
-    frames_sent:  Arc<AtomicU64>,
+    session:        SenderSession,
+    preview:        PreviewSlot,
+    remote_preview: PreviewSlot,
 }
 
 impl WinSenderPipeline {
     /// Spawn the async pipeline task and return a handle to it.
     pub fn spawn(config: PipelineConfig, status_tx: mpsc::Sender<PipelineStatus>) -> Self {
-        let stop_notify = Arc::new(Notify::new());
-        let frames_sent = Arc::new(AtomicU64::new(0));
-        let fs = Arc::clone(&frames_sent);
-        let sn = Arc::clone(&stop_notify);
+        let preview = PreviewSlot::default();
+        let remote_preview = PreviewSlot::default();
+        let session_config = SessionConfig {
+            host:          config.host.clone(),
+            pairing_pin:   config.pairing_pin.clone(),
+            display_index: config.display_index,
+            ports:         config.ports.clone(),
+            width:         config.width,
+            height:        config.height,
+            fps:           config.fps,
+            bitrate_kbps:  config.bitrate_kbps,
+            preset:        config.preset,
+            latency_mode:  config.latency_mode,
+            adaptive_fps:  false,
+            remote_preview: config.remote_preview,
+            network_caps:  config.network_caps.clone(),
+            budget:        config.budget.clone(),
+            // WGC only delivers frames when the screen changes, so a static
+            // desktop would look like a stalled capture.
+            capture_stall: std::time::Duration::ZERO,
+            idle_after:    configured_idle_after(),
+            match_receiver_resolution: false,
+            queues:        config.queues,
+        };
+        let platform = WinPlatform { config, preview: preview.clone(), remote_preview: remote_preview.clone() };
+        let session = SenderSession::spawn(session_config, platform, status_tx);
 
-        tokio::spawn(async move {
-            run_pipeline(config, status_tx, sn, fs).await;
-        });
+        Self { session, preview, remote_preview }
+    }
+
+    /// Event log of this pipeline (shared with the task).
+    pub fn log(&self) -> &PipelineLog {
+        self.session.log()
+    }
+
+    /// Latest thumbnail of what this pipeline sends (shared with the task).
+    pub fn preview(&self) -> &PreviewSlot {
+        &self.preview
+    }
+
+    /// Latest thumbnail of what the receiver shows, if asked for.
+    pub fn remote_preview(&self) -> &PreviewSlot {
+        &self.remote_preview
+    }
+
+    /// Switch the running pipeline to `preset` (non-blocking).
+    pub fn apply_preset(&self, preset: QualityPreset) {
+        self.session.apply_preset(preset);
+    }
+
+    /// Blank the receiver's display, or show the stream again (non-blocking).
+    pub fn set_remote_blank(&self, enabled: bool) {
+        self.session.set_remote_blank(enabled);
+    }
 
-        Self { stop_notify, frames_sent }
+    /// Switch the running pipeline to `kbps` (non-blocking).
+    pub fn set_bitrate(&self, kbps: u32) {
+        self.session.set_bitrate(kbps);
+    }
+
+    /// Switch the running pipeline to `fps` (non-blocking).
+    pub fn set_fps(&self, fps: u32) {
+        self.session.set_fps(fps);
+    }
+
+    /// Ask the running pipeline for a keyframe (non-blocking).
+    pub fn force_keyframe(&self) {
+        self.session.force_keyframe();
+    }
+
+    /// Pause or resume this display's stream (non-blocking); the other
+    /// displays and the session carry on.
+    pub fn set_paused(&self, paused: bool) {
+        self.session.set_paused(paused);
     }
 
     /// Signal the pipeline to stop gracefully.
     pub fn stop(&self) {
-        self.stop_notify.notify_one();
+        self.session.stop();
     }
 
     pub fn frames_sent(&self) -> u64 {
-        self.frames_sent.load(Ordering::Relaxed)
+        self.session.frames_sent()
     }
 }
 
-// ── Pipeline task ─────────────────────────────────────────────────────────────
-
-async fn run_pipeline(
-    cfg: PipelineConfig,
-    status_tx: mpsc::Sender<PipelineStatus>,
-    stop_notify: Arc<Notify>,
-    frames_sent: Arc<AtomicU64>,
-) {
-    let idx = cfg.display_index;
-
-    macro_rules! report {
-        ($state:expr) => {
-            let _ = status_tx.try_send(PipelineStatus {
-                display_index: idx,
-                state: $state,
-                fps: 0.0,
-                frames_sent: frames_sent.load(Ordering::Relaxed),
-            });
-        };
-        ($state:expr, $fps:expr) => {
-            let _ = status_tx.try_send(PipelineStatus {
-                display_index: idx,
-                state: $state,
-                fps: $fps,
-                frames_sent: frames_sent.load(Ordering::Relaxed),
-            });
-        };
-    }
+// ── Windows platform ──────────────────────────────────────────────────────────
 
-    report!(PipelineState::Connecting);
+/// WGC capture, GStreamer encoding and `SendInput` under a [`SenderSession`].
+struct WinPlatform {
+    config:         PipelineConfig,
+    preview:        PreviewSlot,
+    remote_preview: PreviewSlot,
+}
 
-    // ── 1. Connect signaling ──────────────────────────────────────────────
-    let mut sig = match SignalingClient::connect(&cfg.host, idx).await {
-        Ok(s) => s,
-        Err(e) => {
-            report!(PipelineState::Failed(format!("Signaling: {e}")));
-            return;
-        }
-    };
-
-    let session_id = format!("win-sender-{idx}-{}", ts_ms());
-    let stream_cfg = StreamConfig {
-        width: cfg.width,
-        height: cfg.height,
-        fps: cfg.fps,
-        ..Default::default()
-    };
-    match sig.send_hello(&session_id, hostname(), stream_cfg.clone(), &cfg.pairing_pin).await {
-        Ok(ack) if !ack.accepted => {
-            report!(PipelineState::Failed(format!("Rejected: {:?}", ack.reason)));
-            return;
+impl WinPlatform {
+    fn capture_config(&self, width: u32, height: u32) -> CaptureConfig {
+        CaptureConfig {
+            display_index: self.config.display_index,
+            width,
+            height,
+            fps: self.config.fps,
+            monitor: self.config.monitor.clone(),
         }
-        Err(e) => {
-            report!(PipelineState::Failed(format!("Hello: {e}")));
-            return;
-        }
-        Ok(_) => {}
     }
+}
 
-    let (mut sig_writer, mut input_rx) = sig.start_recv_loop();
+impl Platform for WinPlatform {
+    type Capture = WinCapture;
+    type Encoder = WinEncoder;
 
-    // ── 2. Connect UDP sender ─────────────────────────────────────────────
-    let video = match VideoSender::connect(&cfg.host, idx).await {
-        Ok(v) => v,
-        Err(e) => {
-            report!(PipelineState::Failed(format!("UDP: {e}")));
-            return;
-        }
-    };
-
-    // ── 3. Open screen capturer ───────────────────────────────────────────
-    let cap_cfg = CaptureConfig {
-        display_index: cfg.display_index,
-        width: cfg.width,
-        height: cfg.height,
-        fps: cfg.fps,
-    };
-    let mut capturer = match ScreenCapturer::open(cap_cfg).await {
-        Ok(c) => c,
-        Err(e) => {
-            report!(PipelineState::Failed(format!("Capture: {e}")));
+    const NAME: &'static str = "windows-sender";
+
+    fn route_kind(host: &str) -> NetworkKind {
+        crate::network::route_kind(host)
+    }
+
+    fn prepare(&mut self, config: &mut StreamConfig, log: &PipelineLog) {
+        if !self.config.hdr || self.config.test_pattern.is_some() {
             return;
         }
-    };
-
-    // ── 4. Create encoder ─────────────────────────────────────────────────
-    let mut encoder = match super::encoder::GstEncoder::new(
-        cfg.width, cfg.height, cfg.fps, cfg.bitrate_kbps,
-    ) {
-        Ok(e) => e,
-        Err(e) => {
-            report!(PipelineState::Failed(format!("Encoder: {e}")));
-            return;
+        match display_hdr_metadata(&self.capture_config(self.config.width, self.config.height)) {
+            Some(meta) => {
+                config.codec = VideoCodec::H265;
+                config.hdr = Some(meta);
+            }
+            None => log.warn("HDR requested but the display is not in HDR mode — sending SDR"),
         }
-    };
-
-    report!(PipelineState::Streaming);
-    info!("Display[{idx}] WinSenderPipeline streaming → {}", cfg.host);
+    }
 
-    let mut fps_counter = FpsCounter::new();
-    let mut keepalive = tokio::time::interval(Duration::from_secs(1));
+    async fn open(
+        &mut self,
+        stream: &StreamConfig,
+        _log: &PipelineLog,
+    ) -> anyhow::Result<(Option<WinCapture>, WinEncoder)> {
+        let (width, height) = (stream.resolution.width, stream.resolution.height);
+        let (tune, gop) = match stream.quality_preset {
+            Some(p) => (p.params().tune, p.params().keyframe_interval),
+            None => (EncoderTune::LowLatency, CUSTOM_GOP),
+        };
+        let kbps = (stream.max_bitrate_bps / 1000) as u32;
+        let latency = stream.latency_mode;
+        let (fps, queues) = (self.config.fps, self.config.queues);
+        if let Some(pattern) = self.config.test_pattern {
+            let encoder = GstEncoder::new_test_pattern(pattern, width, height, fps, kbps, tune, latency, gop, queues)
+                .context("Encoder")?;
+            encoder.set_preview(self.preview.clone());
+            return Ok((None, WinEncoder(encoder)));
+        }
+        let capturer = ScreenCapturer::open(self.capture_config(width, height)).await.context("Capture")?;
+        let hdr = stream.hdr.as_ref();
+        let encoder =
+            GstEncoder::new(width, height, fps, kbps, tune, latency, gop, hdr, queues).context("Encoder")?;
+        encoder.set_preview(self.preview.clone());
+        Ok((Some(WinCapture(capturer)), WinEncoder(encoder)))
+    }
 
-    loop {
-        tokio::select! {
-            _ = stop_notify.notified() => {
-                info!("Display[{idx}] stop requested");
-                break;
+    // Decode receiver thumbnails off the send loop; ends with the recv loop.
+    fn remote_previews(&mut self, mut previews: watch::Receiver<Option<Bytes>>) {
+        let idx = self.config.display_index;
+        let remote_preview = self.remote_preview.clone();
+        tokio::spawn(async move {
+            while previews.changed().await.is_ok() {
+                let Some(jpeg) = previews.borrow_and_update().clone() else { continue };
+                match tokio::task::spawn_blocking(move || preview::decode_jpeg(&jpeg)).await {
+                    Ok(Ok((width, height, rgba))) => remote_preview.publish(width, height, rgba),
+                    Ok(Err(e)) => tracing::warn!("Display[{idx}] {e:#}"),
+                    Err(_) => break,
+                }
             }
+        });
+    }
 
-            maybe_raw = capturer.next_frame() => {
-                let Some(raw) = maybe_raw else { break; };
-                let _ = encoder.push_frame(raw);
-            }
+    async fn inject(&mut self, event: InputEvent) {
+        // Inject the input event into the local Windows session.
+        crate::input_inject::inject_input_event(&event);
+        tracing::debug!("Display[{}] input injected: {:?}", self.config.display_index, event);
+    }
 
-            maybe_enc = tokio::task::spawn_blocking({
-                // Poll encoder in a blocking-compatible way
-                let mut enc = unsafe {
-                    &mut *(&mut encoder as *mut super::encoder::GstEncoder)
-                };
-                move || enc.next_encoded()
-            }) => {
-                if let Ok(Some(enc)) = maybe_enc {
-                    if let Err(e) = video.send_frame(&enc).await {
-                        warn!("Display[{idx}] send_frame: {e:#}");
-                    }
-                    frames_sent.fetch_add(1, Ordering::Relaxed);
-                    fps_counter.tick();
-                }
-            }
+    // Keep the display on and the machine awake; released when the session ends.
+    async fn keep_awake(&mut self) -> Option<Box<dyn Send>> {
+        Some(Box::new(AwakeGuard::acquire()?))
+    }
+}
 
-            _ = keepalive.tick() => {
-                let _ = sig_writer.send_keepalive(ts_ms()).await;
-                report!(PipelineState::Streaming, fps_counter.fps());
-            }
+/// WGC capture at the rate it was opened with.
+struct WinCapture(ScreenCapturer);
 
-            maybe_ev = input_rx.recv() => {
-                match maybe_ev {
-                    Some(ev) => {
-                        // Inject the input event into the local Windows session.
-                        super::input_inject::inject_input_event(&ev);
-                        tracing::debug!("Display[{idx}] input injected: {:?}", ev);
-                    }
-                    None => break,
-                }
-            }
-        }
+impl Capture for WinCapture {
+    type Frame = CapturedFrame;
+
+    async fn next_frame(&mut self) -> Option<CapturedFrame> {
+        self.0.next_frame().await
     }
 
-    encoder.send_eos();
-    let _ = sig_writer.send_stop(&session_id).await;
-    report!(PipelineState::Stopped);
-    info!("Display[{idx}] WinSenderPipeline stopped");
+    fn set_max_fps(&mut self, _fps: u32) -> bool {
+        // WGC's rate is fixed at open; the session drops the extra frames.
+        false
+    }
 }
 
-// ── FpsCounter ────────────────────────────────────────────────────────────────
+struct WinEncoder(GstEncoder);
 
-struct FpsCounter {
-    timestamps: VecDeque<std::time::Instant>,
-}
+impl Encoder for WinEncoder {
+    type Frame = CapturedFrame;
 
-impl FpsCounter {
-    fn new() -> Self { Self { timestamps: VecDeque::with_capacity(128) } }
+    fn push_frame(&mut self, frame: CapturedFrame) -> anyhow::Result<()> {
+        self.0.push_frame(frame)
+    }
 
-    fn tick(&mut self) {
-        let now = std::time::Instant::now();
-        self.timestamps.push_back(now);
-        while self.timestamps.front().map_or(false, |t| now - *t > Duration::from_secs(1)) {
-            self.timestamps.pop_front();
-        }
+    async fn next_encoded(&mut self) -> Option<EncodedFrame> {
+        self.0.next_encoded().await
     }
 
-    fn fps(&self) -> f32 { self.timestamps.len() as f32 }
-}
+    fn set_bitrate(&mut self, kbps: u32) {
+        self.0.set_bitrate(kbps);
+    }
 
-// ── Helpers ───────────────────────────────────────────────────────────────────
+    fn set_fps(&mut self, fps: u32) {
+        self.0.set_fps(fps);
+    }
 
-fn ts_ms() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis() as u64
-}
+    fn set_gop(&mut self, frames: u32) {
+        self.0.set_gop(frames);
+    }
+
+    fn force_keyframe(&mut self) {
+        self.0.force_keyframe();
+    }
 
-fn hostname() -> &'static str {
-    Box::leak(
-        hostname::get()
-            .ok()
-            .and_then(|h| h.into_string().ok())
-            .unwrap_or_else(|| "windows-sender".to_owned())
-            .into_boxed_str(),
-    )
+    fn send_eos(&mut self) {
+        self.0.send_eos();
+    }
+
+    fn element_name(&self) -> &str {
+        self.0.element_name()
+    }
+
+    fn is_hardware_accelerated(&self) -> bool {
+        self.0.element_name() != "x264enc" && self.0.element_name() != "x265enc"
+    }
 }
@@ -0,0 +1,64 @@
+//! Keep Windows awake while streaming, via `SetThreadExecutionState`.
+//!
+//! The execution state belongs to the thread that set it, so an
+//! [`AwakeGuard`] owns a small thread that sets it, parks until the guard is
+//! dropped and then clears it — async tasks hop between runtime threads.
+
+use tracing::{info, warn};
+
+/// Prevents display sleep and system idle sleep until dropped.
+pub struct AwakeGuard {
+    stop:   Option<std::sync::mpsc::Sender<()>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl AwakeGuard {
+    /// `None` if the execution state could not be set (or not on Windows).
+    #[cfg(target_os = "windows")]
+    pub fn acquire() -> Option<Self> {
+        use windows::Win32::System::Power::{
+            SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+        };
+
+        let (stop_tx, stop_rx) = std::sync::mpsc::channel::<()>();
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel::<bool>();
+        let thread = std::thread::Builder::new()
+            .name("duallink-awake".into())
+            .spawn(move || {
+                let prev = unsafe {
+                    SetThreadExecutionState(ES_CONTINUOUS | ES_DISPLAY_REQUIRED | ES_SYSTEM_REQUIRED)
+                };
+                let _ = ready_tx.send(prev.0 != 0);
+                // Returns once the guard drops its sender.
+                let _ = stop_rx.recv();
+                unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+            })
+            .map_err(|e| warn!("Keep-awake thread: {e}"))
+            .ok()?;
+
+        if ready_rx.recv() != Ok(true) {
+            warn!("SetThreadExecutionState failed — the display may sleep while streaming");
+            drop(stop_tx);
+            let _ = thread.join();
+            return None;
+        }
+        info!("Display and system sleep inhibited while streaming");
+        Some(Self { stop: Some(stop_tx), thread: Some(thread) })
+    }
+
+    /// Non-Windows stub.
+    #[cfg(not(target_os = "windows"))]
+    pub fn acquire() -> Option<Self> {
+        None
+    }
+}
+
+impl Drop for AwakeGuard {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+            info!("Sleep inhibition released");
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Text of the sender UI in every [`Language`](duallink_core::Language)
+//! (see [`duallink_core::locale`]).
+//!
+//! Pipeline log entries stay in English: they are the same lines the
+//! terminal log shows.
+
+use std::fmt::Display;
+
+use duallink_core::locale::{self, Catalog};
+
+/// Key in the current language.
+pub fn t(key: &'static str) -> &'static str {
+    locale::tr(STRINGS, key)
+}
+
+/// Key in the current language with `{name}` placeholders filled in.
+pub fn tf(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    locale::tr_fill(STRINGS, key, args)
+}
+
+#[rustfmt::skip]
+const STRINGS: Catalog = &[
+    ("app.language", ["Language", "Idioma", "Idioma"]),
+    ("appearance.theme", ["Theme", "Tema", "Tema"]),
+    ("appearance.dark", ["Dark", "Escuro", "Oscuro"]),
+    ("appearance.light", ["Light", "Claro", "Claro"]),
+    ("appearance.system", ["System", "Sistema", "Sistema"]),
+    ("appearance.scale", [
+        "UI scale, for HiDPI screens",
+        "Escala da interface, para telas HiDPI",
+        "Escala de la interfaz, para pantallas HiDPI",
+    ]),
+
+    // ── Settings ──────────────────────────────────────────────────────────
+    ("settings.receiver_ip", ["Receiver IP:", "IP do receptor:", "IP del receptor:"]),
+    ("settings.pin", ["PIN:", "PIN:", "PIN:"]),
+    ("settings.discovered", ["Discovered:", "Encontrados:", "Encontrados:"]),
+    ("settings.scan_placeholder", ["— scan for receivers —", "— procure receptores —", "— busca receptores —"]),
+    ("settings.scan", ["⟳ Scan", "⟳ Procurar", "⟳ Buscar"]),
+    ("settings.wake_mac", ["Wake MAC:", "MAC para despertar:", "MAC para despertar:"]),
+    ("settings.wake", ["⏻ Wake", "⏻ Despertar", "⏻ Despertar"]),
+    ("settings.displays", ["Displays:", "Telas:", "Pantallas:"]),
+    ("settings.resolution", ["Resolution:", "Resolução:", "Resolución:"]),
+    ("settings.monitor", ["Monitor {n}:", "Monitor {n}:", "Monitor {n}:"]),
+    ("settings.auto", ["Auto", "Automático", "Automático"]),
+    ("settings.windows_monitor", ["Monitor #{n} in Windows order", "Monitor nº {n} na ordem do Windows", "Monitor n.º {n} en el orden de Windows"]),
+    ("settings.primary", [" (primary)", " (principal)", " (principal)"]),
+    ("settings.redetect", ["Re-detect monitors", "Detectar monitores de novo", "Volver a detectar monitores"]),
+    ("settings.preset", ["Preset:", "Predefinição:", "Preajuste:"]),
+    ("settings.custom", ["Custom", "Personalizado", "Personalizado"]),
+    ("settings.fps", ["FPS:", "FPS:", "FPS:"]),
+    ("settings.bitrate", ["Bitrate:", "Bitrate:", "Bitrate:"]),
+    ("settings.hdr", ["HDR:", "HDR:", "HDR:"]),
+    ("settings.hdr_check", ["HDR10 (HEVC Main10)", "HDR10 (HEVC Main10)", "HDR10 (HEVC Main10)"]),
+    ("settings.hdr_hint", [
+        "Only for displays with Windows HD Color on; needs a receiver with 10-bit HEVC decode",
+        "Só para telas com o Windows HD Color ativado; requer receptor com decodificação HEVC de 10 bits",
+        "Solo para pantallas con Windows HD Color activado; requiere un receptor con decodificación HEVC de 10 bits",
+    ]),
+    ("settings.remote_preview", ["Receiver preview:", "Prévia do receptor:", "Vista previa del receptor:"]),
+    ("settings.remote_preview_check", [
+        "Show what the receiver displays",
+        "Mostrar o que o receptor exibe",
+        "Mostrar lo que muestra el receptor",
+    ]),
+    ("settings.remote_preview_hint", [
+        "The receiver sends a small JPEG of its screen every few seconds",
+        "O receptor envia um pequeno JPEG da tela a cada poucos segundos",
+        "El receptor envía un pequeño JPEG de su pantalla cada pocos segundos",
+    ]),
+
+    // ── Wake-on-LAN ───────────────────────────────────────────────────────
+    ("wake.waking", ["⏻ Waking receiver…", "⏻ Despertando o receptor…", "⏻ Despertando el receptor…"]),
+    ("wake.up", ["● Receiver up after {seconds}s", "● Receptor ativo após {seconds}s", "● Receptor activo tras {seconds}s"]),
+    ("wake.failed", ["✗ Wake failed: {error}", "✗ Falha ao despertar: {error}", "✗ No se pudo despertar: {error}"]),
+
+    // ── Actions ───────────────────────────────────────────────────────────
+    ("action.start", ["▶  Start Streaming", "▶  Iniciar transmissão", "▶  Iniciar transmisión"]),
+    ("action.start_all", ["▶  Start All Displays", "▶  Iniciar todas as telas", "▶  Iniciar todas las pantallas"]),
+    ("action.stop", ["■  Stop", "■  Parar", "■  Detener"]),
+    ("action.blank", ["Blank receiver", "Ocultar receptor", "Ocultar receptor"]),
+    ("action.blank_hint", [
+        "Black out the receiver's screens without ending the session",
+        "Escurece as telas do receptor sem encerrar a sessão",
+        "Oscurece las pantallas del receptor sin terminar la sesión",
+    ]),
+    ("action.fps_hint", [
+        "Capped at the capture rate the stream started with",
+        "Limitado à taxa de captura com que a transmissão começou",
+        "Limitado a la tasa de captura con la que empezó la transmisión",
+    ]),
+    ("action.keyframe", ["Keyframe", "Quadro-chave", "Fotograma clave"]),
+    ("action.keyframe_hint", ["Send a keyframe now", "Enviar um quadro-chave agora", "Enviar un fotograma clave ahora"]),
+
+    // ── Display status ────────────────────────────────────────────────────
+    ("status.title", ["Display Status", "Estado das telas", "Estado de las pantallas"]),
+    ("status.not_connected", [
+        "Configure above and click Start Streaming.",
+        "Configure acima e clique em Iniciar transmissão.",
+        "Configura arriba y pulsa Iniciar transmisión.",
+    ]),
+    ("status.display", ["Display {n}", "Tela {n}", "Pantalla {n}"]),
+    ("status.idle", ["⊘ Idle", "⊘ Inativo", "⊘ Inactivo"]),
+    ("status.connecting", ["⟳ Connecting…", "⟳ Conectando…", "⟳ Conectando…"]),
+    ("status.remote_preview", ["What the receiver shows", "O que o receptor exibe", "Lo que muestra el receptor"]),
+    ("status.paused", ["⏸ Paused", "⏸ Pausado", "⏸ En pausa"]),
+    ("status.paused_hint", [
+        "Capture runs but nothing is sent to the receiver",
+        "A captura continua, mas nada é enviado ao receptor",
+        "La captura sigue, pero no se envía nada al receptor",
+    ]),
+    ("status.streaming", ["● Streaming", "● Transmitindo", "● Transmitiendo"]),
+    ("status.resume", ["▶ Resume", "▶ Retomar", "▶ Reanudar"]),
+    ("status.resume_hint", ["Stream this display again", "Voltar a transmitir esta tela", "Volver a transmitir esta pantalla"]),
+    ("status.pause", ["⏸ Pause", "⏸ Pausar", "⏸ Pausar"]),
+    ("status.pause_hint", [
+        "Stop sending this display; the others and the session carry on",
+        "Para de enviar esta tela; as outras e a sessão continuam",
+        "Deja de enviar esta pantalla; las demás y la sesión siguen",
+    ]),
+    ("status.frames", ["{count} frames", "{count} quadros", "{count} fotogramas"]),
+    ("status.saver", ["🔋 saver", "🔋 economia", "🔋 ahorro"]),
+    ("status.no_battery", ["no battery", "sem bateria", "sin batería"]),
+    ("status.power_hint", [
+        "This machine: {this}\nReceiver: {receiver}\nBattery saver streams at 30 fps and a lower bitrate while either end runs low",
+        "Esta máquina: {this}\nReceptor: {receiver}\nA economia de bateria transmite a 30 fps e com bitrate menor enquanto um dos lados estiver com pouca carga",
+        "Este equipo: {this}\nReceptor: {receiver}\nEl ahorro de batería transmite a 30 fps y con menor bitrate mientras cualquiera de los dos tenga poca carga",
+    ]),
+    ("status.link_hint", [
+        "Keepalive round trip and frames the receiver could not reassemble since the last second",
+        "Ida e volta do keepalive e quadros que o receptor não conseguiu remontar no último segundo",
+        "Ida y vuelta del keepalive y fotogramas que el receptor no pudo reensamblar en el último segundo",
+    ]),
+    ("status.stopped", ["○ Stopped", "○ Parado", "○ Detenido"]),
+
+    // ── Pipeline log ──────────────────────────────────────────────────────
+    ("log.title", ["Log ({count})", "Registro ({count})", "Registro ({count})"]),
+    ("log.title_problems", ["Log ({count}, {problems} ⚠)", "Registro ({count}, {problems} ⚠)", "Registro ({count}, {problems} ⚠)"]),
+];
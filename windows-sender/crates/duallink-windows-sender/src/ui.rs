@@ -8,22 +8,49 @@
 //! ├────────────────────────────────────────────────────────┤
 //! │  Receiver IP  [192.168.1.100_______]  PIN  [123456__]  │
 //! │  Discovered   [— select —___________]                  │
+//! │  Wake MAC     [aa:bb:cc:dd:ee:ff]  [⏻ Wake]            │
 //! │  Displays [1▼]  Resolution [1920×1080___▼]  FPS [60▼]  │
+//! │  Monitor 0  [\\.\DISPLAY1 — 2560×1440 ▼]  [⟳]         │
+//! │  Preset   [Text sharp ▼]                               │
 //! │  Bitrate  [8000] kbps                                  │
 //! ├────────────────────────────────────────────────────────┤
 //! │  [▶ Start Streaming]          [■ Stop]                 │
 //! ├────────────────────────────────────────────────────────┤
-//! │  Display 0  ● Streaming  47.2 fps  12340 frames        │
+//! │  Display 0  [▣]  ● Streaming  47.2 fps  RTT 3 ms       │
+//! │  ▸ Log (12)                                            │
 //! └────────────────────────────────────────────────────────┘
 //! ```
+//!
+//! The per-stream monitor choice is saved with [`MonitorAssignments`], and
+//! receiver MACs for the Wake button with [`WakeAddresses`].
+//! Each display row has a collapsible [`PipelineLog`] that is kept after the
+//! pipeline fails or stops. Streaming rows start with a 1 fps thumbnail of
+//! what that pipeline sends; hover it for full size.
+//!
+//! Labels and hints come from [`crate::strings`], in the language picked
+//! next to the title. The theme and UI scale pickers beside it, and the
+//! window geometry, are kept in [`AppearanceSettings`] shared with the
+//! other DualLink UIs.
 
 use std::collections::HashMap;
-
+use std::time::Duration;
+
+use duallink_capture_windows::list_monitors;
+use duallink_core::locale::language;
+use duallink_core::{
+    set_language, AppearanceSettings, Language, LatencyMode, MonitorAssignments, MonitorInfo, NetworkPolicy,
+    QualityPreset, SenderQueues, Theme, WakeAddresses, WindowGeometry, UI_SCALES, test_pattern_arg,
+};
+use duallink_sender_lib::pipeline_log::{LogLevel, PipelineLog};
+use duallink_sender_lib::preview::PreviewSlot;
+use duallink_sender_lib::BitrateAllocator;
+use duallink_transport_client::{ports_from_txt, signaling_port, wake_receiver, PortMap};
 use eframe::egui::{self, Color32, RichText};
 use tokio::runtime::Handle;
 use tokio::sync::mpsc;
 
 use crate::pipeline::{PipelineConfig, PipelineState, PipelineStatus, WinSenderPipeline};
+use crate::strings::{t, tf};
 
 // ── Discovered receiver (via mDNS) ────────────────────────────────────────────
 
@@ -31,10 +58,16 @@ use crate::pipeline::{PipelineConfig, PipelineState, PipelineStatus, WinSenderPi
 pub struct DiscoveredReceiver {
     pub name:     String,
     pub host:     String,
-    pub port:     u16,
+    /// Per-display ports from the TXT record.
+    pub ports:    PortMap,
     pub displays: u8,
+    /// MAC address from the `mac` TXT key (Wake-on-LAN), if advertised.
+    pub mac:      Option<String>,
 }
 
+/// How long to wait for a woken receiver's signaling port to open.
+const WAKE_TIMEOUT: Duration = Duration::from_secs(90);
+
 // ── WinSenderApp ──────────────────────────────────────────────────────────────
 
 pub struct WinSenderApp {
@@ -46,25 +79,59 @@ pub struct WinSenderApp {
     height:         u32,
     fps:            u32,
     bitrate_kbps:   u32,
+    /// Quality preset that filled fps/bitrate (`None` = custom values).
+    preset:         Option<QualityPreset>,
+    /// Send HDR10 (HEVC Main10) from displays in HDR mode.
+    hdr:            bool,
+    /// Ask receivers for thumbnails of what they show.
+    remote_preview: bool,
+    /// Pattern streamed instead of the screen (`--test-pattern`).
+    test_pattern:   Option<&'static str>,
     resolution_idx: usize,
 
+    // ── Monitor selection ──
+    monitors:       Vec<MonitorInfo>,
+    assignments:    MonitorAssignments,
+
     // ── Discovery ──
     discovered:     Vec<DiscoveredReceiver>,
     discovery_rx:   Option<mpsc::Receiver<DiscoveredReceiver>>,
     selected_peer:  Option<usize>,
 
+    // ── Wake-on-LAN ──
+    receiver_mac:   String,
+    /// Saved across launches, see [`WakeAddresses`].
+    mac_cache:      WakeAddresses,
+    wake_rx:        Option<mpsc::Receiver<Result<Duration, String>>>,
+    wake_status:    Option<(String, Color32)>,
+
     // ── Runtime ──
     running:   bool,
+    /// Receiver displays are blanked ("Blank receiver" toggle).
+    remote_blank: bool,
     pipelines: Vec<WinSenderPipeline>,
     status_rx: mpsc::Receiver<PipelineStatus>,
     status_tx: mpsc::Sender<PipelineStatus>,
     status:    HashMap<u8, PipelineStatus>,
+    /// Event log per display — survives the pipeline until the next start.
+    logs:      HashMap<u8, PipelineLog>,
+    /// Uploaded preview thumbnail per display, with its generation.
+    previews:  HashMap<u8, (u64, egui::TextureHandle)>,
+    /// Likewise for the receivers' thumbnails.
+    remote_previews: HashMap<u8, (u64, egui::TextureHandle)>,
+
+    // ── Appearance ──
+    appearance:     AppearanceSettings,
+    /// Window geometry as of the last frame, saved on exit.
+    geometry:       Option<WindowGeometry>,
     rt_handle: Handle,
 }
 
 impl WinSenderApp {
-    pub fn new(rt_handle: Handle, _cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(rt_handle: Handle, cc: &eframe::CreationContext<'_>) -> Self {
         let (status_tx, status_rx) = mpsc::channel::<PipelineStatus>(64);
+        let appearance = AppearanceSettings::load();
+        apply_appearance(&cc.egui_ctx, &appearance);
         Self {
             host:           "192.168.1.100".to_owned(),
             pairing_pin:    "000000".to_owned(),
@@ -73,15 +140,31 @@ impl WinSenderApp {
             height:         1080,
             fps:            60,
             bitrate_kbps:   8000,
+            preset:         None,
+            hdr:            false,
+            remote_preview: false,
+            test_pattern:   test_pattern_arg(std::env::args()),
             resolution_idx: 2, // 1920×1080
+            monitors:       list_monitors(),
+            assignments:    MonitorAssignments::load(),
             discovered:     Vec::new(),
             discovery_rx:   None,
             selected_peer:  None,
+            receiver_mac:   String::new(),
+            mac_cache:      WakeAddresses::load(),
+            wake_rx:        None,
+            wake_status:    None,
             running:        false,
+            remote_blank:   false,
             pipelines:      Vec::new(),
             status_rx,
             status_tx,
             status:         HashMap::new(),
+            logs:           HashMap::new(),
+            previews:       HashMap::new(),
+            remote_previews: HashMap::new(),
+            appearance,
+            geometry:       None,
             rt_handle,
         }
     }
@@ -103,6 +186,9 @@ impl WinSenderApp {
     fn poll_discovery(&mut self) {
         if let Some(rx) = &mut self.discovery_rx {
             while let Ok(peer) = rx.try_recv() {
+                if let Some(mac) = &peer.mac {
+                    self.remember_mac(&peer.host, mac);
+                }
                 // Deduplicate by host
                 if !self.discovered.iter().any(|p| p.host == peer.host) {
                     self.discovered.push(peer);
@@ -111,32 +197,140 @@ impl WinSenderApp {
         }
     }
 
+    // ── Wake-on-LAN ───────────────────────────────────────────────────────
+
+    /// Remember `mac` for `host` across launches — the receiver may be
+    /// asleep, and not advertising it, the next time the sender starts.
+    fn remember_mac(&mut self, host: &str, mac: &str) {
+        if self.mac_cache.insert(host, mac) {
+            if let Err(e) = self.mac_cache.save() {
+                tracing::warn!("Saving receiver MAC addresses: {}", e);
+            }
+        }
+    }
+
+    fn start_wake(&mut self) {
+        let (tx, rx) = mpsc::channel::<Result<Duration, String>>(1);
+        self.wake_rx = Some(rx);
+        self.wake_status = Some((t("wake.waking").to_owned(), Color32::YELLOW));
+
+        let mac  = self.receiver_mac.clone();
+        let host = self.host.clone();
+        // A MAC typed in by hand is remembered like a discovered one.
+        self.remember_mac(&host, &mac);
+        let port = signaling_port(&self.receiver_ports(), 0);
+        let _guard = self.rt_handle.enter();
+        tokio::spawn(async move {
+            let result = wake_receiver(&mac, &host, port, WAKE_TIMEOUT)
+                .await
+                .map_err(|e| format!("{e:#}"));
+            let _ = tx.send(result).await;
+        });
+    }
+
+    /// Ports advertised by the discovered receiver at `host`, else the
+    /// default layout.
+    fn receiver_ports(&self) -> PortMap {
+        self.discovered
+            .iter()
+            .find(|p| p.host == self.host)
+            .map(|p| p.ports.clone())
+            .unwrap_or_default()
+    }
+
+    fn poll_wake(&mut self) {
+        let Some(rx) = &mut self.wake_rx else { return };
+        if let Ok(result) = rx.try_recv() {
+            self.wake_status = Some(match result {
+                Ok(took) => (tf("wake.up", &[("seconds", &format!("{:.0}", took.as_secs_f32()))]), Color32::GREEN),
+                Err(e)   => (tf("wake.failed", &[("error", &e)]), Color32::RED),
+            });
+            self.wake_rx = None;
+        }
+    }
+
     // ── Pipeline lifecycle ────────────────────────────────────────────────
 
     fn start(&mut self) {
         if self.running { return; }
         self.running = true;
         self.status.clear();
+        self.logs.clear();
+        self.previews.clear();
+        self.remote_previews.clear();
         let _guard = self.rt_handle.enter();
+        let ports = self.receiver_ports();
+        // One budget for this run's displays.
+        let budget = BitrateAllocator::from_env();
         for i in 0..self.display_count as u8 {
             let cfg = PipelineConfig {
                 host:          self.host.clone(),
                 pairing_pin:   self.pairing_pin.clone(),
                 display_index: i,
+                ports:         ports.clone(),
                 width:         self.width,
                 height:        self.height,
                 fps:           self.fps,
                 bitrate_kbps:  self.bitrate_kbps,
+                preset:        self.preset,
+                latency_mode:  LatencyMode::from_env(),
+                hdr:           self.hdr,
+                monitor:       self.assignments.get(i).map(str::to_owned),
+                test_pattern:  self.test_pattern,
+                remote_preview: self.remote_preview,
+                network_caps:  NetworkPolicy::from_env(),
+                budget:        budget.clone(),
+                queues:        SenderQueues::from_env(),
             };
             let pl = WinSenderPipeline::spawn(cfg, self.status_tx.clone());
+            self.logs.insert(i, pl.log().clone());
             self.pipelines.push(pl);
         }
     }
 
+    fn assign_monitor(&mut self, display_index: u8, monitor: Option<String>) {
+        self.assignments.set(display_index, monitor);
+        if let Err(e) = self.assignments.save() {
+            tracing::warn!("Saving monitor assignments: {}", e);
+        }
+    }
+
+    /// Fill fps/bitrate from `preset` and switch any running pipelines to it.
+    fn apply_preset(&mut self, preset: QualityPreset) {
+        let params = preset.params();
+        self.fps = params.target_fps;
+        self.bitrate_kbps = (params.max_bitrate_bps / 1000) as u32;
+        for pl in &self.pipelines { pl.apply_preset(preset); }
+    }
+
+    /// Bitrate / fps / keyframe controls for running pipelines. Changed
+    /// rates apply in place and switch the preset to custom.
+    fn rate_controls(&mut self, ui: &mut egui::Ui) {
+        let bitrate = ui.add(
+            egui::DragValue::new(&mut self.bitrate_kbps).range(500..=50_000).speed(100.0).suffix(" kbps"),
+        );
+        // Send once a drag ends, not on every step of it.
+        if bitrate.drag_stopped() || (bitrate.changed() && !bitrate.dragged()) {
+            self.preset = None;
+            for pl in &self.pipelines { pl.set_bitrate(self.bitrate_kbps); }
+        }
+        let fps = ui
+            .add(egui::DragValue::new(&mut self.fps).range(1..=60).suffix(" fps"))
+            .on_hover_text(t("action.fps_hint"));
+        if fps.drag_stopped() || (fps.changed() && !fps.dragged()) {
+            self.preset = None;
+            for pl in &self.pipelines { pl.set_fps(self.fps); }
+        }
+        if ui.button(t("action.keyframe")).on_hover_text(t("action.keyframe_hint")).clicked() {
+            for pl in &self.pipelines { pl.force_keyframe(); }
+        }
+    }
+
     fn stop(&mut self) {
         for pl in &self.pipelines { pl.stop(); }
         self.pipelines.clear();
         self.running = false;
+        self.remote_blank = false;
     }
 
     fn poll_status(&mut self) {
@@ -154,15 +348,62 @@ impl WinSenderApp {
     }
 }
 
+impl WinSenderApp {
+    /// Upload thumbnails published since the last frame.
+    fn poll_previews(&mut self, ctx: &egui::Context) {
+        // Pipelines are spawned in display order.
+        for (i, pl) in self.pipelines.iter().enumerate() {
+            let i = i as u8;
+            upload_thumbnail(ctx, &mut self.previews, i, pl.preview(), "preview");
+            upload_thumbnail(ctx, &mut self.remote_previews, i, pl.remote_preview(), "remote_preview");
+        }
+    }
+}
+
+/// Upload `slot`'s thumbnail into `textures[i]` if it is newer than the one
+/// there.
+fn upload_thumbnail(
+    ctx: &egui::Context,
+    textures: &mut HashMap<u8, (u64, egui::TextureHandle)>,
+    i: u8,
+    slot: &PreviewSlot,
+    name: &str,
+) {
+    let generation = textures.get(&i).map_or(0, |(g, _)| *g);
+    let Some(thumb) = slot.newer_than(generation) else { return };
+    let image = egui::ColorImage::from_rgba_unmultiplied([thumb.width as usize, thumb.height as usize], &thumb.rgba);
+    match textures.get_mut(&i) {
+        Some((g, texture)) => {
+            texture.set(image, egui::TextureOptions::LINEAR);
+            *g = thumb.generation;
+        }
+        None => {
+            let texture = ctx.load_texture(format!("{name}_{i}"), image, egui::TextureOptions::LINEAR);
+            textures.insert(i, (thumb.generation, texture));
+        }
+    }
+}
+
 impl eframe::App for WinSenderApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.poll_status();
         self.poll_discovery();
+        self.poll_wake();
+        self.poll_previews(ctx);
+        if let Some(geometry) = current_geometry(ctx) {
+            self.geometry = Some(geometry);
+        }
         ctx.request_repaint_after(std::time::Duration::from_millis(500));
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.spacing_mut().item_spacing = egui::vec2(8.0, 6.0);
-            ui.heading("DualLink Windows Sender");
+            ui.horizontal(|ui| {
+                ui.heading("DualLink Windows Sender");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    language_picker(ui);
+                    appearance_pickers(ui, &mut self.appearance);
+                });
+            });
             ui.separator();
 
             let locked = self.running;
@@ -172,13 +413,13 @@ impl eframe::App for WinSenderApp {
                     .spacing([8.0, 4.0])
                     .show(ui, |ui| {
                         // Row 1: IP + PIN
-                        ui.label("Receiver IP:");
+                        ui.label(t("settings.receiver_ip"));
                         ui.add(
                             egui::TextEdit::singleline(&mut self.host)
                                 .hint_text("192.168.1.100")
                                 .desired_width(160.0),
                         );
-                        ui.label("PIN:");
+                        ui.label(t("settings.pin"));
                         ui.add(
                             egui::TextEdit::singleline(&mut self.pairing_pin)
                                 .hint_text("000000")
@@ -187,11 +428,11 @@ impl eframe::App for WinSenderApp {
                         ui.end_row();
 
                         // Row 2: mDNS discovered receivers
-                        ui.label("Discovered:");
+                        ui.label(t("settings.discovered"));
                         let sel_label = self.selected_peer
                             .and_then(|i| self.discovered.get(i))
                             .map(|p| p.name.clone())
-                            .unwrap_or_else(|| "— scan for receivers —".to_owned());
+                            .unwrap_or_else(|| t("settings.scan_placeholder").to_owned());
                         egui::ComboBox::from_id_source("discovered")
                             .selected_text(sel_label)
                             .width(200.0)
@@ -201,16 +442,37 @@ impl eframe::App for WinSenderApp {
                                     if ui.selectable_label(self.selected_peer == Some(i), &label).clicked() {
                                         self.selected_peer = Some(i);
                                         self.host = peer.host.clone();
+                                        if let Some(mac) = &peer.mac {
+                                            self.receiver_mac = mac.clone();
+                                        }
                                     }
                                 }
                             });
-                        if ui.small_button("⟳ Scan").clicked() {
+                        if ui.small_button(t("settings.scan")).clicked() {
                             self.start_discovery();
                         }
                         ui.end_row();
 
+                        // Row 2b: Wake-on-LAN
+                        if self.receiver_mac.is_empty() {
+                            if let Some(mac) = self.mac_cache.get(&self.host) {
+                                self.receiver_mac = mac.to_owned();
+                            }
+                        }
+                        ui.label(t("settings.wake_mac"));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.receiver_mac)
+                                .hint_text("aa:bb:cc:dd:ee:ff")
+                                .desired_width(160.0),
+                        );
+                        let can_wake = self.wake_rx.is_none() && !self.receiver_mac.trim().is_empty();
+                        if ui.add_enabled(can_wake, egui::Button::new(t("settings.wake")).small()).clicked() {
+                            self.start_wake();
+                        }
+                        ui.end_row();
+
                         // Row 3: Display count + Resolution
-                        ui.label("Displays:");
+                        ui.label(t("settings.displays"));
                         egui::ComboBox::from_id_source("display_count")
                             .selected_text(format!("{}", self.display_count))
                             .width(50.0)
@@ -219,7 +481,7 @@ impl eframe::App for WinSenderApp {
                                     ui.selectable_value(&mut self.display_count, n, format!("{n}"));
                                 }
                             });
-                        ui.label("Resolution:");
+                        ui.label(t("settings.resolution"));
                         egui::ComboBox::from_id_source("resolution")
                             .selected_text(format!("{}×{}", self.width, self.height))
                             .width(130.0)
@@ -240,8 +502,53 @@ impl eframe::App for WinSenderApp {
                             });
                         ui.end_row();
 
-                        // Row 4: FPS + Bitrate
-                        ui.label("FPS:");
+                        // Row 3b: per-stream monitor picker
+                        for i in 0..self.display_count as u8 {
+                            ui.label(tf("settings.monitor", &[("n", &i)]));
+                            let current = self.assignments.get(i).map(str::to_owned);
+                            let mut selected = current.clone();
+                            egui::ComboBox::from_id_source(("monitor", i))
+                                .selected_text(current.as_deref().unwrap_or(t("settings.auto")))
+                                .width(190.0)
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut selected, None, t("settings.auto"))
+                                        .on_hover_text(tf("settings.windows_monitor", &[("n", &i)]));
+                                    for m in &self.monitors {
+                                        let label = format!(
+                                            "{} — {} at {},{}{}",
+                                            m.name, m.resolution, m.x, m.y,
+                                            if m.primary { t("settings.primary") } else { "" }
+                                        );
+                                        ui.selectable_value(&mut selected, Some(m.name.clone()), label);
+                                    }
+                                });
+                            if selected != current {
+                                self.assign_monitor(i, selected);
+                            }
+                            if i == 0 && ui.small_button("⟳").on_hover_text(t("settings.redetect")).clicked() {
+                                self.monitors = list_monitors();
+                            }
+                            ui.end_row();
+                        }
+
+                        // Row 4: Quality preset
+                        ui.label(t("settings.preset"));
+                        let prev_preset = self.preset;
+                        egui::ComboBox::from_id_source("preset")
+                            .selected_text(self.preset.map_or(t("settings.custom"), |p| p.label()))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.preset, None, t("settings.custom"));
+                                for p in QualityPreset::ALL {
+                                    ui.selectable_value(&mut self.preset, Some(p), p.label());
+                                }
+                            });
+                        if self.preset != prev_preset {
+                            if let Some(p) = self.preset { self.apply_preset(p); }
+                        }
+                        ui.end_row();
+
+                        // Row 5: FPS + Bitrate
+                        ui.label(t("settings.fps"));
                         egui::ComboBox::from_id_source("fps")
                             .selected_text(format!("{}", self.fps))
                             .width(55.0)
@@ -250,7 +557,7 @@ impl eframe::App for WinSenderApp {
                                     ui.selectable_value(&mut self.fps, *f, format!("{f}"));
                                 }
                             });
-                        ui.label("Bitrate:");
+                        ui.label(t("settings.bitrate"));
                         ui.horizontal(|ui| {
                             ui.add(
                                 egui::DragValue::new(&mut self.bitrate_kbps)
@@ -260,53 +567,121 @@ impl eframe::App for WinSenderApp {
                             ui.label("kbps");
                         });
                         ui.end_row();
+
+                        // Row 6: HDR passthrough
+                        ui.label(t("settings.hdr"));
+                        ui.checkbox(&mut self.hdr, t("settings.hdr_check"))
+                            .on_hover_text(t("settings.hdr_hint"));
+                        ui.end_row();
+
+                        // Row 7: thumbnails back from the receiver
+                        ui.label(t("settings.remote_preview"));
+                        ui.checkbox(&mut self.remote_preview, t("settings.remote_preview_check"))
+                            .on_hover_text(t("settings.remote_preview_hint"));
+                        ui.end_row();
                     });
             });
 
+            if let Some((msg, color)) = &self.wake_status {
+                ui.label(RichText::new(msg).color(*color));
+            }
+
             ui.separator();
 
             // ── Buttons ───────────────────────────────────────────────────
             ui.horizontal(|ui| {
                 if !self.running {
                     if ui.add_sized([160.0, 32.0], egui::Button::new(
-                        if self.display_count == 1 { "▶  Start Streaming" } else { "▶  Start All Displays" }
+                        if self.display_count == 1 { t("action.start") } else { t("action.start_all") }
                     )).clicked() {
                         self.start();
                     }
                 } else {
-                    if ui.add_sized([120.0, 32.0], egui::Button::new("■  Stop")).clicked() {
+                    if ui.add_sized([120.0, 32.0], egui::Button::new(t("action.stop"))).clicked() {
                         self.stop();
                     }
+                    if ui.checkbox(&mut self.remote_blank, t("action.blank"))
+                        .on_hover_text(t("action.blank_hint"))
+                        .changed()
+                    {
+                        for pl in &self.pipelines { pl.set_remote_blank(self.remote_blank); }
+                    }
+                    ui.separator();
+                    self.rate_controls(ui);
                 }
             });
 
             ui.separator();
-            ui.label(RichText::new("Display Status").strong());
+            ui.label(RichText::new(t("status.title")).strong());
 
             if !self.running && self.status.is_empty() {
-                ui.label(RichText::new("Configure above and click Start Streaming.").color(Color32::GRAY));
+                ui.label(RichText::new(t("status.not_connected")).color(Color32::GRAY));
             }
 
             for i in 0..self.display_count as u8 {
                 ui.horizontal(|ui| {
                     match self.status.get(&i) {
                         None => {
-                            ui.label(format!("Display {i}"));
-                            ui.label(RichText::new("⊘ Idle").color(Color32::GRAY));
+                            ui.label(tf("status.display", &[("n", &i)]));
+                            ui.label(RichText::new(t("status.idle")).color(Color32::GRAY));
                         }
                         Some(s) => {
-                            ui.label(format!("Display {i}"));
+                            ui.label(tf("status.display", &[("n", &i)]));
                             match &s.state {
-                                PipelineState::Connecting => {
-                                    ui.label(RichText::new("⟳ Connecting…").color(Color32::YELLOW));
+                                // Windows capture never prompts.
+                                PipelineState::Connecting | PipelineState::AwaitingCapture => {
+                                    ui.label(RichText::new(t("status.connecting")).color(Color32::YELLOW));
                                 }
                                 PipelineState::Streaming => {
-                                    ui.label(RichText::new("● Streaming").color(Color32::GREEN));
+                                    if let Some((_, texture)) = self.previews.get(&i) {
+                                        ui.add(egui::Image::new(texture).max_width(96.0)).on_hover_ui(|ui| {
+                                            ui.image(texture);
+                                        });
+                                    }
+                                    if let Some((_, texture)) = self.remote_previews.get(&i) {
+                                        ui.label("→");
+                                        ui.add(egui::Image::new(texture).max_width(96.0)).on_hover_ui(|ui| {
+                                            ui.label(t("status.remote_preview"));
+                                            ui.image(texture);
+                                        });
+                                    }
+                                    if s.display_paused {
+                                        ui.label(RichText::new(t("status.paused")).color(Color32::YELLOW))
+                                            .on_hover_text(t("status.paused_hint"));
+                                    } else {
+                                        ui.label(RichText::new(t("status.streaming")).color(Color32::GREEN));
+                                    }
+                                    let (label, hover) = if s.display_paused {
+                                        (t("status.resume"), t("status.resume_hint"))
+                                    } else {
+                                        (t("status.pause"), t("status.pause_hint"))
+                                    };
+                                    if ui.small_button(label).on_hover_text(hover).clicked() {
+                                        // Pipelines are started in display order.
+                                        if let Some(pl) = self.pipelines.get(i as usize) { pl.set_paused(!s.display_paused); }
+                                    }
                                     ui.label(format!("{:.1} fps", s.fps));
-                                    ui.label(RichText::new(format!("{} frames", s.frames_sent)).color(Color32::GRAY));
+                                    ui.label(RichText::new(tf("status.frames", &[("count", &s.frames_sent)])).color(Color32::GRAY));
+                                    if let Some(link) = &s.link {
+                                        let color = if link.is_degraded() { Color32::YELLOW } else { Color32::GRAY };
+                                        ui.label(RichText::new(link.to_string()).color(color)).on_hover_text(t("status.link_hint"));
+                                    }
+                                    let on_battery = [s.power, s.receiver_power].iter().flatten().any(|p| p.on_battery);
+                                    if s.battery_saver || on_battery {
+                                        let color = if s.battery_saver { Color32::YELLOW } else { Color32::GRAY };
+                                        let label = if s.battery_saver { t("status.saver") } else { "🔋" };
+                                        let this = s.power.map_or_else(|| t("status.no_battery").to_owned(), |p| p.to_string());
+                                        let receiver = s
+                                            .receiver_power
+                                            .map_or_else(|| t("status.no_battery").to_owned(), |p| p.to_string());
+                                        ui.label(RichText::new(label).color(color)).on_hover_text(tf(
+                                            "status.power_hint",
+                                            &[("this", &this), ("receiver", &receiver)],
+                                        ));
+                                    }
                                 }
                                 PipelineState::Stopped => {
-                                    ui.label(RichText::new("○ Stopped").color(Color32::GRAY));
+                                    ui.label(RichText::new(t("status.stopped")).color(Color32::GRAY));
                                 }
                                 PipelineState::Failed(msg) => {
                                     ui.label(RichText::new(format!("✗ {msg}")).color(Color32::RED));
@@ -315,6 +690,9 @@ impl eframe::App for WinSenderApp {
                         }
                     }
                 });
+                if let Some(log) = self.logs.get(&i) {
+                    render_pipeline_log(ui, i, log);
+                }
             }
 
             ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
@@ -322,6 +700,152 @@ impl eframe::App for WinSenderApp {
             });
         });
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Some(geometry) = self.geometry {
+            if let Err(e) = AppearanceSettings::save_window(WINDOW_KEY, geometry) {
+                tracing::warn!("Saving window geometry: {}", e);
+            }
+        }
+    }
+}
+
+// ── Language ──────────────────────────────────────────────────────────────────
+
+/// UI language picker; the choice applies at once and is saved for the
+/// next launch.
+fn language_picker(ui: &mut egui::Ui) {
+    let current = language();
+    let mut choice = current;
+    egui::ComboBox::from_id_source("language")
+        .selected_text(current.native_name())
+        .show_ui(ui, |ui| {
+            for lang in Language::ALL {
+                ui.selectable_value(&mut choice, lang, lang.native_name());
+            }
+        })
+        .response
+        .on_hover_text(t("app.language"));
+    if choice != current {
+        set_language(choice);
+        if let Err(e) = choice.save() {
+            tracing::warn!("Saving language: {}", e);
+        }
+    }
+}
+
+// ── Appearance ────────────────────────────────────────────────────────────────
+
+/// Key of this UI's window in [`AppearanceSettings::windows`].
+const WINDOW_KEY: &str = "windows-sender";
+
+/// `viewport` at the size and place the window last closed at.
+pub fn restore_window(viewport: egui::ViewportBuilder) -> egui::ViewportBuilder {
+    let Some(geometry) = AppearanceSettings::load().window(WINDOW_KEY) else { return viewport };
+    let viewport = viewport.with_inner_size(geometry.size);
+    match geometry.position {
+        Some(position) => viewport.with_position(position),
+        None => viewport,
+    }
+}
+
+/// Apply the saved theme and scale to `ctx`.
+fn apply_appearance(ctx: &egui::Context, settings: &AppearanceSettings) {
+    ctx.set_theme(match settings.theme {
+        Theme::Dark => egui::ThemePreference::Dark,
+        Theme::Light => egui::ThemePreference::Light,
+        Theme::System => egui::ThemePreference::System,
+    });
+    ctx.set_zoom_factor(settings.scale());
+}
+
+/// The window's current geometry in logical pixels, for saving on exit.
+fn current_geometry(ctx: &egui::Context) -> Option<WindowGeometry> {
+    // Viewport rects are in points, which the zoom factor scales.
+    let zoom = ctx.zoom_factor();
+    ctx.input(|i| {
+        let viewport = i.viewport();
+        let inner = viewport.inner_rect?;
+        Some(WindowGeometry {
+            position: viewport.outer_rect.map(|r| [r.min.x * zoom, r.min.y * zoom]),
+            size:     [inner.width() * zoom, inner.height() * zoom],
+        })
+    })
+}
+
+/// Theme and UI scale pickers; changes apply at once and are saved for
+/// every DualLink UI.
+fn appearance_pickers(ui: &mut egui::Ui, settings: &mut AppearanceSettings) {
+    let before = (settings.theme, settings.ui_scale);
+    egui::ComboBox::from_id_source("theme")
+        .selected_text(theme_label(settings.theme))
+        .show_ui(ui, |ui| {
+            for theme in Theme::ALL {
+                ui.selectable_value(&mut settings.theme, theme, theme_label(theme));
+            }
+        })
+        .response
+        .on_hover_text(t("appearance.theme"));
+    egui::ComboBox::from_id_source("ui_scale")
+        .selected_text(format!("{:.0}%", settings.scale() * 100.0))
+        .show_ui(ui, |ui| {
+            for scale in UI_SCALES {
+                ui.selectable_value(&mut settings.ui_scale, scale, format!("{:.0}%", scale * 100.0));
+            }
+        })
+        .response
+        .on_hover_text(t("appearance.scale"));
+    if (settings.theme, settings.ui_scale) == before {
+        return;
+    }
+    apply_appearance(ui.ctx(), settings);
+    // Geometry is written by each UI on exit; keep the others'.
+    let mut saved = AppearanceSettings::load();
+    saved.theme = settings.theme;
+    saved.ui_scale = settings.ui_scale;
+    if let Err(e) = saved.save() {
+        tracing::warn!("Saving appearance: {}", e);
+    }
+}
+
+fn theme_label(theme: Theme) -> &'static str {
+    t(match theme {
+        Theme::Dark => "appearance.dark",
+        Theme::Light => "appearance.light",
+        Theme::System => "appearance.system",
+    })
+}
+
+// ── Per-display log panel ─────────────────────────────────────────────────────
+
+/// Collapsible log of one pipeline's events, newest at the bottom.
+fn render_pipeline_log(ui: &mut egui::Ui, display_index: u8, log: &PipelineLog) {
+    let entries = log.entries();
+    let problems = entries.iter().filter(|e| e.level != LogLevel::Info).count();
+    let title = if problems > 0 {
+        tf("log.title_problems", &[("count", &entries.len()), ("problems", &problems)])
+    } else {
+        tf("log.title", &[("count", &entries.len())])
+    };
+    egui::CollapsingHeader::new(RichText::new(title).small())
+        .id_salt(("pipeline_log", display_index))
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .id_salt(("pipeline_log_scroll", display_index))
+                .max_height(140.0)
+                .auto_shrink([false, true])
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for entry in &entries {
+                        let color = match entry.level {
+                            LogLevel::Error => Color32::from_rgb(220, 80, 70),
+                            LogLevel::Warn  => Color32::from_rgb(220, 165, 50),
+                            LogLevel::Info  => Color32::from_rgb(160, 170, 185),
+                        };
+                        ui.label(RichText::new(entry.to_string()).monospace().small().color(color));
+                    }
+                });
+        });
 }
 
 // ── mDNS browser task ─────────────────────────────────────────────────────────
@@ -365,20 +889,27 @@ async fn browse_receivers(tx: mpsc::Sender<DiscoveredReceiver>) {
                     });
                 let port = info.get_properties()
                     .get("port")
-                    .and_then(|v| v.val_str().parse().ok())
-                    .unwrap_or(7879u16);
+                    .and_then(|v| v.val_str().parse().ok());
                 let displays = info.get_properties()
                     .get("displays")
                     .and_then(|v| v.val_str().parse().ok())
                     .unwrap_or(1u8);
+                let ports = ports_from_txt(
+                    info.get_properties().get("ports").map(|v| v.val_str()),
+                    port,
+                    displays,
+                );
+                let mac = info.get_properties()
+                    .get("mac")
+                    .map(|v| v.val_str().to_owned());
                 let display_name = info.get_fullname()
                     .split('.')
                     .next()
                     .unwrap_or(&name)
                     .to_owned();
 
-                tracing::info!("[mDNS] Found receiver: {} @ {}:{}", display_name, host, port);
-                let _ = tx.send(DiscoveredReceiver { name: display_name, host, port, displays }).await;
+                tracing::info!("[mDNS] Found receiver: {} @ {} (ports {})", display_name, host, ports.to_txt());
+                let _ = tx.send(DiscoveredReceiver { name: display_name, host, ports, displays, mac }).await;
             }
             Ok(Ok(_)) | Ok(Err(_)) => {}
             Err(_) => break, // timeout